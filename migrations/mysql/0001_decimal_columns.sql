@@ -0,0 +1,18 @@
+-- 将MySQL中以字符串保存的金额字段转换为 DECIMAL(30, 10)
+-- MySQL存储后端不自动执行迁移，已有数据库需要手动执行本脚本。
+-- 严格模式下无法转换的值会使 ALTER 失败，而不是被静默截断为0，执行前请先备份。
+
+ALTER TABLE arbitrage_history
+    MODIFY buy_price DECIMAL(30, 10) NOT NULL,
+    MODIFY sell_price DECIMAL(30, 10) NOT NULL,
+    MODIFY trade_amount DECIMAL(30, 10) NOT NULL,
+    MODIFY profit DECIMAL(30, 10) NOT NULL,
+    MODIFY profit_percentage DECIMAL(30, 10) NOT NULL;
+
+ALTER TABLE daily_stats
+    MODIFY total_profit DECIMAL(30, 10) NOT NULL DEFAULT 0,
+    MODIFY total_volume DECIMAL(30, 10) NOT NULL DEFAULT 0;
+
+ALTER TABLE asset_stats
+    MODIFY total_profit DECIMAL(30, 10) NOT NULL DEFAULT 0,
+    MODIFY total_volume DECIMAL(30, 10) NOT NULL DEFAULT 0;
@@ -0,0 +1,21 @@
+-- 套利中每个订单的明细，记录实际成交数量、成交均价、状态和时间，用于事后分析滑点
+-- MySQL存储后端不自动执行迁移，需要手动执行本脚本。
+-- arbitrage_history 由用户手动创建，ID列类型不确定，因此不添加外键约束，只按 arbitrage_id 建立索引。
+
+CREATE TABLE IF NOT EXISTS orders (
+    id BIGINT AUTO_INCREMENT PRIMARY KEY,
+    arbitrage_id BIGINT NOT NULL,
+    order_id BIGINT NOT NULL,
+    symbol VARCHAR(20) NOT NULL,
+    side VARCHAR(4) NOT NULL,
+    price DECIMAL(30, 10) NOT NULL,
+    requested_qty DECIMAL(30, 10) NOT NULL,
+    executed_qty DECIMAL(30, 10) NOT NULL,
+    avg_price DECIMAL(30, 10) NOT NULL,
+    status VARCHAR(20) NOT NULL,
+    commission DECIMAL(30, 10) NOT NULL DEFAULT 0,
+    commission_asset VARCHAR(20) NOT NULL DEFAULT '',
+    created_at DATETIME(6) NOT NULL,
+    filled_at DATETIME(6) NULL,
+    UNIQUE KEY idx_orders_arbitrage_order (arbitrage_id, order_id)
+);
@@ -0,0 +1,13 @@
+-- 按日期和小时汇总的交易统计，日期和小时按配置的统计时区计算，与 daily_stats 的日期一致
+-- MySQL存储后端不自动执行迁移，需要手动执行本脚本。
+
+CREATE TABLE IF NOT EXISTS hourly_stats (
+    date DATE NOT NULL,
+    hour TINYINT UNSIGNED NOT NULL,
+    trades BIGINT NOT NULL DEFAULT 0,
+    successful_trades BIGINT NOT NULL DEFAULT 0,
+    failed_trades BIGINT NOT NULL DEFAULT 0,
+    total_profit DECIMAL(30, 10) NOT NULL DEFAULT 0,
+    total_volume DECIMAL(30, 10) NOT NULL DEFAULT 0,
+    PRIMARY KEY (date, hour)
+);
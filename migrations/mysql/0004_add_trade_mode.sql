@@ -0,0 +1,7 @@
+-- 交易记录的运行模式（live/simulate/dry_run/backtest）和生效配置的指纹，已有记录视为实盘交易
+-- MySQL存储后端不自动执行迁移，需要手动执行本脚本。
+
+ALTER TABLE arbitrage_history
+    ADD COLUMN mode VARCHAR(16) NOT NULL DEFAULT 'live',
+    ADD COLUMN config_hash VARCHAR(64) NOT NULL DEFAULT '',
+    ADD INDEX idx_arbitrage_history_mode (mode);
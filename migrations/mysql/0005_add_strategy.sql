@@ -0,0 +1,6 @@
+-- 发现套利机会的策略名称，旧记录为NULL，统计时归入未归属
+-- MySQL存储后端不自动执行迁移，需要手动执行本脚本。
+
+ALTER TABLE arbitrage_history
+    ADD COLUMN strategy VARCHAR(64) NULL,
+    ADD INDEX idx_arbitrage_history_strategy (strategy);
@@ -0,0 +1,20 @@
+-- 被风控或余额预留拒绝的套利机会，用于统计拒绝原因和错过的预期利润
+-- 同一机会被多个组件拒绝时只记录一行，controller 和 reason 为各组件及原因的组合
+-- MySQL存储后端不自动执行迁移，需要手动执行本脚本。
+
+CREATE TABLE IF NOT EXISTS rejected_opportunities (
+    id BIGINT AUTO_INCREMENT PRIMARY KEY,
+    base_asset VARCHAR(20) NOT NULL,
+    buy_quote VARCHAR(10) NOT NULL,
+    sell_quote VARCHAR(10) NOT NULL,
+    buy_price DECIMAL(30, 10) NOT NULL,
+    sell_price DECIMAL(30, 10) NOT NULL,
+    trade_amount DECIMAL(30, 10) NOT NULL,
+    profit_percentage DECIMAL(30, 10) NOT NULL,
+    controller VARCHAR(255) NOT NULL,
+    reason TEXT NOT NULL,
+    strategy VARCHAR(64) NULL,
+    mode VARCHAR(16) NOT NULL DEFAULT 'live',
+    rejected_at DATETIME(6) NOT NULL,
+    INDEX idx_rejected_opportunities_rejected_at (rejected_at)
+);
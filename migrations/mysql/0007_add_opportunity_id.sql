@@ -0,0 +1,6 @@
+-- 套利机会ID，关联机会、订单（newClientOrderId前缀）和日志，旧记录为NULL
+-- MySQL存储后端不自动执行迁移，需要手动执行本脚本。
+
+ALTER TABLE arbitrage_history
+    ADD COLUMN opportunity_id CHAR(36) NULL,
+    ADD INDEX idx_arbitrage_history_opportunity_id (opportunity_id);
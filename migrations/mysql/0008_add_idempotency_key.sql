@@ -0,0 +1,6 @@
+-- 幂等键，由订单ID和开始时间生成，重复写入同一笔套利结果时不产生新记录
+-- MySQL存储后端不自动执行迁移，需要手动执行本脚本。
+
+ALTER TABLE arbitrage_history
+    ADD COLUMN idempotency_key VARCHAR(128) NULL,
+    ADD UNIQUE INDEX idx_arbitrage_history_idempotency_key (idempotency_key);
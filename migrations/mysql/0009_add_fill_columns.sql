@@ -0,0 +1,12 @@
+-- 买卖两边的实际成交信息（成交数量、成交均价、手续费及手续费币种），旧记录为0和空字符串
+-- MySQL存储后端不自动执行迁移，需要手动执行本脚本。
+
+ALTER TABLE arbitrage_history
+    ADD COLUMN buy_fill_qty DECIMAL(30, 10) NOT NULL DEFAULT 0,
+    ADD COLUMN buy_fill_price DECIMAL(30, 10) NOT NULL DEFAULT 0,
+    ADD COLUMN buy_commission DECIMAL(30, 10) NOT NULL DEFAULT 0,
+    ADD COLUMN buy_commission_asset VARCHAR(20) NOT NULL DEFAULT '',
+    ADD COLUMN sell_fill_qty DECIMAL(30, 10) NOT NULL DEFAULT 0,
+    ADD COLUMN sell_fill_price DECIMAL(30, 10) NOT NULL DEFAULT 0,
+    ADD COLUMN sell_commission DECIMAL(30, 10) NOT NULL DEFAULT 0,
+    ADD COLUMN sell_commission_asset VARCHAR(20) NOT NULL DEFAULT '';
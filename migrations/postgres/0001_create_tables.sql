@@ -0,0 +1,40 @@
+-- PostgreSQL存储后端的初始表结构
+
+CREATE TABLE IF NOT EXISTS arbitrage_history (
+    id BIGSERIAL PRIMARY KEY,
+    base_asset VARCHAR(20) NOT NULL,
+    buy_quote VARCHAR(10) NOT NULL,
+    sell_quote VARCHAR(10) NOT NULL,
+    buy_price NUMERIC(30, 10) NOT NULL,
+    sell_price NUMERIC(30, 10) NOT NULL,
+    trade_amount NUMERIC(30, 10) NOT NULL,
+    profit NUMERIC(30, 10) NOT NULL,
+    profit_percentage NUMERIC(30, 10) NOT NULL,
+    buy_order_id BIGINT,
+    sell_order_id BIGINT,
+    status VARCHAR(20) NOT NULL,
+    start_time TIMESTAMPTZ NOT NULL,
+    end_time TIMESTAMPTZ NOT NULL,
+    duration_ms BIGINT NOT NULL DEFAULT 0
+);
+
+CREATE INDEX IF NOT EXISTS idx_arbitrage_history_start_time ON arbitrage_history (start_time);
+CREATE INDEX IF NOT EXISTS idx_arbitrage_history_base_asset ON arbitrage_history (base_asset);
+
+CREATE TABLE IF NOT EXISTS daily_stats (
+    date DATE PRIMARY KEY,
+    trades BIGINT NOT NULL DEFAULT 0,
+    successful_trades BIGINT NOT NULL DEFAULT 0,
+    failed_trades BIGINT NOT NULL DEFAULT 0,
+    total_profit NUMERIC(30, 10) NOT NULL DEFAULT 0,
+    total_volume NUMERIC(30, 10) NOT NULL DEFAULT 0
+);
+
+CREATE TABLE IF NOT EXISTS asset_stats (
+    asset VARCHAR(20) PRIMARY KEY,
+    trades BIGINT NOT NULL DEFAULT 0,
+    successful_trades BIGINT NOT NULL DEFAULT 0,
+    failed_trades BIGINT NOT NULL DEFAULT 0,
+    total_profit NUMERIC(30, 10) NOT NULL DEFAULT 0,
+    total_volume NUMERIC(30, 10) NOT NULL DEFAULT 0
+);
@@ -0,0 +1,4 @@
+-- 幂等键，由订单ID和开始时间生成，重复写入同一笔套利结果时不产生新记录
+ALTER TABLE arbitrage_history ADD COLUMN IF NOT EXISTS idempotency_key VARCHAR(128);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_arbitrage_history_idempotency_key ON arbitrage_history (idempotency_key);
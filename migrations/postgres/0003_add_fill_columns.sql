@@ -0,0 +1,10 @@
+-- 买卖两边的实际成交信息（成交数量、成交均价、手续费及手续费币种）
+ALTER TABLE arbitrage_history
+    ADD COLUMN IF NOT EXISTS buy_fill_qty NUMERIC(30, 10) NOT NULL DEFAULT 0,
+    ADD COLUMN IF NOT EXISTS buy_fill_price NUMERIC(30, 10) NOT NULL DEFAULT 0,
+    ADD COLUMN IF NOT EXISTS buy_commission NUMERIC(30, 10) NOT NULL DEFAULT 0,
+    ADD COLUMN IF NOT EXISTS buy_commission_asset VARCHAR(20) NOT NULL DEFAULT '',
+    ADD COLUMN IF NOT EXISTS sell_fill_qty NUMERIC(30, 10) NOT NULL DEFAULT 0,
+    ADD COLUMN IF NOT EXISTS sell_fill_price NUMERIC(30, 10) NOT NULL DEFAULT 0,
+    ADD COLUMN IF NOT EXISTS sell_commission NUMERIC(30, 10) NOT NULL DEFAULT 0,
+    ADD COLUMN IF NOT EXISTS sell_commission_asset VARCHAR(20) NOT NULL DEFAULT '';
@@ -0,0 +1,19 @@
+-- 套利中每个订单的明细，记录实际成交数量、成交均价、状态和时间，用于事后分析滑点
+CREATE TABLE IF NOT EXISTS orders (
+    id BIGSERIAL PRIMARY KEY,
+    arbitrage_id BIGINT NOT NULL REFERENCES arbitrage_history (id) ON DELETE CASCADE,
+    order_id BIGINT NOT NULL,
+    symbol VARCHAR(20) NOT NULL,
+    side VARCHAR(4) NOT NULL,
+    price NUMERIC(30, 10) NOT NULL,
+    requested_qty NUMERIC(30, 10) NOT NULL,
+    executed_qty NUMERIC(30, 10) NOT NULL,
+    avg_price NUMERIC(30, 10) NOT NULL,
+    status VARCHAR(20) NOT NULL,
+    commission NUMERIC(30, 10) NOT NULL DEFAULT 0,
+    commission_asset VARCHAR(20) NOT NULL DEFAULT '',
+    created_at TIMESTAMPTZ NOT NULL,
+    filled_at TIMESTAMPTZ
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_orders_arbitrage_order ON orders (arbitrage_id, order_id);
@@ -0,0 +1,12 @@
+-- 按日期和小时汇总的交易统计，日期和小时按配置的统计时区计算，与 daily_stats 的日期一致
+
+CREATE TABLE IF NOT EXISTS hourly_stats (
+    date DATE NOT NULL,
+    hour SMALLINT NOT NULL,
+    trades BIGINT NOT NULL DEFAULT 0,
+    successful_trades BIGINT NOT NULL DEFAULT 0,
+    failed_trades BIGINT NOT NULL DEFAULT 0,
+    total_profit NUMERIC(30, 10) NOT NULL DEFAULT 0,
+    total_volume NUMERIC(30, 10) NOT NULL DEFAULT 0,
+    PRIMARY KEY (date, hour)
+);
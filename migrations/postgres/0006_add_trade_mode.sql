@@ -0,0 +1,6 @@
+-- 交易记录的运行模式（live/simulate/dry_run/backtest）和生效配置的指纹，已有记录视为实盘交易
+ALTER TABLE arbitrage_history
+    ADD COLUMN IF NOT EXISTS mode VARCHAR(16) NOT NULL DEFAULT 'live',
+    ADD COLUMN IF NOT EXISTS config_hash VARCHAR(64) NOT NULL DEFAULT '';
+
+CREATE INDEX IF NOT EXISTS idx_arbitrage_history_mode ON arbitrage_history (mode);
@@ -0,0 +1,19 @@
+-- 被风控或余额预留拒绝的套利机会，用于统计拒绝原因和错过的预期利润
+-- 同一机会被多个组件拒绝时只记录一行，controller 和 reason 为各组件及原因的组合
+CREATE TABLE IF NOT EXISTS rejected_opportunities (
+    id BIGSERIAL PRIMARY KEY,
+    base_asset VARCHAR(20) NOT NULL,
+    buy_quote VARCHAR(10) NOT NULL,
+    sell_quote VARCHAR(10) NOT NULL,
+    buy_price NUMERIC(30, 10) NOT NULL,
+    sell_price NUMERIC(30, 10) NOT NULL,
+    trade_amount NUMERIC(30, 10) NOT NULL,
+    profit_percentage NUMERIC(30, 10) NOT NULL,
+    controller VARCHAR(255) NOT NULL,
+    reason TEXT NOT NULL,
+    strategy VARCHAR(64),
+    mode VARCHAR(16) NOT NULL DEFAULT 'live',
+    rejected_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_rejected_opportunities_rejected_at ON rejected_opportunities (rejected_at);
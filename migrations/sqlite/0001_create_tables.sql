@@ -0,0 +1,41 @@
+-- SQLite存储后端的初始表结构
+-- 金额字段以TEXT保存Decimal的字符串形式，聚合在程序中以Decimal精确计算
+
+CREATE TABLE IF NOT EXISTS arbitrage_history (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    base_asset TEXT NOT NULL,
+    buy_quote TEXT NOT NULL,
+    sell_quote TEXT NOT NULL,
+    buy_price TEXT NOT NULL,
+    sell_price TEXT NOT NULL,
+    trade_amount TEXT NOT NULL,
+    profit TEXT NOT NULL,
+    profit_percentage TEXT NOT NULL,
+    buy_order_id INTEGER,
+    sell_order_id INTEGER,
+    status TEXT NOT NULL,
+    start_time TEXT NOT NULL,
+    end_time TEXT NOT NULL,
+    duration_ms INTEGER NOT NULL DEFAULT 0
+);
+
+CREATE INDEX IF NOT EXISTS idx_arbitrage_history_start_time ON arbitrage_history (start_time);
+CREATE INDEX IF NOT EXISTS idx_arbitrage_history_base_asset ON arbitrage_history (base_asset);
+
+CREATE TABLE IF NOT EXISTS daily_stats (
+    date TEXT PRIMARY KEY,
+    trades INTEGER NOT NULL DEFAULT 0,
+    successful_trades INTEGER NOT NULL DEFAULT 0,
+    failed_trades INTEGER NOT NULL DEFAULT 0,
+    total_profit TEXT NOT NULL DEFAULT '0',
+    total_volume TEXT NOT NULL DEFAULT '0'
+);
+
+CREATE TABLE IF NOT EXISTS asset_stats (
+    asset TEXT PRIMARY KEY,
+    trades INTEGER NOT NULL DEFAULT 0,
+    successful_trades INTEGER NOT NULL DEFAULT 0,
+    failed_trades INTEGER NOT NULL DEFAULT 0,
+    total_profit TEXT NOT NULL DEFAULT '0',
+    total_volume TEXT NOT NULL DEFAULT '0'
+);
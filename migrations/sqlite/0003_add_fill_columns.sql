@@ -0,0 +1,9 @@
+-- 买卖两边的实际成交信息（成交数量、成交均价、手续费及手续费币种）
+ALTER TABLE arbitrage_history ADD COLUMN buy_fill_qty TEXT NOT NULL DEFAULT '0';
+ALTER TABLE arbitrage_history ADD COLUMN buy_fill_price TEXT NOT NULL DEFAULT '0';
+ALTER TABLE arbitrage_history ADD COLUMN buy_commission TEXT NOT NULL DEFAULT '0';
+ALTER TABLE arbitrage_history ADD COLUMN buy_commission_asset TEXT NOT NULL DEFAULT '';
+ALTER TABLE arbitrage_history ADD COLUMN sell_fill_qty TEXT NOT NULL DEFAULT '0';
+ALTER TABLE arbitrage_history ADD COLUMN sell_fill_price TEXT NOT NULL DEFAULT '0';
+ALTER TABLE arbitrage_history ADD COLUMN sell_commission TEXT NOT NULL DEFAULT '0';
+ALTER TABLE arbitrage_history ADD COLUMN sell_commission_asset TEXT NOT NULL DEFAULT '';
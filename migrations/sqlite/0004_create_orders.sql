@@ -0,0 +1,19 @@
+-- 套利中每个订单的明细，记录实际成交数量、成交均价、状态和时间，用于事后分析滑点
+CREATE TABLE IF NOT EXISTS orders (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    arbitrage_id INTEGER NOT NULL REFERENCES arbitrage_history (id) ON DELETE CASCADE,
+    order_id INTEGER NOT NULL,
+    symbol TEXT NOT NULL,
+    side TEXT NOT NULL,
+    price TEXT NOT NULL,
+    requested_qty TEXT NOT NULL,
+    executed_qty TEXT NOT NULL,
+    avg_price TEXT NOT NULL,
+    status TEXT NOT NULL,
+    commission TEXT NOT NULL DEFAULT '0',
+    commission_asset TEXT NOT NULL DEFAULT '',
+    created_at TEXT NOT NULL,
+    filled_at TEXT
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_orders_arbitrage_order ON orders (arbitrage_id, order_id);
@@ -0,0 +1,12 @@
+-- 按日期和小时汇总的交易统计，日期和小时按配置的统计时区计算，与 daily_stats 的日期一致
+
+CREATE TABLE IF NOT EXISTS hourly_stats (
+    date TEXT NOT NULL,
+    hour INTEGER NOT NULL,
+    trades INTEGER NOT NULL DEFAULT 0,
+    successful_trades INTEGER NOT NULL DEFAULT 0,
+    failed_trades INTEGER NOT NULL DEFAULT 0,
+    total_profit TEXT NOT NULL DEFAULT '0',
+    total_volume TEXT NOT NULL DEFAULT '0',
+    PRIMARY KEY (date, hour)
+);
@@ -0,0 +1,5 @@
+-- 交易记录的运行模式（live/simulate/dry_run/backtest）和生效配置的指纹，已有记录视为实盘交易
+ALTER TABLE arbitrage_history ADD COLUMN mode TEXT NOT NULL DEFAULT 'live';
+ALTER TABLE arbitrage_history ADD COLUMN config_hash TEXT NOT NULL DEFAULT '';
+
+CREATE INDEX IF NOT EXISTS idx_arbitrage_history_mode ON arbitrage_history (mode);
@@ -0,0 +1,4 @@
+-- 发现套利机会的策略名称，旧记录为NULL，统计时归入未归属
+ALTER TABLE arbitrage_history ADD COLUMN strategy TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_arbitrage_history_strategy ON arbitrage_history (strategy);
@@ -0,0 +1,19 @@
+-- 被风控或余额预留拒绝的套利机会，用于统计拒绝原因和错过的预期利润
+-- 同一机会被多个组件拒绝时只记录一行，controller 和 reason 为各组件及原因的组合
+CREATE TABLE IF NOT EXISTS rejected_opportunities (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    base_asset TEXT NOT NULL,
+    buy_quote TEXT NOT NULL,
+    sell_quote TEXT NOT NULL,
+    buy_price TEXT NOT NULL,
+    sell_price TEXT NOT NULL,
+    trade_amount TEXT NOT NULL,
+    profit_percentage TEXT NOT NULL,
+    controller TEXT NOT NULL,
+    reason TEXT NOT NULL,
+    strategy TEXT,
+    mode TEXT NOT NULL DEFAULT 'live',
+    rejected_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_rejected_opportunities_rejected_at ON rejected_opportunities (rejected_at);
@@ -0,0 +1,4 @@
+-- 套利机会ID，关联机会、订单（newClientOrderId前缀）和日志，旧记录为NULL
+ALTER TABLE arbitrage_history ADD COLUMN opportunity_id TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_arbitrage_history_opportunity_id ON arbitrage_history (opportunity_id);
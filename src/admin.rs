@@ -0,0 +1,647 @@
+//! 运行中引擎的管理接口（需要启用 `admin-api` 特性）
+//!
+//! 提供查看状态、交易历史和风控状态，以及暂停/恢复交易、重置风控组件、调整运行时参数和紧急停止的HTTP接口，
+//! 除 `/metrics` 外均返回JSON。接口与监控循环共享引擎，请求不会阻塞交易。
+//! 所有请求需要携带 `Authorization: Bearer <token>`，令牌来自配置 `admin_api.bearer_token`。
+//!
+//! | 方法 | 路径 | 说明 |
+//! |------|------|------|
+//! | GET  | `/status` | 引擎状态快照，包括各风控组件的状态 |
+//! | GET  | `/metrics` | Prometheus文本格式的运行指标，见 `metrics` 模块 |
+//! | GET  | `/history?limit=20` | 数据库中最近的交易记录，按开始时间从晚到早，`limit` 为1到1000 |
+//! | GET  | `/risk` | 已启用风控组件的名称、描述、当前状态和最近一次拒绝 |
+//! | POST | `/pause` | 手动暂停交易 |
+//! | POST | `/resume` | 解除手动暂停 |
+//! | PUT  | `/settings` | 调整运行时参数，例如 `{"min_profit_percentage": 0.2, "dry_run": true}`，未提供的字段保持不变，最小利润率为 `null` 表示取消覆盖 |
+//! | POST | `/risk/reset` | 重置风控组件，例如 `{"controller": "每日亏损限制"}` |
+//! | POST | `/blacklist/{asset}` | 将基础资产加入运行时黑名单 |
+//! | DELETE | `/blacklist/{asset}` | 将基础资产移出运行时黑名单 |
+//! | POST | `/kill-switch` | 紧急停止：暂停交易并取消两个交易对的全部挂单 |
+//! | POST | `/liquidate` | 紧急清仓：暂停交易并市价卖出全部基础资产 |
+
+use crate::arbitrage::{ArbitrageEngine, EngineStatus, LiquidationReport, RuntimeSettings, SettingsUpdate};
+use crate::binance::ExchangeApi;
+use crate::config::AdminApiSettings;
+use crate::db::TradeRecord;
+use crate::models::OrderInfo;
+use crate::risk::RiskControllerState;
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use axum::extract::{Path, Query, Request, State};
+use axum::http::{header, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post, put};
+use axum::{Json, Router};
+use log::{info, warn};
+use serde::Deserialize;
+use serde_json::json;
+use std::sync::Arc;
+
+/// 管理接口可以操作的引擎
+#[async_trait]
+pub trait EngineControl: Send + Sync {
+    /// 当前运行状态快照
+    fn status(&self) -> EngineStatus;
+
+    /// 手动暂停交易
+    fn pause(&self);
+
+    /// 解除手动暂停
+    fn resume(&self);
+
+    /// 更新运行时参数，返回更新后的参数
+    fn update_settings(&self, update: SettingsUpdate) -> Result<RuntimeSettings>;
+
+    /// 已启用的风控组件名称
+    fn risk_controller_names(&self) -> Vec<String>;
+
+    /// 按名称重置风控组件，返回是否找到该组件
+    async fn reset_risk_controller(&self, name: &str) -> Result<bool>;
+
+    /// 紧急清仓
+    async fn emergency_liquidate(&self) -> Result<LiquidationReport>;
+
+    /// 紧急停止，返回被取消的挂单
+    async fn kill_switch(&self) -> Result<Vec<OrderInfo>>;
+
+    /// 将基础资产加入运行时黑名单，返回是否新加入
+    fn blacklist_asset(&self, asset: &str) -> bool;
+
+    /// 将基础资产移出运行时黑名单，返回是否在黑名单中
+    fn unblacklist_asset(&self, asset: &str) -> bool;
+
+    /// 已启用风控组件的当前状态
+    fn risk_controller_states(&self) -> Vec<RiskControllerState>;
+
+    /// Prometheus文本格式的运行指标
+    fn render_metrics(&self) -> String;
+
+    /// 最近的交易记录，未设置数据库时返回None
+    async fn recent_trades(&self, limit: i64) -> Result<Option<Vec<TradeRecord>>>;
+}
+
+#[async_trait]
+impl<T: ExchangeApi + Send + Sync + 'static> EngineControl for ArbitrageEngine<T> {
+    fn status(&self) -> EngineStatus {
+        ArbitrageEngine::status(self)
+    }
+
+    fn pause(&self) {
+        ArbitrageEngine::pause(self)
+    }
+
+    fn resume(&self) {
+        ArbitrageEngine::resume(self)
+    }
+
+    fn update_settings(&self, update: SettingsUpdate) -> Result<RuntimeSettings> {
+        ArbitrageEngine::update_settings(self, update)
+    }
+
+    fn risk_controller_names(&self) -> Vec<String> {
+        ArbitrageEngine::risk_controller_names(self)
+    }
+
+    async fn reset_risk_controller(&self, name: &str) -> Result<bool> {
+        ArbitrageEngine::reset_risk_controller(self, name).await
+    }
+
+    async fn emergency_liquidate(&self) -> Result<LiquidationReport> {
+        ArbitrageEngine::emergency_liquidate(self).await
+    }
+
+    async fn kill_switch(&self) -> Result<Vec<OrderInfo>> {
+        ArbitrageEngine::kill_switch(self).await
+    }
+
+    fn blacklist_asset(&self, asset: &str) -> bool {
+        ArbitrageEngine::blacklist_asset(self, asset)
+    }
+
+    fn unblacklist_asset(&self, asset: &str) -> bool {
+        ArbitrageEngine::unblacklist_asset(self, asset)
+    }
+
+    fn risk_controller_states(&self) -> Vec<RiskControllerState> {
+        ArbitrageEngine::risk_controller_states(self)
+    }
+
+    fn render_metrics(&self) -> String {
+        self.metrics().render()
+    }
+
+    async fn recent_trades(&self, limit: i64) -> Result<Option<Vec<TradeRecord>>> {
+        ArbitrageEngine::recent_trades(self, limit).await
+    }
+}
+
+#[derive(Clone)]
+struct AdminState {
+    engine: Arc<dyn EngineControl>,
+    token: Arc<str>,
+}
+
+/// 管理接口的错误响应，返回 `{"error": "..."}`
+struct AdminError(StatusCode, String);
+
+impl IntoResponse for AdminError {
+    fn into_response(self) -> Response {
+        (self.0, Json(json!({ "error": self.1 }))).into_response()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ResetRequest {
+    controller: String,
+}
+
+/// `/history` 一次最多返回的记录数
+const MAX_HISTORY_LIMIT: i64 = 1000;
+
+#[derive(Debug, Deserialize)]
+struct HistoryQuery {
+    #[serde(default = "default_history_limit")]
+    limit: i64,
+}
+
+fn default_history_limit() -> i64 {
+    20
+}
+
+/// 创建管理接口路由
+pub fn router(engine: Arc<dyn EngineControl>, token: &str) -> Router {
+    let state = AdminState { engine, token: Arc::from(token) };
+
+    Router::new()
+        .route("/status", get(get_status))
+        .route("/metrics", get(get_metrics))
+        .route("/history", get(get_history))
+        .route("/risk", get(get_risk))
+        .route("/pause", post(pause))
+        .route("/resume", post(resume))
+        .route("/settings", put(update_settings))
+        .route("/risk/reset", post(reset_risk_controller))
+        .route("/blacklist/:asset", post(blacklist_asset).delete(unblacklist_asset))
+        .route("/kill-switch", post(kill_switch))
+        .route("/liquidate", post(liquidate))
+        .layer(middleware::from_fn_with_state(state.clone(), require_token))
+        .with_state(state)
+}
+
+/// 在配置的地址上启动管理接口，直到出错才返回
+pub async fn serve(engine: Arc<dyn EngineControl>, settings: &AdminApiSettings) -> Result<()> {
+    if settings.bearer_token.is_empty() {
+        return Err(anyhow!("管理接口未配置访问令牌 (admin_api.bearer_token 或环境变量 ADMIN_API_TOKEN)"));
+    }
+
+    let listener = tokio::net::TcpListener::bind(&settings.bind_address)
+        .await
+        .with_context(|| format!("管理接口无法监听 {}", settings.bind_address))?;
+    info!("管理接口已启动: http://{}", settings.bind_address);
+
+    axum::serve(listener, router(engine, &settings.bearer_token))
+        .await
+        .context("管理接口运行失败")
+}
+
+/// 校验 `Authorization: Bearer <token>`
+async fn require_token(State(state): State<AdminState>, request: Request, next: Next) -> Response {
+    let provided = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+
+    match provided {
+        Some(token) if token_matches(&state.token, token) => next.run(request).await,
+        _ => {
+            warn!("管理接口拒绝未授权的请求: {} {}", request.method(), request.uri().path());
+            AdminError(StatusCode::UNAUTHORIZED, "缺少或无效的访问令牌".to_string()).into_response()
+        }
+    }
+}
+
+/// 逐字节比较令牌，耗时不随第一个不同字节的位置变化
+fn token_matches(expected: &str, provided: &str) -> bool {
+    expected.len() == provided.len()
+        && expected.bytes().zip(provided.bytes()).fold(0u8, |acc, (a, b)| acc | (a ^ b)) == 0
+}
+
+async fn get_status(State(state): State<AdminState>) -> Json<EngineStatus> {
+    Json(state.engine.status())
+}
+
+async fn get_metrics(State(state): State<AdminState>) -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4; charset=utf-8")],
+        state.engine.render_metrics(),
+    )
+}
+
+async fn get_history(
+    State(state): State<AdminState>,
+    Query(query): Query<HistoryQuery>,
+) -> Result<Json<Vec<TradeRecord>>, AdminError> {
+    if !(1..=MAX_HISTORY_LIMIT).contains(&query.limit) {
+        return Err(AdminError(
+            StatusCode::BAD_REQUEST,
+            format!("limit 必须在 1 到 {} 之间: {}", MAX_HISTORY_LIMIT, query.limit),
+        ));
+    }
+    match state.engine.recent_trades(query.limit).await {
+        Ok(Some(trades)) => Ok(Json(trades)),
+        Ok(None) => Err(AdminError(StatusCode::SERVICE_UNAVAILABLE, "未连接数据库，没有交易历史".to_string())),
+        Err(e) => Err(AdminError(StatusCode::INTERNAL_SERVER_ERROR, e.to_string())),
+    }
+}
+
+async fn get_risk(State(state): State<AdminState>) -> Json<Vec<RiskControllerState>> {
+    Json(state.engine.risk_controller_states())
+}
+
+async fn pause(State(state): State<AdminState>) -> Json<EngineStatus> {
+    info!("管理接口: 暂停交易");
+    state.engine.pause();
+    Json(state.engine.status())
+}
+
+async fn resume(State(state): State<AdminState>) -> Json<EngineStatus> {
+    info!("管理接口: 恢复交易");
+    state.engine.resume();
+    Json(state.engine.status())
+}
+
+async fn update_settings(
+    State(state): State<AdminState>,
+    Json(update): Json<SettingsUpdate>,
+) -> Result<Json<EngineStatus>, AdminError> {
+    info!("管理接口: 更新运行时参数 {:?}", update);
+    state
+        .engine
+        .update_settings(update)
+        .map_err(|e| AdminError(StatusCode::BAD_REQUEST, e.to_string()))?;
+    Ok(Json(state.engine.status()))
+}
+
+async fn reset_risk_controller(
+    State(state): State<AdminState>,
+    Json(request): Json<ResetRequest>,
+) -> Result<Json<serde_json::Value>, AdminError> {
+    info!("管理接口: 重置风控组件 {}", request.controller);
+    let found = state
+        .engine
+        .reset_risk_controller(&request.controller)
+        .await
+        .map_err(|e| AdminError(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    if !found {
+        return Err(AdminError(
+            StatusCode::NOT_FOUND,
+            format!(
+                "未启用风控组件 {}，可用的组件: {}",
+                request.controller,
+                state.engine.risk_controller_names().join(", ")
+            ),
+        ));
+    }
+
+    Ok(Json(json!({ "reset": request.controller })))
+}
+
+async fn blacklist_asset(State(state): State<AdminState>, Path(asset): Path<String>) -> Json<EngineStatus> {
+    info!("管理接口: 将 {} 加入运行时黑名单", asset);
+    state.engine.blacklist_asset(&asset);
+    Json(state.engine.status())
+}
+
+async fn unblacklist_asset(
+    State(state): State<AdminState>,
+    Path(asset): Path<String>,
+) -> Result<Json<EngineStatus>, AdminError> {
+    info!("管理接口: 将 {} 移出运行时黑名单", asset);
+    if !state.engine.unblacklist_asset(&asset) {
+        return Err(AdminError(StatusCode::NOT_FOUND, format!("{} 不在运行时黑名单中", asset)));
+    }
+    Ok(Json(state.engine.status()))
+}
+
+async fn kill_switch(State(state): State<AdminState>) -> Result<Json<serde_json::Value>, AdminError> {
+    warn!("管理接口: 触发紧急停止");
+    let cancelled = state
+        .engine
+        .kill_switch()
+        .await
+        .map_err(|e| AdminError(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    Ok(Json(json!({ "cancelled": cancelled, "status": state.engine.status() })))
+}
+
+async fn liquidate(State(state): State<AdminState>) -> Result<Json<LiquidationReport>, AdminError> {
+    warn!("管理接口: 触发紧急清仓");
+    state
+        .engine
+        .emergency_liquidate()
+        .await
+        .map(Json)
+        .map_err(|e| AdminError(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::binance::MockBinanceApi;
+    use crate::config::{ArbitrageSettings, Config, FeeSettings, RiskControllerType, RiskSettings};
+    use crate::db::DatabaseManager;
+    use crate::models::{Side, TimeInForce, TradingMode};
+    use axum::body::Body;
+    use axum::http::Method;
+    use rust_decimal::Decimal;
+    use rust_decimal_macros::dec;
+    use serde_json::Value;
+    use tokio::time::{sleep, Duration};
+    use tower::ServiceExt;
+
+    const TOKEN: &str = "test-token";
+
+    fn test_config() -> Config {
+        Config {
+            arbitrage_settings: ArbitrageSettings {
+                check_interval_ms: 10,
+                ..ArbitrageSettings::default()
+            },
+            risk_settings: RiskSettings {
+                enabled_controllers: vec![RiskControllerType::DailyLossLimit],
+                ..RiskSettings::default()
+            },
+            // 测试行情的价差小于默认手续费，手续费设为0
+            fees: FeeSettings { maker_rate: Decimal::ZERO, taker_rate: Decimal::ZERO, ..FeeSettings::default() },
+            ..Config::default()
+        }
+    }
+
+    fn test_engine(api: &MockBinanceApi) -> Arc<ArbitrageEngine<MockBinanceApi>> {
+        // 制造明显的价差，监控循环每轮都会执行套利
+        api.update_price("BTCUSDC", dec!(51000));
+        Arc::new(ArbitrageEngine::new(api.clone(), test_config(), "BTC").unwrap())
+    }
+
+    async fn send(app: &Router, method: Method, uri: &str, token: Option<&str>, body: Option<Value>) -> (StatusCode, Value) {
+        let mut request = axum::http::Request::builder().method(method).uri(uri);
+        if let Some(token) = token {
+            request = request.header(header::AUTHORIZATION, format!("Bearer {}", token));
+        }
+        let body = match body {
+            Some(body) => {
+                request = request.header(header::CONTENT_TYPE, "application/json");
+                Body::from(body.to_string())
+            }
+            None => Body::empty(),
+        };
+
+        let response = app.clone().oneshot(request.body(body).unwrap()).await.unwrap();
+        let status = response.status();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let value = serde_json::from_slice(&bytes).unwrap_or(Value::Null);
+        (status, value)
+    }
+
+    #[tokio::test]
+    async fn test_requests_require_bearer_token() {
+        let api = MockBinanceApi::new();
+        let app = router(test_engine(&api), TOKEN);
+
+        let (status, body) = send(&app, Method::GET, "/status", None, None).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert!(body["error"].is_string());
+
+        let (status, _) = send(&app, Method::POST, "/pause", Some("wrong-token"), None).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        let (status, body) = send(&app, Method::GET, "/status", Some(TOKEN), None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["manually_paused"], false);
+    }
+
+    #[tokio::test]
+    async fn test_pause_stops_trading_until_resumed() {
+        let api = MockBinanceApi::new();
+        let engine = test_engine(&api);
+        let app = router(engine.clone(), TOKEN);
+
+        let monitor = {
+            let engine = engine.clone();
+            tokio::spawn(async move { engine.monitor_opportunities().await })
+        };
+
+        // 等待监控循环执行至少一次套利
+        for _ in 0..100 {
+            if engine.session_stats().trades > 0 {
+                break;
+            }
+            sleep(Duration::from_millis(10)).await;
+        }
+        assert!(engine.session_stats().trades > 0);
+
+        let (status, body) = send(&app, Method::POST, "/pause", Some(TOKEN), None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["manually_paused"], true);
+
+        // 暂停前已开始的一轮可能仍在执行，之后不再有新的交易
+        sleep(Duration::from_millis(50)).await;
+        let paused_trades = engine.session_stats().trades;
+        sleep(Duration::from_millis(100)).await;
+        assert_eq!(engine.session_stats().trades, paused_trades);
+        assert!(!monitor.is_finished(), "暂停期间监控循环应继续运行");
+
+        let (status, body) = send(&app, Method::POST, "/resume", Some(TOKEN), None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["manually_paused"], false);
+        sleep(Duration::from_millis(100)).await;
+        assert!(engine.session_stats().trades > paused_trades);
+
+        monitor.abort();
+    }
+
+    #[tokio::test]
+    async fn test_update_min_profit_and_reset_controller() {
+        let api = MockBinanceApi::new();
+        let app = router(test_engine(&api), TOKEN);
+
+        let (status, body) = send(&app, Method::PUT, "/settings", Some(TOKEN), Some(json!({ "min_profit_percentage": 5 }))).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["runtime_settings"]["min_profit_percentage"], "5");
+
+        let (status, _) = send(&app, Method::PUT, "/settings", Some(TOKEN), Some(json!({ "min_profit_percentage": -1 }))).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        // 未提供的字段保持不变
+        let (status, body) = send(&app, Method::PUT, "/settings", Some(TOKEN), Some(json!({ "check_interval_ms": 20, "dry_run": true }))).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["runtime_settings"]["min_profit_percentage"], "5");
+        assert_eq!(body["runtime_settings"]["check_interval_ms"], 20);
+        assert_eq!(body["runtime_settings"]["dry_run"], true);
+
+        let (status, _) = send(&app, Method::PUT, "/settings", Some(TOKEN), Some(json!({ "max_trade_amount_usdt": 0 }))).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let (status, body) = send(&app, Method::PUT, "/settings", Some(TOKEN), Some(json!({ "min_profit_percentage": null }))).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(body["runtime_settings"]["min_profit_percentage"].is_null());
+        assert_eq!(body["runtime_settings"]["dry_run"], true);
+
+        let (status, body) = send(&app, Method::POST, "/risk/reset", Some(TOKEN), Some(json!({ "controller": "每日亏损限制" }))).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["reset"], "每日亏损限制");
+
+        let (status, body) = send(&app, Method::POST, "/risk/reset", Some(TOKEN), Some(json!({ "controller": "不存在" }))).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert!(body["error"].as_str().unwrap().contains("每日亏损限制"));
+    }
+
+    #[tokio::test]
+    async fn test_runtime_min_profit_blocks_trades() {
+        let api = MockBinanceApi::new();
+        let engine = test_engine(&api);
+        let app = router(engine.clone(), TOKEN);
+
+        // 价差约2%，低于设置的5%
+        let (status, _) = send(&app, Method::PUT, "/settings", Some(TOKEN), Some(json!({ "min_profit_percentage": 5 }))).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let monitor = tokio::time::timeout(Duration::from_millis(100), engine.monitor_opportunities()).await;
+        assert!(monitor.is_err());
+        assert_eq!(engine.session_stats().trades, 0);
+    }
+
+    #[tokio::test]
+    async fn test_liquidate_sells_base_asset_and_pauses() {
+        let api = MockBinanceApi::new();
+        let app = router(test_engine(&api), TOKEN);
+
+        let (status, body) = send(&app, Method::POST, "/liquidate", Some(TOKEN), None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["asset"], "BTC");
+        assert_eq!(body["symbol"], "BTCUSDT");
+        assert_eq!(body["quantity"], "1.0");
+        assert!(body["order"]["order_id"].is_u64());
+        assert_eq!(api.get_account_balance("BTC").await.unwrap(), Decimal::ZERO);
+
+        let (_, body) = send(&app, Method::GET, "/status", Some(TOKEN), None).await;
+        assert_eq!(body["manually_paused"], true);
+
+        // 余额为0时不再下单
+        let (status, body) = send(&app, Method::POST, "/liquidate", Some(TOKEN), None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(body["order"].is_null());
+    }
+
+    #[tokio::test]
+    async fn test_simulated_engine_pause_history_and_risk() {
+        let api = MockBinanceApi::new();
+        api.update_price("BTCUSDC", dec!(51000));
+        let mut engine = ArbitrageEngine::new(api.clone(), test_config(), "BTC").unwrap();
+        engine.set_trading_mode(TradingMode::Simulate);
+        engine.set_db_manager(DatabaseManager::in_memory().await.unwrap());
+        let engine = Arc::new(engine);
+        let app = router(engine.clone(), TOKEN);
+
+        let (status, body) = send(&app, Method::GET, "/status", Some(TOKEN), None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["mode"], "simulate");
+
+        let monitor = {
+            let engine = engine.clone();
+            tokio::spawn(async move { engine.monitor_opportunities().await })
+        };
+        for _ in 0..100 {
+            if engine.session_stats().trades > 0 {
+                break;
+            }
+            sleep(Duration::from_millis(10)).await;
+        }
+        assert!(engine.session_stats().trades > 0);
+
+        let (status, _) = send(&app, Method::POST, "/pause", Some(TOKEN), None).await;
+        assert_eq!(status, StatusCode::OK);
+        sleep(Duration::from_millis(50)).await;
+        let paused_trades = engine.session_stats().trades;
+        sleep(Duration::from_millis(100)).await;
+        assert_eq!(engine.session_stats().trades, paused_trades);
+        monitor.abort();
+
+        let (status, body) = send(&app, Method::GET, "/history?limit=1", Some(TOKEN), None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body.as_array().unwrap().len(), 1);
+        assert_eq!(body[0]["result"]["mode"], "simulate");
+
+        let (status, _) = send(&app, Method::GET, "/history?limit=0", Some(TOKEN), None).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let (status, body) = send(&app, Method::GET, "/risk", Some(TOKEN), None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body[0]["name"], "每日亏损限制");
+        assert_eq!(body[0]["state"]["limit_reached"], false);
+        assert!(body[0]["state"]["daily_pnl"].is_string());
+        assert!(body[0]["last_rejection"].is_null());
+
+        let (_, status_body) = send(&app, Method::GET, "/status", Some(TOKEN), None).await;
+        assert_eq!(status_body["risk"], body);
+    }
+
+    #[tokio::test]
+    async fn test_history_requires_database() {
+        let api = MockBinanceApi::new();
+        let app = router(test_engine(&api), TOKEN);
+
+        let (status, body) = send(&app, Method::GET, "/history", Some(TOKEN), None).await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert!(body["error"].is_string());
+    }
+
+    #[tokio::test]
+    async fn test_runtime_blacklist_skips_asset() {
+        let api = MockBinanceApi::new();
+        let engine = test_engine(&api);
+        let app = router(engine.clone(), TOKEN);
+
+        let (status, body) = send(&app, Method::POST, "/blacklist/btc", Some(TOKEN), None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["runtime_settings"]["blacklisted_assets"], json!(["BTC"]));
+
+        let monitor = tokio::time::timeout(Duration::from_millis(100), engine.monitor_opportunities()).await;
+        assert!(monitor.is_err());
+        assert_eq!(engine.session_stats().trades, 0);
+
+        let request = axum::http::Request::builder()
+            .uri("/metrics")
+            .header(header::AUTHORIZATION, format!("Bearer {}", TOKEN))
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let metrics = String::from_utf8(bytes.to_vec()).unwrap();
+        assert!(metrics.contains(r#"controller="运行时黑名单",reason="blacklisted""#));
+
+        let (status, body) = send(&app, Method::DELETE, "/blacklist/BTC", Some(TOKEN), None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["runtime_settings"]["blacklisted_assets"], json!([]));
+        let (status, _) = send(&app, Method::DELETE, "/blacklist/BTC", Some(TOKEN), None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_kill_switch_pauses_and_keeps_manual_orders() {
+        let api = MockBinanceApi::new();
+        let app = router(test_engine(&api), TOKEN);
+        api.set_hold_orders(true);
+        // 不是引擎提交的挂单，紧急停止不取消
+        let order = api.place_order("BTCUSDT", Side::Buy, dec!(0.01), Some(dec!(49000)), TimeInForce::GTC, None).await.unwrap();
+
+        let (status, body) = send(&app, Method::POST, "/kill-switch", Some(TOKEN), None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["cancelled"], json!([]));
+        assert_eq!(body["status"]["manually_paused"], true);
+        assert_eq!(api.get_open_orders("BTCUSDT").await.unwrap()[0].order_id, order.order_id);
+    }
+}
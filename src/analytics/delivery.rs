@@ -0,0 +1,314 @@
+//! 定时生成前一天的绩效报告，并通过webhook或邮件推送
+//!
+//! 推送失败按退避时间重试，重试用尽后只记录错误并等待下一次推送，不影响交易。
+
+use super::{html, AnalyticsManager, PerformanceReport, TimeRange};
+use crate::config::{ReportChannel, ReportDeliveryFormat, ReportDeliverySettings, SmtpSettings};
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, FixedOffset, NaiveDate, TimeZone, Utc};
+use log::{error, info, warn};
+use std::time::Duration;
+use tokio::time::sleep;
+
+/// 定时报告推送
+pub struct ReportDelivery {
+    analytics: AnalyticsManager,
+    settings: ReportDeliverySettings,
+    /// 推送时间和报告日期使用的时区
+    offset: FixedOffset,
+    client: reqwest::Client,
+}
+
+impl ReportDelivery {
+    pub fn new(analytics: AnalyticsManager, settings: ReportDeliverySettings, offset: FixedOffset) -> Self {
+        Self {
+            analytics,
+            settings,
+            offset,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// 按推送时间持续推送前一天的报告，只在推送时间无法计算时返回
+    pub async fn run(&self) {
+        self.run_with_clock(Utc::now).await
+    }
+
+    /// 使用指定的时钟计算推送时间，测试中用于模拟时间
+    pub async fn run_with_clock<F: Fn() -> DateTime<Utc>>(&self, now: F) {
+        let mut last_run: Option<DateTime<Utc>> = None;
+        loop {
+            // 时钟略慢于定时器时不会重复推送同一时间点
+            let current = now();
+            let after = last_run.map_or(current, |last| last.max(current));
+            let next = match self.settings.schedule.next_after(after, self.offset) {
+                Ok(next) => next,
+                Err(e) => {
+                    error!("无法计算下一次报告推送时间，停止定时推送: {:#}", e);
+                    return;
+                }
+            };
+            info!("下一次报告推送时间: {}", next.with_timezone(&self.offset).format("%Y-%m-%d %H:%M:%S %:z"));
+            sleep((next - current).to_std().unwrap_or_default()).await;
+            last_run = Some(next);
+
+            let day = next.with_timezone(&self.offset).date_naive() - chrono::Duration::days(1);
+            if let Err(e) = self.deliver_day(day).await {
+                error!("{:#}", e);
+            }
+        }
+    }
+
+    /// 生成指定日期（按推送时区）的报告并推送，失败时按退避时间重试
+    pub async fn deliver_day(&self, day: NaiveDate) -> Result<()> {
+        let attempts = self.settings.max_retries + 1;
+        let mut backoff = Duration::from_millis(self.settings.retry_backoff_ms);
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            match self.try_deliver(day).await {
+                Ok(()) => {
+                    info!("已推送 {} 的绩效报告", day);
+                    return Ok(());
+                }
+                Err(e) if attempt < attempts => {
+                    warn!("推送 {} 的绩效报告失败（第 {} 次）: {:#}，{} ms 后重试", day, attempt, e, backoff.as_millis());
+                    sleep(backoff).await;
+                    backoff *= 2;
+                }
+                Err(e) => {
+                    return Err(e.context(format!("推送 {} 的绩效报告失败，已尝试 {} 次", day, attempts)));
+                }
+            }
+        }
+    }
+
+    /// 指定日期在推送时区内的时间范围，包含当天最后一秒
+    fn day_range(&self, day: NaiveDate) -> Result<TimeRange> {
+        let start = self
+            .offset
+            .from_local_datetime(&day.and_hms_opt(0, 0, 0).unwrap())
+            .single()
+            .ok_or_else(|| anyhow!("无法确定 {} 的开始时间", day))?
+            .with_timezone(&Utc);
+        let end = start + chrono::Duration::days(1) - chrono::Duration::seconds(1);
+        Ok(TimeRange::Custom(start, end))
+    }
+
+    async fn try_deliver(&self, day: NaiveDate) -> Result<()> {
+        let report = self.analytics.generate_report(self.day_range(day)?).await?;
+        let body = self.render(&report)?;
+
+        match &self.settings.channel {
+            ReportChannel::Webhook { url } => {
+                let content_type = match self.settings.format {
+                    ReportDeliveryFormat::Json => "application/json",
+                    ReportDeliveryFormat::Html => "text/html; charset=utf-8",
+                };
+                self.client
+                    .post(url)
+                    .header(reqwest::header::CONTENT_TYPE, content_type)
+                    .body(body)
+                    .send()
+                    .await
+                    .context("请求webhook失败")?
+                    .error_for_status()
+                    .context("webhook返回错误状态")?;
+                Ok(())
+            }
+            ReportChannel::Email(smtp) => send_email(smtp, &report.title, body, self.settings.format).await,
+        }
+    }
+
+    fn render(&self, report: &PerformanceReport) -> Result<String> {
+        match self.settings.format {
+            ReportDeliveryFormat::Json => Ok(serde_json::to_string_pretty(report)?),
+            ReportDeliveryFormat::Html => Ok(html::render_report(report)),
+        }
+    }
+}
+
+#[cfg(feature = "email")]
+async fn send_email(smtp: &SmtpSettings, subject: &str, body: String, format: ReportDeliveryFormat) -> Result<()> {
+    use lettre::message::header::ContentType;
+    use lettre::transport::smtp::authentication::Credentials;
+    use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+
+    let content_type = match format {
+        ReportDeliveryFormat::Json => ContentType::TEXT_PLAIN,
+        ReportDeliveryFormat::Html => ContentType::TEXT_HTML,
+    };
+    let mut builder = Message::builder()
+        .from(smtp.from.parse().with_context(|| format!("无效的发件人地址: {}", smtp.from))?)
+        .subject(subject)
+        .header(content_type);
+    for to in &smtp.to {
+        builder = builder.to(to.parse().with_context(|| format!("无效的收件人地址: {}", to))?);
+    }
+    let message = builder.body(body).context("构造邮件失败")?;
+
+    let mut transport = AsyncSmtpTransport::<Tokio1Executor>::relay(&smtp.host)
+        .with_context(|| format!("无效的SMTP服务器: {}", smtp.host))?
+        .port(smtp.port);
+    if !smtp.username.is_empty() {
+        transport = transport.credentials(Credentials::new(smtp.username.clone(), smtp.password.clone()));
+    }
+    transport.build().send(message).await.context("发送邮件失败")?;
+    Ok(())
+}
+
+#[cfg(not(feature = "email"))]
+async fn send_email(_smtp: &SmtpSettings, _subject: &str, _body: String, _format: ReportDeliveryFormat) -> Result<()> {
+    Err(anyhow!("编译时未启用 email 特性，无法通过邮件推送报告"))
+}
+
+#[cfg(all(test, feature = "db-sqlite"))]
+mod tests {
+    use super::*;
+    use crate::config::ReportSchedule;
+    use crate::db::DatabaseManager;
+    use crate::models::{ArbitrageResult, ArbitrageStatus, FillInfo, TradingMode};
+    use rust_decimal_macros::dec;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+    use tokio::sync::mpsc;
+
+    /// 东八区 2024-03-01 10:00 有一笔利润 10 的交易，03-02 00:30 有一笔利润 5 的交易
+    async fn make_manager() -> AnalyticsManager {
+        let db = DatabaseManager::new("sqlite::memory:").await.expect("创建SQLite数据库失败");
+        let trades = [
+            (Utc.with_ymd_and_hms(2024, 3, 1, 2, 0, 0).unwrap(), dec!(10)),
+            (Utc.with_ymd_and_hms(2024, 3, 1, 16, 30, 0).unwrap(), dec!(5)),
+        ];
+        for (index, (start_time, profit)) in trades.into_iter().enumerate() {
+            let result = ArbitrageResult {
+                base_asset: "BTC".to_string(),
+                buy_quote: "USDT".to_string(),
+                sell_quote: "USDC".to_string(),
+                buy_price: dec!(50000),
+                sell_price: dec!(50100),
+                trade_amount: dec!(0.1),
+                profit,
+                profit_percentage: dec!(0.2),
+                buy_order_id: Some(index as u64 * 2 + 1),
+                sell_order_id: Some(index as u64 * 2 + 2),
+                status: ArbitrageStatus::Completed,
+                timestamp: start_time,
+                start_time,
+                end_time: start_time,
+                buy_fill: FillInfo::default(),
+                sell_fill: FillInfo::default(),
+                mode: TradingMode::Live,
+                config_hash: String::new(),
+                strategy: None,
+                opportunity_id: None,
+            };
+            db.record_arbitrage_result(&result).await.unwrap();
+        }
+        AnalyticsManager::new(db)
+    }
+
+    fn offset() -> FixedOffset {
+        FixedOffset::east_opt(8 * 3600).unwrap()
+    }
+
+    /// 本地webhook服务，依次按 `statuses` 中的状态码响应，收到的请求体发送到返回的通道
+    async fn spawn_webhook(statuses: Vec<u16>) -> (String, mpsc::UnboundedReceiver<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/report", listener.local_addr().unwrap());
+        let (sender, receiver) = mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            for status in statuses {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut request = Vec::new();
+                let mut buffer = [0u8; 4096];
+                // 读取请求头，再按 Content-Length 读取请求体
+                let body_start = loop {
+                    let read = stream.read(&mut buffer).await.unwrap();
+                    request.extend_from_slice(&buffer[..read]);
+                    if let Some(position) = request.windows(4).position(|window| window == b"\r\n\r\n") {
+                        break position + 4;
+                    }
+                };
+                let headers = String::from_utf8_lossy(&request[..body_start]).to_lowercase();
+                let length: usize = headers
+                    .lines()
+                    .find_map(|line| line.strip_prefix("content-length:"))
+                    .map(|value| value.trim().parse().unwrap())
+                    .unwrap_or(0);
+                while request.len() < body_start + length {
+                    let read = stream.read(&mut buffer).await.unwrap();
+                    request.extend_from_slice(&buffer[..read]);
+                }
+
+                let response = format!("HTTP/1.1 {} Test\r\nContent-Length: 0\r\nConnection: close\r\n\r\n", status);
+                stream.write_all(response.as_bytes()).await.unwrap();
+                let _ = sender.send(String::from_utf8(request[body_start..].to_vec()).unwrap());
+            }
+        });
+        (url, receiver)
+    }
+
+    fn settings(url: String, format: ReportDeliveryFormat) -> ReportDeliverySettings {
+        ReportDeliverySettings {
+            enabled: true,
+            schedule: ReportSchedule::Daily { time: "08:00".to_string() },
+            channel: ReportChannel::Webhook { url },
+            format,
+            max_retries: 2,
+            retry_backoff_ms: 10,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_webhook_delivery_retries_after_failure() {
+        let (url, mut requests) = spawn_webhook(vec![500, 200]).await;
+        let delivery = ReportDelivery::new(make_manager().await, settings(url, ReportDeliveryFormat::Json), offset());
+
+        delivery.deliver_day(NaiveDate::from_ymd_opt(2024, 3, 1).unwrap()).await.unwrap();
+
+        // 第一次返回500后重试，两次请求都包含完整的报告
+        for _ in 0..2 {
+            let body = requests.recv().await.unwrap();
+            let report: PerformanceReport = serde_json::from_str(&body).unwrap();
+            // 只包含东八区 03-01 当天的交易
+            assert_eq!(report.overview.total_trades, 1);
+            assert_eq!(report.overview.total_profit, dec!(10));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_delivery_fails_after_retries_exhausted() {
+        let (url, mut requests) = spawn_webhook(vec![500, 502, 503]).await;
+        let delivery = ReportDelivery::new(make_manager().await, settings(url, ReportDeliveryFormat::Html), offset());
+
+        let err = delivery.deliver_day(NaiveDate::from_ymd_opt(2024, 3, 1).unwrap()).await.unwrap_err();
+        assert!(format!("{:#}", err).contains("已尝试 3 次"));
+        for _ in 0..3 {
+            assert!(requests.recv().await.unwrap().contains("<svg"));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_schedule_triggers_previous_day_report() {
+        let (url, mut requests) = spawn_webhook(vec![200]).await;
+        let delivery = ReportDelivery::new(make_manager().await, settings(url, ReportDeliveryFormat::Json), offset());
+
+        // 模拟的时钟从东八区 2024-03-02 07:59:59.800 开始走
+        let started = std::time::Instant::now();
+        let fake_start = Utc.with_ymd_and_hms(2024, 3, 1, 23, 59, 59).unwrap() + chrono::Duration::milliseconds(800);
+        let clock = move || fake_start + chrono::Duration::from_std(started.elapsed()).unwrap();
+        let runner = tokio::spawn(async move { delivery.run_with_clock(clock).await });
+
+        let body = tokio::time::timeout(Duration::from_secs(5), requests.recv())
+            .await
+            .expect("到达推送时间后应推送报告")
+            .unwrap();
+        let report: PerformanceReport = serde_json::from_str(&body).unwrap();
+        // 03-02 08:00 推送 03-01 的报告，不包含 03-02 00:30 的交易
+        assert_eq!(report.overview.total_trades, 1);
+        assert_eq!(report.overview.total_profit, dec!(10));
+
+        runner.abort();
+    }
+}
@@ -0,0 +1,179 @@
+//! 交易执行耗时的分布，以及耗时与交易结果的关系
+
+use crate::db::TradeRecord;
+use crate::models::ArbitrageStatus;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+/// 交易耗时统计
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DurationStats {
+    /// 参与统计的交易数
+    pub trades: usize,
+    /// 耗时中位数（毫秒）
+    pub p50_ms: i64,
+    /// 耗时90分位数（毫秒）
+    pub p90_ms: i64,
+    /// 耗时99分位数（毫秒）
+    pub p99_ms: i64,
+    /// 已完成交易的平均耗时（毫秒），没有已完成的交易时为None
+    pub avg_completed_ms: Option<i64>,
+    /// 未完成交易的平均耗时（毫秒），没有未完成的交易时为None
+    pub avg_failed_ms: Option<i64>,
+    /// 按耗时四分位分组的平均利润，固定4组，从耗时最短的一组开始
+    pub quartiles: Vec<DurationQuartile>,
+}
+
+/// 耗时四分位分组
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DurationQuartile {
+    /// 分组序号，1-4
+    pub quartile: u8,
+    /// 分组耗时的上限（毫秒，含），依次为25、50、75分位数和最大耗时；下限为上一组的上限（不含）
+    pub upper_ms: i64,
+    /// 分组内的交易数
+    pub trades: usize,
+    /// 分组内的平均利润，没有交易时为0
+    pub avg_profit: Decimal,
+}
+
+impl DurationStats {
+    /// 由交易记录计算耗时统计，没有交易时为None
+    ///
+    /// 分位数按最近秩法取值。交易按耗时落入第一个上限不小于其耗时的分组，
+    /// 耗时相同的交易总在同一组，因此耗时集中时各组的交易数可能不均。
+    pub fn from_trades(trades: &[TradeRecord]) -> Option<Self> {
+        if trades.is_empty() {
+            return None;
+        }
+
+        let mut durations: Vec<i64> = trades.iter().map(|record| record.duration_ms).collect();
+        durations.sort_unstable();
+        let upper_bounds = [
+            percentile(&durations, 25),
+            percentile(&durations, 50),
+            percentile(&durations, 75),
+            durations[durations.len() - 1],
+        ];
+
+        let mut completed = (0i64, 0i64);
+        let mut failed = (0i64, 0i64);
+        // 每组的 (交易数, 总利润)
+        let mut buckets = [(0usize, Decimal::ZERO); 4];
+        for record in trades {
+            let totals = if record.result.status == ArbitrageStatus::Completed { &mut completed } else { &mut failed };
+            totals.0 += 1;
+            totals.1 += record.duration_ms;
+
+            let quartile = upper_bounds.iter().position(|upper| record.duration_ms <= *upper).unwrap_or(3);
+            buckets[quartile].0 += 1;
+            buckets[quartile].1 += record.result.profit;
+        }
+
+        let average = |(count, total): (i64, i64)| (count > 0).then(|| total / count);
+        Some(Self {
+            trades: trades.len(),
+            p50_ms: upper_bounds[1],
+            p90_ms: percentile(&durations, 90),
+            p99_ms: percentile(&durations, 99),
+            avg_completed_ms: average(completed),
+            avg_failed_ms: average(failed),
+            quartiles: buckets
+                .iter()
+                .zip(upper_bounds)
+                .enumerate()
+                .map(|(index, ((trades, profit), upper_ms))| DurationQuartile {
+                    quartile: index as u8 + 1,
+                    upper_ms,
+                    trades: *trades,
+                    avg_profit: if *trades > 0 { *profit / Decimal::from(*trades) } else { Decimal::ZERO },
+                })
+                .collect(),
+        })
+    }
+}
+
+/// 升序排列的非空序列的分位数（最近秩法）
+fn percentile(sorted: &[i64], percent: usize) -> i64 {
+    let rank = (percent * sorted.len()).div_ceil(100).max(1);
+    sorted[rank - 1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::testing::{arbitrage_result, trade_record};
+    use crate::models::ArbitrageResult;
+    use rust_decimal_macros::dec;
+
+    fn record(duration_ms: i64, status: ArbitrageStatus, profit: Decimal) -> TradeRecord {
+        let result = ArbitrageResult { profit, ..arbitrage_result(status) };
+        TradeRecord { duration_ms, ..trade_record(0, result) }
+    }
+
+    #[test]
+    fn test_percentiles() {
+        let durations: Vec<i64> = (1..=100).collect();
+        assert_eq!(percentile(&durations, 50), 50);
+        assert_eq!(percentile(&durations, 90), 90);
+        assert_eq!(percentile(&durations, 99), 99);
+        assert_eq!(percentile(&[7], 99), 7);
+        assert_eq!(percentile(&[1, 2, 3], 50), 2);
+    }
+
+    #[test]
+    fn test_duration_stats_buckets_by_quartile() {
+        // 8笔交易，耗时较长的两笔超时失败
+        let trades = [
+            record(100, ArbitrageStatus::Completed, dec!(4)),
+            record(200, ArbitrageStatus::Completed, dec!(2)),
+            record(300, ArbitrageStatus::Completed, dec!(3)),
+            record(400, ArbitrageStatus::Completed, dec!(1)),
+            record(500, ArbitrageStatus::Completed, dec!(1)),
+            record(600, ArbitrageStatus::Completed, dec!(3)),
+            record(10000, ArbitrageStatus::Failed, dec!(-2)),
+            record(12000, ArbitrageStatus::Failed, dec!(-4)),
+        ];
+
+        let stats = DurationStats::from_trades(&trades).unwrap();
+        assert_eq!(stats.trades, 8);
+        assert_eq!(stats.p50_ms, 400);
+        assert_eq!(stats.p90_ms, 12000);
+        assert_eq!(stats.p99_ms, 12000);
+        assert_eq!(stats.avg_completed_ms, Some(350));
+        assert_eq!(stats.avg_failed_ms, Some(11000));
+
+        let buckets: Vec<(u8, i64, usize, Decimal)> = stats
+            .quartiles
+            .iter()
+            .map(|q| (q.quartile, q.upper_ms, q.trades, q.avg_profit))
+            .collect();
+        assert_eq!(buckets, vec![
+            (1, 200, 2, dec!(3)),
+            (2, 400, 2, dec!(2)),
+            (3, 600, 2, dec!(2)),
+            (4, 12000, 2, dec!(-3)),
+        ]);
+
+        assert!(DurationStats::from_trades(&[]).is_none());
+    }
+
+    #[test]
+    fn test_equal_durations_share_quartile() {
+        let trades = [
+            record(100, ArbitrageStatus::Completed, dec!(1)),
+            record(100, ArbitrageStatus::Completed, dec!(3)),
+            record(100, ArbitrageStatus::Completed, dec!(2)),
+            record(900, ArbitrageStatus::Failed, dec!(-1)),
+        ];
+
+        let stats = DurationStats::from_trades(&trades).unwrap();
+        assert_eq!(stats.avg_failed_ms, Some(900));
+        // 前三个分位数均为100，耗时100的交易都落入第一组
+        let counts: Vec<usize> = stats.quartiles.iter().map(|q| q.trades).collect();
+        assert_eq!(counts, vec![3, 0, 0, 1]);
+        assert_eq!(stats.quartiles[0].avg_profit, dec!(2));
+        assert_eq!(stats.quartiles[1].avg_profit, Decimal::ZERO);
+        assert_eq!(stats.quartiles[3].upper_ms, 900);
+    }
+}
@@ -0,0 +1,320 @@
+//! 将绩效报告渲染为单个HTML文件
+//!
+//! 图表使用内联SVG生成，不引用外部脚本、样式或字体，文件可以直接在浏览器中打开或作为附件发送。
+
+use super::PerformanceReport;
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use std::fmt::Write;
+
+/// 图表宽度
+const CHART_WIDTH: f64 = 720.0;
+/// 折线图高度
+const CHART_HEIGHT: f64 = 240.0;
+/// 图表四周留白
+const CHART_PADDING: f64 = 40.0;
+/// 热力图每个小时格子的宽度（含间隔）
+const HEATMAP_CELL: f64 = 30.0;
+/// 柱状图每个币种占用的高度
+const BAR_ROW: f64 = 28.0;
+/// 柱状图最长柱子的宽度
+const BAR_MAX_WIDTH: f64 = 480.0;
+
+const POSITIVE_COLOR: &str = "46,160,67";
+const NEGATIVE_COLOR: &str = "218,54,51";
+
+const STYLE: &str = "body{font-family:-apple-system,\"Segoe UI\",\"PingFang SC\",\"Microsoft YaHei\",sans-serif;margin:24px auto;max-width:800px;color:#24292f}\
+h1{font-size:24px}h2{font-size:18px;margin-top:32px;border-bottom:1px solid #d0d7de;padding-bottom:4px}\
+table{border-collapse:collapse}td{border:1px solid #d0d7de;padding:4px 12px}td:last-child{text-align:right}\
+.meta{color:#57606a}.empty{color:#57606a;font-style:italic}svg text{font-size:11px;fill:#57606a}";
+
+/// 生成包含总体统计表、每日盈亏和权益折线图、按小时热力图和币种利润柱状图的HTML
+pub fn render_report(report: &PerformanceReport) -> String {
+    let mut html = String::new();
+    let _ = write!(
+        html,
+        "<!DOCTYPE html>\n<html lang=\"zh-CN\">\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>{}</style>\n</head>\n<body>\n",
+        escape(&report.title),
+        STYLE
+    );
+    let _ = writeln!(html, "<h1>{}</h1>", escape(&report.title));
+    let mode = report.mode.map(|mode| mode.to_string()).unwrap_or_else(|| "全部".to_string());
+    let _ = writeln!(
+        html,
+        "<p class=\"meta\">时间范围: {} | 运行模式: {} | 生成时间: {}</p>",
+        escape(&report.time_range),
+        mode,
+        report.generated_at.format("%Y-%m-%d %H:%M:%S UTC")
+    );
+
+    html.push_str("<h2>总体统计</h2>\n");
+    render_overview(&mut html, report);
+
+    html.push_str("<h2>每日盈亏</h2>\n");
+    let daily: Vec<(&str, Decimal)> = report.daily_stats.iter().map(|stats| (stats.date.as_str(), stats.profit)).collect();
+    render_line_chart(&mut html, &daily, "利润", true);
+
+    html.push_str("<h2>权益曲线</h2>\n");
+    let equity: Vec<(&str, Decimal)> = report.rolling.iter().map(|point| (point.date.as_str(), point.equity)).collect();
+    render_line_chart(&mut html, &equity, "权益", false);
+
+    html.push_str("<h2>按小时利润</h2>\n");
+    render_hourly_heatmap(&mut html, report);
+
+    html.push_str("<h2>币种利润</h2>\n");
+    render_asset_bars(&mut html, report);
+
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+fn render_overview(html: &mut String, report: &PerformanceReport) {
+    let overview = &report.overview;
+    let rows = [
+        ("总交易次数", overview.total_trades.to_string()),
+        ("成功交易次数", overview.successful_trades.to_string()),
+        ("失败交易次数", overview.failed_trades.to_string()),
+        ("总利润(USDT)", overview.total_profit.to_string()),
+        ("总交易量(USDT)", overview.total_volume.to_string()),
+        ("平均每笔利润(USDT)", overview.avg_profit_per_trade.to_string()),
+        ("最大单笔利润(USDT)", overview.max_profit.to_string()),
+        ("最大单笔亏损(USDT)", overview.max_loss.to_string()),
+        ("成功率(%)", format!("{:.2}", report.success_rate)),
+        ("盈亏比", format!("{:.2}", report.profit_loss_ratio)),
+        ("夏普比率", format!("{:.4}", report.risk_metrics.sharpe_ratio)),
+        ("最大回撤(USDT)", report.risk_metrics.max_drawdown.to_string()),
+        ("平均每日利润(USDT)", report.avg_daily_profit.to_string()),
+    ];
+
+    html.push_str("<table>\n");
+    for (label, value) in rows {
+        let _ = writeln!(html, "<tr><td>{}</td><td>{}</td></tr>", label, value);
+    }
+    html.push_str("</table>\n");
+}
+
+/// 按日期的折线图，`include_zero`为true时纵轴包含0并绘制0值参考线
+fn render_line_chart(html: &mut String, data: &[(&str, Decimal)], label: &str, include_zero: bool) {
+    if data.is_empty() {
+        html.push_str("<p class=\"empty\">无数据</p>\n");
+        return;
+    }
+
+    let values: Vec<f64> = data.iter().map(|(_, value)| to_f64(*value)).collect();
+    let (max_start, min_start) = if include_zero { (0.0, 0.0) } else { (values[0], values[0]) };
+    let max = values.iter().copied().fold(max_start, f64::max);
+    let min = values.iter().copied().fold(min_start, f64::min);
+    let inner_width = CHART_WIDTH - 2.0 * CHART_PADDING;
+    let inner_height = CHART_HEIGHT - 2.0 * CHART_PADDING;
+    let x = |index: usize| {
+        if values.len() == 1 {
+            CHART_WIDTH / 2.0
+        } else {
+            CHART_PADDING + inner_width * index as f64 / (values.len() - 1) as f64
+        }
+    };
+    let y = |value: f64| {
+        if max == min {
+            CHART_HEIGHT / 2.0
+        } else {
+            CHART_PADDING + (max - value) / (max - min) * inner_height
+        }
+    };
+
+    let _ = writeln!(html, "<svg width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\">", CHART_WIDTH, CHART_HEIGHT, CHART_WIDTH, CHART_HEIGHT);
+    if include_zero {
+        let _ = writeln!(
+            html,
+            "<line x1=\"{:.1}\" y1=\"{:.1}\" x2=\"{:.1}\" y2=\"{:.1}\" stroke=\"#8c959f\" stroke-dasharray=\"4\"/>",
+            CHART_PADDING, y(0.0), CHART_WIDTH - CHART_PADDING, y(0.0)
+        );
+    }
+    let points: Vec<String> = values.iter().enumerate().map(|(i, value)| format!("{:.1},{:.1}", x(i), y(*value))).collect();
+    let _ = writeln!(html, "<polyline fill=\"none\" stroke=\"#0969da\" stroke-width=\"2\" points=\"{}\"/>", points.join(" "));
+    for (i, (date, value)) in data.iter().enumerate() {
+        let _ = writeln!(
+            html,
+            "<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"3\" fill=\"#0969da\"><title>{} {} {}</title></circle>",
+            x(i), y(values[i]), escape(date), label, value
+        );
+    }
+    let first = data.first().map(|(date, _)| *date).unwrap_or_default();
+    let last = data.last().map(|(date, _)| *date).unwrap_or_default();
+    let _ = writeln!(html, "<text x=\"{:.1}\" y=\"{:.1}\">{}</text>", CHART_PADDING, CHART_HEIGHT - 12.0, escape(first));
+    let _ = writeln!(html, "<text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"end\">{}</text>", CHART_WIDTH - CHART_PADDING, CHART_HEIGHT - 12.0, escape(last));
+    let _ = writeln!(html, "<text x=\"4\" y=\"{:.1}\">{}</text>", CHART_PADDING, format_axis(max));
+    let _ = writeln!(html, "<text x=\"4\" y=\"{:.1}\">{}</text>", CHART_HEIGHT - CHART_PADDING, format_axis(min));
+    html.push_str("</svg>\n");
+}
+
+/// 24小时利润热力图，颜色深浅按利润绝对值相对最大值，盈利为绿色、亏损为红色
+fn render_hourly_heatmap(html: &mut String, report: &PerformanceReport) {
+    if report.hourly_stats.is_empty() {
+        html.push_str("<p class=\"empty\">无数据</p>\n");
+        return;
+    }
+
+    let max_abs = report.hourly_stats.iter().map(|stats| to_f64(stats.profit).abs()).fold(0.0, f64::max);
+    let width = HEATMAP_CELL * report.hourly_stats.len() as f64;
+    let _ = writeln!(html, "<svg width=\"{}\" height=\"60\" viewBox=\"0 0 {} 60\">", width, width);
+    for (i, stats) in report.hourly_stats.iter().enumerate() {
+        let x = HEATMAP_CELL * i as f64;
+        let _ = writeln!(
+            html,
+            "<rect x=\"{:.1}\" y=\"0\" width=\"{:.1}\" height=\"40\" fill=\"{}\"><title>{:02}时 交易 {} 利润 {}</title></rect>",
+            x, HEATMAP_CELL - 2.0, heat_color(to_f64(stats.profit), stats.trades, max_abs), stats.hour, stats.trades, stats.profit
+        );
+        let _ = writeln!(html, "<text x=\"{:.1}\" y=\"54\">{:02}</text>", x + 6.0, stats.hour);
+    }
+    html.push_str("</svg>\n");
+}
+
+/// 各币种总利润的横向柱状图
+fn render_asset_bars(html: &mut String, report: &PerformanceReport) {
+    if report.asset_stats.is_empty() {
+        html.push_str("<p class=\"empty\">无数据</p>\n");
+        return;
+    }
+
+    let max_abs = report.asset_stats.iter().map(|stats| to_f64(stats.profit).abs()).fold(0.0, f64::max);
+    let height = BAR_ROW * report.asset_stats.len() as f64;
+    let _ = writeln!(html, "<svg width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\">", CHART_WIDTH, height, CHART_WIDTH, height);
+    for (i, stats) in report.asset_stats.iter().enumerate() {
+        let y = BAR_ROW * i as f64;
+        let profit = to_f64(stats.profit);
+        let width = if max_abs > 0.0 { profit.abs() / max_abs * BAR_MAX_WIDTH } else { 0.0 };
+        let color = if profit < 0.0 { NEGATIVE_COLOR } else { POSITIVE_COLOR };
+        let _ = writeln!(html, "<text x=\"0\" y=\"{:.1}\">{}</text>", y + 16.0, escape(&stats.asset));
+        let _ = writeln!(
+            html,
+            "<rect x=\"80\" y=\"{:.1}\" width=\"{:.1}\" height=\"20\" fill=\"rgb({})\"><title>{} 利润 {}</title></rect>",
+            y + 2.0, width, color, escape(&stats.asset), stats.profit
+        );
+        let _ = writeln!(html, "<text x=\"{:.1}\" y=\"{:.1}\">{}</text>", 86.0 + width, y + 16.0, stats.profit);
+    }
+    html.push_str("</svg>\n");
+}
+
+fn heat_color(profit: f64, trades: i64, max_abs: f64) -> String {
+    if trades == 0 {
+        return "#eaeef2".to_string();
+    }
+    let intensity = if max_abs > 0.0 { profit.abs() / max_abs } else { 0.0 };
+    let color = if profit < 0.0 { NEGATIVE_COLOR } else { POSITIVE_COLOR };
+    format!("rgba({},{:.2})", color, 0.15 + 0.85 * intensity)
+}
+
+fn format_axis(value: f64) -> String {
+    format!("{:.2}", value)
+}
+
+fn to_f64(value: Decimal) -> f64 {
+    value.to_f64().unwrap_or_default()
+}
+
+/// 转义HTML文本中的特殊字符
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analytics::{rolling, RiskMetrics};
+    use crate::db::{hourly_profit_distribution, AssetStats, DailyStats, HourlyStats, RejectionStats, TradeStats};
+    use crate::models::TradingMode;
+    use chrono::{TimeZone, Utc};
+    use rust_decimal_macros::dec;
+
+    /// 两个交易日、两个币种的固定报告
+    fn fixture_report() -> PerformanceReport {
+        let daily_stats = vec![
+            DailyStats { date: "2024-03-01".to_string(), trades: 2, profit: dec!(12.5), volume: dec!(200), successful_rate: 100.0 },
+            DailyStats { date: "2024-03-02".to_string(), trades: 1, profit: dec!(-2.5), volume: dec!(100), successful_rate: 0.0 },
+        ];
+        let rolling = rolling::rolling_series(&daily_stats, dec!(1000));
+        let hourly_stats: Vec<HourlyStats> = (0..24)
+            .map(|hour| {
+                let (trades, profit) = match hour {
+                    9 => (2, dec!(12.5)),
+                    15 => (1, dec!(-2.5)),
+                    _ => (0, Decimal::ZERO),
+                };
+                HourlyStats { hour, trades, profit, volume: Decimal::ZERO, successful_rate: 0.0 }
+            })
+            .collect();
+
+        PerformanceReport {
+            title: "绩效报告 <BTC & ETH>".to_string(),
+            time_range: "2024-03-01 至 2024-03-02".to_string(),
+            mode: Some(TradingMode::Live),
+            generated_at: Utc.with_ymd_and_hms(2024, 3, 3, 8, 0, 0).unwrap(),
+            overview: TradeStats {
+                total_trades: 3,
+                successful_trades: 2,
+                failed_trades: 1,
+                total_profit: dec!(10.0),
+                total_volume: dec!(300),
+                avg_profit_per_trade: dec!(3.33),
+                max_profit: dec!(7.5),
+                max_loss: dec!(-2.5),
+                avg_trade_duration_ms: 500,
+                winning_trades: 2,
+                losing_trades: 1,
+                avg_win: dec!(6.25),
+                avg_loss: dec!(-2.5),
+            },
+            daily_stats,
+            hourly_profit: hourly_profit_distribution(&hourly_stats),
+            hourly_stats,
+            asset_stats: vec![
+                AssetStats { asset: "BTC".to_string(), trades: 2, successful_trades: 2, profit: dec!(8.0), volume: dec!(200), avg_profit: dec!(4.0), successful_rate: 100.0 },
+                AssetStats { asset: "ETH".to_string(), trades: 1, successful_trades: 0, profit: dec!(2.0), volume: dec!(100), avg_profit: dec!(2.0), successful_rate: 0.0 },
+            ],
+            asset_daily: Vec::new(),
+            strategy_stats: Vec::new(),
+            fee_slippage: None,
+            duration_stats: None,
+            rejections: RejectionStats::default(),
+            success_rate: 200.0 / 3.0,
+            profit_loss_ratio: 2.5,
+            risk_metrics: RiskMetrics { max_drawdown: dec!(2.5), ..RiskMetrics::default() },
+            rolling,
+            initial_balance: dec!(1000),
+            avg_daily_volume: dec!(150),
+            avg_daily_profit: dec!(5.0),
+            best_day: None,
+            worst_day: None,
+            fee_scenarios: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_html_contains_expected_data_points() {
+        let html = render_report(&fixture_report());
+
+        // 期望的片段每行一个，#开头的行为注释
+        let expected = include_str!("testdata/report_html_expected.txt");
+        for line in expected.lines().filter(|line| !line.is_empty() && !line.starts_with('#')) {
+            assert!(html.contains(line), "HTML中缺少: {}", line);
+        }
+
+        // 文件自包含，不引用外部资源
+        assert!(!html.contains("<script"));
+        assert!(!html.contains("<link"));
+        assert!(!html.contains("http"));
+    }
+
+    #[test]
+    fn test_empty_report_renders_placeholders() {
+        let mut report = fixture_report();
+        report.daily_stats.clear();
+        report.rolling.clear();
+        report.hourly_stats.clear();
+        report.asset_stats.clear();
+
+        let html = render_report(&report);
+        assert_eq!(html.matches("<p class=\"empty\">无数据</p>").count(), 4);
+        assert!(!html.contains("<svg"));
+    }
+}
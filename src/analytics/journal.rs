@@ -0,0 +1,162 @@
+//! 交易日志，按CoinTracking、Koinly等资产管理工具可以导入的通用CSV格式导出成交记录
+//!
+//! 每笔套利拆成买入和卖出两条成交，使用各自的实际成交数量、均价和手续费；没有成交的一边不输出。
+//! 时间统一为UTC，导入时在工具中选择UTC时区。
+
+use crate::db::TradeRecord;
+use crate::models::FillInfo;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use csv::Writer as CsvWriter;
+use rust_decimal::Decimal;
+use serde::Serialize;
+use std::io::Write;
+
+/// 交易日志的表头，与 [`JournalEntry::to_record`] 的列顺序一致
+pub const JOURNAL_HEADER: [&str; 7] = ["Date", "Pair", "Side", "Amount", "Price", "Fee", "Fee Currency"];
+
+/// 成交方向
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum JournalSide {
+    Buy,
+    Sell,
+}
+
+impl JournalSide {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JournalSide::Buy => "BUY",
+            JournalSide::Sell => "SELL",
+        }
+    }
+}
+
+/// 交易日志中的一条成交
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct JournalEntry {
+    /// 成交时间：买入为套利的开始时间，卖出为结束时间
+    pub date: DateTime<Utc>,
+    /// 交易对，格式为 `BTC/USDT`
+    pub pair: String,
+    pub side: JournalSide,
+    /// 成交数量（基础资产）
+    pub amount: Decimal,
+    /// 成交均价
+    pub price: Decimal,
+    pub fee: Decimal,
+    /// 手续费币种，没有手续费时为空
+    pub fee_currency: String,
+}
+
+impl JournalEntry {
+    fn from_fill(date: DateTime<Utc>, base: &str, quote: &str, side: JournalSide, fill: &FillInfo) -> Option<Self> {
+        (fill.qty > Decimal::ZERO).then(|| Self {
+            date,
+            pair: format!("{}/{}", base, quote),
+            side,
+            amount: fill.qty,
+            price: fill.avg_price,
+            fee: fill.commission,
+            fee_currency: if fill.commission > Decimal::ZERO { fill.commission_asset.clone() } else { String::new() },
+        })
+    }
+
+    /// 一笔套利的买入和卖出成交，按买入在前排列
+    pub fn from_trade(record: &TradeRecord) -> Vec<Self> {
+        let result = &record.result;
+        let buy = Self::from_fill(result.start_time, &result.base_asset, &result.buy_quote, JournalSide::Buy, &result.buy_fill);
+        let sell = Self::from_fill(result.end_time, &result.base_asset, &result.sell_quote, JournalSide::Sell, &result.sell_fill);
+        buy.into_iter().chain(sell).collect()
+    }
+
+    /// CSV中的一行
+    pub fn to_record(&self) -> [String; 7] {
+        [
+            self.date.format("%Y-%m-%d %H:%M:%S").to_string(),
+            self.pair.clone(),
+            self.side.as_str().to_string(),
+            self.amount.normalize().to_string(),
+            self.price.normalize().to_string(),
+            self.fee.normalize().to_string(),
+            self.fee_currency.clone(),
+        ]
+    }
+}
+
+/// 将交易记录按开始时间从早到晚写成交易日志，返回写入的成交条数
+pub fn write_journal<W: Write>(writer: W, trades: &[TradeRecord]) -> Result<usize> {
+    let mut ordered: Vec<&TradeRecord> = trades.iter().collect();
+    ordered.sort_by_key(|record| (record.result.start_time, record.id));
+
+    let mut writer = CsvWriter::from_writer(writer);
+    writer.write_record(JOURNAL_HEADER)?;
+    let mut rows = 0;
+    for entry in ordered.into_iter().flat_map(JournalEntry::from_trade) {
+        writer.write_record(entry.to_record())?;
+        rows += 1;
+    }
+    writer.flush()?;
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::testing::{arbitrage_result_at, fill, trade_record};
+    use crate::models::{ArbitrageResult, ArbitrageStatus};
+    use rust_decimal_macros::dec;
+
+    fn record(id: i64, start: &str, status: ArbitrageStatus, buy_fill: FillInfo, sell_fill: FillInfo) -> TradeRecord {
+        let start_time = DateTime::parse_from_rfc3339(start).unwrap().with_timezone(&Utc);
+        let result = arbitrage_result_at(status, start_time, chrono::Duration::seconds(2));
+        trade_record(id, ArbitrageResult { buy_fill, sell_fill, ..result })
+    }
+
+    #[test]
+    fn test_two_rows_per_trade() {
+        let trades = vec![
+            record(
+                2,
+                "2024-05-01T08:05:00Z",
+                ArbitrageStatus::Completed,
+                fill(dec!(0.2), dec!(50010), dec!(0.0002), "BTC"),
+                fill(dec!(0.2), dec!(50060), dec!(10.012), "USDC"),
+            ),
+            record(
+                1,
+                "2024-05-01T08:00:00Z",
+                ArbitrageStatus::Completed,
+                fill(dec!(0.1000), dec!(50000.00), dec!(0.0001), "BTC"),
+                fill(dec!(0.1), dec!(50100), dec!(5.01), "USDC"),
+            ),
+        ];
+
+        let mut output = Vec::new();
+        assert_eq!(write_journal(&mut output, &trades).unwrap(), 4);
+        let expected = "\
+Date,Pair,Side,Amount,Price,Fee,Fee Currency
+2024-05-01 08:00:00,BTC/USDT,BUY,0.1,50000,0.0001,BTC
+2024-05-01 08:00:02,BTC/USDC,SELL,0.1,50100,5.01,USDC
+2024-05-01 08:05:00,BTC/USDT,BUY,0.2,50010,0.0002,BTC
+2024-05-01 08:05:02,BTC/USDC,SELL,0.2,50060,10.012,USDC
+";
+        assert_eq!(String::from_utf8(output).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_unfilled_leg_and_zero_fee() {
+        // 卖出失败只输出买入一条
+        let trade = record(
+            1,
+            "2024-05-01T08:00:00Z",
+            ArbitrageStatus::Failed,
+            fill(dec!(0.1), dec!(50000), Decimal::ZERO, "BNB"),
+            FillInfo::default(),
+        );
+        let entries = JournalEntry::from_trade(&trade);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].side, JournalSide::Buy);
+        assert_eq!(entries[0].to_record()[5..], ["0".to_string(), String::new()]);
+    }
+}
@@ -0,0 +1,287 @@
+//! 报告语言和报告中使用的文字
+//!
+//! 报告标题、时间范围描述、CSV表头和命令行摘要中的文字都从这里获取，默认使用中文。
+
+use anyhow::{anyhow, Result};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/// 报告语言
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Locale {
+    /// 中文
+    #[default]
+    Zh,
+    /// 英文
+    En,
+}
+
+impl FromStr for Locale {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "zh" => Ok(Locale::Zh),
+            "en" => Ok(Locale::En),
+            _ => Err(anyhow!("未知的报告语言: {}，可选值: zh, en", s)),
+        }
+    }
+}
+
+impl Locale {
+    /// 连接两段文字，中文直接相连，英文以空格分隔
+    pub fn join(self, first: &str, second: &str) -> String {
+        match self {
+            Locale::Zh => format!("{}{}", first, second),
+            Locale::En => format!("{} {}", first, second),
+        }
+    }
+}
+
+/// 报告中的文字
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Label {
+    // 时间范围
+    Today,
+    Yesterday,
+    Past,
+    ThisWeek,
+    LastWeek,
+    ThisMonth,
+    LastMonth,
+    AllTime,
+    To,
+    // 标题和分节
+    ReportTitle,
+    SimulationReportTitle,
+    SummaryTitle,
+    StrategyStats,
+    FeesAndSlippage,
+    FeeScenarios,
+    // 统计表列名和统计指标
+    TimeRange,
+    Mode,
+    Date,
+    Hour,
+    Asset,
+    Strategy,
+    Metric,
+    Value,
+    Trades,
+    TradeCount,
+    Profit,
+    Volume,
+    SuccessRate,
+    RollingProfit,
+    RollingSuccessRate,
+    Equity,
+    TotalProfit,
+    TotalVolume,
+    AvgProfit,
+    AvgProfitPerTrade,
+    AvgSlippage,
+    TotalTrades,
+    SuccessfulTrades,
+    FailedTrades,
+    MaxProfit,
+    MaxLoss,
+    WinningTrades,
+    LosingTrades,
+    AvgWin,
+    AvgLoss,
+    ProfitLossRatio,
+    SharpeRatio,
+    SortinoRatio,
+    AnnualizationDays,
+    MaxDrawdown,
+    MaxDrawdownDays,
+    AvgDailyVolume,
+    AvgDailyProfit,
+    EndingEquity,
+    InitialBalance,
+    BestHour,
+    TopAsset,
+    FilledTrades,
+    TotalFees,
+    UnconvertedFeeTrades,
+    GrossProfit,
+    NetProfit,
+    FeeRatio,
+    AvgBuySlippage,
+    AvgSellSlippage,
+    FlippedTrades,
+    TakerFeeRate,
+    TradeDuration,
+    DurationP50,
+    DurationP90,
+    DurationP99,
+    AvgCompletedDuration,
+    AvgFailedDuration,
+    DurationQuartile,
+    DurationUpperBound,
+    Rejections,
+    Controller,
+    Reason,
+    RejectionCount,
+    ForegoneProfit,
+    ForegoneProfitNote,
+    // 税务报告
+    AcquiredAt,
+    AcquiredFrom,
+    AcquiredTo,
+    DisposedAt,
+    Quantity,
+    Proceeds,
+    CostBasis,
+    Gain,
+    Fees,
+    UnmatchedQuantity,
+    ArbitrageId,
+    // 单位
+    Milliseconds,
+    TradesUnit,
+    DaysUnit,
+}
+
+impl Label {
+    /// 指定语言的文字
+    pub fn text(self, locale: Locale) -> &'static str {
+        let (zh, en) = self.texts();
+        match locale {
+            Locale::Zh => zh,
+            Locale::En => en,
+        }
+    }
+
+    /// 带单位的文字，用于表头，例如 `利润(USDT)`、`Profit (USDT)`
+    pub fn with_unit(self, locale: Locale, unit: &str) -> String {
+        match locale {
+            Locale::Zh => format!("{}({})", self.text(locale), unit),
+            Locale::En => format!("{} ({})", self.text(locale), unit),
+        }
+    }
+
+    /// (中文, 英文)
+    fn texts(self) -> (&'static str, &'static str) {
+        match self {
+            Label::Today => ("今日", "Today"),
+            Label::Yesterday => ("昨日", "Yesterday"),
+            Label::Past => ("过去", "Last"),
+            Label::ThisWeek => ("本周", "This week"),
+            Label::LastWeek => ("上周", "Last week"),
+            Label::ThisMonth => ("本月", "This month"),
+            Label::LastMonth => ("上月", "Last month"),
+            Label::AllTime => ("全部历史", "All time"),
+            Label::To => ("至", "to"),
+            Label::ReportTitle => ("套利交易绩效报告", "Arbitrage Performance Report"),
+            Label::SimulationReportTitle => ("模拟交易绩效报告", "Simulation Performance Report"),
+            Label::SummaryTitle => ("绩效报告摘要", "Performance Summary"),
+            Label::StrategyStats => ("策略统计", "Strategies"),
+            Label::FeesAndSlippage => ("手续费和滑点", "Fees and slippage"),
+            Label::FeeScenarios => ("手续费对比", "Fee scenarios"),
+            Label::TimeRange => ("时间范围", "Time range"),
+            Label::Mode => ("运行模式", "Mode"),
+            Label::Date => ("日期", "Date"),
+            Label::Hour => ("小时", "Hour"),
+            Label::Asset => ("币种", "Asset"),
+            Label::Strategy => ("策略", "Strategy"),
+            Label::Metric => ("统计指标", "Metric"),
+            Label::Value => ("数值", "Value"),
+            Label::Trades => ("交易数量", "Trades"),
+            Label::TradeCount => ("交易数", "Trades"),
+            Label::Profit => ("利润", "Profit"),
+            Label::Volume => ("交易量", "Volume"),
+            Label::SuccessRate => ("成功率", "Success rate"),
+            Label::RollingProfit => ("7日滚动利润", "7-day rolling profit"),
+            Label::RollingSuccessRate => ("7日滚动成功率", "7-day rolling success rate"),
+            Label::Equity => ("权益", "Equity"),
+            Label::TotalProfit => ("总利润", "Total profit"),
+            Label::TotalVolume => ("总交易量", "Total volume"),
+            Label::AvgProfit => ("平均利润", "Avg profit"),
+            Label::AvgProfitPerTrade => ("平均每笔利润", "Avg profit per trade"),
+            Label::AvgSlippage => ("平均滑点", "Avg slippage"),
+            Label::TotalTrades => ("总交易次数", "Total trades"),
+            Label::SuccessfulTrades => ("成功交易次数", "Successful trades"),
+            Label::FailedTrades => ("失败交易次数", "Failed trades"),
+            Label::MaxProfit => ("最大单笔利润", "Max profit"),
+            Label::MaxLoss => ("最大单笔亏损", "Max loss"),
+            Label::WinningTrades => ("盈利交易次数", "Winning trades"),
+            Label::LosingTrades => ("亏损交易次数", "Losing trades"),
+            Label::AvgWin => ("平均盈利", "Avg win"),
+            Label::AvgLoss => ("平均亏损", "Avg loss"),
+            Label::ProfitLossRatio => ("盈亏比", "Profit/loss ratio"),
+            Label::SharpeRatio => ("夏普比率", "Sharpe ratio"),
+            Label::SortinoRatio => ("索提诺比率", "Sortino ratio"),
+            Label::AnnualizationDays => ("年化天数", "Annualization days"),
+            Label::MaxDrawdown => ("最大回撤", "Max drawdown"),
+            Label::MaxDrawdownDays => ("最长回撤天数", "Longest drawdown days"),
+            Label::AvgDailyVolume => ("平均每日交易量", "Avg daily volume"),
+            Label::AvgDailyProfit => ("平均每日利润", "Avg daily profit"),
+            Label::EndingEquity => ("期末权益", "Ending equity"),
+            Label::InitialBalance => ("初始资金", "Initial balance"),
+            Label::BestHour => ("利润最高时段", "Most profitable hour"),
+            Label::TopAsset => ("币种统计首位", "Top asset"),
+            Label::FilledTrades => ("有成交信息的交易数", "Trades with fill data"),
+            Label::TotalFees => ("总手续费", "Total fees"),
+            Label::UnconvertedFeeTrades => ("手续费无法折算的交易数", "Trades with unconverted fees"),
+            Label::GrossProfit => ("扣费前利润", "Gross profit"),
+            Label::NetProfit => ("净利润", "Net profit"),
+            Label::FeeRatio => ("手续费占扣费前利润", "Fees of gross profit"),
+            Label::AvgBuySlippage => ("买入平均滑点", "Avg buy slippage"),
+            Label::AvgSellSlippage => ("卖出平均滑点", "Avg sell slippage"),
+            Label::FlippedTrades => ("因手续费和滑点转为亏损的交易数", "Trades turned to loss by fees and slippage"),
+            Label::TakerFeeRate => ("吃单手续费率", "Taker fee rate"),
+            Label::TradeDuration => ("交易耗时", "Trade duration"),
+            Label::DurationP50 => ("耗时P50", "Duration P50"),
+            Label::DurationP90 => ("耗时P90", "Duration P90"),
+            Label::DurationP99 => ("耗时P99", "Duration P99"),
+            Label::AvgCompletedDuration => ("已完成交易平均耗时", "Avg duration of completed trades"),
+            Label::AvgFailedDuration => ("未完成交易平均耗时", "Avg duration of failed trades"),
+            Label::DurationQuartile => ("耗时分组", "Duration quartile"),
+            Label::DurationUpperBound => ("耗时上限", "Duration upper bound"),
+            Label::Rejections => ("被拒绝的机会", "Rejected opportunities"),
+            Label::Controller => ("拒绝组件", "Controller"),
+            Label::Reason => ("拒绝原因", "Reason"),
+            Label::RejectionCount => ("拒绝次数", "Rejections"),
+            Label::ForegoneProfit => ("错过的预估利润", "Foregone profit"),
+            Label::ForegoneProfitNote => (
+                "按报价全部成交估算，且同一价差连续被拒绝时重复计入，是乐观估计",
+                "estimated assuming full fills at quoted prices and counted again on every rejected round, so an optimistic upper bound",
+            ),
+            Label::AcquiredAt => ("取得时间", "Date acquired"),
+            Label::AcquiredFrom => ("最早取得时间", "Date acquired (earliest)"),
+            Label::AcquiredTo => ("最晚取得时间", "Date acquired (latest)"),
+            Label::DisposedAt => ("处置时间", "Date sold"),
+            Label::Quantity => ("数量", "Quantity"),
+            Label::Proceeds => ("处置收入", "Proceeds"),
+            Label::CostBasis => ("成本", "Cost basis"),
+            Label::Gain => ("收益", "Gain"),
+            Label::Fees => ("手续费", "Fees"),
+            Label::UnmatchedQuantity => ("无成本记录的数量", "Quantity without cost basis"),
+            Label::ArbitrageId => ("套利记录ID", "Arbitrage ID"),
+            Label::Milliseconds => ("毫秒", "ms"),
+            Label::TradesUnit => ("笔", "trades"),
+            Label::DaysUnit => ("天", "days"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_locale_labels() {
+        assert_eq!("EN".parse::<Locale>().unwrap(), Locale::En);
+        assert_eq!("zh".parse::<Locale>().unwrap(), Locale::Zh);
+        assert!("fr".parse::<Locale>().is_err());
+        assert_eq!(serde_json::to_string(&Locale::En).unwrap(), "\"en\"");
+
+        assert_eq!(Label::Profit.with_unit(Locale::Zh, "USDT"), "利润(USDT)");
+        assert_eq!(Label::Profit.with_unit(Locale::En, "USDT"), "Profit (USDT)");
+        assert_eq!(Locale::Zh.join("BTC", "利润"), "BTC利润");
+        assert_eq!(Locale::En.join("BTC", "Profit"), "BTC Profit");
+    }
+}
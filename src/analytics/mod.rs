@@ -1,19 +1,39 @@
 //! 套利绩效分析模块，提供数据分析和报告生成功能
 
-use crate::db::{DatabaseManager, TradeStats, DailyStats, AssetStats};
+use crate::db::{DatabaseManager, TradeHistoryFilter, TradeRecord, TradeStats, DailyStats, HourlyStats, AssetStats, AssetStatsQuery, AssetSortBy, AssetDailyStats, OTHER_ASSETS, StatsDeltas, StrategyStats, FeeSlippageStats, RejectionStats, hourly_buckets, hourly_profit_distribution};
+use crate::models::{ArbitrageResult, TradingMode};
 use anyhow::{Context, Result};
-use chrono::{DateTime, Utc, Duration, Local, TimeZone, NaiveDate};
+use chrono::{DateTime, Datelike, Utc, Duration, Local, LocalResult, TimeZone, NaiveDate, NaiveTime};
 use log::{debug, info, warn, error};
+use rust_decimal::prelude::ToPrimitive;
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
 use serde::{Serialize, Deserialize};
-use std::path::Path;
-use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::fs::{self, File};
+use std::str::FromStr;
 use std::io::Write;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use csv::Writer as CsvWriter;
 
+pub mod delivery;
+pub mod duration;
+pub mod html;
+pub mod journal;
+pub mod locale;
+pub mod risk_metrics;
+pub mod rolling;
+pub mod tax;
+
+pub use delivery::ReportDelivery;
+pub use duration::DurationStats;
+pub use locale::{Label, Locale};
+pub use risk_metrics::{RiskMetrics, DEFAULT_ANNUALIZATION_DAYS};
+pub use rolling::RollingPoint;
+pub use tax::TaxReport;
+
 /// 分析时间范围
+#[derive(Debug, Clone)]
 pub enum TimeRange {
     /// 今日数据
     Today,
@@ -23,6 +43,12 @@ pub enum TimeRange {
     Last7Days,
     /// 过去30天
     Last30Days,
+    /// 过去N天
+    LastNDays(u32),
+    /// 本周数据（从周一开始）
+    ThisWeek,
+    /// 上周数据（周一至周日）
+    LastWeek,
     /// 本月数据
     ThisMonth,
     /// 上月数据
@@ -33,37 +59,129 @@ pub enum TimeRange {
     Custom(DateTime<Utc>, DateTime<Utc>),
 }
 
+/// 时区内指定日期和时间对应的UTC时间
+///
+/// 夏令时回拨导致本地时间出现两次时取较早的一次；夏令时开始导致本地时间不存在时，
+/// 取之后第一个存在的本地时间（按分钟查找）。
+fn local_to_utc<Tz: TimeZone>(tz: &Tz, date: NaiveDate, time: NaiveTime) -> DateTime<Utc> {
+    let mut local = date.and_time(time);
+    // 夏令时跳变最长不超过一天
+    for _ in 0..=24 * 60 {
+        match local.and_local_timezone(tz.clone()) {
+            LocalResult::Single(datetime) => return datetime.with_timezone(&Utc),
+            LocalResult::Ambiguous(earliest, _) => return earliest.with_timezone(&Utc),
+            LocalResult::None => local += Duration::minutes(1),
+        }
+    }
+    // 不会出现连续一天不存在的本地时间，兜底按UTC处理
+    Utc.from_utc_datetime(&date.and_time(time))
+}
+
+/// 时区内指定日期的零点对应的UTC时间
+fn local_midnight<Tz: TimeZone>(tz: &Tz, date: NaiveDate) -> DateTime<Utc> {
+    local_to_utc(tz, date, NaiveTime::MIN)
+}
+
 impl TimeRange {
-    /// 获取时间范围的开始和结束时间
+    /// 根据YYYY-MM-DD格式的开始和结束日期创建自定义时间范围（按本地时区，包含结束日当天）
+    pub fn custom_from_dates(start: &str, end: &str) -> Result<Self> {
+        Self::custom_from_dates_in(&Local, start, end, Utc::now())
+    }
+
+    /// 按指定时区根据开始和结束日期创建自定义时间范围，`now` 为当前时间
+    pub fn custom_from_dates_in<Tz: TimeZone>(tz: &Tz, start: &str, end: &str, now: DateTime<Utc>) -> Result<Self> {
+        let start_date = NaiveDate::from_str(start)
+            .map_err(|_| anyhow::anyhow!("无效的开始日期格式，应为YYYY-MM-DD"))?;
+        let end_date = NaiveDate::from_str(end)
+            .map_err(|_| anyhow::anyhow!("无效的结束日期格式，应为YYYY-MM-DD"))?;
+
+        let today = now.with_timezone(tz).date_naive();
+        if start_date > end_date {
+            anyhow::bail!("开始日期 {} 晚于结束日期 {}", start_date, end_date);
+        }
+        if end_date > today {
+            anyhow::bail!("结束日期 {} 晚于今天 {}", end_date, today);
+        }
+
+        let start_datetime = local_midnight(tz, start_date);
+        let end_datetime = local_to_utc(tz, end_date, NaiveTime::from_hms_opt(23, 59, 59).unwrap());
+
+        let range = TimeRange::Custom(start_datetime, end_datetime);
+        range.validate_at(now)?;
+        Ok(range)
+    }
+
+    /// 检查时间范围是否有效：自定义范围的开始时间不晚于结束时间且不晚于当前时间，过去N天至少为1天
+    pub fn validate(&self) -> Result<()> {
+        self.validate_at(Utc::now())
+    }
+
+    fn validate_at(&self, now: DateTime<Utc>) -> Result<()> {
+        match self {
+            TimeRange::LastNDays(0) => Err(anyhow::anyhow!("过去N天的天数至少为1")),
+            TimeRange::Custom(start, end) if start > end => Err(anyhow::anyhow!(
+                "自定义时间范围的开始时间 {} 晚于结束时间 {}",
+                start.to_rfc3339(),
+                end.to_rfc3339()
+            )),
+            TimeRange::Custom(start, _) if *start > now => Err(anyhow::anyhow!(
+                "自定义时间范围的开始时间 {} 晚于当前时间 {}",
+                start.to_rfc3339(),
+                now.to_rfc3339()
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// 时间范围的英文标识，用于命令行参数和导出文件名
+    pub fn key(&self) -> String {
+        match self {
+            TimeRange::Today => "today".to_string(),
+            TimeRange::Yesterday => "yesterday".to_string(),
+            TimeRange::Last7Days => "last7days".to_string(),
+            TimeRange::Last30Days => "last30days".to_string(),
+            TimeRange::LastNDays(days) => format!("last{}days", days),
+            TimeRange::ThisWeek => "thisweek".to_string(),
+            TimeRange::LastWeek => "lastweek".to_string(),
+            TimeRange::ThisMonth => "thismonth".to_string(),
+            TimeRange::LastMonth => "lastmonth".to_string(),
+            TimeRange::AllTime => "alltime".to_string(),
+            TimeRange::Custom(start, end) => {
+                format!("custom_{}_{}", start.format("%Y%m%d"), end.format("%Y%m%d"))
+            },
+        }
+    }
+
+    /// 获取时间范围的开始和结束时间（按本地时区）
     pub fn get_date_range(&self) -> (Option<DateTime<Utc>>, Option<DateTime<Utc>>) {
-        let now = Utc::now();
-        let today = Local::now().date_naive();
+        self.date_range_in(&Local, Utc::now())
+    }
+
+    /// 按指定时区获取时间范围的开始和结束时间，`now` 为当前时间
+    pub fn date_range_in<Tz: TimeZone>(&self, tz: &Tz, now: DateTime<Utc>) -> (Option<DateTime<Utc>>, Option<DateTime<Utc>>) {
+        let today = now.with_timezone(tz).date_naive();
         
         match self {
-            TimeRange::Today => {
-                let start = Local.from_local_date(&today).unwrap().and_hms_opt(0, 0, 0).unwrap().with_timezone(&Utc);
-                (Some(start), None)
-            },
+            TimeRange::Today => (Some(local_midnight(tz, today)), None),
             TimeRange::Yesterday => {
                 let yesterday = today.pred_opt().unwrap();
-                let start = Local.from_local_date(&yesterday).unwrap().and_hms_opt(0, 0, 0).unwrap().with_timezone(&Utc);
-                let end = Local.from_local_date(&today).unwrap().and_hms_opt(0, 0, 0).unwrap().with_timezone(&Utc);
-                (Some(start), Some(end))
+                (Some(local_midnight(tz, yesterday)), Some(local_midnight(tz, today)))
             },
-            TimeRange::Last7Days => {
-                let start = now - Duration::days(7);
-                (Some(start), None)
+            TimeRange::Last7Days => (Some(now - Duration::days(7)), None),
+            TimeRange::Last30Days => (Some(now - Duration::days(30)), None),
+            TimeRange::LastNDays(days) => (Some(now - Duration::days(*days as i64)), None),
+            TimeRange::ThisWeek => {
+                let monday = today - Duration::days(today.weekday().num_days_from_monday() as i64);
+                (Some(local_midnight(tz, monday)), None)
             },
-            TimeRange::Last30Days => {
-                let start = now - Duration::days(30);
-                (Some(start), None)
+            TimeRange::LastWeek => {
+                let monday = today - Duration::days(today.weekday().num_days_from_monday() as i64);
+                let prev_monday = monday - Duration::days(7);
+                (Some(local_midnight(tz, prev_monday)), Some(local_midnight(tz, monday)))
             },
             TimeRange::ThisMonth => {
-                let current_month = today.month();
-                let current_year = today.year();
-                let first_day = NaiveDate::from_ymd_opt(current_year, current_month, 1).unwrap();
-                let start = Local.from_local_date(&first_day).unwrap().and_hms_opt(0, 0, 0).unwrap().with_timezone(&Utc);
-                (Some(start), None)
+                let first_day = NaiveDate::from_ymd_opt(today.year(), today.month(), 1).unwrap();
+                (Some(local_midnight(tz, first_day)), None)
             },
             TimeRange::LastMonth => {
                 let current_month = today.month();
@@ -76,38 +194,134 @@ impl TimeRange {
                 };
                 
                 let first_day_prev = NaiveDate::from_ymd_opt(prev_year, prev_month, 1).unwrap();
-                let start = Local.from_local_date(&first_day_prev).unwrap().and_hms_opt(0, 0, 0).unwrap().with_timezone(&Utc);
-                
                 let first_day_current = NaiveDate::from_ymd_opt(current_year, current_month, 1).unwrap();
-                let end = Local.from_local_date(&first_day_current).unwrap().and_hms_opt(0, 0, 0).unwrap().with_timezone(&Utc);
                 
-                (Some(start), Some(end))
+                (Some(local_midnight(tz, first_day_prev)), Some(local_midnight(tz, first_day_current)))
             },
             TimeRange::AllTime => (None, None),
             TimeRange::Custom(start, end) => (Some(*start), Some(*end)),
         }
     }
     
-    /// 获取时间范围的描述
+    /// 获取时间范围的中文描述
     pub fn description(&self) -> String {
+        self.description_in(Locale::Zh)
+    }
+    
+    /// 获取时间范围在指定语言下的描述
+    pub fn description_in(&self, locale: Locale) -> String {
+        let last_days = |days: u32| {
+            locale.join(&locale.join(Label::Past.text(locale), &days.to_string()), Label::DaysUnit.text(locale))
+        };
         match self {
-            TimeRange::Today => "今日".to_string(),
-            TimeRange::Yesterday => "昨日".to_string(),
-            TimeRange::Last7Days => "过去7天".to_string(),
-            TimeRange::Last30Days => "过去30天".to_string(),
-            TimeRange::ThisMonth => "本月".to_string(),
-            TimeRange::LastMonth => "上月".to_string(),
-            TimeRange::AllTime => "全部历史".to_string(),
+            TimeRange::Today => Label::Today.text(locale).to_string(),
+            TimeRange::Yesterday => Label::Yesterday.text(locale).to_string(),
+            TimeRange::Last7Days => last_days(7),
+            TimeRange::Last30Days => last_days(30),
+            TimeRange::LastNDays(days) => last_days(*days),
+            TimeRange::ThisWeek => Label::ThisWeek.text(locale).to_string(),
+            TimeRange::LastWeek => Label::LastWeek.text(locale).to_string(),
+            TimeRange::ThisMonth => Label::ThisMonth.text(locale).to_string(),
+            TimeRange::LastMonth => Label::LastMonth.text(locale).to_string(),
+            TimeRange::AllTime => Label::AllTime.text(locale).to_string(),
             TimeRange::Custom(start, end) => {
-                format!("{}至{}", 
-                    start.format("%Y-%m-%d"),
-                    end.format("%Y-%m-%d")
-                )
+                let start = start.format("%Y-%m-%d").to_string();
+                let end = end.format("%Y-%m-%d").to_string();
+                locale.join(&locale.join(&start, Label::To.text(locale)), &end)
             },
         }
     }
 }
 
+impl FromStr for TimeRange {
+    type Err = anyhow::Error;
+
+    /// 解析预设时间范围名称（包括 `lastNdays`，如 `last90days`），自定义范围请使用 `TimeRange::custom_from_dates`
+    fn from_str(s: &str) -> Result<Self> {
+        let name = s.trim().to_lowercase();
+        match name.as_str() {
+            "today" => Ok(TimeRange::Today),
+            "yesterday" => Ok(TimeRange::Yesterday),
+            "last7days" => Ok(TimeRange::Last7Days),
+            "last30days" => Ok(TimeRange::Last30Days),
+            "thisweek" => Ok(TimeRange::ThisWeek),
+            "lastweek" => Ok(TimeRange::LastWeek),
+            "thismonth" => Ok(TimeRange::ThisMonth),
+            "lastmonth" => Ok(TimeRange::LastMonth),
+            "alltime" => Ok(TimeRange::AllTime),
+            _ => {
+                let days = name
+                    .strip_prefix("last")
+                    .and_then(|rest| rest.strip_suffix("days"))
+                    .and_then(|days| days.parse::<u32>().ok())
+                    .ok_or_else(|| anyhow::anyhow!("无效的时间范围: {}", s))?;
+                let range = TimeRange::LastNDays(days);
+                range.validate()?;
+                Ok(range)
+            }
+        }
+    }
+}
+
+/// 报告导出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// 单个JSON文件
+    Json,
+    /// 包含多个CSV文件的目录
+    Csv,
+    /// 内嵌图表的单个HTML文件
+    Html,
+    /// 资产管理工具可以导入的交易日志CSV，每笔套利的买入和卖出各一行，见 `journal` 模块
+    Journal,
+}
+
+impl FromStr for ExportFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "json" => Ok(ExportFormat::Json),
+            "csv" => Ok(ExportFormat::Csv),
+            "html" => Ok(ExportFormat::Html),
+            "journal" => Ok(ExportFormat::Journal),
+            _ => Err(anyhow::anyhow!("不支持的导出格式: {}", s)),
+        }
+    }
+}
+
+/// 假设手续费率下的净利润
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FeeScenario {
+    /// 假设的吃单手续费率（小数形式，0.001 表示 0.1%）
+    pub taker_fee_rate: Decimal,
+    /// 按该费率重新计算的总手续费
+    pub total_fees: Decimal,
+    /// 扣除手续费后的总净利润
+    pub net_profit: Decimal,
+}
+
+impl FeeScenario {
+    /// 根据交易记录计算指定费率下的净利润，买卖两腿按成交名义金额分别收取手续费
+    pub fn from_trades(taker_fee_rate: Decimal, trades: &[ArbitrageResult]) -> Self {
+        let mut gross_profit = Decimal::ZERO;
+        let mut notional = Decimal::ZERO;
+
+        for trade in trades {
+            gross_profit += trade.profit;
+            notional += trade.trade_amount * (trade.buy_price + trade.sell_price);
+        }
+
+        let total_fees = notional * taker_fee_rate;
+
+        Self {
+            taker_fee_rate,
+            total_fees,
+            net_profit: gross_profit - total_fees,
+        }
+    }
+}
+
 /// 绩效报告
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PerformanceReport {
@@ -115,18 +329,51 @@ pub struct PerformanceReport {
     pub title: String,
     /// 时间范围描述
     pub time_range: String,
+    /// 只统计该运行模式的交易，为None时统计全部交易
+    #[serde(default)]
+    pub mode: Option<TradingMode>,
     /// 生成时间
     pub generated_at: DateTime<Utc>,
     /// 总体统计
     pub overview: TradeStats,
     /// 每日统计
     pub daily_stats: Vec<DailyStats>,
+    /// 按一天中的小时汇总的利润，固定24个桶
+    #[serde(default)]
+    pub hourly_stats: Vec<HourlyStats>,
+    /// 按统计时区的一天中的小时汇总的已实现利润 (小时, 利润)，固定24个
+    #[serde(default)]
+    pub hourly_profit: Vec<(u8, Decimal)>,
     /// 币种统计
     pub asset_stats: Vec<AssetStats>,
+    /// 按日期和币种汇总的统计，只保留交易量最大的币种，其余合并为 `other`
+    #[serde(default)]
+    pub asset_daily: Vec<AssetDailyStats>,
+    /// 按发现机会的策略汇总的统计
+    #[serde(default)]
+    pub strategy_stats: Vec<StrategyStats>,
+    /// 手续费和滑点分析，范围内没有成交信息时为None
+    #[serde(default)]
+    pub fee_slippage: Option<FeeSlippageStats>,
+    /// 交易耗时的分位数、按结果的平均耗时和按耗时四分位的平均利润，范围内没有交易时为None
+    #[serde(default)]
+    pub duration_stats: Option<DurationStats>,
+    /// 被风控或余额预留拒绝的机会统计，错过的利润是乐观估计
+    #[serde(default)]
+    pub rejections: RejectionStats,
     /// 成功率 (百分比)
     pub success_rate: f64,
-    /// 盈亏比
+    /// 盈亏比（盈利交易平均利润 / 亏损交易平均亏损）
     pub profit_loss_ratio: f64,
+    /// 基于每日盈亏的夏普、索提诺比率和最大回撤
+    #[serde(default)]
+    pub risk_metrics: RiskMetrics,
+    /// 补齐缺失日期后每天的7日滚动统计和权益曲线
+    #[serde(default)]
+    pub rolling: Vec<RollingPoint>,
+    /// 权益曲线的初始资金
+    #[serde(default)]
+    pub initial_balance: Decimal,
     /// 日均交易量
     pub avg_daily_volume: Decimal,
     /// 日均利润
@@ -135,38 +382,254 @@ pub struct PerformanceReport {
     pub best_day: Option<DailyStats>,
     /// 最差交易日
     pub worst_day: Option<DailyStats>,
+    /// 不同手续费率下的净利润对比
+    #[serde(default)]
+    pub fee_scenarios: Vec<FeeScenario>,
 }
 
 /// 分析管理器
 pub struct AnalyticsManager {
     db: DatabaseManager,
+    fee_scenarios: Vec<Decimal>,
+    top_assets: i32,
+    asset_filter: Option<String>,
+    asset_sort: AssetSortBy,
+    mode: Option<TradingMode>,
+    annualization_days: u32,
+    initial_balance: Decimal,
+    locale: Locale,
 }
 
 impl AnalyticsManager {
     /// 创建新的分析管理器
     pub fn new(db: DatabaseManager) -> Self {
-        Self { db }
+        Self {
+            db,
+            fee_scenarios: Vec::new(),
+            top_assets: 10,
+            asset_filter: None,
+            asset_sort: AssetSortBy::default(),
+            mode: None,
+            annualization_days: DEFAULT_ANNUALIZATION_DAYS,
+            initial_balance: Decimal::ZERO,
+            locale: Locale::default(),
+        }
+    }
+
+    /// 设置报告中对比的吃单手续费率（小数形式），为空时不生成手续费对比
+    pub fn with_fee_scenarios(mut self, fee_rates: Vec<Decimal>) -> Self {
+        self.fee_scenarios = fee_rates;
+        self
+    }
+
+    /// 设置报告中币种统计的数量、币种过滤和排序方式
+    pub fn with_asset_stats(mut self, limit: i32, asset: Option<String>, sort_by: AssetSortBy) -> Self {
+        self.top_assets = limit;
+        self.asset_filter = asset;
+        self.asset_sort = sort_by;
+        self
+    }
+
+    /// 只统计指定运行模式的交易，例如只看实盘交易、排除模拟交易
+    pub fn with_mode(mut self, mode: Option<TradingMode>) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// 设置夏普和索提诺比率的年化天数，默认365天
+    pub fn with_annualization_days(mut self, days: u32) -> Self {
+        self.annualization_days = days.max(1);
+        self
+    }
+
+    /// 设置权益曲线的初始资金，默认为0即只显示累计利润
+    pub fn with_initial_balance(mut self, initial_balance: Decimal) -> Self {
+        self.initial_balance = initial_balance;
+        self
+    }
+
+    /// 设置报告标题、时间范围描述和CSV表头的语言，默认中文
+    pub fn with_locale(mut self, locale: Locale) -> Self {
+        self.locale = locale;
+        self
+    }
+
+    /// 报告使用的语言
+    pub fn locale(&self) -> Locale {
+        self.locale
+    }
+
+    /// 获取时间范围内的全部交易记录，设置了运行模式时只包含该模式的交易
+    async fn load_trades(&self, range: &TimeRange) -> Result<Vec<TradeRecord>> {
+        let (start_date, end_date) = range.get_date_range();
+        self.load_trades_between(start_date, end_date, self.mode).await
+    }
+
+    /// 按游标分页加载开始时间在 `[start_date, end_date)` 范围内、指定运行模式的全部交易记录
+    async fn load_trades_between(
+        &self,
+        start_date: Option<DateTime<Utc>>,
+        end_date: Option<DateTime<Utc>>,
+        mode: Option<TradingMode>,
+    ) -> Result<Vec<TradeRecord>> {
+        const PAGE_SIZE: i64 = 1000;
+
+        let filter = TradeHistoryFilter {
+            mode,
+            ..TradeHistoryFilter::between(start_date, end_date)
+        };
+        let mut trades = Vec::new();
+        let mut cursor = None;
+
+        loop {
+            let page = self.db.get_trade_history_page(&filter, PAGE_SIZE, cursor.as_ref()).await?;
+            trades.extend(page.items);
+
+            match page.next_cursor {
+                Some(next) => cursor = Some(next),
+                None => break,
+            }
+        }
+
+        Ok(trades)
+    }
+    
+    /// 由交易记录计算总体、每日、按小时和币种统计，结果与存储后端的统计查询一致
+    fn aggregate_trades(&self, trades: &[TradeRecord]) -> (TradeStats, Vec<DailyStats>, Vec<HourlyStats>, Vec<AssetStats>) {
+        let mut overview = TradeStats {
+            total_trades: 0,
+            successful_trades: 0,
+            failed_trades: 0,
+            total_profit: Decimal::ZERO,
+            total_volume: Decimal::ZERO,
+            avg_profit_per_trade: Decimal::ZERO,
+            max_profit: Decimal::ZERO,
+            max_loss: Decimal::ZERO,
+            avg_trade_duration_ms: 0,
+            winning_trades: 0,
+            losing_trades: 0,
+            avg_win: Decimal::ZERO,
+            avg_loss: Decimal::ZERO,
+        };
+        let mut deltas = StatsDeltas::default();
+        let offset = self.db.stats_offset();
+        let mut total_duration_ms: i64 = 0;
+        let mut total_win = Decimal::ZERO;
+        let mut total_loss = Decimal::ZERO;
+        
+        for (index, record) in trades.iter().enumerate() {
+            let result = &record.result;
+            deltas.add_result(result, offset);
+            
+            overview.total_trades += 1;
+            overview.total_profit += result.profit;
+            overview.total_volume += result.trade_amount;
+            if index == 0 {
+                overview.max_profit = result.profit;
+                overview.max_loss = result.profit;
+            } else {
+                overview.max_profit = overview.max_profit.max(result.profit);
+                overview.max_loss = overview.max_loss.min(result.profit);
+            }
+            total_duration_ms += record.duration_ms;
+            if result.profit > Decimal::ZERO {
+                overview.winning_trades += 1;
+                total_win += result.profit;
+            } else if result.profit < Decimal::ZERO {
+                overview.losing_trades += 1;
+                total_loss += result.profit;
+            }
+        }
+        if overview.total_trades > 0 {
+            overview.successful_trades = deltas.daily.values().map(|delta| delta.successful_trades).sum();
+            overview.failed_trades = overview.total_trades - overview.successful_trades;
+            overview.avg_profit_per_trade = overview.total_profit / Decimal::from(overview.total_trades);
+            overview.avg_trade_duration_ms = total_duration_ms / overview.total_trades;
+        }
+        if overview.winning_trades > 0 {
+            overview.avg_win = total_win / Decimal::from(overview.winning_trades);
+        }
+        if overview.losing_trades > 0 {
+            overview.avg_loss = total_loss / Decimal::from(overview.losing_trades);
+        }
+        
+        let daily_stats = deltas
+            .daily
+            .iter()
+            .map(|(date, delta)| DailyStats {
+                date: date.clone(),
+                trades: delta.trades,
+                profit: delta.profit,
+                volume: delta.volume,
+                successful_rate: delta.successful_trades as f64 / delta.trades as f64 * 100.0,
+            })
+            .collect();
+        
+        let mut hours: BTreeMap<u32, (i64, i64, Decimal, Decimal)> = BTreeMap::new();
+        for ((_, hour), delta) in &deltas.hourly {
+            let bucket = hours.entry(*hour).or_insert((0, 0, Decimal::ZERO, Decimal::ZERO));
+            bucket.0 += delta.trades;
+            bucket.1 += delta.successful_trades;
+            bucket.2 += delta.profit;
+            bucket.3 += delta.volume;
+        }
+        let hourly_stats = hourly_buckets(&hours);
+        
+        let mut asset_stats: Vec<AssetStats> = deltas
+            .assets
+            .into_iter()
+            .filter(|(asset, _)| self.asset_filter.as_ref().is_none_or(|filter| filter == asset))
+            .map(|(asset, delta)| AssetStats::from_totals(asset, delta.trades, delta.successful_trades, delta.profit, delta.volume))
+            .collect();
+        self.asset_sort.sort(&mut asset_stats);
+        asset_stats.truncate(self.top_assets.max(0) as usize);
+        
+        (overview, daily_stats, hourly_stats, asset_stats)
     }
     
     /// 生成绩效分析报告
     pub async fn generate_report(&self, range: TimeRange) -> Result<PerformanceReport> {
+        range.validate()?;
         let (start_date, end_date) = range.get_date_range();
         
-        // 获取总体统计
-        let overview = self.db.get_overall_stats().await?;
-        
-        // 计算每日统计
-        let daily_length = match &range {
-            TimeRange::Last7Days => 7,
-            TimeRange::Last30Days => 30,
-            TimeRange::ThisMonth | TimeRange::LastMonth => 31,
-            _ => 30, // 默认显示30天数据
+        // 所有统计均只包含该时间范围内的交易，AllTime不限制范围。
+        // 存储后端的统计查询不区分运行模式，指定模式时按该模式的交易记录重新统计
+        let mode_trades = match self.mode {
+            Some(_) => Some(self.load_trades(&range).await?),
+            None => None,
+        };
+        let (overview, daily_stats, hourly_stats, asset_stats) = match &mode_trades {
+            Some(trades) => self.aggregate_trades(trades),
+            None => (
+                self.db.get_overall_stats_between(start_date, end_date).await?,
+                self.db.get_daily_stats_between(start_date, end_date).await?,
+                self.db.get_hourly_stats_between(start_date, end_date).await?,
+                self.db.query_asset_stats(&AssetStatsQuery {
+                    limit: self.top_assets,
+                    start: start_date,
+                    end: end_date,
+                    asset: self.asset_filter.clone(),
+                    sort_by: self.asset_sort,
+                }).await?,
+            ),
         };
         
-        let daily_stats = self.db.get_daily_stats(daily_length).await?;
-        
-        // 获取币种统计
-        let asset_stats = self.db.get_asset_stats(10).await?;
+        let top_assets = self.top_assets.max(0) as usize;
+        let asset_daily = match &mode_trades {
+            Some(trades) => AssetDailyStats::limit_assets(
+                AssetDailyStats::from_trades(trades, self.db.stats_offset()),
+                top_assets,
+            ),
+            None => self.db.get_asset_daily_stats(start_date, end_date, top_assets).await?,
+        };
+        let strategy_stats = match &mode_trades {
+            Some(trades) => StrategyStats::from_trades(trades),
+            None => self.db.get_strategy_stats(start_date, end_date).await?,
+        };
+        let fee_slippage = match &mode_trades {
+            Some(trades) => FeeSlippageStats::from_trades(trades),
+            None => self.db.get_fee_slippage_stats(start_date, end_date).await?,
+        };
         
         // 计算成功率
         let success_rate = if overview.total_trades > 0 {
@@ -176,12 +639,16 @@ impl AnalyticsManager {
         };
         
         // 计算盈亏比 (平均盈利 / 平均亏损)
-        let profit_loss_ratio = if overview.max_loss.abs() > dec!(0) {
-            (overview.max_profit / overview.max_loss.abs()).to_f64().unwrap_or(0.0)
+        let profit_loss_ratio = if overview.avg_loss.abs() > dec!(0) {
+            (overview.avg_win / overview.avg_loss.abs()).to_f64().unwrap_or(0.0)
         } else {
             0.0
         };
         
+        let risk_metrics = RiskMetrics::from_daily_stats(&daily_stats, self.annualization_days);
+        let rolling = rolling::rolling_series(&daily_stats, self.initial_balance);
+        let hourly_profit = hourly_profit_distribution(&hourly_stats);
+        
         // 找出最佳和最差交易日
         let mut best_day = None;
         let mut worst_day = None;
@@ -219,28 +686,65 @@ impl AnalyticsManager {
             Decimal::ZERO
         };
         
+        // 耗时分布需要每笔交易的耗时，未指定模式时在这里加载交易记录
+        let trades = match mode_trades {
+            Some(trades) => trades,
+            None => self.load_trades(&range).await?,
+        };
+        let duration_stats = DurationStats::from_trades(&trades);
+        let rejections = self.db.get_rejection_stats(start_date, end_date, self.mode).await?;
+        
+        // 按不同手续费率重新计算净利润
+        let fee_scenarios = if self.fee_scenarios.is_empty() {
+            Vec::new()
+        } else {
+            let trades: Vec<ArbitrageResult> = trades.into_iter().map(|record| record.result).collect();
+            self.fee_scenarios
+                .iter()
+                .map(|rate| FeeScenario::from_trades(*rate, &trades))
+                .collect()
+        };
+        
         Ok(PerformanceReport {
-            title: format!("套利交易绩效报告 - {}", range.description()),
-            time_range: range.description(),
+            title: format!("{} - {}", Label::ReportTitle.text(self.locale), range.description_in(self.locale)),
+            time_range: range.description_in(self.locale),
+            mode: self.mode,
             generated_at: Utc::now(),
             overview,
             daily_stats,
+            hourly_stats,
+            hourly_profit,
             asset_stats,
+            asset_daily,
+            strategy_stats,
+            fee_slippage,
+            duration_stats,
+            rejections,
             success_rate,
             profit_loss_ratio,
+            risk_metrics,
+            rolling,
+            initial_balance: self.initial_balance,
             avg_daily_volume,
             avg_daily_profit,
             best_day,
             worst_day,
+            fee_scenarios,
         })
     }
     
     /// 将报告导出为CSV格式
     pub async fn export_report_to_csv(&self, report: &PerformanceReport, path: &Path) -> Result<()> {
+        let locale = self.locale;
+        let text = |label: Label| label.text(locale).to_string();
+        let usdt = |label: Label| label.with_unit(locale, "USDT");
+        let percent = |label: Label| label.with_unit(locale, "%");
+        let ms = |label: Label| label.with_unit(locale, Label::Milliseconds.text(locale));
+        
         let mut daily_writer = CsvWriter::from_path(path.join("daily_stats.csv"))?;
         
         // 写入表头
-        daily_writer.write_record(&["日期", "交易数量", "利润(USDT)", "交易量(USDT)", "成功率(%)"])?;
+        daily_writer.write_record(&[text(Label::Date), text(Label::Trades), usdt(Label::Profit), usdt(Label::Volume), percent(Label::SuccessRate)])?;
         
         // 写入每日数据
         for stats in &report.daily_stats {
@@ -254,9 +758,54 @@ impl AnalyticsManager {
         }
         daily_writer.flush()?;
         
+        // 写入按小时统计
+        let mut hourly_writer = CsvWriter::from_path(path.join("hourly_stats.csv"))?;
+        hourly_writer.write_record(&[text(Label::Hour), text(Label::Trades), usdt(Label::Profit), usdt(Label::Volume), percent(Label::SuccessRate)])?;
+        
+        for stats in &report.hourly_stats {
+            hourly_writer.write_record(&[
+                &format!("{:02}", stats.hour),
+                &stats.trades.to_string(),
+                &stats.profit.to_string(),
+                &stats.volume.to_string(),
+                &format!("{:.2}", stats.successful_rate),
+            ])?;
+        }
+        hourly_writer.flush()?;
+        
+        // 写入滚动统计和权益曲线
+        let mut equity_writer = CsvWriter::from_path(path.join("equity_curve.csv"))?;
+        equity_writer.write_record(&[
+            text(Label::Date),
+            text(Label::Trades),
+            usdt(Label::Profit),
+            usdt(Label::RollingProfit),
+            percent(Label::RollingSuccessRate),
+            usdt(Label::Equity),
+        ])?;
+        
+        for point in &report.rolling {
+            equity_writer.write_record(&[
+                &point.date,
+                &point.trades.to_string(),
+                &point.profit.to_string(),
+                &point.rolling_profit.to_string(),
+                &point.rolling_success_rate.map(|rate| format!("{:.2}", rate)).unwrap_or_default(),
+                &point.equity.to_string(),
+            ])?;
+        }
+        equity_writer.flush()?;
+        
         // 写入币种统计
         let mut asset_writer = CsvWriter::from_path(path.join("asset_stats.csv"))?;
-        asset_writer.write_record(&["币种", "交易数量", "总利润(USDT)", "总交易量(USDT)", "平均每笔利润(USDT)"])?;
+        asset_writer.write_record(&[
+            text(Label::Asset),
+            text(Label::Trades),
+            usdt(Label::TotalProfit),
+            usdt(Label::TotalVolume),
+            usdt(Label::AvgProfitPerTrade),
+            percent(Label::SuccessRate),
+        ])?;
         
         for stats in &report.asset_stats {
             asset_writer.write_record(&[
@@ -265,34 +814,235 @@ impl AnalyticsManager {
                 &stats.profit.to_string(),
                 &stats.volume.to_string(),
                 &stats.avg_profit.to_string(),
+                &format!("{:.2}", stats.successful_rate),
             ])?;
         }
         asset_writer.flush()?;
         
+        // 写入按币种的每日利润，每个币种一列，按交易量排名排列，`other` 在最后
+        let mut assets = AssetDailyStats::asset_ranking(&report.asset_daily);
+        if let Some(index) = assets.iter().position(|asset| asset == OTHER_ASSETS) {
+            let other = assets.remove(index);
+            assets.push(other);
+        }
+        let mut profits: BTreeMap<&str, HashMap<&str, Decimal>> = BTreeMap::new();
+        for stats in &report.asset_daily {
+            profits.entry(stats.date.as_str()).or_default().insert(stats.asset.as_str(), stats.profit);
+        }
+        
+        let mut asset_daily_writer = CsvWriter::from_path(path.join("asset_daily.csv"))?;
+        let mut header = vec![text(Label::Date)];
+        header.extend(assets.iter().map(|asset| locale.join(asset, &usdt(Label::Profit))));
+        asset_daily_writer.write_record(&header)?;
+        
+        for (date, day) in &profits {
+            let mut record = vec![date.to_string()];
+            record.extend(assets.iter().map(|asset| {
+                day.get(asset.as_str()).copied().unwrap_or(Decimal::ZERO).to_string()
+            }));
+            asset_daily_writer.write_record(&record)?;
+        }
+        asset_daily_writer.flush()?;
+        
+        // 写入策略统计
+        let mut strategy_writer = CsvWriter::from_path(path.join("strategy_stats.csv"))?;
+        strategy_writer.write_record(&[
+            text(Label::Strategy),
+            text(Label::Trades),
+            percent(Label::SuccessRate),
+            usdt(Label::TotalProfit),
+            usdt(Label::AvgProfitPerTrade),
+            percent(Label::AvgSlippage),
+        ])?;
+        
+        for stats in &report.strategy_stats {
+            strategy_writer.write_record(&[
+                &stats.strategy,
+                &stats.trades.to_string(),
+                &format!("{:.2}", stats.successful_rate),
+                &stats.total_profit.to_string(),
+                &stats.avg_profit.to_string(),
+                &stats.avg_slippage_percentage.map(|s| s.round_dp(6).to_string()).unwrap_or_default(),
+            ])?;
+        }
+        strategy_writer.flush()?;
+        
         // 写入总体统计
         let mut overview_writer = CsvWriter::from_path(path.join("overview.csv"))?;
-        overview_writer.write_record(&["统计指标", "数值"])?;
-        
-        overview_writer.write_record(&["总交易次数", &report.overview.total_trades.to_string()])?;
-        overview_writer.write_record(&["成功交易次数", &report.overview.successful_trades.to_string()])?;
-        overview_writer.write_record(&["失败交易次数", &report.overview.failed_trades.to_string()])?;
-        overview_writer.write_record(&["总利润(USDT)", &report.overview.total_profit.to_string()])?;
-        overview_writer.write_record(&["总交易量(USDT)", &report.overview.total_volume.to_string()])?;
-        overview_writer.write_record(&["平均每笔利润(USDT)", &report.overview.avg_profit_per_trade.to_string()])?;
-        overview_writer.write_record(&["最大单笔利润(USDT)", &report.overview.max_profit.to_string()])?;
-        overview_writer.write_record(&["最大单笔亏损(USDT)", &report.overview.max_loss.to_string()])?;
-        overview_writer.write_record(&["成功率(%)", &format!("{:.2}", report.success_rate)])?;
-        overview_writer.write_record(&["盈亏比", &format!("{:.2}", report.profit_loss_ratio)])?;
-        overview_writer.write_record(&["平均每日交易量(USDT)", &report.avg_daily_volume.to_string()])?;
-        overview_writer.write_record(&["平均每日利润(USDT)", &report.avg_daily_profit.to_string()])?;
+        overview_writer.write_record(&[text(Label::Metric), text(Label::Value)])?;
+        
+        overview_writer.write_record(&[text(Label::TotalTrades), report.overview.total_trades.to_string()])?;
+        overview_writer.write_record(&[text(Label::SuccessfulTrades), report.overview.successful_trades.to_string()])?;
+        overview_writer.write_record(&[text(Label::FailedTrades), report.overview.failed_trades.to_string()])?;
+        overview_writer.write_record(&[usdt(Label::TotalProfit), report.overview.total_profit.to_string()])?;
+        overview_writer.write_record(&[usdt(Label::TotalVolume), report.overview.total_volume.to_string()])?;
+        overview_writer.write_record(&[usdt(Label::AvgProfitPerTrade), report.overview.avg_profit_per_trade.to_string()])?;
+        overview_writer.write_record(&[usdt(Label::MaxProfit), report.overview.max_profit.to_string()])?;
+        overview_writer.write_record(&[usdt(Label::MaxLoss), report.overview.max_loss.to_string()])?;
+        overview_writer.write_record(&[text(Label::WinningTrades), report.overview.winning_trades.to_string()])?;
+        overview_writer.write_record(&[text(Label::LosingTrades), report.overview.losing_trades.to_string()])?;
+        overview_writer.write_record(&[usdt(Label::AvgWin), report.overview.avg_win.to_string()])?;
+        overview_writer.write_record(&[usdt(Label::AvgLoss), report.overview.avg_loss.to_string()])?;
+        overview_writer.write_record(&[percent(Label::SuccessRate), format!("{:.2}", report.success_rate)])?;
+        overview_writer.write_record(&[text(Label::ProfitLossRatio), format!("{:.2}", report.profit_loss_ratio)])?;
+        overview_writer.write_record(&[text(Label::SharpeRatio), format!("{:.4}", report.risk_metrics.sharpe_ratio)])?;
+        overview_writer.write_record(&[text(Label::SortinoRatio), format!("{:.4}", report.risk_metrics.sortino_ratio)])?;
+        overview_writer.write_record(&[text(Label::AnnualizationDays), report.risk_metrics.annualization_days.to_string()])?;
+        overview_writer.write_record(&[usdt(Label::MaxDrawdown), report.risk_metrics.max_drawdown.to_string()])?;
+        overview_writer.write_record(&[text(Label::MaxDrawdownDays), report.risk_metrics.max_drawdown_days.to_string()])?;
+        overview_writer.write_record(&[usdt(Label::AvgDailyVolume), report.avg_daily_volume.to_string()])?;
+        overview_writer.write_record(&[usdt(Label::AvgDailyProfit), report.avg_daily_profit.to_string()])?;
         
         overview_writer.flush()?;
         
+        // 写入手续费和滑点分析，没有成交信息时不生成
+        if let Some(stats) = &report.fee_slippage {
+            let mut fee_slippage_writer = CsvWriter::from_path(path.join("fee_slippage.csv"))?;
+            fee_slippage_writer.write_record(&[text(Label::Metric), text(Label::Value)])?;
+            fee_slippage_writer.write_record(&[text(Label::FilledTrades), stats.trades.to_string()])?;
+            fee_slippage_writer.write_record(&[usdt(Label::TotalFees), stats.total_fees.to_string()])?;
+            fee_slippage_writer.write_record(&[text(Label::UnconvertedFeeTrades), stats.unconverted_fee_trades.to_string()])?;
+            fee_slippage_writer.write_record(&[usdt(Label::GrossProfit), stats.gross_profit.to_string()])?;
+            fee_slippage_writer.write_record(&[usdt(Label::NetProfit), stats.net_profit.to_string()])?;
+            fee_slippage_writer.write_record(&[
+                percent(Label::FeeRatio),
+                stats.fee_ratio.map(|ratio| format!("{:.2}", ratio * 100.0)).unwrap_or_default(),
+            ])?;
+            fee_slippage_writer.write_record(&[Label::AvgBuySlippage.with_unit(locale, "bp"), stats.avg_buy_slippage_bps.round_dp(4).to_string()])?;
+            fee_slippage_writer.write_record(&[Label::AvgSellSlippage.with_unit(locale, "bp"), stats.avg_sell_slippage_bps.round_dp(4).to_string()])?;
+            fee_slippage_writer.write_record(&[text(Label::FlippedTrades), stats.flipped_trades.to_string()])?;
+            fee_slippage_writer.flush()?;
+        }
+        
+        // 写入耗时分布，没有交易时不生成
+        if let Some(stats) = &report.duration_stats {
+            let optional_ms = |value: Option<i64>| value.map(|ms| ms.to_string()).unwrap_or_default();
+            let mut duration_writer = CsvWriter::from_path(path.join("duration_stats.csv"))?;
+            duration_writer.write_record(&[text(Label::Metric), text(Label::Value)])?;
+            duration_writer.write_record(&[text(Label::TradeCount), stats.trades.to_string()])?;
+            duration_writer.write_record(&[ms(Label::DurationP50), stats.p50_ms.to_string()])?;
+            duration_writer.write_record(&[ms(Label::DurationP90), stats.p90_ms.to_string()])?;
+            duration_writer.write_record(&[ms(Label::DurationP99), stats.p99_ms.to_string()])?;
+            duration_writer.write_record(&[ms(Label::AvgCompletedDuration), optional_ms(stats.avg_completed_ms)])?;
+            duration_writer.write_record(&[ms(Label::AvgFailedDuration), optional_ms(stats.avg_failed_ms)])?;
+            duration_writer.flush()?;
+            
+            let mut quartile_writer = CsvWriter::from_path(path.join("duration_quartiles.csv"))?;
+            quartile_writer.write_record(&[text(Label::DurationQuartile), ms(Label::DurationUpperBound), text(Label::Trades), usdt(Label::AvgProfit)])?;
+            for quartile in &stats.quartiles {
+                quartile_writer.write_record(&[
+                    &format!("Q{}", quartile.quartile),
+                    &quartile.upper_ms.to_string(),
+                    &quartile.trades.to_string(),
+                    &quartile.avg_profit.to_string(),
+                ])?;
+            }
+            quartile_writer.flush()?;
+        }
+        
+        // 写入被拒绝机会的统计，没有被拒绝的机会时不生成
+        if report.rejections.total > 0 {
+            let mut reason_writer = CsvWriter::from_path(path.join("rejection_reasons.csv"))?;
+            reason_writer.write_record(&[text(Label::Controller), text(Label::Reason), text(Label::RejectionCount), usdt(Label::ForegoneProfit)])?;
+            for stats in &report.rejections.by_reason {
+                reason_writer.write_record(&[
+                    &stats.controller,
+                    &stats.reason,
+                    &stats.count.to_string(),
+                    &stats.foregone_profit.to_string(),
+                ])?;
+            }
+            reason_writer.flush()?;
+            
+            let mut hour_writer = CsvWriter::from_path(path.join("rejection_hours.csv"))?;
+            hour_writer.write_record(&[text(Label::Hour), text(Label::RejectionCount), usdt(Label::ForegoneProfit)])?;
+            for stats in &report.rejections.hourly {
+                hour_writer.write_record(&[
+                    &stats.hour.to_string(),
+                    &stats.count.to_string(),
+                    &stats.foregone_profit.to_string(),
+                ])?;
+            }
+            hour_writer.flush()?;
+        }
+        
+        // 写入手续费对比
+        if !report.fee_scenarios.is_empty() {
+            let mut fee_writer = CsvWriter::from_path(path.join("fee_scenarios.csv"))?;
+            fee_writer.write_record(&[percent(Label::TakerFeeRate), usdt(Label::TotalFees), usdt(Label::NetProfit)])?;
+
+            for scenario in &report.fee_scenarios {
+                fee_writer.write_record(&[
+                    &(scenario.taker_fee_rate * dec!(100)).normalize().to_string(),
+                    &scenario.total_fees.to_string(),
+                    &scenario.net_profit.to_string(),
+                ])?;
+            }
+            fee_writer.flush()?;
+        }
+        
         info!("已将绩效报告导出为CSV格式: {:?}", path);
         
         Ok(())
     }
     
+    /// 按指定格式导出报告，返回生成的文件（JSON、HTML）或目录（CSV）路径
+    ///
+    /// `name` 为不含扩展名的文件名，CSV格式下作为目录名。
+    pub async fn export_report(
+        &self,
+        report: &PerformanceReport,
+        format: ExportFormat,
+        export_dir: &Path,
+        name: &str,
+    ) -> Result<PathBuf> {
+        fs::create_dir_all(export_dir)
+            .with_context(|| format!("无法创建导出目录: {:?}", export_dir))?;
+
+        match format {
+            ExportFormat::Json => {
+                let json_path = export_dir.join(format!("{}.json", name));
+                self.export_report_to_json(report, &json_path).await?;
+                Ok(json_path)
+            },
+            ExportFormat::Csv => {
+                let report_dir = export_dir.join(name);
+                fs::create_dir_all(&report_dir)?;
+                self.export_report_to_csv(report, &report_dir).await?;
+                Ok(report_dir)
+            },
+            ExportFormat::Html => {
+                let html_path = export_dir.join(format!("{}.html", name));
+                self.export_report_to_html(report, &html_path).await?;
+                Ok(html_path)
+            },
+            ExportFormat::Journal => Err(anyhow::anyhow!("交易日志不是绩效报告格式，请使用 export_journal 按时间范围导出")),
+        }
+    }
+
+    /// 一次生成多个时间范围的报告，每个范围导出到独立的文件
+    ///
+    /// 文件名格式为 `report_<范围标识>_<时间戳>`，返回各报告及其导出路径。
+    pub async fn export_reports(
+        &self,
+        ranges: &[TimeRange],
+        format: ExportFormat,
+        export_dir: &Path,
+    ) -> Result<Vec<(PerformanceReport, PathBuf)>> {
+        let timestamp = Local::now().format("%Y%m%d_%H%M%S");
+        let mut exported = Vec::with_capacity(ranges.len());
+
+        for range in ranges {
+            info!("开始生成绩效分析报告 - 时间范围: {}", range.description());
+            let report = self.generate_report(range.clone()).await?;
+            let name = format!("report_{}_{}", range.key(), timestamp);
+            let path = self.export_report(&report, format, export_dir, &name).await?;
+            exported.push((report, path));
+        }
+
+        Ok(exported)
+    }
+
     /// 将报告保存为JSON格式
     pub async fn export_report_to_json(&self, report: &PerformanceReport, path: &Path) -> Result<()> {
         let json = serde_json::to_string_pretty(report)?;
@@ -303,4 +1053,826 @@ impl AnalyticsManager {
         
         Ok(())
     }
+    
+    /// 将报告保存为单个HTML文件，包含总体统计表、每日盈亏折线图、按小时热力图和币种利润柱状图
+    pub async fn export_report_to_html(&self, report: &PerformanceReport, path: &Path) -> Result<()> {
+        fs::write(path, html::render_report(report))
+            .with_context(|| format!("无法写入HTML报告: {:?}", path))?;
+        
+        info!("已将绩效报告导出为HTML格式: {:?}", path);
+        
+        Ok(())
+    }
+    
+    /// 将时间范围内的交易按买入和卖出两条成交写成交易日志CSV，返回写入的成交条数
+    ///
+    /// 只导出设置的运行模式的交易，未设置时导出全部交易。
+    pub async fn export_journal(&self, range: &TimeRange, path: &Path) -> Result<usize> {
+        range.validate()?;
+        let trades = self.load_trades(range).await?;
+        let file = File::create(path).with_context(|| format!("无法创建交易日志: {:?}", path))?;
+        let rows = journal::write_journal(std::io::BufWriter::new(file), &trades)?;
+        
+        info!("已将 {} 笔交易的 {} 条成交导出为交易日志: {:?}", trades.len(), rows, path);
+        
+        Ok(rows)
+    }
+    
+    /// 按先进先出匹配取得和处置，导出 `year` 年的税务报告，年份按统计时区划分
+    ///
+    /// 只统计设置的运行模式的交易，未设置时只统计实盘交易。CSV格式每行一次处置，写入 `path`，
+    /// 年末结转的批次写入同目录下的 `<文件名>_open_lots.csv`；JSON格式将完整报告写入 `path`。
+    pub async fn export_tax_report(&self, year: i32, path: &Path, format: ExportFormat) -> Result<TaxReport> {
+        if matches!(format, ExportFormat::Html | ExportFormat::Journal) {
+            return Err(anyhow::anyhow!("税务报告只支持csv和json格式"));
+        }
+        
+        let offset = self.db.stats_offset();
+        let year_end = tax::year_boundary(year + 1, offset)?;
+        let mode = self.mode.or(Some(TradingMode::Live));
+        let trades = self.load_trades_between(None, Some(year_end), mode).await?;
+        let report = TaxReport::from_trades(year, &trades, offset)?;
+        
+        match format {
+            ExportFormat::Json => {
+                fs::write(path, serde_json::to_string_pretty(&report)?)
+                    .with_context(|| format!("无法写入税务报告: {:?}", path))?;
+            },
+            _ => {
+                let locale = self.locale;
+                let text = |label: Label| label.text(locale).to_string();
+                let time = |at: DateTime<Utc>| at.with_timezone(&offset).format("%Y-%m-%d %H:%M:%S").to_string();
+                let optional_time = |at: Option<DateTime<Utc>>| at.map(&time).unwrap_or_default();
+                
+                let mut writer = CsvWriter::from_path(path)
+                    .with_context(|| format!("无法创建税务报告: {:?}", path))?;
+                writer.write_record(&[
+                    text(Label::Asset),
+                    text(Label::AcquiredFrom),
+                    text(Label::AcquiredTo),
+                    text(Label::DisposedAt),
+                    text(Label::Quantity),
+                    text(Label::Proceeds),
+                    text(Label::CostBasis),
+                    text(Label::Gain),
+                    text(Label::Fees),
+                    text(Label::UnmatchedQuantity),
+                    text(Label::ArbitrageId),
+                ])?;
+                for disposal in &report.disposals {
+                    writer.write_record(&[
+                        disposal.asset.clone(),
+                        optional_time(disposal.acquired_from),
+                        optional_time(disposal.acquired_to),
+                        time(disposal.disposed_at),
+                        disposal.quantity.normalize().to_string(),
+                        disposal.proceeds.normalize().to_string(),
+                        disposal.cost_basis.normalize().to_string(),
+                        disposal.gain.normalize().to_string(),
+                        disposal.fees.normalize().to_string(),
+                        disposal.unmatched_quantity.normalize().to_string(),
+                        disposal.arbitrage_id.to_string(),
+                    ])?;
+                }
+                writer.flush()?;
+                
+                let stem = path.file_stem().and_then(|stem| stem.to_str()).unwrap_or("tax_report");
+                let lots_path = path.with_file_name(format!("{}_open_lots.csv", stem));
+                let mut lots_writer = CsvWriter::from_path(&lots_path)
+                    .with_context(|| format!("无法创建结转批次文件: {:?}", lots_path))?;
+                lots_writer.write_record(&[
+                    text(Label::Asset),
+                    text(Label::AcquiredAt),
+                    text(Label::Quantity),
+                    text(Label::CostBasis),
+                    text(Label::Fees),
+                    text(Label::ArbitrageId),
+                ])?;
+                for lot in &report.closing_lots {
+                    lots_writer.write_record(&[
+                        lot.asset.clone(),
+                        time(lot.acquired_at),
+                        lot.quantity.normalize().to_string(),
+                        lot.cost_basis.normalize().to_string(),
+                        lot.fees.normalize().to_string(),
+                        lot.arbitrage_id.to_string(),
+                    ])?;
+                }
+                lots_writer.flush()?;
+            },
+        }
+        
+        info!("已导出 {} 年税务报告: {:?}，处置 {} 笔", year, path, report.disposals.len());
+        
+        Ok(report)
+    }
+}
+
+#[cfg(all(test, feature = "db-sqlite"))]
+mod tests {
+    use super::*;
+    use crate::models::{ArbitrageStatus, FillInfo};
+
+    async fn make_manager() -> AnalyticsManager {
+        let db = DatabaseManager::new("sqlite::memory:").await.expect("创建SQLite数据库失败");
+        let now = Utc::now();
+        let result = ArbitrageResult {
+            base_asset: "BTC".to_string(),
+            buy_quote: "USDT".to_string(),
+            sell_quote: "USDC".to_string(),
+            buy_price: dec!(50000),
+            sell_price: dec!(50100),
+            trade_amount: dec!(0.1),
+            profit: dec!(10),
+            profit_percentage: dec!(0.2),
+            buy_order_id: Some(1),
+            sell_order_id: Some(2),
+            status: ArbitrageStatus::Completed,
+            timestamp: now,
+            start_time: now,
+            end_time: now + Duration::milliseconds(500),
+            buy_fill: FillInfo::default(),
+            sell_fill: FillInfo::default(),
+            mode: TradingMode::Live,
+            config_hash: String::new(),
+            strategy: None,
+            opportunity_id: None,
+        };
+        db.record_arbitrage_result(&result).await.unwrap();
+        AnalyticsManager::new(db)
+    }
+
+    fn temp_export_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("arbitrage_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_parse_export_format() {
+        assert_eq!(ExportFormat::from_str("HTML").unwrap(), ExportFormat::Html);
+        assert_eq!(ExportFormat::from_str("journal").unwrap(), ExportFormat::Journal);
+        assert!(ExportFormat::from_str("pdf").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_export_journal() {
+        let db = DatabaseManager::in_memory().await.unwrap();
+        let now = Utc::now();
+        let fill = |qty: Decimal, avg_price: Decimal, commission: Decimal, asset: &str| FillInfo {
+            qty,
+            avg_price,
+            commission,
+            commission_asset: asset.to_string(),
+        };
+        let result = ArbitrageResult {
+            base_asset: "ETH".to_string(),
+            buy_quote: "USDC".to_string(),
+            sell_quote: "USDT".to_string(),
+            buy_price: dec!(3000),
+            sell_price: dec!(3010),
+            trade_amount: dec!(1),
+            profit: dec!(10),
+            profit_percentage: dec!(0.33),
+            buy_order_id: Some(1),
+            sell_order_id: Some(2),
+            status: ArbitrageStatus::Completed,
+            timestamp: now,
+            start_time: now,
+            end_time: now + Duration::milliseconds(500),
+            buy_fill: fill(dec!(1), dec!(3000), dec!(0.001), "ETH"),
+            sell_fill: fill(dec!(1), dec!(3010), dec!(3.01), "USDT"),
+            mode: TradingMode::Live,
+            config_hash: String::new(),
+            strategy: None,
+            opportunity_id: None,
+        };
+        db.record_arbitrage_result(&result).await.unwrap();
+        // 其他运行模式的交易不导出
+        let simulated = ArbitrageResult { mode: TradingMode::Simulate, start_time: now - Duration::seconds(1), ..result.clone() };
+        db.record_arbitrage_result(&simulated).await.unwrap();
+
+        let analytics = AnalyticsManager::new(db).with_mode(Some(TradingMode::Live));
+        let dir = temp_export_dir("journal");
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("journal.csv");
+        assert_eq!(analytics.export_journal(&TimeRange::Today, &path).await.unwrap(), 2);
+
+        let csv = fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], "Date,Pair,Side,Amount,Price,Fee,Fee Currency");
+        assert!(lines[1].ends_with(",ETH/USDC,BUY,1,3000,0.001,ETH"));
+        assert!(lines[2].ends_with(",ETH/USDT,SELL,1,3010,3.01,USDT"));
+
+        let report = analytics.generate_report(TimeRange::Today).await.unwrap();
+        assert!(analytics.export_report(&report, ExportFormat::Journal, &dir, "report").await.is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_export_report_html() {
+        let analytics = make_manager().await;
+        let dir = temp_export_dir("html");
+
+        let report = analytics.generate_report(TimeRange::Today).await.unwrap();
+        let path = analytics.export_report(&report, ExportFormat::Html, &dir, "report").await.unwrap();
+        assert_eq!(path, dir.join("report.html"));
+
+        let html = fs::read_to_string(&path).unwrap();
+        assert!(html.contains("<tr><td>总交易次数</td><td>1</td></tr>"));
+        assert!(html.contains("<title>BTC 利润 10"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_parse_time_range() {
+        assert_eq!(TimeRange::from_str("Last7Days").unwrap().key(), "last7days");
+        assert_eq!(TimeRange::from_str(" thismonth ").unwrap().key(), "thismonth");
+        assert!(TimeRange::from_str("custom").is_err());
+        assert_eq!(
+            TimeRange::custom_from_dates("2024-01-01", "2024-01-31").unwrap().description(),
+            "2024-01-01至2024-01-31"
+        );
+        assert_eq!(ExportFormat::from_str("CSV").unwrap(), ExportFormat::Csv);
+        assert!(ExportFormat::from_str("xml").is_err());
+    }
+
+    #[test]
+    fn test_parse_extended_time_ranges() {
+        let range = TimeRange::from_str("Last90Days").unwrap();
+        assert!(matches!(range, TimeRange::LastNDays(90)));
+        assert_eq!(range.key(), "last90days");
+        assert_eq!(range.description(), "过去90天");
+        // 已有的预设范围保持不变
+        assert!(matches!(TimeRange::from_str("last7days").unwrap(), TimeRange::Last7Days));
+        assert!(TimeRange::from_str("last0days").is_err());
+        assert!(TimeRange::from_str("lastxdays").is_err());
+
+        assert_eq!(TimeRange::from_str("thisweek").unwrap().key(), "thisweek");
+        assert_eq!(TimeRange::from_str("LastWeek").unwrap().description(), "上周");
+    }
+
+    #[test]
+    fn test_date_range_month_and_week_boundaries() {
+        let tz = chrono::FixedOffset::east_opt(8 * 3600).unwrap();
+        let utc = |y, m, d, h| Utc.with_ymd_and_hms(y, m, d, h, 0, 0).unwrap();
+        // UTC时间仍是12月31日，+08:00时区已是1月1日（周一）
+        let now = Utc.with_ymd_and_hms(2023, 12, 31, 16, 30, 0).unwrap();
+
+        assert_eq!(TimeRange::Today.date_range_in(&tz, now), (Some(utc(2023, 12, 31, 16)), None));
+        assert_eq!(TimeRange::ThisMonth.date_range_in(&tz, now), (Some(utc(2023, 12, 31, 16)), None));
+        assert_eq!(
+            TimeRange::LastMonth.date_range_in(&tz, now),
+            (Some(utc(2023, 11, 30, 16)), Some(utc(2023, 12, 31, 16)))
+        );
+        assert_eq!(TimeRange::ThisWeek.date_range_in(&tz, now), (Some(utc(2023, 12, 31, 16)), None));
+        assert_eq!(
+            TimeRange::LastWeek.date_range_in(&tz, now),
+            (Some(utc(2023, 12, 24, 16)), Some(utc(2023, 12, 31, 16)))
+        );
+
+        // 闰年2月，3月1日（周五）
+        let now = utc(2024, 3, 1, 4);
+        assert_eq!(
+            TimeRange::LastMonth.date_range_in(&tz, now),
+            (Some(utc(2024, 1, 31, 16)), Some(utc(2024, 2, 29, 16)))
+        );
+        assert_eq!(
+            TimeRange::Yesterday.date_range_in(&tz, now),
+            (Some(utc(2024, 2, 28, 16)), Some(utc(2024, 2, 29, 16)))
+        );
+        assert_eq!(TimeRange::ThisWeek.date_range_in(&tz, now), (Some(utc(2024, 2, 25, 16)), None));
+        assert_eq!(TimeRange::LastNDays(90).date_range_in(&tz, now), (Some(now - Duration::days(90)), None));
+    }
+
+    #[test]
+    fn test_date_range_on_dst_transitions() {
+        // 古巴在零点切换夏令时：2024-03-10 零点直接跳到1点，2024-11-03 1点回拨到零点
+        let tz = chrono_tz::America::Havana;
+        let utc = |y, m, d, h| Utc.with_ymd_and_hms(y, m, d, h, 0, 0).unwrap();
+
+        // 当天零点不存在，从 01:00 (UTC-4) 开始
+        let now = utc(2024, 3, 10, 16);
+        assert_eq!(TimeRange::Today.date_range_in(&tz, now), (Some(utc(2024, 3, 10, 5)), None));
+
+        // 零点出现两次，取较早的 00:00 (UTC-4)，当天共25小时
+        let now = utc(2024, 11, 4, 17);
+        let (start, end) = TimeRange::Yesterday.date_range_in(&tz, now);
+        assert_eq!(start, Some(utc(2024, 11, 3, 4)));
+        assert_eq!(end, Some(utc(2024, 11, 4, 5)));
+        assert_eq!(end.unwrap() - start.unwrap(), Duration::hours(25));
+
+        let range = TimeRange::custom_from_dates_in(&tz, "2024-03-10", "2024-03-10", utc(2024, 3, 20, 0)).unwrap();
+        assert_eq!(
+            range.get_date_range(),
+            (Some(utc(2024, 3, 10, 5)), Some(Utc.with_ymd_and_hms(2024, 3, 11, 3, 59, 59).unwrap()))
+        );
+    }
+
+    #[test]
+    fn test_invalid_custom_ranges() {
+        let tz = chrono::FixedOffset::east_opt(0).unwrap();
+        let now = Utc.with_ymd_and_hms(2024, 3, 15, 12, 0, 0).unwrap();
+
+        let err = TimeRange::custom_from_dates_in(&tz, "2024-03-10", "2024-03-01", now).unwrap_err();
+        assert!(err.to_string().contains("开始日期 2024-03-10 晚于结束日期 2024-03-01"), "{}", err);
+        let err = TimeRange::custom_from_dates_in(&tz, "2024-03-10", "2024-03-16", now).unwrap_err();
+        assert!(err.to_string().contains("晚于今天"), "{}", err);
+        assert!(TimeRange::custom_from_dates_in(&tz, "2024/03/01", "2024-03-10", now).is_err());
+        // 结束日期为今天时允许结束时间晚于当前时间
+        assert!(TimeRange::custom_from_dates_in(&tz, "2024-03-01", "2024-03-15", now).is_ok());
+
+        let err = TimeRange::Custom(now, now - Duration::days(1)).validate().unwrap_err();
+        assert!(err.to_string().contains("晚于结束时间"), "{}", err);
+        let future = Utc::now() + Duration::days(1);
+        let err = TimeRange::Custom(future, future + Duration::days(1)).validate().unwrap_err();
+        assert!(err.to_string().contains("晚于当前时间"), "{}", err);
+        assert!(TimeRange::LastNDays(0).validate().is_err());
+        assert!(TimeRange::Custom(now - Duration::days(1), now).validate().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_fee_scenarios() {
+        let analytics = make_manager().await
+            .with_fee_scenarios(vec![dec!(0), dec!(0.00075), dec!(0.001)]);
+
+        let report = analytics.generate_report(TimeRange::AllTime).await.unwrap();
+        assert_eq!(report.fee_scenarios.len(), 3);
+
+        // 名义金额 = 0.1 * (50000 + 50100) = 10010
+        assert_eq!(report.fee_scenarios[0].total_fees, dec!(0));
+        assert_eq!(report.fee_scenarios[0].net_profit, dec!(10));
+        assert_eq!(report.fee_scenarios[1].total_fees, dec!(7.5075));
+        assert_eq!(report.fee_scenarios[1].net_profit, dec!(2.4925));
+        assert_eq!(report.fee_scenarios[2].net_profit, dec!(-0.01));
+
+        // 费率越高净利润越低
+        for pair in report.fee_scenarios.windows(2) {
+            assert!(pair[1].net_profit < pair[0].net_profit);
+        }
+
+        // 未设置费率时不生成对比
+        let analytics = make_manager().await;
+        let report = analytics.generate_report(TimeRange::AllTime).await.unwrap();
+        assert!(report.fee_scenarios.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_report_respects_time_range() {
+        let db = DatabaseManager::new("sqlite::memory:").await.expect("创建SQLite数据库失败");
+        let now = Utc::now();
+
+        // 今天 BTC 10，昨天 ETH 5，前天 BTC 3
+        for (days_ago, asset, profit) in [(0, "BTC", dec!(10)), (1, "ETH", dec!(5)), (2, "BTC", dec!(3))] {
+            let start_time = now - Duration::days(days_ago);
+            let result = ArbitrageResult {
+                base_asset: asset.to_string(),
+                buy_quote: "USDT".to_string(),
+                sell_quote: "USDC".to_string(),
+                buy_price: dec!(50000),
+                sell_price: dec!(50100),
+                trade_amount: dec!(0.1),
+                profit,
+                profit_percentage: dec!(0.2),
+                buy_order_id: Some(1),
+                sell_order_id: Some(2),
+                status: ArbitrageStatus::Completed,
+                timestamp: start_time,
+                start_time,
+                end_time: start_time + Duration::milliseconds(500),
+                buy_fill: FillInfo::default(),
+                sell_fill: FillInfo::default(),
+                mode: TradingMode::Live,
+                config_hash: String::new(),
+                strategy: None,
+                opportunity_id: None,
+            };
+            db.record_arbitrage_result(&result).await.unwrap();
+        }
+        let analytics = AnalyticsManager::new(db);
+
+        let today = analytics.generate_report(TimeRange::Today).await.unwrap();
+        assert_eq!(today.overview.total_trades, 1);
+        assert_eq!(today.overview.total_profit, dec!(10));
+        assert_eq!(today.daily_stats.len(), 1);
+        assert_eq!(today.asset_stats.len(), 1);
+        assert_eq!(today.asset_stats[0].asset, "BTC");
+
+        let yesterday = analytics.generate_report(TimeRange::Yesterday).await.unwrap();
+        assert_eq!(yesterday.overview.total_trades, 1);
+        assert_eq!(yesterday.overview.total_profit, dec!(5));
+        assert_eq!(yesterday.asset_stats[0].asset, "ETH");
+
+        let week = analytics.generate_report(TimeRange::Last7Days).await.unwrap();
+        assert_eq!(week.overview.total_trades, 3);
+        assert_eq!(week.overview.total_profit, dec!(18));
+        assert_eq!(week.daily_stats.len(), 3);
+        assert_eq!(week.asset_stats[0].asset, "BTC");
+        assert_eq!(week.asset_stats[0].profit, dec!(13));
+        assert_eq!(week.hourly_stats.len(), 24);
+        assert_eq!(week.hourly_stats.iter().map(|h| h.trades).sum::<i64>(), 3);
+        assert_eq!(week.hourly_stats.iter().map(|h| h.profit).sum::<Decimal>(), dec!(18));
+
+        let all_time = analytics.generate_report(TimeRange::AllTime).await.unwrap();
+        assert_eq!(all_time.overview.total_trades, 3);
+        assert_eq!(all_time.overview.total_profit, dec!(18));
+
+        // 币种过滤只影响币种统计，且同样遵循报告的时间范围
+        let analytics = analytics.with_asset_stats(10, Some("BTC".to_string()), AssetSortBy::Trades);
+        let yesterday = analytics.generate_report(TimeRange::Yesterday).await.unwrap();
+        assert_eq!(yesterday.overview.total_trades, 1);
+        assert!(yesterday.asset_stats.is_empty());
+        let week = analytics.generate_report(TimeRange::Last7Days).await.unwrap();
+        assert_eq!(week.asset_stats.len(), 1);
+        assert_eq!(week.asset_stats[0].trades, 2);
+    }
+
+    #[tokio::test]
+    async fn test_report_asset_daily_matrix() {
+        let db = DatabaseManager::new("sqlite::memory:").await.expect("创建SQLite数据库失败");
+
+        // BTC 每笔交易量 2，ETH 每笔 1：1日 BTC 10、ETH 5，2日 BTC 3，3日 BTC 4、ETH -1（失败）
+        let trades = [
+            (1, "BTC", dec!(10), dec!(2), ArbitrageStatus::Completed),
+            (1, "ETH", dec!(5), dec!(1), ArbitrageStatus::Completed),
+            (2, "BTC", dec!(3), dec!(2), ArbitrageStatus::Completed),
+            (3, "BTC", dec!(4), dec!(2), ArbitrageStatus::Completed),
+            (3, "ETH", dec!(-1), dec!(1), ArbitrageStatus::Failed),
+        ];
+        for (index, (day, asset, profit, trade_amount, status)) in trades.into_iter().enumerate() {
+            let start_time = Utc.with_ymd_and_hms(2024, 1, day, 12, 0, 0).unwrap();
+            let result = ArbitrageResult {
+                base_asset: asset.to_string(),
+                buy_quote: "USDT".to_string(),
+                sell_quote: "USDC".to_string(),
+                buy_price: dec!(50000),
+                sell_price: dec!(50100),
+                trade_amount,
+                profit,
+                profit_percentage: dec!(0.2),
+                buy_order_id: Some(index as u64 * 2 + 1),
+                sell_order_id: Some(index as u64 * 2 + 2),
+                status,
+                timestamp: start_time,
+                start_time,
+                end_time: start_time + Duration::milliseconds(500),
+                buy_fill: FillInfo::default(),
+                sell_fill: FillInfo::default(),
+                mode: TradingMode::Live,
+                config_hash: String::new(),
+                strategy: None,
+                opportunity_id: None,
+            };
+            db.record_arbitrage_result(&result).await.unwrap();
+        }
+        let analytics = AnalyticsManager::new(db);
+
+        let report = analytics.generate_report(TimeRange::AllTime).await.unwrap();
+        let cells: Vec<(&str, &str, i64, Decimal)> = report
+            .asset_daily
+            .iter()
+            .map(|stats| (stats.date.as_str(), stats.asset.as_str(), stats.trades, stats.profit))
+            .collect();
+        assert_eq!(cells, vec![
+            ("2024-01-01", "BTC", 1, dec!(10)),
+            ("2024-01-01", "ETH", 1, dec!(5)),
+            ("2024-01-02", "BTC", 1, dec!(3)),
+            ("2024-01-03", "BTC", 1, dec!(4)),
+            ("2024-01-03", "ETH", 1, dec!(-1)),
+        ]);
+        assert_eq!(report.asset_daily[4].successful_trades, 0);
+        assert_eq!(report.asset_daily[0].volume, dec!(2));
+
+        // 按币种透视导出，没有交易的日期填0
+        let dir = temp_export_dir("asset_daily");
+        fs::create_dir_all(&dir).unwrap();
+        analytics.export_report_to_csv(&report, &dir).await.unwrap();
+        let matrix = fs::read_to_string(dir.join("asset_daily.csv")).unwrap();
+        let lines: Vec<&str> = matrix.lines().collect();
+        assert_eq!(lines, vec![
+            "日期,BTC利润(USDT),ETH利润(USDT)",
+            "2024-01-01,10,5",
+            "2024-01-02,3,0",
+            "2024-01-03,4,-1",
+        ]);
+        fs::remove_dir_all(&dir).unwrap();
+
+        // 只保留交易量最大的币种，其余合并为 other
+        let analytics = analytics.with_asset_stats(1, None, AssetSortBy::Profit);
+        let report = analytics.generate_report(TimeRange::AllTime).await.unwrap();
+        let assets: Vec<&str> = report.asset_daily.iter().map(|stats| stats.asset.as_str()).collect();
+        assert_eq!(assets, vec!["BTC", OTHER_ASSETS, "BTC", "BTC", OTHER_ASSETS]);
+        assert_eq!(report.asset_daily[1].profit, dec!(5));
+
+        // 指定运行模式时在内存中统计，结果与数据库统计一致
+        let live = analytics.with_mode(Some(TradingMode::Live)).generate_report(TimeRange::AllTime).await.unwrap();
+        assert_eq!(live.asset_daily, report.asset_daily);
+    }
+
+    #[tokio::test]
+    async fn test_report_filtered_by_mode() {
+        let db = DatabaseManager::new("sqlite::memory:").await.expect("创建SQLite数据库失败");
+        let now = Utc::now();
+
+        // 实盘 BTC 10、ETH -2（失败），模拟 BTC 100
+        let trades = [
+            (TradingMode::Live, "BTC", dec!(10), ArbitrageStatus::Completed),
+            (TradingMode::Live, "ETH", dec!(-2), ArbitrageStatus::Failed),
+            (TradingMode::Simulate, "BTC", dec!(100), ArbitrageStatus::Completed),
+        ];
+        for (index, (mode, asset, profit, status)) in trades.into_iter().enumerate() {
+            let start_time = now - Duration::minutes(index as i64);
+            let result = ArbitrageResult {
+                base_asset: asset.to_string(),
+                buy_quote: "USDT".to_string(),
+                sell_quote: "USDC".to_string(),
+                buy_price: dec!(50000),
+                sell_price: dec!(50100),
+                trade_amount: dec!(0.1),
+                profit,
+                profit_percentage: dec!(0.2),
+                buy_order_id: Some(index as u64 * 2 + 1),
+                sell_order_id: Some(index as u64 * 2 + 2),
+                status,
+                timestamp: start_time,
+                start_time,
+                end_time: start_time + Duration::milliseconds(500),
+                buy_fill: FillInfo::default(),
+                sell_fill: FillInfo::default(),
+                mode,
+                config_hash: String::new(),
+                strategy: None,
+                opportunity_id: None,
+            };
+            db.record_arbitrage_result(&result).await.unwrap();
+        }
+        let analytics = AnalyticsManager::new(db);
+
+        let all = analytics.generate_report(TimeRange::AllTime).await.unwrap();
+        assert_eq!(all.mode, None);
+        assert_eq!(all.overview.total_trades, 3);
+        assert_eq!(all.overview.total_profit, dec!(108));
+
+        let analytics = analytics
+            .with_mode(Some(TradingMode::Live))
+            .with_fee_scenarios(vec![dec!(0)]);
+        let live = analytics.generate_report(TimeRange::AllTime).await.unwrap();
+        assert_eq!(live.mode, Some(TradingMode::Live));
+        assert_eq!(live.overview.total_trades, 2);
+        assert_eq!(live.overview.successful_trades, 1);
+        assert_eq!(live.overview.failed_trades, 1);
+        assert_eq!(live.overview.total_profit, dec!(8));
+        assert_eq!(live.overview.max_profit, dec!(10));
+        assert_eq!(live.overview.max_loss, dec!(-2));
+        assert_eq!(live.overview.avg_trade_duration_ms, 500);
+        assert_eq!(live.success_rate, 50.0);
+        assert_eq!(live.overview.avg_win, dec!(10));
+        assert_eq!(live.overview.avg_loss, dec!(-2));
+        assert_eq!(live.profit_loss_ratio, 5.0);
+        assert_eq!(live.daily_stats.iter().map(|d| d.trades).sum::<i64>(), 2);
+        assert_eq!(live.hourly_stats.len(), 24);
+        assert_eq!(live.hourly_stats.iter().map(|h| h.profit).sum::<Decimal>(), dec!(8));
+        assert_eq!(live.hourly_profit.len(), 24);
+        assert_eq!(live.hourly_profit.iter().map(|(_, profit)| *profit).sum::<Decimal>(), dec!(8));
+        assert_eq!(live.asset_stats.len(), 2);
+        assert_eq!(live.asset_stats[0].asset, "BTC");
+        assert_eq!(live.asset_stats[0].profit, dec!(10));
+        assert_eq!(live.strategy_stats.len(), 1);
+        assert_eq!(live.strategy_stats[0].trades, 2);
+        assert_eq!(live.strategy_stats[0].total_profit, dec!(8));
+        assert_eq!(live.fee_scenarios[0].net_profit, dec!(8));
+
+        let analytics = analytics.with_mode(Some(TradingMode::Simulate));
+        let simulated = analytics.generate_report(TimeRange::AllTime).await.unwrap();
+        assert_eq!(simulated.overview.total_trades, 1);
+        assert_eq!(simulated.overview.total_profit, dec!(100));
+
+        let analytics = analytics.with_mode(Some(TradingMode::Backtest));
+        let empty = analytics.generate_report(TimeRange::AllTime).await.unwrap();
+        assert_eq!(empty.overview.total_trades, 0);
+        assert!(empty.daily_stats.is_empty());
+        assert!(empty.asset_stats.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_export_multiple_ranges_json() {
+        let analytics = make_manager().await;
+        let dir = temp_export_dir("batch_json");
+
+        let ranges = vec![TimeRange::Today, TimeRange::Last7Days];
+        let exported = analytics.export_reports(&ranges, ExportFormat::Json, &dir).await.unwrap();
+        assert_eq!(exported.len(), 2);
+
+        for ((report, path), range) in exported.iter().zip(ranges.iter()) {
+            assert!(path.exists());
+            let file_name = path.file_name().unwrap().to_string_lossy().to_string();
+            assert!(file_name.starts_with(&format!("report_{}_", range.key())));
+
+            let content = fs::read_to_string(path).unwrap();
+            let parsed: PerformanceReport = serde_json::from_str(&content).unwrap();
+            assert_eq!(parsed.time_range, range.description());
+            assert_eq!(parsed.overview.total_trades, report.overview.total_trades);
+            assert_eq!(parsed.overview.total_profit, dec!(10));
+            assert_eq!(parsed.hourly_stats.len(), 24);
+            assert_eq!(parsed.hourly_profit, report.hourly_profit);
+            assert_eq!(parsed.risk_metrics, report.risk_metrics);
+            assert_eq!(parsed.risk_metrics.annualization_days, DEFAULT_ANNUALIZATION_DAYS);
+            assert_eq!(parsed.hourly_stats.iter().map(|h| h.trades).sum::<i64>(), 1);
+            assert_eq!(parsed.asset_daily, report.asset_daily);
+            assert_eq!(parsed.duration_stats, report.duration_stats);
+            assert_eq!(parsed.duration_stats.as_ref().unwrap().p50_ms, 500);
+            assert_eq!(parsed.asset_daily.len(), 1);
+        }
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_export_multiple_ranges_csv() {
+        let analytics = make_manager().await;
+        let dir = temp_export_dir("batch_csv");
+
+        let ranges = vec![TimeRange::Today, TimeRange::AllTime];
+        let exported = analytics.export_reports(&ranges, ExportFormat::Csv, &dir).await.unwrap();
+        assert_eq!(exported.len(), 2);
+        assert_ne!(exported[0].1, exported[1].1);
+
+        for (_, path) in &exported {
+            assert!(path.is_dir());
+            for file in ["overview.csv", "daily_stats.csv", "hourly_stats.csv", "asset_stats.csv", "asset_daily.csv", "strategy_stats.csv", "equity_curve.csv"] {
+                assert!(path.join(file).exists());
+            }
+
+            let overview = fs::read_to_string(path.join("overview.csv")).unwrap();
+            assert!(overview.contains("总交易次数,1"));
+            assert!(overview.contains("最大回撤(USDT),0"));
+            let assets = fs::read_to_string(path.join("asset_stats.csv")).unwrap();
+            assert!(assets.contains("BTC,1,10"));
+            let strategies = fs::read_to_string(path.join("strategy_stats.csv")).unwrap();
+            assert!(strategies.contains("unattributed,1,100.00,10"));
+            let equity = fs::read_to_string(path.join("equity_curve.csv")).unwrap();
+            assert_eq!(equity.lines().count(), 2);
+            assert!(equity.lines().nth(1).unwrap().contains(",1,10"));
+            assert!(equity.contains(",100.00,"));
+            // 测试数据没有成交信息和被拒绝的机会
+            assert!(!path.join("fee_slippage.csv").exists());
+            assert!(!path.join("rejection_reasons.csv").exists());
+            let durations = fs::read_to_string(path.join("duration_stats.csv")).unwrap();
+            assert!(durations.contains("耗时P90(毫秒),500"));
+            assert!(durations.lines().any(|line| line == "未完成交易平均耗时(毫秒),"));
+            let quartiles = fs::read_to_string(path.join("duration_quartiles.csv")).unwrap();
+            assert_eq!(quartiles.lines().count(), 5);
+            assert!(quartiles.contains("Q1,500,1,10"));
+        }
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_csv_headers_by_locale() {
+        let first_line = |path: &Path, file: &str| {
+            fs::read_to_string(path.join(file)).unwrap().lines().next().unwrap().to_string()
+        };
+
+        let dir = temp_export_dir("locale_zh");
+        fs::create_dir_all(&dir).unwrap();
+        let analytics = make_manager().await;
+        let report = analytics.generate_report(TimeRange::AllTime).await.unwrap();
+        assert_eq!(report.title, "套利交易绩效报告 - 全部历史");
+        analytics.export_report_to_csv(&report, &dir).await.unwrap();
+        assert_eq!(first_line(&dir, "daily_stats.csv"), "日期,交易数量,利润(USDT),交易量(USDT),成功率(%)");
+        assert_eq!(first_line(&dir, "asset_daily.csv"), "日期,BTC利润(USDT)");
+        assert_eq!(first_line(&dir, "duration_quartiles.csv"), "耗时分组,耗时上限(毫秒),交易数量,平均利润(USDT)");
+        assert!(fs::read_to_string(dir.join("overview.csv")).unwrap().contains("总交易次数,1"));
+        fs::remove_dir_all(&dir).unwrap();
+
+        let dir = temp_export_dir("locale_en");
+        fs::create_dir_all(&dir).unwrap();
+        let analytics = make_manager().await.with_locale(Locale::En);
+        let report = analytics.generate_report(TimeRange::AllTime).await.unwrap();
+        assert_eq!(report.title, "Arbitrage Performance Report - All time");
+        analytics.export_report_to_csv(&report, &dir).await.unwrap();
+        assert_eq!(first_line(&dir, "daily_stats.csv"), "Date,Trades,Profit (USDT),Volume (USDT),Success rate (%)");
+        assert_eq!(first_line(&dir, "asset_daily.csv"), "Date,BTC Profit (USDT)");
+        assert_eq!(first_line(&dir, "duration_quartiles.csv"), "Duration quartile,Duration upper bound (ms),Trades,Avg profit (USDT)");
+        assert!(fs::read_to_string(dir.join("overview.csv")).unwrap().contains("Total trades,1"));
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(TimeRange::LastNDays(90).description_in(Locale::En), "Last 90 days");
+        assert_eq!(TimeRange::Last7Days.description(), "过去7天");
+        let custom = TimeRange::Custom(
+            Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap(),
+            Utc.with_ymd_and_hms(2024, 1, 31, 23, 59, 59).unwrap(),
+        );
+        assert_eq!(custom.description(), "2024-01-01至2024-01-31");
+        assert_eq!(custom.description_in(Locale::En), "2024-01-01 to 2024-01-31");
+    }
+
+    #[tokio::test]
+    async fn test_rejections_in_report() {
+        use crate::db::RejectedOpportunity;
+        use crate::models::{ArbitrageOpportunity, QuoteCurrency};
+
+        let analytics = make_manager().await;
+        let opportunity = ArbitrageOpportunity::new("BTC", QuoteCurrency::USDT, QuoteCurrency::USDC, dec!(50000), dec!(50100), dec!(1000));
+        for reasons in [vec!["每日亏损限制: 今日亏损已达上限"], vec!["每日亏损限制: 今日亏损已达上限"], vec!["交易频率限制: 交易过于频繁"]] {
+            let reasons: Vec<String> = reasons.into_iter().map(String::from).collect();
+            let rejection = RejectedOpportunity::from_reasons(&opportunity, &reasons, TradingMode::Live, Utc::now());
+            analytics.db.record_rejection(&rejection).await.unwrap();
+        }
+
+        let report = analytics.generate_report(TimeRange::Today).await.unwrap();
+        assert_eq!(report.rejections.total, 3);
+        assert_eq!(report.rejections.foregone_profit, dec!(6));
+        assert_eq!(report.rejections.by_controller[0].controller, "每日亏损限制");
+        assert_eq!(report.rejections.by_controller[0].count, 2);
+
+        let dir = temp_export_dir("rejections");
+        fs::create_dir_all(&dir).unwrap();
+        analytics.export_report_to_csv(&report, &dir).await.unwrap();
+        let reasons = fs::read_to_string(dir.join("rejection_reasons.csv")).unwrap();
+        assert_eq!(reasons.lines().next().unwrap(), "拒绝组件,拒绝原因,拒绝次数,错过的预估利润(USDT)");
+        assert!(reasons.lines().any(|line| line.starts_with("每日亏损限制,每日亏损限制: 今日亏损已达上限,2,")));
+        let hours = fs::read_to_string(dir.join("rejection_hours.csv")).unwrap();
+        assert_eq!(hours.lines().count(), 25);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_export_tax_report() {
+        let db = DatabaseManager::new("sqlite::memory:").await.expect("创建SQLite数据库失败");
+        let fill = |qty, avg_price, commission, commission_asset: &str| FillInfo {
+            qty,
+            avg_price,
+            commission,
+            commission_asset: commission_asset.to_string(),
+        };
+        let result = |start_time: DateTime<Utc>, status, buy_fill: FillInfo, sell_fill: FillInfo, mode| ArbitrageResult {
+            base_asset: "BTC".to_string(),
+            buy_quote: "USDT".to_string(),
+            sell_quote: "USDC".to_string(),
+            buy_price: buy_fill.avg_price,
+            sell_price: sell_fill.avg_price,
+            trade_amount: buy_fill.qty,
+            profit: Decimal::ZERO,
+            profit_percentage: Decimal::ZERO,
+            buy_order_id: None,
+            sell_order_id: None,
+            status,
+            timestamp: start_time,
+            start_time,
+            end_time: start_time + Duration::seconds(1),
+            buy_fill,
+            sell_fill,
+            mode,
+            config_hash: String::new(),
+            strategy: None,
+            opportunity_id: None,
+        };
+
+        // 上一年只买入成功的批次结转到2024年，模拟交易不参与计算
+        db.record_arbitrage_result(&result(
+            Utc.with_ymd_and_hms(2023, 12, 31, 0, 0, 0).unwrap(),
+            ArbitrageStatus::Failed,
+            fill(dec!(1), dec!(120), dec!(0.12), "USDT"),
+            FillInfo::default(),
+            TradingMode::Live,
+        )).await.unwrap();
+        db.record_arbitrage_result(&result(
+            Utc.with_ymd_and_hms(2024, 1, 5, 0, 0, 0).unwrap(),
+            ArbitrageStatus::Completed,
+            fill(dec!(2.002), dec!(130), dec!(0.002), "BTC"),
+            fill(dec!(2), dec!(140), dec!(0.28), "USDC"),
+            TradingMode::Live,
+        )).await.unwrap();
+        db.record_arbitrage_result(&result(
+            Utc.with_ymd_and_hms(2024, 2, 1, 0, 0, 0).unwrap(),
+            ArbitrageStatus::Failed,
+            FillInfo::default(),
+            fill(dec!(1), dec!(1000), Decimal::ZERO, "USDC"),
+            TradingMode::Simulate,
+        )).await.unwrap();
+
+        let dir = temp_export_dir("tax");
+        fs::create_dir_all(&dir).unwrap();
+        let analytics = AnalyticsManager::new(db);
+        let path = dir.join("tax_2024.csv");
+        let report = analytics.export_tax_report(2024, &path, ExportFormat::Csv).await.unwrap();
+        assert_eq!(report.total_gain, dec!(29.47));
+
+        let disposals = fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = disposals.lines().collect();
+        assert_eq!(lines, vec![
+            "币种,最早取得时间,最晚取得时间,处置时间,数量,处置收入,成本,收益,手续费,无成本记录的数量,套利记录ID",
+            "BTC,2023-12-31 00:00:00,2024-01-05 00:00:00,2024-01-05 00:00:01,2,279.72,250.25,29.47,0.53,0,2",
+        ]);
+        let lots = fs::read_to_string(dir.join("tax_2024_open_lots.csv")).unwrap();
+        assert_eq!(lots.lines().nth(1), Some("BTC,2024-01-05 00:00:00,1,130.13,0.13,2"));
+
+        assert!(analytics.export_tax_report(2024, &dir.join("tax.html"), ExportFormat::Html).await.is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
 }
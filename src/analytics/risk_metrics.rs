@@ -0,0 +1,198 @@
+//! 基于每日盈亏序列的风险调整收益指标：夏普比率、索提诺比率和最大回撤
+
+use crate::db::DailyStats;
+use chrono::NaiveDate;
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use serde::{Serialize, Deserialize};
+
+/// 默认年化天数，加密货币市场全年无休
+pub const DEFAULT_ANNUALIZATION_DAYS: u32 = 365;
+
+/// 风险调整收益指标
+///
+/// 由于没有资金基数，收益率直接使用每日盈亏（USDT），无风险利率按0计算。
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RiskMetrics {
+    /// 年化使用的天数
+    pub annualization_days: u32,
+    /// 参与计算的天数（含区间内无交易、盈亏为0的日期）
+    pub trading_days: usize,
+    /// 年化夏普比率，样本不足或波动为0时为0
+    pub sharpe_ratio: f64,
+    /// 年化索提诺比率，只以亏损日的波动作为风险，没有亏损日时为0
+    pub sortino_ratio: f64,
+    /// 累计盈亏从峰值回落的最大金额（USDT，非负）
+    pub max_drawdown: Decimal,
+    /// 累计盈亏低于此前峰值的最长连续天数，未恢复的回撤计算到最后一天
+    pub max_drawdown_days: i64,
+}
+
+impl RiskMetrics {
+    /// 由按日期升序排列的每日统计计算指标，日期之间缺失的天按盈亏为0补齐
+    pub fn from_daily_stats(daily_stats: &[DailyStats], annualization_days: u32) -> Self {
+        Self::from_daily_pnl(&daily_pnl_series(daily_stats), annualization_days)
+    }
+
+    /// 由每日盈亏序列计算指标
+    pub fn from_daily_pnl(pnl: &[Decimal], annualization_days: u32) -> Self {
+        let (max_drawdown, max_drawdown_days) = max_drawdown(pnl);
+        let mut metrics = Self {
+            annualization_days,
+            trading_days: pnl.len(),
+            max_drawdown,
+            max_drawdown_days,
+            ..Self::default()
+        };
+
+        if pnl.len() < 2 {
+            return metrics;
+        }
+
+        let returns: Vec<f64> = pnl.iter().map(|value| value.to_f64().unwrap_or(0.0)).collect();
+        let count = returns.len() as f64;
+        let mean = returns.iter().sum::<f64>() / count;
+        let annualization = (annualization_days as f64).sqrt();
+
+        // 样本标准差
+        let variance = returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (count - 1.0);
+        let std_dev = variance.sqrt();
+        if std_dev > 0.0 {
+            metrics.sharpe_ratio = mean / std_dev * annualization;
+        }
+
+        // 下行偏差：只累计亏损日，分母为全部天数
+        let downside = returns.iter().map(|r| r.min(0.0).powi(2)).sum::<f64>() / count;
+        let downside_dev = downside.sqrt();
+        if downside_dev > 0.0 {
+            metrics.sortino_ratio = mean / downside_dev * annualization;
+        }
+
+        metrics
+    }
+}
+
+/// 将每日统计转换为连续的每日盈亏序列，两条统计之间缺失的日期补0
+///
+/// 无法解析的日期不补齐，直接按原顺序追加。
+pub fn daily_pnl_series(daily_stats: &[DailyStats]) -> Vec<Decimal> {
+    let mut series = Vec::with_capacity(daily_stats.len());
+    let mut last_date: Option<NaiveDate> = None;
+
+    for stats in daily_stats {
+        let date = NaiveDate::parse_from_str(&stats.date, "%Y-%m-%d").ok();
+        if let (Some(last), Some(date)) = (last_date, date) {
+            let gap = (date - last).num_days() - 1;
+            series.extend((0..gap.max(0)).map(|_| Decimal::ZERO));
+        }
+        series.push(stats.profit);
+        if date.is_some() {
+            last_date = date;
+        }
+    }
+
+    series
+}
+
+/// 计算累计盈亏曲线（从0开始）的最大回撤金额和最长回撤天数
+fn max_drawdown(pnl: &[Decimal]) -> (Decimal, i64) {
+    let mut equity = Decimal::ZERO;
+    let mut peak = Decimal::ZERO;
+    // 峰值所在的天，-1 表示序列开始之前
+    let mut peak_index: i64 = -1;
+    let mut max_drawdown = Decimal::ZERO;
+    let mut max_days = 0;
+
+    for (index, value) in pnl.iter().enumerate() {
+        let index = index as i64;
+        equity += value;
+
+        if equity >= peak {
+            peak = equity;
+            peak_index = index;
+        } else {
+            max_drawdown = max_drawdown.max(peak - equity);
+            max_days = max_days.max(index - peak_index);
+        }
+    }
+
+    (max_drawdown, max_days)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn day(date: &str, profit: Decimal) -> DailyStats {
+        DailyStats {
+            date: date.to_string(),
+            trades: 1,
+            profit,
+            volume: dec!(1),
+            successful_rate: 100.0,
+        }
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-9, "{} != {}", actual, expected);
+    }
+
+    #[test]
+    fn test_ratios_and_drawdown() {
+        // 均值 6，样本方差 670/4 = 167.5，下行偏差 sqrt((25 + 100) / 5) = 5
+        // 累计盈亏 10, 5, 20, 10, 30：最大回撤 10，最长回撤 1 天
+        let pnl = [dec!(10), dec!(-5), dec!(15), dec!(-10), dec!(20)];
+
+        let metrics = RiskMetrics::from_daily_pnl(&pnl, 1);
+        assert_eq!(metrics.trading_days, 5);
+        assert_close(metrics.sharpe_ratio, 6.0 / 167.5f64.sqrt());
+        assert_close(metrics.sortino_ratio, 1.2);
+        assert_eq!(metrics.max_drawdown, dec!(10));
+        assert_eq!(metrics.max_drawdown_days, 1);
+
+        let annualized = RiskMetrics::from_daily_pnl(&pnl, 365);
+        assert_eq!(annualized.annualization_days, 365);
+        assert_close(annualized.sharpe_ratio, 6.0 / 167.5f64.sqrt() * 365f64.sqrt());
+        assert_close(annualized.sortino_ratio, 1.2 * 365f64.sqrt());
+    }
+
+    #[test]
+    fn test_missing_days_filled_with_zero() {
+        // 补齐后 10, 0, 0, -30, 5，累计 10, 10, 10, -20, -15：回撤 30，持续到最后 2 天
+        let stats = [
+            day("2024-01-01", dec!(10)),
+            day("2024-01-04", dec!(-30)),
+            day("2024-01-05", dec!(5)),
+        ];
+        assert_eq!(
+            daily_pnl_series(&stats),
+            vec![dec!(10), dec!(0), dec!(0), dec!(-30), dec!(5)]
+        );
+
+        let metrics = RiskMetrics::from_daily_stats(&stats, 365);
+        assert_eq!(metrics.trading_days, 5);
+        assert_eq!(metrics.max_drawdown, dec!(30));
+        assert_eq!(metrics.max_drawdown_days, 2);
+    }
+
+    #[test]
+    fn test_drawdown_from_start_and_degenerate_series() {
+        // 首日亏损相对起点0回撤，次日回到0即恢复
+        let metrics = RiskMetrics::from_daily_pnl(&[dec!(-5), dec!(5)], 365);
+        assert_eq!(metrics.max_drawdown, dec!(5));
+        assert_eq!(metrics.max_drawdown_days, 1);
+
+        // 无波动、无亏损日时比率为0
+        let flat = RiskMetrics::from_daily_pnl(&[dec!(2), dec!(2), dec!(2)], 365);
+        assert_eq!(flat.sharpe_ratio, 0.0);
+        assert_eq!(flat.sortino_ratio, 0.0);
+        assert_eq!(flat.max_drawdown, Decimal::ZERO);
+
+        let single = RiskMetrics::from_daily_pnl(&[dec!(-3)], 365);
+        assert_eq!(single.sharpe_ratio, 0.0);
+        assert_eq!(single.max_drawdown, dec!(3));
+
+        assert_eq!(RiskMetrics::from_daily_pnl(&[], 365).trading_days, 0);
+    }
+}
@@ -0,0 +1,159 @@
+//! 按日的滚动窗口统计和权益曲线
+
+use crate::db::DailyStats;
+use chrono::{Duration, NaiveDate};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// 滚动统计的窗口天数
+pub const ROLLING_WINDOW_DAYS: usize = 7;
+
+/// 权益曲线上的一天
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RollingPoint {
+    /// 日期 (YYYY-MM-DD)
+    pub date: String,
+    /// 当日交易次数，没有交易的日期为0
+    pub trades: i64,
+    /// 当日利润
+    pub profit: Decimal,
+    /// 截至当日（含）最近7天的利润之和
+    pub rolling_profit: Decimal,
+    /// 最近7天的成功率（百分比），窗口内没有交易时为None
+    pub rolling_success_rate: Option<f64>,
+    /// 初始资金加截至当日的累计利润
+    pub equity: Decimal,
+}
+
+/// 由按日期升序排列的每日统计计算滚动统计和权益曲线
+///
+/// 两条统计之间缺失的日期按交易次数和利润为0补齐，无法解析的日期不补齐，直接按原顺序追加。
+/// 窗口不足7天时按已有的天数计算。
+pub fn rolling_series(daily_stats: &[DailyStats], initial_balance: Decimal) -> Vec<RollingPoint> {
+    let mut points = Vec::with_capacity(daily_stats.len());
+    // 窗口内每天的 (交易次数, 成功次数, 利润)
+    let mut window: VecDeque<(i64, f64, Decimal)> = VecDeque::with_capacity(ROLLING_WINDOW_DAYS);
+    let mut equity = initial_balance;
+    let mut last_date: Option<NaiveDate> = None;
+
+    let mut push_day = |date: String, trades: i64, successful: f64, profit: Decimal, points: &mut Vec<RollingPoint>| {
+        if window.len() == ROLLING_WINDOW_DAYS {
+            window.pop_front();
+        }
+        window.push_back((trades, successful, profit));
+        equity += profit;
+
+        let window_trades: i64 = window.iter().map(|(trades, _, _)| trades).sum();
+        let window_successful: f64 = window.iter().map(|(_, successful, _)| successful).sum();
+        points.push(RollingPoint {
+            date,
+            trades,
+            profit,
+            rolling_profit: window.iter().map(|(_, _, profit)| *profit).sum(),
+            rolling_success_rate: (window_trades > 0).then(|| window_successful / window_trades as f64 * 100.0),
+            equity,
+        });
+    };
+
+    for stats in daily_stats {
+        let date = NaiveDate::parse_from_str(&stats.date, "%Y-%m-%d").ok();
+        if let (Some(last), Some(date)) = (last_date, date) {
+            let mut missing = last + Duration::days(1);
+            while missing < date {
+                push_day(missing.format("%Y-%m-%d").to_string(), 0, 0.0, Decimal::ZERO, &mut points);
+                missing += Duration::days(1);
+            }
+        }
+
+        // 每日统计只保存成功率，按交易次数换算成功次数
+        let successful = stats.trades as f64 * stats.successful_rate / 100.0;
+        push_day(stats.date.clone(), stats.trades, successful, stats.profit, &mut points);
+        if date.is_some() {
+            last_date = date;
+        }
+    }
+
+    points
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn day(date: &str, trades: i64, profit: Decimal, successful_rate: f64) -> DailyStats {
+        DailyStats {
+            date: date.to_string(),
+            trades,
+            profit,
+            volume: dec!(1),
+            successful_rate,
+        }
+    }
+
+    #[test]
+    fn test_missing_days_filled_and_rolling_window() {
+        // 03-03 至 03-07 以及 03-09 没有交易
+        let stats = [
+            day("2024-03-01", 2, dec!(10), 100.0),
+            day("2024-03-02", 2, dec!(-4), 50.0),
+            day("2024-03-08", 4, dec!(6), 25.0),
+            day("2024-03-10", 1, dec!(3), 100.0),
+        ];
+
+        let points = rolling_series(&stats, dec!(1000));
+        let dates: Vec<&str> = points.iter().map(|point| point.date.as_str()).collect();
+        assert_eq!(
+            dates,
+            vec![
+                "2024-03-01", "2024-03-02", "2024-03-03", "2024-03-04", "2024-03-05",
+                "2024-03-06", "2024-03-07", "2024-03-08", "2024-03-09", "2024-03-10",
+            ]
+        );
+
+        // 补齐的日期没有交易和利润
+        assert_eq!(points[4].trades, 0);
+        assert_eq!(points[4].profit, Decimal::ZERO);
+
+        // 权益从初始资金开始累计
+        let equity: Vec<Decimal> = points.iter().map(|point| point.equity).collect();
+        assert_eq!(
+            equity,
+            vec![
+                dec!(1010), dec!(1006), dec!(1006), dec!(1006), dec!(1006),
+                dec!(1006), dec!(1006), dec!(1012), dec!(1012), dec!(1015),
+            ]
+        );
+
+        // 03-07 的窗口为 03-01 至 03-07：利润 10 - 4，成功 2 + 1 次 / 共 4 次
+        assert_eq!(points[6].rolling_profit, dec!(6));
+        assert_eq!(points[6].rolling_success_rate, Some(75.0));
+
+        // 03-08 的窗口为 03-02 至 03-08：利润 -4 + 6，成功 1 + 1 次 / 共 6 次
+        assert_eq!(points[7].rolling_profit, dec!(2));
+        assert!((points[7].rolling_success_rate.unwrap() - 100.0 / 3.0).abs() < 1e-9);
+
+        // 03-10 的窗口为 03-04 至 03-10：利润 6 + 3，成功 1 + 1 次 / 共 5 次
+        assert_eq!(points[9].rolling_profit, dec!(9));
+        assert_eq!(points[9].rolling_success_rate, Some(40.0));
+    }
+
+    #[test]
+    fn test_window_without_trades() {
+        let stats = [
+            day("2024-03-01", 1, dec!(5), 100.0),
+            day("2024-03-10", 1, dec!(-1), 0.0),
+        ];
+
+        let points = rolling_series(&stats, Decimal::ZERO);
+        assert_eq!(points.len(), 10);
+        // 03-08 的窗口 03-02 至 03-08 内没有交易
+        assert_eq!(points[7].rolling_profit, Decimal::ZERO);
+        assert_eq!(points[7].rolling_success_rate, None);
+        assert_eq!(points[9].rolling_success_rate, Some(0.0));
+        assert_eq!(points[9].equity, dec!(4));
+
+        assert!(rolling_series(&[], dec!(100)).is_empty());
+    }
+}
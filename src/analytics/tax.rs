@@ -0,0 +1,335 @@
+//! 按先进先出（FIFO）匹配基础资产的取得和处置，生成年度税务报告
+//!
+//! 每笔套利的买入成交视为取得一批基础资产（一个批次），卖出成交视为处置，处置按取得时间从早到晚消耗批次。
+//! 金额均以报价货币计，USDT和USDC按1:1视为同一种报价货币。
+
+use crate::db::TradeRecord;
+use crate::models::FillInfo;
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, FixedOffset, NaiveDate, TimeZone, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+
+/// 尚未处置完的取得批次
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaxLot {
+    pub asset: String,
+    /// 取得时间（买入套利的开始时间）
+    pub acquired_at: DateTime<Utc>,
+    /// 剩余数量，已扣除以基础资产收取的买入手续费
+    pub quantity: Decimal,
+    /// 剩余数量的成本，包括以报价货币收取的买入手续费
+    pub cost_basis: Decimal,
+    /// 剩余数量分摊的买入手续费
+    pub fees: Decimal,
+    /// 买入所属的套利记录ID
+    pub arbitrage_id: i64,
+}
+
+/// 一次处置，即一笔卖出成交
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaxDisposal {
+    pub asset: String,
+    /// 匹配的批次中最早的取得时间，完全没有匹配到批次时为None
+    pub acquired_from: Option<DateTime<Utc>>,
+    /// 匹配的批次中最晚的取得时间
+    pub acquired_to: Option<DateTime<Utc>>,
+    /// 处置时间（卖出套利的结束时间）
+    pub disposed_at: DateTime<Utc>,
+    /// 处置数量，包括以基础资产收取的卖出手续费
+    pub quantity: Decimal,
+    /// 处置收入，已扣除以报价货币收取的卖出手续费
+    pub proceeds: Decimal,
+    /// 匹配批次的成本
+    pub cost_basis: Decimal,
+    /// 收益（处置收入 - 成本）
+    pub gain: Decimal,
+    /// 匹配批次分摊的买入手续费与卖出手续费之和
+    pub fees: Decimal,
+    /// 没有可匹配批次的数量，按成本为0计算，通常是程序运行前已持有的资产
+    pub unmatched_quantity: Decimal,
+    /// 卖出所属的套利记录ID
+    pub arbitrage_id: i64,
+}
+
+/// 年度税务报告
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaxReport {
+    /// 日历年，按统计时区划分
+    pub year: i32,
+    /// 年内的处置，按处置时间排列
+    pub disposals: Vec<TaxDisposal>,
+    /// 年初从上一年结转的批次
+    pub opening_lots: Vec<TaxLot>,
+    /// 年末结转到下一年的批次
+    pub closing_lots: Vec<TaxLot>,
+    pub total_proceeds: Decimal,
+    pub total_cost_basis: Decimal,
+    pub total_gain: Decimal,
+    pub total_fees: Decimal,
+    /// 截至年末手续费币种无法折算（如BNB）的成交笔数，这部分手续费未计入成本和收入
+    pub unconverted_fee_fills: i64,
+}
+
+impl TaxReport {
+    /// 由交易记录计算 `year` 年的报告，年份边界按 `offset` 时区划分
+    ///
+    /// 需要传入年末之前的全部交易，才能得到正确的结转批次；年末之后的交易被忽略。
+    /// 只成交了一边的失败交易同样参与匹配，没有成交信息的一边不产生批次或处置。
+    pub fn from_trades(year: i32, trades: &[TradeRecord], offset: FixedOffset) -> Result<Self> {
+        let year_start = year_boundary(year, offset)?;
+        let year_end = year_boundary(year + 1, offset)?;
+
+        let mut ordered: Vec<&TradeRecord> = trades
+            .iter()
+            .filter(|record| record.result.start_time < year_end)
+            .collect();
+        ordered.sort_by_key(|record| (record.result.start_time, record.id));
+
+        let mut lots: BTreeMap<String, VecDeque<TaxLot>> = BTreeMap::new();
+        let mut opening_lots = None;
+        let mut disposals = Vec::new();
+        let mut unconverted_fee_fills = 0;
+
+        for record in ordered {
+            let result = &record.result;
+            if opening_lots.is_none() && result.start_time >= year_start {
+                opening_lots = Some(open_lots(&lots));
+            }
+
+            let asset = result.base_asset.as_str();
+            let buy = &result.buy_fill;
+            if buy.qty > Decimal::ZERO {
+                let (base_fee, quote_fee, fee_value) = split_fee(buy, asset, &mut unconverted_fee_fills);
+                lots.entry(asset.to_string()).or_default().push_back(TaxLot {
+                    asset: asset.to_string(),
+                    acquired_at: result.start_time,
+                    quantity: buy.qty - base_fee,
+                    cost_basis: buy.qty * buy.avg_price + quote_fee,
+                    fees: fee_value,
+                    arbitrage_id: record.id,
+                });
+            }
+
+            let sell = &result.sell_fill;
+            if sell.qty > Decimal::ZERO {
+                let (base_fee, quote_fee, fee_value) = split_fee(sell, asset, &mut unconverted_fee_fills);
+                let mut disposal = TaxDisposal {
+                    asset: asset.to_string(),
+                    acquired_from: None,
+                    acquired_to: None,
+                    disposed_at: result.end_time,
+                    quantity: sell.qty + base_fee,
+                    proceeds: sell.qty * sell.avg_price - quote_fee,
+                    cost_basis: Decimal::ZERO,
+                    gain: Decimal::ZERO,
+                    fees: fee_value,
+                    unmatched_quantity: Decimal::ZERO,
+                    arbitrage_id: record.id,
+                };
+                match_lots(&mut disposal, lots.entry(asset.to_string()).or_default());
+                disposal.gain = disposal.proceeds - disposal.cost_basis;
+                if disposal.disposed_at >= year_start {
+                    disposals.push(disposal);
+                }
+            }
+        }
+
+        let closing_lots = open_lots(&lots);
+        Ok(Self {
+            year,
+            total_proceeds: disposals.iter().map(|d| d.proceeds).sum(),
+            total_cost_basis: disposals.iter().map(|d| d.cost_basis).sum(),
+            total_gain: disposals.iter().map(|d| d.gain).sum(),
+            total_fees: disposals.iter().map(|d| d.fees).sum(),
+            disposals,
+            // 年内没有交易时，年初结转的批次即为年末的批次
+            opening_lots: opening_lots.unwrap_or_else(|| closing_lots.clone()),
+            closing_lots,
+            unconverted_fee_fills,
+        })
+    }
+}
+
+/// 按统计时区 `year` 年1月1日零点对应的UTC时间
+pub(crate) fn year_boundary(year: i32, offset: FixedOffset) -> Result<DateTime<Utc>> {
+    NaiveDate::from_ymd_opt(year, 1, 1)
+        .and_then(|date| offset.from_local_datetime(&date.and_hms_opt(0, 0, 0)?).single())
+        .map(|start| start.with_timezone(&Utc))
+        .ok_or_else(|| anyhow!("无效的年份: {}", year))
+}
+
+/// 拆分一边成交的手续费，返回 (以基础资产收取的数量, 以报价货币收取的金额, 折算为报价货币的手续费)
+///
+/// 无法折算的手续费不影响数量和金额，计入 `unconverted`。
+fn split_fee(fill: &FillInfo, base_asset: &str, unconverted: &mut i64) -> (Decimal, Decimal, Decimal) {
+    let Some(value) = fill.commission_in_quote(base_asset) else {
+        *unconverted += 1;
+        return (Decimal::ZERO, Decimal::ZERO, Decimal::ZERO);
+    };
+    if fill.commission_asset == base_asset {
+        (fill.commission, Decimal::ZERO, value)
+    } else {
+        (Decimal::ZERO, value, value)
+    }
+}
+
+/// 按先进先出消耗批次，累计处置的成本和分摊的买入手续费
+fn match_lots(disposal: &mut TaxDisposal, lots: &mut VecDeque<TaxLot>) {
+    let mut remaining = disposal.quantity;
+    while remaining > Decimal::ZERO {
+        let Some(lot) = lots.front_mut() else {
+            disposal.unmatched_quantity = remaining;
+            break;
+        };
+
+        let taken = remaining.min(lot.quantity);
+        let (cost, fees) = if taken == lot.quantity {
+            (lot.cost_basis, lot.fees)
+        } else {
+            (lot.cost_basis * taken / lot.quantity, lot.fees * taken / lot.quantity)
+        };
+        disposal.acquired_from.get_or_insert(lot.acquired_at);
+        disposal.acquired_to = Some(lot.acquired_at);
+        disposal.cost_basis += cost;
+        disposal.fees += fees;
+        remaining -= taken;
+
+        lot.quantity -= taken;
+        lot.cost_basis -= cost;
+        lot.fees -= fees;
+        if lot.quantity <= Decimal::ZERO {
+            lots.pop_front();
+        }
+    }
+}
+
+fn open_lots(lots: &BTreeMap<String, VecDeque<TaxLot>>) -> Vec<TaxLot> {
+    lots.values().flatten().cloned().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::testing::{arbitrage_result_at, fill, trade_record};
+    use crate::models::{ArbitrageResult, ArbitrageStatus};
+    use rust_decimal_macros::dec;
+
+    fn trade(id: i64, time: DateTime<Utc>, status: ArbitrageStatus, buy_fill: FillInfo, sell_fill: FillInfo) -> TradeRecord {
+        let result = ArbitrageResult {
+            buy_price: buy_fill.avg_price,
+            sell_price: sell_fill.avg_price,
+            trade_amount: buy_fill.qty,
+            profit: Decimal::ZERO,
+            profit_percentage: Decimal::ZERO,
+            buy_order_id: None,
+            sell_order_id: None,
+            buy_fill,
+            sell_fill,
+            ..arbitrage_result_at(status, time, chrono::Duration::seconds(1))
+        };
+        trade_record(id, result)
+    }
+
+    fn at(year: i32, month: u32, day: u32, hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(year, month, day, hour, 0, 0).unwrap()
+    }
+
+    /// 跨年的固定交易序列，期望值按手工计算
+    fn sequence() -> Vec<TradeRecord> {
+        let none = FillInfo::default();
+        vec![
+            // 2023年内买卖各1个，不出现在2024年的处置中
+            trade(1, at(2023, 12, 20, 0), ArbitrageStatus::Completed,
+                fill(dec!(1), dec!(100), dec!(0.1), "USDT"), fill(dec!(1), dec!(110), dec!(0.11), "USDC")),
+            // 只买入成功，批次结转到2024年：数量1，成本120.12
+            trade(2, at(2023, 12, 31, 0), ArbitrageStatus::Failed,
+                fill(dec!(1), dec!(120), dec!(0.12), "USDT"), none.clone()),
+            // 手续费以BTC收取：批次数量2，成本260.26，手续费0.26
+            // 卖出2个先消耗上一年的批次，再消耗本笔批次的一半
+            trade(3, at(2024, 1, 5, 0), ArbitrageStatus::Completed,
+                fill(dec!(2.002), dec!(130), dec!(0.002), "BTC"), fill(dec!(2), dec!(140), dec!(0.28), "USDC")),
+            // 只卖出成功：消耗剩余的1个，另外0.5个没有成本记录
+            trade(4, at(2024, 3, 1, 0), ArbitrageStatus::Failed,
+                none.clone(), fill(dec!(1.5), dec!(150), Decimal::ZERO, "USDC")),
+            // 两边都没有成交
+            trade(5, at(2024, 6, 1, 0), ArbitrageStatus::Failed, none.clone(), none.clone()),
+            // 年末只买入成功，BNB手续费无法折算，批次结转到下一年
+            trade(6, at(2024, 12, 31, 23), ArbitrageStatus::Failed,
+                fill(dec!(0.5), dec!(200), dec!(0.1), "BNB"), none.clone()),
+            // 下一年的交易被忽略
+            trade(7, at(2025, 1, 2, 0), ArbitrageStatus::Completed,
+                fill(dec!(1), dec!(300), Decimal::ZERO, "USDT"), fill(dec!(1), dec!(310), Decimal::ZERO, "USDC")),
+        ]
+    }
+
+    #[test]
+    fn test_fifo_disposals_across_years() {
+        let mut trades = sequence();
+        // 输入顺序不影响结果
+        trades.reverse();
+        let report = TaxReport::from_trades(2024, &trades, FixedOffset::east_opt(0).unwrap()).unwrap();
+
+        assert_eq!(report.disposals.len(), 2);
+        let first = &report.disposals[0];
+        assert_eq!(first.arbitrage_id, 3);
+        assert_eq!(first.acquired_from, Some(at(2023, 12, 31, 0)));
+        assert_eq!(first.acquired_to, Some(at(2024, 1, 5, 0)));
+        assert_eq!(first.quantity, dec!(2));
+        assert_eq!(first.proceeds, dec!(279.72));
+        assert_eq!(first.cost_basis, dec!(250.25));
+        assert_eq!(first.gain, dec!(29.47));
+        assert_eq!(first.fees, dec!(0.53));
+        assert_eq!(first.unmatched_quantity, Decimal::ZERO);
+
+        let second = &report.disposals[1];
+        assert_eq!(second.arbitrage_id, 4);
+        assert_eq!(second.acquired_from, Some(at(2024, 1, 5, 0)));
+        assert_eq!(second.quantity, dec!(1.5));
+        assert_eq!(second.proceeds, dec!(225));
+        assert_eq!(second.cost_basis, dec!(130.13));
+        assert_eq!(second.gain, dec!(94.87));
+        assert_eq!(second.fees, dec!(0.13));
+        assert_eq!(second.unmatched_quantity, dec!(0.5));
+
+        assert_eq!(report.total_proceeds, dec!(504.72));
+        assert_eq!(report.total_cost_basis, dec!(380.38));
+        assert_eq!(report.total_gain, dec!(124.34));
+        assert_eq!(report.total_fees, dec!(0.66));
+        assert_eq!(report.unconverted_fee_fills, 1);
+
+        assert_eq!(report.opening_lots.len(), 1);
+        assert_eq!(report.opening_lots[0].arbitrage_id, 2);
+        assert_eq!(report.opening_lots[0].cost_basis, dec!(120.12));
+        assert_eq!(report.closing_lots.len(), 1);
+        assert_eq!(report.closing_lots[0].arbitrage_id, 6);
+        assert_eq!(report.closing_lots[0].quantity, dec!(0.5));
+        assert_eq!(report.closing_lots[0].cost_basis, dec!(100));
+    }
+
+    #[test]
+    fn test_year_boundary_uses_offset() {
+        // 东八区2024年从UTC 2023-12-31 16:00开始，UTC 2023-12-31 20:00 的卖出属于2024年
+        let offset = FixedOffset::east_opt(8 * 3600).unwrap();
+        assert_eq!(year_boundary(2024, offset).unwrap(), at(2023, 12, 31, 16));
+
+        let none = FillInfo::default();
+        let trades = vec![
+            trade(1, at(2023, 12, 31, 10), ArbitrageStatus::Failed,
+                fill(dec!(1), dec!(100), Decimal::ZERO, "USDT"), none.clone()),
+            trade(2, at(2023, 12, 31, 20), ArbitrageStatus::Failed,
+                none, fill(dec!(1), dec!(90), Decimal::ZERO, "USDC")),
+        ];
+        let report = TaxReport::from_trades(2024, &trades, offset).unwrap();
+        assert_eq!(report.opening_lots.len(), 1);
+        assert!(report.closing_lots.is_empty());
+        assert_eq!(report.disposals.len(), 1);
+        assert_eq!(report.total_gain, dec!(-10));
+
+        // 年内没有交易时，年初和年末结转相同的批次
+        let report = TaxReport::from_trades(2025, &trades[..1], offset).unwrap();
+        assert!(report.disposals.is_empty());
+        assert_eq!(report.opening_lots, report.closing_lots);
+    }
+}
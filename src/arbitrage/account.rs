@@ -0,0 +1,199 @@
+use crate::binance::ExchangeApi;
+use crate::models::AssetBalance;
+use anyhow::Result;
+use rust_decimal::Decimal;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt::Write;
+
+/// 账户中一个币种的余额、折算价值和风险敞口限制
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BalanceLine {
+    pub asset: String,
+    /// 可用余额
+    pub free: Decimal,
+    /// 挂单等冻结的余额
+    pub locked: Decimal,
+    /// 按当前价格折算的USDT价值，没有该币种的USDT交易对价格时为None
+    pub usdt_value: Option<Decimal>,
+    /// 配置的最大风险敞口，未配置时为None
+    pub exposure_limit: Option<Decimal>,
+    /// 可用余额是否在风险敞口限制内，与风险敞口控制器一样按可用余额比较；未配置限制时为None
+    pub within_limit: Option<bool>,
+}
+
+/// 账户余额概览，用于启动前确认账户状态
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AccountOverview {
+    pub balances: Vec<BalanceLine>,
+    /// 可以折算的币种合计的USDT价值
+    pub total_usdt_value: Decimal,
+}
+
+impl AccountOverview {
+    /// 查询账户余额和当前价格，生成指定币种的概览
+    ///
+    /// `assets` 为None时列出所有余额不为0的币种（按币种名称排序），否则按给定顺序列出，账户中没有的币种余额为0。
+    /// 非USDT币种按 `<币种>USDT` 交易对的价格折算；没有USDCUSDT价格时USDC按1:1折算，与利润计算一致。
+    pub async fn fetch<T: ExchangeApi + ?Sized>(
+        api: &T,
+        assets: Option<&[String]>,
+        max_exposures: &HashMap<String, Decimal>,
+    ) -> Result<Self> {
+        let account = api.get_account_balances().await?;
+        let selected: Vec<AssetBalance> = match assets {
+            Some(assets) => assets
+                .iter()
+                .map(|asset| {
+                    account.iter().find(|balance| &balance.asset == asset).cloned().unwrap_or_else(|| AssetBalance {
+                        asset: asset.clone(),
+                        free: Decimal::ZERO,
+                        locked: Decimal::ZERO,
+                    })
+                })
+                .collect(),
+            None => {
+                let mut balances: Vec<AssetBalance> = account.into_iter().filter(|balance| !balance.total().is_zero()).collect();
+                balances.sort_by(|a, b| a.asset.cmp(&b.asset));
+                balances
+            },
+        };
+
+        let prices: HashMap<String, Decimal> = api
+            .get_prices(&[])
+            .await?
+            .into_iter()
+            .map(|price| (price.symbol, price.price))
+            .collect();
+        let usdt_price = |asset: &str| -> Option<Decimal> {
+            match asset {
+                "USDT" => Some(Decimal::ONE),
+                "USDC" => Some(prices.get("USDCUSDT").copied().unwrap_or(Decimal::ONE)),
+                _ => prices.get(&format!("{}USDT", asset)).copied(),
+            }
+        };
+
+        let balances: Vec<BalanceLine> = selected
+            .into_iter()
+            .map(|balance| {
+                let exposure_limit = max_exposures.get(&balance.asset).copied();
+                BalanceLine {
+                    usdt_value: usdt_price(&balance.asset).map(|price| balance.total() * price),
+                    exposure_limit,
+                    within_limit: exposure_limit.map(|limit| balance.free.abs() <= limit),
+                    asset: balance.asset,
+                    free: balance.free,
+                    locked: balance.locked,
+                }
+            })
+            .collect();
+        let total_usdt_value = balances.iter().filter_map(|line| line.usdt_value).sum();
+
+        Ok(Self { balances, total_usdt_value })
+    }
+
+    /// 是否有币种超出风险敞口限制
+    pub fn exceeds_limits(&self) -> bool {
+        self.balances.iter().any(|line| line.within_limit == Some(false))
+    }
+
+    /// 对齐的表格，最后一行为合计价值
+    pub fn to_table(&self) -> String {
+        const HEADERS: [&str; 6] = ["asset", "free", "locked", "usdt_value", "exposure_limit", "status"];
+        let rows: Vec<[String; 6]> = self
+            .balances
+            .iter()
+            .map(|line| {
+                let optional = |value: Option<Decimal>| value.map(|v| v.normalize().to_string()).unwrap_or_else(|| "-".to_string());
+                [
+                    line.asset.clone(),
+                    line.free.normalize().to_string(),
+                    line.locked.normalize().to_string(),
+                    optional(line.usdt_value.map(|value| value.round_dp(2))),
+                    optional(line.exposure_limit),
+                    match line.within_limit {
+                        Some(true) => "正常".to_string(),
+                        Some(false) => "超出限制".to_string(),
+                        None => "-".to_string(),
+                    },
+                ]
+            })
+            .collect();
+
+        let mut widths = HEADERS.map(str::len);
+        for row in &rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.len());
+            }
+        }
+        let mut out = String::new();
+        let mut write_row = |cells: &[String; 6]| {
+            // 币种和状态左对齐，数值右对齐；状态为最后一列，不需要补齐
+            let line = format!(
+                "{:<w0$}  {:>w1$}  {:>w2$}  {:>w3$}  {:>w4$}  {}",
+                cells[0], cells[1], cells[2], cells[3], cells[4], cells[5],
+                w0 = widths[0], w1 = widths[1], w2 = widths[2], w3 = widths[3], w4 = widths[4]
+            );
+            let _ = writeln!(out, "{}", line.trim_end());
+        };
+        write_row(&HEADERS.map(String::from));
+        for row in &rows {
+            write_row(row);
+        }
+        let _ = writeln!(out, "合计 (USDT): {}", self.total_usdt_value.round_dp(2).normalize());
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::binance::MockBinanceApi;
+    use rust_decimal_macros::dec;
+
+    #[tokio::test]
+    async fn test_selected_assets_with_exposure_limits() {
+        let api = MockBinanceApi::new();
+        let assets: Vec<String> = ["USDT", "USDC", "BTC", "SOL"].iter().map(|s| s.to_string()).collect();
+        let limits = HashMap::from([("BTC".to_string(), dec!(0.5)), ("SOL".to_string(), dec!(10))]);
+
+        let overview = AccountOverview::fetch(&api, Some(&assets), &limits).await.unwrap();
+        let btc = &overview.balances[2];
+        assert_eq!(btc.free, dec!(1));
+        assert_eq!(btc.usdt_value, Some(dec!(50000)));
+        assert_eq!(btc.within_limit, Some(false));
+        // 账户中没有的币种余额为0，没有价格时不折算
+        let sol = &overview.balances[3];
+        assert_eq!((sol.free, sol.usdt_value, sol.within_limit), (Decimal::ZERO, None, Some(true)));
+        assert_eq!(overview.balances[1].usdt_value, Some(dec!(10000)));
+        assert_eq!(overview.total_usdt_value, dec!(70000));
+        assert!(overview.exceeds_limits());
+
+        let expected = "\
+asset   free  locked  usdt_value  exposure_limit  status
+USDT   10000       0       10000               -  -
+USDC   10000       0       10000               -  -
+BTC        1       0       50000             0.5  超出限制
+SOL        0       0           -              10  正常
+合计 (USDT): 70000
+";
+        assert_eq!(overview.to_table(), expected);
+    }
+
+    #[tokio::test]
+    async fn test_all_lists_non_zero_balances() {
+        let api = MockBinanceApi::new();
+        api.set_balance("ETH", Decimal::ZERO);
+        api.set_balance("BNB", dec!(2));
+
+        let overview = AccountOverview::fetch(&api, None, &HashMap::new()).await.unwrap();
+        let assets: Vec<&str> = overview.balances.iter().map(|line| line.asset.as_str()).collect();
+        assert_eq!(assets, vec!["BNB", "BTC", "USDC", "USDT"]);
+        assert_eq!(overview.balances[0].usdt_value, Some(dec!(600)));
+        assert!(!overview.exceeds_limits());
+
+        let json = serde_json::to_value(&overview).unwrap();
+        assert_eq!(json["balances"][0]["asset"], "BNB");
+        assert_eq!(json["balances"][0]["within_limit"], serde_json::Value::Null);
+    }
+}
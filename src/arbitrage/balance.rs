@@ -0,0 +1,108 @@
+use log::debug;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// 正在执行的套利预留的余额，可在多个引擎间共享（`Arc<BalanceLedger>`）
+///
+/// 引擎开始执行套利前按机会的交易金额预留买入所用的计价币余额，返回的预留在套利完成、
+/// 失败或任务被取消时释放。同时执行的多笔套利合计不会超过账户的可用余额。
+#[derive(Debug, Default)]
+pub struct BalanceLedger {
+    reserved: Mutex<HashMap<String, Decimal>>,
+}
+
+impl BalanceLedger {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 可用余额扣除已预留金额后足够时预留 `amount`，否则返回拒绝原因
+    ///
+    /// 检查和预留在同一个锁内完成，并发的套利不会同时通过检查。
+    pub fn try_reserve(self: &Arc<Self>, asset: &str, amount: Decimal, free_balance: Decimal) -> Result<BalanceReservation, String> {
+        let mut reserved = self.reserved.lock().unwrap();
+        let current = reserved.get(asset).copied().unwrap_or(Decimal::ZERO);
+        let available = free_balance - current;
+        if available < amount {
+            return Err(format!(
+                "{} 可用余额 {} 扣除已预留的 {} 后不足本次交易金额 {}",
+                asset, free_balance, current, amount
+            ));
+        }
+
+        reserved.insert(asset.to_string(), current + amount);
+        debug!("预留 {} {}，当前已预留: {}", amount, asset, current + amount);
+        Ok(BalanceReservation {
+            ledger: self.clone(),
+            asset: asset.to_string(),
+            amount,
+        })
+    }
+}
+
+/// 一笔正在执行的套利预留的余额，释放时从账本中扣除
+#[derive(Debug)]
+pub struct BalanceReservation {
+    ledger: Arc<BalanceLedger>,
+    asset: String,
+    amount: Decimal,
+}
+
+impl BalanceReservation {
+    /// 预留的金额
+    pub fn amount(&self) -> Decimal {
+        self.amount
+    }
+}
+
+impl Drop for BalanceReservation {
+    fn drop(&mut self) {
+        let mut reserved = self.ledger.reserved.lock().unwrap();
+        if let Some(current) = reserved.get_mut(&self.asset) {
+            *current -= self.amount;
+            debug!("释放预留的 {} {}，当前已预留: {}", self.amount, self.asset, current);
+            if current.is_zero() {
+                reserved.remove(&self.asset);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn reserved(ledger: &BalanceLedger, asset: &str) -> Decimal {
+        ledger.reserved.lock().unwrap().get(asset).copied().unwrap_or(Decimal::ZERO)
+    }
+
+    #[test]
+    fn test_reservations_limit_concurrent_trades() {
+        let ledger = Arc::new(BalanceLedger::new());
+
+        let first = ledger.try_reserve("USDT", dec!(100), dec!(150)).unwrap();
+        assert_eq!(first.amount(), dec!(100));
+        assert_eq!(reserved(&ledger, "USDT"), dec!(100));
+
+        // 扣除第一笔预留后只剩 50
+        let reason = ledger.try_reserve("USDT", dec!(100), dec!(150)).unwrap_err();
+        assert!(reason.contains("不足本次交易金额 100"));
+        assert_eq!(reserved(&ledger, "USDT"), dec!(100));
+
+        // 其他币种互不影响
+        let usdc = ledger.try_reserve("USDC", dec!(100), dec!(100)).unwrap();
+        assert_eq!(reserved(&ledger, "USDC"), dec!(100));
+
+        // 释放后可以再次预留
+        drop(first);
+        assert_eq!(reserved(&ledger, "USDT"), Decimal::ZERO);
+        let second = ledger.try_reserve("USDT", dec!(100), dec!(150)).unwrap();
+        assert_eq!(reserved(&ledger, "USDT"), dec!(100));
+
+        drop(second);
+        drop(usdc);
+        assert_eq!(reserved(&ledger, "USDC"), Decimal::ZERO);
+    }
+}
@@ -0,0 +1,112 @@
+use crate::config::OpportunityDedupSettings;
+use crate::models::{ArbitrageOpportunity, QuoteCurrency};
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// 判断两个机会是否相同的键：资产、买卖方向和四舍五入后的利润率
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct DedupKey {
+    base_asset: String,
+    buy_quote: QuoteCurrency,
+    sell_quote: QuoteCurrency,
+    spread: Decimal,
+}
+
+/// 最近执行过的套利机会，用于在短时间内跳过几乎相同的机会
+///
+/// 内部加锁，可以在多个任务之间共享。
+#[derive(Debug, Default)]
+pub struct OpportunityDedup {
+    /// 每个键最近一次执行的时间
+    seen: Mutex<HashMap<DedupKey, Instant>>,
+}
+
+impl OpportunityDedup {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn key(opportunity: &ArbitrageOpportunity, settings: &OpportunityDedupSettings) -> DedupKey {
+        DedupKey {
+            base_asset: opportunity.base_asset.clone(),
+            buy_quote: opportunity.buy_quote,
+            sell_quote: opportunity.sell_quote,
+            spread: opportunity.profit_percentage.round_dp(settings.spread_decimal_places).normalize(),
+        }
+    }
+
+    /// 窗口内是否已执行过相同的机会，未启用去重时总是返回false
+    pub fn is_duplicate(&self, opportunity: &ArbitrageOpportunity, settings: &OpportunityDedupSettings, now: Instant) -> bool {
+        if settings.window_ms == 0 {
+            return false;
+        }
+        let window = Duration::from_millis(settings.window_ms);
+        let mut seen = self.seen.lock().unwrap();
+        // 顺便清理过期的记录，避免价差不断变化时无限增长
+        seen.retain(|_, at| now.saturating_duration_since(*at) < window);
+        seen.contains_key(&Self::key(opportunity, settings))
+    }
+
+    /// 记录执行了该机会，窗口内相同的机会将被跳过
+    pub fn record(&self, opportunity: &ArbitrageOpportunity, settings: &OpportunityDedupSettings, now: Instant) {
+        if settings.window_ms == 0 {
+            return;
+        }
+        self.seen.lock().unwrap().insert(Self::key(opportunity, settings), now);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn settings(window_ms: u64) -> OpportunityDedupSettings {
+        OpportunityDedupSettings { window_ms, spread_decimal_places: 2 }
+    }
+
+    fn opportunity(buy: QuoteCurrency, sell: QuoteCurrency, buy_price: Decimal, sell_price: Decimal) -> ArbitrageOpportunity {
+        ArbitrageOpportunity::new("BTC", buy, sell, buy_price, sell_price, dec!(1000))
+    }
+
+    #[test]
+    fn test_identical_opportunities_suppressed_within_window() {
+        let dedup = OpportunityDedup::new();
+        let settings = settings(1000);
+        let now = Instant::now();
+        let first = opportunity(QuoteCurrency::USDT, QuoteCurrency::USDC, dec!(50000), dec!(50100));
+
+        assert!(!dedup.is_duplicate(&first, &settings, now));
+        dedup.record(&first, &settings, now);
+        assert!(dedup.is_duplicate(&first, &settings, now + Duration::from_millis(500)));
+        // 利润率相差不到0.01%视为同一个机会
+        let close = opportunity(QuoteCurrency::USDT, QuoteCurrency::USDC, dec!(50000), dec!(50101));
+        assert!(dedup.is_duplicate(&close, &settings, now + Duration::from_millis(500)));
+
+        // 方向、资产或价差不同的机会不受影响
+        let reversed = opportunity(QuoteCurrency::USDC, QuoteCurrency::USDT, dec!(50000), dec!(50100));
+        assert!(!dedup.is_duplicate(&reversed, &settings, now));
+        let wider = opportunity(QuoteCurrency::USDT, QuoteCurrency::USDC, dec!(50000), dec!(50200));
+        assert!(!dedup.is_duplicate(&wider, &settings, now));
+        let eth = ArbitrageOpportunity::new("ETH", QuoteCurrency::USDT, QuoteCurrency::USDC, dec!(50000), dec!(50100), dec!(1000));
+        assert!(!dedup.is_duplicate(&eth, &settings, now));
+
+        // 窗口过后再次执行，过期记录被清理
+        assert!(!dedup.is_duplicate(&first, &settings, now + Duration::from_millis(1000)));
+        assert!(dedup.seen.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_disabled_when_window_is_zero() {
+        let dedup = OpportunityDedup::new();
+        let settings = settings(0);
+        let now = Instant::now();
+        let first = opportunity(QuoteCurrency::USDT, QuoteCurrency::USDC, dec!(50000), dec!(50100));
+
+        dedup.record(&first, &settings, now);
+        assert!(!dedup.is_duplicate(&first, &settings, now));
+        assert!(dedup.seen.lock().unwrap().is_empty());
+    }
+}
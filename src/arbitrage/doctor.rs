@@ -0,0 +1,281 @@
+use crate::binance::ExchangeApi;
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use std::fmt;
+use std::time::Instant;
+
+/// 本机时钟与服务器时间允许的最大偏差（毫秒）
+///
+/// 币安拒绝时间戳比服务器时间快1000毫秒以上的签名请求，慢的一侧受 `recvWindow`（默认5000毫秒）限制，这里按较严格的一侧检查。
+pub const MAX_CLOCK_SKEW_MS: i64 = 1000;
+
+/// 单项检查的结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    Pass,
+    Fail,
+    /// 依赖的检查失败，无法进行
+    Skipped,
+}
+
+impl fmt::Display for CheckStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CheckStatus::Pass => write!(f, "通过"),
+            CheckStatus::Fail => write!(f, "失败"),
+            CheckStatus::Skipped => write!(f, "跳过"),
+        }
+    }
+}
+
+/// 一项检查
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ConnectionCheck {
+    pub name: String,
+    pub status: CheckStatus,
+    /// 通过时为查询到的信息，失败时为原因和处理建议
+    pub detail: String,
+}
+
+impl ConnectionCheck {
+    fn new(name: impl Into<String>, status: CheckStatus, detail: impl Into<String>) -> Self {
+        Self { name: name.into(), status, detail: detail.into() }
+    }
+}
+
+/// 按本机时间和服务器时间检查时钟偏差
+fn clock_skew_check(local_time: DateTime<Utc>, server_time: DateTime<Utc>) -> ConnectionCheck {
+    let skew_ms = (local_time - server_time).num_milliseconds();
+    let direction = if skew_ms >= 0 { "快" } else { "慢" };
+    if skew_ms.abs() <= MAX_CLOCK_SKEW_MS {
+        ConnectionCheck::new(
+            "时钟偏差",
+            CheckStatus::Pass,
+            format!("本机时钟比服务器{} {} ms", direction, skew_ms.abs()),
+        )
+    } else {
+        ConnectionCheck::new(
+            "时钟偏差",
+            CheckStatus::Fail,
+            format!(
+                "本机时钟比服务器{} {} ms，超过 {} ms，签名请求会被拒绝，请同步系统时间",
+                direction, skew_ms.abs(), MAX_CLOCK_SKEW_MS
+            ),
+        )
+    }
+}
+
+/// 启动前的连接自检，确认网络、时钟、API密钥、余额和交易对都已就绪
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ConnectionReport {
+    pub checks: Vec<ConnectionCheck>,
+}
+
+impl ConnectionReport {
+    /// 依次检查交易所连接、时钟偏差、API密钥、余额和基础资产的两个交易对
+    ///
+    /// `min_balance` 为USDT和USDC各自至少需要的可用余额，通常为每笔最大交易金额；
+    /// 任一报价货币不足时，从该货币买入方向的套利无法执行。
+    pub async fn run<T: ExchangeApi + ?Sized>(api: &T, base_asset: &str, min_balance: Decimal) -> Self {
+        let mut checks = Vec::new();
+
+        let sent_at = Utc::now();
+        let started = Instant::now();
+        match api.get_server_time().await {
+            Ok(server_time) => {
+                let round_trip = started.elapsed();
+                checks.push(ConnectionCheck::new(
+                    "连接交易所",
+                    CheckStatus::Pass,
+                    format!("响应 {} ms", round_trip.as_millis()),
+                ));
+
+                // 服务器时间近似对应请求往返的中点
+                let local_time = sent_at + chrono::Duration::from_std(round_trip / 2).unwrap_or_else(|_| chrono::Duration::zero());
+                checks.push(clock_skew_check(local_time, server_time));
+            },
+            Err(e) => {
+                checks.push(ConnectionCheck::new(
+                    "连接交易所",
+                    CheckStatus::Fail,
+                    format!("{:#}，请检查网络和 base_url 配置", e),
+                ));
+                checks.push(ConnectionCheck::new("时钟偏差", CheckStatus::Skipped, "无法获取服务器时间"));
+            },
+        }
+
+        match api.get_account_balances().await {
+            Ok(balances) => {
+                checks.push(ConnectionCheck::new(
+                    "API密钥",
+                    CheckStatus::Pass,
+                    format!("签名请求成功，账户有 {} 个币种", balances.len()),
+                ));
+
+                let free = |asset: &str| {
+                    balances.iter().find(|balance| balance.asset == asset).map_or(Decimal::ZERO, |balance| balance.free)
+                };
+                let (usdt, usdc) = (free("USDT"), free("USDC"));
+                let detail = format!("USDT {}, USDC {}", usdt.normalize(), usdc.normalize());
+                let insufficient: Vec<&str> = [("USDT", usdt), ("USDC", usdc)]
+                    .into_iter()
+                    .filter(|(_, amount)| *amount < min_balance)
+                    .map(|(asset, _)| asset)
+                    .collect();
+                if insufficient.is_empty() {
+                    checks.push(ConnectionCheck::new("余额", CheckStatus::Pass, detail));
+                } else {
+                    checks.push(ConnectionCheck::new(
+                        "余额",
+                        CheckStatus::Fail,
+                        format!(
+                            "{}，{} 低于每笔交易金额 {}，无法从该货币买入",
+                            detail,
+                            insufficient.join("、"),
+                            min_balance.normalize()
+                        ),
+                    ));
+                }
+            },
+            Err(e) => {
+                checks.push(ConnectionCheck::new(
+                    "API密钥",
+                    CheckStatus::Fail,
+                    format!("{:#}，请检查API Key、Secret、IP白名单和现货交易权限", e),
+                ));
+                checks.push(ConnectionCheck::new("余额", CheckStatus::Skipped, "API密钥无效，无法查询余额"));
+            },
+        }
+
+        for quote in ["USDT", "USDC"] {
+            let symbol = format!("{}{}", base_asset.to_uppercase(), quote);
+            let name = format!("交易对 {}", symbol);
+            let listed = match api.get_symbol_info(&symbol).await {
+                Ok(_) => api.get_price(&symbol).await,
+                Err(e) => Err(e),
+            };
+            checks.push(match listed {
+                Ok(price) => ConnectionCheck::new(name, CheckStatus::Pass, format!("最新价格 {}", price.price.normalize())),
+                Err(e) => ConnectionCheck::new(name, CheckStatus::Fail, format!("{:#}", e)),
+            });
+        }
+
+        Self { checks }
+    }
+
+    /// 是否所有检查都通过
+    pub fn all_passed(&self) -> bool {
+        self.checks.iter().all(|check| check.status == CheckStatus::Pass)
+    }
+}
+
+impl fmt::Display for ConnectionReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for check in &self.checks {
+            writeln!(f, "[{}] {}: {}", check.status, check.name, check.detail)?;
+        }
+        let failed = self.checks.iter().filter(|check| check.status != CheckStatus::Pass).count();
+        if failed == 0 {
+            write!(f, "全部 {} 项检查通过", self.checks.len())
+        } else {
+            write!(f, "{} 项检查未通过", failed)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::binance::MockBinanceApi;
+    use rust_decimal_macros::dec;
+
+    fn statuses(report: &ConnectionReport) -> Vec<(&str, CheckStatus)> {
+        report.checks.iter().map(|check| (check.name.as_str(), check.status)).collect()
+    }
+
+    #[tokio::test]
+    async fn test_all_checks_pass_against_mock() {
+        let api = MockBinanceApi::new();
+        let report = ConnectionReport::run(&api, "btc", dec!(100)).await;
+
+        assert!(report.all_passed());
+        assert_eq!(
+            statuses(&report),
+            vec![
+                ("连接交易所", CheckStatus::Pass),
+                ("时钟偏差", CheckStatus::Pass),
+                ("API密钥", CheckStatus::Pass),
+                ("余额", CheckStatus::Pass),
+                ("交易对 BTCUSDT", CheckStatus::Pass),
+                ("交易对 BTCUSDC", CheckStatus::Pass),
+            ]
+        );
+
+        let output = report.to_string();
+        assert!(output.contains("[通过] 余额: USDT 10000, USDC 10000\n"));
+        assert!(output.contains("[通过] 交易对 BTCUSDC: 最新价格 50025\n"));
+        assert!(output.ends_with("全部 6 项检查通过"));
+    }
+
+    #[tokio::test]
+    async fn test_clock_skew_fails() {
+        let api = MockBinanceApi::new();
+        api.set_server_time_offset(chrono::Duration::seconds(-3));
+        let report = ConnectionReport::run(&api, "BTC", dec!(100)).await;
+
+        assert!(!report.all_passed());
+        // 偏差包含请求往返时间的估计误差，只检查状态
+        assert_eq!(report.checks[1].status, CheckStatus::Fail);
+        assert!(report.to_string().contains("[失败] 时钟偏差: 本机时钟比服务器快 "));
+        assert!(report.to_string().ends_with("1 项检查未通过"));
+    }
+
+    #[test]
+    fn test_clock_skew_check_reports_direction_and_limit() {
+        let server_time = Utc::now();
+
+        let check = clock_skew_check(server_time + chrono::Duration::milliseconds(3000), server_time);
+        assert_eq!(check.status, CheckStatus::Fail);
+        assert_eq!(check.detail, "本机时钟比服务器快 3000 ms，超过 1000 ms，签名请求会被拒绝，请同步系统时间");
+
+        let check = clock_skew_check(server_time - chrono::Duration::milliseconds(MAX_CLOCK_SKEW_MS), server_time);
+        assert_eq!(check.status, CheckStatus::Pass);
+        assert_eq!(check.detail, "本机时钟比服务器慢 1000 ms");
+    }
+
+    #[tokio::test]
+    async fn test_invalid_api_key_skips_balance_check() {
+        let api = MockBinanceApi::new();
+        api.set_invalid_api_key(true);
+        let report = ConnectionReport::run(&api, "BTC", dec!(100)).await;
+
+        assert_eq!(report.checks[2].status, CheckStatus::Fail);
+        assert!(report.checks[2].detail.contains("Invalid API-key"));
+        assert_eq!(report.checks[3].status, CheckStatus::Skipped);
+        // 行情接口不需要API密钥
+        assert_eq!(report.checks[4].status, CheckStatus::Pass);
+        assert!(report.to_string().contains("[跳过] 余额: API密钥无效，无法查询余额\n"));
+    }
+
+    #[tokio::test]
+    async fn test_insufficient_balance_and_missing_pair_fail() {
+        let api = MockBinanceApi::new();
+        api.set_balance("USDC", dec!(50));
+        let report = ConnectionReport::run(&api, "SOL", dec!(100)).await;
+
+        assert_eq!(
+            statuses(&report)[3..],
+            [
+                ("余额", CheckStatus::Fail),
+                ("交易对 SOLUSDT", CheckStatus::Fail),
+                ("交易对 SOLUSDC", CheckStatus::Fail),
+            ]
+        );
+        let output = report.to_string();
+        assert!(output.contains("[失败] 余额: USDT 10000, USDC 50，USDC 低于每笔交易金额 100，无法从该货币买入\n"));
+        assert!(output.contains("[失败] 交易对 SOLUSDT: 价格不可用: SOLUSDT\n"));
+        assert!(output.ends_with("3 项检查未通过"));
+    }
+}
@@ -1,26 +1,402 @@
+use super::balance::{BalanceLedger, BalanceReservation};
+use super::dedup::OpportunityDedup;
+use super::inventory::InventorySkew;
+use super::order_updates::OrderUpdates;
+use super::profit::{FeeProfitCalculator, ProfitCalculator};
+use super::profit_distribution::{ProfitDistributionState, ProfitDistributor};
+use super::ranking::{best_execution_opportunity, price_volatility, select_best_opportunity, ExecutionCosts};
+use super::shutdown::{SessionSummary, ShutdownSignal};
+use super::spread::{fetch_pair_prices, spread_percentage};
 use crate::binance::ExchangeApi;
-use crate::config::{Config, StrategyType, RiskControllerType};
-use crate::models::{ArbitrageOpportunity, ArbitrageResult, ArbitrageStatus, OrderStatus, Price, QuoteCurrency, Side};
-use crate::strategies::{TradingStrategy, SimpleArbitrageStrategy, TimeWeightedAverageStrategy, OrderBookDepthStrategy, SlippageControlStrategy, TrendFollowingStrategy};
-use crate::risk::{RiskManager, RiskController, DailyLossLimitController, AbnormalPriceController, ExposureController, TradingTimeWindowController, TradingFrequencyController, PairBlacklistController};
-use crate::db::DatabaseManager;
+use crate::config::{ArbitrageSettings, Config, ConfigError, DustAction, OpportunityRanking, StrategyType, RiskControllerType};
+use crate::models::{ArbitrageOpportunity, ArbitrageResult, ArbitrageStatus, DustConversion, FillInfo, OrderInfo, OrderStatus, OrderType, Price, PriceSource, QuoteCurrency, RejectionReason, Side, TimeInForce, TradingMode, UserDataEvent};
+use crate::strategies::{TradingStrategy, SimpleArbitrageStrategy, TimeWeightedAverageStrategy, OrderBookDepthStrategy, SlippageControlStrategy, TrendFollowingStrategy, CompositeStrategy, LiquidityGuard};
+use crate::risk::{RiskManager, RiskController, RiskControllerState, DailyLossLimitController, AbnormalPriceController, ExposureController, TradingTimeWindowController, TradingFrequencyController, PairBlacklistController, OpenPositionTracker, OpenPositionsController};
+use crate::market_data::{PriceCache, PriceCacheUsage};
+use crate::metrics::Metrics;
+use crate::db::{DatabaseManager, DatabaseHealth, OrderRecord, RejectedOpportunity, TradeHistoryFilter, TradeRecord, WriteMetricsSnapshot};
 use anyhow::{anyhow, Context, Result};
+use chrono::Utc;
+use futures::StreamExt;
 use log::{debug, info, warn, error};
+use serde::{Deserialize, Deserializer, Serialize};
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
-use std::sync::Arc;
-use tokio::time::{sleep, Duration};
-use std::collections::HashMap;
+use std::sync::{Arc, Mutex, RwLock};
+use std::sync::atomic::{AtomicBool, Ordering};
+use tokio::time::{sleep, Duration, Instant};
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+use tokio::sync::mpsc;
+
+/// 余额不足跳过机会时记录到风控拒绝指标中的组件名称
+const BALANCE_RESERVATION: &str = "余额预留";
+/// 价差不足以覆盖手续费跳过机会时记录到风控拒绝指标中的组件名称
+const ROUND_TRIP_FEES: &str = "手续费";
+/// 交易金额低于盈亏平衡金额跳过机会时记录到风控拒绝指标中的组件名称
+const NOTIONAL_FLOOR: &str = "最小交易金额";
+/// 基础资产被管理接口临时加入黑名单跳过机会时记录到风控拒绝指标中的组件名称
+const RUNTIME_BLACKLIST: &str = "运行时黑名单";
+
+/// 等待市价单成交或结束的最长时间，限价单按 `limit_order_timeout_ms` 等待
+const MARKET_ORDER_WAIT_TIMEOUT: Duration = Duration::from_secs(10);
+/// 未使用用户数据流时轮询订单状态的间隔
+const ORDER_POLL_INTERVAL: Duration = Duration::from_millis(1000);
+/// listenKey 的续期间隔，listenKey 60分钟未续期即失效
+const LISTEN_KEY_KEEPALIVE_INTERVAL: Duration = Duration::from_secs(30 * 60);
+/// 用户数据流断开后重新连接的间隔
+const USER_DATA_RECONNECT_DELAY: Duration = Duration::from_secs(5);
+/// 内存中保留的最近套利结果数量，不依赖数据库
+const RECENT_RESULTS_CAPACITY: usize = 50;
+
+/// 单次运行（会话）的交易限制，达到任一限制后监控循环正常退出
+///
+/// 与每日亏损风控不同，风控只拒绝新的交易，会话限制会结束 `monitor_opportunities`。
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SessionLimits {
+    /// 最多执行的套利次数（包括失败的套利）
+    pub max_trades: Option<u64>,
+    /// 会话累计利润达到该值后停止
+    pub max_profit: Option<Decimal>,
+    /// 会话累计亏损达到该值（正数）后停止
+    pub max_loss: Option<Decimal>,
+}
+
+impl SessionLimits {
+    /// 是否设置了任何限制
+    pub fn is_unlimited(&self) -> bool {
+        self.max_trades.is_none() && self.max_profit.is_none() && self.max_loss.is_none()
+    }
+
+    /// 检查会话统计是否达到限制，返回达到的限制说明
+    pub fn reached(&self, stats: &SessionStats) -> Option<String> {
+        if let Some(max_trades) = self.max_trades {
+            if stats.trades >= max_trades {
+                return Some(format!("交易次数达到 {}", max_trades));
+            }
+        }
+        if let Some(max_profit) = self.max_profit {
+            if stats.profit >= max_profit {
+                return Some(format!("累计利润 {} 达到 {}", stats.profit, max_profit));
+            }
+        }
+        if let Some(max_loss) = self.max_loss {
+            if stats.profit <= -max_loss {
+                return Some(format!("累计亏损 {} 达到 {}", -stats.profit, max_loss));
+            }
+        }
+        None
+    }
+}
+
+/// 会话内的交易统计
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct SessionStats {
+    /// 执行的套利次数
+    pub trades: u64,
+    /// 成功的套利次数
+    pub successful_trades: u64,
+    /// 失败的套利次数
+    pub failed_trades: u64,
+    /// 累计利润（扣除手续费）
+    pub profit: Decimal,
+    /// 可以折算为报价货币的手续费合计，以BNB等其他币种收取的手续费不计入
+    pub fees: Decimal,
+    /// 利润最高的一笔套利的利润，没有交易时为None
+    pub best_profit: Option<Decimal>,
+    /// 利润最低（亏损最多）的一笔套利的利润，没有交易时为None
+    pub worst_profit: Option<Decimal>,
+}
+
+impl SessionStats {
+    /// 累计一次套利结果
+    pub fn record(&mut self, result: &ArbitrageResult) {
+        self.trades += 1;
+        if result.status == ArbitrageStatus::Completed {
+            self.successful_trades += 1;
+        } else {
+            self.failed_trades += 1;
+        }
+        self.profit += result.profit;
+        self.fees += [&result.buy_fill, &result.sell_fill]
+            .iter()
+            .filter_map(|fill| fill.commission_in_quote(&result.base_asset))
+            .sum::<Decimal>();
+        self.best_profit = Some(self.best_profit.map_or(result.profit, |best| best.max(result.profit)));
+        self.worst_profit = Some(self.worst_profit.map_or(result.profit, |worst| worst.min(result.profit)));
+    }
+    
+    /// 成功次数占套利次数的百分比，没有交易时为None
+    pub fn success_rate(&self) -> Option<Decimal> {
+        (self.trades > 0).then(|| Decimal::from(self.successful_trades) / Decimal::from(self.trades) * dec!(100))
+    }
+}
+
+/// 运行时可调整的引擎参数，监控循环每轮读取最新的值
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RuntimeSettings {
+    /// 覆盖的最小利润率（百分比），为None时只使用策略配置的阈值
+    ///
+    /// 在策略验证之后额外检查，利润率低于该值的机会不执行。
+    pub min_profit_percentage: Option<Decimal>,
+    /// 单次套利的最大交易金额（USDT），策略给出的交易金额超过时按该值执行
+    pub max_trade_amount_usdt: Decimal,
+    /// 价格检查间隔，毫秒
+    pub check_interval_ms: u64,
+    /// 只记录通过风控的套利机会，不实际下单
+    pub dry_run: bool,
+    /// 是否被手动暂停交易，与系统维护状态相互独立
+    pub paused: bool,
+    /// 运行时加入黑名单的基础资产，不执行这些资产的套利，重启后清空
+    pub blacklisted_assets: BTreeSet<String>,
+}
+
+impl RuntimeSettings {
+    /// 由配置文件中的套利参数创建
+    pub fn from_config(settings: &ArbitrageSettings) -> Self {
+        Self {
+            min_profit_percentage: None,
+            max_trade_amount_usdt: settings.max_trade_amount_usdt,
+            check_interval_ms: settings.check_interval_ms,
+            dry_run: settings.dry_run,
+            paused: false,
+            blacklisted_assets: BTreeSet::new(),
+        }
+    }
+
+    fn validate(&self) -> Result<()> {
+        if let Some(value) = self.min_profit_percentage {
+            if value.is_sign_negative() {
+                return Err(anyhow!("最小利润率不能为负数: {}", value));
+            }
+        }
+        if self.max_trade_amount_usdt <= Decimal::ZERO {
+            return Err(anyhow!("最大交易金额必须大于0: {}", self.max_trade_amount_usdt));
+        }
+        if self.check_interval_ms == 0 {
+            return Err(anyhow!("价格检查间隔必须大于0"));
+        }
+        Ok(())
+    }
+}
+
+/// 运行时参数的部分更新，为None的字段保持不变
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SettingsUpdate {
+    /// `Some(None)` 表示取消最小利润率覆盖（JSON中为 `null`），字段缺失时不修改
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub min_profit_percentage: Option<Option<Decimal>>,
+    #[serde(default)]
+    pub max_trade_amount_usdt: Option<Decimal>,
+    #[serde(default)]
+    pub check_interval_ms: Option<u64>,
+    #[serde(default)]
+    pub dry_run: Option<bool>,
+    #[serde(default)]
+    pub paused: Option<bool>,
+}
+
+/// 区分字段缺失和显式的 `null`
+fn deserialize_some<'de, D, T>(deserializer: D) -> std::result::Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    T::deserialize(deserializer).map(Some)
+}
+
+impl SettingsUpdate {
+    fn apply(&self, settings: &mut RuntimeSettings) {
+        if let Some(value) = self.min_profit_percentage {
+            settings.min_profit_percentage = value;
+        }
+        if let Some(value) = self.max_trade_amount_usdt {
+            settings.max_trade_amount_usdt = value;
+        }
+        if let Some(value) = self.check_interval_ms {
+            settings.check_interval_ms = value;
+        }
+        if let Some(value) = self.dry_run {
+            settings.dry_run = value;
+        }
+        if let Some(value) = self.paused {
+            settings.paused = value;
+        }
+    }
+}
+
+/// 紧急清仓的结果
+#[derive(Debug, Clone, Serialize)]
+pub struct LiquidationReport {
+    /// 清仓的基础资产
+    pub asset: String,
+    /// 卖出使用的交易对
+    pub symbol: String,
+    /// 清仓前的基础资产余额
+    pub quantity: Decimal,
+    /// 市价卖单，余额为0时为None
+    pub order: Option<OrderInfo>,
+}
+
+/// 小额资产的处理结果
+#[derive(Debug, Clone, Serialize)]
+pub enum DustOutcome {
+    /// 只记录，未处理
+    Reported,
+    /// 已兑换为BNB
+    Converted(DustConversion),
+    /// 已提交市价卖单
+    Sold(OrderInfo),
+}
+
+/// 一次小额资产清理的结果
+#[derive(Debug, Clone, Serialize)]
+pub struct DustSweep {
+    /// 基础资产
+    pub asset: String,
+    /// 清理前的基础资产余额
+    pub quantity: Decimal,
+    /// 小额资产的数量上限（不含）
+    pub threshold: Decimal,
+    pub outcome: DustOutcome,
+}
+
+/// 预热进度，从第一轮成功获取行情开始计算
+#[derive(Debug, Default)]
+struct WarmupProgress {
+    /// 已观察的行情轮数
+    ticks: u64,
+    started_at: Option<tokio::time::Instant>,
+    completed: bool,
+}
+
+/// 套利结果写入数据库的连续失败情况，用于 `max_consecutive_db_failures`
+#[derive(Default)]
+struct DbWriteFailures {
+    /// 连续写入失败的次数
+    consecutive: u32,
+    /// 是否因连续写入失败暂停交易
+    paused: bool,
+    /// 启用暂停时未能写入、等待重试的套利结果及其订单
+    unsaved: Vec<(ArbitrageResult, Vec<OrderRecord>)>,
+    /// 上次同步时写入队列的已写入记录数
+    writer_written: u64,
+    /// 上次同步时写入队列的失败记录数
+    writer_failed: u64,
+}
+
+/// 一次套利中已提交的订单，释放时从引擎跟踪的活动订单中移除，但保留在引擎提交过的订单中
+struct TrackedOrders<'a> {
+    active: &'a Mutex<HashSet<u64>>,
+    placed: &'a Mutex<HashSet<u64>>,
+    order_ids: Vec<u64>,
+}
+
+impl<'a> TrackedOrders<'a> {
+    fn new(active: &'a Mutex<HashSet<u64>>, placed: &'a Mutex<HashSet<u64>>) -> Self {
+        Self { active, placed, order_ids: Vec::new() }
+    }
+    
+    fn track(&mut self, order_id: u64) {
+        self.active.lock().unwrap().insert(order_id);
+        self.placed.lock().unwrap().insert(order_id);
+        self.order_ids.push(order_id);
+    }
+}
+
+impl Drop for TrackedOrders<'_> {
+    fn drop(&mut self) {
+        let mut active = self.active.lock().unwrap();
+        for order_id in &self.order_ids {
+            active.remove(order_id);
+        }
+    }
+}
+
+/// 引擎运行状态快照
+#[derive(Debug, Clone, Serialize)]
+pub struct EngineStatus {
+    /// 运行模式，写入每笔交易记录
+    pub mode: TradingMode,
+    /// 生效配置的指纹，写入每笔交易记录
+    pub config_hash: String,
+    /// 是否因交易所系统维护而暂停交易
+    pub trading_paused: bool,
+    /// 是否被手动暂停交易
+    pub manually_paused: bool,
+    /// 是否因连续未能写入数据库而暂停交易
+    pub db_write_paused: bool,
+    /// 是否处于启动预热期，预热期间不交易
+    pub warming_up: bool,
+    /// 正在执行的套利数量，共享计数时包括其他引擎的套利
+    pub open_positions: usize,
+    /// 当前生效的运行时参数
+    pub runtime_settings: RuntimeSettings,
+    /// 当前会话的交易统计
+    pub session: SessionStats,
+    /// 最近一次数据库健康检查的结果，未设置数据库时为None
+    pub database: Option<DatabaseHealth>,
+    /// 异步写入队列的指标，未启用写入队列时为None
+    pub write_metrics: Option<WriteMetricsSnapshot>,
+    /// 行情缓存的行情条数和估算的内存占用
+    pub price_cache: PriceCacheUsage,
+    /// 各风控组件的当前状态，按启用顺序排列
+    pub risk: Vec<RiskControllerState>,
+}
 
 /// 套利引擎，使用多种交易策略和风控机制进行USDT和USDC之间的套利
 pub struct ArbitrageEngine<T: ExchangeApi + Send + Sync + 'static> {
     api: Arc<T>,
-    config: Config,
+    /// 当前生效的配置，热加载配置时整体替换
+    config: RwLock<Config>,
     base_asset: String,
-    strategies: Vec<Box<dyn TradingStrategy>>,
-    risk_manager: RiskManager,
+    /// 启用的策略，循环中取快照使用，热加载时整体替换
+    strategies: RwLock<Arc<Vec<Box<dyn TradingStrategy>>>>,
+    risk_manager: RwLock<Arc<RiskManager>>,
+    /// 每轮获取的行情，与风控组件共享
+    price_cache: Arc<PriceCache>,
+    /// 正在执行的套利计数，与最大持仓数风控共享
+    open_positions: Arc<OpenPositionTracker>,
+    /// 正在执行的套利预留的计价币余额
+    balance_ledger: Arc<BalanceLedger>,
+    /// 正在执行的套利中已提交的订单，过期挂单清理不会取消这些订单
+    active_orders: Mutex<HashSet<u64>>,
+    /// 本次运行中套利提交过的全部订单，紧急停止和结束会话只取消其中仍未结束的挂单
+    placed_orders: Mutex<HashSet<u64>>,
+    /// 用户数据流推送的订单状态
+    order_updates: OrderUpdates,
+    /// 运行指标，可在多个引擎间共享
+    metrics: Arc<Metrics>,
     // 添加数据库管理器
     db_manager: Option<Arc<DatabaseManager>>,
+    /// 运行模式，写入每笔交易记录
+    mode: TradingMode,
+    /// 生效配置的指纹，热加载配置时更新
+    config_hash: RwLock<String>,
+    /// 交易所系统维护期间暂停交易
+    trading_paused: AtomicBool,
+    /// 运行时可调整的参数，包括手动暂停标志
+    runtime_settings: Arc<RwLock<RuntimeSettings>>,
+    /// 会话交易限制
+    session_limits: SessionLimits,
+    /// 会话内的交易统计
+    session_stats: Mutex<SessionStats>,
+    /// 本会话最近的套利结果，从早到晚排列
+    recent_results: Mutex<VecDeque<ArbitrageResult>>,
+    /// 累计待分配的利润
+    profit_distributor: ProfitDistributor,
+    /// 启动预热进度
+    warmup: Mutex<WarmupProgress>,
+    /// 最近执行过的套利机会，窗口内相同的机会不再执行
+    opportunity_dedup: OpportunityDedup,
+    /// 自定义的利润计算方式，未设置时按配置的手续费使用 `FeeProfitCalculator`
+    profit_calculator: Option<Arc<dyn ProfitCalculator>>,
+    /// 套利结果写入数据库的连续失败情况
+    db_write_failures: Mutex<DbWriteFailures>,
+    /// 优雅停止的请求标志，可与信号处理任务共享
+    shutdown: ShutdownSignal,
+    /// 引擎创建时间，用于会话汇总的运行时间
+    started_at: Instant,
 }
 
 impl<T: ExchangeApi + Send + Sync + 'static> ArbitrageEngine<T> {
@@ -30,158 +406,59 @@ impl<T: ExchangeApi + Send + Sync + 'static> ArbitrageEngine<T> {
         // 保留原有的实现代码...
         let api_arc = Arc::new(api);
         
-        // 初始化交易策略
-        let mut strategies: Vec<Box<dyn TradingStrategy>> = Vec::new();
-        
-        // 根据配置启用的策略类型初始化相应的策略
-        for strategy_type in &config.strategy_settings.enabled_strategies {
-            match strategy_type {
-                StrategyType::Simple => {
-                    info!("启用简单价格差异套利策略");
-                    strategies.push(Box::new(SimpleArbitrageStrategy::new(config.clone())));
-                },
-                StrategyType::TimeWeighted => {
-                    info!("启用时间加权平均价格(TWAP)套利策略");
-                    let settings = &config.strategy_settings.twap;
-                    strategies.push(Box::new(TimeWeightedAverageStrategy::new(
-                        config.clone(),
-                        settings.slices,
-                        settings.interval_seconds,
-                    )));
-                },
-                StrategyType::OrderBookDepth => {
-                    info!("启用订单簿深度分析套利策略");
-                    let settings = &config.strategy_settings.order_book_depth;
-                    strategies.push(Box::new(OrderBookDepthStrategy::new(
-                        config.clone(),
-                        api_arc.clone(),
-                        settings.depth_levels,
-                        Decimal::from_f64(settings.min_liquidity).unwrap_or(dec!(1.0)),
-                    )));
-                },
-                StrategyType::SlippageControl => {
-                    info!("启用滑点控制套利策略");
-                    let settings = &config.strategy_settings.slippage_control;
-                    strategies.push(Box::new(SlippageControlStrategy::new(
-                        config.clone(),
-                        Decimal::from_f64(settings.max_slippage_pct).unwrap_or(dec!(0.5)),
-                        settings.volatility_window_size,
-                    )));
-                },
-                StrategyType::TrendFollowing => {
-                    info!("启用趋势跟踪套利策略");
-                    let settings = &config.strategy_settings.trend_following;
-                    strategies.push(Box::new(TrendFollowingStrategy::new(
-                        config.clone(),
-                        settings.short_window,
-                        settings.long_window,
-                        Decimal::from_f64(settings.trend_threshold).unwrap_or(dec!(1.0)),
-                    )));
-                },
-            }
-        }
-        
-        // 如果没有启用任何策略，则默认使用简单策略
-        if strategies.is_empty() {
-            info!("未配置任何策略，使用默认的简单价格差异套利策略");
-            strategies.push(Box::new(SimpleArbitrageStrategy::new(config.clone())));
-        }
+        let strategies = Self::build_strategies(&config, &api_arc);
+        let price_cache = Arc::new(PriceCache::from_settings(&config.arbitrage_settings.price_cache));
+        let open_positions = Arc::new(OpenPositionTracker::new());
+        let risk_manager = Self::build_risk_manager(&config, &api_arc, &price_cache, &open_positions);
         
-        // 初始化风控管理器
-        let mut risk_manager = RiskManager::new(config.clone());
+        let runtime_settings = RuntimeSettings::from_config(&config.arbitrage_settings);
+        runtime_settings.validate().context("无效的套利参数")?;
         
-        // 根据配置启用的风控类型初始化相应的控制器
-        for controller_type in &config.risk_settings.enabled_controllers {
-            match controller_type {
-                RiskControllerType::DailyLossLimit => {
-                    info!("启用每日亏损限制风控");
-                    risk_manager.add_controller(DailyLossLimitController::new(
-                        Decimal::from_f64(config.risk_settings.daily_loss_limit.max_daily_loss).unwrap_or(dec!(50.0))
-                    ));
-                },
-                // ... 其他风控初始化代码 ...
-                // 保留原有的风控初始化代码...
-                RiskControllerType::AbnormalPrice => {
-                    info!("启用异常价格保护风控");
-                    let settings = &config.risk_settings.abnormal_price;
-                    risk_manager.add_controller(AbnormalPriceController::new(
-                        settings.window_size,
-                        Decimal::from_f64(settings.abnormal_threshold).unwrap_or(dec!(5.0)),
-                        settings.cooldown_period,
-                    ));
-                },
-                RiskControllerType::Exposure => {
-                    info!("启用风险敞口控制风控");
-                    let mut exposure_controller = ExposureController::new(api_arc.clone());
-                    
-                    // 设置每种币的最大风险敞口
-                    for (asset, max_exposure) in &config.risk_settings.exposure.max_exposures {
-                        exposure_controller.set_max_exposure(
-                            asset, 
-                            Decimal::from_f64(*max_exposure).unwrap_or(Decimal::MAX)
-                        );
-                    }
-                    
-                    risk_manager.add_controller(exposure_controller);
-                },
-                RiskControllerType::TradingTimeWindow => {
-                    info!("启用交易时间窗口风控");
-                    let settings = &config.risk_settings.trading_time_window;
-                    
-                    if let Ok(controller) = TradingTimeWindowController::new(
-                        settings.start_hour,
-                        settings.start_minute,
-                        settings.end_hour,
-                        settings.end_minute,
-                        settings.trade_on_weekends,
-                    ) {
-                        risk_manager.add_controller(controller);
-                    } else {
-                        warn!("无法创建交易时间窗口控制器，时间设置无效");
-                    }
-                },
-                RiskControllerType::TradingFrequency => {
-                    info!("启用交易频率控制风控");
-                    let settings = &config.risk_settings.trading_frequency;
-                    risk_manager.add_controller(TradingFrequencyController::new(
-                        settings.min_interval_seconds,
-                        settings.max_trades_per_timeframe,
-                        settings.timeframe_seconds,
-                    ));
-                },
-                RiskControllerType::PairBlacklist => {
-                    info!("启用交易对黑名单风控");
-                    let controller = PairBlacklistController::new();
-                    
-                    // 添加黑名单交易对
-                    for pair in &config.risk_settings.pair_blacklist.blacklisted_pairs {
-                        let pair_str = pair.as_str();
-                        
-                        if pair_str.ends_with("USDT") {
-                            let base = &pair_str[0..pair_str.len() - 4];
-                            controller.add_to_blacklist(base, "USDT");
-                        } else if pair_str.ends_with("USDC") {
-                            let base = &pair_str[0..pair_str.len() - 4];
-                            controller.add_to_blacklist(base, "USDC");
-                        } else {
-                            warn!("无效的交易对格式: {}, 应该以USDT或USDC结尾", pair);
-                        }
-                    }
-                    
-                    risk_manager.add_controller(controller);
-                },
-            }
-        }
+        let config_hash = config.fingerprint();
+        info!("配置指纹: {}", config_hash);
         
         Ok(Self {
             api: api_arc,
-            config,
+            config: RwLock::new(config),
             base_asset: base_asset.to_string(),
-            strategies,
-            risk_manager,
+            strategies: RwLock::new(Arc::new(strategies)),
+            risk_manager: RwLock::new(Arc::new(risk_manager)),
+            price_cache,
+            open_positions,
+            balance_ledger: Arc::new(BalanceLedger::new()),
+            active_orders: Mutex::new(HashSet::new()),
+            placed_orders: Mutex::new(HashSet::new()),
+            order_updates: OrderUpdates::new(),
+            metrics: Arc::new(Metrics::new()),
             db_manager: None,
+            mode: TradingMode::Live,
+            config_hash: RwLock::new(config_hash),
+            trading_paused: AtomicBool::new(false),
+            runtime_settings: Arc::new(RwLock::new(runtime_settings)),
+            session_limits: SessionLimits::default(),
+            session_stats: Mutex::new(SessionStats::default()),
+            recent_results: Mutex::new(VecDeque::with_capacity(RECENT_RESULTS_CAPACITY)),
+            profit_distributor: ProfitDistributor::new(),
+            warmup: Mutex::new(WarmupProgress::default()),
+            opportunity_dedup: OpportunityDedup::new(),
+            profit_calculator: None,
+            db_write_failures: Mutex::new(DbWriteFailures::default()),
+            shutdown: ShutdownSignal::new(),
+            started_at: Instant::now(),
         })
     }
+    
+    /// 使用外部的停止标志，例如由Ctrl-C信号处理任务触发
+    pub fn set_shutdown_signal(&mut self, shutdown: ShutdownSignal) {
+        self.shutdown = shutdown;
+    }
+    
+    /// 请求优雅停止：监控循环在本轮结束后返回，正在执行的套利照常完成
+    pub fn request_shutdown(&self) {
+        if self.shutdown.request() {
+            warn!("收到停止请求，不再开始新的套利");
+        }
+    }
 
     /// 设置数据库管理器
     pub fn set_db_manager(&mut self, db_manager: DatabaseManager) {
@@ -189,288 +466,3840 @@ impl<T: ExchangeApi + Send + Sync + 'static> ArbitrageEngine<T> {
         info!("已设置数据库管理器，套利结果将被记录");
     }
 
-    /// 持续监控币对价格，寻找套利机会
-    pub async fn monitor_opportunities(&self) -> Result<()> {
-        info!("开始监控 {}-USDT/USDC 套利机会", self.base_asset);
-        
-        loop {
-            if let Ok(opportunity) = self.find_best_arbitrage_opportunity().await {
-                // 验证风控规则
-                let (is_valid, rejection_reasons) = self.risk_manager.validate_opportunity(&opportunity).await?;
-                
-                if !is_valid {
-                    for reason in rejection_reasons {
-                        warn!("风控拒绝: {}", reason);
-                    }
-                    debug!("套利机会被风控拒绝，跳过");
-                } else {
-                    // 如果通过风控，执行套利
-                    info!(
-                        "发现套利机会: {} 买入: {} {}, 卖出: {} {}, 价差: {}, 利润率: {}%",
-                        opportunity.base_asset,
-                        opportunity.buy_quote,
-                        opportunity.buy_price,
-                        opportunity.sell_quote,
-                        opportunity.sell_price,
-                        opportunity.price_diff,
-                        opportunity.profit_percentage
-                    );
-                    
-                    match self.execute_arbitrage(&opportunity).await {
-                        Ok(result) => {
-                            info!(
-                                "套利完成: {} 利润: {} ({}%)",
-                                result.base_asset, result.profit, result.profit_percentage
-                            );
-                            
-                            // 记录交易结果
-                            self.risk_manager.record_result(&result).await?;
-                            
-                            // 如果设置了数据库，保存套利结果
-                            if let Some(db) = &self.db_manager {
-                                match db.record_arbitrage_result(&result).await {
-                                    Ok(id) => {
-                                        info!("已记录套利结果到数据库: ID={}", id);
-                                    },
-                                    Err(e) => {
-                                        error!("记录套利结果到数据库失败: {}", e);
-                                    }
-                                }
-                            }
-                        }
-                        Err(e) => {
-                            error!("套利执行失败: {}", e);
-                            
-                            // 创建失败结果并记录
-                            let failed_result = ArbitrageResult {
-                                base_asset: opportunity.base_asset.clone(),
-                                buy_quote: opportunity.buy_quote.to_string(),
-                                sell_quote: opportunity.sell_quote.to_string(),
-                                buy_price: opportunity.buy_price,
-                                sell_price: opportunity.sell_price,
-                                trade_amount: Decimal::ZERO,
-                                profit: Decimal::ZERO,
-                                profit_percentage: Decimal::ZERO,
-                                buy_order_id: None,
-                                sell_order_id: None,
-                                status: ArbitrageStatus::Failed,
-                                timestamp: opportunity.timestamp,
-                            };
-                            
-                            self.risk_manager.record_result(&failed_result).await?;
-                            
-                            // 如果设置了数据库，保存失败记录
-                            if let Some(db) = &self.db_manager {
-                                if let Err(e) = db.record_arbitrage_result(&failed_result).await {
-                                    error!("记录失败的套利结果到数据库失败: {}", e);
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-            
-            // 等待指定的时间间隔
-            sleep(Duration::from_millis(self.config.arbitrage_settings.check_interval_ms)).await;
+    /// 设置运行模式，默认为实盘交易
+    pub fn set_trading_mode(&mut self, mode: TradingMode) {
+        self.mode = mode;
+        info!("运行模式: {}", mode);
+    }
+    
+    /// 运行模式
+    pub fn trading_mode(&self) -> TradingMode {
+        self.mode
+    }
+    
+    /// 套利的基础资产
+    pub fn base_asset(&self) -> &str {
+        &self.base_asset
+    }
+    
+    /// 当前生效配置的指纹
+    pub fn config_hash(&self) -> String {
+        self.config_hash.read().unwrap().clone()
+    }
+    
+    /// 引擎共享的行情缓存
+    pub fn price_cache(&self) -> Arc<PriceCache> {
+        self.price_cache.clone()
+    }
+
+    /// 使用共享的行情缓存，多个引擎共用一个缓存时 `max_total_records` 限制所有交易对合计的行情条数
+    pub fn set_price_cache(&mut self, cache: Arc<PriceCache>) {
+        self.price_cache = cache;
+        let config = self.config.read().unwrap().clone();
+        *self.risk_manager.write().unwrap() = Arc::new(Self::build_risk_manager(&config, &self.api, &self.price_cache, &self.open_positions));
+    }
+
+    /// 使用自定义的利润计算方式，估算机会和结算成交都按该方式计算
+    pub fn set_profit_calculator(&mut self, calculator: Arc<dyn ProfitCalculator>) {
+        self.profit_calculator = Some(calculator);
+    }
+
+    /// 当前使用的利润计算方式，未自定义时按当前配置的手续费构造
+    pub fn profit_calculator(&self) -> Arc<dyn ProfitCalculator> {
+        match &self.profit_calculator {
+            Some(calculator) => calculator.clone(),
+            None => Arc::new(FeeProfitCalculator::from_config(&self.config.read().unwrap())),
         }
     }
+
+    /// 正在执行的套利预留的余额
+    pub fn balance_ledger(&self) -> Arc<BalanceLedger> {
+        self.balance_ledger.clone()
+    }
     
-    // ... existing code ...
-    // 保留原有的其他方法实现...
+    /// 使用共享的余额预留，共用同一账户的多个引擎应共享同一个账本
+    pub fn set_balance_ledger(&mut self, ledger: Arc<BalanceLedger>) {
+        self.balance_ledger = ledger;
+    }
 
-    /// 使用所有启用的策略寻找最佳套利机会
-    async fn find_best_arbitrage_opportunity(&self) -> Result<ArbitrageOpportunity> {
-        // 构造交易对名称
-        let usdt_symbol = format!("{}{}", self.base_asset, "USDT");
-        let usdc_symbol = format!("{}{}", self.base_asset, "USDC");
+    /// 用户数据流是否已连接，未连接时通过轮询获取订单状态
+    pub fn user_data_stream_connected(&self) -> bool {
+        self.order_updates.is_connected()
+    }
+
+    /// 引擎更新的运行指标
+    pub fn metrics(&self) -> Arc<Metrics> {
+        self.metrics.clone()
+    }
+    
+    /// 使用共享的运行指标，例如由指标接口导出的注册表
+    pub fn set_metrics(&mut self, metrics: Arc<Metrics>) {
+        self.metrics = metrics;
+    }
+
+    /// 设置会话交易限制
+    pub fn set_session_limits(&mut self, limits: SessionLimits) {
+        if !limits.is_unlimited() {
+            info!("会话限制: 最多交易 {:?} 次, 利润上限 {:?}, 亏损上限 {:?}", limits.max_trades, limits.max_profit, limits.max_loss);
+        }
+        self.session_limits = limits;
+    }
+    
+    /// 当前会话的交易统计
+    pub fn session_stats(&self) -> SessionStats {
+        self.session_stats.lock().unwrap().clone()
+    }
+    
+    /// 本会话最近的套利结果（最多50笔），按结束时间从晚到早，未设置数据库时同样可用
+    pub fn recent_results(&self) -> Vec<ArbitrageResult> {
+        self.recent_results.lock().unwrap().iter().rev().cloned().collect()
+    }
+    
+    /// 利润分配的累计状态
+    pub fn profit_distribution(&self) -> ProfitDistributionState {
+        self.profit_distributor.state()
+    }
+    
+    /// 当前运行状态快照
+    pub fn status(&self) -> EngineStatus {
+        EngineStatus {
+            mode: self.mode,
+            config_hash: self.config_hash(),
+            trading_paused: self.is_trading_paused(),
+            manually_paused: self.is_manually_paused(),
+            db_write_paused: self.is_db_write_paused(),
+            warming_up: self.is_warming_up(),
+            open_positions: self.open_positions.count(),
+            runtime_settings: self.runtime_settings(),
+            session: self.session_stats(),
+            database: self.db_manager.as_ref().map(|db| db.health()),
+            write_metrics: self.db_manager.as_ref().and_then(|db| db.write_metrics()),
+            price_cache: self.price_cache.usage(),
+            risk: self.risk_controller_states(),
+        }
+    }
+    
+    /// 检查数据库连接，返回检查结果，未设置数据库时返回None
+    ///
+    /// 数据库不可用时不暂停交易，写入失败的结果由写入队列的重试缓冲暂存。
+    pub async fn check_database_health(&self) -> Option<DatabaseHealth> {
+        match &self.db_manager {
+            Some(db) => Some(db.health_check().await),
+            None => None,
+        }
+    }
+    
+    /// 累计会话统计，达到会话限制时返回限制说明
+    fn record_session_result(&self, result: &ArbitrageResult) -> Option<String> {
+        if result.status == ArbitrageStatus::Completed {
+            self.metrics.record_trade_completed(result.profit);
+        } else {
+            self.metrics.record_trade_failed();
+        }
         
-        // 获取价格
-        let usdt_price = self.api.get_price(&usdt_symbol).await?;
-        let usdc_price = self.api.get_price(&usdc_symbol).await?;
+        {
+            let mut recent = self.recent_results.lock().unwrap();
+            if recent.len() == RECENT_RESULTS_CAPACITY {
+                recent.pop_front();
+            }
+            recent.push_back(result.clone());
+        }
         
-        debug!("{} 价格: {}", usdt_symbol, usdt_price.price);
-        debug!("{} 价格: {}", usdc_symbol, usdc_price.price);
+        let mut stats = self.session_stats.lock().unwrap();
+        stats.record(result);
+        self.session_limits.reached(&stats)
+    }
+    
+    /// 交易是否因交易所系统维护而暂停
+    pub fn is_trading_paused(&self) -> bool {
+        self.trading_paused.load(Ordering::SeqCst)
+    }
+    
+    /// 手动暂停交易，监控循环继续运行但不再执行套利
+    pub fn pause(&self) {
+        let was_paused = std::mem::replace(&mut self.runtime_settings.write().unwrap().paused, true);
+        if !was_paused {
+            warn!("交易已手动暂停");
+        }
+    }
+    
+    /// 解除手动暂停
+    pub fn resume(&self) {
+        let was_paused = std::mem::replace(&mut self.runtime_settings.write().unwrap().paused, false);
+        if was_paused {
+            info!("已解除手动暂停，恢复交易");
+        }
+    }
+    
+    /// 将基础资产加入运行时黑名单，返回是否新加入
+    pub fn blacklist_asset(&self, asset: &str) -> bool {
+        let added = self.runtime_settings.write().unwrap().blacklisted_assets.insert(asset.to_uppercase());
+        if added {
+            warn!("{} 已加入运行时黑名单，不再执行该资产的套利", asset.to_uppercase());
+        }
+        added
+    }
+    
+    /// 将基础资产移出运行时黑名单，返回是否在黑名单中
+    pub fn unblacklist_asset(&self, asset: &str) -> bool {
+        let removed = self.runtime_settings.write().unwrap().blacklisted_assets.remove(&asset.to_uppercase());
+        if removed {
+            info!("{} 已移出运行时黑名单", asset.to_uppercase());
+        }
+        removed
+    }
+    
+    /// 基础资产是否在运行时黑名单中
+    pub fn is_asset_blacklisted(&self, asset: &str) -> bool {
+        self.runtime_settings.read().unwrap().blacklisted_assets.contains(asset)
+    }
+    
+    /// 交易是否被手动暂停
+    pub fn is_manually_paused(&self) -> bool {
+        self.runtime_settings.read().unwrap().paused
+    }
+    
+    /// 交易是否因连续未能将套利结果写入数据库而暂停
+    pub fn is_db_write_paused(&self) -> bool {
+        self.db_write_failures.lock().unwrap().paused
+    }
+    
+    /// 将套利结果及其订单写入数据库（或写入队列），未设置数据库时不做任何操作
+    ///
+    /// 设置了 `max_consecutive_db_failures` 时，写入失败的结果保留下来等待重试，连续失败达到该次数后暂停交易。
+    async fn save_result(&self, result: &ArbitrageResult, orders: Vec<OrderRecord>) {
+        let Some(db) = &self.db_manager else {
+            return;
+        };
+        let retained = self.max_consecutive_db_failures() > 0;
+        let retry_orders = if retained { orders.clone() } else { Vec::new() };
+        match db.queue_arbitrage_result_with_orders(result, orders).await {
+            Ok(()) => self.record_db_write(true),
+            Err(e) => {
+                error!("记录套利结果到数据库失败 ({:?}): {}", result.status, e);
+                self.metrics.record_db_write_failure();
+                if retained {
+                    self.db_write_failures.lock().unwrap().unsaved.push((result.clone(), retry_orders));
+                }
+                self.record_db_write(false);
+            }
+        }
+    }
+    
+    /// 配置的连续写入失败次数上限，0表示不暂停
+    fn max_consecutive_db_failures(&self) -> u32 {
+        self.config.read().unwrap().arbitrage_settings.max_consecutive_db_failures
+    }
+    
+    /// 记录一次写入的结果：失败时累计连续失败次数，达到上限后暂停交易；成功时清零并恢复交易
+    fn record_db_write(&self, success: bool) {
+        let limit = self.max_consecutive_db_failures();
+        let mut failures = self.db_write_failures.lock().unwrap();
+        if success {
+            if failures.paused {
+                info!("数据库写入已恢复 (此前连续失败 {} 次)，恢复交易", failures.consecutive);
+            }
+            failures.consecutive = 0;
+            failures.paused = false;
+            return;
+        }
         
-        let mut best_opportunity: Option<ArbitrageOpportunity> = None;
-        let mut best_profit = Decimal::ZERO;
+        failures.consecutive += 1;
+        if limit > 0 && failures.consecutive >= limit && !failures.paused {
+            failures.paused = true;
+            error!("连续 {} 次未能将套利结果写入数据库，暂停交易直到写入恢复", failures.consecutive);
+        }
+    }
+    
+    /// 按写入队列的指标同步连续失败次数：有新写入的记录视为写入成功，否则有新失败的记录视为写入失败
+    ///
+    /// 入队时丢弃的记录已由 `save_result` 按返回的错误计数，这里只统计后台写入失败的记录。
+    fn sync_writer_failures(&self) {
+        let Some(write_metrics) = self.db_manager.as_ref().and_then(|db| db.write_metrics()) else {
+            return;
+        };
+        let failed = write_metrics.failed;
+        let (written, new_failures) = {
+            let mut failures = self.db_write_failures.lock().unwrap();
+            let written = write_metrics.written > failures.writer_written;
+            let new_failures = failed.saturating_sub(failures.writer_failed);
+            failures.writer_written = write_metrics.written;
+            failures.writer_failed = failed;
+            (written, new_failures)
+        };
+        if written {
+            self.record_db_write(true);
+        } else {
+            for _ in 0..new_failures {
+                self.record_db_write(false);
+            }
+        }
+    }
+    
+    /// 重试写入之前未能写入的套利结果，遇到失败时停止，剩余的结果继续保留
+    ///
+    /// 这些结果首次写入失败时已经计数，重试失败不再累计连续失败次数。
+    async fn retry_unsaved_results(&self) {
+        let Some(db) = &self.db_manager else {
+            return;
+        };
+        let mut unsaved = std::mem::take(&mut self.db_write_failures.lock().unwrap().unsaved);
+        if unsaved.is_empty() {
+            return;
+        }
         
-        // 使用每个策略寻找机会
-        for strategy in &self.strategies {
-            match strategy.find_opportunity(&self.base_asset, &usdt_price, &usdc_price).await {
-                Ok(Some(opportunity)) => {
-                    // 验证是否符合策略要求
-                    match strategy.validate_opportunity(&opportunity).await {
-                        Ok(true) => {
-                            if opportunity.profit_percentage > best_profit {
-                                best_profit = opportunity.profit_percentage;
-                                best_opportunity = Some(opportunity);
-                                debug!(
-                                    "发现更优套利机会 (策略: {}): 利润率 {}%, 价差: {}",
-                                    strategy.name(), opportunity.profit_percentage, opportunity.price_diff
-                                );
-                            }
-                        },
-                        Ok(false) => {
-                            debug!(
-                                "策略 {} 发现机会但验证失败: 利润率 {}% 不足",
-                                strategy.name(), opportunity.profit_percentage
-                            );
-                        },
-                        Err(e) => {
-                            warn!("策略 {} 验证出错: {}", strategy.name(), e);
-                        }
-                    }
-                },
-                Ok(None) => {
-                    debug!("策略 {} 未发现有效套利机会", strategy.name());
-                },
-                Err(e) => {
-                    warn!("策略 {} 寻找机会出错: {}", strategy.name(), e);
-                }
+        info!("重试写入 {} 条未保存的套利结果", unsaved.len());
+        while let Some((result, orders)) = unsaved.first() {
+            if let Err(e) = db.queue_arbitrage_result_with_orders(result, orders.clone()).await {
+                warn!("重试写入套利结果失败: {}", e);
+                self.metrics.record_db_write_failure();
+                let mut failures = self.db_write_failures.lock().unwrap();
+                unsaved.append(&mut failures.unsaved);
+                failures.unsaved = unsaved;
+                return;
             }
+            unsaved.remove(0);
+            self.record_db_write(true);
+        }
+    }
+    
+    /// 配置的预热轮数和时长
+    fn warmup_settings(&self) -> (u64, Duration) {
+        let config = self.config.read().unwrap();
+        (
+            config.arbitrage_settings.warmup_ticks,
+            Duration::from_secs(config.arbitrage_settings.warmup_seconds),
+        )
+    }
+    
+    /// 是否处于启动预热期
+    pub fn is_warming_up(&self) -> bool {
+        let (ticks, duration) = self.warmup_settings();
+        !self.warmup.lock().unwrap().completed && (ticks > 0 || !duration.is_zero())
+    }
+    
+    /// 记录一轮行情，返回本轮是否仍处于预热期
+    fn observe_warmup_tick(&self) -> bool {
+        let (warmup_ticks, warmup_duration) = self.warmup_settings();
+        let mut warmup = self.warmup.lock().unwrap();
+        if warmup.completed {
+            return false;
         }
         
-        // 如果没有找到任何机会，创建一个基本的机会（默认使用简单策略的逻辑）
-        if best_opportunity.is_none() {
-            let max_trade_amount = Decimal::from(self.config.arbitrage_settings.max_trade_amount_usdt);
-            
-            let opportunity = if usdt_price.price < usdc_price.price {
-                // USDT买入，USDC卖出
-                ArbitrageOpportunity::new(
-                    &self.base_asset,
-                    QuoteCurrency::USDT,
-                    QuoteCurrency::USDC,
-                    usdt_price.price,
-                    usdc_price.price,
-                    max_trade_amount,
-                )
-            } else {
-                // USDC买入，USDT卖出
-                ArbitrageOpportunity::new(
-                    &self.base_asset,
-                    QuoteCurrency::USDC,
-                    QuoteCurrency::USDT,
-                    usdc_price.price,
-                    usdt_price.price,
-                    max_trade_amount,
-                )
-            };
-            
-            return Ok(opportunity);
+        let started_at = *warmup.started_at.get_or_insert_with(tokio::time::Instant::now);
+        warmup.ticks += 1;
+        if warmup.ticks <= warmup_ticks || started_at.elapsed() < warmup_duration {
+            return true;
         }
         
-        Ok(best_opportunity.unwrap())
+        warmup.completed = true;
+        if warmup_ticks > 0 || !warmup_duration.is_zero() {
+            info!(
+                "预热完成: 已观察 {} 轮行情，用时 {:.1} 秒，开始交易",
+                warmup.ticks - 1,
+                started_at.elapsed().as_secs_f64()
+            );
+        }
+        false
     }
     
-    /// 执行套利交易
-    async fn execute_arbitrage(&self, opportunity: &ArbitrageOpportunity) -> Result<ArbitrageResult> {
-        // 计算交易量
-        let trade_amount_quote = opportunity.max_trade_amount;
-        let trade_amount_base = trade_amount_quote / opportunity.buy_price;
+    /// 当前的运行时参数
+    pub fn runtime_settings(&self) -> RuntimeSettings {
+        self.runtime_settings.read().unwrap().clone()
+    }
+    
+    /// 运行时最小利润率（百分比），None表示只使用策略配置的阈值
+    pub fn min_profit_percentage(&self) -> Option<Decimal> {
+        self.runtime_settings.read().unwrap().min_profit_percentage
+    }
+    
+    /// 单次套利的最大交易金额（USDT）
+    pub fn max_trade_amount(&self) -> Decimal {
+        self.runtime_settings.read().unwrap().max_trade_amount_usdt
+    }
+    
+    /// 价格检查间隔
+    pub fn check_interval(&self) -> Duration {
+        Duration::from_millis(self.runtime_settings.read().unwrap().check_interval_ms)
+    }
+    
+    /// 是否只记录套利机会而不下单
+    pub fn is_dry_run(&self) -> bool {
+        self.runtime_settings.read().unwrap().dry_run
+    }
+    
+    /// 更新运行时参数，校验失败时保持原有参数不变，返回更新后的参数
+    ///
+    /// 正在执行的套利不受影响，监控循环从下一轮开始使用新的参数。
+    pub fn update_settings(&self, update: SettingsUpdate) -> Result<RuntimeSettings> {
+        let mut settings = self.runtime_settings.write().unwrap();
+        let mut updated = settings.clone();
+        update.apply(&mut updated);
+        updated.validate()?;
         
-        let mut result = ArbitrageResult {
-            base_asset: opportunity.base_asset.clone(),
-            buy_quote: opportunity.buy_quote.to_string(),
-            sell_quote: opportunity.sell_quote.to_string(),
-            buy_price: opportunity.buy_price,
-            sell_price: opportunity.sell_price,
-            trade_amount: trade_amount_base,
-            profit: Decimal::ZERO,
-            profit_percentage: opportunity.profit_percentage,
-            buy_order_id: None,
-            sell_order_id: None,
-            status: ArbitrageStatus::Identified,
-            timestamp: opportunity.timestamp,
-        };
+        if updated != *settings {
+            info!(
+                "运行时参数已更新: 最小利润率 {:?}, 最大交易金额 {} USDT, 检查间隔 {} ms, 只记录不下单 {}, 手动暂停 {}",
+                updated.min_profit_percentage, updated.max_trade_amount_usdt, updated.check_interval_ms, updated.dry_run, updated.paused
+            );
+        }
+        *settings = updated.clone();
+        Ok(updated)
+    }
+    
+    /// 设置运行时最小利润率（百分比），None表示取消覆盖
+    pub fn set_min_profit_percentage(&self, value: Option<Decimal>) -> Result<()> {
+        self.update_settings(SettingsUpdate { min_profit_percentage: Some(value), ..SettingsUpdate::default() })?;
+        Ok(())
+    }
+    
+    /// 当前的风控管理器
+    fn risk_manager(&self) -> Arc<RiskManager> {
+        self.risk_manager.read().unwrap().clone()
+    }
+    
+    /// 当前生效的配置
+    pub fn config(&self) -> Config {
+        self.config.read().unwrap().clone()
+    }
+    
+    /// 热加载配置，返回变化的字段
+    ///
+    /// 新配置校验失败时返回错误并保持原有配置。套利参数更新到运行时参数（保留手动暂停和
+    /// 运行时最小利润率覆盖）；策略参数通过 `TradingStrategy::update_params` 原地更新，风控参数通过
+    /// `RiskManager::update_settings` 原地更新，保留价格历史、当日亏损等状态。组件不支持原地更新时重新创建，
+    /// 启用的风控组件变化时同样重新创建，重新创建的组件不保留之前的状态。API密钥、接口地址（包括备用地址）、API跟踪、
+    /// 管理接口设置和启用的策略列表需要重启才能生效，文件中的变化被忽略并记录在日志中。
+    pub fn reload_config(&self, mut new_config: Config) -> Result<Vec<String>> {
+        new_config.validate().map_err(ConfigError::combine).context("新配置无效")?;
         
-        // 构造交易对
-        let buy_symbol = format!("{}{}", opportunity.base_asset, opportunity.buy_quote);
-        let sell_symbol = format!("{}{}", opportunity.base_asset, opportunity.sell_quote);
+        let mut config = self.config.write().unwrap();
         
-        info!("执行套利交易 - 买入: {} @ {}, 卖出: {} @ {}, 数量: {}", 
-            buy_symbol, opportunity.buy_price,
-            sell_symbol, opportunity.sell_price,
-            trade_amount_base
-        );
+        // 以下设置需要重启才能生效，部分可能来自命令行参数或环境变量，保持启动时的值
+        let requested = new_config.clone();
+        new_config.api_key = config.api_key.clone();
+        new_config.api_secret = config.api_secret.clone();
+        new_config.secret = config.secret.clone();
+        new_config.active_profile = config.active_profile.clone();
+        new_config.base_url = config.base_url.clone();
+        new_config.fallback_base_urls = config.fallback_base_urls.clone();
+        new_config.trace_api = config.trace_api;
+        new_config.admin_api = config.admin_api.clone();
+        new_config.strategy_settings.enabled_strategies = config.strategy_settings.enabled_strategies.clone();
+        // 交易对由启动参数的基础资产决定
+        new_config.arbitrage_settings.usdt_symbol = config.arbitrage_settings.usdt_symbol.clone();
+        new_config.arbitrage_settings.usdc_symbol = config.arbitrage_settings.usdc_symbol.clone();
         
-        // 执行买入订单
-        let buy_order = match self.api.place_order(&buy_symbol, Side::Buy, trade_amount_base, None).await {
-            Ok(order) => {
-                info!("买入订单已提交: ID={}, 状态={:?}", order.order_id, order.status);
-                result.buy_order_id = Some(order.order_id);
-                result.status = ArbitrageStatus::BuyOrderPlaced;
-                order
-            },
-            Err(e) => {
-                result.status = ArbitrageStatus::Failed;
-                return Err(anyhow!("买入订单失败: {}", e));
+        for ignored in new_config.diff(&requested) {
+            warn!("配置变化需要重启才能生效，已忽略: {}", ignored);
+        }
+        
+        let changes = config.diff(&new_config);
+        if changes.is_empty() {
+            info!("配置未变化");
+            return Ok(changes);
+        }
+        
+        let changed = |prefix: &str| changes.iter().any(|c| c.starts_with(prefix));
+        let arbitrage_changed = changed("arbitrage_settings.");
+        // 策略读取的套利参数
+        let strategy_changed = changed("strategy_settings.")
+            || changed("arbitrage_settings.min_profit_percentage:")
+            || changed("arbitrage_settings.max_trade_amount_usdt:")
+            || changed("arbitrage_settings.price_diff_threshold:");
+        let risk_changed = changed("risk_settings.");
+        
+        if arbitrage_changed {
+            let mut runtime = self.runtime_settings.write().unwrap();
+            let reloaded = RuntimeSettings::from_config(&new_config.arbitrage_settings);
+            *runtime = RuntimeSettings {
+                min_profit_percentage: runtime.min_profit_percentage,
+                paused: runtime.paused,
+                blacklisted_assets: runtime.blacklisted_assets.clone(),
+                ..reloaded
+            };
+        }
+        if strategy_changed {
+            let strategies = self.strategies.read().unwrap().clone();
+            if strategies.iter().all(|strategy| strategy.update_params(&new_config)) {
+                info!("策略参数已更新");
+            } else {
+                warn!("部分策略不支持原地更新参数，重新创建策略，之前的价格历史不保留");
+                *self.strategies.write().unwrap() = Arc::new(Self::build_strategies(&new_config, &self.api));
             }
-        };
+        }
+        if risk_changed {
+            let risk_manager = self.risk_manager();
+            if config.risk_settings.enabled_controllers != new_config.risk_settings.enabled_controllers {
+                info!("启用的风控组件变化，重新创建风控组件");
+                *self.risk_manager.write().unwrap() = Arc::new(Self::build_risk_manager(&new_config, &self.api, &self.price_cache, &self.open_positions));
+            } else if risk_manager.update_settings(&new_config.risk_settings) {
+                info!("风控参数已更新");
+            } else {
+                warn!("部分风控组件不支持原地更新参数，重新创建风控组件，之前的状态不保留");
+                *self.risk_manager.write().unwrap() = Arc::new(Self::build_risk_manager(&new_config, &self.api, &self.price_cache, &self.open_positions));
+            }
+        }
         
-        // 等待买入订单完成
-        let mut buy_order_status = buy_order;
-        for _ in 0..10 {
-            if buy_order_status.status == OrderStatus::Filled {
-                break;
+        for change in &changes {
+            info!("配置变化: {}", change);
+        }
+        let config_hash = new_config.fingerprint();
+        info!("新的配置指纹: {}", config_hash);
+        *self.config_hash.write().unwrap() = config_hash;
+        *config = new_config;
+        info!("配置已重新加载，共 {} 项变化", changes.len());
+        Ok(changes)
+    }
+    
+    /// 从配置文件热加载配置，读取或校验失败时保持原有配置
+    ///
+    /// API私钥需要重启才能生效，热加载时不读取私钥文件、加密私钥或系统钥匙串，也不会提示输入口令。
+    /// 启动时选择了配置档时合并同一配置档的设置。
+    pub fn reload_config_file(&self, path: &Path) -> Result<Vec<String>> {
+        let profile = self.config.read().unwrap().active_profile.clone();
+        let new_config = Config::from_file_unchecked_with_profile(&path.to_string_lossy(), profile.as_deref())?;
+        self.reload_config(new_config)
+    }
+    
+    /// 每次收到触发信号时从配置文件热加载配置，触发通道关闭后返回
+    ///
+    /// 实时模式下由SIGHUP信号或 `watch_config_file` 触发。加载失败只记录警告，继续使用原有配置。
+    pub async fn watch_config_reloads(&self, path: PathBuf, mut trigger: mpsc::Receiver<()>) {
+        while trigger.recv().await.is_some() {
+            info!("重新加载配置文件: {}", path.display());
+            if let Err(e) = self.reload_config_file(&path) {
+                warn!("重新加载配置失败，继续使用原有配置: {:#}", e);
             }
+        }
+    }
+    
+    /// 已启用的风控组件名称
+    pub fn risk_controller_names(&self) -> Vec<String> {
+        self.risk_manager().controller_names()
+    }
+    
+    /// 已启用风控组件的当前状态
+    pub fn risk_controller_states(&self) -> Vec<RiskControllerState> {
+        self.risk_manager().controller_states()
+    }
+    
+    /// 按名称重置风控组件，返回是否找到该组件
+    pub async fn reset_risk_controller(&self, name: &str) -> Result<bool> {
+        let found = self.risk_manager().reset_controller(name).await?;
+        if found {
+            info!("已重置风控组件: {}", name);
+        }
+        Ok(found)
+    }
+    
+    /// 紧急清仓：手动暂停交易，并以市价卖出全部基础资产换回USDT
+    ///
+    /// 正在执行的套利不会被中断，清仓只保证之后不再开始新的套利。
+    pub async fn emergency_liquidate(&self) -> Result<LiquidationReport> {
+        self.pause();
+        
+        let symbol = format!("{}{}", self.base_asset, QuoteCurrency::USDT);
+        let quantity = self.api.get_account_balance(&self.base_asset).await
+            .context("查询基础资产余额失败")?;
+        
+        let order = if quantity > Decimal::ZERO {
+            warn!("紧急清仓: 市价卖出 {} {} ({})", quantity, self.base_asset, symbol);
+            let order = self.api.place_order(&symbol, Side::Sell, quantity, None, TimeInForce::GTC, None).await
+                .context("紧急清仓下单失败")?;
+            info!("紧急清仓订单已提交: ID={}, 状态={:?}", order.order_id, order.status);
+            Some(order)
+        } else {
+            info!("紧急清仓: {} 余额为0，无需卖出", self.base_asset);
+            None
+        };
+        
+        Ok(LiquidationReport {
+            asset: self.base_asset.clone(),
+            symbol,
+            quantity,
+            order,
+        })
+    }
+    
+    /// 紧急停止：手动暂停交易，并取消引擎提交的全部挂单，返回被取消的订单
+    ///
+    /// 与过期挂单清理不同，正在执行的套利的订单也会被取消；账户中手动或其他程序提交的挂单不受影响。
+    /// 不卖出基础资产，需要时使用 `emergency_liquidate`。单个订单取消失败只记录警告。
+    pub async fn kill_switch(&self) -> Result<Vec<OrderInfo>> {
+        self.pause();
+        self.cancel_open_orders("紧急停止").await
+    }
+    
+    /// 取消两个交易对中由引擎提交的挂单，`action` 用于日志
+    async fn cancel_open_orders(&self, action: &str) -> Result<Vec<OrderInfo>> {
+        let mut cancelled = Vec::new();
+        for quote in [QuoteCurrency::USDT, QuoteCurrency::USDC] {
+            let symbol = format!("{}{}", self.base_asset, quote);
+            let open_orders = self.api.get_open_orders(&symbol).await
+                .inspect_err(|_| self.metrics.record_api_error("get_open_orders"))
+                .with_context(|| format!("查询 {} 未结束订单失败", symbol))?;
             
-            sleep(Duration::from_millis(1000)).await;
-            buy_order_status = self.api.get_order_status(&buy_symbol, buy_order.order_id).await?;
-            info!("买入订单状态: {:?}", buy_order_status.status);
+            for order in open_orders {
+                if !self.placed_orders.lock().unwrap().contains(&order.order_id) {
+                    continue;
+                }
+                
+                warn!("{}: 取消挂单 {} ID={}, {:?} {} @ {}", action, symbol, order.order_id, order.side, order.qty, order.price);
+                match self.api.cancel_order(&symbol, order.order_id).await {
+                    Ok(order) => cancelled.push(order),
+                    Err(e) => warn!("{}: 取消挂单 {} 失败: {}", action, order.order_id, e),
+                }
+            }
         }
         
-        if buy_order_status.status != OrderStatus::Filled {
-            info!("取消买入订单...");
-            self.api.cancel_order(&buy_symbol, buy_order.order_id).await?;
-            result.status = ArbitrageStatus::Failed;
-            return Err(anyhow!("买入订单未在预期时间内完成"));
+        Ok(cancelled)
+    }
+    
+    /// 结束会话：取消引擎提交的剩余挂单，写入数据库中尚未写入的结果，返回会话汇总
+    ///
+    /// 在监控循环返回后调用；取消挂单或写入失败只记录错误，汇总照常返回。
+    pub async fn finish_session(&self) -> SessionSummary {
+        let cancelled_orders = self.cancel_open_orders("结束会话").await.unwrap_or_else(|e| {
+            error!("结束会话时取消挂单失败: {:#}", e);
+            Vec::new()
+        });
+        if let Some(db) = &self.db_manager {
+            if let Err(e) = db.flush().await {
+                error!("写入剩余套利结果失败: {}", e);
+            }
         }
         
-        result.status = ArbitrageStatus::BuyOrderFilled;
+        SessionSummary {
+            runtime: self.started_at.elapsed(),
+            stats: self.session_stats(),
+            rejections: self.metrics.rejection_counts(),
+            cancelled_orders,
+        }
+    }
+    
+    /// 数据库中最近的交易记录，按开始时间从晚到早排列，未设置数据库时返回None
+    pub async fn recent_trades(&self, limit: i64) -> Result<Option<Vec<TradeRecord>>> {
+        match &self.db_manager {
+            Some(db) => Ok(Some(db.get_trade_history(&TradeHistoryFilter::default(), limit, 0).await?)),
+            None => Ok(None),
+        }
+    }
+    
+    /// 将数据库中今天（本地时间）开始的交易按开始时间从早到晚回放到风控组件，返回回放的交易数
+    ///
+    /// 风控组件的状态不写入数据库，据此恢复当日盈亏、交易频率等状态；只回放当前运行模式产生的交易。
+    pub async fn restore_risk_state(&self) -> Result<usize> {
+        let Some(db) = &self.db_manager else {
+            return Err(anyhow!("未设置数据库，无法恢复风控状态"));
+        };
+        let midnight = chrono::Local::now().date_naive().and_time(chrono::NaiveTime::MIN);
+        let since = midnight
+            .and_local_timezone(chrono::Local)
+            .earliest()
+            .map_or_else(Utc::now, |time| time.with_timezone(&Utc));
+        let filter = TradeHistoryFilter { mode: Some(self.mode), ..TradeHistoryFilter::between(Some(since), None) };
         
-        // 执行卖出订单
-        let sell_order = match self.api.place_order(&sell_symbol, Side::Sell, trade_amount_base, None).await {
-            Ok(order) => {
-                info!("卖出订单已提交: ID={}, 状态={:?}", order.order_id, order.status);
-                result.sell_order_id = Some(order.order_id);
-                result.status = ArbitrageStatus::SellOrderPlaced;
-                order
+        let mut trades = Vec::new();
+        let mut cursor = None;
+        loop {
+            let page = db.get_trade_history_page(&filter, 500, cursor.as_ref()).await?;
+            trades.extend(page.items);
+            match page.next_cursor {
+                Some(next) => cursor = Some(next),
+                None => break,
+            }
+        }
+        
+        let risk_manager = self.risk_manager();
+        for record in trades.iter().rev() {
+            risk_manager.record_result(&record.result).await?;
+        }
+        info!("已将今天的 {} 笔交易回放到风控组件", trades.len());
+        Ok(trades.len())
+    }
+    
+    /// 成交均价相对报价的不利偏离（百分比）
+    ///
+    /// 买入成交价高于报价、卖出成交价低于报价为不利偏离；有利偏离返回0，报价或均价为0时返回 None。
+    fn adverse_deviation_pct(side: Side, quoted: Decimal, filled: Decimal) -> Option<Decimal> {
+        if quoted.is_zero() || filled.is_zero() {
+            return None;
+        }
+        let diff = match side {
+            Side::Buy => filled - quoted,
+            Side::Sell => quoted - filled,
+        };
+        Some((diff / quoted * dec!(100)).max(Decimal::ZERO))
+    }
+    
+    /// 机会是否低于运行时最小利润率
+    fn below_runtime_min_profit(&self, opportunity: &ArbitrageOpportunity) -> bool {
+        match self.min_profit_percentage() {
+            Some(min_profit) => opportunity.profit_percentage < min_profit,
+            None => false,
+        }
+    }
+    
+    /// 查询交易所系统状态并更新暂停标志，返回当前是否允许交易
+    /// 查询失败时保持原有状态
+    pub async fn refresh_system_status(&self) -> bool {
+        match self.api.get_system_status().await {
+            Ok(status) => {
+                let was_paused = self.trading_paused.swap(status.maintenance, Ordering::SeqCst);
+                if status.maintenance && !was_paused {
+                    warn!("交易所系统维护中 ({})，暂停交易", status.message);
+                } else if !status.maintenance && was_paused {
+                    info!("交易所系统恢复正常，恢复交易");
+                }
             },
             Err(e) => {
-                result.status = ArbitrageStatus::Failed;
-                return Err(anyhow!("卖出订单失败: {}", e));
+                warn!("查询交易所系统状态失败: {}", e);
+                self.metrics.record_api_error("get_system_status");
             }
-        };
+        }
         
-        // 等待卖出订单完成
-        let mut sell_order_status = sell_order;
-        for _ in 0..10 {
-            if sell_order_status.status == OrderStatus::Filled {
-                break;
-            }
+        !self.is_trading_paused()
+    }
+    
+    /// 取消两个交易对中挂单时间超过 `max_order_age_seconds` 的订单，返回被取消的订单
+    ///
+    /// 正在执行的套利的订单不论挂单多久都不会被取消，单个订单取消失败只记录警告。
+    pub async fn cleanup_stale_orders(&self) -> Result<Vec<OrderInfo>> {
+        let max_age = chrono::Duration::seconds(self.config.read().unwrap().arbitrage_settings.max_order_age_seconds as i64);
+        let now = Utc::now();
+        let mut cancelled = Vec::new();
+        
+        for quote in [QuoteCurrency::USDT, QuoteCurrency::USDC] {
+            let symbol = format!("{}{}", self.base_asset, quote);
+            let open_orders = self.api.get_open_orders(&symbol).await
+                .inspect_err(|_| self.metrics.record_api_error("get_open_orders"))
+                .with_context(|| format!("查询 {} 未结束订单失败", symbol))?;
             
-            sleep(Duration::from_millis(1000)).await;
-            sell_order_status = self.api.get_order_status(&sell_symbol, sell_order.order_id).await?;
-            info!("卖出订单状态: {:?}", sell_order_status.status);
+            for order in open_orders {
+                if now - order.timestamp < max_age || self.active_orders.lock().unwrap().contains(&order.order_id) {
+                    continue;
+                }
+                
+                warn!(
+                    "取消过期挂单: {} ID={}, {:?} {} @ {}, 下单时间 {}",
+                    symbol, order.order_id, order.side, order.qty, order.price, order.timestamp
+                );
+                match self.api.cancel_order(&symbol, order.order_id).await {
+                    Ok(order) => cancelled.push(order),
+                    Err(e) => warn!("取消过期挂单 {} 失败: {}", order.order_id, e),
+                }
+            }
         }
         
-        if sell_order_status.status != OrderStatus::Filled {
-            info!("取消卖出订单...");
-            self.api.cancel_order(&sell_symbol, sell_order.order_id).await?;
-            result.status = ArbitrageStatus::Failed;
-            return Err(anyhow!("卖出订单未在预期时间内完成"));
+        Ok(cancelled)
+    }
+    
+    /// 按 `stale_order_cleanup_interval_ms` 定期清理过期挂单，间隔为0（包括热加载后改为0）时返回
+    pub async fn watch_stale_orders(&self) {
+        loop {
+            let interval = self.config.read().unwrap().arbitrage_settings.stale_order_cleanup_interval_ms;
+            if interval == 0 {
+                info!("过期挂单清理已停用");
+                return;
+            }
+            
+            sleep(Duration::from_millis(interval)).await;
+            match self.cleanup_stale_orders().await {
+                Ok(cancelled) if !cancelled.is_empty() => info!("已取消 {} 个过期挂单", cancelled.len()),
+                Ok(_) => debug!("没有过期挂单"),
+                Err(e) => warn!("清理过期挂单失败: {:#}", e),
+            }
+        }
+    }
+    
+    /// 计入一笔已完成套利的净利润，累计达到阈值时将配置比例的利润划转到子账户
+    ///
+    /// 未启用划转时只记录分配金额；划转失败时累计利润保留，下一笔交易后重试。
+    async fn distribute_profit(&self, profit: Decimal) {
+        let settings = self.config.read().unwrap().arbitrage_settings.profit_distribution.clone();
+        if !settings.enabled {
+            return;
         }
         
-        result.status = ArbitrageStatus::Completed;
-        
-        // 计算实际利润
-        let buy_total = trade_amount_base * buy_order_status.price;
-        let sell_total = trade_amount_base * sell_order_status.price;
-        let profit = sell_total - buy_total;
+        let Some(distribution) = self.profit_distributor.record(profit, settings.threshold_usdt, settings.portion) else {
+            return;
+        };
         
-        result.profit = profit;
+        if !settings.transfer_enabled {
+            self.profit_distributor.mark_earmarked(distribution.amount);
+            info!(
+                "累计利润 {} 达到阈值 {}，记录待分配 {} {}（未启用划转）",
+                distribution.accumulated, settings.threshold_usdt, distribution.amount, settings.asset
+            );
+            return;
+        }
         
-        info!("套利交易完成! 利润: {}", profit);
+        match self.api.transfer_to_sub_account(&settings.to_email, &settings.asset, distribution.amount).await {
+            Ok(transfer) => {
+                self.profit_distributor.mark_transferred(transfer.amount);
+                info!(
+                    "累计利润 {} 达到阈值 {}，已划转 {} {} 到子账户 {}，交易ID={}",
+                    distribution.accumulated, settings.threshold_usdt, transfer.amount, transfer.asset, transfer.to_email, transfer.txn_id
+                );
+            },
+            Err(e) => {
+                self.metrics.record_api_error("transfer_to_sub_account");
+                self.profit_distributor.restore(&distribution);
+                error!("划转利润 {} {} 到子账户 {} 失败: {}", distribution.amount, settings.asset, settings.to_email, e);
+            },
+        }
+    }
+    
+    /// 检查基础资产余额，余额大于0且低于小额资产上限时按配置的方式处理，没有小额资产时返回None
+    ///
+    /// 小额资产上限取交易对最小下单量和 `dust_threshold` 中的较大者。有套利正在执行时跳过，
+    /// 避免把已买入、尚未卖出的基础资产当作小额资产。
+    pub async fn sweep_dust(&self) -> Result<Option<DustSweep>> {
+        if self.open_positions.count() > 0 {
+            debug!("有套利正在执行，跳过小额资产检查");
+            return Ok(None);
+        }
+        
+        let settings = self.config.read().unwrap().arbitrage_settings.dust_sweep.clone();
+        let symbol = format!("{}{}", self.base_asset, QuoteCurrency::USDT);
+        let symbol_info = self.api.get_symbol_info(&symbol).await
+            .inspect_err(|_| self.metrics.record_api_error("get_symbol_info"))
+            .with_context(|| format!("查询 {} 交易规则失败", symbol))?;
+        let threshold = symbol_info.min_qty.max(settings.dust_threshold);
+        let quantity = self.api.get_account_balance(&self.base_asset).await
+            .context("查询基础资产余额失败")?;
+        if quantity <= Decimal::ZERO || quantity >= threshold {
+            return Ok(None);
+        }
+        
+        info!("发现小额资产: {} {}，低于上限 {}", quantity, self.base_asset, threshold);
+        let outcome = match settings.action {
+            DustAction::Report => DustOutcome::Reported,
+            DustAction::Convert => {
+                let conversion = self.api.convert_dust(&self.base_asset).await
+                    .context("兑换小额资产失败")?;
+                info!("已将 {} {} 兑换为 {} BNB", conversion.amount, conversion.asset, conversion.transferred);
+                DustOutcome::Converted(conversion)
+            },
+            DustAction::MarketSell => {
+                let sell_quantity = if symbol_info.step_size > Decimal::ZERO {
+                    (quantity / symbol_info.step_size).floor() * symbol_info.step_size
+                } else {
+                    quantity
+                };
+                let price = self.api.get_price(&symbol).await
+                    .inspect_err(|_| self.metrics.record_api_error("get_price"))?;
+                if sell_quantity < symbol_info.min_qty || sell_quantity * price.price < symbol_info.min_notional {
+                    warn!(
+                        "小额资产 {} {} 不足最小下单量 {} 或最小下单金额 {}，无法卖出",
+                        quantity, self.base_asset, symbol_info.min_qty, symbol_info.min_notional
+                    );
+                    DustOutcome::Reported
+                } else {
+                    let order = self.api.place_order(&symbol, Side::Sell, sell_quantity, None, TimeInForce::GTC, None).await
+                        .context("卖出小额资产失败")?;
+                    info!("已市价卖出小额资产: {} {}, 订单ID={}", sell_quantity, self.base_asset, order.order_id);
+                    DustOutcome::Sold(order)
+                }
+            },
+        };
+        
+        Ok(Some(DustSweep {
+            asset: self.base_asset.clone(),
+            quantity,
+            threshold,
+            outcome,
+        }))
+    }
+    
+    /// 按 `dust_sweep.interval_ms` 定期清理小额资产，未启用（包括热加载后停用）时返回
+    pub async fn watch_dust(&self) {
+        loop {
+            let (enabled, interval) = {
+                let config = self.config.read().unwrap();
+                (config.arbitrage_settings.dust_sweep.enabled, config.arbitrage_settings.dust_sweep.interval_ms)
+            };
+            if !enabled || interval == 0 {
+                info!("小额资产清理已停用");
+                return;
+            }
+            
+            sleep(Duration::from_millis(interval)).await;
+            match self.sweep_dust().await {
+                Ok(Some(sweep)) => debug!("小额资产清理结果: {:?}", sweep.outcome),
+                Ok(None) => debug!("没有小额资产"),
+                Err(e) => warn!("清理小额资产失败: {:#}", e),
+            }
+        }
+    }
+    
+    /// 订阅用户数据流，通过推送获取订单状态，断开后每隔 `USER_DATA_RECONNECT_DELAY` 重新连接
+    ///
+    /// 未连接期间等待订单成交改为轮询订单状态。
+    pub async fn watch_user_data_stream(&self) {
+        loop {
+            match self.run_user_data_stream().await {
+                Ok(()) => warn!("用户数据流已断开，改为轮询订单状态"),
+                Err(e) => warn!("用户数据流不可用，改为轮询订单状态: {:#}", e),
+            }
+            self.order_updates.set_connected(false);
+            sleep(USER_DATA_RECONNECT_DELAY).await;
+        }
+    }
+    
+    /// 连接用户数据流并处理推送，直到连接断开
+    async fn run_user_data_stream(&self) -> Result<()> {
+        let listen_key = self.api.create_listen_key().await.context("创建listenKey失败")?;
+        let mut stream = self.api.subscribe_user_data(&listen_key).await?;
+        self.order_updates.set_connected(true);
+        info!("已订阅用户数据流，订单状态通过推送获取");
+        
+        let usdt_symbol = format!("{}USDT", self.base_asset);
+        let usdc_symbol = format!("{}USDC", self.base_asset);
+        let mut keepalive = tokio::time::interval_at(Instant::now() + LISTEN_KEY_KEEPALIVE_INTERVAL, LISTEN_KEY_KEEPALIVE_INTERVAL);
+        loop {
+            tokio::select! {
+                event = stream.next() => match event {
+                    // 同一账户的数据流包含所有交易对的订单，只保留本引擎的交易对
+                    Some(Ok(UserDataEvent::ExecutionReport(order))) if order.symbol == usdt_symbol || order.symbol == usdc_symbol => {
+                        debug!("收到订单状态推送: ID={}, 状态={:?}", order.order_id, order.status);
+                        self.order_updates.apply(order);
+                    },
+                    Some(Ok(_)) => {},
+                    Some(Err(e)) => return Err(e),
+                    None => return Ok(()),
+                },
+                _ = keepalive.tick() => {
+                    if let Err(e) = self.api.keepalive_listen_key(&listen_key).await {
+                        warn!("listenKey续期失败: {:#}", e);
+                    }
+                },
+            }
+        }
+    }
+    
+    /// 持续监控币对价格，寻找套利机会
+    pub async fn monitor_opportunities(&self) -> Result<()> {
+        info!("开始监控 {}-USDT/USDC 套利机会", self.base_asset);
+        
+        if self.is_warming_up() {
+            let (ticks, duration) = self.warmup_settings();
+            info!("预热期: 至少观察 {} 轮行情、{} 秒，期间不执行交易", ticks, duration.as_secs());
+        }
+        
+        self.refresh_system_status().await;
+        let mut last_status_check = tokio::time::Instant::now();
+        
+        self.check_database_health().await;
+        let mut last_db_check = tokio::time::Instant::now();
+        
+        loop {
+            if self.shutdown.is_requested() {
+                info!("已停止监控 {}-USDT/USDC 套利机会", self.base_asset);
+                return Ok(());
+            }
+            
+            // 热加载配置后检查间隔可能变化，每轮读取
+            let (status_check_interval, db_check_interval) = {
+                let config = self.config.read().unwrap();
+                (
+                    Duration::from_millis(config.arbitrage_settings.system_status_check_interval_ms),
+                    Duration::from_millis(config.arbitrage_settings.db_health_check_interval_ms),
+                )
+            };
+            
+            // 定期检查交易所系统状态，维护期间跳过本轮
+            if last_status_check.elapsed() >= status_check_interval {
+                self.refresh_system_status().await;
+                last_status_check = tokio::time::Instant::now();
+            }
+            
+            // 定期检查数据库连接，并重试写入之前未能保存的套利结果
+            if last_db_check.elapsed() >= db_check_interval {
+                self.check_database_health().await;
+                self.retry_unsaved_results().await;
+                last_db_check = tokio::time::Instant::now();
+            }
+            
+            if self.is_db_write_paused() {
+                self.sync_writer_failures();
+                debug!("套利结果无法写入数据库，跳过本轮套利检查");
+                sleep(self.check_interval()).await;
+                continue;
+            }
+            
+            if self.is_trading_paused() {
+                debug!("交易所系统维护中，跳过本轮套利检查");
+                sleep(self.check_interval()).await;
+                continue;
+            }
+            
+            if self.is_manually_paused() {
+                debug!("交易已手动暂停，跳过本轮套利检查");
+                sleep(self.check_interval()).await;
+                continue;
+            }
+            
+            let round_started = std::time::Instant::now();
+            let found = self.find_best_arbitrage_opportunity().await;
+            if let Ok(None) = found {
+                // 原始价差低于阈值的一轮同样计入预热
+                self.observe_warmup_tick();
+            }
+            if let Ok(Some(mut opportunity)) = found {
+                // 预热期间行情已写入缓存和策略历史，但不执行交易
+                if self.observe_warmup_tick() {
+                    debug!("预热中，跳过套利机会: 利润率 {}%", opportunity.profit_percentage);
+                    self.record_round_metrics(round_started);
+                    sleep(self.check_interval()).await;
+                    continue;
+                }
+                
+                // 寻找机会期间可能已被手动暂停或调整了最小利润率
+                if self.is_manually_paused() || self.below_runtime_min_profit(&opportunity) {
+                    debug!("套利机会利润率 {}% 低于运行时阈值或交易已暂停，跳过", opportunity.profit_percentage);
+                    self.record_round_metrics(round_started);
+                    sleep(self.check_interval()).await;
+                    continue;
+                }
+                
+                // 价差没有变化时同一个机会会在连续几轮被重复发现
+                let dedup_settings = self.config.read().unwrap().arbitrage_settings.opportunity_dedup.clone();
+                if self.opportunity_dedup.is_duplicate(&opportunity, &dedup_settings, std::time::Instant::now()) {
+                    debug!(
+                        "套利机会 [{}] 与 {}ms 内执行过的机会相同 ({} {} -> {}, 利润率 {}%)，跳过",
+                        opportunity.opportunity_id,
+                        dedup_settings.window_ms,
+                        opportunity.base_asset,
+                        opportunity.buy_quote,
+                        opportunity.sell_quote,
+                        opportunity.profit_percentage
+                    );
+                    self.record_round_metrics(round_started);
+                    sleep(self.check_interval()).await;
+                    continue;
+                }
+                
+                if self.is_asset_blacklisted(&opportunity.base_asset) {
+                    let reason = format!("{} 在运行时黑名单中，不执行套利", opportunity.base_asset);
+                    debug!("跳过套利机会 [{}]: {}", opportunity.opportunity_id, reason);
+                    self.metrics.record_rejection(RUNTIME_BLACKLIST, RejectionReason::Blacklisted);
+                    self.record_rejected_opportunity(&opportunity, &[format!("{}: {}", RUNTIME_BLACKLIST, reason)]).await;
+                    self.record_round_metrics(round_started);
+                    sleep(self.check_interval()).await;
+                    continue;
+                }
+                
+                // 价差不足以覆盖买卖两边的手续费时不执行
+                let estimated_profit = self.profit_calculator().estimated_profit(&opportunity);
+                if estimated_profit < Decimal::ZERO {
+                    let reason = format!("利润率 {}% 扣除买卖两边的手续费后预计亏损 {}", opportunity.profit_percentage, estimated_profit);
+                    debug!("跳过套利机会 [{}]: {}", opportunity.opportunity_id, reason);
+                    self.metrics.record_rejection(ROUND_TRIP_FEES, RejectionReason::BelowTradingFees);
+                    self.record_rejected_opportunity(&opportunity, &[format!("{}: {}", ROUND_TRIP_FEES, reason)]).await;
+                    self.record_round_metrics(round_started);
+                    sleep(self.check_interval()).await;
+                    continue;
+                }
+                
+                // 交易金额不超过运行时的最大交易金额
+                let max_trade_amount = self.max_trade_amount();
+                if opportunity.max_trade_amount > max_trade_amount {
+                    opportunity.max_trade_amount = max_trade_amount;
+                }
+                
+                // 按稳定币库存调整交易金额，使库存远离目标比例的方向可能降为0
+                self.apply_inventory_skew(&mut opportunity).await;
+                if opportunity.max_trade_amount <= Decimal::ZERO {
+                    debug!("库存偏离目标比例，{} 买入的交易金额降为0，跳过", opportunity.buy_quote);
+                    self.record_round_metrics(round_started);
+                    sleep(self.check_interval()).await;
+                    continue;
+                }
+                
+                // 交易金额太小时利润不足以覆盖每笔的固定成本
+                if let Some(reason) = self.below_notional_floor(&opportunity) {
+                    debug!("跳过套利机会 [{}]: {}", opportunity.opportunity_id, reason);
+                    self.metrics.record_rejection(NOTIONAL_FLOOR, RejectionReason::BelowTradingFees);
+                    self.record_rejected_opportunity(&opportunity, &[format!("{}: {}", NOTIONAL_FLOOR, reason)]).await;
+                    self.record_round_metrics(round_started);
+                    sleep(self.check_interval()).await;
+                    continue;
+                }
+                
+                // 验证风控规则，本轮使用同一个风控管理器
+                let risk_manager = self.risk_manager();
+                let (is_valid, rejection_reasons) = risk_manager.validate_opportunity(&opportunity).await?;
+                self.metrics.record_opportunity();
+                
+                if !is_valid {
+                    for rejection in &rejection_reasons {
+                        tracing::warn!(
+                            asset = %opportunity.base_asset,
+                            strategy = opportunity.strategy.as_deref().unwrap_or_default(),
+                            controller = %rejection.controller,
+                            reason = %rejection.reason(),
+                            opportunity_id = %opportunity.opportunity_id,
+                            "风控拒绝 ({}): {}", rejection.reason(), rejection
+                        );
+                        self.metrics.record_rejection(&rejection.controller, rejection.reason());
+                    }
+                    let reasons: Vec<String> = rejection_reasons.iter().map(ToString::to_string).collect();
+                    self.record_rejected_opportunity(&opportunity, &reasons).await;
+                    debug!("套利机会被风控拒绝，跳过");
+                } else if self.is_dry_run() {
+                    tracing::info!(
+                        asset = %opportunity.base_asset,
+                        strategy = opportunity.strategy.as_deref().unwrap_or_default(),
+                        profit_percentage = %opportunity.profit_percentage,
+                        opportunity_id = %opportunity.opportunity_id,
+                        dry_run = true,
+                        "[只记录] 发现套利机会 [{}]: {} 买入: {} {}, 卖出: {} {}, 利润率: {}%, 交易金额: {}，未下单",
+                        opportunity.opportunity_id,
+                        opportunity.base_asset,
+                        opportunity.buy_quote,
+                        opportunity.buy_price,
+                        opportunity.sell_quote,
+                        opportunity.sell_price,
+                        opportunity.profit_percentage,
+                        opportunity.max_trade_amount
+                    );
+                    self.opportunity_dedup.record(&opportunity, &dedup_settings, std::time::Instant::now());
+                } else {
+                    // 如果通过风控，执行套利
+                    tracing::info!(
+                        asset = %opportunity.base_asset,
+                        strategy = opportunity.strategy.as_deref().unwrap_or_default(),
+                        profit_percentage = %opportunity.profit_percentage,
+                        opportunity_id = %opportunity.opportunity_id,
+                        "发现套利机会 [{}]: {} 买入: {} {}, 卖出: {} {}, 价差: {}, 利润率: {}%",
+                        opportunity.opportunity_id,
+                        opportunity.base_asset,
+                        opportunity.buy_quote,
+                        opportunity.buy_price,
+                        opportunity.sell_quote,
+                        opportunity.sell_price,
+                        opportunity.price_diff,
+                        opportunity.profit_percentage
+                    );
+                    
+                    // 预留买入所用的余额，套利结束（本分支结束）时释放
+                    let _reservation = match self.reserve_balance(&opportunity).await {
+                        Ok(reservation) => reservation,
+                        Err(reason) => {
+                            warn!("余额不足，跳过套利机会 [{}]: {}", opportunity.opportunity_id, reason);
+                            self.metrics.record_rejection(BALANCE_RESERVATION, RejectionReason::InsufficientBalance);
+                            self.record_rejected_opportunity(
+                                &opportunity,
+                                &[format!("{}: {}", BALANCE_RESERVATION, reason)],
+                            ).await;
+                            self.record_round_metrics(round_started);
+                            sleep(self.check_interval()).await;
+                            continue;
+                        }
+                    };
+                    self.opportunity_dedup.record(&opportunity, &dedup_settings, std::time::Instant::now());
+                    
+                    // 执行过程中已结束的订单，随套利结果一起记录
+                    let mut orders = Vec::new();
+                    let result = match self.execute_arbitrage(&opportunity, &mut orders).await {
+                        Ok(result) => {
+                            tracing::info!(
+                                asset = %result.base_asset,
+                                strategy = result.strategy.as_deref().unwrap_or_default(),
+                                status = ?result.status,
+                                profit = %result.profit,
+                                profit_percentage = %result.profit_percentage,
+                                opportunity_id = %opportunity.opportunity_id,
+                                "套利结束 [{}]: {} 状态: {:?}, 利润: {} ({}%)",
+                                opportunity.opportunity_id, result.base_asset, result.status, result.profit, result.profit_percentage
+                            );
+                            
+                            // 记录交易结果
+                            risk_manager.record_result(&result).await?;
+                            
+                            if result.status == ArbitrageStatus::Completed {
+                                self.distribute_profit(result.profit).await;
+                            }
+                            
+                            // 如果设置了数据库，保存套利结果
+                            self.save_result(&result, orders).await;
+                            
+                            result
+                        }
+                        Err(e) => {
+                            tracing::error!(
+                                asset = %opportunity.base_asset,
+                                strategy = opportunity.strategy.as_deref().unwrap_or_default(),
+                                reason = %e,
+                                opportunity_id = %opportunity.opportunity_id,
+                                "套利执行失败 [{}]: {}", opportunity.opportunity_id, e
+                            );
+                            
+                            // 创建失败结果并记录
+                            let failed_result = ArbitrageResult {
+                                base_asset: opportunity.base_asset.clone(),
+                                buy_quote: opportunity.buy_quote.to_string(),
+                                sell_quote: opportunity.sell_quote.to_string(),
+                                buy_price: opportunity.buy_price,
+                                sell_price: opportunity.sell_price,
+                                trade_amount: Decimal::ZERO,
+                                profit: Decimal::ZERO,
+                                profit_percentage: Decimal::ZERO,
+                                buy_order_id: None,
+                                sell_order_id: None,
+                                status: ArbitrageStatus::Failed,
+                                timestamp: opportunity.timestamp,
+                                start_time: opportunity.timestamp,
+                                end_time: Utc::now(),
+                                buy_fill: FillInfo::default(),
+                                sell_fill: FillInfo::default(),
+                                mode: self.mode,
+                                config_hash: self.config_hash(),
+                                strategy: opportunity.strategy.clone(),
+                                opportunity_id: Some(opportunity.opportunity_id),
+                            };
+                            
+                            risk_manager.record_result(&failed_result).await?;
+                            
+                            // 如果设置了数据库，保存失败记录
+                            self.save_result(&failed_result, orders).await;
+                            
+                            failed_result
+                        }
+                    };
+                    
+                    // 达到会话限制时结束监控
+                    if let Some(reason) = self.record_session_result(&result) {
+                        self.record_round_metrics(round_started);
+                        let stats = self.session_stats();
+                        info!(
+                            "已达到会话限制 ({})，停止监控。本次会话: 交易 {} 次, 成功 {} 次, 失败 {} 次, 累计利润 {}",
+                            reason, stats.trades, stats.successful_trades, stats.failed_trades, stats.profit
+                        );
+                        return Ok(());
+                    }
+                }
+            }
+            
+            self.record_round_metrics(round_started);
+            // 等待指定的时间间隔
+            sleep(self.check_interval()).await;
+        }
+    }
+    
+    /// 交易金额低于最小交易金额时返回原因，未设置每笔固定成本时不检查
+    ///
+    /// 最小交易金额为按当前手续费率和机会价差计算的盈亏平衡金额乘以 `fees.min_notional_multiple`，与交易所的最小下单金额无关。
+    fn below_notional_floor(&self, opportunity: &ArbitrageOpportunity) -> Option<String> {
+        let (fixed_cost, multiple) = {
+            let config = self.config.read().unwrap();
+            (config.fees.fixed_cost_per_trade, config.fees.min_notional_multiple)
+        };
+        if fixed_cost <= Decimal::ZERO {
+            return None;
+        }
+        
+        let Some(break_even) = self.profit_calculator().break_even_notional(opportunity, fixed_cost) else {
+            return Some(format!("扣除手续费后没有利润，无法覆盖每笔固定成本 {}", fixed_cost));
+        };
+        let min_notional = break_even * multiple;
+        if opportunity.max_trade_amount < min_notional {
+            Some(format!(
+                "交易金额 {} 低于按每笔固定成本 {} 和利润率 {}% 计算的最小交易金额 {}",
+                opportunity.max_trade_amount, fixed_cost, opportunity.profit_percentage, min_notional.round_dp(2)
+            ))
+        } else {
+            None
+        }
+    }
+    
+    /// 按USDT和USDC库存调整机会的交易金额，未启用库存偏斜时不调整
+    ///
+    /// 调整后的金额不超过买入一侧稳定币的余额和最大交易金额；查询余额失败时保持原交易金额。
+    async fn apply_inventory_skew(&self, opportunity: &mut ArbitrageOpportunity) {
+        let settings = self.config.read().unwrap().arbitrage_settings.inventory_skew.clone();
+        if !settings.enabled {
+            return;
+        }
+        
+        let balances = tokio::try_join!(
+            self.api.get_account_balance("USDT"),
+            self.api.get_account_balance("USDC"),
+        );
+        let (usdt_balance, usdc_balance) = match balances {
+            Ok(balances) => balances,
+            Err(e) => {
+                self.metrics.record_api_error("get_account_balance");
+                warn!("查询稳定币余额失败，不按库存调整交易金额: {}", e);
+                return;
+            }
+        };
+        
+        let multiplier = InventorySkew::from_settings(&settings).size_multiplier(usdt_balance, usdc_balance, opportunity.buy_quote);
+        let available = match opportunity.buy_quote {
+            QuoteCurrency::USDT => usdt_balance,
+            QuoteCurrency::USDC => usdc_balance,
+        };
+        let amount = (opportunity.max_trade_amount * multiplier)
+            .min(available)
+            .min(self.max_trade_amount())
+            .max(Decimal::ZERO);
+        debug!(
+            "库存偏斜: USDT {} / USDC {}，{} 买入的交易金额 {} -> {} (倍数 {})",
+            usdt_balance, usdc_balance, opportunity.buy_quote, opportunity.max_trade_amount, amount, multiplier
+        );
+        opportunity.max_trade_amount = amount;
+    }
+    
+    /// 按机会的交易金额预留买入所用的计价币余额，未启用余额预留时返回None
+    ///
+    /// 查询余额失败或可用余额扣除其他套利已预留的金额后不足时返回原因，不执行该机会。
+    async fn reserve_balance(&self, opportunity: &ArbitrageOpportunity) -> std::result::Result<Option<BalanceReservation>, String> {
+        if !self.config.read().unwrap().arbitrage_settings.reserve_balance {
+            return Ok(None);
+        }
+        
+        let asset = opportunity.buy_quote.to_string();
+        let free_balance = self
+            .api
+            .get_account_balance(&asset)
+            .await
+            .inspect_err(|_| self.metrics.record_api_error("get_account_balance"))
+            .map_err(|e| format!("查询 {} 余额失败: {}", asset, e))?;
+        self.balance_ledger
+            .try_reserve(&asset, opportunity.max_trade_amount, free_balance)
+            .map(Some)
+    }
+    
+    /// 设置了数据库时记录被拒绝的套利机会，用于拒绝原因分析，写入失败只记录日志
+    async fn record_rejected_opportunity(&self, opportunity: &ArbitrageOpportunity, reasons: &[String]) {
+        if let Some(db) = &self.db_manager {
+            let rejection = RejectedOpportunity::from_reasons(opportunity, reasons, self.mode, Utc::now());
+            if let Err(e) = db.record_rejection(&rejection).await {
+                error!("记录被拒绝的套利机会到数据库失败: {}", e);
+            }
+        }
+    }
+    
+    /// 记录一轮套利检查的耗时，并同步写入队列的失败计数
+    fn record_round_metrics(&self, started: std::time::Instant) {
+        self.metrics.observe_loop_latency(started.elapsed());
+        if let Some(write_metrics) = self.db_manager.as_ref().and_then(|db| db.write_metrics()) {
+            self.metrics.set_db_writer_failures(write_metrics.failed + write_metrics.dropped);
+        }
+        self.sync_writer_failures();
+    }
+    
+    // ... existing code ...
+    // 保留原有的其他方法实现...
+
+    /// 按配置创建启用的策略，未启用任何策略时使用简单策略
+    fn build_strategies(config: &Config, api_arc: &Arc<T>) -> Vec<Box<dyn TradingStrategy>> {
+        let mut strategies: Vec<Box<dyn TradingStrategy>> = Vec::new();
+        
+        // 根据配置启用的策略类型初始化相应的策略
+        for strategy_type in &config.strategy_settings.enabled_strategies {
+            strategies.push(Self::build_strategy(strategy_type, config, api_arc));
+        }
+        
+        // 如果没有启用任何策略，则默认使用简单策略
+        if strategies.is_empty() {
+            info!("未配置任何策略，使用默认的简单价格差异套利策略");
+            strategies.push(Box::new(SimpleArbitrageStrategy::new(config.clone())));
+        }
+        
+        strategies
+    }
+    
+    /// 按配置创建风控管理器及启用的风控组件
+    fn build_risk_manager(
+        config: &Config,
+        api_arc: &Arc<T>,
+        price_cache: &Arc<PriceCache>,
+        open_positions: &Arc<OpenPositionTracker>,
+    ) -> RiskManager {
+        let mut risk_manager = RiskManager::new(config.clone());
+        
+        // 根据配置启用的风控类型初始化相应的控制器
+        for controller_type in &config.risk_settings.enabled_controllers {
+            match controller_type {
+                RiskControllerType::DailyLossLimit => {
+                    info!("启用每日亏损限制风控");
+                    risk_manager.add_controller(DailyLossLimitController::new(
+                        config.risk_settings.daily_loss_limit.max_daily_loss
+                    ));
+                },
+                // ... 其他风控初始化代码 ...
+                // 保留原有的风控初始化代码...
+                RiskControllerType::AbnormalPrice => {
+                    info!("启用异常价格保护风控");
+                    let settings = &config.risk_settings.abnormal_price;
+                    risk_manager.add_controller(AbnormalPriceController::with_price_cache(
+                        price_cache.clone(),
+                        settings.window_size,
+                        settings.abnormal_threshold,
+                        settings.cooldown_period,
+                    ));
+                },
+                RiskControllerType::Exposure => {
+                    info!("启用风险敞口控制风控");
+                    let settings = &config.risk_settings.exposure;
+                    let mut exposure_controller = ExposureController::new(api_arc.clone())
+                        .with_update_mode(settings.update_mode, Duration::from_secs(settings.update_interval_seconds));
+                    
+                    // 设置每种币的最大风险敞口
+                    for (asset, max_exposure) in &settings.max_exposures {
+                        exposure_controller.set_max_exposure(asset, *max_exposure);
+                    }
+                    
+                    risk_manager.add_controller(exposure_controller);
+                },
+                RiskControllerType::TradingTimeWindow => {
+                    info!("启用交易时间窗口风控");
+                    let settings = &config.risk_settings.trading_time_window;
+                    
+                    if let Ok(controller) = TradingTimeWindowController::new(
+                        settings.start_hour,
+                        settings.start_minute,
+                        settings.end_hour,
+                        settings.end_minute,
+                        settings.trade_on_weekends,
+                    ) {
+                        risk_manager.add_controller(controller);
+                    } else {
+                        warn!("无法创建交易时间窗口控制器，时间设置无效");
+                    }
+                },
+                RiskControllerType::TradingFrequency => {
+                    info!("启用交易频率控制风控");
+                    let settings = &config.risk_settings.trading_frequency;
+                    let mut controller = TradingFrequencyController::new(
+                        settings.min_interval_seconds,
+                        settings.max_trades_per_timeframe,
+                        settings.timeframe_seconds,
+                    );
+                    if settings.min_spread_persistence_ms > 0 {
+                        controller = controller.with_spread_persistence(
+                            price_cache.clone(),
+                            settings.min_spread_percentage,
+                            chrono::Duration::milliseconds(settings.min_spread_persistence_ms as i64),
+                        );
+                    }
+                    risk_manager.add_controller(controller);
+                },
+                RiskControllerType::PairBlacklist => {
+                    info!("启用交易对黑名单风控");
+                    let controller = PairBlacklistController::from_entries(&config.risk_settings.pair_blacklist.blacklisted_pairs);
+                    risk_manager.add_controller(controller);
+                },
+                RiskControllerType::OpenPositions => {
+                    info!("启用最大持仓数限制风控");
+                    risk_manager.add_controller(OpenPositionsController::new(
+                        open_positions.clone(),
+                        config.risk_settings.open_positions.max_open_positions,
+                    ));
+                },
+            }
+        }
+        
+        risk_manager
+    }
+    
+    /// 根据策略类型创建策略实例
+    /// 按配置创建策略共用的盘口流动性检查，未启用时返回None
+    fn liquidity_guard(config: &Config, api_arc: &Arc<T>) -> Option<LiquidityGuard> {
+        let settings = &config.strategy_settings.liquidity_guard;
+        if !settings.enabled {
+            return None;
+        }
+        info!("启用盘口流动性检查，参与计算的档位数量: {}", settings.levels);
+        Some(LiquidityGuard::new(api_arc.clone(), settings.levels))
+    }
+    
+    fn build_strategy(strategy_type: &StrategyType, config: &Config, api_arc: &Arc<T>) -> Box<dyn TradingStrategy> {
+        match strategy_type {
+            StrategyType::Simple => {
+                info!("启用简单价格差异套利策略");
+                let strategy = SimpleArbitrageStrategy::new(config.clone());
+                match Self::liquidity_guard(config, api_arc) {
+                    Some(guard) => Box::new(strategy.with_liquidity_guard(guard)),
+                    None => Box::new(strategy),
+                }
+            },
+            StrategyType::TimeWeighted => {
+                info!("启用时间加权平均价格(TWAP)套利策略");
+                let settings = &config.strategy_settings.twap;
+                Box::new(TimeWeightedAverageStrategy::new(
+                    config.clone(),
+                    settings.slices,
+                    settings.interval_seconds,
+                ))
+            },
+            StrategyType::OrderBookDepth => {
+                info!("启用订单簿深度分析套利策略");
+                let settings = &config.strategy_settings.order_book_depth;
+                Box::new(OrderBookDepthStrategy::new(
+                    config.clone(),
+                    api_arc.clone(),
+                    settings.depth_levels,
+                    settings.min_liquidity,
+                ))
+            },
+            StrategyType::SlippageControl => {
+                info!("启用滑点控制套利策略");
+                let settings = &config.strategy_settings.slippage_control;
+                Box::new(SlippageControlStrategy::new(
+                    config.clone(),
+                    settings.max_slippage_pct,
+                    settings.volatility_window_size,
+                ))
+            },
+            StrategyType::TrendFollowing => {
+                info!("启用趋势跟踪套利策略");
+                let settings = &config.strategy_settings.trend_following;
+                let strategy = TrendFollowingStrategy::new(
+                    config.clone(),
+                    settings.short_window,
+                    settings.long_window,
+                    settings.trend_threshold,
+                ).with_trend_method(
+                    settings.trend_method,
+                    settings.ema_smoothing,
+                );
+                match Self::liquidity_guard(config, api_arc) {
+                    Some(guard) => Box::new(strategy.with_liquidity_guard(guard)),
+                    None => Box::new(strategy),
+                }
+            },
+            StrategyType::Composite => {
+                info!("启用多策略加权组合");
+                let settings = &config.strategy_settings.composite;
+                let mut members: Vec<(Box<dyn TradingStrategy>, f64)> = Vec::new();
+                for (member_type, weight) in &settings.members {
+                    if *member_type == StrategyType::Composite {
+                        warn!("组合策略不能嵌套组合策略，已忽略");
+                        continue;
+                    }
+                    members.push((Self::build_strategy(member_type, config, api_arc), *weight));
+                }
+                Box::new(CompositeStrategy::new(config.clone(), members, settings.min_agreement))
+            },
+        }
+    }
+    
+    /// 获取两个交易对的最新价格，开启 `batch_price_fetch` 时一次请求获取
+    async fn fetch_ticker_prices(&self, usdt_symbol: &str, usdc_symbol: &str) -> Result<(Price, Price)> {
+        let batch = self.config.read().unwrap().arbitrage_settings.batch_price_fetch;
+        if !batch {
+            let usdt_price = self.api.get_price(usdt_symbol).await
+                .inspect_err(|_| self.metrics.record_api_error("get_price"))?;
+            let usdc_price = self.api.get_price(usdc_symbol).await
+                .inspect_err(|_| self.metrics.record_api_error("get_price"))?;
+            return Ok((usdt_price, usdc_price));
+        }
+        
+        let pairs = [(usdt_symbol.to_string(), usdc_symbol.to_string())];
+        let mut prices = fetch_pair_prices(self.api.as_ref(), &pairs).await
+            .inspect_err(|_| self.metrics.record_api_error("get_prices"))?;
+        Ok(prices.remove(0))
+    }
+    
+    /// 使用所有启用的策略寻找最佳套利机会
+    ///
+    /// 原始价差百分比低于 `price_diff_threshold` 时返回 `Ok(None)`，不运行策略，也不计算手续费和滑点。
+    /// 没有策略给出机会且两个交易对价格相同时同样返回 `Ok(None)`。
+    async fn find_best_arbitrage_opportunity(&self) -> Result<Option<ArbitrageOpportunity>> {
+        // 构造交易对名称
+        let usdt_symbol = format!("{}{}", self.base_asset, "USDT");
+        let usdc_symbol = format!("{}{}", self.base_asset, "USDC");
+        
+        // 获取价格
+        let (usdt_price, usdc_price) = self.fetch_ticker_prices(&usdt_symbol, &usdc_symbol).await?;
+        
+        debug!("{} 价格: {}", usdt_symbol, usdt_price.price);
+        debug!("{} 价格: {}", usdc_symbol, usdc_price.price);
+        
+        // 每轮行情都写入缓存，不论之后是否形成套利机会
+        self.price_cache.record(&usdt_price);
+        self.price_cache.record(&usdc_price);
+        
+        if let Some(spread) = spread_percentage(usdt_price.price, usdc_price.price) {
+            self.metrics.set_spread(&self.base_asset, spread.abs());
+        }
+        
+        // 先按原始价差过滤，明显过小的价差不值得运行完整的策略评估
+        if let Some(spread) = self.raw_spread_below_threshold(usdt_price.price, usdc_price.price) {
+            debug!("{} 原始价差 {}% 低于阈值，跳过策略评估", self.base_asset, spread);
+            return Ok(None);
+        }
+        
+        // 按策略偏好的来源获取参考价格，同一轮检查内相同来源只获取一次
+        let mut reference_prices: HashMap<PriceSource, (Price, Price)> = HashMap::new();
+        reference_prices.insert(PriceSource::Ticker, (usdt_price.clone(), usdc_price.clone()));
+        
+        let mut candidates: Vec<ArbitrageOpportunity> = Vec::new();
+        let max_trade_amount = self.max_trade_amount();
+        
+        // 使用每个策略寻找机会
+        let strategies = self.strategies.read().unwrap().clone();
+        for strategy in strategies.iter() {
+            let source = strategy.price_source();
+            if !reference_prices.contains_key(&source) {
+                let prices = tokio::try_join!(
+                    self.api.get_reference_price(&usdt_symbol, source),
+                    self.api.get_reference_price(&usdc_symbol, source),
+                );
+                match prices {
+                    Ok(prices) => {
+                        debug!("{} 参考价格 - {}: {}, {}: {}", source, usdt_symbol, prices.0.price, usdc_symbol, prices.1.price);
+                        reference_prices.insert(source, prices);
+                    },
+                    Err(e) => {
+                        warn!("策略 {} 获取 {} 参考价格失败: {}", strategy.name(), source, e);
+                        self.metrics.record_api_error("get_reference_price");
+                        continue;
+                    }
+                }
+            }
+            let (usdt_price, usdc_price) = &reference_prices[&source];
+            
+            match strategy.find_opportunity(&self.base_asset, usdt_price, usdc_price).await {
+                Ok(Some(mut opportunity)) => {
+                    opportunity.strategy = Some(strategy.name().to_string());
+                    // 验证是否符合策略要求
+                    match strategy.validate_opportunity(&opportunity).await {
+                        Ok((true, _)) => {
+                            debug!(
+                                "策略 {} 发现套利机会: 利润率 {}%, 价差: {}, 交易金额: {}",
+                                strategy.name(), opportunity.profit_percentage, opportunity.price_diff, opportunity.max_trade_amount
+                            );
+                            // 按实际可交易的金额排序
+                            opportunity.max_trade_amount = opportunity.max_trade_amount.min(max_trade_amount);
+                            candidates.push(opportunity);
+                        },
+                        Ok((false, rejection)) => {
+                            match rejection {
+                                Some(rejection) => debug!(
+                                    "策略 {} 发现机会但验证失败 ({}): {}",
+                                    strategy.name(), rejection.reason, rejection
+                                ),
+                                None => debug!(
+                                    "策略 {} 发现机会但验证失败: 利润率 {}%",
+                                    strategy.name(), opportunity.profit_percentage
+                                ),
+                            }
+                        },
+                        Err(e) => {
+                            warn!("策略 {} 验证出错: {}", strategy.name(), e);
+                        }
+                    }
+                },
+                Ok(None) => {
+                    debug!("策略 {} 未发现有效套利机会", strategy.name());
+                },
+                Err(e) => {
+                    warn!("策略 {} 寻找机会出错: {}", strategy.name(), e);
+                }
+            }
+        }
+        
+        let ranking = self.config.read().unwrap().arbitrage_settings.opportunity_ranking;
+        let volatility = if ranking == OpportunityRanking::ByRiskAdjusted {
+            self.recent_volatility(&usdt_symbol, &usdc_symbol)
+        } else {
+            Decimal::ZERO
+        };
+        let best_opportunity = select_best_opportunity(candidates, ranking, volatility);
+        
+        // 如果没有找到任何机会，创建一个基本的机会（默认使用简单策略的逻辑）
+        let Some(best_opportunity) = best_opportunity else {
+            let opportunity = if let Some(costs) = self.execution_costs().await {
+                let opportunity = best_execution_opportunity(
+                    &self.base_asset,
+                    usdt_price.price,
+                    usdc_price.price,
+                    max_trade_amount,
+                    &costs,
+                );
+                debug!(
+                    "按净收益选择方向: {} 买入, {} 卖出, 扣除手续费和兑换后利润率 {}%",
+                    opportunity.buy_quote, opportunity.sell_quote, opportunity.profit_percentage
+                );
+                opportunity
+            } else if usdt_price.price == usdc_price.price {
+                // 与简单策略相同，不按兑换价格折算时价格相同即没有价差
+                return Ok(None);
+            } else if usdt_price.price < usdc_price.price {
+                // USDT买入，USDC卖出
+                ArbitrageOpportunity::new(
+                    &self.base_asset,
+                    QuoteCurrency::USDT,
+                    QuoteCurrency::USDC,
+                    usdt_price.price,
+                    usdc_price.price,
+                    max_trade_amount,
+                )
+            } else {
+                // USDC买入，USDT卖出
+                ArbitrageOpportunity::new(
+                    &self.base_asset,
+                    QuoteCurrency::USDC,
+                    QuoteCurrency::USDT,
+                    usdc_price.price,
+                    usdt_price.price,
+                    max_trade_amount,
+                )
+            };
+            
+            // 没有策略验证时仍按配置的最小利润率过滤
+            let min_profit = self.config.read().unwrap().arbitrage_settings.min_profit_percentage;
+            if opportunity.profit_percentage < min_profit {
+                debug!("{} 利润率 {}% 低于最小利润率 {}%", self.base_asset, opportunity.profit_percentage, min_profit);
+                return Ok(None);
+            }
+            
+            return Ok(Some(opportunity));
+        };
+        
+        debug!(
+            "按 {:?} 选择套利机会 (策略: {}): 利润率 {}%, 交易金额: {}",
+            ranking,
+            best_opportunity.strategy.as_deref().unwrap_or("-"),
+            best_opportunity.profit_percentage,
+            best_opportunity.max_trade_amount
+        );
+        Ok(Some(best_opportunity))
+    }
+    
+    /// 原始价差百分比的绝对值低于 `price_diff_threshold` 时返回该价差，阈值为0时不过滤
+    ///
+    /// 价格无效时无法计算价差，同样视为低于阈值。
+    fn raw_spread_below_threshold(&self, usdt_price: Decimal, usdc_price: Decimal) -> Option<Decimal> {
+        let threshold = self.config.read().unwrap().arbitrage_settings.price_diff_threshold;
+        if threshold <= Decimal::ZERO {
+            return None;
+        }
+        match spread_percentage(usdt_price, usdc_price) {
+            Some(spread) if spread.abs() >= threshold => None,
+            spread => Some(spread.map_or(Decimal::ZERO, |spread| spread.abs())),
+        }
+    }
+    
+    /// 启用最优执行时两个交易对的手续费率和USDC兑USDT的价格，未启用时为None
+    async fn execution_costs(&self) -> Option<ExecutionCosts> {
+        let settings = self.config.read().unwrap().arbitrage_settings.best_execution.clone();
+        if !settings.enabled {
+            return None;
+        }
+        
+        let usdc_rate = match self.api.get_price(&settings.conversion_symbol).await {
+            Ok(price) if price.price > Decimal::ZERO => price.price,
+            Ok(price) => {
+                warn!("{} 价格无效: {}，按1:1折算", settings.conversion_symbol, price.price);
+                Decimal::ONE
+            },
+            Err(e) => {
+                warn!("获取 {} 价格失败: {}，按1:1折算", settings.conversion_symbol, e);
+                self.metrics.record_api_error("get_price");
+                Decimal::ONE
+            }
+        };
+        Some(ExecutionCosts {
+            usdt_fee_rate: settings.usdt_fee_rate,
+            usdc_fee_rate: settings.usdc_fee_rate,
+            usdc_rate,
+        })
+    }
+    
+    /// 行情缓存中两个交易对的价格波动率（百分比），取较大者
+    fn recent_volatility(&self, usdt_symbol: &str, usdc_symbol: &str) -> Decimal {
+        [usdt_symbol, usdc_symbol]
+            .iter()
+            .map(|symbol| {
+                let prices: Vec<Decimal> = self.price_cache.history(symbol, None).iter().map(|tick| tick.price.price).collect();
+                price_volatility(&prices)
+            })
+            .max()
+            .unwrap_or(Decimal::ZERO)
+    }
+    
+    /// 订单是否已结束且未完全成交（IOC/FOK过期、被拒绝或已取消）
+    fn is_terminal_unfilled(status: OrderStatus) -> bool {
+        matches!(status, OrderStatus::Expired | OrderStatus::Rejected | OrderStatus::Cancelled)
+    }
+    
+    /// 等待订单成交或结束，最长等待 `timeout`，返回最后获取到的订单状态
+    ///
+    /// 用户数据流已连接时等待推送的订单状态，未连接（包括等待期间断开）时每秒轮询一次。
+    async fn wait_for_order(&self, symbol: &str, order: OrderInfo, label: &str, timeout: Duration) -> Result<OrderInfo> {
+        let is_done = |status: OrderStatus| status == OrderStatus::Filled || Self::is_terminal_unfilled(status);
+        let order_id = order.order_id;
+        let deadline = Instant::now() + timeout;
+        // 先订阅再检查最新状态，避免错过两者之间到达的推送
+        let mut changed = self.order_updates.subscribe();
+        let mut current = order;
+        let mut waited_for_stream = false;
+        
+        loop {
+            if !is_done(current.status) {
+                if let Some(update) = self.order_updates.latest(order_id) {
+                    current = update;
+                }
+            }
+            if is_done(current.status) {
+                break;
+            }
+            
+            let now = Instant::now();
+            if now >= deadline {
+                break;
+            }
+            
+            if self.order_updates.is_connected() {
+                waited_for_stream = true;
+                // 推送的状态在下一次循环中检查，超时后循环结束
+                let _ = tokio::time::timeout(deadline - now, changed.recv()).await;
+            } else {
+                sleep(ORDER_POLL_INTERVAL.min(deadline - now)).await;
+                current = self.api.get_order_status(symbol, order_id).await?;
+                info!("{}订单状态: {:?}", label, current.status);
+            }
+        }
+        
+        // 数据流可能漏掉推送，超时前确认一次订单状态
+        if waited_for_stream && !is_done(current.status) {
+            current = self.api.get_order_status(symbol, order_id).await?;
+        }
+        self.order_updates.forget(order_id);
+        Ok(current)
+    }
+    
+    /// 执行套利交易
+    ///
+    /// 每个订单结束（成交、过期或取消）后将其最终状态加入 `orders`，执行失败时已结束的订单同样保留。
+    async fn execute_arbitrage(&self, opportunity: &ArbitrageOpportunity, orders: &mut Vec<OrderRecord>) -> Result<ArbitrageResult> {
+        // 执行期间计入持仓数，返回或任务被取消时释放
+        let _position = self.open_positions.open();
+        // 执行期间跟踪已提交的订单，避免被过期挂单清理取消
+        let mut tracked_orders = TrackedOrders::new(&self.active_orders, &self.placed_orders);
+        
+        // 计算交易量
+        let trade_amount_quote = opportunity.max_trade_amount;
+        let trade_amount_base = trade_amount_quote / opportunity.buy_price;
+        
+        let mut result = ArbitrageResult {
+            base_asset: opportunity.base_asset.clone(),
+            buy_quote: opportunity.buy_quote.to_string(),
+            sell_quote: opportunity.sell_quote.to_string(),
+            buy_price: opportunity.buy_price,
+            sell_price: opportunity.sell_price,
+            trade_amount: trade_amount_base,
+            profit: Decimal::ZERO,
+            profit_percentage: opportunity.profit_percentage,
+            buy_order_id: None,
+            sell_order_id: None,
+            status: ArbitrageStatus::Identified,
+            timestamp: opportunity.timestamp,
+            start_time: Utc::now(),
+            end_time: Utc::now(),
+            buy_fill: FillInfo::default(),
+            sell_fill: FillInfo::default(),
+            mode: self.mode,
+            config_hash: self.config_hash(),
+            strategy: opportunity.strategy.clone(),
+            opportunity_id: Some(opportunity.opportunity_id),
+        };
+        
+        // 构造交易对
+        let buy_symbol = format!("{}{}", opportunity.base_asset, opportunity.buy_quote);
+        let sell_symbol = format!("{}{}", opportunity.base_asset, opportunity.sell_quote);
+        
+        info!("执行套利交易 [{}] - 买入: {} @ {}, 卖出: {} @ {}, 数量: {}", 
+            opportunity.opportunity_id,
+            buy_symbol, opportunity.buy_price,
+            sell_symbol, opportunity.sell_price,
+            trade_amount_base
+        );
+        
+        // 每边按各自的订单类型下单：限价单按机会价格下单，市价单不指定价格
+        let (time_in_force, buy_type, sell_type, limit_timeout, max_deviation) = {
+            let config = self.config.read().unwrap();
+            let (buy_type, sell_type, time_in_force) = config.order_types();
+            (
+                time_in_force,
+                buy_type,
+                sell_type,
+                Duration::from_millis(config.arbitrage_settings.limit_order_timeout_ms),
+                config.execution.max_fill_deviation_pct,
+            )
+        };
+        let limit_price = |order_type: OrderType, price: Decimal| (order_type == OrderType::Limit).then_some(price);
+        let wait_timeout = |order_type: OrderType| match order_type {
+            OrderType::Market => MARKET_ORDER_WAIT_TIMEOUT,
+            OrderType::Limit => limit_timeout,
+        };
+        let buy_limit = limit_price(buy_type, opportunity.buy_price);
+        let sell_limit = limit_price(sell_type, opportunity.sell_price);
+        
+        // 执行买入订单
+        let buy_client_id = opportunity.client_order_id(Side::Buy);
+        let buy_order = match self.api.place_order(&buy_symbol, Side::Buy, trade_amount_base, buy_limit, time_in_force, Some(&buy_client_id)).await {
+            Ok(order) => {
+                info!("买入订单已提交: ID={}, 客户端ID={}, 类型={:?}, 状态={:?}", order.order_id, buy_client_id, buy_type, order.status);
+                tracked_orders.track(order.order_id);
+                result.buy_order_id = Some(order.order_id);
+                result.status = ArbitrageStatus::BuyOrderPlaced;
+                order
+            },
+            Err(e) => {
+                result.status = ArbitrageStatus::Failed;
+                return Err(anyhow!("买入订单失败: {}", e));
+            }
+        };
+        
+        // 等待买入订单完成
+        let buy_order_id = buy_order.order_id;
+        let buy_order_status = self.wait_for_order(&buy_symbol, buy_order, "买入", wait_timeout(buy_type)).await?;
+        
+        if Self::is_terminal_unfilled(buy_order_status.status) {
+            let reason = format!("买入订单未能成交: {:?} (已成交 {})", buy_order_status.status, buy_order_status.executed_qty);
+            // 部分成交后过期的订单已买入部分基础资产，卖回后结束
+            if buy_order_status.executed_qty > Decimal::ZERO {
+                result.buy_fill = FillInfo::from_order(&buy_order_status);
+                orders.push(OrderRecord::resolved(buy_order_status));
+                return self.unwind_bought(opportunity, result, reason, time_in_force, orders, &mut tracked_orders).await;
+            }
+            orders.push(OrderRecord::resolved(buy_order_status));
+            result.status = ArbitrageStatus::Failed;
+            return Err(anyhow!(reason));
+        }
+        
+        if buy_order_status.status != OrderStatus::Filled {
+            info!("取消买入订单...");
+            let cancelled = self.api.cancel_order(&buy_symbol, buy_order_id).await?;
+            let reason = format!("买入订单未在预期时间内完成 (已成交 {})", cancelled.executed_qty);
+            if cancelled.executed_qty > Decimal::ZERO {
+                result.buy_fill = FillInfo::from_order(&cancelled);
+                orders.push(OrderRecord::resolved(cancelled));
+                return self.unwind_bought(opportunity, result, reason, time_in_force, orders, &mut tracked_orders).await;
+            }
+            orders.push(OrderRecord::resolved(cancelled));
+            result.status = ArbitrageStatus::Failed;
+            return Err(anyhow!(reason));
+        }
+        
+        // 查询最终订单信息，记录实际成交数量、均价和手续费
+        let buy_final = self.api.get_order_status(&buy_symbol, buy_order_id).await?;
+        result.buy_fill = FillInfo::from_order(&buy_final);
+        orders.push(OrderRecord::resolved(buy_final));
+        result.status = ArbitrageStatus::BuyOrderFilled;
+        
+        // 买入均价相对机会报价的不利偏离超过限制时，卖回已买入的资产，不再执行卖出腿
+        if let Some(deviation) = Self::adverse_deviation_pct(Side::Buy, opportunity.buy_price, result.buy_fill.avg_price) {
+            if max_deviation > Decimal::ZERO && deviation > max_deviation {
+                let reason = format!(
+                    "买入成交均价 {} 偏离报价 {} 达 {}%，超过限制 {}%",
+                    result.buy_fill.avg_price, opportunity.buy_price, deviation.round_dp(4), max_deviation
+                );
+                return self.unwind_bought(opportunity, result, reason, time_in_force, orders, &mut tracked_orders).await;
+            }
+        }
+        
+        // 执行卖出订单，卖出实际到账的数量（以基础资产收取的买入手续费已扣除）
+        let sell_qty = result.buy_fill.received_base_qty(&opportunity.base_asset);
+        let sell_client_id = opportunity.client_order_id(Side::Sell);
+        let sell_order = match self.api.place_order(&sell_symbol, Side::Sell, sell_qty, sell_limit, time_in_force, Some(&sell_client_id)).await {
+            Ok(order) => {
+                info!("卖出订单已提交: ID={}, 客户端ID={}, 类型={:?}, 状态={:?}", order.order_id, sell_client_id, sell_type, order.status);
+                tracked_orders.track(order.order_id);
+                result.sell_order_id = Some(order.order_id);
+                result.status = ArbitrageStatus::SellOrderPlaced;
+                order
+            },
+            Err(e) => {
+                let reason = format!("卖出订单失败: {}", e);
+                return self.unwind_bought(opportunity, result, reason, time_in_force, orders, &mut tracked_orders).await;
+            }
+        };
+        
+        // 等待卖出订单完成
+        let sell_order_id = sell_order.order_id;
+        let sell_order_status = self.wait_for_order(&sell_symbol, sell_order, "卖出", wait_timeout(sell_type)).await?;
+        
+        if Self::is_terminal_unfilled(sell_order_status.status) {
+            let reason = format!("卖出订单未能成交: {:?} (已成交 {})", sell_order_status.status, sell_order_status.executed_qty);
+            result.sell_fill = FillInfo::from_order(&sell_order_status);
+            orders.push(OrderRecord::resolved(sell_order_status));
+            return self.unwind_bought(opportunity, result, reason, time_in_force, orders, &mut tracked_orders).await;
+        }
+        
+        if sell_order_status.status != OrderStatus::Filled {
+            info!("取消卖出订单...");
+            let cancelled = self.api.cancel_order(&sell_symbol, sell_order_id).await?;
+            let reason = format!("卖出订单未在预期时间内完成 (已成交 {})", cancelled.executed_qty);
+            result.sell_fill = FillInfo::from_order(&cancelled);
+            orders.push(OrderRecord::resolved(cancelled));
+            return self.unwind_bought(opportunity, result, reason, time_in_force, orders, &mut tracked_orders).await;
+        }
+        
+        let sell_final = self.api.get_order_status(&sell_symbol, sell_order_id).await?;
+        result.sell_fill = FillInfo::from_order(&sell_final);
+        orders.push(OrderRecord::resolved(sell_final));
+        result.status = ArbitrageStatus::Completed;
+        
+        if let Some(deviation) = Self::adverse_deviation_pct(Side::Sell, opportunity.sell_price, result.sell_fill.avg_price) {
+            if max_deviation > Decimal::ZERO && deviation > max_deviation {
+                warn!(
+                    "卖出成交均价 {} 偏离报价 {} 达 {}%，超过限制 {}%",
+                    result.sell_fill.avg_price, opportunity.sell_price, deviation.round_dp(4), max_deviation
+                );
+            }
+        }
+        
+        // 按实际成交金额和手续费计算净利润
+        let profit = self.profit_calculator().realized_profit(
+            &opportunity.base_asset,
+            &[(opportunity.buy_quote, &result.buy_fill)],
+            &[(opportunity.sell_quote, &result.sell_fill)],
+        );
+        
+        result.profit = profit;
+        result.end_time = Utc::now();
+        
+        info!("套利交易完成! 利润: {}", profit);
+        Ok(result)
+    }
+    
+    /// 买入腿部分成交或卖出腿失败后，将已买入但未卖出的基础资产按市价卖回买入交易对
+    ///
+    /// 返回状态为 `PartiallyUnwound` 的结果，利润为卖出腿已成交部分和回补的所得减去买入成本及三笔订单的手续费，
+    /// 通常为负数，与已完成的套利一样计入每日亏损等风控统计。回补下单或成交失败时返回错误，基础资产留在账户中。
+    async fn unwind_bought(
+        &self,
+        opportunity: &ArbitrageOpportunity,
+        mut result: ArbitrageResult,
+        reason: String,
+        time_in_force: TimeInForce,
+        orders: &mut Vec<OrderRecord>,
+        tracked_orders: &mut TrackedOrders<'_>,
+    ) -> Result<ArbitrageResult> {
+        let buy_symbol = &format!("{}{}", opportunity.base_asset, opportunity.buy_quote);
+        let remaining = result.buy_fill.received_base_qty(&result.base_asset) - result.sell_fill.qty;
+        let mut unwind_fill = FillInfo::default();
+        if remaining > Decimal::ZERO {
+            warn!("{}，按市价卖回 {} {} 回补已买入的 {}", reason, buy_symbol, remaining, result.base_asset);
+            let client_id = opportunity.unwind_client_order_id();
+            let order = self.api.place_order(buy_symbol, Side::Sell, remaining, None, time_in_force, Some(&client_id)).await
+                .map_err(|e| anyhow!("{}，回补下单失败，{} {} 需人工处理: {}", reason, remaining, result.base_asset, e))?;
+            tracked_orders.track(order.order_id);
+            let order_id = order.order_id;
+            let status = self.wait_for_order(buy_symbol, order, "回补", MARKET_ORDER_WAIT_TIMEOUT).await?;
+            if status.status != OrderStatus::Filled {
+                orders.push(OrderRecord::resolved(status.clone()));
+                return Err(anyhow!("{}，回补订单未能成交: {:?} (已成交 {})", reason, status.status, status.executed_qty));
+            }
+            
+            let unwind_final = self.api.get_order_status(buy_symbol, order_id).await?;
+            unwind_fill = FillInfo::from_order(&unwind_final);
+            orders.push(OrderRecord::resolved(unwind_final));
+        }
+        
+        result.profit = self.profit_calculator().realized_profit(
+            &result.base_asset,
+            &[(opportunity.buy_quote, &result.buy_fill)],
+            &[(opportunity.sell_quote, &result.sell_fill), (opportunity.buy_quote, &unwind_fill)],
+        );
+        result.status = ArbitrageStatus::PartiallyUnwound;
+        result.end_time = Utc::now();
+        
+        warn!("套利已回补: {}，已实现盈亏: {}", reason, result.profit);
         Ok(result)
     }
 }
+
+/// 每隔 `interval` 检查配置文件，内容变化后发送一次触发信号，触发通道关闭后返回
+///
+/// 与 `ArbitrageEngine::watch_config_reloads` 配合使用。读取失败（例如编辑器保存时文件短暂不存在）时跳过本次检查，
+/// 上一次加载尚未完成时合并触发。
+pub async fn watch_config_file(path: PathBuf, interval: Duration, trigger: mpsc::Sender<()>) {
+    let mut last_contents = std::fs::read(&path).ok();
+    loop {
+        sleep(interval).await;
+        if trigger.is_closed() {
+            return;
+        }
+        
+        let Ok(contents) = std::fs::read(&path) else {
+            continue;
+        };
+        if last_contents.as_ref() != Some(&contents) {
+            debug!("配置文件 {} 已变化", path.display());
+            last_contents = Some(contents);
+            if let Err(mpsc::error::TrySendError::Closed(_)) = trigger.try_send(()) {
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::binance::MockBinanceApi;
+    use crate::config::{ApiLatencySettings, ArbitrageSettings, DustSweepSettings, FeeSettings, ProfitDistributionSettings, RiskSettings};
+    use crate::models::SystemStatus;
+    use crate::db::testing::MemoryStore;
+
+    fn test_config() -> Config {
+        Config {
+            arbitrage_settings: ArbitrageSettings {
+                check_interval_ms: 10,
+                system_status_check_interval_ms: 10,
+                ..ArbitrageSettings::default()
+            },
+            risk_settings: RiskSettings {
+                enabled_controllers: Vec::new(),
+                ..RiskSettings::default()
+            },
+            // 测试行情的价差小于默认手续费，手续费设为0
+            fees: FeeSettings { maker_rate: Decimal::ZERO, taker_rate: Decimal::ZERO, ..FeeSettings::default() },
+            ..Config::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_maintenance_pauses_and_resumes_trading() {
+        let api = MockBinanceApi::new();
+        let engine = ArbitrageEngine::new(api.clone(), test_config(), "BTC").unwrap();
+
+        assert!(engine.refresh_system_status().await);
+        assert!(!engine.is_trading_paused());
+
+        api.set_system_status(SystemStatus::maintenance("system maintenance"));
+        assert!(!engine.refresh_system_status().await);
+        assert!(engine.is_trading_paused());
+
+        api.set_system_status(SystemStatus::normal());
+        assert!(engine.refresh_system_status().await);
+        assert!(!engine.is_trading_paused());
+    }
+
+    #[tokio::test]
+    async fn test_no_orders_during_maintenance() {
+        let api = MockBinanceApi::new();
+        // 制造明显的价差，正常状态下会触发套利
+        api.update_price("BTCUSDC", dec!(51000));
+        api.set_system_status(SystemStatus::maintenance("system maintenance"));
+
+        let engine = ArbitrageEngine::new(api.clone(), test_config(), "BTC").unwrap();
+        let usdt_before = api.get_account_balance("USDT").await.unwrap();
+        let btc_before = api.get_account_balance("BTC").await.unwrap();
+
+        let monitor = tokio::time::timeout(Duration::from_millis(100), engine.monitor_opportunities()).await;
+        assert!(monitor.is_err(), "监控循环在维护期间应持续运行");
+        assert!(engine.is_trading_paused());
+
+        assert_eq!(api.get_account_balance("USDT").await.unwrap(), usdt_before);
+        assert_eq!(api.get_account_balance("BTC").await.unwrap(), btc_before);
+
+        // 恢复正常后监控循环重新检查状态并解除暂停
+        api.set_system_status(SystemStatus::normal());
+        let _ = tokio::time::timeout(Duration::from_millis(30), engine.monitor_opportunities()).await;
+        assert!(!engine.is_trading_paused());
+    }
+
+    #[tokio::test]
+    async fn test_execute_arbitrage_captures_fills() {
+        let api = MockBinanceApi::new();
+        api.set_commission_rate(dec!(0.001));
+
+        let engine = ArbitrageEngine::new(api.clone(), test_config(), "BTC").unwrap();
+        let opportunity = ArbitrageOpportunity::new(
+            "BTC",
+            QuoteCurrency::USDT,
+            QuoteCurrency::USDC,
+            dec!(50000),
+            dec!(50025),
+            dec!(1000),
+        );
+
+        let mut orders = Vec::new();
+        let result = engine.execute_arbitrage(&opportunity, &mut orders).await.unwrap();
+        assert_eq!(result.status, ArbitrageStatus::Completed);
+
+        // 两个订单按执行顺序记录最终状态
+        assert_eq!(orders.len(), 2);
+        assert_eq!(orders[0].order.side, Side::Buy);
+        assert_eq!(orders[0].order.symbol, "BTCUSDT");
+        assert_eq!(Some(orders[0].order.order_id), result.buy_order_id);
+        assert_eq!(orders[1].order.side, Side::Sell);
+        assert_eq!(orders[1].order.symbol, "BTCUSDC");
+        assert!(orders.iter().all(|o| o.order.status == OrderStatus::Filled && o.filled_at.is_some()));
+
+        assert_eq!(result.buy_fill.qty, dec!(0.02));
+        assert_eq!(result.buy_fill.avg_price, dec!(50000));
+        assert_eq!(result.buy_fill.commission, dec!(0.00002));
+        assert_eq!(result.buy_fill.commission_asset, "BTC");
+
+        // 只卖出扣除BTC手续费后到账的数量
+        assert_eq!(result.sell_fill.qty, dec!(0.01998));
+        assert_eq!(result.sell_fill.avg_price, dec!(50025));
+        assert_eq!(result.sell_fill.commission, dec!(0.9994995));
+        assert_eq!(result.sell_fill.commission_asset, "USDC");
+
+        // 999.4995 - 0.9994995 USDC - 1000
+        assert_eq!(result.profit, dec!(-1.4999995));
+    }
+
+    #[tokio::test]
+    async fn test_realized_profit_matches_opportunity_estimate() {
+        let api = MockBinanceApi::new();
+        api.update_price("BTCUSDT", dec!(50000));
+        api.update_price("BTCUSDC", dec!(50500));
+        api.set_commission_rate(dec!(0.001));
+
+        // 市价单按吃单费率估算，与模拟交易所收取的手续费相同
+        let mut config = test_config();
+        config.fees = FeeSettings::default();
+        let engine = ArbitrageEngine::new(api.clone(), config, "BTC").unwrap();
+        let opportunity = ArbitrageOpportunity::new(
+            "BTC",
+            QuoteCurrency::USDT,
+            QuoteCurrency::USDC,
+            dec!(50000),
+            dec!(50500),
+            dec!(1000),
+        );
+        let estimated = engine.profit_calculator().estimated_profit(&opportunity);
+
+        let mut orders = Vec::new();
+        let result = engine.execute_arbitrage(&opportunity, &mut orders).await.unwrap();
+        assert_eq!(result.status, ArbitrageStatus::Completed);
+        // 1010 × 0.999 - 1000 × 1.001
+        assert_eq!(estimated, dec!(7.99));
+        // 买入手续费以BTC收取，卖出 0.01998 BTC：1008.99 × 0.999 - 1000，与估算只差手续费的手续费
+        assert_eq!(result.profit, dec!(7.98101));
+        assert!((result.profit - estimated).abs() < dec!(0.01));
+    }
+
+    #[tokio::test]
+    async fn test_opportunity_id_carried_to_result_and_orders() {
+        let api = MockBinanceApi::new();
+        let engine = ArbitrageEngine::new(api.clone(), test_config(), "BTC").unwrap();
+        let opportunity = ArbitrageOpportunity::new(
+            "BTC",
+            QuoteCurrency::USDT,
+            QuoteCurrency::USDC,
+            dec!(50000),
+            dec!(50025),
+            dec!(1000),
+        );
+        // 每个机会的ID不同
+        let other = ArbitrageOpportunity::new("BTC", QuoteCurrency::USDT, QuoteCurrency::USDC, dec!(50000), dec!(50025), dec!(1000));
+        assert_ne!(opportunity.opportunity_id, other.opportunity_id);
+
+        let mut orders = Vec::new();
+        let result = engine.execute_arbitrage(&opportunity, &mut orders).await.unwrap();
+        assert_eq!(result.opportunity_id, Some(opportunity.opportunity_id));
+
+        // 订单的客户端ID以机会ID为前缀，并且不超过币安限制的36个字符
+        let prefix = opportunity.opportunity_id.simple().to_string();
+        let buy_client_id = api.client_order_id(result.buy_order_id.unwrap()).unwrap();
+        let sell_client_id = api.client_order_id(result.sell_order_id.unwrap()).unwrap();
+        assert_eq!(buy_client_id, format!("{}-b", prefix));
+        assert_eq!(sell_client_id, format!("{}-s", prefix));
+        assert!(buy_client_id.len() <= 36);
+    }
+
+    #[tokio::test]
+    async fn test_opportunity_id_persisted_with_result() {
+        use crate::db::TradeHistoryFilter;
+
+        let api = MockBinanceApi::new();
+        let engine = ArbitrageEngine::new(api.clone(), test_config(), "BTC").unwrap();
+        let db = DatabaseManager::new("sqlite::memory:").await.unwrap();
+        let opportunity = ArbitrageOpportunity::new(
+            "BTC",
+            QuoteCurrency::USDT,
+            QuoteCurrency::USDC,
+            dec!(50000),
+            dec!(50025),
+            dec!(1000),
+        );
+
+        let mut orders = Vec::new();
+        let result = engine.execute_arbitrage(&opportunity, &mut orders).await.unwrap();
+        db.record_arbitrage_result(&result).await.unwrap();
+
+        let records = db.get_trade_history(&TradeHistoryFilter::default(), 10, 0).await.unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].result.opportunity_id, Some(opportunity.opportunity_id));
+    }
+
+    #[tokio::test]
+    async fn test_profit_distribution_transfers_portion_above_threshold() {
+        let api = MockBinanceApi::new();
+        let mut config = test_config();
+        config.arbitrage_settings.profit_distribution = ProfitDistributionSettings {
+            enabled: true,
+            threshold_usdt: dec!(100),
+            portion: dec!(0.5),
+            asset: "USDT".to_string(),
+            transfer_enabled: true,
+            to_email: "savings@example.com".to_string(),
+        };
+        let engine = ArbitrageEngine::new(api.clone(), config, "BTC").unwrap();
+
+        engine.distribute_profit(dec!(60)).await;
+        assert!(api.sub_account_transfers().is_empty());
+
+        engine.distribute_profit(dec!(60)).await;
+        let transfers = api.sub_account_transfers();
+        assert_eq!(transfers.len(), 1);
+        assert_eq!(transfers[0].to_email, "savings@example.com");
+        assert_eq!(transfers[0].asset, "USDT");
+        assert_eq!(transfers[0].amount, dec!(60));
+        assert_eq!(api.get_account_balance("USDT").await.unwrap(), dec!(9940));
+
+        let state = engine.profit_distribution();
+        assert_eq!(state.pending, Decimal::ZERO);
+        assert_eq!(state.transferred, dec!(60));
+    }
+
+    #[tokio::test]
+    async fn test_profit_distribution_earmarks_without_transfer() {
+        let api = MockBinanceApi::new();
+        let mut config = test_config();
+        config.arbitrage_settings.profit_distribution = ProfitDistributionSettings {
+            enabled: true,
+            threshold_usdt: dec!(100),
+            portion: dec!(0.25),
+            ..ProfitDistributionSettings::default()
+        };
+        let engine = ArbitrageEngine::new(api.clone(), config, "BTC").unwrap();
+
+        engine.distribute_profit(dec!(120)).await;
+        assert!(api.sub_account_transfers().is_empty());
+        assert_eq!(engine.profit_distribution().earmarked, dec!(30));
+    }
+
+    #[tokio::test]
+    async fn test_profit_distribution_retries_after_failed_transfer() {
+        let api = MockBinanceApi::new();
+        api.set_balance("USDT", dec!(10));
+        let mut config = test_config();
+        config.arbitrage_settings.profit_distribution = ProfitDistributionSettings {
+            enabled: true,
+            transfer_enabled: true,
+            to_email: "savings@example.com".to_string(),
+            ..ProfitDistributionSettings::default()
+        };
+        let engine = ArbitrageEngine::new(api.clone(), config, "BTC").unwrap();
+
+        // 余额不足划转失败，累计利润保留
+        engine.distribute_profit(dec!(100)).await;
+        assert!(api.sub_account_transfers().is_empty());
+        assert_eq!(engine.profit_distribution().pending, dec!(100));
+
+        api.set_balance("USDT", dec!(1000));
+        engine.distribute_profit(dec!(20)).await;
+        assert_eq!(api.sub_account_transfers()[0].amount, dec!(60));
+    }
+
+    #[tokio::test]
+    async fn test_failed_arbitrage_keeps_resolved_orders() {
+        let api = MockBinanceApi::new();
+        // 卖出一侧深度不足，FOK卖单整单过期
+        api.set_order_book("BTCUSDC", vec![(dec!(50025), dec!(0.001))], vec![(dec!(50030), dec!(1))]);
+
+        let mut config = test_config();
+        config.arbitrage_settings.use_limit_orders = true;
+        config.arbitrage_settings.time_in_force = TimeInForce::FOK;
+        let engine = ArbitrageEngine::new(api.clone(), config, "BTC").unwrap();
+        let opportunity = ArbitrageOpportunity::new(
+            "BTC",
+            QuoteCurrency::USDT,
+            QuoteCurrency::USDC,
+            dec!(50000),
+            dec!(50025),
+            dec!(1000),
+        );
+
+        // 卖出腿失败后回补，结果和三笔订单都保留
+        let mut orders = Vec::new();
+        let result = engine.execute_arbitrage(&opportunity, &mut orders).await.unwrap();
+        assert_eq!(result.status, ArbitrageStatus::PartiallyUnwound);
+        assert_eq!(result.sell_fill.qty, Decimal::ZERO);
+
+        assert_eq!(orders.len(), 3);
+        assert_eq!(orders[0].order.status, OrderStatus::Filled);
+        assert!(orders[0].filled_at.is_some());
+        assert_eq!(orders[1].order.side, Side::Sell);
+        assert_eq!(orders[1].order.status, OrderStatus::Expired);
+        assert_eq!(orders[1].order.executed_qty, Decimal::ZERO);
+        assert!(orders[1].filled_at.is_none());
+        // 回补在买入交易对按市价卖回全部买入数量
+        assert_eq!(orders[2].order.symbol, "BTCUSDT");
+        assert_eq!(orders[2].order.side, Side::Sell);
+        assert_eq!(orders[2].order.status, OrderStatus::Filled);
+        assert_eq!(orders[2].order.executed_qty, orders[0].order.executed_qty);
+    }
+
+    /// 按指定的两边订单类型执行一次套利；市价单按当前价格成交，限价单按机会价格成交
+    async fn execute_with_order_types(buy_type: OrderType, sell_type: OrderType) -> ArbitrageResult {
+        let api = MockBinanceApi::new();
+        api.update_price("BTCUSDT", dec!(49990));
+        api.update_price("BTCUSDC", dec!(50020));
+
+        let mut config = test_config();
+        config.arbitrage_settings.buy_order_type = Some(buy_type);
+        config.arbitrage_settings.sell_order_type = Some(sell_type);
+        let engine = ArbitrageEngine::new(api, config, "BTC").unwrap();
+        let opportunity = ArbitrageOpportunity::new(
+            "BTC",
+            QuoteCurrency::USDT,
+            QuoteCurrency::USDC,
+            dec!(50000),
+            dec!(50025),
+            dec!(1000),
+        );
+
+        let mut orders = Vec::new();
+        engine.execute_arbitrage(&opportunity, &mut orders).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_market_buy_limit_sell() {
+        let result = execute_with_order_types(OrderType::Market, OrderType::Limit).await;
+        assert_eq!(result.status, ArbitrageStatus::Completed);
+        assert_eq!(result.buy_fill.avg_price, dec!(49990));
+        assert_eq!(result.sell_fill.avg_price, dec!(50025));
+    }
+
+    #[tokio::test]
+    async fn test_limit_buy_market_sell() {
+        let result = execute_with_order_types(OrderType::Limit, OrderType::Market).await;
+        assert_eq!(result.status, ArbitrageStatus::Completed);
+        assert_eq!(result.buy_fill.avg_price, dec!(50000));
+        assert_eq!(result.sell_fill.avg_price, dec!(50020));
+    }
+
+    #[tokio::test]
+    async fn test_unfilled_limit_leg_cancelled_after_limit_timeout() {
+        let api = MockBinanceApi::new();
+        api.set_hold_orders(true);
+
+        let mut config = test_config();
+        config.arbitrage_settings.buy_order_type = Some(OrderType::Limit);
+        config.arbitrage_settings.sell_order_type = Some(OrderType::Market);
+        config.arbitrage_settings.limit_order_timeout_ms = 50;
+        let engine = ArbitrageEngine::new(api.clone(), config, "BTC").unwrap();
+        let opportunity = ArbitrageOpportunity::new(
+            "BTC",
+            QuoteCurrency::USDT,
+            QuoteCurrency::USDC,
+            dec!(50000),
+            dec!(50025),
+            dec!(1000),
+        );
+
+        // 限价买单未成交，按限价单的等待时间取消，而不是等待市价单的10秒
+        let started = Instant::now();
+        let mut orders = Vec::new();
+        assert!(engine.execute_arbitrage(&opportunity, &mut orders).await.is_err());
+        assert!(started.elapsed() < ORDER_POLL_INTERVAL);
+        assert_eq!(orders.len(), 1);
+        assert_eq!(orders[0].order.status, OrderStatus::Cancelled);
+    }
+
+    #[tokio::test]
+    async fn test_failed_sell_leg_unwinds_and_records_loss() {
+        let api = MockBinanceApi::new();
+        api.update_price("BTCUSDT", dec!(49990));
+        api.set_commission_rate(dec!(0.001));
+        // USDC买盘没有不低于限价 50025 的挂单，IOC卖单直接过期
+        api.set_order_book("BTCUSDC", vec![(dec!(50000), dec!(1))], vec![(dec!(50030), dec!(1))]);
+
+        let mut config = test_config();
+        config.arbitrage_settings.buy_order_type = Some(OrderType::Market);
+        config.arbitrage_settings.sell_order_type = Some(OrderType::Limit);
+        config.arbitrage_settings.time_in_force = TimeInForce::IOC;
+        let engine = ArbitrageEngine::new(api.clone(), config, "BTC").unwrap();
+        let opportunity = ArbitrageOpportunity::new(
+            "BTC",
+            QuoteCurrency::USDT,
+            QuoteCurrency::USDC,
+            dec!(50000),
+            dec!(50025),
+            dec!(1000),
+        );
+
+        let mut orders = Vec::new();
+        let result = engine.execute_arbitrage(&opportunity, &mut orders).await.unwrap();
+        assert_eq!(result.status, ArbitrageStatus::PartiallyUnwound);
+        // 买入、过期的卖出和回补三笔订单
+        assert_eq!(orders.len(), 3);
+        assert_eq!(orders[1].order.status, OrderStatus::Expired);
+        assert_eq!(orders[2].order.symbol, "BTCUSDT");
+        assert_eq!(orders[2].order.side, Side::Sell);
+        // 卖回扣除BTC手续费后到账的 0.01998 BTC
+        assert_eq!(orders[2].order.executed_qty, dec!(0.01998));
+        // 按 49990 买入又卖回，998.8002 - 0.9988002 - 999.8，约亏损两笔手续费
+        assert_eq!(result.profit, dec!(-1.9986002));
+
+        // 回补的亏损计入每日盈亏
+        let controller = DailyLossLimitController::new(dec!(1));
+        controller.record_result(&result).await.unwrap();
+        let (allowed, _) = controller.check_opportunity(&opportunity).await.unwrap();
+        assert!(!allowed);
+    }
+
+    #[tokio::test]
+    async fn test_partially_filled_sell_leg_unwinds_remaining() {
+        let api = MockBinanceApi::new();
+        api.update_price("BTCUSDT", dec!(50000));
+        // USDC买盘在限价上只有 0.005 BTC，IOC卖单部分成交后过期
+        api.set_order_book("BTCUSDC", vec![(dec!(50025), dec!(0.005))], vec![(dec!(50030), dec!(1))]);
+
+        let mut config = test_config();
+        config.arbitrage_settings.buy_order_type = Some(OrderType::Market);
+        config.arbitrage_settings.sell_order_type = Some(OrderType::Limit);
+        config.arbitrage_settings.time_in_force = TimeInForce::IOC;
+        let engine = ArbitrageEngine::new(api.clone(), config, "BTC").unwrap();
+        let opportunity = ArbitrageOpportunity::new(
+            "BTC",
+            QuoteCurrency::USDT,
+            QuoteCurrency::USDC,
+            dec!(50000),
+            dec!(50025),
+            dec!(1000),
+        );
+
+        let mut orders = Vec::new();
+        let result = engine.execute_arbitrage(&opportunity, &mut orders).await.unwrap();
+        assert_eq!(result.status, ArbitrageStatus::PartiallyUnwound);
+        assert_eq!(result.buy_fill.qty, dec!(0.02));
+        assert_eq!(result.sell_fill.qty, dec!(0.005));
+        // 只卖回卖出腿没有成交的部分
+        assert_eq!(orders.len(), 3);
+        assert_eq!(orders[1].order.status, OrderStatus::Expired);
+        assert_eq!(orders[2].order.symbol, "BTCUSDT");
+        assert_eq!(orders[2].order.executed_qty, dec!(0.015));
+        // 回补订单同样以机会ID为客户端ID前缀
+        let unwind_client_id = api.client_order_id(orders[2].order.order_id).unwrap();
+        assert_eq!(unwind_client_id, format!("{}-u", opportunity.opportunity_id.simple()));
+        assert!(unwind_client_id.len() <= 36);
+        // 已卖出部分赚 0.005 * 25，卖回部分不赚不亏（手续费为0）
+        assert_eq!(result.profit, dec!(0.125));
+    }
+
+    #[tokio::test]
+    async fn test_sell_leg_sells_quantity_net_of_base_commission() {
+        let api = MockBinanceApi::new();
+        // 账户中没有其他BTC，买入手续费以BTC收取
+        api.set_balance("BTC", Decimal::ZERO);
+        api.set_commission_rate(dec!(0.001));
+        let engine = ArbitrageEngine::new(api.clone(), test_config(), "BTC").unwrap();
+        let opportunity = ArbitrageOpportunity::new(
+            "BTC",
+            QuoteCurrency::USDT,
+            QuoteCurrency::USDC,
+            dec!(50000),
+            dec!(50025),
+            dec!(1000),
+        );
+
+        let mut orders = Vec::new();
+        let result = engine.execute_arbitrage(&opportunity, &mut orders).await.unwrap();
+        assert_eq!(result.status, ArbitrageStatus::Completed);
+        assert_eq!(result.buy_fill.qty, dec!(0.02));
+        assert_eq!(result.sell_fill.qty, dec!(0.01998));
+        assert_eq!(api.get_account_balance("BTC").await.unwrap(), Decimal::ZERO);
+    }
+
+    #[tokio::test]
+    async fn test_partially_filled_buy_leg_unwinds() {
+        let api = MockBinanceApi::new();
+        // USDT卖盘在限价上只有 0.005 BTC，IOC买单部分成交后过期
+        api.set_order_book("BTCUSDT", vec![(dec!(49990), dec!(1))], vec![(dec!(50000), dec!(0.005))]);
+
+        let mut config = test_config();
+        config.arbitrage_settings.buy_order_type = Some(OrderType::Limit);
+        config.arbitrage_settings.time_in_force = TimeInForce::IOC;
+        let engine = ArbitrageEngine::new(api.clone(), config, "BTC").unwrap();
+        let opportunity = ArbitrageOpportunity::new(
+            "BTC",
+            QuoteCurrency::USDT,
+            QuoteCurrency::USDC,
+            dec!(50000),
+            dec!(50025),
+            dec!(1000),
+        );
+
+        let mut orders = Vec::new();
+        let result = engine.execute_arbitrage(&opportunity, &mut orders).await.unwrap();
+        assert_eq!(result.status, ArbitrageStatus::PartiallyUnwound);
+        assert_eq!(result.buy_fill.qty, dec!(0.005));
+        assert_eq!(result.sell_fill.qty, Decimal::ZERO);
+        // 不执行卖出腿，已买入的部分卖回买入交易对
+        assert_eq!(orders.len(), 2);
+        assert_eq!(orders[0].order.status, OrderStatus::Expired);
+        assert_eq!(orders[1].order.symbol, "BTCUSDT");
+        assert_eq!(orders[1].order.side, Side::Sell);
+        assert_eq!(orders[1].order.executed_qty, dec!(0.005));
+        assert_eq!(api.get_account_balance("BTC").await.unwrap(), dec!(1));
+    }
+
+    #[tokio::test]
+    async fn test_buy_fill_beyond_max_deviation_unwinds() {
+        let api = MockBinanceApi::new();
+        // 市价买入按 50100 成交，比报价高0.2%
+        api.update_price("BTCUSDT", dec!(50100));
+
+        let mut config = test_config();
+        config.execution.max_fill_deviation_pct = dec!(0.1);
+        let engine = ArbitrageEngine::new(api.clone(), config, "BTC").unwrap();
+        let opportunity = ArbitrageOpportunity::new(
+            "BTC",
+            QuoteCurrency::USDT,
+            QuoteCurrency::USDC,
+            dec!(50000),
+            dec!(50200),
+            dec!(1000),
+        );
+
+        let mut orders = Vec::new();
+        let result = engine.execute_arbitrage(&opportunity, &mut orders).await.unwrap();
+        assert_eq!(result.status, ArbitrageStatus::PartiallyUnwound);
+        assert_eq!(result.sell_order_id, None);
+        // 买入后直接在USDT交易对卖回，没有提交USDC卖单
+        assert_eq!(orders.len(), 2);
+        assert_eq!(orders[1].order.symbol, "BTCUSDT");
+        assert_eq!(orders[1].order.side, Side::Sell);
+    }
+
+    #[tokio::test]
+    async fn test_fallback_direction_uses_net_execution_costs() {
+        let api = MockBinanceApi::new();
+        api.update_price("BTCUSDT", dec!(50000));
+        api.update_price("BTCUSDC", dec!(50025));
+        // 1 USDC 只值 0.99 USDT
+        api.update_price("USDCUSDT", dec!(0.99));
+
+        // 不启用策略，只使用默认的机会
+        let mut config = test_config();
+        config.strategy_settings.enabled_strategies = Vec::new();
+        let engine = ArbitrageEngine::new(api.clone(), config.clone(), "BTC").unwrap();
+
+        // 只比较报价时价差 0.05% 低于最小利润率
+        assert!(engine.find_best_arbitrage_opportunity().await.unwrap().is_none());
+
+        // 折算后 USDC 交易对的价格更低，利润率为扣除两边 0.1% 手续费后的净利润率
+        config.arbitrage_settings.best_execution.enabled = true;
+        engine.reload_config(config).unwrap();
+        let opportunity = engine.find_best_arbitrage_opportunity().await.unwrap().unwrap();
+        assert_eq!(opportunity.buy_quote, QuoteCurrency::USDC);
+        assert_eq!(opportunity.sell_quote, QuoteCurrency::USDT);
+        assert_eq!(opportunity.buy_price, dec!(50025));
+        assert!(opportunity.profit_percentage > Decimal::ZERO);
+
+        // 扣除手续费和兑换后亏损，低于最小利润率
+        api.update_price("USDCUSDT", dec!(0.999));
+        assert!(engine.find_best_arbitrage_opportunity().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_inventory_skew_biases_size_toward_rebalancing() {
+        let api = MockBinanceApi::new();
+        api.update_price("BTCUSDT", dec!(50000));
+        api.update_price("BTCUSDC", dec!(50025));
+
+        let mut config = test_config();
+        config.strategy_settings.enabled_strategies = Vec::new();
+        config.arbitrage_settings.min_profit_percentage = Decimal::ZERO;
+        config.arbitrage_settings.inventory_skew.enabled = true;
+        let engine = ArbitrageEngine::new(api.clone(), config, "BTC").unwrap();
+        async fn skewed_amount(engine: &ArbitrageEngine<MockBinanceApi>, amount: Decimal) -> Decimal {
+            let mut opportunity = engine.find_best_arbitrage_opportunity().await.unwrap().unwrap();
+            assert_eq!(opportunity.buy_quote, QuoteCurrency::USDT);
+            assert_eq!(opportunity.max_trade_amount, dec!(100));
+            // 模拟策略按深度降低的交易金额
+            opportunity.max_trade_amount = amount;
+            engine.apply_inventory_skew(&mut opportunity).await;
+            opportunity.max_trade_amount
+        }
+
+        // USDT偏多时，用USDT买入、换得USDC的交易放大，受1.5倍上限限制
+        api.set_balance("USDT", dec!(16000));
+        api.set_balance("USDC", dec!(4000));
+        assert_eq!(skewed_amount(&engine, dec!(50)).await, dec!(75));
+
+        // 交易金额已达到最大交易金额时不再放大
+        assert_eq!(skewed_amount(&engine, dec!(100)).await, dec!(100));
+        assert_eq!(skewed_amount(&engine, dec!(80)).await, dec!(100));
+
+        // USDC偏多时同一方向使库存更失衡，交易金额缩小
+        api.set_balance("USDT", dec!(4000));
+        api.set_balance("USDC", dec!(16000));
+        assert_eq!(skewed_amount(&engine, dec!(100)).await, dec!(40));
+
+        // 放大后的金额不超过USDT余额
+        api.set_balance("USDT", dec!(60));
+        api.set_balance("USDC", Decimal::ZERO);
+        assert_eq!(skewed_amount(&engine, dec!(50)).await, dec!(60));
+    }
+
+    /// 等待交易对出现未成交的订单后将其全部成交
+    async fn fill_next_open_order(api: &MockBinanceApi, symbol: &str) -> OrderInfo {
+        let order = tokio::time::timeout(Duration::from_secs(1), async {
+            loop {
+                if let Some(order) = api.get_open_orders(symbol).await.unwrap().into_iter().next() {
+                    return order;
+                }
+                sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .expect("应提交订单");
+        api.fill_order(order.order_id).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_trade_completes_from_user_data_stream() {
+        let api = MockBinanceApi::new();
+        // 订单提交后不成交，由测试通过数据流推送成交
+        api.set_hold_orders(true);
+
+        let engine = Arc::new(ArbitrageEngine::new(api.clone(), test_config(), "BTC").unwrap());
+        let watcher = tokio::spawn({
+            let engine = engine.clone();
+            async move { engine.watch_user_data_stream().await }
+        });
+        tokio::time::timeout(Duration::from_secs(1), async {
+            while !engine.user_data_stream_connected() {
+                sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .expect("应连接用户数据流");
+
+        let opportunity = ArbitrageOpportunity::new(
+            "BTC",
+            QuoteCurrency::USDT,
+            QuoteCurrency::USDC,
+            dec!(50000),
+            dec!(50025),
+            dec!(1000),
+        );
+        let started = Instant::now();
+        let trade = tokio::spawn({
+            let engine = engine.clone();
+            async move {
+                let mut orders = Vec::new();
+                let result = engine.execute_arbitrage(&opportunity, &mut orders).await;
+                (result, orders)
+            }
+        });
+
+        fill_next_open_order(&api, "BTCUSDT").await;
+        fill_next_open_order(&api, "BTCUSDC").await;
+        let (result, orders) = trade.await.unwrap();
+        let result = result.unwrap();
+
+        // 轮询需要至少等待一个轮询间隔，推送的成交立即被处理
+        assert!(started.elapsed() < ORDER_POLL_INTERVAL);
+        assert_eq!(result.status, ArbitrageStatus::Completed);
+        assert_eq!(result.buy_fill.qty, dec!(0.02));
+        assert_eq!(result.sell_fill.avg_price, dec!(50025));
+        assert_eq!(result.profit, dec!(0.5));
+        assert!(orders.iter().all(|o| o.order.status == OrderStatus::Filled));
+
+        watcher.abort();
+    }
+
+    #[tokio::test]
+    async fn test_falls_back_to_polling_when_stream_drops() {
+        let api = MockBinanceApi::new();
+        api.set_hold_orders(true);
+
+        let engine = Arc::new(ArbitrageEngine::new(api.clone(), test_config(), "BTC").unwrap());
+        let watcher = tokio::spawn({
+            let engine = engine.clone();
+            async move { engine.watch_user_data_stream().await }
+        });
+        tokio::time::timeout(Duration::from_secs(1), async {
+            while !engine.user_data_stream_connected() {
+                sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .expect("应连接用户数据流");
+
+        let opportunity = ArbitrageOpportunity::new(
+            "BTC",
+            QuoteCurrency::USDT,
+            QuoteCurrency::USDC,
+            dec!(50000),
+            dec!(50025),
+            dec!(1000),
+        );
+        let trade = tokio::spawn({
+            let engine = engine.clone();
+            async move {
+                let mut orders = Vec::new();
+                engine.execute_arbitrage(&opportunity, &mut orders).await
+            }
+        });
+
+        // 买单提交后数据流断开，成交不会被推送
+        let buy = tokio::time::timeout(Duration::from_secs(1), async {
+            loop {
+                if let Some(order) = api.get_open_orders("BTCUSDT").await.unwrap().into_iter().next() {
+                    return order;
+                }
+                sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .expect("应提交买单");
+        api.disconnect_user_data();
+        tokio::time::timeout(Duration::from_secs(1), async {
+            while engine.user_data_stream_connected() {
+                sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .expect("数据流断开后应标记为未连接");
+        api.set_hold_orders(false);
+        api.fill_order(buy.order_id).unwrap();
+
+        // 引擎改为轮询，发现买单成交后继续卖出
+        let result = tokio::time::timeout(Duration::from_secs(3), trade)
+            .await
+            .expect("轮询应发现订单成交")
+            .unwrap()
+            .unwrap();
+        assert_eq!(result.status, ArbitrageStatus::Completed);
+        assert_eq!(result.buy_fill.qty, dec!(0.02));
+
+        watcher.abort();
+    }
+
+    #[tokio::test]
+    async fn test_open_positions_limit_rejects_while_trade_in_flight() {
+        let api = MockBinanceApi::new();
+        // 订单一直不成交，第一笔套利停留在等待买入成交的阶段
+        api.set_hold_orders(true);
+
+        let mut config = test_config();
+        config.risk_settings.enabled_controllers = vec![RiskControllerType::OpenPositions];
+        config.risk_settings.open_positions.max_open_positions = 1;
+        let engine = Arc::new(ArbitrageEngine::new(api.clone(), config, "BTC").unwrap());
+        let opportunity = ArbitrageOpportunity::new(
+            "BTC",
+            QuoteCurrency::USDT,
+            QuoteCurrency::USDC,
+            dec!(50000),
+            dec!(50025),
+            dec!(100),
+        );
+
+        let (valid, _) = engine.risk_manager().validate_opportunity(&opportunity).await.unwrap();
+        assert!(valid);
+
+        let in_flight = tokio::spawn({
+            let engine = engine.clone();
+            let opportunity = opportunity.clone();
+            async move {
+                let mut orders = Vec::new();
+                engine.execute_arbitrage(&opportunity, &mut orders).await
+            }
+        });
+        tokio::time::timeout(Duration::from_secs(1), async {
+            while engine.status().open_positions == 0 {
+                sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .expect("第一笔套利应计入持仓数");
+
+        // 第一笔套利未结束时拒绝新的机会
+        let (valid, reasons) = engine.risk_manager().validate_opportunity(&opportunity).await.unwrap();
+        assert!(!valid);
+        assert_eq!(reasons[0].reason(), RejectionReason::MaxOpenPositions);
+        assert!(reasons[0].to_string().contains("最大持仓数限制"));
+
+        // 执行任务结束（此处为取消）后释放名额
+        in_flight.abort();
+        let _ = in_flight.await;
+        assert_eq!(engine.status().open_positions, 0);
+        let (valid, _) = engine.risk_manager().validate_opportunity(&opportunity).await.unwrap();
+        assert!(valid);
+    }
+
+    #[tokio::test]
+    async fn test_balance_reservation_limits_concurrent_trades() {
+        let api = MockBinanceApi::new();
+        // 订单一直不成交，先开始的套利停留在等待买入成交的阶段并保持预留
+        api.set_hold_orders(true);
+        // 只够一笔 100 USDT 的交易
+        api.set_balance("USDT", dec!(150));
+        api.update_price("BTCUSDC", dec!(51000));
+        api.update_price("ETHUSDC", dec!(3100));
+
+        // 两个币种的引擎共用同一账户，共享余额预留和运行指标
+        let ledger = Arc::new(BalanceLedger::new());
+        let metrics = Arc::new(Metrics::new());
+        let mut engines = Vec::new();
+        for asset in ["BTC", "ETH"] {
+            let mut engine = ArbitrageEngine::new(api.clone(), test_config(), asset).unwrap();
+            engine.set_balance_ledger(ledger.clone());
+            engine.set_metrics(metrics.clone());
+            engines.push(Arc::new(engine));
+        }
+        let monitors: Vec<_> = engines
+            .iter()
+            .map(|engine| {
+                let engine = engine.clone();
+                tokio::spawn(async move { engine.monitor_opportunities().await })
+            })
+            .collect();
+
+        let api_ref = &api;
+        let open_buys = move || async move {
+            api_ref.get_open_orders("BTCUSDT").await.unwrap().len() + api_ref.get_open_orders("ETHUSDT").await.unwrap().len()
+        };
+        tokio::time::timeout(Duration::from_secs(2), async {
+            while open_buys().await == 0 || !metrics.render().contains("controller=\"余额预留\"") {
+                sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .expect("先开始的套利应提交买单，后开始的套利应因余额不足被跳过");
+
+        // 只有一笔套利提交了买单并预留了余额
+        sleep(Duration::from_millis(50)).await;
+        assert_eq!(open_buys().await, 1);
+        // 已预留 100，150 中只剩 50 可以预留
+        assert!(ledger.try_reserve("USDT", dec!(51), dec!(150)).is_err());
+        assert!(ledger.try_reserve("USDT", dec!(50), dec!(150)).is_ok());
+        assert_eq!(engines.iter().map(|engine| engine.status().open_positions).sum::<usize>(), 1);
+
+        // 执行任务结束（此处为取消）后释放预留
+        for monitor in monitors {
+            monitor.abort();
+            let _ = monitor.await;
+        }
+        // 全部余额都可以再次预留
+        assert!(ledger.try_reserve("USDT", dec!(150), dec!(150)).is_ok());
+
+        // 未启用余额预留时不检查余额
+        let mut config = test_config();
+        config.arbitrage_settings.reserve_balance = false;
+        let engine = ArbitrageEngine::new(api.clone(), config, "BTC").unwrap();
+        let opportunity = ArbitrageOpportunity::new("BTC", QuoteCurrency::USDT, QuoteCurrency::USDC, dec!(50000), dec!(51000), dec!(1000));
+        assert!(engine.reserve_balance(&opportunity).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_cleanup_cancels_stale_orders_only() {
+        let api = MockBinanceApi::new();
+        api.set_hold_orders(true);
+
+        let mut config = test_config();
+        config.arbitrage_settings.max_order_age_seconds = 60;
+        let engine = Arc::new(ArbitrageEngine::new(api.clone(), config, "BTC").unwrap());
+
+        // 之前运行遗留的挂单，引擎没有跟踪
+        let stale = api.place_order("BTCUSDC", Side::Sell, dec!(0.01), Some(dec!(50100)), TimeInForce::GTC, None).await.unwrap();
+        api.set_order_time(stale.order_id, Utc::now() - chrono::Duration::hours(1));
+        // 刚提交的挂单未超过最长挂单时间
+        let fresh = api.place_order("BTCUSDC", Side::Buy, dec!(0.01), Some(dec!(49900)), TimeInForce::GTC, None).await.unwrap();
+
+        // 正在执行的套利停留在等待买入成交的阶段
+        let opportunity = ArbitrageOpportunity::new(
+            "BTC",
+            QuoteCurrency::USDT,
+            QuoteCurrency::USDC,
+            dec!(50000),
+            dec!(50025),
+            dec!(100),
+        );
+        let in_flight = tokio::spawn({
+            let engine = engine.clone();
+            async move {
+                let mut orders = Vec::new();
+                engine.execute_arbitrage(&opportunity, &mut orders).await
+            }
+        });
+        let active = tokio::time::timeout(Duration::from_secs(1), async {
+            loop {
+                if let Some(order) = api.get_open_orders("BTCUSDT").await.unwrap().pop() {
+                    return order;
+                }
+                sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .expect("套利的买入订单应处于挂单状态");
+        // 即使挂单时间超过上限，正在执行的套利的订单也不会被取消
+        api.set_order_time(active.order_id, Utc::now() - chrono::Duration::hours(1));
+
+        let cancelled = engine.cleanup_stale_orders().await.unwrap();
+        assert_eq!(cancelled.len(), 1);
+        assert_eq!(cancelled[0].order_id, stale.order_id);
+        assert_eq!(api.get_order_status("BTCUSDC", stale.order_id).await.unwrap().status, OrderStatus::Cancelled);
+        assert_eq!(api.get_order_status("BTCUSDC", fresh.order_id).await.unwrap().status, OrderStatus::New);
+        assert_eq!(api.get_order_status("BTCUSDT", active.order_id).await.unwrap().status, OrderStatus::New);
+
+        // 套利结束后其订单不再受保护
+        in_flight.abort();
+        let _ = in_flight.await;
+        let cancelled = engine.cleanup_stale_orders().await.unwrap();
+        assert_eq!(cancelled.len(), 1);
+        assert_eq!(cancelled[0].order_id, active.order_id);
+    }
+
+    #[tokio::test]
+    async fn test_kill_switch_and_finish_session_cancel_only_engine_orders() {
+        let api = MockBinanceApi::new();
+        api.set_hold_orders(true);
+        let engine = Arc::new(ArbitrageEngine::new(api.clone(), test_config(), "BTC").unwrap());
+
+        // 手动提交的挂单不属于引擎
+        let manual = api.place_order("BTCUSDC", Side::Sell, dec!(0.01), Some(dec!(50100)), TimeInForce::GTC, None).await.unwrap();
+
+        let opportunity = ArbitrageOpportunity::new(
+            "BTC",
+            QuoteCurrency::USDT,
+            QuoteCurrency::USDC,
+            dec!(50000),
+            dec!(50025),
+            dec!(100),
+        );
+        let spawn_arbitrage = |engine: Arc<ArbitrageEngine<MockBinanceApi>>, opportunity: ArbitrageOpportunity| {
+            tokio::spawn(async move {
+                let mut orders = Vec::new();
+                engine.execute_arbitrage(&opportunity, &mut orders).await
+            })
+        };
+        let wait_for_buy_order = || async {
+            tokio::time::timeout(Duration::from_secs(1), async {
+                loop {
+                    if let Some(order) = api.get_open_orders("BTCUSDT").await.unwrap().pop() {
+                        return order;
+                    }
+                    sleep(Duration::from_millis(5)).await;
+                }
+            })
+            .await
+            .expect("套利的买入订单应处于挂单状态")
+        };
+
+        // 紧急停止取消正在执行的套利的订单
+        let in_flight = spawn_arbitrage(engine.clone(), opportunity.clone());
+        let active = wait_for_buy_order().await;
+        let cancelled = engine.kill_switch().await.unwrap();
+        assert_eq!(cancelled.len(), 1);
+        assert_eq!(cancelled[0].order_id, active.order_id);
+        assert!(in_flight.await.unwrap().is_err());
+
+        // 结束会话时取消中断的套利遗留的订单
+        let in_flight = spawn_arbitrage(engine.clone(), opportunity);
+        let orphaned = wait_for_buy_order().await;
+        in_flight.abort();
+        let _ = in_flight.await;
+        let summary = engine.finish_session().await;
+        assert_eq!(summary.cancelled_orders.len(), 1);
+        assert_eq!(summary.cancelled_orders[0].order_id, orphaned.order_id);
+
+        assert_eq!(api.get_order_status("BTCUSDC", manual.order_id).await.unwrap().status, OrderStatus::New);
+    }
+
+    fn dust_config(action: DustAction, dust_threshold: Decimal) -> Config {
+        let mut config = test_config();
+        config.arbitrage_settings.dust_sweep = DustSweepSettings {
+            enabled: true,
+            interval_ms: 10,
+            dust_threshold,
+            action,
+        };
+        config
+    }
+
+    #[tokio::test]
+    async fn test_accumulated_dust_is_converted() {
+        let api = MockBinanceApi::new();
+        api.set_balance("BTC", Decimal::ZERO);
+        let engine = ArbitrageEngine::new(api.clone(), dust_config(DustAction::Convert, Decimal::ZERO), "BTC").unwrap();
+        assert!(engine.sweep_dust().await.unwrap().is_none());
+
+        // 多次套利的取整余量累积，仍低于最小下单量 0.0001
+        for _ in 0..3 {
+            let balance = api.get_account_balance("BTC").await.unwrap();
+            api.set_balance("BTC", balance + dec!(0.00002));
+        }
+
+        let sweep = engine.sweep_dust().await.unwrap().expect("应发现小额资产");
+        assert_eq!(sweep.quantity, dec!(0.00006));
+        assert_eq!(sweep.threshold, dec!(0.0001));
+        let DustOutcome::Converted(conversion) = sweep.outcome else {
+            panic!("应兑换为BNB");
+        };
+        assert_eq!(conversion.amount, dec!(0.00006));
+
+        // 余额归零，BNB余额增加
+        assert_eq!(api.get_account_balance("BTC").await.unwrap(), Decimal::ZERO);
+        assert_eq!(api.get_account_balance("BNB").await.unwrap(), conversion.transferred);
+        assert!(engine.sweep_dust().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_dust_sweep_sells_or_reports() {
+        let api = MockBinanceApi::new();
+
+        // 正常持仓不是小额资产
+        let engine = ArbitrageEngine::new(api.clone(), dust_config(DustAction::MarketSell, dec!(0.001)), "BTC").unwrap();
+        assert!(engine.sweep_dust().await.unwrap().is_none());
+
+        // 高于最小下单量、低于配置上限的余额按步长取整后市价卖出，价值 0.00052 × 50000 = 26 USDT
+        api.set_balance("BTC", dec!(0.00052));
+        let usdt_before = api.get_account_balance("USDT").await.unwrap();
+        let sweep = engine.sweep_dust().await.unwrap().expect("应发现小额资产");
+        assert_eq!(sweep.threshold, dec!(0.001));
+        let DustOutcome::Sold(order) = sweep.outcome else {
+            panic!("应市价卖出");
+        };
+        assert_eq!(order.qty, dec!(0.0005));
+        assert_eq!(api.get_account_balance("BTC").await.unwrap(), dec!(0.00002));
+        assert_eq!(api.get_account_balance("USDT").await.unwrap(), usdt_before + dec!(25));
+
+        // 剩余部分不足最小下单量，无法卖出，只记录
+        let sweep = engine.sweep_dust().await.unwrap().expect("应发现小额资产");
+        assert!(matches!(sweep.outcome, DustOutcome::Reported));
+        assert_eq!(api.get_account_balance("BTC").await.unwrap(), dec!(0.00002));
+
+        // 只记录的方式不改变余额
+        let engine = ArbitrageEngine::new(api.clone(), dust_config(DustAction::Report, Decimal::ZERO), "BTC").unwrap();
+        let sweep = engine.sweep_dust().await.unwrap().expect("应发现小额资产");
+        assert!(matches!(sweep.outcome, DustOutcome::Reported));
+        assert_eq!(api.get_account_balance("BTC").await.unwrap(), dec!(0.00002));
+    }
+
+    #[tokio::test]
+    async fn test_session_stops_after_max_trades() {
+        let api = MockBinanceApi::new();
+        api.update_price("BTCUSDC", dec!(51000));
+
+        let mut engine = ArbitrageEngine::new(api.clone(), test_config(), "BTC").unwrap();
+        engine.set_session_limits(SessionLimits { max_trades: Some(3), ..SessionLimits::default() });
+
+        let monitor = tokio::time::timeout(Duration::from_secs(5), engine.monitor_opportunities()).await;
+        assert!(monitor.expect("达到交易次数限制后监控循环应退出").is_ok());
+
+        let stats = engine.session_stats();
+        assert_eq!(stats.trades, 3);
+        assert_eq!(stats.successful_trades, 3);
+        let recent = engine.recent_results();
+        assert_eq!(recent.len(), 3);
+        assert!(recent.windows(2).all(|pair| pair[0].end_time >= pair[1].end_time));
+        // 每次买入 100 USDT
+        assert_eq!(api.get_account_balance("USDT").await.unwrap(), dec!(9700));
+    }
+
+    #[tokio::test]
+    async fn test_session_stops_on_profit_and_loss_limits() {
+        // 每次套利利润 0.002 BTC * 1000 = 2
+        let api = MockBinanceApi::new();
+        api.update_price("BTCUSDC", dec!(51000));
+        let mut engine = ArbitrageEngine::new(api.clone(), test_config(), "BTC").unwrap();
+        engine.set_session_limits(SessionLimits { max_profit: Some(dec!(5)), ..SessionLimits::default() });
+
+        let monitor = tokio::time::timeout(Duration::from_secs(5), engine.monitor_opportunities()).await;
+        assert!(monitor.is_ok());
+        assert_eq!(engine.session_stats().trades, 3);
+        assert_eq!(engine.session_stats().profit, dec!(6));
+
+        // 5% 手续费使每次套利亏损 7.945：只卖出到账的 0.0019 BTC，96.9 - 4.845 - 100
+        let api = MockBinanceApi::new();
+        api.update_price("BTCUSDC", dec!(51000));
+        api.set_commission_rate(dec!(0.05));
+        let mut engine = ArbitrageEngine::new(api.clone(), test_config(), "BTC").unwrap();
+        engine.set_session_limits(SessionLimits { max_loss: Some(dec!(10)), ..SessionLimits::default() });
+
+        let monitor = tokio::time::timeout(Duration::from_secs(5), engine.monitor_opportunities()).await;
+        assert!(monitor.is_ok());
+        assert_eq!(engine.session_stats().trades, 2);
+        assert_eq!(engine.session_stats().profit, dec!(-15.89));
+    }
+
+    #[tokio::test]
+    async fn test_no_orders_during_warmup() {
+        let api = MockBinanceApi::new();
+        api.update_price("BTCUSDC", dec!(51000));
+
+        let mut config = test_config();
+        config.arbitrage_settings.warmup_ticks = 1_000;
+        let engine = ArbitrageEngine::new(api.clone(), config, "BTC").unwrap();
+        let usdt_before = api.get_account_balance("USDT").await.unwrap();
+        assert!(engine.status().warming_up);
+
+        let monitor = tokio::time::timeout(Duration::from_millis(100), engine.monitor_opportunities()).await;
+        assert!(monitor.is_err());
+        assert!(engine.status().warming_up);
+        assert_eq!(engine.session_stats().trades, 0);
+        assert_eq!(api.get_account_balance("USDT").await.unwrap(), usdt_before);
+        // 预热期间的行情同样写入缓存
+        assert!(!engine.price_cache().history("BTCUSDT", None).is_empty());
+
+        let mut config = test_config();
+        config.arbitrage_settings.warmup_seconds = 3_600;
+        let engine = ArbitrageEngine::new(api.clone(), config, "BTC").unwrap();
+        let _ = tokio::time::timeout(Duration::from_millis(50), engine.monitor_opportunities()).await;
+        assert_eq!(engine.session_stats().trades, 0);
+        assert_eq!(api.get_account_balance("USDT").await.unwrap(), usdt_before);
+    }
+
+    #[tokio::test]
+    async fn test_identical_opportunities_deduplicated_within_window() {
+        let api = MockBinanceApi::new();
+        api.update_price("BTCUSDC", dec!(51000));
+
+        let mut config = test_config();
+        config.arbitrage_settings.opportunity_dedup.window_ms = 60_000;
+        let engine = ArbitrageEngine::new(api.clone(), config, "BTC").unwrap();
+
+        // 价格不变，每轮都发现相同的机会，窗口内只执行一次
+        let monitor = tokio::time::timeout(Duration::from_millis(200), engine.monitor_opportunities()).await;
+        assert!(monitor.is_err());
+        assert_eq!(engine.session_stats().trades, 1);
+
+        // 价差变化后是不同的机会
+        api.update_price("BTCUSDC", dec!(52000));
+        let monitor = tokio::time::timeout(Duration::from_millis(200), engine.monitor_opportunities()).await;
+        assert!(monitor.is_err());
+        assert_eq!(engine.session_stats().trades, 2);
+
+        // 未启用去重时相同的机会每轮都会执行
+        let api = MockBinanceApi::new();
+        api.update_price("BTCUSDC", dec!(51000));
+        let engine = ArbitrageEngine::new(api.clone(), test_config(), "BTC").unwrap();
+        let monitor = tokio::time::timeout(Duration::from_millis(200), engine.monitor_opportunities()).await;
+        assert!(monitor.is_err());
+        assert!(engine.session_stats().trades > 1);
+    }
+
+    #[tokio::test]
+    async fn test_skips_spread_below_round_trip_fees() {
+        let api = MockBinanceApi::new();
+        let mut config = test_config();
+        config.fees = FeeSettings::default();
+        assert_eq!(config.round_trip_fee_percentage(), dec!(0.2));
+        
+        // 默认行情的价差为0.05%，不足以覆盖两笔0.1%的手续费
+        let engine = ArbitrageEngine::new(api.clone(), config.clone(), "BTC").unwrap();
+        let usdt_before = api.get_account_balance("USDT").await.unwrap();
+        let _ = tokio::time::timeout(Duration::from_millis(50), engine.monitor_opportunities()).await;
+        assert_eq!(engine.session_stats().trades, 0);
+        assert_eq!(api.get_account_balance("USDT").await.unwrap(), usdt_before);
+        
+        // 价差约2%时覆盖手续费，正常执行
+        api.update_price("BTCUSDC", dec!(51000));
+        let engine = ArbitrageEngine::new(api.clone(), config, "BTC").unwrap();
+        let _ = tokio::time::timeout(Duration::from_millis(50), engine.monitor_opportunities()).await;
+        assert!(engine.session_stats().trades > 0);
+    }
+
+    #[tokio::test]
+    async fn test_skips_notional_below_fixed_cost_break_even() {
+        let api = MockBinanceApi::new();
+        api.update_price("BTCUSDC", dec!(51000));
+        let mut config = test_config();
+        config.fees = FeeSettings { fixed_cost_per_trade: dec!(5), ..FeeSettings::default() };
+        config.arbitrage_settings.max_trade_amount_usdt = dec!(100);
+        
+        // 价差约2%，扣除手续费后100 USDT的利润约1.8，不足以覆盖5 USDT的固定成本
+        let engine = ArbitrageEngine::new(api.clone(), config.clone(), "BTC").unwrap();
+        let usdt_before = api.get_account_balance("USDT").await.unwrap();
+        let _ = tokio::time::timeout(Duration::from_millis(50), engine.monitor_opportunities()).await;
+        assert_eq!(engine.session_stats().trades, 0);
+        assert_eq!(api.get_account_balance("USDT").await.unwrap(), usdt_before);
+        assert!(engine.metrics().render().contains("controller=\"最小交易金额\",reason=\"below_trading_fees\""));
+        
+        // 交易金额超过盈亏平衡金额（约280 USDT）时正常执行
+        config.arbitrage_settings.max_trade_amount_usdt = dec!(1000);
+        let engine = ArbitrageEngine::new(api.clone(), config, "BTC").unwrap();
+        let _ = tokio::time::timeout(Duration::from_millis(50), engine.monitor_opportunities()).await;
+        assert!(engine.session_stats().trades > 0);
+    }
+
+    #[tokio::test]
+    async fn test_trading_starts_after_warmup() {
+        let api = MockBinanceApi::new();
+        api.update_price("BTCUSDC", dec!(51000));
+
+        let mut config = test_config();
+        config.arbitrage_settings.warmup_ticks = 3;
+        let mut engine = ArbitrageEngine::new(api.clone(), config, "BTC").unwrap();
+        engine.set_session_limits(SessionLimits { max_trades: Some(1), ..SessionLimits::default() });
+
+        let monitor = tokio::time::timeout(Duration::from_secs(5), engine.monitor_opportunities()).await;
+        assert!(monitor.expect("预热结束后应执行交易并达到会话限制").is_ok());
+        assert!(!engine.status().warming_up);
+        assert_eq!(engine.session_stats().trades, 1);
+        // 3 轮预热后第 4 轮交易
+        assert_eq!(engine.price_cache().history("BTCUSDT", None).len(), 4);
+        assert_eq!(api.get_account_balance("USDT").await.unwrap(), dec!(9900));
+
+        // 未配置预热时第一轮即可交易
+        let engine = ArbitrageEngine::new(api.clone(), test_config(), "BTC").unwrap();
+        assert!(!engine.status().warming_up);
+    }
+
+    #[tokio::test]
+    async fn test_manual_pause_and_runtime_min_profit() {
+        let api = MockBinanceApi::new();
+        api.update_price("BTCUSDC", dec!(51000));
+        let engine = ArbitrageEngine::new(api.clone(), test_config(), "BTC").unwrap();
+        let usdt_before = api.get_account_balance("USDT").await.unwrap();
+
+        engine.pause();
+        let monitor = tokio::time::timeout(Duration::from_millis(50), engine.monitor_opportunities()).await;
+        assert!(monitor.is_err());
+        assert!(engine.status().manually_paused);
+        assert_eq!(engine.session_stats().trades, 0);
+
+        // 价差约2%，低于运行时最小利润率时同样不交易
+        engine.resume();
+        assert!(engine.set_min_profit_percentage(Some(dec!(-1))).is_err());
+        engine.set_min_profit_percentage(Some(dec!(5))).unwrap();
+        let _ = tokio::time::timeout(Duration::from_millis(50), engine.monitor_opportunities()).await;
+        assert_eq!(engine.session_stats().trades, 0);
+        assert_eq!(api.get_account_balance("USDT").await.unwrap(), usdt_before);
+
+        engine.set_min_profit_percentage(None).unwrap();
+        let _ = tokio::time::timeout(Duration::from_millis(50), engine.monitor_opportunities()).await;
+        assert!(engine.session_stats().trades > 0);
+    }
+
+    #[tokio::test]
+    async fn test_min_profit_updated_mid_run() {
+        let api = MockBinanceApi::new();
+        api.update_price("BTCUSDC", dec!(51000));
+        let engine = Arc::new(ArbitrageEngine::new(api.clone(), test_config(), "BTC").unwrap());
+        engine.set_min_profit_percentage(Some(dec!(5))).unwrap();
+
+        let monitor = tokio::spawn({
+            let engine = engine.clone();
+            async move { engine.monitor_opportunities().await }
+        });
+
+        // 价差约2%，低于5%时不交易
+        sleep(Duration::from_millis(50)).await;
+        assert_eq!(engine.session_stats().trades, 0);
+
+        // 降低阈值后下一轮即开始交易，无需重启监控循环
+        let updated = engine
+            .update_settings(SettingsUpdate { min_profit_percentage: Some(Some(dec!(1))), ..SettingsUpdate::default() })
+            .unwrap();
+        assert_eq!(updated.min_profit_percentage, Some(dec!(1)));
+        sleep(Duration::from_millis(50)).await;
+        let trades = engine.session_stats().trades;
+        assert!(trades > 0);
+
+        // 再次提高阈值后停止交易
+        engine.set_min_profit_percentage(Some(dec!(5))).unwrap();
+        sleep(Duration::from_millis(30)).await;
+        let trades = engine.session_stats().trades;
+        sleep(Duration::from_millis(50)).await;
+        assert_eq!(engine.session_stats().trades, trades);
+        assert!(!monitor.is_finished());
+
+        monitor.abort();
+    }
+
+    #[tokio::test]
+    async fn test_update_settings_dry_run_and_trade_amount() {
+        let api = MockBinanceApi::new();
+        api.update_price("BTCUSDC", dec!(51000));
+        let mut engine = ArbitrageEngine::new(api.clone(), test_config(), "BTC").unwrap();
+        engine.set_session_limits(SessionLimits { max_trades: Some(1), ..SessionLimits::default() });
+        let settings = engine.runtime_settings();
+        assert_eq!(settings.max_trade_amount_usdt, dec!(100));
+        assert_eq!(engine.check_interval(), Duration::from_millis(10));
+        assert!(!engine.is_dry_run());
+
+        // 校验失败时参数保持不变
+        assert!(engine.update_settings(SettingsUpdate { max_trade_amount_usdt: Some(dec!(0)), ..SettingsUpdate::default() }).is_err());
+        assert!(engine.update_settings(SettingsUpdate { check_interval_ms: Some(0), dry_run: Some(true), ..SettingsUpdate::default() }).is_err());
+        assert_eq!(engine.runtime_settings(), settings);
+
+        // 只记录不下单
+        engine.update_settings(SettingsUpdate { dry_run: Some(true), ..SettingsUpdate::default() }).unwrap();
+        let monitor = tokio::time::timeout(Duration::from_millis(50), engine.monitor_opportunities()).await;
+        assert!(monitor.is_err());
+        assert_eq!(engine.session_stats().trades, 0);
+        assert_eq!(api.get_account_balance("USDT").await.unwrap(), dec!(10000));
+
+        // 按运行时的最大交易金额下单
+        engine.update_settings(SettingsUpdate {
+            dry_run: Some(false),
+            max_trade_amount_usdt: Some(dec!(50)),
+            ..SettingsUpdate::default()
+        }).unwrap();
+        let monitor = tokio::time::timeout(Duration::from_secs(5), engine.monitor_opportunities()).await;
+        assert!(monitor.is_ok());
+        assert_eq!(engine.session_stats().trades, 1);
+        assert_eq!(api.get_account_balance("USDT").await.unwrap(), dec!(9950));
+
+        // 通过参数更新暂停
+        engine.update_settings(SettingsUpdate { paused: Some(true), ..SettingsUpdate::default() }).unwrap();
+        assert!(engine.status().manually_paused);
+    }
+
+    #[tokio::test]
+    async fn test_reload_config_on_trigger() {
+        let api = MockBinanceApi::new();
+        api.update_price("BTCUSDC", dec!(51000));
+        let mut engine = ArbitrageEngine::new(api.clone(), test_config(), "BTC").unwrap();
+        engine.set_session_limits(SessionLimits { max_trades: Some(1), ..SessionLimits::default() });
+        let engine = Arc::new(engine);
+        assert_eq!(engine.config_hash(), test_config().fingerprint());
+        engine.pause();
+        engine.set_min_profit_percentage(Some(dec!(1))).unwrap();
+
+        let path = std::env::temp_dir().join(format!("arbitrage_reload_{}.json", std::process::id()));
+        let (trigger, receiver) = mpsc::channel(1);
+        let watcher = tokio::spawn({
+            let engine = engine.clone();
+            let path = path.clone();
+            async move { engine.watch_config_reloads(path, receiver).await }
+        });
+
+        let mut new_config = test_config();
+        new_config.arbitrage_settings.max_trade_amount_usdt = dec!(50);
+        new_config.risk_settings.enabled_controllers = vec![RiskControllerType::PairBlacklist];
+        std::fs::write(&path, serde_json::to_string(&new_config).unwrap()).unwrap();
+        trigger.send(()).await.unwrap();
+
+        tokio::time::timeout(Duration::from_secs(1), async {
+            while engine.max_trade_amount() != dec!(50) {
+                sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .expect("配置应被重新加载");
+        assert_eq!(engine.config().arbitrage_settings.max_trade_amount_usdt, dec!(50));
+        assert_eq!(engine.risk_controller_names().len(), 1);
+        assert_eq!(engine.config_hash(), new_config.fingerprint());
+        // 手动暂停和运行时最小利润率不受影响
+        assert!(engine.is_manually_paused());
+        assert_eq!(engine.min_profit_percentage(), Some(dec!(1)));
+
+        // 无效的配置被拒绝，保持原有配置
+        let mut invalid = new_config.clone();
+        invalid.arbitrage_settings.max_trade_amount_usdt = Decimal::ZERO;
+        std::fs::write(&path, serde_json::to_string(&invalid).unwrap()).unwrap();
+        trigger.send(()).await.unwrap();
+        std::fs::write(&path, "{ not json").unwrap();
+        trigger.send(()).await.unwrap();
+        drop(trigger);
+        watcher.await.unwrap();
+        assert_eq!(engine.max_trade_amount(), dec!(50));
+        assert!(engine.reload_config(invalid).is_err());
+
+        // 新的最大交易金额用于下一次套利
+        engine.resume();
+        let monitor = tokio::time::timeout(Duration::from_secs(5), engine.monitor_opportunities()).await;
+        assert!(monitor.is_ok());
+        assert_eq!(api.get_account_balance("USDT").await.unwrap(), dec!(9950));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_config_file_change_applies_new_threshold() {
+        let api = MockBinanceApi::new();
+        // 约2%的价差，低于初始的最小利润率
+        api.update_price("BTCUSDC", dec!(51000));
+        let mut config = test_config();
+        config.arbitrage_settings.min_profit_percentage = dec!(5);
+        config.risk_settings.enabled_controllers = vec![RiskControllerType::DailyLossLimit];
+        let mut engine = ArbitrageEngine::new(api.clone(), config.clone(), "BTC").unwrap();
+        engine.set_session_limits(SessionLimits { max_trades: Some(1), ..SessionLimits::default() });
+        let engine = Arc::new(engine);
+        let strategies = engine.strategies.read().unwrap().clone();
+        let risk_manager = engine.risk_manager();
+
+        let path = std::env::temp_dir().join(format!("arbitrage_watch_{}.json", std::process::id()));
+        std::fs::write(&path, serde_json::to_string(&config).unwrap()).unwrap();
+        let (trigger, receiver) = mpsc::channel(1);
+        tokio::spawn(watch_config_file(path.clone(), Duration::from_millis(10), trigger));
+        tokio::spawn({
+            let engine = engine.clone();
+            let path = path.clone();
+            async move { engine.watch_config_reloads(path, receiver).await }
+        });
+        let monitor = tokio::spawn({
+            let engine = engine.clone();
+            async move { engine.monitor_opportunities().await }
+        });
+
+        // 利润率不足，运行一段时间没有交易
+        sleep(Duration::from_millis(100)).await;
+        assert!(!monitor.is_finished());
+        assert_eq!(api.get_account_balance("USDT").await.unwrap(), dec!(10000));
+
+        // 运行中修改配置文件：降低最小利润率，同时修改API密钥和启用的策略
+        let mut new_config = config.clone();
+        new_config.arbitrage_settings.min_profit_percentage = dec!(0.5);
+        new_config.risk_settings.daily_loss_limit.max_daily_loss = dec!(80);
+        new_config.api_key = "new-key".to_string();
+        new_config.strategy_settings.enabled_strategies = vec![StrategyType::TimeWeighted];
+        std::fs::write(&path, serde_json::to_string(&new_config).unwrap()).unwrap();
+
+        // 新的阈值生效后完成一笔套利
+        tokio::time::timeout(Duration::from_secs(5), monitor)
+            .await
+            .expect("新的最小利润率应生效")
+            .unwrap()
+            .unwrap();
+        assert_eq!(engine.session_stats().trades, 1);
+
+        let applied = engine.config();
+        assert_eq!(applied.arbitrage_settings.min_profit_percentage, dec!(0.5));
+        assert_eq!(applied.risk_settings.daily_loss_limit.max_daily_loss, dec!(80));
+        // API密钥和策略列表不热加载
+        assert_eq!(applied.api_key, "");
+        assert_eq!(applied.strategy_settings.enabled_strategies, config.strategy_settings.enabled_strategies);
+        // 策略和风控组件原地更新，没有重新创建
+        assert!(Arc::ptr_eq(&strategies, &engine.strategies.read().unwrap()));
+        assert!(Arc::ptr_eq(&risk_manager, &engine.risk_manager()));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[cfg(feature = "db-sqlite")]
+    #[tokio::test]
+    async fn test_status_reports_database_health() {
+        let api = MockBinanceApi::new();
+        let mut engine = ArbitrageEngine::new(api.clone(), test_config(), "BTC").unwrap();
+        assert!(engine.status().database.is_none());
+        assert!(engine.check_database_health().await.is_none());
+
+        let db = DatabaseManager::new("sqlite::memory:").await.unwrap();
+        engine.set_db_manager(db);
+        let health = engine.check_database_health().await.unwrap();
+        assert!(health.healthy);
+
+        let status = engine.status();
+        assert!(!status.trading_paused);
+        assert_eq!(status.session, SessionStats::default());
+        let database = status.database.unwrap();
+        assert!(database.healthy);
+        assert_eq!(database.checked_at, health.checked_at);
+        assert!(status.write_metrics.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_restore_risk_state_replays_todays_trades() {
+        let api = MockBinanceApi::new();
+        let mut config = test_config();
+        config.risk_settings.enabled_controllers = vec![RiskControllerType::DailyLossLimit, RiskControllerType::TradingFrequency];
+        config.risk_settings.daily_loss_limit.max_daily_loss = dec!(100);
+        let mut engine = ArbitrageEngine::new(api, config, "BTC").unwrap();
+        assert!(engine.restore_risk_state().await.is_err());
+
+        let db = DatabaseManager::in_memory().await.unwrap();
+        let trade = |profit: Decimal, status: ArbitrageStatus, mode: TradingMode, start: chrono::DateTime<Utc>| ArbitrageResult {
+            base_asset: "BTC".to_string(),
+            buy_quote: "USDT".to_string(),
+            sell_quote: "USDC".to_string(),
+            buy_price: dec!(50000),
+            sell_price: dec!(50100),
+            trade_amount: dec!(0.1),
+            profit,
+            profit_percentage: Decimal::ZERO,
+            buy_order_id: None,
+            sell_order_id: None,
+            status,
+            timestamp: start,
+            start_time: start,
+            end_time: start,
+            buy_fill: FillInfo::default(),
+            sell_fill: FillInfo::default(),
+            mode,
+            config_hash: String::new(),
+            strategy: None,
+            opportunity_id: None,
+        };
+        let now = Utc::now();
+        for result in [
+            trade(dec!(-30), ArbitrageStatus::Completed, TradingMode::Live, now - chrono::Duration::seconds(2)),
+            trade(dec!(10), ArbitrageStatus::Completed, TradingMode::Live, now - chrono::Duration::seconds(1)),
+            trade(Decimal::ZERO, ArbitrageStatus::Failed, TradingMode::Live, now),
+            // 其他运行模式和之前几天的交易不回放
+            trade(dec!(-500), ArbitrageStatus::Completed, TradingMode::Simulate, now - chrono::Duration::seconds(3)),
+            trade(dec!(-500), ArbitrageStatus::Completed, TradingMode::Live, now - chrono::Duration::days(2)),
+        ] {
+            db.record_arbitrage_result(&result).await.unwrap();
+        }
+        engine.set_db_manager(db);
+
+        assert_eq!(engine.restore_risk_state().await.unwrap(), 3);
+        let states = engine.risk_controller_states();
+        assert_eq!(states[0].name, "每日亏损限制");
+        assert_eq!(states[0].state["daily_pnl"], "-20");
+        assert_eq!(states[0].state["remaining_loss"], "80");
+        assert_eq!(states[0].state["limit_reached"], false);
+        assert_eq!(states[1].state["trades_in_timeframe"], 3);
+        assert_eq!(engine.status().risk, states);
+    }
+
+    #[tokio::test]
+    async fn test_raw_spread_below_threshold_skips_strategy_evaluation() {
+        use crate::binance::LatencyTrackedApi;
+
+        let mock = MockBinanceApi::new();
+        let settings = ApiLatencySettings { enabled: true, window_size: 10, warn_threshold_ms: 0 };
+        let mut config = test_config();
+        // 订单簿深度策略按盘口中间价评估，每次评估都会查询订单簿
+        config.strategy_settings.enabled_strategies = vec![StrategyType::OrderBookDepth];
+        config.arbitrage_settings.price_diff_threshold = dec!(0.1);
+        config.arbitrage_settings.min_profit_percentage = Decimal::ZERO;
+        let engine = ArbitrageEngine::new(LatencyTrackedApi::new(mock.clone(), &settings), config.clone(), "BTC").unwrap();
+        let order_book_calls = |engine: &ArbitrageEngine<LatencyTrackedApi<MockBinanceApi>>| {
+            engine
+                .api
+                .latency_stats()
+                .into_iter()
+                .find(|stats| stats.method == "get_order_book")
+                .map_or(0, |stats| stats.calls)
+        };
+
+        // 默认行情价差 0.05%，低于阈值时不运行策略，行情仍写入缓存
+        assert!(engine.find_best_arbitrage_opportunity().await.unwrap().is_none());
+        assert_eq!(order_book_calls(&engine), 0);
+        assert_eq!(engine.price_cache().history("BTCUSDT", None).len(), 1);
+
+        // 价差 0.2% 达到阈值后照常评估策略
+        mock.update_price("BTCUSDC", dec!(50100));
+        assert!(engine.find_best_arbitrage_opportunity().await.unwrap().is_some());
+        assert!(order_book_calls(&engine) > 0);
+
+        // 阈值为0时不过滤
+        mock.update_price("BTCUSDC", dec!(50010));
+        config.arbitrage_settings.price_diff_threshold = Decimal::ZERO;
+        engine.reload_config(config).unwrap();
+        assert!(engine.find_best_arbitrage_opportunity().await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_equal_prices_produce_no_opportunity() {
+        let api = MockBinanceApi::new();
+        api.update_price("BTCUSDC", dec!(50000));
+        let mut config = test_config();
+        config.arbitrage_settings.price_diff_threshold = Decimal::ZERO;
+
+        let api_arc = Arc::new(api.clone());
+        let now = Utc::now();
+        let usdt = Price { symbol: "BTCUSDT".to_string(), price: dec!(50000), timestamp: now };
+        let usdc = Price { symbol: "BTCUSDC".to_string(), price: dec!(50000), timestamp: now };
+        for strategy_type in [
+            StrategyType::Simple,
+            StrategyType::TimeWeighted,
+            StrategyType::OrderBookDepth,
+            StrategyType::SlippageControl,
+            StrategyType::TrendFollowing,
+            StrategyType::Composite,
+        ] {
+            let strategy = ArbitrageEngine::build_strategy(&strategy_type, &config, &api_arc);
+            let opportunity = strategy.find_opportunity("BTC", &usdt, &usdc).await.unwrap();
+            assert!(opportunity.is_none(), "{:?} 在价格相同时不应给出机会", strategy_type);
+        }
+
+        // 没有策略给出机会时同样不构造零价差的机会
+        let engine = ArbitrageEngine::new(api.clone(), config.clone(), "BTC").unwrap();
+        assert!(engine.find_best_arbitrage_opportunity().await.unwrap().is_none());
+
+        // 启用最优执行时报价相同也按兑换价格折算：1 USDC 值 1.01 USDT，用USDT买入、USDC卖出有净收益
+        api.update_price("USDCUSDT", dec!(1.01));
+        config.arbitrage_settings.best_execution.enabled = true;
+        engine.reload_config(config).unwrap();
+        let opportunity = engine.find_best_arbitrage_opportunity().await.unwrap().unwrap();
+        assert_eq!(opportunity.buy_quote, QuoteCurrency::USDT);
+        assert_eq!(opportunity.sell_quote, QuoteCurrency::USDC);
+        assert!(opportunity.profit_percentage > Decimal::ZERO);
+    }
+
+    #[tokio::test]
+    async fn test_untraded_ticks_feed_abnormal_price_window() {
+        let api = MockBinanceApi::new();
+        let mut config = test_config();
+        config.risk_settings.enabled_controllers = vec![RiskControllerType::AbnormalPrice];
+        config.risk_settings.abnormal_price.window_size = 5;
+        config.risk_settings.abnormal_price.abnormal_threshold = dec!(10);
+        let engine = ArbitrageEngine::new(api.clone(), config, "BTC").unwrap();
+
+        // 只获取行情、不经过风控检查，行情同样写入共享缓存
+        for _ in 0..3 {
+            engine.find_best_arbitrage_opportunity().await.unwrap();
+        }
+        api.update_price("BTCUSDT", dec!(60000));
+        api.update_price("BTCUSDC", dec!(60025));
+        engine.find_best_arbitrage_opportunity().await.unwrap();
+
+        let cache = engine.price_cache();
+        assert_eq!(cache.history("BTCUSDT", None).len(), 4);
+        assert_eq!(cache.latest("BTCUSDC").unwrap().price.price, dec!(60025));
+
+        // 风控没有见过的价格突变同样触发异常价格保护
+        let opportunity = ArbitrageOpportunity::new(
+            "BTC",
+            QuoteCurrency::USDT,
+            QuoteCurrency::USDC,
+            dec!(60000),
+            dec!(60100),
+            dec!(100),
+        );
+        let (valid, reasons) = engine.risk_manager().validate_opportunity(&opportunity).await.unwrap();
+        assert!(!valid);
+        assert_eq!(reasons[0].reason(), RejectionReason::AbnormalPrice);
+        assert!(reasons[0].to_string().contains("BTCUSDT"));
+
+        // 热加载风控设置后新的控制器继续使用同一个缓存
+        let mut new_config = engine.config();
+        new_config.risk_settings.abnormal_price.cooldown_period = 0;
+        engine.reload_config(new_config).unwrap();
+        let (valid, _) = engine.risk_manager().validate_opportunity(&opportunity).await.unwrap();
+        assert!(!valid);
+        assert!(Arc::ptr_eq(&cache, &engine.price_cache()));
+    }
+
+    #[cfg(feature = "db-sqlite")]
+    #[tokio::test]
+    async fn test_results_record_mode_and_config_hash() {
+        use crate::db::TradeHistoryFilter;
+
+        let api = MockBinanceApi::new();
+        api.update_price("BTCUSDC", dec!(51000));
+        let mut engine = ArbitrageEngine::new(api.clone(), test_config(), "BTC").unwrap();
+        engine.set_session_limits(SessionLimits { max_trades: Some(2), ..SessionLimits::default() });
+        engine.set_trading_mode(TradingMode::Simulate);
+        let db = DatabaseManager::new("sqlite::memory:").await.unwrap();
+        engine.set_db_manager(db.clone());
+
+        let status = engine.status();
+        assert_eq!(status.mode, TradingMode::Simulate);
+        assert_eq!(status.config_hash, test_config().fingerprint());
+
+        let monitor = tokio::time::timeout(Duration::from_secs(5), engine.monitor_opportunities()).await;
+        assert!(monitor.is_ok());
+        db.flush().await.unwrap();
+
+        let filter = TradeHistoryFilter { mode: Some(TradingMode::Simulate), ..TradeHistoryFilter::default() };
+        let records = db.get_trade_history(&filter, 10, 0).await.unwrap();
+        assert_eq!(records.len(), 2);
+        assert!(records.iter().all(|r| r.result.config_hash == status.config_hash));
+
+        let filter = TradeHistoryFilter { mode: Some(TradingMode::Live), ..TradeHistoryFilter::default() };
+        assert!(db.get_trade_history(&filter, 10, 0).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_profit_attributed_to_strategy() {
+        use crate::db::TradeHistoryFilter;
+
+        let api = MockBinanceApi::new();
+        api.update_price("BTCUSDC", dec!(51000));
+        let mut engine = ArbitrageEngine::new(api.clone(), test_config(), "BTC").unwrap();
+        engine.set_session_limits(SessionLimits { max_trades: Some(2), ..SessionLimits::default() });
+        let db = DatabaseManager::new("sqlite::memory:").await.unwrap();
+        engine.set_db_manager(db.clone());
+
+        let opportunity = engine.find_best_arbitrage_opportunity().await.unwrap().unwrap();
+        assert_eq!(opportunity.strategy.as_deref(), Some("简单价格差异套利"));
+
+        let monitor = tokio::time::timeout(Duration::from_secs(5), engine.monitor_opportunities()).await;
+        assert!(monitor.is_ok());
+        db.flush().await.unwrap();
+
+        let records = db.get_trade_history(&TradeHistoryFilter::default(), 10, 0).await.unwrap();
+        assert!(records.iter().all(|r| r.result.strategy.as_deref() == Some("简单价格差异套利")));
+
+        let stats = db.get_strategy_stats(None, None).await.unwrap();
+        assert_eq!(stats.len(), 1);
+        assert_eq!(stats[0].strategy, "简单价格差异套利");
+        assert_eq!(stats[0].trades, 2);
+        assert_eq!(stats[0].total_profit, engine.session_stats().profit);
+        // 模拟接口按机会价格成交，没有滑点
+        assert_eq!(stats[0].avg_slippage_percentage, Some(Decimal::ZERO));
+    }
+
+    #[tokio::test]
+    async fn test_trade_and_rejection_logged_with_structured_fields() {
+        use crate::logging::{CapturedLogs, LogFormat};
+
+        let logs = CapturedLogs::default();
+        let _guard = tracing::subscriber::set_default(logs.subscriber(LogFormat::Json));
+        let api = MockBinanceApi::new();
+        api.update_price("BTCUSDC", dec!(51000));
+        let mut engine = ArbitrageEngine::new(api.clone(), test_config(), "BTC").unwrap();
+        engine.set_session_limits(SessionLimits { max_trades: Some(1), ..SessionLimits::default() });
+        let monitor = tokio::time::timeout(Duration::from_secs(5), engine.monitor_opportunities()).await;
+        assert!(monitor.is_ok());
+
+        let events = logs.json_events();
+        let found = events.iter().find(|event| event["message"].as_str().unwrap().starts_with("发现套利机会")).expect("应记录发现的机会");
+        assert_eq!(found["asset"], "BTC");
+        assert_eq!(found["strategy"], "简单价格差异套利");
+        assert!(found["profit_percentage"].is_string());
+        let finished = events.iter().find(|event| event["message"].as_str().unwrap().starts_with("套利结束")).expect("应记录交易结果");
+        assert_eq!(finished["asset"], "BTC");
+        assert_eq!(finished["strategy"], "简单价格差异套利");
+        assert_eq!(finished["status"], "Completed");
+        assert_eq!(finished["profit"], engine.session_stats().profit.to_string());
+        assert_eq!(finished["opportunity_id"], found["opportunity_id"]);
+
+        // 风控拒绝记录组件和原因
+        let mut config = test_config();
+        config.risk_settings.enabled_controllers = vec![RiskControllerType::PairBlacklist];
+        config.risk_settings.pair_blacklist.blacklisted_pairs = vec!["BTC".to_string()];
+        let engine = ArbitrageEngine::new(api, config, "BTC").unwrap();
+        let _ = tokio::time::timeout(Duration::from_millis(100), engine.monitor_opportunities()).await;
+
+        let events = logs.json_events();
+        let rejected = events.iter().find(|event| event["reason"] == "blacklisted").expect("应记录风控拒绝");
+        assert_eq!(rejected["level"], "WARN");
+        assert_eq!(rejected["asset"], "BTC");
+        assert_eq!(rejected["controller"], "交易对黑名单");
+    }
+
+    #[tokio::test]
+    async fn test_pauses_after_consecutive_db_write_failures() {
+        let api = MockBinanceApi::new();
+        api.update_price("BTCUSDC", dec!(51000));
+        let mut config = test_config();
+        config.arbitrage_settings.max_consecutive_db_failures = 2;
+        config.arbitrage_settings.db_health_check_interval_ms = 10;
+        let mut engine = ArbitrageEngine::new(api.clone(), config, "BTC").unwrap();
+        let store = MemoryStore::new();
+        store.set_down(true);
+        engine.set_db_manager(DatabaseManager::from_store(store.clone()));
+
+        // 连续两次写入失败后暂停交易，不再继续下单
+        let _ = tokio::time::timeout(Duration::from_millis(200), engine.monitor_opportunities()).await;
+        assert!(engine.is_db_write_paused());
+        assert!(engine.status().db_write_paused);
+        assert_eq!(engine.session_stats().trades, 2);
+
+        // 写入恢复后补写未保存的结果并恢复交易
+        store.set_down(false);
+        let _ = tokio::time::timeout(Duration::from_millis(100), engine.monitor_opportunities()).await;
+        assert!(!engine.is_db_write_paused());
+        assert!(engine.session_stats().trades > 2);
+        assert!(store.count() > 2);
+    }
+}
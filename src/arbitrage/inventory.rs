@@ -0,0 +1,81 @@
+use crate::config::InventorySkewSettings;
+use crate::models::QuoteCurrency;
+use rust_decimal::Decimal;
+
+/// 按USDT和USDC库存偏离目标比例调整交易金额
+///
+/// 每次套利用买入一侧的稳定币换得卖出一侧的稳定币。USDT比例高于目标时，用USDT买入的交易使库存回到目标，
+/// 按 `1 + skew_factor × 偏差` 放大；用USDC买入的交易使库存进一步偏离，按 `1 - skew_factor × 偏差` 缩小。
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InventorySkew {
+    /// USDT占USDT和USDC合计的目标比例
+    pub target_usdt_ratio: Decimal,
+    /// 偏斜力度
+    pub skew_factor: Decimal,
+    /// 倍数上限
+    pub max_multiplier: Decimal,
+}
+
+impl InventorySkew {
+    pub fn from_settings(settings: &InventorySkewSettings) -> Self {
+        Self {
+            target_usdt_ratio: settings.target_usdt_ratio,
+            skew_factor: settings.skew_factor,
+            max_multiplier: settings.max_multiplier,
+        }
+    }
+
+    /// 在 `buy_quote` 一侧买入时交易金额的倍数，限制在0到 `max_multiplier` 之间
+    ///
+    /// USDC按1:1计入库存，两种稳定币余额都为0时不调整。
+    pub fn size_multiplier(&self, usdt_balance: Decimal, usdc_balance: Decimal, buy_quote: QuoteCurrency) -> Decimal {
+        let total = usdt_balance + usdc_balance;
+        if total <= Decimal::ZERO {
+            return Decimal::ONE;
+        }
+
+        let deviation = usdt_balance / total - self.target_usdt_ratio;
+        // 用USDT买入降低USDT比例，用USDC买入提高USDT比例
+        let toward_target = match buy_quote {
+            QuoteCurrency::USDT => deviation,
+            QuoteCurrency::USDC => -deviation,
+        };
+        (Decimal::ONE + self.skew_factor * toward_target)
+            .max(Decimal::ZERO)
+            .min(self.max_multiplier)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn skew() -> InventorySkew {
+        InventorySkew {
+            target_usdt_ratio: dec!(0.5),
+            skew_factor: dec!(2),
+            max_multiplier: dec!(1.5),
+        }
+    }
+
+    #[test]
+    fn test_multiplier_favours_rebalancing_direction() {
+        let skew = skew();
+        // 库存平衡时不调整
+        assert_eq!(skew.size_multiplier(dec!(5000), dec!(5000), QuoteCurrency::USDT), Decimal::ONE);
+        assert_eq!(skew.size_multiplier(Decimal::ZERO, Decimal::ZERO, QuoteCurrency::USDC), Decimal::ONE);
+
+        // USDT占60%，偏差0.1
+        assert_eq!(skew.size_multiplier(dec!(6000), dec!(4000), QuoteCurrency::USDT), dec!(1.2));
+        assert_eq!(skew.size_multiplier(dec!(6000), dec!(4000), QuoteCurrency::USDC), dec!(0.8));
+
+        // USDT占80%，放大受上限限制
+        assert_eq!(skew.size_multiplier(dec!(8000), dec!(2000), QuoteCurrency::USDT), dec!(1.5));
+        assert_eq!(skew.size_multiplier(dec!(8000), dec!(2000), QuoteCurrency::USDC), dec!(0.4));
+
+        // 只剩USDC时用USDC买入按上限放大，用USDT买入的交易金额降为0
+        assert_eq!(skew.size_multiplier(Decimal::ZERO, dec!(10000), QuoteCurrency::USDC), dec!(1.5));
+        assert_eq!(skew.size_multiplier(Decimal::ZERO, dec!(10000), QuoteCurrency::USDT), Decimal::ZERO);
+    }
+}
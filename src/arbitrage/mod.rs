@@ -1,3 +1,20 @@
+pub mod account;
+pub mod balance;
+pub mod dedup;
+pub mod doctor;
 pub mod engine;
+pub mod inventory;
+pub mod order_updates;
+pub mod profit;
+pub mod profit_distribution;
+pub mod ranking;
+pub mod shutdown;
+pub mod spread;
 
-pub use engine::ArbitrageEngine;
+pub use account::AccountOverview;
+pub use balance::BalanceLedger;
+pub use doctor::ConnectionReport;
+pub use profit_distribution::ProfitDistributionState;
+pub use shutdown::{SessionSummary, ShutdownSignal};
+pub use spread::{render_table as render_spread_table, spread_percentage, SpreadFormat, SpreadMonitor, SpreadSample};
+pub use engine::{watch_config_file, ArbitrageEngine, DustOutcome, DustSweep, EngineStatus, LiquidationReport, RuntimeSettings, SessionLimits, SessionStats, SettingsUpdate};
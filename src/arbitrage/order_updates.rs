@@ -0,0 +1,125 @@
+use crate::models::{OrderInfo, OrderStatus};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use tokio::sync::broadcast;
+
+/// 保存的订单状态超过该数量时清理已结束的订单，避免不经引擎提交的订单的推送持续累积
+const MAX_TRACKED_ORDERS: usize = 1000;
+
+/// 用户数据流推送的订单状态，等待订单成交的任务从这里获取最新状态
+///
+/// 数据流已连接时等待推送，未连接或断开后等待方改为轮询订单状态。
+#[derive(Debug)]
+pub struct OrderUpdates {
+    latest: Mutex<HashMap<u64, OrderInfo>>,
+    /// 订单状态更新或连接状态变化时通知等待方
+    changed: broadcast::Sender<()>,
+    connected: AtomicBool,
+}
+
+impl Default for OrderUpdates {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl OrderUpdates {
+    pub fn new() -> Self {
+        let (changed, _) = broadcast::channel(64);
+        Self {
+            latest: Mutex::new(HashMap::new()),
+            changed,
+            connected: AtomicBool::new(false),
+        }
+    }
+
+    /// 用户数据流是否已连接
+    pub fn is_connected(&self) -> bool {
+        self.connected.load(Ordering::SeqCst)
+    }
+
+    /// 更新连接状态并通知等待方，断开时等待方改为轮询
+    pub fn set_connected(&self, connected: bool) {
+        self.connected.store(connected, Ordering::SeqCst);
+        let _ = self.changed.send(());
+    }
+
+    /// 记录推送的订单状态；已结束的订单不会被之后到达的较早状态覆盖
+    pub fn apply(&self, order: OrderInfo) {
+        let mut latest = self.latest.lock().unwrap();
+        if let Some(current) = latest.get(&order.order_id) {
+            if Self::is_final(current.status) && !Self::is_final(order.status) {
+                return;
+            }
+        }
+        latest.insert(order.order_id, order);
+        if latest.len() > MAX_TRACKED_ORDERS {
+            latest.retain(|_, order| !Self::is_final(order.status));
+        }
+        drop(latest);
+        let _ = self.changed.send(());
+    }
+
+    /// 订单最近一次推送的状态
+    pub fn latest(&self, order_id: u64) -> Option<OrderInfo> {
+        self.latest.lock().unwrap().get(&order_id).cloned()
+    }
+
+    /// 订单处理完毕后移除其状态
+    pub fn forget(&self, order_id: u64) {
+        self.latest.lock().unwrap().remove(&order_id);
+    }
+
+    /// 订阅状态变化通知，应在检查最新状态之前订阅，避免错过通知
+    pub fn subscribe(&self) -> broadcast::Receiver<()> {
+        self.changed.subscribe()
+    }
+
+    fn is_final(status: OrderStatus) -> bool {
+        !matches!(status, OrderStatus::New | OrderStatus::PartiallyFilled)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Side;
+    use chrono::Utc;
+    use rust_decimal::Decimal;
+
+    fn order(order_id: u64, status: OrderStatus) -> OrderInfo {
+        OrderInfo {
+            order_id,
+            symbol: "BTCUSDT".to_string(),
+            price: Decimal::ONE,
+            qty: Decimal::ONE,
+            executed_qty: Decimal::ZERO,
+            avg_price: Decimal::ZERO,
+            commission: Decimal::ZERO,
+            commission_asset: String::new(),
+            side: Side::Buy,
+            status,
+            timestamp: Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_updates_notify_and_keep_final_status() {
+        let updates = OrderUpdates::new();
+        let mut changed = updates.subscribe();
+
+        updates.apply(order(1, OrderStatus::Filled));
+        changed.recv().await.unwrap();
+        assert_eq!(updates.latest(1).unwrap().status, OrderStatus::Filled);
+
+        // 乱序到达的较早状态被忽略
+        updates.apply(order(1, OrderStatus::New));
+        assert_eq!(updates.latest(1).unwrap().status, OrderStatus::Filled);
+
+        updates.set_connected(true);
+        assert!(updates.is_connected());
+        updates.forget(1);
+        assert!(updates.latest(1).is_none());
+    }
+}
@@ -0,0 +1,207 @@
+use crate::config::Config;
+use crate::models::{ArbitrageOpportunity, FillInfo, QuoteCurrency, Side};
+use log::warn;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+/// 套利利润的计算方式
+///
+/// 引擎结算成交、监控循环估算机会和最优执行比较两个方向都通过该接口计算，手续费和报价货币折算只在这里处理。
+/// 实现只需提供手续费率和折算比例，利润按默认方法计算，结果统一以USDT计价。
+pub trait ProfitCalculator: Send + Sync {
+    /// 在 `quote` 交易对买入或卖出的手续费率，小数（例如 0.001 表示0.1%）
+    fn fee_rate(&self, side: Side, quote: QuoteCurrency) -> Decimal;
+
+    /// 1单位 `quote` 折算的USDT数量
+    fn conversion_rate(&self, quote: QuoteCurrency) -> Decimal;
+
+    /// 成交的手续费，折算为该交易对的报价货币
+    ///
+    /// 以基础资产收取的手续费按成交均价折算，以USDT/USDC收取的视为等值报价货币；其他币种（如BNB）无法折算，记为0并告警。
+    fn fill_fee(&self, fill: &FillInfo, base_asset: &str) -> Decimal {
+        fill.commission_in_quote(base_asset).unwrap_or_else(|| {
+            warn!("无法折算手续费 {} {}，净利润中未扣除", fill.commission, fill.commission_asset);
+            Decimal::ZERO
+        })
+    }
+
+    /// 按实际成交计算的净利润：各笔卖出所得减去各笔买入成本，扣除全部手续费后折算为USDT
+    ///
+    /// `buys` 和 `sells` 为成交所在交易对的报价货币和成交信息。引擎只卖出买入后实际到账的数量，
+    /// 以基础资产收取的买入手续费已体现在较少的卖出所得中，不再重复计入成本。
+    fn realized_profit(&self, base_asset: &str, buys: &[(QuoteCurrency, &FillInfo)], sells: &[(QuoteCurrency, &FillInfo)]) -> Decimal {
+        let proceeds: Decimal = sells
+            .iter()
+            .map(|(quote, fill)| (fill.notional() - self.fill_fee(fill, base_asset)) * self.conversion_rate(*quote))
+            .sum();
+        let cost: Decimal = buys
+            .iter()
+            .map(|(quote, fill)| {
+                let fee = if fill.commission_asset == base_asset { Decimal::ZERO } else { self.fill_fee(fill, base_asset) };
+                (fill.notional() + fee) * self.conversion_rate(*quote)
+            })
+            .sum();
+        proceeds - cost
+    }
+
+    /// 以 `buy_price` 在 `buy_quote` 交易对买入、以 `sell_price` 在 `sell_quote` 交易对卖出的净利润率（百分比）
+    ///
+    /// 买入成本为买入价 × (1 + 手续费率)，卖出所得为卖出价 × (1 - 手续费率)，两边均折算为USDT后比较。
+    fn net_profit_percentage(
+        &self,
+        buy_quote: QuoteCurrency,
+        buy_price: Decimal,
+        sell_quote: QuoteCurrency,
+        sell_price: Decimal,
+    ) -> Decimal {
+        let cost = buy_price * (Decimal::ONE + self.fee_rate(Side::Buy, buy_quote)) * self.conversion_rate(buy_quote);
+        if cost <= Decimal::ZERO {
+            return Decimal::ZERO;
+        }
+        let proceeds = sell_price * (Decimal::ONE - self.fee_rate(Side::Sell, sell_quote)) * self.conversion_rate(sell_quote);
+        (proceeds - cost) / cost * dec!(100)
+    }
+
+    /// 按机会的报价和计划交易金额估算的净利润
+    ///
+    /// 买入数量为 `max_trade_amount / buy_price`，与引擎下单的数量相同；报价全部成交且手续费以报价货币收取时等于 `realized_profit`。
+    fn estimated_profit(&self, opportunity: &ArbitrageOpportunity) -> Decimal {
+        if opportunity.buy_price.is_zero() {
+            return Decimal::ZERO;
+        }
+        let qty = opportunity.max_trade_amount / opportunity.buy_price;
+        let buy_notional = qty * opportunity.buy_price;
+        let sell_notional = qty * opportunity.sell_price;
+        let cost = buy_notional
+            * (Decimal::ONE + self.fee_rate(Side::Buy, opportunity.buy_quote))
+            * self.conversion_rate(opportunity.buy_quote);
+        let proceeds = sell_notional
+            * (Decimal::ONE - self.fee_rate(Side::Sell, opportunity.sell_quote))
+            * self.conversion_rate(opportunity.sell_quote);
+        proceeds - cost
+    }
+
+    /// 按机会的价差和手续费率计算的盈亏平衡交易金额（USDT）：`fixed_cost` 除以每单位交易金额扣除手续费后的利润
+    ///
+    /// 扣除手续费后没有利润时返回None，任何交易金额都无法覆盖固定成本。
+    fn break_even_notional(&self, opportunity: &ArbitrageOpportunity, fixed_cost: Decimal) -> Option<Decimal> {
+        if opportunity.max_trade_amount <= Decimal::ZERO {
+            return None;
+        }
+        let margin = self.estimated_profit(opportunity) / opportunity.max_trade_amount;
+        if margin <= Decimal::ZERO {
+            return None;
+        }
+        Some(fixed_cost / margin)
+    }
+}
+
+/// 默认的利润计算：按配置的手续费设置和订单类型估算两边的手续费率
+///
+/// 买入、卖出按各自的订单类型取挂单或吃单费率；USDC默认按1:1折算为USDT。
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FeeProfitCalculator {
+    pub buy_fee_rate: Decimal,
+    pub sell_fee_rate: Decimal,
+    /// 1 USDC 折算的 USDT 数量
+    pub usdc_rate: Decimal,
+}
+
+impl FeeProfitCalculator {
+    pub fn new(buy_fee_rate: Decimal, sell_fee_rate: Decimal) -> Self {
+        Self {
+            buy_fee_rate,
+            sell_fee_rate,
+            usdc_rate: Decimal::ONE,
+        }
+    }
+
+    /// 按 `fees` 和下单方式确定两边的手续费率
+    pub fn from_config(config: &Config) -> Self {
+        let (buy_type, sell_type, time_in_force) = config.order_types();
+        Self::new(
+            config.fees.leg_rate(buy_type, time_in_force),
+            config.fees.leg_rate(sell_type, time_in_force),
+        )
+    }
+
+    /// 设置USDC兑USDT的折算比例
+    pub fn with_usdc_rate(mut self, usdc_rate: Decimal) -> Self {
+        self.usdc_rate = usdc_rate;
+        self
+    }
+}
+
+impl ProfitCalculator for FeeProfitCalculator {
+    fn fee_rate(&self, side: Side, _quote: QuoteCurrency) -> Decimal {
+        match side {
+            Side::Buy => self.buy_fee_rate,
+            Side::Sell => self.sell_fee_rate,
+        }
+    }
+
+    fn conversion_rate(&self, quote: QuoteCurrency) -> Decimal {
+        match quote {
+            QuoteCurrency::USDT => Decimal::ONE,
+            QuoteCurrency::USDC => self.usdc_rate,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::testing::fill;
+
+    #[test]
+    fn test_estimate_matches_realized_profit_at_quoted_prices() {
+        let calculator = FeeProfitCalculator::new(dec!(0.001), dec!(0.00075));
+        let opportunity = ArbitrageOpportunity::new("BTC", QuoteCurrency::USDT, QuoteCurrency::USDC, dec!(50000), dec!(50500), dec!(1000));
+
+        // 买入手续费以USDT收取，卖出手续费以USDC收取
+        let buy = fill(dec!(0.02), dec!(50000), dec!(1), "USDT");
+        let sell = fill(dec!(0.02), dec!(50500), dec!(0.7575), "USDC");
+        let realized = calculator.realized_profit("BTC", &[(QuoteCurrency::USDT, &buy)], &[(QuoteCurrency::USDC, &sell)]);
+        assert_eq!(realized, dec!(8.2425));
+        assert_eq!(calculator.estimated_profit(&opportunity), realized);
+    }
+
+    #[test]
+    fn test_base_asset_buy_fee_not_counted_twice() {
+        let calculator = FeeProfitCalculator::new(dec!(0.001), dec!(0.001));
+        // 买入手续费以BTC收取，只卖出到账的 0.01998 BTC
+        let buy = fill(dec!(0.02), dec!(50000), dec!(0.00002), "BTC");
+        let sell = fill(dec!(0.01998), dec!(50500), dec!(1.00899), "USDC");
+        let realized = calculator.realized_profit("BTC", &[(QuoteCurrency::USDT, &buy)], &[(QuoteCurrency::USDC, &sell)]);
+        // 1008.99 - 1.00899 - 1000
+        assert_eq!(realized, dec!(7.98101));
+    }
+
+    #[test]
+    fn test_break_even_notional() {
+        let calculator = FeeProfitCalculator::new(dec!(0.001), dec!(0.001));
+        // 价差1%，扣除两边手续费后每1000 USDT利润7.99
+        let opportunity = ArbitrageOpportunity::new("BTC", QuoteCurrency::USDT, QuoteCurrency::USDC, dec!(50000), dec!(50500), dec!(1000));
+        assert_eq!(calculator.estimated_profit(&opportunity), dec!(7.99));
+        assert_eq!(calculator.break_even_notional(&opportunity, dec!(7.99)), Some(dec!(1000)));
+        assert_eq!(calculator.break_even_notional(&opportunity, dec!(0.799)), Some(dec!(100)));
+
+        // 价差不足以覆盖手续费
+        let thin = ArbitrageOpportunity::new("BTC", QuoteCurrency::USDT, QuoteCurrency::USDC, dec!(50000), dec!(50050), dec!(1000));
+        assert_eq!(calculator.break_even_notional(&thin, dec!(1)), None);
+    }
+
+    #[test]
+    fn test_converts_usdc_and_skips_unconvertible_fees() {
+        let calculator = FeeProfitCalculator::new(Decimal::ZERO, Decimal::ZERO).with_usdc_rate(dec!(0.999));
+        let buy = fill(dec!(1), dec!(100), dec!(0.01), "BNB");
+        let sell = fill(dec!(1), dec!(101), Decimal::ZERO, "USDC");
+        // 101 USDC 折算为 100.899 USDT，BNB手续费无法折算
+        let profit = calculator.realized_profit("ETH", &[(QuoteCurrency::USDT, &buy)], &[(QuoteCurrency::USDC, &sell)]);
+        assert_eq!(profit, dec!(0.899));
+        assert_eq!(
+            calculator.net_profit_percentage(QuoteCurrency::USDT, dec!(100), QuoteCurrency::USDC, dec!(101)),
+            dec!(0.899)
+        );
+    }
+}
@@ -0,0 +1,119 @@
+use log::debug;
+use rust_decimal::{Decimal, RoundingStrategy};
+use serde::Serialize;
+use std::sync::Mutex;
+
+/// 划转数量保留的小数位数
+const AMOUNT_DECIMALS: u32 = 8;
+
+/// 利润分配的累计状态
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct ProfitDistributionState {
+    /// 尚未分配的累计净利润，亏损时可以为负数
+    pub pending: Decimal,
+    /// 已划转到子账户的合计
+    pub transferred: Decimal,
+    /// 未启用划转时记录的待分配合计
+    pub earmarked: Decimal,
+}
+
+/// 一次达到阈值时的分配
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ProfitDistribution {
+    /// 触发分配时的累计净利润
+    pub accumulated: Decimal,
+    /// 应分配的金额
+    pub amount: Decimal,
+}
+
+/// 累计已实现的净利润，达到阈值时按比例分配
+#[derive(Debug, Default)]
+pub struct ProfitDistributor {
+    state: Mutex<ProfitDistributionState>,
+}
+
+impl ProfitDistributor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 当前的累计状态
+    pub fn state(&self) -> ProfitDistributionState {
+        *self.state.lock().unwrap()
+    }
+
+    /// 计入一笔交易的净利润，累计达到 `threshold` 时返回应分配的金额并清零累计
+    ///
+    /// 分配金额为累计利润 × `portion`，按8位小数向下取整；为0时不分配。
+    pub fn record(&self, profit: Decimal, threshold: Decimal, portion: Decimal) -> Option<ProfitDistribution> {
+        let mut state = self.state.lock().unwrap();
+        state.pending += profit;
+        if state.pending < threshold {
+            debug!("累计待分配利润 {}，未达到阈值 {}", state.pending, threshold);
+            return None;
+        }
+
+        let amount = (state.pending * portion).round_dp_with_strategy(AMOUNT_DECIMALS, RoundingStrategy::ToZero);
+        if amount <= Decimal::ZERO {
+            return None;
+        }
+        let accumulated = state.pending;
+        state.pending = Decimal::ZERO;
+        Some(ProfitDistribution { accumulated, amount })
+    }
+
+    /// 划转失败时将累计利润加回，下一笔交易后重新尝试
+    pub fn restore(&self, distribution: &ProfitDistribution) {
+        self.state.lock().unwrap().pending += distribution.accumulated;
+    }
+
+    /// 记录已划转的金额
+    pub fn mark_transferred(&self, amount: Decimal) {
+        self.state.lock().unwrap().transferred += amount;
+    }
+
+    /// 记录未划转、只标记为待分配的金额
+    pub fn mark_earmarked(&self, amount: Decimal) {
+        self.state.lock().unwrap().earmarked += amount;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_distributes_portion_when_threshold_reached() {
+        let distributor = ProfitDistributor::new();
+        assert_eq!(distributor.record(dec!(40), dec!(100), dec!(0.25)), None);
+        // 亏损抵减累计利润
+        assert_eq!(distributor.record(dec!(-10), dec!(100), dec!(0.25)), None);
+        assert_eq!(distributor.state().pending, dec!(30));
+
+        let distribution = distributor.record(dec!(80), dec!(100), dec!(0.25)).unwrap();
+        assert_eq!(distribution, ProfitDistribution { accumulated: dec!(110), amount: dec!(27.5) });
+        assert_eq!(distributor.state().pending, Decimal::ZERO);
+
+        // 分配金额按8位小数向下取整
+        let distribution = distributor.record(dec!(100), dec!(100), dec!(0.333333333333)).unwrap();
+        assert_eq!(distribution.amount, dec!(33.33333333));
+    }
+
+    #[test]
+    fn test_restore_after_failed_transfer() {
+        let distributor = ProfitDistributor::new();
+        let distribution = distributor.record(dec!(150), dec!(100), dec!(0.5)).unwrap();
+        distributor.restore(&distribution);
+        assert_eq!(distributor.state().pending, dec!(150));
+
+        let retried = distributor.record(dec!(10), dec!(100), dec!(0.5)).unwrap();
+        assert_eq!(retried.amount, dec!(80));
+        distributor.mark_transferred(retried.amount);
+        distributor.mark_earmarked(dec!(5));
+        assert_eq!(
+            distributor.state(),
+            ProfitDistributionState { pending: Decimal::ZERO, transferred: dec!(80), earmarked: dec!(5) }
+        );
+    }
+}
@@ -0,0 +1,228 @@
+use super::profit::ProfitCalculator;
+use crate::config::OpportunityRanking;
+use crate::models::{ArbitrageOpportunity, QuoteCurrency, Side};
+use rust_decimal::{Decimal, MathematicalOps};
+use rust_decimal_macros::dec;
+
+/// 按排序方式计算套利机会的得分，得分越高越优先
+///
+/// - `ByPercentage`: 利润率
+/// - `ByAbsoluteProfit`: 预期利润，即交易金额 × 利润率 / 100
+/// - `ByRiskAdjusted`: 预期利润 × 利润率 / (利润率 + 波动率)，利润率相对近期价格波动越薄折扣越大，
+///   波动率为0时与预期利润相同
+///
+/// `volatility` 为近期价格波动率（百分比），只有 `ByRiskAdjusted` 使用。
+pub fn rank_opportunity(opportunity: &ArbitrageOpportunity, ranking: OpportunityRanking, volatility: Decimal) -> Decimal {
+    let expected_profit = opportunity.max_trade_amount * opportunity.profit_percentage / dec!(100);
+    match ranking {
+        OpportunityRanking::ByPercentage => opportunity.profit_percentage,
+        OpportunityRanking::ByAbsoluteProfit => expected_profit,
+        OpportunityRanking::ByRiskAdjusted => {
+            let margin = opportunity.profit_percentage;
+            let denominator = margin + volatility.max(Decimal::ZERO);
+            if margin <= Decimal::ZERO || denominator.is_zero() {
+                // 没有利润的机会不需要打折，直接按预期利润（0或负数）排序
+                expected_profit
+            } else {
+                expected_profit * margin / denominator
+            }
+        }
+    }
+}
+
+/// 从候选机会中选出得分最高的，得分相同时保留先出现的，只保留利润率为正的机会
+pub fn select_best_opportunity(
+    candidates: Vec<ArbitrageOpportunity>,
+    ranking: OpportunityRanking,
+    volatility: Decimal,
+) -> Option<ArbitrageOpportunity> {
+    let mut best: Option<(Decimal, ArbitrageOpportunity)> = None;
+    for opportunity in candidates {
+        if opportunity.profit_percentage <= Decimal::ZERO {
+            continue;
+        }
+        let score = rank_opportunity(&opportunity, ranking, volatility);
+        if best.as_ref().is_none_or(|(best_score, _)| score > *best_score) {
+            best = Some((score, opportunity));
+        }
+    }
+    best.map(|(_, opportunity)| opportunity)
+}
+
+/// 价格序列的波动率：样本标准差 / 均值 × 100，少于2个价格时为0
+pub fn price_volatility(prices: &[Decimal]) -> Decimal {
+    if prices.len() < 2 {
+        return Decimal::ZERO;
+    }
+
+    let mean = prices.iter().sum::<Decimal>() / Decimal::from(prices.len());
+    if mean.is_zero() {
+        return Decimal::ZERO;
+    }
+    let variance = prices.iter().map(|price| (*price - mean).powu(2)).sum::<Decimal>() / Decimal::from(prices.len() - 1);
+    variance.sqrt().unwrap_or(Decimal::ZERO) / mean * dec!(100)
+}
+
+/// 两个交易对的交易成本，手续费率为小数（例如 0.001 表示0.1%）
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ExecutionCosts {
+    pub usdt_fee_rate: Decimal,
+    pub usdc_fee_rate: Decimal,
+    /// 1 USDC 折算的 USDT 数量
+    pub usdc_rate: Decimal,
+}
+
+impl ProfitCalculator for ExecutionCosts {
+    fn fee_rate(&self, _side: Side, quote: QuoteCurrency) -> Decimal {
+        match quote {
+            QuoteCurrency::USDT => self.usdt_fee_rate,
+            QuoteCurrency::USDC => self.usdc_fee_rate,
+        }
+    }
+
+    fn conversion_rate(&self, quote: QuoteCurrency) -> Decimal {
+        match quote {
+            QuoteCurrency::USDT => Decimal::ONE,
+            QuoteCurrency::USDC => self.usdc_rate,
+        }
+    }
+}
+
+/// 比较两个方向扣除手续费并折算为USDT后的净利润率，构造净利润率更高的套利机会
+///
+/// 返回的机会保留两个交易对的原始报价，`profit_percentage` 为净利润率；两个方向相同时在USDC交易对买入。
+pub fn best_execution_opportunity(
+    base_asset: &str,
+    usdt_price: Decimal,
+    usdc_price: Decimal,
+    max_trade_amount: Decimal,
+    costs: &ExecutionCosts,
+) -> ArbitrageOpportunity {
+    let buy_usdt = costs.net_profit_percentage(QuoteCurrency::USDT, usdt_price, QuoteCurrency::USDC, usdc_price);
+    let buy_usdc = costs.net_profit_percentage(QuoteCurrency::USDC, usdc_price, QuoteCurrency::USDT, usdt_price);
+
+    let (mut opportunity, net_profit_percentage) = if buy_usdt > buy_usdc {
+        (
+            ArbitrageOpportunity::new(base_asset, QuoteCurrency::USDT, QuoteCurrency::USDC, usdt_price, usdc_price, max_trade_amount),
+            buy_usdt,
+        )
+    } else {
+        (
+            ArbitrageOpportunity::new(base_asset, QuoteCurrency::USDC, QuoteCurrency::USDT, usdc_price, usdt_price, max_trade_amount),
+            buy_usdc,
+        )
+    };
+    opportunity.profit_percentage = net_profit_percentage;
+    opportunity
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate(strategy: &str, buy_price: Decimal, sell_price: Decimal, max_trade_amount: Decimal) -> ArbitrageOpportunity {
+        let mut opportunity = ArbitrageOpportunity::new(
+            "BTC",
+            QuoteCurrency::USDT,
+            QuoteCurrency::USDC,
+            buy_price,
+            sell_price,
+            max_trade_amount,
+        );
+        opportunity.strategy = Some(strategy.to_string());
+        opportunity
+    }
+
+    fn best_strategy(candidates: &[ArbitrageOpportunity], ranking: OpportunityRanking, volatility: Decimal) -> String {
+        select_best_opportunity(candidates.to_vec(), ranking, volatility)
+            .and_then(|opportunity| opportunity.strategy)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_rankings_pick_expected_candidate() {
+        // 利润率 0.5%，交易金额 100：预期利润 0.5
+        let wide = candidate("wide", dec!(100), dec!(100.5), dec!(100));
+        // 利润率 0.1%，交易金额 2000：预期利润 2
+        let deep = candidate("deep", dec!(100), dec!(100.1), dec!(2000));
+        let candidates = [wide.clone(), deep.clone()];
+
+        assert_eq!(rank_opportunity(&wide, OpportunityRanking::ByPercentage, Decimal::ZERO), dec!(0.5));
+        assert_eq!(rank_opportunity(&deep, OpportunityRanking::ByAbsoluteProfit, Decimal::ZERO), dec!(2));
+
+        assert_eq!(best_strategy(&candidates, OpportunityRanking::ByPercentage, Decimal::ZERO), "wide");
+        assert_eq!(best_strategy(&candidates, OpportunityRanking::ByAbsoluteProfit, Decimal::ZERO), "deep");
+
+        // 没有波动时风险调整后的得分等于预期利润
+        assert_eq!(best_strategy(&candidates, OpportunityRanking::ByRiskAdjusted, Decimal::ZERO), "deep");
+        // 波动率 0.5%：wide 得分 0.5 × 0.5 / 1.0 = 0.25，deep 得分 2 × 0.1 / 0.6 ≈ 0.33
+        assert_eq!(best_strategy(&candidates, OpportunityRanking::ByRiskAdjusted, dec!(0.5)), "deep");
+        // 波动率 2%：wide 得分 0.5 × 0.5 / 2.5 = 0.1，deep 得分 2 × 0.1 / 2.1 ≈ 0.095，薄利的机会被淘汰
+        assert_eq!(rank_opportunity(&wide, OpportunityRanking::ByRiskAdjusted, dec!(2)), dec!(0.1));
+        assert_eq!(best_strategy(&candidates, OpportunityRanking::ByRiskAdjusted, dec!(2)), "wide");
+    }
+
+    #[test]
+    fn test_select_best_skips_unprofitable_and_keeps_first_on_tie() {
+        let losing = candidate("losing", dec!(100), dec!(99), dec!(1000));
+        assert!(select_best_opportunity(vec![losing.clone()], OpportunityRanking::ByAbsoluteProfit, Decimal::ZERO).is_none());
+
+        let first = candidate("first", dec!(100), dec!(100.2), dec!(100));
+        let second = candidate("second", dec!(100), dec!(100.2), dec!(100));
+        assert_eq!(best_strategy(&[losing, first, second], OpportunityRanking::ByPercentage, Decimal::ZERO), "first");
+    }
+
+    #[test]
+    fn test_price_volatility() {
+        assert_eq!(price_volatility(&[dec!(100)]), Decimal::ZERO);
+        assert_eq!(price_volatility(&[dec!(100), dec!(100), dec!(100)]), Decimal::ZERO);
+
+        // 均值 100，样本标准差 1
+        let volatility = price_volatility(&[dec!(99), dec!(100), dec!(101)]);
+        assert!((volatility - dec!(1)).abs() < dec!(0.000001));
+    }
+
+    fn costs(usdt_fee_rate: Decimal, usdc_fee_rate: Decimal, usdc_rate: Decimal) -> ExecutionCosts {
+        ExecutionCosts { usdt_fee_rate, usdc_fee_rate, usdc_rate }
+    }
+
+    #[test]
+    fn test_best_execution_includes_fees() {
+        // 不计成本时 USDT 交易对便宜 0.05%，在 USDT 买入有利可图
+        let free = costs(Decimal::ZERO, Decimal::ZERO, Decimal::ONE);
+        let opportunity = best_execution_opportunity("BTC", dec!(100), dec!(100.05), dec!(1000), &free);
+        assert_eq!(opportunity.buy_quote, QuoteCurrency::USDT);
+        assert_eq!(opportunity.profit_percentage, dec!(0.05));
+
+        // 两边各 0.1% 手续费后该方向变为亏损，报价保持不变
+        let fees = costs(dec!(0.001), dec!(0.001), Decimal::ONE);
+        let opportunity = best_execution_opportunity("BTC", dec!(100), dec!(100.05), dec!(1000), &fees);
+        assert_eq!(opportunity.buy_quote, QuoteCurrency::USDT);
+        assert_eq!(opportunity.buy_price, dec!(100));
+        assert_eq!(opportunity.sell_price, dec!(100.05));
+        assert!(opportunity.profit_percentage < Decimal::ZERO);
+        // 卖出所得 100.05 × 0.999 = 99.94995，买入成本 100.1
+        assert_eq!(
+            fees.net_profit_percentage(QuoteCurrency::USDT, dec!(100), QuoteCurrency::USDC, dec!(100.05)).round_dp(6),
+            dec!(-0.149900)
+        );
+    }
+
+    #[test]
+    fn test_best_execution_flips_direction_with_conversion() {
+        // 报价上 USDT 交易对更便宜，但 1 USDC 只值 0.999 USDT，
+        // USDC 交易对折算后的价格为 100.05 × 0.999 = 99.95005，在 USDC 买入才是净收益更高的方向
+        let discounted = costs(Decimal::ZERO, Decimal::ZERO, dec!(0.999));
+        let opportunity = best_execution_opportunity("BTC", dec!(100), dec!(100.05), dec!(1000), &discounted);
+        assert_eq!(opportunity.buy_quote, QuoteCurrency::USDC);
+        assert_eq!(opportunity.sell_quote, QuoteCurrency::USDT);
+        assert_eq!(opportunity.buy_price, dec!(100.05));
+        assert!(opportunity.profit_percentage > Decimal::ZERO);
+
+        // 计入两边 0.1% 手续费后仍选择 USDC 买入，但已无利可图
+        let with_fees = costs(dec!(0.001), dec!(0.001), dec!(0.999));
+        let opportunity = best_execution_opportunity("BTC", dec!(100), dec!(100.05), dec!(1000), &with_fees);
+        assert_eq!(opportunity.buy_quote, QuoteCurrency::USDC);
+        assert!(opportunity.profit_percentage < Decimal::ZERO);
+    }
+}
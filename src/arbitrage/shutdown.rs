@@ -0,0 +1,128 @@
+use super::engine::SessionStats;
+use crate::models::{OrderInfo, RejectionReason};
+use rust_decimal::Decimal;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// 优雅停止的请求标志，可以在信号处理任务和引擎之间共享
+///
+/// 请求停止后监控循环在本轮结束时返回，正在执行的套利照常完成或回补，不再开始新的套利。
+#[derive(Debug, Clone, Default)]
+pub struct ShutdownSignal(Arc<AtomicBool>);
+
+impl ShutdownSignal {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 请求停止，返回是否为第一次请求
+    pub fn request(&self) -> bool {
+        !self.0.swap(true, Ordering::SeqCst)
+    }
+
+    /// 是否已请求停止
+    pub fn is_requested(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+/// 会话结束时的汇总
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionSummary {
+    /// 引擎创建到结束的运行时间
+    pub runtime: Duration,
+    /// 会话的交易统计
+    pub stats: SessionStats,
+    /// 各风控组件按拒绝原因的拒绝次数，按组件名称和原因排序
+    pub rejections: Vec<(String, RejectionReason, u64)>,
+    /// 结束时取消的挂单
+    pub cancelled_orders: Vec<OrderInfo>,
+}
+
+impl SessionSummary {
+    /// 扣除手续费之前的利润
+    pub fn gross_profit(&self) -> Decimal {
+        self.stats.profit + self.stats.fees
+    }
+}
+
+impl fmt::Display for SessionSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let seconds = self.runtime.as_secs();
+        writeln!(f, "========== 会话汇总 ==========")?;
+        writeln!(f, "运行时间: {:02}:{:02}:{:02}", seconds / 3600, seconds % 3600 / 60, seconds % 60)?;
+        writeln!(
+            f,
+            "交易次数: {} (成功 {}, 失败 {})",
+            self.stats.trades, self.stats.successful_trades, self.stats.failed_trades
+        )?;
+        if let Some(rate) = self.stats.success_rate() {
+            // Decimal按精度格式化时截断，先四舍五入
+            writeln!(f, "成功率: {:.2}%", rate.round_dp(2))?;
+        }
+        writeln!(f, "毛利润: {:.4} USDT", self.gross_profit())?;
+        writeln!(f, "手续费: {:.4} USDT", self.stats.fees)?;
+        writeln!(f, "净利润: {:.4} USDT", self.stats.profit)?;
+        if let (Some(best), Some(worst)) = (self.stats.best_profit, self.stats.worst_profit) {
+            writeln!(f, "最佳交易: {:.4} USDT", best)?;
+            writeln!(f, "最差交易: {:.4} USDT", worst)?;
+        }
+        let total_rejections: u64 = self.rejections.iter().map(|(_, _, count)| count).sum();
+        writeln!(f, "被拒绝的机会: {}", total_rejections)?;
+        for (controller, reason, count) in &self.rejections {
+            writeln!(f, "  {} ({}): {}", controller, reason, count)?;
+        }
+        write!(f, "结束时取消的挂单: {}", self.cancelled_orders.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_signal_reports_first_request() {
+        let signal = ShutdownSignal::new();
+        let shared = signal.clone();
+        assert!(!shared.is_requested());
+        assert!(signal.request());
+        assert!(shared.is_requested());
+        assert!(!shared.request());
+    }
+
+    #[test]
+    fn test_summary_display() {
+        let summary = SessionSummary {
+            runtime: Duration::from_secs(3725),
+            stats: SessionStats {
+                trades: 3,
+                successful_trades: 2,
+                failed_trades: 1,
+                profit: dec!(1.5),
+                fees: dec!(0.25),
+                best_profit: Some(dec!(1.25)),
+                worst_profit: Some(dec!(-0.5)),
+            },
+            rejections: vec![("交易频率控制".to_string(), RejectionReason::FrequencyLimited, 4)],
+            cancelled_orders: Vec::new(),
+        };
+
+        let expected = "\
+========== 会话汇总 ==========
+运行时间: 01:02:05
+交易次数: 3 (成功 2, 失败 1)
+成功率: 66.67%
+毛利润: 1.7500 USDT
+手续费: 0.2500 USDT
+净利润: 1.5000 USDT
+最佳交易: 1.2500 USDT
+最差交易: -0.5000 USDT
+被拒绝的机会: 4
+  交易频率控制 (frequency_limited): 4
+结束时取消的挂单: 0";
+        assert_eq!(summary.to_string(), expected);
+    }
+}
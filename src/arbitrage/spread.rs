@@ -0,0 +1,251 @@
+use crate::binance::ExchangeApi;
+use crate::models::Price;
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use std::collections::HashMap;
+use std::fmt::{self, Write};
+use std::str::FromStr;
+
+/// USDT和USDC交易对的价差百分比（USDC价格减USDT价格，除以较低的价格）
+///
+/// 正数表示USDC交易对更贵，应在USDT交易对买入；价格不为正数时返回None。
+pub fn spread_percentage(usdt_price: Decimal, usdc_price: Decimal) -> Option<Decimal> {
+    let lower = usdt_price.min(usdc_price);
+    (lower > Decimal::ZERO).then(|| (usdc_price - usdt_price) / lower * dec!(100))
+}
+
+/// 一次请求获取多组 (USDT交易对, USDC交易对) 的价格，按给定顺序返回
+///
+/// 只使用公开的行情接口，不需要API密钥。
+pub async fn fetch_pair_prices<T: ExchangeApi + ?Sized>(api: &T, pairs: &[(String, String)]) -> Result<Vec<(Price, Price)>> {
+    let symbols: Vec<String> = pairs.iter().flat_map(|(usdt, usdc)| [usdt.clone(), usdc.clone()]).collect();
+    let prices = api.get_prices(&symbols).await?;
+    let find = |symbol: &str| {
+        prices
+            .iter()
+            .find(|price| price.symbol == symbol)
+            .cloned()
+            .ok_or_else(|| anyhow!("批量行情缺少交易对: {}", symbol))
+    };
+    pairs.iter().map(|(usdt, usdc)| Ok((find(usdt)?, find(usdc)?))).collect()
+}
+
+/// 价差的输出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpreadFormat {
+    /// 对齐的表格，每轮刷新一次
+    Table,
+    /// CSV，第一行为表头，每轮每个资产一行
+    Csv,
+}
+
+impl FromStr for SpreadFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "table" => Ok(SpreadFormat::Table),
+            "csv" => Ok(SpreadFormat::Csv),
+            _ => Err(anyhow!("不支持的输出格式: {}，应为 table 或 csv", s)),
+        }
+    }
+}
+
+impl fmt::Display for SpreadFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SpreadFormat::Table => write!(f, "table"),
+            SpreadFormat::Csv => write!(f, "csv"),
+        }
+    }
+}
+
+/// 一个资产在某一时刻的价差
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpreadSample {
+    pub timestamp: DateTime<Utc>,
+    pub asset: String,
+    pub usdt_price: Decimal,
+    pub usdc_price: Decimal,
+    /// USDC价格减USDT价格
+    pub spread: Decimal,
+    /// 价差百分比，价格无效时为None
+    pub spread_percentage: Option<Decimal>,
+    /// 本次运行中该资产价差百分比绝对值的最大值
+    pub max_spread_percentage: Decimal,
+}
+
+impl SpreadSample {
+    pub const CSV_HEADER: &'static str = "timestamp,asset,usdt_price,usdc_price,spread,spread_percentage,max_spread_percentage";
+
+    pub fn to_csv_row(&self) -> String {
+        format!(
+            "{},{},{},{},{},{},{}",
+            self.timestamp.to_rfc3339(),
+            self.asset,
+            self.usdt_price.normalize(),
+            self.usdc_price.normalize(),
+            self.spread.normalize(),
+            self.spread_percentage.map(|pct| pct.round_dp(4).normalize().to_string()).unwrap_or_default(),
+            self.max_spread_percentage.round_dp(4).normalize()
+        )
+    }
+}
+
+/// 只读的价差监视，不下单，记录本次运行中各资产的最大价差
+pub struct SpreadMonitor {
+    base_assets: Vec<String>,
+    max_spreads: HashMap<String, Decimal>,
+}
+
+impl SpreadMonitor {
+    pub fn new(base_assets: &[String]) -> Self {
+        Self {
+            base_assets: base_assets.iter().map(|asset| asset.trim().to_uppercase()).collect(),
+            max_spreads: HashMap::new(),
+        }
+    }
+
+    /// 获取所有资产的最新价格，按资产顺序返回价差
+    pub async fn poll<T: ExchangeApi + ?Sized>(&mut self, api: &T) -> Result<Vec<SpreadSample>> {
+        let pairs: Vec<(String, String)> = self
+            .base_assets
+            .iter()
+            .map(|asset| (format!("{}USDT", asset), format!("{}USDC", asset)))
+            .collect();
+        let prices = fetch_pair_prices(api, &pairs).await?;
+        let timestamp = Utc::now();
+        let assets = self.base_assets.clone();
+        Ok(assets
+            .iter()
+            .zip(prices)
+            .map(|(asset, (usdt, usdc))| self.observe(asset, usdt.price, usdc.price, timestamp))
+            .collect())
+    }
+
+    /// 计算一组价格的价差并更新最大价差
+    pub fn observe(&mut self, asset: &str, usdt_price: Decimal, usdc_price: Decimal, timestamp: DateTime<Utc>) -> SpreadSample {
+        let spread_percentage = spread_percentage(usdt_price, usdc_price);
+        let max = self.max_spreads.entry(asset.to_string()).or_insert(Decimal::ZERO);
+        if let Some(pct) = spread_percentage {
+            *max = (*max).max(pct.abs());
+        }
+        SpreadSample {
+            timestamp,
+            asset: asset.to_string(),
+            usdt_price,
+            usdc_price,
+            spread: usdc_price - usdt_price,
+            spread_percentage,
+            max_spread_percentage: *max,
+        }
+    }
+}
+
+/// 对齐的价差表格，第一行为表头
+pub fn render_table(samples: &[SpreadSample]) -> String {
+    const HEADERS: [&str; 6] = ["asset", "usdt_price", "usdc_price", "spread", "spread_pct", "max_pct"];
+    let rows: Vec<[String; 6]> = samples
+        .iter()
+        .map(|sample| {
+            [
+                sample.asset.clone(),
+                sample.usdt_price.normalize().to_string(),
+                sample.usdc_price.normalize().to_string(),
+                sample.spread.normalize().to_string(),
+                sample
+                    .spread_percentage
+                    .map(|pct| format!("{:.4}%", pct))
+                    .unwrap_or_else(|| "-".to_string()),
+                format!("{:.4}%", sample.max_spread_percentage),
+            ]
+        })
+        .collect();
+
+    let mut widths = HEADERS.map(str::len);
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.len());
+        }
+    }
+    let mut out = String::new();
+    let mut write_row = |cells: &[String; 6]| {
+        // 资产左对齐，数值右对齐
+        let _ = writeln!(
+            out,
+            "{:<w0$}  {:>w1$}  {:>w2$}  {:>w3$}  {:>w4$}  {:>w5$}",
+            cells[0], cells[1], cells[2], cells[3], cells[4], cells[5],
+            w0 = widths[0], w1 = widths[1], w2 = widths[2], w3 = widths[3], w4 = widths[4], w5 = widths[5]
+        );
+    };
+    write_row(&HEADERS.map(String::from));
+    for row in &rows {
+        write_row(row);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::binance::MockBinanceApi;
+
+    #[test]
+    fn test_spread_percentage() {
+        assert_eq!(spread_percentage(dec!(50000), dec!(50025)), Some(dec!(0.05)));
+        // 按较低的价格计算，USDT交易对更贵时为负数
+        assert_eq!(spread_percentage(dec!(100.5), dec!(100)), Some(dec!(-0.5)));
+        assert_eq!(spread_percentage(dec!(100), dec!(100)), Some(Decimal::ZERO));
+        assert_eq!(spread_percentage(Decimal::ZERO, dec!(100)), None);
+    }
+
+    #[test]
+    fn test_max_spread_tracks_absolute_percentage_per_asset() {
+        let mut monitor = SpreadMonitor::new(&["btc".to_string()]);
+        let now = Utc::now();
+
+        let sample = monitor.observe("BTC", dec!(100), dec!(100.2), now);
+        assert_eq!(sample.spread, dec!(0.2));
+        assert_eq!(sample.max_spread_percentage, dec!(0.2));
+        let sample = monitor.observe("BTC", dec!(100.5), dec!(100), now);
+        assert_eq!(sample.spread_percentage, Some(dec!(-0.5)));
+        assert_eq!(sample.max_spread_percentage, dec!(0.5));
+        let sample = monitor.observe("BTC", dec!(100), dec!(100.1), now);
+        assert_eq!(sample.max_spread_percentage, dec!(0.5));
+        // 各资产分别记录
+        assert_eq!(monitor.observe("ETH", dec!(100), dec!(100), now).max_spread_percentage, Decimal::ZERO);
+    }
+
+    #[tokio::test]
+    async fn test_poll_reports_each_asset() {
+        let api = MockBinanceApi::new();
+        let mut monitor = SpreadMonitor::new(&["BTC".to_string(), "eth".to_string()]);
+
+        let samples = monitor.poll(&api).await.unwrap();
+        assert_eq!(samples.len(), 2);
+        assert_eq!((samples[0].asset.as_str(), samples[0].spread), ("BTC", dec!(25)));
+        assert_eq!(samples[1].asset, "ETH");
+        assert_eq!(samples[1].usdc_price, dec!(3002.5));
+
+        let expected = "\
+asset  usdt_price  usdc_price  spread  spread_pct  max_pct
+BTC         50000       50025      25     0.0500%  0.0500%
+ETH          3000      3002.5     2.5     0.0833%  0.0833%
+";
+        assert_eq!(render_table(&samples), expected);
+
+        assert!(SpreadMonitor::new(&["SOL".to_string()]).poll(&api).await.is_err());
+    }
+
+    #[test]
+    fn test_csv_row() {
+        let mut monitor = SpreadMonitor::new(&[]);
+        let timestamp = DateTime::parse_from_rfc3339("2024-05-01T08:00:00Z").unwrap().with_timezone(&Utc);
+        let sample = monitor.observe("BTC", dec!(50000.00), dec!(49990.00), timestamp);
+
+        assert_eq!(SpreadSample::CSV_HEADER.split(',').count(), sample.to_csv_row().split(',').count());
+        assert_eq!(sample.to_csv_row(), "2024-05-01T08:00:00+00:00,BTC,50000,49990,-10,-0.02,0.02");
+    }
+}
@@ -1,40 +1,156 @@
 use crate::config::Config;
-use crate::models::{OrderBook, Price, QuoteCurrency, Side, Symbol, OrderInfo, OrderStatus};
+use crate::models::{AssetBalance, DustConversion, OrderBook, Price, PriceSource, QuoteCurrency, Side, SubAccountTransfer, Symbol, OrderInfo, OrderStatus, SystemStatus, TimeInForce, UserDataEvent};
 use anyhow::{Context, Result, anyhow};
 use async_trait::async_trait;
 use chrono::{Utc, DateTime};
+use futures::stream::{BoxStream, StreamExt};
 use hmac::{Hmac, Mac};
-use reqwest::{Client, RequestBuilder, Url};
+use reqwest::{Client, Request, RequestBuilder, Url};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use sha2::Sha256;
 use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
 use std::time::{SystemTime, UNIX_EPOCH};
-use log::{debug, info, warn, error};
+use tokio_tungstenite::tungstenite::Message;
+use log::{debug, info, warn, error, trace, log_enabled, Level};
 
 type HmacSha256 = Hmac<Sha256>;
 
+/// 当前API地址连续失败多少次后切换到下一个地址
+const FAILOVER_THRESHOLD: u32 = 3;
+
+/// 交易所返回5xx状态码，说明当前API地址暂时不可用
+#[derive(Debug)]
+struct ServerError {
+    body: String,
+}
+
+impl fmt::Display for ServerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "API error: {}", self.body)
+    }
+}
+
+impl std::error::Error for ServerError {}
+
+/// 用户数据流，连接断开时结束
+pub type UserDataStream = BoxStream<'static, Result<UserDataEvent>>;
+
 #[async_trait]
 pub trait ExchangeApi {
     async fn get_symbol_info(&self, symbol: &str) -> Result<Symbol>;
     async fn get_price(&self, symbol: &str) -> Result<Price>;
+    /// 一次请求获取多个交易对的价格，`symbols` 为空时返回全部交易对
+    async fn get_prices(&self, symbols: &[String]) -> Result<Vec<Price>>;
     async fn get_order_book(&self, symbol: &str, limit: Option<u32>) -> Result<OrderBook>;
-    async fn place_order(&self, symbol: &str, side: Side, quantity: Decimal, price: Option<Decimal>) -> Result<OrderInfo>;
+    /// 获取最近一笔成交的价格
+    async fn get_last_trade_price(&self, symbol: &str) -> Result<Price>;
+    /// 下单；`price` 为 None 时为市价单，否则为限价单并使用 `time_in_force` 指定有效方式
+    ///
+    /// `client_order_id` 作为 `newClientOrderId` 提交，为 None 时由交易所生成。
+    async fn place_order(&self, symbol: &str, side: Side, quantity: Decimal, price: Option<Decimal>, time_in_force: TimeInForce, client_order_id: Option<&str>) -> Result<OrderInfo>;
     async fn get_order_status(&self, symbol: &str, order_id: u64) -> Result<OrderInfo>;
     async fn cancel_order(&self, symbol: &str, order_id: u64) -> Result<OrderInfo>;
+    /// 查询交易对当前未结束的订单，`timestamp` 为下单时间
+    async fn get_open_orders(&self, symbol: &str) -> Result<Vec<OrderInfo>>;
     async fn get_account_balance(&self, asset: &str) -> Result<Decimal>;
+    /// 查询账户中所有币种的可用和冻结余额
+    async fn get_account_balances(&self) -> Result<Vec<AssetBalance>>;
+    /// 查询交易所系统状态，维护期间不应下单
+    async fn get_system_status(&self) -> Result<SystemStatus>;
+    /// 查询交易所服务器时间，签名请求的时间戳与其相差过大时会被拒绝
+    async fn get_server_time(&self) -> Result<DateTime<Utc>>;
+    /// 创建用户数据流的 listenKey，有效期60分钟
+    async fn create_listen_key(&self) -> Result<String>;
+    /// 延长 listenKey 的有效期，应至少每30分钟调用一次
+    async fn keepalive_listen_key(&self, listen_key: &str) -> Result<()>;
+    /// 订阅用户数据流，推送订单状态更新等事件
+    async fn subscribe_user_data(&self, listen_key: &str) -> Result<UserDataStream>;
+    /// 将币种的全部可用余额兑换为BNB，用于处理低于最小下单量的小额资产
+    async fn convert_dust(&self, asset: &str) -> Result<DustConversion>;
+    /// 从当前账户向子账户划转资产，用于分配已实现的利润
+    async fn transfer_to_sub_account(&self, to_email: &str, asset: &str, amount: Decimal) -> Result<SubAccountTransfer>;
+    
+    /// 按指定来源获取参考价格
+    async fn get_reference_price(&self, symbol: &str, source: PriceSource) -> Result<Price> {
+        match source {
+            PriceSource::Ticker => self.get_price(symbol).await,
+            PriceSource::LastTrade => self.get_last_trade_price(symbol).await,
+            PriceSource::BookMid => {
+                let book = self.get_order_book(symbol, Some(5)).await?;
+                let price = book.mid_price()
+                    .ok_or_else(|| anyhow!("订单簿为空，无法计算中间价: {}", symbol))?;
+                Ok(Price {
+                    symbol: symbol.to_string(),
+                    price,
+                    timestamp: book.timestamp,
+                })
+            },
+        }
+    }
 }
 
 pub struct BinanceApi {
     client: Client,
     config: Config,
+    /// 主地址 `base_url` 和备用地址 `fallback_base_urls`，按顺序轮换
+    base_urls: Vec<String>,
+    /// 当前使用的地址在 `base_urls` 中的位置
+    active_url: AtomicUsize,
+    /// 当前地址连续失败的次数
+    consecutive_failures: AtomicU32,
 }
 
 impl BinanceApi {
     pub fn new(config: Config) -> Self {
+        let base_urls = std::iter::once(config.base_url.clone())
+            .chain(config.fallback_base_urls.iter().cloned())
+            .collect();
         Self {
             client: Client::new(),
             config,
+            base_urls,
+            active_url: AtomicUsize::new(0),
+            consecutive_failures: AtomicU32::new(0),
+        }
+    }
+    
+    /// 当前使用的API地址
+    fn base_url(&self) -> &str {
+        &self.base_urls[self.active_url.load(Ordering::Relaxed)]
+    }
+    
+    /// 连接失败、超时或5xx状态码说明API地址不可用，交易所拒绝请求等其他错误不计入
+    fn is_endpoint_failure(error: &anyhow::Error) -> bool {
+        error.downcast_ref::<reqwest::Error>().is_some() || error.downcast_ref::<ServerError>().is_some()
+    }
+    
+    /// 记录行情请求的结果，第 `index` 个地址连续失败 `FAILOVER_THRESHOLD` 次后切换到下一个地址
+    ///
+    /// 最后一个备用地址也失败时回到主地址，没有备用地址时不切换。
+    fn record_endpoint_result(&self, index: usize, failed: bool) {
+        if self.active_url.load(Ordering::Relaxed) != index {
+            // 其他请求已经切换了地址
+            return;
+        }
+        if !failed {
+            self.consecutive_failures.store(0, Ordering::Relaxed);
+            return;
+        }
+        
+        let failures = self.consecutive_failures.fetch_add(1, Ordering::Relaxed) + 1;
+        if failures < FAILOVER_THRESHOLD || self.base_urls.len() < 2 {
+            return;
+        }
+        let next = (index + 1) % self.base_urls.len();
+        if self.active_url.compare_exchange(index, next, Ordering::Relaxed, Ordering::Relaxed).is_ok() {
+            self.consecutive_failures.store(0, Ordering::Relaxed);
+            warn!(
+                "API地址 {} 连续 {} 次请求失败，切换到 {}",
+                self.base_urls[index], failures, self.base_urls[next]
+            );
         }
     }
 
@@ -56,8 +172,10 @@ impl BinanceApi {
         Ok(hex::encode(signature))
     }
 
+    /// 发送行情等公开接口的请求，按结果统计当前API地址的连续失败次数
     async fn send_public_request(&self, endpoint: &str, params: Option<HashMap<String, String>>) -> Result<serde_json::Value> {
-        let url = format!("{}{}", self.config.base_url, endpoint);
+        let index = self.active_url.load(Ordering::Relaxed);
+        let url = format!("{}{}", self.base_urls[index], endpoint);
         
         let mut request_builder = self.client.get(&url);
         
@@ -65,7 +183,9 @@ impl BinanceApi {
             request_builder = request_builder.query(&params);
         }
         
-        self.send_request(request_builder).await
+        let result = self.send_request(request_builder).await;
+        self.record_endpoint_result(index, result.as_ref().is_err_and(Self::is_endpoint_failure));
+        result
     }
 
     async fn send_signed_request(&self, endpoint: &str, method: &str, mut params: HashMap<String, String>) -> Result<serde_json::Value> {
@@ -79,7 +199,7 @@ impl BinanceApi {
         let signature = self.sign_payload(&query)?;
         params.insert("signature".to_string(), signature);
         
-        let url = format!("{}{}", self.config.base_url, endpoint);
+        let url = format!("{}{}", self.base_url(), endpoint);
         
         let request_builder = match method {
             "GET" => self.client.get(&url).query(&params),
@@ -103,15 +223,284 @@ impl BinanceApi {
             .join("&")
     }
 
+    /// 根据累计成交金额计算成交均价，未成交时返回0
+    fn parse_avg_price(response: &serde_json::Value, executed_qty: Decimal) -> Result<Decimal> {
+        let quote_qty = match response["cummulativeQuoteQty"].as_str() {
+            Some(q) => q.parse::<Decimal>()?,
+            None => Decimal::ZERO,
+        };
+        
+        if executed_qty.is_zero() {
+            Ok(Decimal::ZERO)
+        } else {
+            Ok(quote_qty / executed_qty)
+        }
+    }
+    
+    /// 解析 /api/v3/ticker/price 返回的价格数组
+    fn parse_ticker_prices(response: &serde_json::Value) -> Result<Vec<Price>> {
+        let tickers = response.as_array().context("Ticker prices not found in response")?;
+        let timestamp = Utc::now();
+        
+        tickers.iter()
+            .map(|ticker| {
+                let symbol = ticker["symbol"].as_str().context("Symbol not found in ticker")?;
+                let price_str = ticker["price"].as_str().context("Price not found in ticker")?;
+                Ok(Price {
+                    symbol: symbol.to_string(),
+                    price: price_str.parse::<Decimal>()?,
+                    timestamp,
+                })
+            })
+            .collect()
+    }
+    
+    /// 汇总成交明细中的手续费，返回 (手续费, 手续费币种)
+    /// 成交明细来自下单的FULL响应或 /api/v3/myTrades
+    fn sum_commission(fills: &[serde_json::Value]) -> Result<(Decimal, String)> {
+        let mut commission = Decimal::ZERO;
+        let mut commission_asset = String::new();
+        
+        for fill in fills {
+            if let Some(c) = fill["commission"].as_str() {
+                commission += c.parse::<Decimal>()?;
+            }
+            if let Some(asset) = fill["commissionAsset"].as_str() {
+                if !commission_asset.is_empty() && commission_asset != asset {
+                    warn!("订单手续费包含多个币种: {} / {}", commission_asset, asset);
+                }
+                commission_asset = asset.to_string();
+            }
+        }
+        
+        Ok((commission, commission_asset))
+    }
+    
+    /// 查询订单的成交明细并汇总手续费
+    async fn get_order_commission(&self, symbol: &str, order_id: u64) -> Result<(Decimal, String)> {
+        let mut params = HashMap::new();
+        params.insert("symbol".to_string(), symbol.to_string());
+        params.insert("orderId".to_string(), order_id.to_string());
+        
+        let response = self.send_signed_request("/api/v3/myTrades", "GET", params).await?;
+        let trades = response.as_array().context("Trades not found in response")?;
+        
+        Self::sum_commission(trades)
+    }
+
+    /// 是否记录API调用的原始请求和响应，未开启或日志级别不足时不做任何格式化
+    fn trace_enabled(&self) -> bool {
+        self.config.trace_api && log_enabled!(Level::Trace)
+    }
+
+    /// 隐藏密钥，仅保留前4位用于辨认
+    fn mask_secret(value: &str) -> String {
+        match value.get(..4) {
+            Some(prefix) if value.len() > 8 => format!("{}****", prefix),
+            _ => "****".to_string(),
+        }
+    }
+
+    /// 格式化请求用于跟踪日志，签名参数和API Key均被隐藏
+    fn format_request_trace(request: &Request) -> String {
+        let mut url = request.url().clone();
+        let pairs: Vec<(String, String)> = url
+            .query_pairs()
+            .map(|(k, v)| {
+                let v = if k == "signature" || k == "listenKey" { "***".to_string() } else { v.into_owned() };
+                (k.into_owned(), v)
+            })
+            .collect();
+        if !pairs.is_empty() {
+            url.query_pairs_mut().clear().extend_pairs(pairs);
+        }
+
+        let headers = request
+            .headers()
+            .iter()
+            .map(|(name, value)| {
+                let value = value.to_str().unwrap_or("<binary>");
+                if name.as_str().eq_ignore_ascii_case("X-MBX-APIKEY") {
+                    format!("{}: {}", name, Self::mask_secret(value))
+                } else {
+                    format!("{}: {}", name, value)
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        format!("{} {} [{}]", request.method(), url, headers)
+    }
+
+    /// 发送只需要API Key、不需要签名的请求（用户数据流相关接口）
+    async fn send_api_key_request(&self, endpoint: &str, method: &str, params: HashMap<String, String>) -> Result<serde_json::Value> {
+        let url = format!("{}{}", self.base_url(), endpoint);
+        
+        let request_builder = match method {
+            "POST" => self.client.post(&url).query(&params),
+            "PUT" => self.client.put(&url).query(&params),
+            "DELETE" => self.client.delete(&url).query(&params),
+            _ => return Err(anyhow!("Unsupported HTTP method: {}", method)),
+        };
+        
+        let request_builder = request_builder.header("X-MBX-APIKEY", &self.config.api_key);
+        
+        self.send_request(request_builder).await
+    }
+    
+    /// 用户数据流的WebSocket地址，测试网使用测试网的行情流地址
+    fn user_data_stream_url(&self, listen_key: &str) -> String {
+        let ws_base = if self.config.base_url.contains("testnet") {
+            "wss://testnet.binance.vision/ws"
+        } else {
+            "wss://stream.binance.com:9443/ws"
+        };
+        format!("{}/{}", ws_base, listen_key)
+    }
+    
+    /// 解析账户信息接口响应中的 `balances`
+    fn parse_account_balances(response: &serde_json::Value) -> Result<Vec<AssetBalance>> {
+        let balances = response["balances"].as_array().context("账户信息中没有余额列表")?;
+        balances
+            .iter()
+            .map(|balance| {
+                let asset = balance["asset"].as_str().context("余额中没有币种")?;
+                let amount = |field: &str| -> Result<Decimal> {
+                    let value = balance[field].as_str().unwrap_or("0");
+                    value.parse::<Decimal>().with_context(|| format!("{} 的余额无效: {}", asset, value))
+                };
+                Ok(AssetBalance {
+                    asset: asset.to_string(),
+                    free: amount("free")?,
+                    locked: amount("locked")?,
+                })
+            })
+            .collect()
+    }
+    
+    /// 解析小额资产兑换接口的响应
+    fn parse_dust_conversion(asset: &str, response: &serde_json::Value) -> Result<DustConversion> {
+        let result = response["transferResult"]
+            .as_array()
+            .and_then(|results| results.iter().find(|result| result["fromAsset"].as_str() == Some(asset)))
+            .with_context(|| format!("兑换结果中没有 {}", asset))?;
+        let decimal = |value: &serde_json::Value, field: &str| -> Result<Decimal> {
+            value[field]
+                .as_str()
+                .with_context(|| format!("{} not found in response", field))?
+                .parse::<Decimal>()
+                .with_context(|| format!("无效的 {}", field))
+        };
+        
+        Ok(DustConversion {
+            asset: asset.to_string(),
+            amount: decimal(result, "amount")?,
+            transferred: decimal(result, "transferedAmount")?,
+            service_charge: decimal(result, "serviceChargeAmount")?,
+        })
+    }
+    
+    /// 解析子账户划转的响应，划转ID可能为字符串或数字
+    fn parse_sub_account_transfer(to_email: &str, asset: &str, amount: Decimal, response: &serde_json::Value) -> Result<SubAccountTransfer> {
+        if response["success"].as_bool() == Some(false) {
+            return Err(anyhow!("子账户划转失败: {}", response));
+        }
+        let txn_id = match &response["txnId"] {
+            serde_json::Value::String(id) => id.clone(),
+            serde_json::Value::Number(id) => id.to_string(),
+            _ => return Err(anyhow!("txnId not found in response")),
+        };
+        
+        Ok(SubAccountTransfer {
+            txn_id,
+            to_email: to_email.to_string(),
+            asset: asset.to_string(),
+            amount,
+        })
+    }
+    
+    /// 解析用户数据流的消息，订单状态更新转换为订单信息
+    fn parse_user_data_event(message: &str) -> Result<UserDataEvent> {
+        let event: serde_json::Value = serde_json::from_str(message)
+            .context(format!("无法解析用户数据流消息: {}", message))?;
+        let event_type = event["e"].as_str().unwrap_or_default();
+        if event_type != "executionReport" {
+            return Ok(UserDataEvent::Other(event_type.to_string()));
+        }
+        
+        let decimal = |key: &str| -> Result<Decimal> {
+            Ok(event[key].as_str().unwrap_or("0").parse::<Decimal>()?)
+        };
+        let executed_qty = decimal("z")?;
+        let quote_qty = decimal("Z")?;
+        let status = match event["X"].as_str().unwrap_or("NEW") {
+            "PARTIALLY_FILLED" => OrderStatus::PartiallyFilled,
+            "FILLED" => OrderStatus::Filled,
+            "CANCELED" => OrderStatus::Cancelled,
+            "REJECTED" => OrderStatus::Rejected,
+            "EXPIRED" | "EXPIRED_IN_MATCH" => OrderStatus::Expired,
+            _ => OrderStatus::New,
+        };
+        
+        Ok(UserDataEvent::ExecutionReport(OrderInfo {
+            order_id: event["i"].as_u64().context("Order ID not found in execution report")?,
+            symbol: event["s"].as_str().unwrap_or_default().to_string(),
+            price: decimal("p")?,
+            qty: decimal("q")?,
+            executed_qty,
+            avg_price: if executed_qty.is_zero() { Decimal::ZERO } else { quote_qty / executed_qty },
+            commission: decimal("n")?,
+            commission_asset: event["N"].as_str().unwrap_or_default().to_string(),
+            side: match event["S"].as_str().unwrap_or("BUY") {
+                "SELL" => Side::Sell,
+                _ => Side::Buy,
+            },
+            status,
+            timestamp: event["O"].as_i64()
+                .and_then(DateTime::from_timestamp_millis)
+                .unwrap_or_else(Utc::now),
+        }))
+    }
+
     async fn send_request(&self, request_builder: RequestBuilder) -> Result<serde_json::Value> {
-        let response = request_builder.send().await?;
+        let request = request_builder.build()?;
+        let path = request.url().path().to_string();
+        let trace = self.trace_enabled();
+        let request_line = if trace {
+            let line = Self::format_request_trace(&request);
+            trace!("API请求: {}", line);
+            line
+        } else {
+            String::new()
+        };
+        
+        let response = self.client.execute(request).await?;
+        let status = response.status();
+        let body = response.text().await?;
+        
+        if trace {
+            trace!("API响应: {} -> {} {}", request_line, status, body);
+        }
+        
+        if status.is_success() {
+            let json = serde_json::from_str::<serde_json::Value>(&body)
+                .context(format!("无法解析API响应: {}", body))?;
+            return Ok(json);
+        }
         
-        if response.status().is_success() {
-            let json = response.json::<serde_json::Value>().await?;
-            Ok(json)
+        // 币安的错误响应为 {"code": -1121, "msg": "Invalid symbol."}
+        let error: serde_json::Value = serde_json::from_str(&body).unwrap_or_default();
+        tracing::warn!(
+            path = %path,
+            status = status.as_u16(),
+            code = error["code"].as_i64(),
+            reason = error["msg"].as_str().unwrap_or(&body),
+            "API请求失败: {} {} {}", path, status, body
+        );
+        if status.is_server_error() {
+            Err(ServerError { body }.into())
         } else {
-            let error_text = response.text().await?;
-            Err(anyhow!("API error: {}", error_text))
+            Err(anyhow!("API error: {}", body))
         }
     }
 }
@@ -190,6 +579,50 @@ impl ExchangeApi for BinanceApi {
         })
     }
     
+    async fn get_prices(&self, symbols: &[String]) -> Result<Vec<Price>> {
+        let params = if symbols.is_empty() {
+            None
+        } else {
+            let mut params = HashMap::new();
+            params.insert("symbols".to_string(), serde_json::to_string(symbols)?);
+            Some(params)
+        };
+        
+        let response = self.send_public_request("/api/v3/ticker/price", params).await?;
+        let prices = Self::parse_ticker_prices(&response)?;
+        
+        for symbol in symbols {
+            if !prices.iter().any(|p| &p.symbol == symbol) {
+                return Err(anyhow!("Price not found for symbol: {}", symbol));
+            }
+        }
+        
+        Ok(prices)
+    }
+    
+    async fn get_last_trade_price(&self, symbol: &str) -> Result<Price> {
+        let mut params = HashMap::new();
+        params.insert("symbol".to_string(), symbol.to_string());
+        params.insert("limit".to_string(), "1".to_string());
+        
+        let response = self.send_public_request("/api/v3/trades", Some(params)).await?;
+        
+        let trade = response.as_array()
+            .and_then(|trades| trades.last())
+            .context("No trades in response")?;
+        let price_str = trade["price"].as_str().context("Price not found in trade")?;
+        let price = price_str.parse::<Decimal>()?;
+        let timestamp = trade["time"].as_i64()
+            .and_then(DateTime::from_timestamp_millis)
+            .unwrap_or_else(Utc::now);
+        
+        Ok(Price {
+            symbol: symbol.to_string(),
+            price,
+            timestamp,
+        })
+    }
+    
     async fn get_order_book(&self, symbol: &str, limit: Option<u32>) -> Result<OrderBook> {
         let mut params = HashMap::new();
         params.insert("symbol".to_string(), symbol.to_string());
@@ -230,7 +663,7 @@ impl ExchangeApi for BinanceApi {
         })
     }
     
-    async fn place_order(&self, symbol: &str, side: Side, quantity: Decimal, price: Option<Decimal>) -> Result<OrderInfo> {
+    async fn place_order(&self, symbol: &str, side: Side, quantity: Decimal, price: Option<Decimal>, time_in_force: TimeInForce, client_order_id: Option<&str>) -> Result<OrderInfo> {
         let mut params = HashMap::new();
         params.insert("symbol".to_string(), symbol.to_string());
         params.insert("side".to_string(), side.to_string());
@@ -246,9 +679,16 @@ impl ExchangeApi for BinanceApi {
         
         if let Some(price) = price {
             params.insert("price".to_string(), price.to_string());
-            params.insert("timeInForce".to_string(), "GTC".to_string());
+            params.insert("timeInForce".to_string(), time_in_force.to_string());
+        }
+        
+        if let Some(client_order_id) = client_order_id {
+            params.insert("newClientOrderId".to_string(), client_order_id.to_string());
         }
         
+        // FULL响应包含成交明细，用于获取手续费
+        params.insert("newOrderRespType".to_string(), "FULL".to_string());
+        
         let response = self.send_signed_request("/api/v3/order", "POST", params).await?;
         
         let order_id = response["orderId"].as_u64().context("Order ID not found in response")?;
@@ -264,6 +704,12 @@ impl ExchangeApi for BinanceApi {
             Decimal::ZERO
         };
         
+        let executed_qty = if let Some(q) = response["executedQty"].as_str() {
+            q.parse::<Decimal>()?
+        } else {
+            Decimal::ZERO
+        };
+        
         let status_str = response["status"].as_str().unwrap_or("NEW");
         let status = match status_str {
             "NEW" => OrderStatus::New,
@@ -275,11 +721,19 @@ impl ExchangeApi for BinanceApi {
             _ => OrderStatus::New,
         };
         
+        let avg_price = Self::parse_avg_price(&response, executed_qty)?;
+        let fills = response["fills"].as_array().cloned().unwrap_or_default();
+        let (commission, commission_asset) = Self::sum_commission(&fills)?;
+        
         Ok(OrderInfo {
             order_id,
             symbol: symbol.to_string(),
             price,
             qty,
+            executed_qty,
+            avg_price,
+            commission,
+            commission_asset,
             side,
             status,
             timestamp: Utc::now(),
@@ -312,6 +766,12 @@ impl ExchangeApi for BinanceApi {
             Decimal::ZERO
         };
         
+        let executed_qty = if let Some(q) = response["executedQty"].as_str() {
+            q.parse::<Decimal>()?
+        } else {
+            Decimal::ZERO
+        };
+        
         let status_str = response["status"].as_str().unwrap_or("NEW");
         let status = match status_str {
             "NEW" => OrderStatus::New,
@@ -323,11 +783,23 @@ impl ExchangeApi for BinanceApi {
             _ => OrderStatus::New,
         };
         
+        // 订单结束后查询成交明细获取手续费，未结束的订单不查询以减少请求
+        let avg_price = Self::parse_avg_price(&response, executed_qty)?;
+        let (commission, commission_asset) = if executed_qty > Decimal::ZERO && status != OrderStatus::New && status != OrderStatus::PartiallyFilled {
+            self.get_order_commission(symbol, order_id).await?
+        } else {
+            (Decimal::ZERO, String::new())
+        };
+        
         Ok(OrderInfo {
             order_id,
             symbol: symbol.to_string(),
             price,
             qty,
+            executed_qty,
+            avg_price,
+            commission,
+            commission_asset,
             side,
             status,
             timestamp: Utc::now(),
@@ -360,17 +832,74 @@ impl ExchangeApi for BinanceApi {
             Decimal::ZERO
         };
         
+        let executed_qty = if let Some(q) = response["executedQty"].as_str() {
+            q.parse::<Decimal>()?
+        } else {
+            Decimal::ZERO
+        };
+        
+        let avg_price = Self::parse_avg_price(&response, executed_qty)?;
+        
         Ok(OrderInfo {
             order_id,
             symbol: symbol.to_string(),
             price,
             qty,
+            executed_qty,
+            avg_price,
+            commission: Decimal::ZERO,
+            commission_asset: String::new(),
             side,
             status: OrderStatus::Cancelled,
             timestamp: Utc::now(),
         })
     }
     
+    async fn get_open_orders(&self, symbol: &str) -> Result<Vec<OrderInfo>> {
+        let mut params = HashMap::new();
+        params.insert("symbol".to_string(), symbol.to_string());
+        
+        let response = self.send_signed_request("/api/v3/openOrders", "GET", params).await?;
+        let orders = response.as_array().context("Orders not found in response")?;
+        
+        let mut open_orders = Vec::with_capacity(orders.len());
+        for order in orders {
+            let order_id = order["orderId"].as_u64().context("Order ID not found in response")?;
+            let side = match order["side"].as_str().unwrap_or("BUY") {
+                "SELL" => Side::Sell,
+                _ => Side::Buy,
+            };
+            let price = order["price"].as_str().unwrap_or("0").parse::<Decimal>()?;
+            let qty = order["origQty"].as_str().unwrap_or("0").parse::<Decimal>()?;
+            let executed_qty = order["executedQty"].as_str().unwrap_or("0").parse::<Decimal>()?;
+            let status = match order["status"].as_str().unwrap_or("NEW") {
+                "PARTIALLY_FILLED" => OrderStatus::PartiallyFilled,
+                _ => OrderStatus::New,
+            };
+            let avg_price = Self::parse_avg_price(order, executed_qty)?;
+            // 下单时间，用于判断订单是否过期未处理
+            let timestamp = order["time"].as_i64()
+                .and_then(DateTime::from_timestamp_millis)
+                .unwrap_or_else(Utc::now);
+            
+            open_orders.push(OrderInfo {
+                order_id,
+                symbol: symbol.to_string(),
+                price,
+                qty,
+                executed_qty,
+                avg_price,
+                commission: Decimal::ZERO,
+                commission_asset: String::new(),
+                side,
+                status,
+                timestamp,
+            });
+        }
+        
+        Ok(open_orders)
+    }
+    
     async fn get_account_balance(&self, asset: &str) -> Result<Decimal> {
         let params = HashMap::new();
         
@@ -387,4 +916,328 @@ impl ExchangeApi for BinanceApi {
         
         Err(anyhow!("Balance not found for asset: {}", asset))
     }
+    
+    async fn get_account_balances(&self) -> Result<Vec<AssetBalance>> {
+        let response = self.send_signed_request("/api/v3/account", "GET", HashMap::new()).await?;
+        Self::parse_account_balances(&response)
+    }
+    
+    async fn get_system_status(&self) -> Result<SystemStatus> {
+        let response = self.send_public_request("/sapi/v1/system/status", None).await?;
+        
+        // status: 0 正常, 1 系统维护
+        let status = response["status"].as_u64().context("Status not found in response")?;
+        let message = response["msg"].as_str().unwrap_or_default().to_string();
+        
+        Ok(SystemStatus {
+            maintenance: status != 0,
+            message,
+        })
+    }
+    
+    async fn get_server_time(&self) -> Result<DateTime<Utc>> {
+        let response = self.send_public_request("/api/v3/time", None).await?;
+        
+        response["serverTime"].as_i64()
+            .and_then(DateTime::from_timestamp_millis)
+            .context("Server time not found in response")
+    }
+    
+    async fn create_listen_key(&self) -> Result<String> {
+        let response = self.send_api_key_request("/api/v3/userDataStream", "POST", HashMap::new()).await?;
+        let listen_key = response["listenKey"].as_str().context("listenKey not found in response")?;
+        Ok(listen_key.to_string())
+    }
+    
+    async fn keepalive_listen_key(&self, listen_key: &str) -> Result<()> {
+        let mut params = HashMap::new();
+        params.insert("listenKey".to_string(), listen_key.to_string());
+        self.send_api_key_request("/api/v3/userDataStream", "PUT", params).await?;
+        Ok(())
+    }
+    
+    async fn subscribe_user_data(&self, listen_key: &str) -> Result<UserDataStream> {
+        let (socket, _) = tokio_tungstenite::connect_async(self.user_data_stream_url(listen_key))
+            .await
+            .context("连接用户数据流失败")?;
+        debug!("已连接用户数据流");
+        
+        // 只处理文本消息，Ping/Pong由WebSocket库自动应答，服务端关闭连接时流结束
+        let stream = socket
+            .take_while(|message| futures::future::ready(!matches!(message, Ok(Message::Close(_)))))
+            .filter_map(|message| async move {
+                match message {
+                    Ok(Message::Text(text)) => Some(Self::parse_user_data_event(&text)),
+                    Ok(_) => None,
+                    Err(e) => Some(Err(anyhow!("用户数据流出错: {}", e))),
+                }
+            });
+        Ok(stream.boxed())
+    }
+    
+    async fn convert_dust(&self, asset: &str) -> Result<DustConversion> {
+        let mut params = HashMap::new();
+        params.insert("asset".to_string(), asset.to_string());
+        
+        let response = self.send_signed_request("/sapi/v1/asset/dust", "POST", params).await?;
+        Self::parse_dust_conversion(asset, &response)
+    }
+    
+    async fn transfer_to_sub_account(&self, to_email: &str, asset: &str, amount: Decimal) -> Result<SubAccountTransfer> {
+        let mut params = HashMap::new();
+        params.insert("toEmail".to_string(), to_email.to_string());
+        params.insert("asset".to_string(), asset.to_string());
+        params.insert("amount".to_string(), amount.normalize().to_string());
+        
+        let response = self.send_signed_request("/sapi/v1/sub-account/transfer", "POST", params).await?;
+        Self::parse_sub_account_transfer(to_email, asset, amount, &response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_request_trace_masks_secrets() {
+        let api_key = "vmPUZE6mv9SD5VNHk4HlWFsOr6aKE2zvsw0MuIgwCIPy6utIco14y7Ju91duEh8A";
+        let signature = "c8db56825ae71d6d79447849e617115f4a920fa2acdcab2b053c4b2838bd6b71";
+
+        let request = Client::new()
+            .get("https://api.binance.com/api/v3/order")
+            .query(&[("symbol", "BTCUSDT"), ("orderId", "42"), ("signature", signature)])
+            .header("X-MBX-APIKEY", api_key)
+            .build()
+            .unwrap();
+
+        let trace = BinanceApi::format_request_trace(&request);
+        assert!(!trace.contains(signature));
+        assert!(!trace.contains(api_key));
+        assert!(trace.contains("signature=***"));
+        assert!(trace.contains("vmPU****"));
+        assert!(trace.contains("symbol=BTCUSDT"));
+        assert!(trace.contains("orderId=42"));
+        assert!(trace.starts_with("GET https://api.binance.com/api/v3/order?"));
+    }
+
+    #[test]
+    fn test_parse_ticker_prices() {
+        let response: serde_json::Value = serde_json::from_str(
+            r#"[{"symbol":"BTCUSDT","price":"50000.10"},{"symbol":"BTCUSDC","price":"49990.00000000"}]"#
+        ).unwrap();
+
+        let prices = BinanceApi::parse_ticker_prices(&response).unwrap();
+        assert_eq!(prices.len(), 2);
+        assert_eq!(prices[0].symbol, "BTCUSDT");
+        assert_eq!(prices[0].price, "50000.10".parse::<Decimal>().unwrap());
+        assert_eq!(prices[1].symbol, "BTCUSDC");
+        assert_eq!(prices[1].price, "49990".parse::<Decimal>().unwrap());
+
+        let empty: serde_json::Value = serde_json::from_str("[]").unwrap();
+        assert!(BinanceApi::parse_ticker_prices(&empty).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_parse_ticker_prices_rejects_malformed_response() {
+        // 单个交易对的响应是对象而非数组
+        let single: serde_json::Value = serde_json::from_str(r#"{"symbol":"BTCUSDT","price":"50000"}"#).unwrap();
+        assert!(BinanceApi::parse_ticker_prices(&single).is_err());
+
+        let missing_price: serde_json::Value = serde_json::from_str(r#"[{"symbol":"BTCUSDT"}]"#).unwrap();
+        assert!(BinanceApi::parse_ticker_prices(&missing_price).is_err());
+
+        let bad_price: serde_json::Value = serde_json::from_str(r#"[{"symbol":"BTCUSDT","price":"abc"}]"#).unwrap();
+        assert!(BinanceApi::parse_ticker_prices(&bad_price).is_err());
+    }
+
+    #[test]
+    fn test_parse_execution_report() {
+        let message = r#"{"e":"executionReport","E":1700000000100,"s":"BTCUSDT","c":"abc","S":"BUY","o":"LIMIT","f":"GTC",
+            "q":"0.002","p":"50000","X":"FILLED","i":42,"l":"0.001","z":"0.002","L":"50010","n":"0.000001","N":"BTC",
+            "T":1700000000090,"O":1700000000000,"Z":"100.01"}"#;
+
+        let UserDataEvent::ExecutionReport(order) = BinanceApi::parse_user_data_event(message).unwrap() else {
+            panic!("应解析为订单状态更新");
+        };
+        assert_eq!(order.order_id, 42);
+        assert_eq!(order.symbol, "BTCUSDT");
+        assert_eq!(order.side, Side::Buy);
+        assert_eq!(order.status, OrderStatus::Filled);
+        assert_eq!(order.executed_qty, "0.002".parse::<Decimal>().unwrap());
+        assert_eq!(order.avg_price, "50005".parse::<Decimal>().unwrap());
+        assert_eq!(order.commission_asset, "BTC");
+        assert_eq!(order.timestamp.timestamp_millis(), 1700000000000);
+
+        let balance = r#"{"e":"outboundAccountPosition","E":1700000000100,"B":[]}"#;
+        assert!(matches!(
+            BinanceApi::parse_user_data_event(balance).unwrap(),
+            UserDataEvent::Other(event) if event == "outboundAccountPosition"
+        ));
+        assert!(BinanceApi::parse_user_data_event("not json").is_err());
+    }
+
+    #[test]
+    fn test_parse_account_balances() {
+        let response = serde_json::json!({
+            "makerCommission": 10,
+            "balances": [
+                {"asset": "BTC", "free": "0.50000000", "locked": "0.01000000"},
+                {"asset": "USDT", "free": "1200.5", "locked": "0.00000000"}
+            ]
+        });
+
+        let balances = BinanceApi::parse_account_balances(&response).unwrap();
+        assert_eq!(balances.len(), 2);
+        assert_eq!(balances[0].asset, "BTC");
+        assert_eq!(balances[0].free, "0.5".parse::<Decimal>().unwrap());
+        assert_eq!(balances[0].locked, "0.01".parse::<Decimal>().unwrap());
+        assert_eq!(balances[0].total(), "0.51".parse::<Decimal>().unwrap());
+        assert_eq!(balances[1].free, "1200.5".parse::<Decimal>().unwrap());
+
+        assert!(BinanceApi::parse_account_balances(&serde_json::json!({})).is_err());
+        let bad = serde_json::json!({"balances": [{"asset": "BTC", "free": "abc", "locked": "0"}]});
+        assert!(BinanceApi::parse_account_balances(&bad).is_err());
+    }
+
+    #[test]
+    fn test_parse_dust_conversion() {
+        let response = serde_json::json!({
+            "totalServiceCharge": "0.00002",
+            "totalTransfered": "0.00098",
+            "transferResult": [{
+                "amount": "0.00003",
+                "fromAsset": "BTC",
+                "operateTime": 1700000000000u64,
+                "serviceChargeAmount": "0.00002",
+                "tranId": 2970932918u64,
+                "transferedAmount": "0.00098"
+            }]
+        });
+
+        let conversion = BinanceApi::parse_dust_conversion("BTC", &response).unwrap();
+        assert_eq!(conversion.asset, "BTC");
+        assert_eq!(conversion.amount, "0.00003".parse::<Decimal>().unwrap());
+        assert_eq!(conversion.transferred, "0.00098".parse::<Decimal>().unwrap());
+        assert_eq!(conversion.service_charge, "0.00002".parse::<Decimal>().unwrap());
+        assert!(BinanceApi::parse_dust_conversion("ETH", &response).is_err());
+    }
+
+    #[test]
+    fn test_parse_sub_account_transfer() {
+        let amount = "12.5".parse::<Decimal>().unwrap();
+        let response = serde_json::json!({"success": true, "txnId": "2966662589"});
+        let transfer = BinanceApi::parse_sub_account_transfer("savings@example.com", "USDT", amount, &response).unwrap();
+        assert_eq!(transfer.txn_id, "2966662589");
+        assert_eq!(transfer.to_email, "savings@example.com");
+        assert_eq!(transfer.asset, "USDT");
+        assert_eq!(transfer.amount, amount);
+
+        let numeric = serde_json::json!({"txnId": 2966662589u64});
+        assert_eq!(BinanceApi::parse_sub_account_transfer("savings@example.com", "USDT", amount, &numeric).unwrap().txn_id, "2966662589");
+
+        let failed = serde_json::json!({"success": false, "txnId": "1"});
+        assert!(BinanceApi::parse_sub_account_transfer("savings@example.com", "USDT", amount, &failed).is_err());
+        assert!(BinanceApi::parse_sub_account_transfer("savings@example.com", "USDT", amount, &serde_json::json!({})).is_err());
+    }
+
+    /// 在本地端口上返回固定响应的HTTP服务，返回服务地址和收到的请求数
+    async fn serve_fixed_response(status: &'static str, body: &'static str) -> (String, Arc<AtomicUsize>) {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(AtomicUsize::new(0));
+        let counter = requests.clone();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                counter.fetch_add(1, Ordering::SeqCst);
+                let mut buffer = [0u8; 4096];
+                let _ = stream.read(&mut buffer).await;
+                let response = format!(
+                    "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status, body.len(), body
+                );
+                let _ = stream.write_all(response.as_bytes()).await;
+            }
+        });
+        (address, requests)
+    }
+    
+    #[tokio::test]
+    async fn test_fails_over_to_fallback_base_url() {
+        let (primary, primary_requests) = serve_fixed_response("503 Service Unavailable", r#"{"code":-1001,"msg":"unavailable"}"#).await;
+        let (fallback, fallback_requests) = serve_fixed_response("200 OK", r#"{"symbol":"BTCUSDT","price":"50000.00"}"#).await;
+        let api = BinanceApi::new(Config {
+            base_url: primary.clone(),
+            fallback_base_urls: vec![fallback.clone()],
+            ..Config::default()
+        });
+        
+        // 未达到连续失败次数前继续使用主地址
+        for _ in 0..FAILOVER_THRESHOLD {
+            assert_eq!(api.base_url(), primary);
+            assert!(api.get_price("BTCUSDT").await.is_err());
+        }
+        assert_eq!(primary_requests.load(Ordering::SeqCst), FAILOVER_THRESHOLD as usize);
+        
+        assert_eq!(api.base_url(), fallback);
+        let price = api.get_price("BTCUSDT").await.unwrap();
+        assert_eq!(price.price, "50000".parse::<Decimal>().unwrap());
+        assert_eq!(fallback_requests.load(Ordering::SeqCst), 1);
+        assert_eq!(primary_requests.load(Ordering::SeqCst), FAILOVER_THRESHOLD as usize);
+    }
+    
+    #[tokio::test]
+    async fn test_rejected_requests_do_not_fail_over() {
+        let (primary, _) = serve_fixed_response("400 Bad Request", r#"{"code":-1121,"msg":"Invalid symbol."}"#).await;
+        let (fallback, fallback_requests) = serve_fixed_response("200 OK", r#"{"symbol":"BTCUSDT","price":"50000.00"}"#).await;
+        let api = BinanceApi::new(Config {
+            base_url: primary.clone(),
+            fallback_base_urls: vec![fallback],
+            ..Config::default()
+        });
+        
+        for _ in 0..FAILOVER_THRESHOLD + 1 {
+            assert!(api.get_price("NOSUCH").await.is_err());
+        }
+        assert_eq!(api.base_url(), primary);
+        assert_eq!(fallback_requests.load(Ordering::SeqCst), 0);
+    }
+    
+    #[tokio::test]
+    async fn test_api_error_logged_with_fields() {
+        use crate::logging::{CapturedLogs, LogFormat};
+        
+        let (primary, _) = serve_fixed_response("400 Bad Request", r#"{"code":-1121,"msg":"Invalid symbol."}"#).await;
+        let api = BinanceApi::new(Config { base_url: primary, ..Config::default() });
+        let logs = CapturedLogs::default();
+        let _guard = tracing::subscriber::set_default(logs.subscriber(LogFormat::Json));
+        
+        assert!(api.get_price("NOSUCH").await.is_err());
+        let events = logs.json_events();
+        let event = events.iter().find(|event| event["path"] == "/api/v3/ticker/price").expect("应记录API错误");
+        assert_eq!(event["level"], "WARN");
+        assert_eq!(event["status"], 400);
+        assert_eq!(event["code"], -1121);
+        assert_eq!(event["reason"], "Invalid symbol.");
+    }
+    
+    #[tokio::test]
+    async fn test_without_fallback_keeps_primary_base_url() {
+        let (primary, primary_requests) = serve_fixed_response("500 Internal Server Error", "{}").await;
+        let api = BinanceApi::new(Config { base_url: primary.clone(), ..Config::default() });
+        
+        for _ in 0..FAILOVER_THRESHOLD * 2 {
+            assert!(api.get_price("BTCUSDT").await.is_err());
+        }
+        assert_eq!(api.base_url(), primary);
+        assert_eq!(primary_requests.load(Ordering::SeqCst), FAILOVER_THRESHOLD as usize * 2);
+    }
+
+    #[test]
+    fn test_mask_short_secret() {
+        assert_eq!(BinanceApi::mask_secret(""), "****");
+        assert_eq!(BinanceApi::mask_secret("abcd1234"), "****");
+    }
 }
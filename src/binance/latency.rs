@@ -0,0 +1,298 @@
+//! 交易所API调用耗时统计
+//!
+//! `LatencyTrackedApi` 包装任意 `ExchangeApi`，记录每个方法最近若干次调用的往返耗时，
+//! 单次耗时超过阈值时输出警告。关闭统计时各方法直接转发给内部API，不做计时。
+
+use crate::binance::{ExchangeApi, UserDataStream};
+use crate::config::ApiLatencySettings;
+use crate::models::{AssetBalance, DustConversion, OrderBook, OrderInfo, Price, Side, SubAccountTransfer, Symbol, SystemStatus, TimeInForce};
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use log::warn;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// 一个API方法的耗时统计
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ApiLatencyStats {
+    /// 方法名，例如 `get_price`
+    pub method: String,
+    /// 累计调用次数，包括失败的调用
+    pub calls: u64,
+    /// 窗口内的平均耗时（毫秒）
+    pub avg_ms: f64,
+    /// 窗口内耗时的95分位数（毫秒，最近秩法）
+    pub p95_ms: f64,
+    /// 最近一次调用的耗时（毫秒）
+    pub last_ms: f64,
+}
+
+/// 单个方法的耗时窗口
+#[derive(Debug, Default)]
+struct MethodLatency {
+    calls: u64,
+    window: VecDeque<Duration>,
+}
+
+/// 按API方法记录最近若干次调用的耗时，可以在多个线程间共享
+#[derive(Debug)]
+pub struct LatencyTracker {
+    window_size: usize,
+    warn_threshold: Option<Duration>,
+    methods: Mutex<BTreeMap<&'static str, MethodLatency>>,
+}
+
+impl LatencyTracker {
+    pub fn new(settings: &ApiLatencySettings) -> Self {
+        Self {
+            window_size: settings.window_size.max(1),
+            warn_threshold: (settings.warn_threshold_ms > 0).then(|| Duration::from_millis(settings.warn_threshold_ms)),
+            methods: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// 记录一次调用的耗时，超过阈值时输出警告
+    pub fn record(&self, method: &'static str, elapsed: Duration) {
+        if let Some(threshold) = self.warn_threshold {
+            if elapsed > threshold {
+                warn!(
+                    "API调用 {} 耗时 {} ms，超过阈值 {} ms，可能错过套利机会",
+                    method,
+                    elapsed.as_millis(),
+                    threshold.as_millis()
+                );
+            }
+        }
+
+        let mut methods = self.methods.lock().unwrap();
+        let latency = methods.entry(method).or_default();
+        latency.calls += 1;
+        if latency.window.len() == self.window_size {
+            latency.window.pop_front();
+        }
+        latency.window.push_back(elapsed);
+    }
+
+    /// 各方法的耗时统计，按方法名排序
+    pub fn stats(&self) -> Vec<ApiLatencyStats> {
+        let methods = self.methods.lock().unwrap();
+        methods
+            .iter()
+            .filter(|(_, latency)| !latency.window.is_empty())
+            .map(|(method, latency)| {
+                let mut sorted: Vec<Duration> = latency.window.iter().copied().collect();
+                sorted.sort_unstable();
+                let total: Duration = sorted.iter().sum();
+                let rank = (95 * sorted.len()).div_ceil(100).max(1);
+                ApiLatencyStats {
+                    method: method.to_string(),
+                    calls: latency.calls,
+                    avg_ms: as_millis(total) / sorted.len() as f64,
+                    p95_ms: as_millis(sorted[rank - 1]),
+                    last_ms: as_millis(*latency.window.back().unwrap()),
+                }
+            })
+            .collect()
+    }
+}
+
+fn as_millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+/// 记录每次调用耗时的 `ExchangeApi` 包装
+pub struct LatencyTrackedApi<T> {
+    inner: T,
+    /// 关闭统计时为None
+    tracker: Option<Arc<LatencyTracker>>,
+}
+
+impl<T: ExchangeApi + Send + Sync> LatencyTrackedApi<T> {
+    /// 按设置包装API，`settings.enabled` 为false时不记录耗时
+    pub fn new(inner: T, settings: &ApiLatencySettings) -> Self {
+        Self {
+            inner,
+            tracker: settings.enabled.then(|| Arc::new(LatencyTracker::new(settings))),
+        }
+    }
+
+    /// 内部的API
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    /// 共享的耗时记录，关闭统计时为None，可在API交给引擎后继续查询
+    pub fn tracker(&self) -> Option<Arc<LatencyTracker>> {
+        self.tracker.clone()
+    }
+
+    /// 各方法的耗时统计，关闭统计时为空
+    pub fn latency_stats(&self) -> Vec<ApiLatencyStats> {
+        self.tracker.as_ref().map(|tracker| tracker.stats()).unwrap_or_default()
+    }
+
+    async fn timed<R>(&self, method: &'static str, call: impl Future<Output = R>) -> R {
+        match &self.tracker {
+            Some(tracker) => {
+                let started = Instant::now();
+                let result = call.await;
+                tracker.record(method, started.elapsed());
+                result
+            },
+            None => call.await,
+        }
+    }
+}
+
+#[async_trait]
+impl<T: ExchangeApi + Send + Sync> ExchangeApi for LatencyTrackedApi<T> {
+    async fn get_symbol_info(&self, symbol: &str) -> Result<Symbol> {
+        self.timed("get_symbol_info", self.inner.get_symbol_info(symbol)).await
+    }
+
+    async fn get_price(&self, symbol: &str) -> Result<Price> {
+        self.timed("get_price", self.inner.get_price(symbol)).await
+    }
+
+    async fn get_prices(&self, symbols: &[String]) -> Result<Vec<Price>> {
+        self.timed("get_prices", self.inner.get_prices(symbols)).await
+    }
+
+    async fn get_order_book(&self, symbol: &str, limit: Option<u32>) -> Result<OrderBook> {
+        self.timed("get_order_book", self.inner.get_order_book(symbol, limit)).await
+    }
+
+    async fn get_last_trade_price(&self, symbol: &str) -> Result<Price> {
+        self.timed("get_last_trade_price", self.inner.get_last_trade_price(symbol)).await
+    }
+
+    async fn place_order(&self, symbol: &str, side: Side, quantity: Decimal, price: Option<Decimal>, time_in_force: TimeInForce, client_order_id: Option<&str>) -> Result<OrderInfo> {
+        self.timed("place_order", self.inner.place_order(symbol, side, quantity, price, time_in_force, client_order_id)).await
+    }
+
+    async fn get_order_status(&self, symbol: &str, order_id: u64) -> Result<OrderInfo> {
+        self.timed("get_order_status", self.inner.get_order_status(symbol, order_id)).await
+    }
+
+    async fn cancel_order(&self, symbol: &str, order_id: u64) -> Result<OrderInfo> {
+        self.timed("cancel_order", self.inner.cancel_order(symbol, order_id)).await
+    }
+
+    async fn get_open_orders(&self, symbol: &str) -> Result<Vec<OrderInfo>> {
+        self.timed("get_open_orders", self.inner.get_open_orders(symbol)).await
+    }
+
+    async fn get_account_balance(&self, asset: &str) -> Result<Decimal> {
+        self.timed("get_account_balance", self.inner.get_account_balance(asset)).await
+    }
+
+    async fn get_account_balances(&self) -> Result<Vec<AssetBalance>> {
+        self.timed("get_account_balances", self.inner.get_account_balances()).await
+    }
+
+    async fn get_system_status(&self) -> Result<SystemStatus> {
+        self.timed("get_system_status", self.inner.get_system_status()).await
+    }
+
+    async fn get_server_time(&self) -> Result<DateTime<Utc>> {
+        self.timed("get_server_time", self.inner.get_server_time()).await
+    }
+
+    async fn create_listen_key(&self) -> Result<String> {
+        self.timed("create_listen_key", self.inner.create_listen_key()).await
+    }
+
+    async fn keepalive_listen_key(&self, listen_key: &str) -> Result<()> {
+        self.timed("keepalive_listen_key", self.inner.keepalive_listen_key(listen_key)).await
+    }
+
+    /// 只统计建立连接的耗时
+    async fn subscribe_user_data(&self, listen_key: &str) -> Result<UserDataStream> {
+        self.timed("subscribe_user_data", self.inner.subscribe_user_data(listen_key)).await
+    }
+
+    async fn convert_dust(&self, asset: &str) -> Result<DustConversion> {
+        self.timed("convert_dust", self.inner.convert_dust(asset)).await
+    }
+
+    async fn transfer_to_sub_account(&self, to_email: &str, asset: &str, amount: Decimal) -> Result<SubAccountTransfer> {
+        self.timed("transfer_to_sub_account", self.inner.transfer_to_sub_account(to_email, asset, amount)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::binance::MockBinanceApi;
+
+    fn slow_api(delay_ms: u64) -> MockBinanceApi {
+        let api = MockBinanceApi::new();
+        api.set_price_delay(Duration::from_millis(delay_ms));
+        api
+    }
+
+    fn settings(enabled: bool, window_size: usize) -> ApiLatencySettings {
+        ApiLatencySettings { enabled, window_size, warn_threshold_ms: 20 }
+    }
+
+    #[tokio::test]
+    async fn test_recorded_latency_reflects_delay() {
+        let api = LatencyTrackedApi::new(slow_api(30), &settings(true, 4));
+        for _ in 0..3 {
+            api.get_price("BTCUSDT").await.unwrap();
+        }
+        api.get_account_balance("USDT").await.unwrap();
+
+        let stats = api.latency_stats();
+        assert_eq!(stats.len(), 2);
+        assert_eq!(stats[0].method, "get_account_balance");
+        assert!(stats[0].p95_ms < 30.0);
+
+        let price = &stats[1];
+        assert_eq!(price.method, "get_price");
+        assert_eq!(price.calls, 3);
+        assert!(price.avg_ms >= 30.0);
+        assert!(price.p95_ms >= 30.0);
+        // 默认参考价格来源经由 get_price，同样被统计
+        api.get_reference_price("BTCUSDT", crate::models::PriceSource::Ticker).await.unwrap();
+        assert_eq!(api.latency_stats()[1].calls, 4);
+
+        // 窗口只保留最近4次，变慢后P95随之上升，累计次数不受窗口限制
+        api.inner().set_price_delay(Duration::from_millis(80));
+        let started = Instant::now();
+        for _ in 0..4 {
+            api.get_price("BTCUSDT").await.unwrap();
+        }
+        let elapsed_ms = as_millis(started.elapsed());
+        let price = api.tracker().unwrap().stats().remove(1);
+        assert_eq!(price.calls, 8);
+        assert!(price.avg_ms >= 80.0);
+        assert!(price.last_ms >= 80.0);
+        assert!(price.avg_ms * 4.0 <= elapsed_ms);
+    }
+
+    #[tokio::test]
+    async fn test_disabled_tracking_records_nothing() {
+        let api = LatencyTrackedApi::new(slow_api(5), &settings(false, 4));
+        api.get_price("BTCUSDT").await.unwrap();
+        assert!(api.tracker().is_none());
+        assert!(api.latency_stats().is_empty());
+    }
+
+    #[test]
+    fn test_p95_over_window() {
+        let tracker = LatencyTracker::new(&settings(true, 20));
+        for ms in 1..=20 {
+            tracker.record("get_price", Duration::from_millis(ms));
+        }
+        let stats = tracker.stats();
+        assert_eq!(stats[0].p95_ms, 19.0);
+        assert_eq!(stats[0].avg_ms, 10.5);
+        assert_eq!(stats[0].last_ms, 20.0);
+    }
+}
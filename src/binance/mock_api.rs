@@ -1,21 +1,51 @@
-use crate::binance::ExchangeApi;
-use crate::models::{OrderBook, Price, QuoteCurrency, Side, Symbol, OrderInfo, OrderStatus};
+use crate::binance::{ExchangeApi, UserDataStream};
+use crate::models::{AssetBalance, DustConversion, OrderBook, Price, QuoteCurrency, Side, SubAccountTransfer, Symbol, OrderInfo, OrderStatus, SystemStatus, TimeInForce, UserDataEvent};
 use anyhow::{anyhow, Context, Result};
 use async_trait::async_trait;
-use chrono::Utc;
+use chrono::{DateTime, Utc};
+use futures::stream::{self, StreamExt};
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
 use std::sync::{Arc, Mutex};
 use std::collections::HashMap;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use log::{debug, info, warn};
+use tokio::sync::mpsc;
+
+/// 模拟的小额资产兑换手续费率，与币安收取的2%一致
+const DUST_SERVICE_CHARGE_RATE: Decimal = dec!(0.02);
 
 /// 模拟币安API，用于测试和开发
+///
+/// 克隆后的实例共享同一份模拟状态
+#[derive(Clone)]
 pub struct MockBinanceApi {
     prices: Arc<Mutex<HashMap<String, Decimal>>>,
     balances: Arc<Mutex<HashMap<String, Decimal>>>,
     orders: Arc<Mutex<HashMap<u64, OrderInfo>>>,
     next_order_id: Arc<Mutex<u64>>,
+    /// 手动设置的订单簿，设置后限价IOC/FOK订单按其深度撮合
+    order_books: Arc<Mutex<HashMap<String, OrderBook>>>,
+    /// 模拟的交易所系统状态
+    system_status: Arc<Mutex<SystemStatus>>,
+    /// 模拟的吃单手续费率，买入从获得的基础资产中扣除，卖出从获得的报价资产中扣除
+    commission_rate: Arc<Mutex<Decimal>>,
+    /// 各交易对最近一笔成交价格，未成交过的交易对以当前价格代替
+    last_trades: Arc<Mutex<HashMap<String, Decimal>>>,
+    /// 设置后新订单保持未成交状态，用于模拟长时间挂单
+    hold_orders: Arc<Mutex<bool>>,
+    /// 用户数据流的订阅者，订单状态变化时推送执行报告
+    user_data_subscribers: Arc<Mutex<Vec<mpsc::UnboundedSender<Result<UserDataEvent>>>>>,
+    /// 下单时提交的 `newClientOrderId`，按订单ID记录
+    client_order_ids: Arc<Mutex<HashMap<u64, String>>>,
+    /// 已完成的子账户划转，按划转顺序
+    sub_account_transfers: Arc<Mutex<Vec<SubAccountTransfer>>>,
+    /// 模拟的服务器时间与本机时间之差
+    server_time_offset: Arc<Mutex<chrono::Duration>>,
+    /// 设置后查询账户余额的签名请求按API密钥无效被拒绝
+    invalid_api_key: Arc<Mutex<bool>>,
+    /// 查询价格前等待的时间，用于模拟响应缓慢的接口
+    price_delay: Arc<Mutex<Duration>>,
 }
 
 impl MockBinanceApi {
@@ -26,6 +56,8 @@ impl MockBinanceApi {
         prices.insert("BTCUSDC".to_string(), dec!(50025.00));
         prices.insert("ETHUSDT".to_string(), dec!(3000.00));
         prices.insert("ETHUSDC".to_string(), dec!(3002.50));
+        // 小额资产按USDT价格折算为BNB
+        prices.insert("BNBUSDT".to_string(), dec!(300.00));
         
         let mut balances = HashMap::new();
         // 设置初始余额
@@ -39,6 +71,17 @@ impl MockBinanceApi {
             balances: Arc::new(Mutex::new(balances)),
             orders: Arc::new(Mutex::new(HashMap::new())),
             next_order_id: Arc::new(Mutex::new(1)),
+            order_books: Arc::new(Mutex::new(HashMap::new())),
+            system_status: Arc::new(Mutex::new(SystemStatus::normal())),
+            commission_rate: Arc::new(Mutex::new(Decimal::ZERO)),
+            last_trades: Arc::new(Mutex::new(HashMap::new())),
+            hold_orders: Arc::new(Mutex::new(false)),
+            user_data_subscribers: Arc::new(Mutex::new(Vec::new())),
+            client_order_ids: Arc::new(Mutex::new(HashMap::new())),
+            sub_account_transfers: Arc::new(Mutex::new(Vec::new())),
+            server_time_offset: Arc::new(Mutex::new(chrono::Duration::zero())),
+            invalid_api_key: Arc::new(Mutex::new(false)),
+            price_delay: Arc::new(Mutex::new(Duration::ZERO)),
         }
     }
     
@@ -48,6 +91,161 @@ impl MockBinanceApi {
         prices.insert(symbol.to_string(), price);
     }
     
+    /// 设置模拟订单簿，用于模拟有限深度的市场
+    pub fn set_order_book(&self, symbol: &str, bids: Vec<(Decimal, Decimal)>, asks: Vec<(Decimal, Decimal)>) {
+        let mut order_books = self.order_books.lock().unwrap();
+        order_books.insert(symbol.to_string(), OrderBook {
+            symbol: symbol.to_string(),
+            bids,
+            asks,
+            timestamp: Utc::now(),
+        });
+    }
+    
+    /// 设置模拟的交易所系统状态，用于模拟系统维护
+    pub fn set_system_status(&self, status: SystemStatus) {
+        *self.system_status.lock().unwrap() = status;
+    }
+    
+    /// 设置模拟的手续费率（小数形式，0.001 表示 0.1%），默认不收手续费
+    pub fn set_commission_rate(&self, rate: Decimal) {
+        *self.commission_rate.lock().unwrap() = rate;
+    }
+    
+    /// 设置模拟的最近成交价格
+    pub fn set_last_trade_price(&self, symbol: &str, price: Decimal) {
+        self.last_trades.lock().unwrap().insert(symbol.to_string(), price);
+    }
+    
+    /// 订单下单时提交的 `newClientOrderId`，未提交时为None
+    pub fn client_order_id(&self, order_id: u64) -> Option<String> {
+        self.client_order_ids.lock().unwrap().get(&order_id).cloned()
+    }
+    
+    /// 已完成的子账户划转
+    pub fn sub_account_transfers(&self) -> Vec<SubAccountTransfer> {
+        self.sub_account_transfers.lock().unwrap().clone()
+    }
+    
+    /// 设置服务器时间与本机时间之差，正数表示服务器时间比本机快
+    pub fn set_server_time_offset(&self, offset: chrono::Duration) {
+        *self.server_time_offset.lock().unwrap() = offset;
+    }
+    
+    /// 设置API密钥是否无效，无效时查询账户余额返回与币安相同的错误
+    pub fn set_invalid_api_key(&self, invalid: bool) {
+        *self.invalid_api_key.lock().unwrap() = invalid;
+    }
+    
+    /// 设置查询价格前等待的时间，为0时立即返回
+    pub fn set_price_delay(&self, delay: Duration) {
+        *self.price_delay.lock().unwrap() = delay;
+    }
+    
+    /// API密钥无效时返回签名请求的错误
+    fn check_api_key(&self) -> Result<()> {
+        if *self.invalid_api_key.lock().unwrap() {
+            return Err(anyhow!(r#"API error: {{"code":-2015,"msg":"Invalid API-key, IP, or permissions for action."}}"#));
+        }
+        Ok(())
+    }
+    
+    /// 设置模拟账户中指定币种的余额
+    pub fn set_balance(&self, asset: &str, amount: Decimal) {
+        self.balances.lock().unwrap().insert(asset.to_string(), amount);
+    }
+    
+    /// 设置新订单是否保持未成交（New）状态，不改变余额，直到被取消
+    pub fn set_hold_orders(&self, hold: bool) {
+        *self.hold_orders.lock().unwrap() = hold;
+    }
+    
+    /// 修改模拟订单的下单时间，用于模拟长时间未处理的订单
+    pub fn set_order_time(&self, order_id: u64, timestamp: DateTime<Utc>) {
+        if let Some(order) = self.orders.lock().unwrap().get_mut(&order_id) {
+            order.timestamp = timestamp;
+        }
+    }
+    
+    /// 按订单价格全部成交一个未结束的订单，更新余额并向用户数据流推送执行报告
+    pub fn fill_order(&self, order_id: u64) -> Result<OrderInfo> {
+        let mut orders = self.orders.lock().unwrap();
+        let order = orders.get_mut(&order_id).ok_or_else(|| anyhow!("订单不存在: {}", order_id))?;
+        if !matches!(order.status, OrderStatus::New | OrderStatus::PartiallyFilled) {
+            return Err(anyhow!("订单已结束: {:?}", order.status));
+        }
+        
+        let (base_asset, quote_asset) = self.parse_symbol(&order.symbol)?;
+        let fill_qty = order.qty - order.executed_qty;
+        let fill_value = fill_qty * order.price;
+        let commission_rate = *self.commission_rate.lock().unwrap();
+        let (commission, commission_asset) = match order.side {
+            Side::Buy => (fill_qty * commission_rate, base_asset.clone()),
+            Side::Sell => (fill_value * commission_rate, quote_asset.clone()),
+        };
+        
+        {
+            let mut balances = self.balances.lock().unwrap();
+            match order.side {
+                Side::Buy => {
+                    *balances.entry(quote_asset).or_insert(Decimal::ZERO) -= fill_value;
+                    *balances.entry(base_asset).or_insert(Decimal::ZERO) += fill_qty - commission;
+                },
+                Side::Sell => {
+                    *balances.entry(base_asset).or_insert(Decimal::ZERO) -= fill_qty;
+                    *balances.entry(quote_asset).or_insert(Decimal::ZERO) += fill_value - commission;
+                }
+            }
+        }
+        
+        order.executed_qty = order.qty;
+        order.avg_price = order.price;
+        order.commission += commission;
+        order.commission_asset = commission_asset;
+        order.status = OrderStatus::Filled;
+        let order = order.clone();
+        drop(orders);
+        
+        self.set_last_trade_price(&order.symbol, order.price);
+        self.publish_order_update(&order);
+        Ok(order)
+    }
+    
+    /// 断开所有用户数据流，已订阅的流随即结束
+    pub fn disconnect_user_data(&self) {
+        self.user_data_subscribers.lock().unwrap().clear();
+    }
+    
+    /// 向用户数据流的订阅者推送订单状态更新，已关闭的订阅被移除
+    fn publish_order_update(&self, order: &OrderInfo) {
+        self.user_data_subscribers
+            .lock()
+            .unwrap()
+            .retain(|subscriber| subscriber.send(Ok(UserDataEvent::ExecutionReport(order.clone()))).is_ok());
+    }
+    
+    /// 计算限价单在设置的订单簿上可立即成交的数量
+    /// 未设置订单簿时返回 None，表示流动性不受限制
+    fn fillable_quantity(&self, symbol: &str, side: Side, limit_price: Decimal) -> Option<Decimal> {
+        let order_books = self.order_books.lock().unwrap();
+        let book = order_books.get(symbol)?;
+        
+        let fillable = match side {
+            // 买入吃掉价格不高于限价的卖单
+            Side::Buy => book.asks.iter()
+                .filter(|(price, _)| *price <= limit_price)
+                .map(|(_, qty)| *qty)
+                .sum(),
+            // 卖出吃掉价格不低于限价的买单
+            Side::Sell => book.bids.iter()
+                .filter(|(price, _)| *price >= limit_price)
+                .map(|(_, qty)| *qty)
+                .sum(),
+        };
+        
+        Some(fillable)
+    }
+    
     /// 获取当前时间戳（毫秒）
     fn get_timestamp(&self) -> u64 {
         SystemTime::now()
@@ -86,6 +284,10 @@ impl ExchangeApi for MockBinanceApi {
     }
     
     async fn get_price(&self, symbol: &str) -> Result<Price> {
+        let delay = *self.price_delay.lock().unwrap();
+        if !delay.is_zero() {
+            tokio::time::sleep(delay).await;
+        }
         let prices = self.prices.lock().unwrap();
         
         if let Some(price) = prices.get(symbol) {
@@ -99,7 +301,29 @@ impl ExchangeApi for MockBinanceApi {
         }
     }
     
+    async fn get_prices(&self, symbols: &[String]) -> Result<Vec<Price>> {
+        let prices = self.prices.lock().unwrap();
+        let timestamp = Utc::now();
+        
+        if symbols.is_empty() {
+            return Ok(prices.iter()
+                .map(|(symbol, price)| Price { symbol: symbol.clone(), price: *price, timestamp })
+                .collect());
+        }
+        
+        symbols.iter()
+            .map(|symbol| {
+                let price = prices.get(symbol).ok_or_else(|| anyhow!("价格不可用: {}", symbol))?;
+                Ok(Price { symbol: symbol.clone(), price: *price, timestamp })
+            })
+            .collect()
+    }
+    
     async fn get_order_book(&self, symbol: &str, _limit: Option<u32>) -> Result<OrderBook> {
+        if let Some(book) = self.order_books.lock().unwrap().get(symbol) {
+            return Ok(book.clone());
+        }
+        
         let price = {
             let prices = self.prices.lock().unwrap();
             *prices.get(symbol).ok_or_else(|| anyhow!("价格不可用: {}", symbol))?
@@ -131,7 +355,19 @@ impl ExchangeApi for MockBinanceApi {
         })
     }
     
-    async fn place_order(&self, symbol: &str, side: Side, quantity: Decimal, price: Option<Decimal>) -> Result<OrderInfo> {
+    async fn get_last_trade_price(&self, symbol: &str) -> Result<Price> {
+        if let Some(price) = self.last_trades.lock().unwrap().get(symbol) {
+            return Ok(Price {
+                symbol: symbol.to_string(),
+                price: *price,
+                timestamp: Utc::now(),
+            });
+        }
+        
+        self.get_price(symbol).await
+    }
+    
+    async fn place_order(&self, symbol: &str, side: Side, quantity: Decimal, price: Option<Decimal>, time_in_force: TimeInForce, client_order_id: Option<&str>) -> Result<OrderInfo> {
         let (base_asset, quote_asset) = self.parse_symbol(symbol)?;
         
         // 获取当前价格
@@ -143,8 +379,37 @@ impl ExchangeApi for MockBinanceApi {
         // 使用指定价格或者当前市场价格
         let execution_price = price.unwrap_or(current_price);
         
-        // 计算总价值
-        let total_value = quantity * execution_price;
+        // 计算成交数量和订单状态
+        // 市价单和GTC限价单在模拟环境中立即全部成交；
+        // IOC成交可成交部分后取消剩余，FOK不能全部成交时整单过期
+        let (executed_qty, status) = match (price, time_in_force) {
+            _ if *self.hold_orders.lock().unwrap() => (Decimal::ZERO, OrderStatus::New),
+            (Some(limit_price), TimeInForce::IOC) => {
+                let fillable = self.fillable_quantity(symbol, side, limit_price).unwrap_or(quantity);
+                if fillable >= quantity {
+                    (quantity, OrderStatus::Filled)
+                } else {
+                    (fillable, OrderStatus::Expired)
+                }
+            },
+            (Some(limit_price), TimeInForce::FOK) => {
+                let fillable = self.fillable_quantity(symbol, side, limit_price).unwrap_or(quantity);
+                if fillable >= quantity {
+                    (quantity, OrderStatus::Filled)
+                } else {
+                    (Decimal::ZERO, OrderStatus::Expired)
+                }
+            },
+            _ => (quantity, OrderStatus::Filled),
+        };
+        
+        // 计算总价值和手续费
+        let total_value = executed_qty * execution_price;
+        let commission_rate = *self.commission_rate.lock().unwrap();
+        let (commission, commission_asset) = match side {
+            Side::Buy => (executed_qty * commission_rate, base_asset.clone()),
+            Side::Sell => (total_value * commission_rate, quote_asset.clone()),
+        };
         
         // 检查余额
         {
@@ -160,18 +425,18 @@ impl ExchangeApi for MockBinanceApi {
                     
                     // 扣除报价资产，增加基础资产
                     *balances.entry(quote_asset.clone()).or_insert(Decimal::ZERO) -= total_value;
-                    *balances.entry(base_asset.clone()).or_insert(Decimal::ZERO) += quantity;
+                    *balances.entry(base_asset.clone()).or_insert(Decimal::ZERO) += executed_qty - commission;
                 },
                 Side::Sell => {
                     // 卖出需要检查基础资产余额
                     let balance = balances.get(&base_asset).cloned().unwrap_or_default();
-                    if balance < quantity {
-                        return Err(anyhow!("余额不足: {} < {}", balance, quantity));
+                    if balance < executed_qty {
+                        return Err(anyhow!("余额不足: {} < {}", balance, executed_qty));
                     }
                     
                     // 扣除基础资产，增加报价资产
-                    *balances.entry(base_asset.clone()).or_insert(Decimal::ZERO) -= quantity;
-                    *balances.entry(quote_asset.clone()).or_insert(Decimal::ZERO) += total_value;
+                    *balances.entry(base_asset.clone()).or_insert(Decimal::ZERO) -= executed_qty;
+                    *balances.entry(quote_asset.clone()).or_insert(Decimal::ZERO) += total_value - commission;
                 }
             }
         }
@@ -189,8 +454,12 @@ impl ExchangeApi for MockBinanceApi {
             symbol: symbol.to_string(),
             price: execution_price,
             qty: quantity,
+            executed_qty,
+            avg_price: if executed_qty.is_zero() { Decimal::ZERO } else { execution_price },
+            commission,
+            commission_asset,
             side,
-            status: OrderStatus::Filled,  // 模拟环境中，订单立即成交
+            status,
             timestamp: Utc::now(),
         };
         
@@ -199,9 +468,17 @@ impl ExchangeApi for MockBinanceApi {
             let mut orders = self.orders.lock().unwrap();
             orders.insert(order_id, order.clone());
         }
+        if let Some(client_order_id) = client_order_id {
+            self.client_order_ids.lock().unwrap().insert(order_id, client_order_id.to_string());
+        }
         
-        info!("Mock API: 订单已执行 - ID: {}, 交易对: {}, 方向: {:?}, 价格: {}, 数量: {}", 
-            order_id, symbol, side, execution_price, quantity);
+        if !executed_qty.is_zero() {
+            self.set_last_trade_price(symbol, execution_price);
+        }
+        self.publish_order_update(&order);
+        
+        info!("Mock API: 订单已执行 - ID: {}, 交易对: {}, 方向: {:?}, 价格: {}, 数量: {}, 成交: {}, 状态: {:?}", 
+            order_id, symbol, side, execution_price, quantity, executed_qty, status);
         
         Ok(order)
     }
@@ -232,6 +509,8 @@ impl ExchangeApi for MockBinanceApi {
                 
                 order.status = OrderStatus::Cancelled;
                 orders.insert(order_id, order.clone());
+                drop(orders);
+                self.publish_order_update(&order);
                 
                 Ok(order)
             } else {
@@ -242,7 +521,21 @@ impl ExchangeApi for MockBinanceApi {
         }
     }
     
+    async fn get_open_orders(&self, symbol: &str) -> Result<Vec<OrderInfo>> {
+        let orders = self.orders.lock().unwrap();
+        let mut open_orders: Vec<OrderInfo> = orders
+            .values()
+            .filter(|order| order.symbol == symbol)
+            .filter(|order| matches!(order.status, OrderStatus::New | OrderStatus::PartiallyFilled))
+            .cloned()
+            .collect();
+        open_orders.sort_by_key(|order| order.order_id);
+        
+        Ok(open_orders)
+    }
+    
     async fn get_account_balance(&self, asset: &str) -> Result<Decimal> {
+        self.check_api_key()?;
         let balances = self.balances.lock().unwrap();
         
         if let Some(balance) = balances.get(asset) {
@@ -251,11 +544,104 @@ impl ExchangeApi for MockBinanceApi {
             Ok(Decimal::ZERO)  // 如果资产不存在，返回零余额
         }
     }
+    
+    /// 模拟账户没有冻结余额，按币种名称排序
+    async fn get_account_balances(&self) -> Result<Vec<AssetBalance>> {
+        self.check_api_key()?;
+        let mut balances: Vec<AssetBalance> = self.balances.lock().unwrap()
+            .iter()
+            .map(|(asset, free)| AssetBalance { asset: asset.clone(), free: *free, locked: Decimal::ZERO })
+            .collect();
+        balances.sort_by(|a, b| a.asset.cmp(&b.asset));
+        Ok(balances)
+    }
+    
+    async fn get_system_status(&self) -> Result<SystemStatus> {
+        Ok(self.system_status.lock().unwrap().clone())
+    }
+    
+    async fn get_server_time(&self) -> Result<DateTime<Utc>> {
+        Ok(Utc::now() + *self.server_time_offset.lock().unwrap())
+    }
+    
+    async fn create_listen_key(&self) -> Result<String> {
+        Ok("mock-listen-key".to_string())
+    }
+    
+    async fn keepalive_listen_key(&self, _listen_key: &str) -> Result<()> {
+        Ok(())
+    }
+    
+    async fn subscribe_user_data(&self, _listen_key: &str) -> Result<UserDataStream> {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        self.user_data_subscribers.lock().unwrap().push(sender);
+        
+        let stream = stream::unfold(receiver, |mut receiver| async move {
+            receiver.recv().await.map(|event| (event, receiver))
+        });
+        Ok(stream.boxed())
+    }
+    
+    async fn convert_dust(&self, asset: &str) -> Result<DustConversion> {
+        let prices = self.prices.lock().unwrap();
+        let asset_price = *prices.get(&format!("{}USDT", asset)).ok_or_else(|| anyhow!("价格不可用: {}USDT", asset))?;
+        let bnb_price = *prices.get("BNBUSDT").ok_or_else(|| anyhow!("价格不可用: BNBUSDT"))?;
+        drop(prices);
+        
+        let mut balances = self.balances.lock().unwrap();
+        let amount = balances.get(asset).copied().unwrap_or(Decimal::ZERO);
+        if amount <= Decimal::ZERO {
+            return Err(anyhow!("没有可兑换的 {} 余额", asset));
+        }
+        
+        // 按USDT价格折算为BNB，扣除手续费后计入BNB余额
+        let total = amount * asset_price / bnb_price;
+        let service_charge = total * DUST_SERVICE_CHARGE_RATE;
+        let transferred = total - service_charge;
+        balances.insert(asset.to_string(), Decimal::ZERO);
+        *balances.entry("BNB".to_string()).or_insert(Decimal::ZERO) += transferred;
+        
+        info!("模拟小额资产兑换: {} {} -> {} BNB", amount, asset, transferred);
+        Ok(DustConversion {
+            asset: asset.to_string(),
+            amount,
+            transferred,
+            service_charge,
+        })
+    }
+    
+    async fn transfer_to_sub_account(&self, to_email: &str, asset: &str, amount: Decimal) -> Result<SubAccountTransfer> {
+        if amount <= Decimal::ZERO {
+            return Err(anyhow!("划转数量必须大于0: {}", amount));
+        }
+        
+        {
+            let mut balances = self.balances.lock().unwrap();
+            let balance = balances.get(asset).copied().unwrap_or(Decimal::ZERO);
+            if balance < amount {
+                return Err(anyhow!("余额不足: {} < {}", balance, amount));
+            }
+            balances.insert(asset.to_string(), balance - amount);
+        }
+        
+        let mut transfers = self.sub_account_transfers.lock().unwrap();
+        let transfer = SubAccountTransfer {
+            txn_id: (transfers.len() + 1).to_string(),
+            to_email: to_email.to_string(),
+            asset: asset.to_string(),
+            amount,
+        };
+        transfers.push(transfer.clone());
+        
+        info!("模拟子账户划转: {} {} -> {}", amount, asset, to_email);
+        Ok(transfer)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::PriceSource;
     use rust_decimal_macros::dec;
 
     #[tokio::test]
@@ -271,7 +657,7 @@ mod tests {
         let initial_btc = api.get_account_balance("BTC").await.unwrap();
         
         // 买入0.1 BTC
-        let buy_order = api.place_order("BTCUSDT", Side::Buy, dec!(0.1), None).await.unwrap();
+        let buy_order = api.place_order("BTCUSDT", Side::Buy, dec!(0.1), None, TimeInForce::GTC, None).await.unwrap();
         assert_eq!(buy_order.status, OrderStatus::Filled);
         
         // 检查余额变化
@@ -282,7 +668,7 @@ mod tests {
         assert_eq!(after_buy_btc, initial_btc + dec!(0.1));
         
         // 卖出0.05 BTC
-        let sell_order = api.place_order("BTCUSDT", Side::Sell, dec!(0.05), None).await.unwrap();
+        let sell_order = api.place_order("BTCUSDT", Side::Sell, dec!(0.05), None, TimeInForce::GTC, None).await.unwrap();
         assert_eq!(sell_order.status, OrderStatus::Filled);
         
         // 检查余额变化
@@ -292,4 +678,187 @@ mod tests {
         assert_eq!(after_sell_usdt, after_buy_usdt + dec!(0.05) * dec!(50000.00));
         assert_eq!(after_sell_btc, after_buy_btc - dec!(0.05));
     }
+    
+    #[tokio::test]
+    async fn test_get_prices() {
+        let api = MockBinanceApi::new();
+        
+        let symbols = vec!["BTCUSDC".to_string(), "BTCUSDT".to_string()];
+        let prices = api.get_prices(&symbols).await.unwrap();
+        assert_eq!(prices.len(), 2);
+        assert_eq!(prices[0].symbol, "BTCUSDC");
+        assert_eq!(prices[1].price, dec!(50000.00));
+        
+        // 不指定交易对时返回全部价格
+        let all = api.get_prices(&[]).await.unwrap();
+        assert!(all.len() >= 2);
+        
+        assert!(api.get_prices(&["ETHBTC".to_string()]).await.is_err());
+    }
+    
+    #[tokio::test]
+    async fn test_commission() {
+        let api = MockBinanceApi::new();
+        api.set_commission_rate(dec!(0.001));
+        
+        let initial_btc = api.get_account_balance("BTC").await.unwrap();
+        let initial_usdc = api.get_account_balance("USDC").await.unwrap();
+        
+        // 买入手续费以基础资产收取
+        let buy_order = api.place_order("BTCUSDT", Side::Buy, dec!(0.1), None, TimeInForce::GTC, None).await.unwrap();
+        assert_eq!(buy_order.avg_price, dec!(50000.00));
+        assert_eq!(buy_order.commission, dec!(0.0001));
+        assert_eq!(buy_order.commission_asset, "BTC");
+        assert_eq!(api.get_account_balance("BTC").await.unwrap(), initial_btc + dec!(0.0999));
+        
+        // 卖出手续费以报价资产收取
+        let sell_order = api.place_order("BTCUSDC", Side::Sell, dec!(0.1), None, TimeInForce::GTC, None).await.unwrap();
+        assert_eq!(sell_order.commission, dec!(5.0025));
+        assert_eq!(sell_order.commission_asset, "USDC");
+        assert_eq!(api.get_account_balance("USDC").await.unwrap(), initial_usdc + dec!(5002.5) - dec!(5.0025));
+        
+        // 查询订单状态返回相同的成交信息
+        let status = api.get_order_status("BTCUSDC", sell_order.order_id).await.unwrap();
+        assert_eq!(status.commission, sell_order.commission);
+        assert_eq!(status.avg_price, dec!(50025.00));
+    }
+    
+    /// 设置一个有限深度的BTCUSDT订单簿：卖单共0.3 BTC，买单共0.3 BTC
+    fn setup_shallow_book(api: &MockBinanceApi) {
+        api.set_order_book(
+            "BTCUSDT",
+            vec![(dec!(49990), dec!(0.1)), (dec!(49980), dec!(0.2))],
+            vec![(dec!(50010), dec!(0.1)), (dec!(50020), dec!(0.2))],
+        );
+    }
+    
+    #[tokio::test]
+    async fn test_limit_order_gtc() {
+        let api = MockBinanceApi::new();
+        setup_shallow_book(&api);
+        
+        // GTC限价单在模拟环境中立即全部成交
+        let order = api.place_order("BTCUSDT", Side::Buy, dec!(0.15), Some(dec!(50010)), TimeInForce::GTC, None).await.unwrap();
+        assert_eq!(order.status, OrderStatus::Filled);
+        assert_eq!(order.executed_qty, dec!(0.15));
+    }
+    
+    #[tokio::test]
+    async fn test_limit_order_ioc_partial_fill() {
+        let api = MockBinanceApi::new();
+        setup_shallow_book(&api);
+        let initial_btc = api.get_account_balance("BTC").await.unwrap();
+        
+        // 限价50010只能吃到第一档0.1 BTC，剩余部分取消
+        let order = api.place_order("BTCUSDT", Side::Buy, dec!(0.5), Some(dec!(50010)), TimeInForce::IOC, None).await.unwrap();
+        assert_eq!(order.status, OrderStatus::Expired);
+        assert_eq!(order.executed_qty, dec!(0.1));
+        assert_eq!(api.get_account_balance("BTC").await.unwrap(), initial_btc + dec!(0.1));
+        
+        // 深度足够时全部成交
+        let order = api.place_order("BTCUSDT", Side::Sell, dec!(0.3), Some(dec!(49980)), TimeInForce::IOC, None).await.unwrap();
+        assert_eq!(order.status, OrderStatus::Filled);
+        assert_eq!(order.executed_qty, dec!(0.3));
+    }
+    
+    #[tokio::test]
+    async fn test_limit_order_fok() {
+        let api = MockBinanceApi::new();
+        setup_shallow_book(&api);
+        let initial_usdt = api.get_account_balance("USDT").await.unwrap();
+        let initial_btc = api.get_account_balance("BTC").await.unwrap();
+        
+        // 深度不足，整单过期，余额不变
+        let order = api.place_order("BTCUSDT", Side::Buy, dec!(0.5), Some(dec!(50020)), TimeInForce::FOK, None).await.unwrap();
+        assert_eq!(order.status, OrderStatus::Expired);
+        assert_eq!(order.executed_qty, Decimal::ZERO);
+        assert_eq!(api.get_account_balance("USDT").await.unwrap(), initial_usdt);
+        assert_eq!(api.get_account_balance("BTC").await.unwrap(), initial_btc);
+        
+        // 深度足够，全部成交
+        let order = api.place_order("BTCUSDT", Side::Buy, dec!(0.15), Some(dec!(50020)), TimeInForce::FOK, None).await.unwrap();
+        assert_eq!(order.status, OrderStatus::Filled);
+        assert_eq!(order.executed_qty, dec!(0.15));
+    }
+    
+    #[tokio::test]
+    async fn test_reference_price_book_mid() {
+        let api = MockBinanceApi::new();
+        api.set_order_book(
+            "BTCUSDT",
+            vec![(dec!(49990), dec!(1)), (dec!(49980), dec!(2))],
+            vec![(dec!(50010), dec!(1)), (dec!(50020), dec!(2))],
+        );
+        
+        // 中间价为最优买价和最优卖价的平均值
+        let mid = api.get_reference_price("BTCUSDT", PriceSource::BookMid).await.unwrap();
+        assert_eq!(mid.price, dec!(50000));
+        
+        let ticker = api.get_reference_price("BTCUSDT", PriceSource::Ticker).await.unwrap();
+        assert_eq!(ticker.price, dec!(50000.00));
+        
+        // 空的订单簿无法计算中间价
+        api.set_order_book("BTCUSDT", vec![(dec!(49990), dec!(1))], Vec::new());
+        assert!(api.get_reference_price("BTCUSDT", PriceSource::BookMid).await.is_err());
+    }
+    
+    #[tokio::test]
+    async fn test_reference_price_last_trade() {
+        let api = MockBinanceApi::new();
+        
+        // 没有成交时以当前价格代替
+        let last = api.get_reference_price("BTCUSDT", PriceSource::LastTrade).await.unwrap();
+        assert_eq!(last.price, dec!(50000.00));
+        
+        api.place_order("BTCUSDT", Side::Buy, dec!(0.1), Some(dec!(49950)), TimeInForce::GTC, None).await.unwrap();
+        api.update_price("BTCUSDT", dec!(50100));
+        let last = api.get_reference_price("BTCUSDT", PriceSource::LastTrade).await.unwrap();
+        assert_eq!(last.price, dec!(49950));
+    }
+    
+    #[tokio::test]
+    async fn test_user_data_stream_pushes_order_updates() {
+        let api = MockBinanceApi::new();
+        api.set_hold_orders(true);
+        
+        let listen_key = api.create_listen_key().await.unwrap();
+        let mut stream = api.subscribe_user_data(&listen_key).await.unwrap();
+        
+        let order = api.place_order("BTCUSDT", Side::Buy, dec!(0.1), Some(dec!(50000)), TimeInForce::GTC, None).await.unwrap();
+        let Some(Ok(UserDataEvent::ExecutionReport(placed))) = stream.next().await else {
+            panic!("下单后应推送执行报告");
+        };
+        assert_eq!(placed.order_id, order.order_id);
+        assert_eq!(placed.status, OrderStatus::New);
+        
+        let filled = api.fill_order(order.order_id).unwrap();
+        assert_eq!(filled.executed_qty, dec!(0.1));
+        assert_eq!(api.get_account_balance("USDT").await.unwrap(), dec!(5000));
+        let Some(Ok(UserDataEvent::ExecutionReport(update))) = stream.next().await else {
+            panic!("成交后应推送执行报告");
+        };
+        assert_eq!(update.status, OrderStatus::Filled);
+        assert_eq!(update.avg_price, dec!(50000));
+        assert!(api.fill_order(order.order_id).is_err());
+        
+        // 断开后流结束
+        api.disconnect_user_data();
+        assert!(stream.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_convert_dust() {
+        let api = MockBinanceApi::new();
+        api.set_balance("BTC", dec!(0.00003));
+
+        // 0.00003 BTC × 50000 / 300 = 0.005 BNB，扣除2%手续费
+        let conversion = api.convert_dust("BTC").await.unwrap();
+        assert_eq!(conversion.amount, dec!(0.00003));
+        assert_eq!(conversion.service_charge, dec!(0.0001));
+        assert_eq!(conversion.transferred, dec!(0.0049));
+        assert_eq!(api.get_account_balance("BTC").await.unwrap(), Decimal::ZERO);
+        assert_eq!(api.get_account_balance("BNB").await.unwrap(), dec!(0.0049));
+
+        assert!(api.convert_dust("BTC").await.is_err());
+    }
 }
@@ -1,5 +1,7 @@
 pub mod api;
+pub mod latency;
 pub mod mock_api;
 
-pub use api::{BinanceApi, ExchangeApi};
+pub use api::{BinanceApi, ExchangeApi, UserDataStream};
+pub use latency::{ApiLatencyStats, LatencyTrackedApi, LatencyTracker};
 pub use mock_api::MockBinanceApi;
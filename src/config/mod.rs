@@ -1,13 +1,27 @@
 use dotenv::dotenv;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 use std::env;
+use std::fmt;
 use std::fs::File;
 use std::io::Read;
-use anyhow::{Context, Result};
+use std::str::FromStr;
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, FixedOffset, NaiveTime, TimeZone, Utc};
 use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use sha2::{Digest, Sha256};
+use crate::analytics::Locale;
+use crate::models::{OrderType, TimeInForce};
+use crate::risk::BlacklistEntry;
+
+mod secret;
+
+pub use secret::{decrypt_secret, encrypt_secret, read_passphrase, SecretSettings, SecretSource, PASSPHRASE_ENV};
 
 /// 交易策略类型
-#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
 pub enum StrategyType {
     /// 简单价格差异套利
     Simple,
@@ -19,10 +33,12 @@ pub enum StrategyType {
     SlippageControl,
     /// 趋势跟踪
     TrendFollowing,
+    /// 多策略加权组合
+    Composite,
 }
 
 /// 风控组件类型
-#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
 pub enum RiskControllerType {
     /// 每日亏损限制
     DailyLossLimit,
@@ -36,29 +52,675 @@ pub enum RiskControllerType {
     TradingFrequency,
     /// 交易对黑名单
     PairBlacklist,
+    /// 最大同时持仓数
+    OpenPositions,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
 pub struct Config {
+    /// 币安API密钥
     pub api_key: String,
+    /// 币安API私钥，`secret_source` 不为 `Inline` 时在加载配置时从对应来源读取
     pub api_secret: String,
+    /// API私钥的来源：`secret_source`、`api_secret_file`、`api_secret_encrypted`
+    #[serde(flatten)]
+    pub secret: SecretSettings,
+    /// 币安API地址，例如 https://api.binance.com
     pub base_url: String,
+    /// 备用API地址（例如 https://api1.binance.com），当前地址连续请求失败时按顺序切换，为空时不切换
+    #[serde(default)]
+    pub fallback_base_urls: Vec<String>,
+    /// 套利参数
     pub arbitrage_settings: ArbitrageSettings,
+    /// 交易策略设置
     pub strategy_settings: StrategySettings,
+    /// 风控设置
     pub risk_settings: RiskSettings,
+    /// 在TRACE日志级别记录每次API调用的请求（已隐藏密钥和签名）和原始响应，默认关闭
+    #[serde(default)]
+    pub trace_api: bool,
+    /// 管理接口设置
+    #[serde(default)]
+    pub admin_api: AdminApiSettings,
+    /// 定时推送绩效报告设置
+    #[serde(default)]
+    pub report_delivery: ReportDeliverySettings,
+    /// 报告标题、时间范围描述、CSV表头和命令行摘要的语言: zh, en，默认中文
+    #[serde(default)]
+    pub report_language: Locale,
+    /// API调用耗时统计设置
+    #[serde(default)]
+    pub api_latency: ApiLatencySettings,
+    /// 手续费率，用于判断机会的价差能否覆盖买卖两边的手续费
+    #[serde(default)]
+    pub fees: FeeSettings,
+    /// 下单方式和成交价格偏离限制
+    #[serde(default)]
+    pub execution: ExecutionSettings,
+    /// 命名配置档，每个配置档只写需要覆盖的设置，通过 `--profile` 或环境变量 BINANCE_PROFILE 选择
+    ///
+    /// 对象逐层合并，数组和其他值整体替换。
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, serde_json::Value>,
+    /// 加载时选择的配置档，热加载配置时使用相同的配置档
+    #[serde(skip)]
+    pub active_profile: Option<String>,
+}
+
+/// 手续费设置，默认为币安现货普通用户（VIP0）的费率
+///
+/// 交易所不提供手续费率时按这里的费率估算买卖两边的手续费，价差不足以覆盖时跳过机会。
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct FeeSettings {
+    /// 挂单（maker）手续费率，小数（例如 0.001 表示0.1%），GTC限价单按此费率估算
+    #[serde(with = "decimal_number")]
+    #[schemars(with = "f64")]
+    pub maker_rate: Decimal,
+    /// 吃单（taker）手续费率，小数，市价单和IOC/FOK限价单按此费率估算
+    #[serde(with = "decimal_number")]
+    #[schemars(with = "f64")]
+    pub taker_rate: Decimal,
+    /// 使用BNB抵扣手续费的折扣，小数（例如 0.25 表示手续费按75%收取），0表示不抵扣
+    #[serde(with = "decimal_number")]
+    #[schemars(with = "f64")]
+    pub fee_asset_discount: Decimal,
+    /// 每笔套利的固定成本（USDT），例如按笔收取的最低手续费或其他开销，0表示没有固定成本
+    ///
+    /// 大于0时，交易金额低于盈亏平衡金额（固定成本 / 扣除手续费后的价差比例）的机会被跳过。
+    #[serde(with = "decimal_number")]
+    #[schemars(with = "f64")]
+    pub fixed_cost_per_trade: Decimal,
+    /// 最小交易金额为盈亏平衡金额的倍数，不小于1，大于1时要求利润覆盖固定成本后仍有余量
+    #[serde(with = "decimal_number")]
+    #[schemars(with = "f64")]
+    pub min_notional_multiple: Decimal,
+}
+
+impl Default for FeeSettings {
+    fn default() -> Self {
+        Self {
+            maker_rate: dec!(0.001),
+            taker_rate: dec!(0.001),
+            fee_asset_discount: Decimal::ZERO,
+            fixed_cost_per_trade: Decimal::ZERO,
+            min_notional_multiple: Decimal::ONE,
+        }
+    }
+}
+
+impl FeeSettings {
+    /// 按订单类型和有效方式估算的单边手续费率，已扣除BNB抵扣折扣
+    pub fn leg_rate(&self, order_type: OrderType, time_in_force: TimeInForce) -> Decimal {
+        let rate = match (order_type, time_in_force) {
+            (OrderType::Limit, TimeInForce::GTC) => self.maker_rate,
+            _ => self.taker_rate,
+        };
+        rate * (Decimal::ONE - self.fee_asset_discount)
+    }
+}
+
+/// 下单设置
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct ExecutionSettings {
+    /// 成交均价相对机会报价的最大不利偏离（百分比），买入成交价超出时卖回已买入的资产并放弃本次套利；0表示不检查
+    #[serde(with = "decimal_number")]
+    #[schemars(with = "f64")]
+    pub max_fill_deviation_pct: Decimal,
+    /// 两边订单的默认类型，`buy_order_type` / `sell_order_type` 未设置时使用；为空时按 `use_limit_orders`
+    pub order_type: Option<OrderType>,
+    /// 限价单的有效方式，设置后覆盖 `arbitrage_settings.time_in_force`
+    pub time_in_force: Option<TimeInForce>,
+}
+
+/// API调用耗时统计设置，只在实时模式下生效
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct ApiLatencySettings {
+    /// 是否记录每次API调用的耗时，关闭时直接调用，不做任何计时
+    pub enabled: bool,
+    /// 每个API方法保留最近多少次调用的耗时，用于计算滚动平均值和P95
+    pub window_size: usize,
+    /// 单次调用耗时超过该值（毫秒）时输出警告，为0时不警告
+    pub warn_threshold_ms: u64,
+}
+
+impl Default for Config {
+    /// 全部使用默认值、未填写API密钥的配置，配置文件中省略的字段取这里的值
+    fn default() -> Self {
+        Self {
+            api_key: String::new(),
+            api_secret: String::new(),
+            secret: SecretSettings::default(),
+            base_url: "https://api.binance.com".to_string(),
+            fallback_base_urls: Vec::new(),
+            arbitrage_settings: ArbitrageSettings::default(),
+            strategy_settings: StrategySettings::default(),
+            risk_settings: RiskSettings::default(),
+            trace_api: false,
+            admin_api: AdminApiSettings::default(),
+            report_delivery: ReportDeliverySettings::default(),
+            report_language: Locale::default(),
+            api_latency: ApiLatencySettings::default(),
+            fees: FeeSettings::default(),
+            execution: ExecutionSettings::default(),
+            profiles: BTreeMap::new(),
+            active_profile: None,
+        }
+    }
+}
+
+impl Default for ApiLatencySettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            window_size: 100,
+            warn_threshold_ms: 1000,
+        }
+    }
+}
+
+/// 管理接口设置，需要启用 `admin-api` 特性
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct AdminApiSettings {
+    /// 是否启动管理接口
+    pub enabled: bool,
+    /// 监听地址
+    pub bind_address: String,
+    /// 访问令牌，请求需携带 `Authorization: Bearer <token>`；为空时拒绝启动
+    pub bearer_token: String,
+}
+
+impl Default for AdminApiSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            bind_address: "127.0.0.1:8081".to_string(),
+            bearer_token: String::new(),
+        }
+    }
+}
+
+/// 定时推送绩效报告设置，每次推送前一天（按统计时区）的报告
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct ReportDeliverySettings {
+    /// 是否在实时模式下后台推送，`report-daemon` 子命令不受此项影响
+    pub enabled: bool,
+    /// 推送时间，按统计时区
+    pub schedule: ReportSchedule,
+    /// 推送渠道
+    pub channel: ReportChannel,
+    /// 推送的报告格式
+    pub format: ReportDeliveryFormat,
+    /// 推送失败后的最大重试次数
+    pub max_retries: u32,
+    /// 第一次重试前的等待时间（毫秒），之后每次重试翻倍
+    pub retry_backoff_ms: u64,
+}
+
+impl Default for ReportDeliverySettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            schedule: ReportSchedule::Daily { time: "08:00".to_string() },
+            channel: ReportChannel::Webhook { url: String::new() },
+            format: ReportDeliveryFormat::Html,
+            max_retries: 3,
+            retry_backoff_ms: 30_000,
+        }
+    }
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+impl ReportDeliverySettings {
+    /// 检查推送时间和渠道设置是否有效
+    pub fn validate(&self) -> Result<()> {
+        self.schedule.validate()?;
+        match &self.channel {
+            ReportChannel::Webhook { url } => {
+                if !url.starts_with("http://") && !url.starts_with("https://") {
+                    return Err(anyhow!("报告推送的webhook地址必须以 http:// 或 https:// 开头"));
+                }
+            },
+            ReportChannel::Email(smtp) => {
+                if smtp.host.is_empty() || smtp.from.is_empty() || smtp.to.is_empty() {
+                    return Err(anyhow!("邮件推送需要设置SMTP服务器、发件人和收件人"));
+                }
+            },
+        }
+        Ok(())
+    }
+}
+
+/// 报告推送时间
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ReportSchedule {
+    /// 每天的固定时间，格式 HH:MM
+    Daily { time: String },
+    /// cron表达式，包含秒字段：秒 分 时 日 月 星期，例如 `0 30 8 * * Mon-Fri`
+    Cron { expression: String },
+}
+
+impl ReportSchedule {
+    /// 检查时间或cron表达式能否解析
+    pub fn validate(&self) -> Result<()> {
+        match self {
+            ReportSchedule::Daily { time } => NaiveTime::parse_from_str(time, "%H:%M")
+                .map(|_| ())
+                .map_err(|_| anyhow!("无效的报告推送时间 {}，应为 HH:MM", time)),
+            ReportSchedule::Cron { expression } => cron::Schedule::from_str(expression)
+                .map(|_| ())
+                .map_err(|e| anyhow!("无效的cron表达式 {}: {}", expression, e)),
+        }
+    }
+
+    /// `after` 之后（不含）的下一次推送时间，时间和cron表达式按 `offset` 时区解释
+    pub fn next_after(&self, after: DateTime<Utc>, offset: FixedOffset) -> Result<DateTime<Utc>> {
+        let local = after.with_timezone(&offset);
+        match self {
+            ReportSchedule::Daily { time } => {
+                let time = NaiveTime::parse_from_str(time, "%H:%M")
+                    .map_err(|_| anyhow!("无效的报告推送时间 {}，应为 HH:MM", time))?;
+                let mut next = local.date_naive().and_time(time);
+                if next <= local.naive_local() {
+                    next += chrono::Duration::days(1);
+                }
+                offset
+                    .from_local_datetime(&next)
+                    .single()
+                    .map(|next| next.with_timezone(&Utc))
+                    .ok_or_else(|| anyhow!("无法确定推送时间 {}", next))
+            },
+            ReportSchedule::Cron { expression } => {
+                let schedule = cron::Schedule::from_str(expression)
+                    .map_err(|e| anyhow!("无效的cron表达式 {}: {}", expression, e))?;
+                schedule
+                    .after(&local)
+                    .next()
+                    .map(|next| next.with_timezone(&Utc))
+                    .ok_or_else(|| anyhow!("cron表达式 {} 之后没有推送时间", expression))
+            },
+        }
+    }
+}
+
+/// 报告推送渠道
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ReportChannel {
+    /// 以POST请求发送到webhook地址，请求体为报告内容
+    Webhook { url: String },
+    /// 通过SMTP发送邮件，需要启用 `email` 特性
+    Email(SmtpSettings),
+}
+
+/// SMTP邮件设置
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct SmtpSettings {
+    /// SMTP服务器地址，使用TLS连接
+    pub host: String,
+    /// SMTP服务器端口
+    #[serde(default = "default_smtp_port")]
+    pub port: u16,
+    /// 登录用户名，为空时不登录
+    #[serde(default)]
+    pub username: String,
+    /// 登录密码
+    #[serde(default)]
+    pub password: String,
+    /// 发件人地址
+    pub from: String,
+    /// 收件人地址列表
+    pub to: Vec<String>,
+}
+
+fn default_smtp_port() -> u16 {
+    465
+}
+
+/// 推送的报告格式
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportDeliveryFormat {
+    /// 完整的JSON报告
+    Json,
+    /// HTML报告，包含总体统计和图表
+    #[default]
+    Html,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
 pub struct ArbitrageSettings {
-    pub min_profit_percentage: f64,   // 0.1%最小利润率
-    pub max_trade_amount_usdt: f64, // 最大交易金额，USDT
-    pub price_diff_threshold: f64,   // 价格差异阈值，百分比
+    /// 最小利润率，百分比（例如 0.1 表示0.1%）
+    #[serde(with = "decimal_number")]
+    #[schemars(with = "f64")]
+    pub min_profit_percentage: Decimal,
+    /// 最大交易金额，USDT
+    #[serde(with = "decimal_number")]
+    #[schemars(with = "f64")]
+    pub max_trade_amount_usdt: Decimal,
+    /// 价格差异阈值，百分比
+    ///
+    /// 两个交易对报价的原始价差低于该值时跳过本轮，不运行策略评估，为0时不过滤
+    #[serde(with = "decimal_number")]
+    #[schemars(with = "f64")]
+    pub price_diff_threshold: Decimal,
+    /// USDT交易对，例如 BTCUSDT
     pub usdt_symbol: String,
+    /// USDC交易对，例如 BTCUSDC
     pub usdc_symbol: String,
+    /// 价格检查间隔，毫秒
     pub check_interval_ms: u64,
+    /// 是否使用限价单执行套利（按机会价格下单），否则使用市价单
+    pub use_limit_orders: bool,
+    /// 限价单有效方式 (GTC/IOC/FOK)
+    pub time_in_force: TimeInForce,
+    /// 买入订单类型，未设置时按 use_limit_orders 决定
+    #[serde(default)]
+    pub buy_order_type: Option<OrderType>,
+    /// 卖出订单类型，未设置时按 use_limit_orders 决定
+    #[serde(default)]
+    pub sell_order_type: Option<OrderType>,
+    /// 限价单等待成交的最长时间，毫秒，超时后取消；市价单最多等待10秒
+    #[serde(default = "default_limit_order_timeout_ms")]
+    pub limit_order_timeout_ms: u64,
+    /// 交易所系统状态检查间隔，毫秒；维护期间暂停交易
+    pub system_status_check_interval_ms: u64,
+    /// 数据库健康检查间隔，毫秒；检查结果记录在引擎状态中，不影响交易
+    #[serde(default = "default_db_health_check_interval_ms")]
+    pub db_health_check_interval_ms: u64,
+    /// 连续多少次未能将套利结果写入数据库后暂停交易，之后每次健康检查时重试写入，成功后恢复交易；0表示不暂停
+    #[serde(default)]
+    pub max_consecutive_db_failures: u32,
+    /// 只记录通过风控的套利机会，不实际下单
+    #[serde(default)]
+    pub dry_run: bool,
+    /// 行情缓存设置
+    #[serde(default)]
+    pub price_cache: PriceCacheSettings,
+    /// 预热的行情轮数，预热期间只观察行情、更新缓存和策略历史，不执行交易
+    #[serde(default)]
+    pub warmup_ticks: u64,
+    /// 预热时长（秒），与 `warmup_ticks` 同时满足后预热结束，均为0时不预热
+    #[serde(default)]
+    pub warmup_seconds: u64,
+    /// 过期挂单清理间隔，毫秒；0表示不清理
+    #[serde(default = "default_stale_order_cleanup_interval_ms")]
+    pub stale_order_cleanup_interval_ms: u64,
+    /// 挂单超过该时长（秒）且不属于正在执行的套利时被取消
+    #[serde(default = "default_max_order_age_seconds")]
+    pub max_order_age_seconds: u64,
+    /// 执行套利前预留买入所用的计价币余额，可用余额扣除正在执行的套利已预留的金额后不足时跳过机会
+    #[serde(default = "default_reserve_balance")]
+    pub reserve_balance: bool,
+    /// 实盘模式下订阅用户数据流获取订单状态，数据流断开时改为轮询
+    #[serde(default = "default_use_user_data_stream")]
+    pub use_user_data_stream: bool,
+    /// 多个策略同时发现机会时选择机会的排序方式
+    #[serde(default)]
+    pub opportunity_ranking: OpportunityRanking,
+    /// 没有策略给出机会时按手续费和稳定币兑换后的净收益选择买卖方向
+    #[serde(default)]
+    pub best_execution: BestExecutionSettings,
+    /// 定期清理套利后剩余的小额基础资产
+    #[serde(default)]
+    pub dust_sweep: DustSweepSettings,
+    /// 按USDT和USDC持仓的偏离调整交易金额
+    #[serde(default)]
+    pub inventory_skew: InventorySkewSettings,
+    /// 每轮用一次请求批量获取所有监控交易对的价格，关闭后逐个交易对请求
+    #[serde(default = "default_batch_price_fetch")]
+    pub batch_price_fetch: bool,
+    /// 已实现利润的分配
+    #[serde(default)]
+    pub profit_distribution: ProfitDistributionSettings,
+    /// 短时间内不重复执行几乎相同的套利机会
+    #[serde(default)]
+    pub opportunity_dedup: OpportunityDedupSettings,
+}
+
+/// 套利机会去重设置
+///
+/// 检查间隔较短时，一笔套利成交后价格还没有反映出来，下一轮会再次发现同一个机会。
+/// 执行（或只记录）一个机会后，`window_ms` 内资产、买卖方向相同且利润率按 `spread_decimal_places` 位小数四舍五入后相等的机会被跳过。
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct OpportunityDedupSettings {
+    /// 去重窗口，毫秒；0表示不去重
+    pub window_ms: u64,
+    /// 比较利润率（百分比）时保留的小数位数，默认2位即0.01%以内的差异视为同一个机会
+    pub spread_decimal_places: u32,
+}
+
+impl Default for OpportunityDedupSettings {
+    fn default() -> Self {
+        Self {
+            window_ms: 0,
+            spread_decimal_places: 2,
+        }
+    }
+}
+
+/// 已实现利润的分配设置
+///
+/// 启用后累计每笔套利的净利润（亏损会抵减累计），累计达到 `threshold_usdt` 时将其中 `portion` 的部分
+/// 划转到子账户；未启用划转时只记录为待分配金额。分配后累计清零，剩余部分继续留作交易资金。
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct ProfitDistributionSettings {
+    /// 是否启用
+    pub enabled: bool,
+    /// 触发分配的累计净利润，USDT
+    #[serde(with = "decimal_number")]
+    #[schemars(with = "f64")]
+    pub threshold_usdt: Decimal,
+    /// 每次分配累计利润的比例，0到1之间（不含0）
+    #[serde(with = "decimal_number")]
+    #[schemars(with = "f64")]
+    pub portion: Decimal,
+    /// 划转的币种
+    pub asset: String,
+    /// 是否通过 `/sapi/v1/sub-account/transfer` 实际划转，关闭时只记录分配金额
+    pub transfer_enabled: bool,
+    /// 接收划转的子账户邮箱
+    pub to_email: String,
+}
+
+impl Default for ProfitDistributionSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            threshold_usdt: dec!(100),
+            portion: dec!(0.5),
+            asset: "USDT".to_string(),
+            transfer_enabled: false,
+            to_email: String::new(),
+        }
+    }
+}
+
+/// 库存偏斜设置
+///
+/// 启用后每次交易前查询USDT和USDC余额（USDC按1:1计），计算USDT占两者合计的比例与目标比例的偏差。
+/// 交易用买入一侧的稳定币换得卖出一侧的稳定币，使库存向目标比例靠拢时放大交易金额，远离时缩小：
+/// 倍数 = 1 ± `skew_factor` × 偏差，限制在0到 `max_multiplier` 之间，且不超过买入一侧的余额和最大交易金额。
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct InventorySkewSettings {
+    /// 是否启用
+    pub enabled: bool,
+    /// USDT占USDT和USDC合计的目标比例，0到1之间，默认0.5即各占一半
+    #[serde(with = "decimal_number")]
+    #[schemars(with = "f64")]
+    pub target_usdt_ratio: Decimal,
+    /// 偏斜力度，越大交易金额对库存偏离越敏感，0表示不调整
+    #[serde(with = "decimal_number")]
+    #[schemars(with = "f64")]
+    pub skew_factor: Decimal,
+    /// 交易金额相对机会交易金额的最大倍数，不小于1
+    #[serde(with = "decimal_number")]
+    #[schemars(with = "f64")]
+    pub max_multiplier: Decimal,
+}
+
+impl Default for InventorySkewSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            target_usdt_ratio: dec!(0.5),
+            skew_factor: dec!(2),
+            max_multiplier: dec!(1.5),
+        }
+    }
+}
+
+/// 小额基础资产的清理设置
+///
+/// 启用后定期检查基础资产余额，余额大于0且低于交易对最小下单量或 `dust_threshold` 时视为小额资产，
+/// 按 `action` 处理。套利执行期间跳过检查。
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct DustSweepSettings {
+    /// 是否启用
+    pub enabled: bool,
+    /// 检查间隔，毫秒
+    pub interval_ms: u64,
+    /// 小额资产的数量上限（基础资产数量，不含），低于交易对最小下单量时使用最小下单量
+    #[serde(with = "decimal_number")]
+    #[schemars(with = "f64")]
+    pub dust_threshold: Decimal,
+    /// 处理方式
+    pub action: DustAction,
+}
+
+impl Default for DustSweepSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_ms: 3_600_000,
+            dust_threshold: Decimal::ZERO,
+            action: DustAction::default(),
+        }
+    }
+}
+
+/// 小额基础资产的处理方式
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub enum DustAction {
+    /// 只记录日志，不处理
+    #[default]
+    Report,
+    /// 通过币安小额资产兑换接口兑换为BNB
+    Convert,
+    /// 在USDT交易对市价卖出，数量按步长向下取整；不足最小下单量或最小下单金额时只记录
+    MarketSell,
+}
+
+/// 跨计价币的最优执行设置
+///
+/// 启用后，没有策略给出机会时分别计算两个方向扣除两个交易对的手续费、
+/// 并把USDC按兑换价折算为USDT后的净利润率，选择净利润率更高的方向；
+/// 未启用时只比较两个交易对的报价，在便宜的一边买入。
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct BestExecutionSettings {
+    /// 是否启用
+    pub enabled: bool,
+    /// USDT交易对的手续费率，小数（例如 0.001 表示0.1%）
+    #[serde(with = "decimal_number")]
+    #[schemars(with = "f64")]
+    pub usdt_fee_rate: Decimal,
+    /// USDC交易对的手续费率，小数
+    #[serde(with = "decimal_number")]
+    #[schemars(with = "f64")]
+    pub usdc_fee_rate: Decimal,
+    /// USDC兑USDT的交易对，获取价格失败时按1:1折算
+    pub conversion_symbol: String,
+}
+
+impl Default for BestExecutionSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            usdt_fee_rate: dec!(0.001),
+            usdc_fee_rate: dec!(0.001),
+            conversion_symbol: "USDCUSDT".to_string(),
+        }
+    }
+}
+
+/// 套利机会的排序方式
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub enum OpportunityRanking {
+    /// 按利润率排序
+    #[default]
+    ByPercentage,
+    /// 按预期利润（交易金额 × 利润率）排序，优先净利润更高的交易
+    ByAbsoluteProfit,
+    /// 按预期利润排序，并按近期价格波动率对利润率较薄的机会打折
+    ByRiskAdjusted,
+}
+
+fn default_db_health_check_interval_ms() -> u64 {
+    30_000
+}
+
+fn default_limit_order_timeout_ms() -> u64 {
+    30_000
+}
+
+fn default_stale_order_cleanup_interval_ms() -> u64 {
+    60_000
+}
+
+fn default_max_order_age_seconds() -> u64 {
+    300
+}
+
+fn default_reserve_balance() -> bool {
+    true
+}
+
+fn default_use_user_data_stream() -> bool {
+    true
+}
+
+fn default_batch_price_fetch() -> bool {
+    true
+}
+
+/// 行情缓存设置，缓存保存每轮获取的行情，供异常价格保护等风控组件使用
+///
+/// 缓存在引擎启动时创建，修改后需要重启才能生效。
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct PriceCacheSettings {
+    /// 每个交易对保留的行情数量，应不小于异常价格保护的窗口大小
+    pub capacity_per_symbol: usize,
+    /// 行情的最长保留时间（秒），0表示只按数量淘汰
+    pub max_age_seconds: u64,
+    /// 所有交易对合计保留的行情条数上限，超出时淘汰最久未使用的交易对，0表示不限制
+    #[serde(default)]
+    pub max_total_records: usize,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+impl Default for PriceCacheSettings {
+    fn default() -> Self {
+        Self {
+            capacity_per_symbol: 200,
+            max_age_seconds: 600,
+            max_total_records: 0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
 pub struct StrategySettings {
     /// 启用的交易策略列表
     pub enabled_strategies: Vec<StrategyType>,
@@ -74,9 +736,18 @@ pub struct StrategySettings {
     
     /// 趋势跟踪策略设置
     pub trend_following: TrendFollowingStrategySettings,
+    
+    /// 组合策略设置
+    #[serde(default)]
+    pub composite: CompositeStrategySettings,
+    
+    /// 盘口流动性检查设置，作用于简单套利和趋势跟踪策略
+    #[serde(default)]
+    pub liquidity_guard: LiquidityGuardSettings,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
 pub struct TwapStrategySettings {
     /// 分割的订单数量
     pub slices: usize,
@@ -84,33 +755,107 @@ pub struct TwapStrategySettings {
     pub interval_seconds: u64,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
 pub struct OrderBookDepthStrategySettings {
     /// 要分析的订单簿深度（价格档位数量）
     pub depth_levels: usize,
     /// 最小流动性要求（以基础货币计）
-    pub min_liquidity: f64,
+    #[serde(with = "decimal_number")]
+    #[schemars(with = "f64")]
+    pub min_liquidity: Decimal,
+    /// 订单簿每一侧至少返回的价格档位数量，档位不足说明市场不活跃或数据不可靠，放弃机会；0表示不检查
+    pub min_book_levels: usize,
+    /// 计划交易量超过可见深度时估算超出部分滑点的模型，Disabled 表示深度不足时放弃机会
+    #[serde(default)]
+    pub slippage_model: SlippageModel,
+    /// 交易量等于可见深度时的最小滑点（百分比），可见档位的实际滑点更大时按实际滑点外推
+    #[serde(default = "default_min_impact_pct", with = "decimal_number")]
+    #[schemars(with = "f64")]
+    pub min_impact_pct: Decimal,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+/// 超出订单簿可见深度部分的滑点估算模型
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub enum SlippageModel {
+    /// 不估算，深度不足时放弃机会
+    #[default]
+    Disabled,
+    /// 滑点与交易量成正比
+    Linear,
+    /// 滑点与交易量的平方根成正比，大额交易的冲击增长较慢
+    SquareRoot,
+}
+
+fn default_min_impact_pct() -> Decimal {
+    dec!(0.1)
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
 pub struct SlippageControlStrategySettings {
     /// 最大允许的滑点百分比
-    pub max_slippage_pct: f64,
+    #[serde(with = "decimal_number")]
+    #[schemars(with = "f64")]
+    pub max_slippage_pct: Decimal,
     /// 历史价格波动率窗口大小
     pub volatility_window_size: usize,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
 pub struct TrendFollowingStrategySettings {
     /// 短期趋势窗口（数据点数量）
     pub short_window: usize,
     /// 长期趋势窗口（数据点数量）
     pub long_window: usize,
     /// 趋势判断阈值（百分比）
-    pub trend_threshold: f64,
+    #[serde(with = "decimal_number")]
+    #[schemars(with = "f64")]
+    pub trend_threshold: Decimal,
+    /// 短期和长期均线的计算方式
+    #[serde(default)]
+    pub trend_method: TrendMethod,
+    /// EMA平滑因子，平滑系数为 ema_smoothing / (窗口 + 1)，只在EMA模式下使用
+    #[serde(default = "default_ema_smoothing", with = "decimal_number")]
+    #[schemars(with = "f64")]
+    pub ema_smoothing: Decimal,
+}
+
+/// 趋势跟踪使用的均线
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub enum TrendMethod {
+    /// 简单移动平均，窗口内各数据点权重相同
+    #[default]
+    Sma,
+    /// 指数移动平均，近期数据点权重更高，对趋势变化反应更快
+    Ema,
+}
+
+fn default_ema_smoothing() -> Decimal {
+    dec!(2)
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct CompositeStrategySettings {
+    /// 参与投票的子策略及其权重
+    pub members: Vec<(StrategyType, f64)>,
+    /// 最低加权一致度 (0-1)，同方向信号的权重×置信度之和占总权重的比例达到该值才交易
+    pub min_agreement: f64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct LiquidityGuardSettings {
+    /// 是否在返回套利机会前检查订单簿盘口的挂单量
+    pub enabled: bool,
+    /// 参与计算的价格档位数量，1表示只看最优档
+    pub levels: usize,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
 pub struct RiskSettings {
     /// 启用的风控组件列表
     pub enabled_controllers: Vec<RiskControllerType>,
@@ -132,31 +877,69 @@ pub struct RiskSettings {
     
     /// 交易对黑名单设置
     pub pair_blacklist: PairBlacklistSettings,
+    
+    /// 最大同时持仓数设置
+    #[serde(default)]
+    pub open_positions: OpenPositionsSettings,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
 pub struct DailyLossLimitSettings {
     /// 每日最大亏损金额
-    pub max_daily_loss: f64,
+    #[serde(with = "decimal_number")]
+    #[schemars(with = "f64")]
+    pub max_daily_loss: Decimal,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
 pub struct AbnormalPriceSettings {
     /// 窗口大小（保留的价格记录数量）
     pub window_size: usize,
     /// 异常价格变化阈值（百分比）
-    pub abnormal_threshold: f64,
+    #[serde(with = "decimal_number")]
+    #[schemars(with = "f64")]
+    pub abnormal_threshold: Decimal,
     /// 冷却期（秒），在检测到异常后暂停交易的时间
     pub cooldown_period: i64,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
 pub struct ExposureSettings {
-    /// 币种最大风险敞口（以USDT计）
-    pub max_exposures: Vec<(String, f64)>,
+    /// 币种最大风险敞口（以USDT计），例如 `{"BTC": 5}`；也接受旧版的 `[["BTC", 5]]` 列表
+    #[serde(with = "decimal_number::map")]
+    #[schemars(with = "HashMap<String, f64>")]
+    pub max_exposures: HashMap<String, Decimal>,
+    /// 查询账户余额刷新持仓的时机
+    #[serde(default)]
+    pub update_mode: ExposureUpdateMode,
+    /// `Interval` 模式下两次查询余额的最短间隔（秒）
+    #[serde(default = "default_exposure_update_interval_seconds")]
+    pub update_interval_seconds: u64,
+}
+
+/// 风险敞口控制器刷新持仓的时机
+///
+/// 每次查询余额都是一次签名请求，检查间隔较短时可以改为定期或在交易结束后刷新。
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub enum ExposureUpdateMode {
+    /// 每次检查套利机会前查询余额
+    #[default]
+    EveryCheck,
+    /// 距离上次查询超过 `update_interval_seconds` 或有套利结束后才查询
+    Interval,
+    /// 只在第一次检查和每次套利结束后查询
+    OnTrade,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+fn default_exposure_update_interval_seconds() -> u64 {
+    30
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
 pub struct TradingTimeWindowSettings {
     /// 允许交易的开始时间 (24小时制，小时)
     pub start_hour: u32,
@@ -170,7 +953,8 @@ pub struct TradingTimeWindowSettings {
     pub trade_on_weekends: bool,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
 pub struct TradingFrequencySettings {
     /// 最小交易间隔（秒）
     pub min_interval_seconds: i64,
@@ -178,23 +962,74 @@ pub struct TradingFrequencySettings {
     pub max_trades_per_timeframe: usize,
     /// 时间窗口长度（秒）
     pub timeframe_seconds: i64,
+    /// 价差需要连续保持在 `min_spread_percentage` 以上的最短时长（毫秒），0表示不要求
+    #[serde(default)]
+    pub min_spread_persistence_ms: u64,
+    /// 价差持续要求使用的价差阈值（百分比），按机会的买卖方向计算
+    #[serde(default = "default_min_spread_percentage", with = "decimal_number")]
+    #[schemars(with = "f64")]
+    pub min_spread_percentage: Decimal,
+}
+
+fn default_min_spread_percentage() -> Decimal {
+    dec!(0.1)
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
 pub struct PairBlacklistSettings {
-    /// 黑名单交易对列表
+    /// 黑名单列表，每项可以是交易对（例如 BTCUSDT）、基础资产（例如 BTC，表示USDT和USDC两个交易对）
+    /// 或 `*USDC` 形式的通配符（表示该报价货币的所有交易对）
     pub blacklisted_pairs: Vec<String>,
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct OpenPositionsSettings {
+    /// 同时执行中的套利数量上限，多币种运行时按所有币种合计
+    pub max_open_positions: usize,
+}
+
+impl Default for OpenPositionsSettings {
+    fn default() -> Self {
+        Self {
+            max_open_positions: 1,
+        }
+    }
+}
+
 impl Default for ArbitrageSettings {
     fn default() -> Self {
         Self {
-            min_profit_percentage: 0.1,   // 0.1%最小利润率
-            max_trade_amount_usdt: 100.0, // 最大交易金额，USDT
-            price_diff_threshold: 0.05,   // 价格差异阈值，百分比
+            min_profit_percentage: dec!(0.1),   // 0.1%最小利润率
+            max_trade_amount_usdt: dec!(100), // 最大交易金额，USDT
+            price_diff_threshold: dec!(0.05),   // 价格差异阈值，百分比
             usdt_symbol: "BTCUSDT".to_string(),
             usdc_symbol: "BTCUSDC".to_string(),
             check_interval_ms: 1000,      // 检查间隔，毫秒
+            use_limit_orders: false,
+            time_in_force: TimeInForce::GTC,
+            buy_order_type: None,
+            sell_order_type: None,
+            limit_order_timeout_ms: default_limit_order_timeout_ms(),
+            system_status_check_interval_ms: 60_000,
+            db_health_check_interval_ms: default_db_health_check_interval_ms(),
+            max_consecutive_db_failures: 0,
+            dry_run: false,
+            price_cache: PriceCacheSettings::default(),
+            warmup_ticks: 0,
+            warmup_seconds: 0,
+            stale_order_cleanup_interval_ms: default_stale_order_cleanup_interval_ms(),
+            max_order_age_seconds: default_max_order_age_seconds(),
+            reserve_balance: default_reserve_balance(),
+            use_user_data_stream: default_use_user_data_stream(),
+            opportunity_ranking: OpportunityRanking::default(),
+            best_execution: BestExecutionSettings::default(),
+            dust_sweep: DustSweepSettings::default(),
+            inventory_skew: InventorySkewSettings::default(),
+            batch_price_fetch: default_batch_price_fetch(),
+            profit_distribution: ProfitDistributionSettings::default(),
+            opportunity_dedup: OpportunityDedupSettings::default(),
         }
     }
 }
@@ -203,23 +1038,76 @@ impl Default for StrategySettings {
     fn default() -> Self {
         Self {
             enabled_strategies: vec![StrategyType::Simple],
-            twap: TwapStrategySettings {
-                slices: 5,
-                interval_seconds: 60,
-            },
-            order_book_depth: OrderBookDepthStrategySettings {
-                depth_levels: 20,
-                min_liquidity: 1.0,
-            },
-            slippage_control: SlippageControlStrategySettings {
-                max_slippage_pct: 0.5,
-                volatility_window_size: 20,
-            },
-            trend_following: TrendFollowingStrategySettings {
-                short_window: 10,
-                long_window: 30,
-                trend_threshold: 1.0,
-            },
+            twap: TwapStrategySettings::default(),
+            order_book_depth: OrderBookDepthStrategySettings::default(),
+            slippage_control: SlippageControlStrategySettings::default(),
+            trend_following: TrendFollowingStrategySettings::default(),
+            composite: CompositeStrategySettings::default(),
+            liquidity_guard: LiquidityGuardSettings::default(),
+        }
+    }
+}
+
+impl Default for TwapStrategySettings {
+    fn default() -> Self {
+        Self {
+            slices: 5,
+            interval_seconds: 60,
+        }
+    }
+}
+
+impl Default for OrderBookDepthStrategySettings {
+    fn default() -> Self {
+        Self {
+            depth_levels: 20,
+            min_liquidity: dec!(1),
+            min_book_levels: 0,
+            slippage_model: SlippageModel::Disabled,
+            min_impact_pct: default_min_impact_pct(),
+        }
+    }
+}
+
+impl Default for SlippageControlStrategySettings {
+    fn default() -> Self {
+        Self {
+            max_slippage_pct: dec!(0.5),
+            volatility_window_size: 20,
+        }
+    }
+}
+
+impl Default for TrendFollowingStrategySettings {
+    fn default() -> Self {
+        Self {
+            short_window: 10,
+            long_window: 30,
+            trend_threshold: dec!(1),
+            trend_method: TrendMethod::Sma,
+            ema_smoothing: default_ema_smoothing(),
+        }
+    }
+}
+
+impl Default for LiquidityGuardSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            levels: 1,
+        }
+    }
+}
+
+impl Default for CompositeStrategySettings {
+    fn default() -> Self {
+        Self {
+            members: vec![
+                (StrategyType::Simple, 1.0),
+                (StrategyType::SlippageControl, 1.0),
+                (StrategyType::TrendFollowing, 1.0),
+            ],
+            min_agreement: 0.6,
         }
     }
 }
@@ -231,67 +1119,1357 @@ impl Default for RiskSettings {
                 RiskControllerType::DailyLossLimit,
                 RiskControllerType::AbnormalPrice,
             ],
-            daily_loss_limit: DailyLossLimitSettings {
-                max_daily_loss: 50.0,
-            },
-            abnormal_price: AbnormalPriceSettings {
-                window_size: 30,
-                abnormal_threshold: 5.0,
-                cooldown_period: 300,
-            },
-            exposure: ExposureSettings {
-                max_exposures: vec![
-                    ("BTC".to_string(), 5.0),
-                    ("ETH".to_string(), 50.0),
-                ],
-            },
-            trading_time_window: TradingTimeWindowSettings {
-                start_hour: 0,
-                start_minute: 0,
-                end_hour: 23,
-                end_minute: 59,
-                trade_on_weekends: true,
-            },
-            trading_frequency: TradingFrequencySettings {
-                min_interval_seconds: 30,
-                max_trades_per_timeframe: 10,
-                timeframe_seconds: 600,
-            },
-            pair_blacklist: PairBlacklistSettings {
-                blacklisted_pairs: vec![],
-            },
+            daily_loss_limit: DailyLossLimitSettings::default(),
+            abnormal_price: AbnormalPriceSettings::default(),
+            exposure: ExposureSettings::default(),
+            trading_time_window: TradingTimeWindowSettings::default(),
+            trading_frequency: TradingFrequencySettings::default(),
+            pair_blacklist: PairBlacklistSettings::default(),
+            open_positions: OpenPositionsSettings::default(),
         }
     }
 }
 
+impl Default for DailyLossLimitSettings {
+    fn default() -> Self {
+        Self {
+            max_daily_loss: dec!(50),
+        }
+    }
+}
+
+impl Default for AbnormalPriceSettings {
+    fn default() -> Self {
+        Self {
+            window_size: 30,
+            abnormal_threshold: dec!(5),
+            cooldown_period: 300,
+        }
+    }
+}
+
+impl Default for ExposureSettings {
+    fn default() -> Self {
+        Self {
+            max_exposures: HashMap::from([
+                ("BTC".to_string(), dec!(5)),
+                ("ETH".to_string(), dec!(50)),
+            ]),
+            update_mode: ExposureUpdateMode::default(),
+            update_interval_seconds: default_exposure_update_interval_seconds(),
+        }
+    }
+}
+
+impl Default for TradingTimeWindowSettings {
+    fn default() -> Self {
+        Self {
+            start_hour: 0,
+            start_minute: 0,
+            end_hour: 23,
+            end_minute: 59,
+            trade_on_weekends: true,
+        }
+    }
+}
+
+impl Default for TradingFrequencySettings {
+    fn default() -> Self {
+        Self {
+            min_interval_seconds: 30,
+            max_trades_per_timeframe: 10,
+            timeframe_seconds: 600,
+            min_spread_persistence_ms: 0,
+            min_spread_percentage: default_min_spread_percentage(),
+        }
+    }
+}
+
+/// 配置项校验错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigError {
+    /// 出错字段的路径，例如 `strategy_settings.twap.slices`
+    pub field: String,
+    /// 错误说明
+    pub message: String,
+}
+
+impl ConfigError {
+    /// 将多个校验错误合并为一个错误，每条一行
+    pub fn combine(errors: Vec<ConfigError>) -> anyhow::Error {
+        let lines: Vec<String> = errors.iter().map(ToString::to_string).collect();
+        anyhow!("{} 项配置错误:\n{}", errors.len(), lines.join("\n"))
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.field, self.message)
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// 校验过程中收集的错误
+#[derive(Default)]
+struct ConfigErrors(Vec<ConfigError>);
+
+impl ConfigErrors {
+    fn push(&mut self, field: &str, message: impl Into<String>) {
+        self.0.push(ConfigError {
+            field: field.to_string(),
+            message: message.into(),
+        });
+    }
+    
+    /// `ok` 为false时记录错误
+    fn check(&mut self, ok: bool, field: &str, message: impl Into<String>) {
+        if !ok {
+            self.push(field, message);
+        }
+    }
+    
+    fn into_result(self) -> std::result::Result<(), Vec<ConfigError>> {
+        if self.0.is_empty() {
+            Ok(())
+        } else {
+            Err(self.0)
+        }
+    }
+}
+
+/// 金额、比例等 Decimal 配置字段的序列化方式
+///
+/// 反序列化同时接受数字和字符串（`0.1` 和 `"0.1"`），数字按其最短的十进制表示转换，0.1 得到精确的 0.1；
+/// 序列化为数字，配置文件格式、配置差异和配置指纹与使用 f64 时保持一致。
+mod decimal_number {
+    use rust_decimal::Decimal;
+    use rust_decimal::prelude::ToPrimitive;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &Decimal, serializer: S) -> Result<S::Ok, S::Error> {
+        match value.to_f64() {
+            Some(number) => serializer.serialize_f64(number),
+            None => serializer.serialize_str(&value.to_string()),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Decimal, D::Error> {
+        <Decimal as Deserialize>::deserialize(deserializer)
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct Pair(String, #[serde(with = "super::decimal_number")] Decimal);
+
+    #[derive(Deserialize)]
+    struct Number(#[serde(with = "super::decimal_number")] Decimal);
+
+    /// 名称到数值的映射，例如各币种的最大风险敞口
+    ///
+    /// 序列化为按名称排序的对象；反序列化同时接受对象和旧版的 `[名称, 数值]` 列表，名称重复时报错。
+    pub mod map {
+        use super::{Number, Pair};
+        use rust_decimal::Decimal;
+        use serde::de::{self, MapAccess, SeqAccess, Visitor};
+        use serde::{Deserializer, Serializer};
+        use std::collections::{BTreeMap, HashMap};
+        use std::fmt;
+
+        pub fn serialize<S: Serializer>(values: &HashMap<String, Decimal>, serializer: S) -> Result<S::Ok, S::Error> {
+            let sorted: BTreeMap<&String, &Decimal> = values.iter().collect();
+            serializer.collect_map(sorted.into_iter().map(|(name, value)| (name, super::Serialized(*value))))
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<HashMap<String, Decimal>, D::Error> {
+            deserializer.deserialize_any(MapVisitor)
+        }
+
+        struct MapVisitor;
+
+        fn insert<E: de::Error>(values: &mut HashMap<String, Decimal>, name: String, value: Decimal) -> Result<(), E> {
+            if values.contains_key(&name) {
+                return Err(E::custom(format!("重复的名称: {}", name)));
+            }
+            values.insert(name, value);
+            Ok(())
+        }
+
+        impl<'de> Visitor<'de> for MapVisitor {
+            type Value = HashMap<String, Decimal>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("名称到数值的对象，或 [名称, 数值] 组成的列表")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut access: A) -> Result<Self::Value, A::Error> {
+                let mut values = HashMap::new();
+                while let Some((name, Number(value))) = access.next_entry::<String, Number>()? {
+                    insert(&mut values, name, value)?;
+                }
+                Ok(values)
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut access: A) -> Result<Self::Value, A::Error> {
+                let mut values = HashMap::new();
+                while let Some(Pair(name, value)) = access.next_element::<Pair>()? {
+                    insert(&mut values, name, value)?;
+                }
+                Ok(values)
+            }
+        }
+    }
+
+    /// 按 `decimal_number` 序列化的数值
+    struct Serialized(Decimal);
+
+    impl Serialize for Serialized {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serialize(&self.0, serializer)
+        }
+    }
+}
+
+fn non_negative(value: Decimal) -> bool {
+    value >= Decimal::ZERO
+}
+
+fn positive(value: Decimal) -> bool {
+    value > Decimal::ZERO
+}
+
+/// 是否为大写字母和数字组成的基础资产加 `quote` 的交易对，例如 BTCUSDT
+fn is_pair(symbol: &str, quote: &str) -> bool {
+    symbol
+        .strip_suffix(quote)
+        .is_some_and(|base| !base.is_empty() && base.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit()))
+}
+
+/// 选择配置档的环境变量，命令行参数 `--profile` 优先
+pub const PROFILE_ENV: &str = "BINANCE_PROFILE";
+
 impl Config {
     pub fn new() -> Result<Self> {
         dotenv().ok();
         
         let api_key = env::var("BINANCE_API_KEY")
             .context("BINANCE_API_KEY not set in environment or .env file")?;
-        let api_secret = env::var("BINANCE_API_SECRET")
-            .context("BINANCE_API_SECRET not set in environment or .env file")?;
+        let secret = SecretSettings::from_env()?;
+        let api_secret = match secret.secret_source {
+            SecretSource::Inline => env::var("BINANCE_API_SECRET")
+                .context("BINANCE_API_SECRET not set in environment or .env file")?,
+            _ => String::new(),
+        };
         let base_url = env::var("BINANCE_API_URL")
             .unwrap_or_else(|_| "https://api.binance.com".to_string());
+        let trace_api = env::var("BINANCE_TRACE_API")
+            .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+            .unwrap_or(false);
+        let admin_api = AdminApiSettings {
+            bearer_token: env::var("ADMIN_API_TOKEN").unwrap_or_default(),
+            ..AdminApiSettings::default()
+        };
             
-        Ok(Config {
+        let mut config = Config {
             api_key,
             api_secret,
+            secret,
             base_url,
+            fallback_base_urls: Vec::new(),
             arbitrage_settings: ArbitrageSettings::default(),
             strategy_settings: StrategySettings::default(),
             risk_settings: RiskSettings::default(),
-        })
+            trace_api,
+            admin_api,
+            report_delivery: ReportDeliverySettings::default(),
+            report_language: Locale::default(),
+            api_latency: ApiLatencySettings::default(),
+            fees: FeeSettings::default(),
+            execution: ExecutionSettings::default(),
+            profiles: BTreeMap::new(),
+            active_profile: None,
+        };
+        config.validate().map_err(ConfigError::combine)?;
+        config.resolve_secret()?;
+        Ok(config)
     }
     
+    /// 读取并校验配置文件，`secret_source` 不为 `Inline` 时从对应来源读取API私钥
     pub fn from_file(path: &str) -> Result<Self> {
+        Self::from_file_with_profile(path, None)
+    }
+    
+    /// 读取配置文件并合并 `profile` 配置档的设置，校验合并后的配置
+    pub fn from_file_with_profile(path: &str, profile: Option<&str>) -> Result<Self> {
+        let mut config = Self::from_file_unchecked_with_profile(path, profile)?;
+        config.validate().map_err(ConfigError::combine).context(format!("配置文件无效: {}", path))?;
+        config.resolve_secret()?;
+        Ok(config)
+    }
+    
+    /// 读取配置文件但不校验参数，也不读取文件、加密或钥匙串中的API私钥，用于 `check-config` 列出全部错误和热加载配置
+    pub fn from_file_unchecked(path: &str) -> Result<Self> {
+        Self::from_file_unchecked_with_profile(path, None)
+    }
+    
+    /// 同 `from_file_unchecked`，合并 `profile` 配置档的设置
+    ///
+    /// 设置的优先级从低到高为：文件中的基础设置、配置档、环境变量、命令行参数。
+    pub fn from_file_unchecked_with_profile(path: &str, profile: Option<&str>) -> Result<Self> {
         let mut file = File::open(path)
             .context(format!("Failed to open config file: {}", path))?;
         let mut contents = String::new();
         file.read_to_string(&mut contents)
             .context(format!("Failed to read config file: {}", path))?;
         
-        serde_json::from_str(&contents).context("Failed to parse config JSON")
+        let mut config = Self::from_json_with_profile(&contents, profile)?;
+        config.fill_credentials_from_env();
+        Ok(config)
+    }
+    
+    /// 配置文件未填写API密钥时使用环境变量（或 .env 文件）中的 BINANCE_API_KEY 和 BINANCE_API_SECRET
+    fn fill_credentials_from_env(&mut self) {
+        dotenv().ok();
+        if self.api_key.is_empty() {
+            self.api_key = env::var("BINANCE_API_KEY").unwrap_or_default();
+        }
+        if self.api_secret.is_empty() && self.secret.secret_source == SecretSource::Inline {
+            self.api_secret = env::var("BINANCE_API_SECRET").unwrap_or_default();
+        }
+    }
+    
+    /// 按 `secret_source` 从私钥文件、加密私钥或系统钥匙串读取API私钥，`Inline` 来源不做处理
+    pub fn resolve_secret(&mut self) -> Result<()> {
+        if let Some(api_secret) = self.secret.resolve(&self.api_key)? {
+            self.api_secret = api_secret;
+        }
+        Ok(())
+    }
+    
+    /// 用于输出的JSON值，私钥来自其他来源时不输出明文
+    fn serializable_value(&self) -> Result<serde_json::Value> {
+        let mut value = serde_json::to_value(self).context("配置序列化失败")?;
+        if self.secret.secret_source != SecretSource::Inline {
+            value["api_secret"] = serde_json::Value::String(String::new());
+        }
+        Ok(value)
+    }
+    
+    /// 解析配置文件格式的JSON内容，不校验参数
+    ///
+    /// 省略的字段和设置组取默认值，可以只写需要修改的部分。
+    pub fn from_json(contents: &str) -> Result<Self> {
+        serde_json::from_str(contents).context("Failed to parse config JSON")
+    }
+    
+    /// 解析配置文件格式的JSON内容，将 `profile` 配置档合并到基础设置上，不校验参数
+    ///
+    /// 配置档不存在时返回错误并列出可用的配置档。
+    pub fn from_json_with_profile(contents: &str, profile: Option<&str>) -> Result<Self> {
+        let Some(name) = profile else {
+            return Self::from_json(contents);
+        };
+        
+        let mut value: serde_json::Value = serde_json::from_str(contents).context("Failed to parse config JSON")?;
+        let overrides = value
+            .get("profiles")
+            .and_then(|profiles| profiles.get(name))
+            .cloned()
+            .ok_or_else(|| {
+                let available: Vec<&str> = value
+                    .get("profiles")
+                    .and_then(|profiles| profiles.as_object())
+                    .map(|profiles| profiles.keys().map(String::as_str).collect())
+                    .unwrap_or_default();
+                anyhow!("配置档 {} 不存在，可用的配置档: [{}]", name, available.join(", "))
+            })?;
+        if !overrides.is_object() {
+            return Err(anyhow!("配置档 {} 必须是JSON对象", name));
+        }
+        merge_values(&mut value, overrides);
+        
+        let mut config: Config = serde_json::from_value(value)
+            .context(format!("合并配置档 {} 后解析配置失败", name))?;
+        config.active_profile = Some(name.to_string());
+        Ok(config)
+    }
+    
+    /// 输出为配置文件格式的JSON，`from_file` 可以读回相同的配置
+    ///
+    /// `secret_source` 不为 `Inline` 时 `api_secret` 输出为空字符串，加载时读取的私钥明文不会写回配置文件。
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(&self.serializable_value()?).context("配置序列化失败")
+    }
+    
+    /// 输出为配置文件格式的JSON，密钥和访问令牌替换为 `******`，用于 `config show`
+    pub fn to_redacted_json(&self) -> Result<String> {
+        let mut value = self.serializable_value()?;
+        for path in SECRET_FIELDS {
+            redact_value(&mut value, path);
+            // 配置档中也可能填写密钥
+            if let Some(profiles) = value.get_mut("profiles").and_then(|profiles| profiles.as_object_mut()) {
+                for profile in profiles.values_mut() {
+                    redact_value(profile, path);
+                }
+            }
+        }
+        serde_json::to_string_pretty(&value).context("配置序列化失败")
+    }
+    
+    /// 全部使用默认值的配置，API密钥为占位符，用于 `config generate` 生成配置文件模板
+    ///
+    /// 包括所有策略和风控组件的设置，未启用的组件也会列出默认参数。
+    pub fn template() -> Self {
+        Config {
+            api_key: "YOUR_BINANCE_API_KEY".to_string(),
+            api_secret: "YOUR_BINANCE_API_SECRET".to_string(),
+            ..Config::default()
+        }
+    }
+    
+    /// 检查配置参数是否有效，启动、加载配置文件和热加载配置时调用
+    ///
+    /// 逐项检查所有设置，一次返回全部不符合要求的字段，而不是遇到第一个错误就停止。
+    pub fn validate(&self) -> std::result::Result<(), Vec<ConfigError>> {
+        let mut errors = ConfigErrors::default();
+        self.validate_arbitrage_settings(&mut errors);
+        self.validate_strategy_settings(&mut errors);
+        self.validate_risk_settings(&mut errors);
+        
+        errors.check(
+            !self.api_latency.enabled || self.api_latency.window_size > 0,
+            "api_latency.window_size",
+            "API耗时统计的窗口大小必须大于0",
+        );
+        if self.report_delivery.enabled {
+            if let Err(e) = self.report_delivery.validate() {
+                errors.push("report_delivery", e.to_string());
+            }
+        }
+        
+        for (index, url) in self.fallback_base_urls.iter().enumerate() {
+            errors.check(
+                url.starts_with("https://") || url.starts_with("http://"),
+                &format!("fallback_base_urls[{}]", index),
+                format!("备用API地址必须以 http:// 或 https:// 开头: {}", url),
+            );
+        }
+        
+        let has_value = |value: &Option<String>| value.as_deref().is_some_and(|value| !value.trim().is_empty());
+        errors.check(
+            self.secret.secret_source != SecretSource::File || has_value(&self.secret.api_secret_file),
+            "api_secret_file",
+            "secret_source 为 File 时必须设置私钥文件路径",
+        );
+        errors.check(
+            self.secret.secret_source != SecretSource::Encrypted || has_value(&self.secret.api_secret_encrypted),
+            "api_secret_encrypted",
+            "secret_source 为 Encrypted 时必须设置加密私钥（由 config encrypt-secret 生成）",
+        );
+        
+        for (field, rate) in [("fees.maker_rate", self.fees.maker_rate), ("fees.taker_rate", self.fees.taker_rate)] {
+            errors.check(
+                non_negative(rate) && rate <= dec!(0.01),
+                field,
+                format!("手续费率应在0到0.01（1%）之间: {}", rate),
+            );
+        }
+        errors.check(
+            non_negative(self.fees.fee_asset_discount) && self.fees.fee_asset_discount < Decimal::ONE,
+            "fees.fee_asset_discount",
+            format!("手续费抵扣折扣应在0到1之间: {}", self.fees.fee_asset_discount),
+        );
+        errors.check(
+            non_negative(self.fees.fixed_cost_per_trade),
+            "fees.fixed_cost_per_trade",
+            format!("每笔套利的固定成本不能为负数: {}", self.fees.fixed_cost_per_trade),
+        );
+        errors.check(
+            self.fees.min_notional_multiple >= Decimal::ONE,
+            "fees.min_notional_multiple",
+            format!("最小交易金额的倍数不能小于1: {}", self.fees.min_notional_multiple),
+        );
+        errors.check(
+            non_negative(self.execution.max_fill_deviation_pct) && self.execution.max_fill_deviation_pct < dec!(100),
+            "execution.max_fill_deviation_pct",
+            format!("成交价格最大偏离应在0到100之间: {}", self.execution.max_fill_deviation_pct),
+        );
+        
+        errors.into_result()
+    }
+    
+    /// 买入、卖出订单的类型和限价单的有效方式
+    ///
+    /// 单边的 `buy_order_type` / `sell_order_type` 优先，其次是 `execution.order_type`，都未设置时按 `use_limit_orders`；
+    /// `execution.time_in_force` 设置后覆盖 `arbitrage_settings.time_in_force`。
+    pub fn order_types(&self) -> (OrderType, OrderType, TimeInForce) {
+        let arbitrage = &self.arbitrage_settings;
+        let default_type = self.execution.order_type.unwrap_or(if arbitrage.use_limit_orders { OrderType::Limit } else { OrderType::Market });
+        (
+            arbitrage.buy_order_type.unwrap_or(default_type),
+            arbitrage.sell_order_type.unwrap_or(default_type),
+            self.execution.time_in_force.unwrap_or(arbitrage.time_in_force),
+        )
+    }
+    
+    /// 按下单方式估算的买卖两边手续费率之和（百分比），机会的利润率低于该值时不足以覆盖手续费
+    pub fn round_trip_fee_percentage(&self) -> Decimal {
+        let (buy_type, sell_type, time_in_force) = self.order_types();
+        (self.fees.leg_rate(buy_type, time_in_force) + self.fees.leg_rate(sell_type, time_in_force)) * dec!(100)
+    }
+    
+    fn validate_arbitrage_settings(&self, errors: &mut ConfigErrors) {
+        let arbitrage = &self.arbitrage_settings;
+        errors.check(
+            non_negative(arbitrage.min_profit_percentage),
+            "arbitrage_settings.min_profit_percentage",
+            format!("最小利润率不能为负数: {}", arbitrage.min_profit_percentage),
+        );
+        errors.check(
+            positive(arbitrage.max_trade_amount_usdt),
+            "arbitrage_settings.max_trade_amount_usdt",
+            format!("最大交易金额必须大于0: {}", arbitrage.max_trade_amount_usdt),
+        );
+        errors.check(
+            non_negative(arbitrage.price_diff_threshold),
+            "arbitrage_settings.price_diff_threshold",
+            format!("价格差异阈值不能为负数: {}", arbitrage.price_diff_threshold),
+        );
+        for (field, symbol, quote) in [
+            ("arbitrage_settings.usdt_symbol", &arbitrage.usdt_symbol, "USDT"),
+            ("arbitrage_settings.usdc_symbol", &arbitrage.usdc_symbol, "USDC"),
+        ] {
+            errors.check(
+                is_pair(symbol, quote),
+                field,
+                format!("交易对 {:?} 无效，应为基础资产加 {}，例如 BTC{}", symbol, quote, quote),
+            );
+        }
+        for (field, interval) in [
+            ("arbitrage_settings.check_interval_ms", arbitrage.check_interval_ms),
+            ("arbitrage_settings.system_status_check_interval_ms", arbitrage.system_status_check_interval_ms),
+            ("arbitrage_settings.db_health_check_interval_ms", arbitrage.db_health_check_interval_ms),
+        ] {
+            errors.check(interval > 0, field, "检查间隔必须大于0");
+        }
+        errors.check(
+            arbitrage.limit_order_timeout_ms > 0,
+            "arbitrage_settings.limit_order_timeout_ms",
+            "限价单等待时间必须大于0",
+        );
+        errors.check(
+            arbitrage.stale_order_cleanup_interval_ms == 0 || arbitrage.max_order_age_seconds > 0,
+            "arbitrage_settings.max_order_age_seconds",
+            "启用过期挂单清理时最长挂单时间必须大于0",
+        );
+        errors.check(
+            arbitrage.price_cache.capacity_per_symbol > 0,
+            "arbitrage_settings.price_cache.capacity_per_symbol",
+            "行情缓存容量必须大于0",
+        );
+        errors.check(
+            arbitrage.price_cache.max_total_records == 0
+                || arbitrage.price_cache.max_total_records >= arbitrage.price_cache.capacity_per_symbol,
+            "arbitrage_settings.price_cache.max_total_records",
+            "行情总条数上限不能小于每个交易对的行情数量",
+        );
+        
+        let dust_sweep = &arbitrage.dust_sweep;
+        errors.check(
+            !dust_sweep.enabled || dust_sweep.interval_ms > 0,
+            "arbitrage_settings.dust_sweep.interval_ms",
+            "启用小额资产清理时检查间隔必须大于0",
+        );
+        errors.check(
+            non_negative(dust_sweep.dust_threshold),
+            "arbitrage_settings.dust_sweep.dust_threshold",
+            format!("小额资产数量上限必须为非负数: {}", dust_sweep.dust_threshold),
+        );
+        
+        let best_execution = &arbitrage.best_execution;
+        for (field, fee_rate) in [
+            ("arbitrage_settings.best_execution.usdt_fee_rate", best_execution.usdt_fee_rate),
+            ("arbitrage_settings.best_execution.usdc_fee_rate", best_execution.usdc_fee_rate),
+        ] {
+            errors.check((Decimal::ZERO..Decimal::ONE).contains(&fee_rate), field, format!("手续费率应在0到1之间: {}", fee_rate));
+        }
+        errors.check(
+            !best_execution.enabled || !best_execution.conversion_symbol.trim().is_empty(),
+            "arbitrage_settings.best_execution.conversion_symbol",
+            "启用最优执行时USDC兑USDT的交易对不能为空",
+        );
+        
+        let inventory_skew = &arbitrage.inventory_skew;
+        errors.check(
+            (Decimal::ZERO..=Decimal::ONE).contains(&inventory_skew.target_usdt_ratio),
+            "arbitrage_settings.inventory_skew.target_usdt_ratio",
+            format!("USDT目标比例应在0到1之间: {}", inventory_skew.target_usdt_ratio),
+        );
+        errors.check(
+            non_negative(inventory_skew.skew_factor),
+            "arbitrage_settings.inventory_skew.skew_factor",
+            format!("库存偏斜力度必须为非负数: {}", inventory_skew.skew_factor),
+        );
+        errors.check(
+            inventory_skew.max_multiplier >= Decimal::ONE,
+            "arbitrage_settings.inventory_skew.max_multiplier",
+            format!("库存偏斜的最大倍数不能小于1: {}", inventory_skew.max_multiplier),
+        );
+        
+        errors.check(
+            arbitrage.opportunity_dedup.spread_decimal_places <= 8,
+            "arbitrage_settings.opportunity_dedup.spread_decimal_places",
+            format!("利润率的小数位数不能超过8: {}", arbitrage.opportunity_dedup.spread_decimal_places),
+        );
+        
+        let distribution = &arbitrage.profit_distribution;
+        errors.check(
+            positive(distribution.threshold_usdt),
+            "arbitrage_settings.profit_distribution.threshold_usdt",
+            format!("利润分配阈值必须大于0: {}", distribution.threshold_usdt),
+        );
+        errors.check(
+            distribution.portion > Decimal::ZERO && distribution.portion <= Decimal::ONE,
+            "arbitrage_settings.profit_distribution.portion",
+            format!("利润分配比例应在0到1之间（不含0）: {}", distribution.portion),
+        );
+        if distribution.enabled && distribution.transfer_enabled {
+            errors.check(
+                !distribution.asset.trim().is_empty(),
+                "arbitrage_settings.profit_distribution.asset",
+                "划转的币种不能为空",
+            );
+            errors.check(
+                distribution.to_email.contains('@'),
+                "arbitrage_settings.profit_distribution.to_email",
+                format!("启用划转时需要设置子账户邮箱: {:?}", distribution.to_email),
+            );
+        }
+    }
+    
+    fn validate_strategy_settings(&self, errors: &mut ConfigErrors) {
+        let strategies = &self.strategy_settings;
+        errors.check(strategies.twap.slices > 0, "strategy_settings.twap.slices", "TWAP分割的订单数量必须大于0");
+        
+        let depth = &strategies.order_book_depth;
+        errors.check(
+            depth.depth_levels > 0,
+            "strategy_settings.order_book_depth.depth_levels",
+            "订单簿分析的深度档位必须大于0",
+        );
+        errors.check(
+            non_negative(depth.min_liquidity),
+            "strategy_settings.order_book_depth.min_liquidity",
+            format!("最小流动性要求不能为负数: {}", depth.min_liquidity),
+        );
+        errors.check(
+            depth.min_book_levels <= depth.depth_levels,
+            "strategy_settings.order_book_depth.min_book_levels",
+            format!("最少档位数量 {} 不能大于获取的深度档位 {}", depth.min_book_levels, depth.depth_levels),
+        );
+        errors.check(
+            non_negative(depth.min_impact_pct),
+            "strategy_settings.order_book_depth.min_impact_pct",
+            format!("最小冲击滑点不能为负数: {}", depth.min_impact_pct),
+        );
+        
+        let slippage = &strategies.slippage_control;
+        errors.check(
+            non_negative(slippage.max_slippage_pct),
+            "strategy_settings.slippage_control.max_slippage_pct",
+            format!("最大允许滑点不能为负数: {}", slippage.max_slippage_pct),
+        );
+        errors.check(
+            slippage.volatility_window_size > 0,
+            "strategy_settings.slippage_control.volatility_window_size",
+            "波动率窗口大小必须大于0",
+        );
+        
+        let trend = &strategies.trend_following;
+        errors.check(trend.short_window > 0, "strategy_settings.trend_following.short_window", "短期趋势窗口必须大于0");
+        errors.check(
+            trend.short_window < trend.long_window,
+            "strategy_settings.trend_following.long_window",
+            format!("长期趋势窗口 {} 必须大于短期趋势窗口 {}", trend.long_window, trend.short_window),
+        );
+        errors.check(
+            non_negative(trend.trend_threshold),
+            "strategy_settings.trend_following.trend_threshold",
+            format!("趋势判断阈值不能为负数: {}", trend.trend_threshold),
+        );
+        errors.check(
+            positive(trend.ema_smoothing),
+            "strategy_settings.trend_following.ema_smoothing",
+            format!("EMA平滑因子必须大于0: {}", trend.ema_smoothing),
+        );
+        
+        let composite = &strategies.composite;
+        errors.check(
+            (0.0..=1.0).contains(&composite.min_agreement),
+            "strategy_settings.composite.min_agreement",
+            format!("组合策略的最低一致度应在0到1之间: {}", composite.min_agreement),
+        );
+        for (index, (member, weight)) in composite.members.iter().enumerate() {
+            let field = format!("strategy_settings.composite.members[{}]", index);
+            errors.check(weight.is_finite() && *weight >= 0.0, &field, format!("子策略 {:?} 的权重不能为负数: {}", member, weight));
+            errors.check(*member != StrategyType::Composite, &field, "组合策略不能嵌套组合策略");
+        }
+        
+        errors.check(
+            strategies.liquidity_guard.levels > 0,
+            "strategy_settings.liquidity_guard.levels",
+            "流动性检查的价格档位数量必须大于0",
+        );
+    }
+    
+    fn validate_risk_settings(&self, errors: &mut ConfigErrors) {
+        let risk = &self.risk_settings;
+        errors.check(
+            positive(risk.daily_loss_limit.max_daily_loss),
+            "risk_settings.daily_loss_limit.max_daily_loss",
+            format!("每日最大亏损金额必须大于0: {}", risk.daily_loss_limit.max_daily_loss),
+        );
+        
+        let abnormal = &risk.abnormal_price;
+        errors.check(abnormal.window_size > 0, "risk_settings.abnormal_price.window_size", "异常价格保护的窗口大小必须大于0");
+        errors.check(
+            positive(abnormal.abnormal_threshold),
+            "risk_settings.abnormal_price.abnormal_threshold",
+            format!("异常价格变化阈值必须大于0: {}", abnormal.abnormal_threshold),
+        );
+        errors.check(
+            abnormal.cooldown_period >= 0,
+            "risk_settings.abnormal_price.cooldown_period",
+            format!("冷却期不能为负数: {}", abnormal.cooldown_period),
+        );
+        
+        let mut exposures: Vec<_> = risk.exposure.max_exposures.iter().collect();
+        exposures.sort();
+        for (asset, limit) in exposures {
+            let field = format!("risk_settings.exposure.max_exposures[{:?}]", asset);
+            errors.check(!asset.trim().is_empty(), &field, "币种不能为空");
+            errors.check(positive(*limit), &field, format!("{} 的最大风险敞口必须大于0: {}", asset, limit));
+        }
+        errors.check(
+            risk.exposure.update_mode != ExposureUpdateMode::Interval || risk.exposure.update_interval_seconds > 0,
+            "risk_settings.exposure.update_interval_seconds",
+            "按间隔刷新持仓时间隔必须大于0",
+        );
+        
+        let window = &risk.trading_time_window;
+        errors.check(window.start_hour <= 23, "risk_settings.trading_time_window.start_hour", format!("小时应在0到23之间: {}", window.start_hour));
+        errors.check(window.end_hour <= 23, "risk_settings.trading_time_window.end_hour", format!("小时应在0到23之间: {}", window.end_hour));
+        errors.check(window.start_minute <= 59, "risk_settings.trading_time_window.start_minute", format!("分钟应在0到59之间: {}", window.start_minute));
+        errors.check(window.end_minute <= 59, "risk_settings.trading_time_window.end_minute", format!("分钟应在0到59之间: {}", window.end_minute));
+        errors.check(
+            (window.start_hour, window.start_minute) != (window.end_hour, window.end_minute),
+            "risk_settings.trading_time_window",
+            format!("开始和结束时间相同 ({:02}:{:02})，每天只能交易一分钟", window.start_hour, window.start_minute),
+        );
+        
+        let frequency = &risk.trading_frequency;
+        errors.check(
+            frequency.timeframe_seconds > 0,
+            "risk_settings.trading_frequency.timeframe_seconds",
+            format!("时间窗口长度必须大于0: {}", frequency.timeframe_seconds),
+        );
+        errors.check(
+            frequency.min_interval_seconds >= 0,
+            "risk_settings.trading_frequency.min_interval_seconds",
+            format!("最小交易间隔不能为负数: {}", frequency.min_interval_seconds),
+        );
+        errors.check(
+            frequency.max_trades_per_timeframe > 0,
+            "risk_settings.trading_frequency.max_trades_per_timeframe",
+            "单位时间最大交易次数必须大于0",
+        );
+        errors.check(
+            non_negative(frequency.min_spread_percentage),
+            "risk_settings.trading_frequency.min_spread_percentage",
+            format!("价差持续要求的价差阈值必须为非负数: {}", frequency.min_spread_percentage),
+        );
+        
+        for (index, pair) in risk.pair_blacklist.blacklisted_pairs.iter().enumerate() {
+            if let Err(e) = pair.parse::<BlacklistEntry>() {
+                errors.push(&format!("risk_settings.pair_blacklist.blacklisted_pairs[{}]", index), e);
+            }
+        }
+        
+        errors.check(
+            risk.open_positions.max_open_positions > 0,
+            "risk_settings.open_positions.max_open_positions",
+            "最大持仓数必须大于0",
+        );
+    }
+    
+    /// 与另一份配置逐项比较，返回变化的字段，例如 `arbitrage_settings.check_interval_ms: 1000 -> 500`
+    ///
+    /// 密钥和访问令牌只提示已修改，不输出具体值。
+    pub fn diff(&self, other: &Config) -> Vec<String> {
+        let mut changes = Vec::new();
+        match (serde_json::to_value(self), serde_json::to_value(other)) {
+            (Ok(old), Ok(new)) => diff_values("", &old, &new, &mut changes),
+            _ => changes.push("无法比较配置".to_string()),
+        }
+        changes
+    }
+    
+    /// 描述完整配置结构的JSON Schema，包括策略和风控组件的可选值及字段说明，可用于编辑器校验和补全配置文件
+    pub fn json_schema() -> serde_json::Value {
+        serde_json::to_value(schemars::schema_for!(Config)).expect("JSON Schema 序列化失败")
+    }
+    
+    /// 交易相关配置（套利、策略、风控）的指纹，用于区分不同参数下产生的交易记录
+    ///
+    /// 对三组配置的JSON表示计算SHA-256，取前16位十六进制字符。JSON对象按键名排序，
+    /// 因此配置文件中字段的书写顺序不影响结果；密钥、接口地址等不参与计算。
+    pub fn fingerprint(&self) -> String {
+        let value = serde_json::json!({
+            "arbitrage_settings": self.arbitrage_settings,
+            "strategy_settings": self.strategy_settings,
+            "risk_settings": self.risk_settings,
+        });
+        let digest = Sha256::digest(value.to_string().as_bytes());
+        let mut hash = hex::encode(digest);
+        hash.truncate(16);
+        hash
+    }
+}
+
+/// 不输出具体值的敏感字段
+const SECRET_FIELDS: [&str; 6] = [
+    "api_key",
+    "api_secret",
+    "api_secret_encrypted",
+    "admin_api.bearer_token",
+    "report_delivery.channel.url",
+    "report_delivery.channel.password",
+];
+
+/// 将 `path` 处非空的字符串替换为 `******`，路径不存在时不做处理
+fn redact_value(value: &mut serde_json::Value, path: &str) {
+    let mut current = value;
+    for key in path.split('.') {
+        match current.get_mut(key) {
+            Some(child) => current = child,
+            None => return,
+        }
+    }
+    if current.as_str().is_some_and(|secret| !secret.is_empty()) {
+        *current = serde_json::Value::String("******".to_string());
+    }
+}
+
+/// 将 `overrides` 合并到 `base`：对象逐个字段递归合并，数组和其他值整体替换
+fn merge_values(base: &mut serde_json::Value, overrides: serde_json::Value) {
+    match (base, overrides) {
+        (serde_json::Value::Object(base_map), serde_json::Value::Object(override_map)) => {
+            for (key, value) in override_map {
+                match base_map.get_mut(&key) {
+                    Some(existing) => merge_values(existing, value),
+                    None => {
+                        base_map.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, value) => *base = value,
+    }
+}
+
+fn diff_values(path: &str, old: &serde_json::Value, new: &serde_json::Value, changes: &mut Vec<String>) {
+    use serde_json::Value;
+    
+    if old == new {
+        return;
+    }
+    match (old, new) {
+        (Value::Object(old_map), Value::Object(new_map)) => {
+            let mut keys: Vec<&String> = old_map.keys().chain(new_map.keys()).collect();
+            keys.sort();
+            keys.dedup();
+            for key in keys {
+                let child = if path.is_empty() { key.clone() } else { format!("{}.{}", path, key) };
+                diff_values(
+                    &child,
+                    old_map.get(key).unwrap_or(&Value::Null),
+                    new_map.get(key).unwrap_or(&Value::Null),
+                    changes,
+                );
+            }
+        },
+        _ if SECRET_FIELDS.contains(&path) => changes.push(format!("{}: 已修改", path)),
+        _ => changes.push(format!("{}: {} -> {}", path, old, new)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn test_config() -> Config {
+        Config {
+            api_key: "key".to_string(),
+            api_secret: "secret".to_string(),
+            base_url: String::new(),
+            ..Config::default()
+        }
+    }
+    
+    #[test]
+    fn test_validate() {
+        assert!(test_config().validate().is_ok());
+        
+        let mut config = test_config();
+        config.arbitrage_settings.max_trade_amount_usdt = Decimal::ZERO;
+        assert!(config.validate().is_err());
+        
+        let mut config = test_config();
+        config.arbitrage_settings.min_profit_percentage = dec!(-0.1);
+        assert!(config.validate().is_err());
+        
+        let mut config = test_config();
+        config.risk_settings.trading_time_window.end_hour = 24;
+        assert!(config.validate().is_err());
+    }
+    
+    /// 校验失败的字段路径
+    fn invalid_fields(config: &Config) -> Vec<String> {
+        config.validate().err().unwrap_or_default().into_iter().map(|e| e.field).collect()
+    }
+    
+    #[test]
+    fn test_validation_rules() {
+        let rules: Vec<(&str, fn(&mut Config))> = vec![
+            ("arbitrage_settings.min_profit_percentage", |c| c.arbitrage_settings.min_profit_percentage = dec!(-1)),
+            ("arbitrage_settings.max_trade_amount_usdt", |c| c.arbitrage_settings.max_trade_amount_usdt = Decimal::ZERO),
+            ("arbitrage_settings.price_diff_threshold", |c| c.arbitrage_settings.price_diff_threshold = dec!(-0.05)),
+            ("arbitrage_settings.usdt_symbol", |c| c.arbitrage_settings.usdt_symbol = String::new()),
+            ("arbitrage_settings.usdc_symbol", |c| c.arbitrage_settings.usdc_symbol = "BTCUSDT".to_string()),
+            ("arbitrage_settings.check_interval_ms", |c| c.arbitrage_settings.check_interval_ms = 0),
+            ("arbitrage_settings.system_status_check_interval_ms", |c| c.arbitrage_settings.system_status_check_interval_ms = 0),
+            ("arbitrage_settings.db_health_check_interval_ms", |c| c.arbitrage_settings.db_health_check_interval_ms = 0),
+            ("arbitrage_settings.limit_order_timeout_ms", |c| c.arbitrage_settings.limit_order_timeout_ms = 0),
+            ("arbitrage_settings.max_order_age_seconds", |c| c.arbitrage_settings.max_order_age_seconds = 0),
+            ("arbitrage_settings.price_cache.capacity_per_symbol", |c| c.arbitrage_settings.price_cache.capacity_per_symbol = 0),
+            ("arbitrage_settings.price_cache.max_total_records", |c| c.arbitrage_settings.price_cache.max_total_records = 10),
+            ("arbitrage_settings.dust_sweep.interval_ms", |c| {
+                c.arbitrage_settings.dust_sweep.enabled = true;
+                c.arbitrage_settings.dust_sweep.interval_ms = 0;
+            }),
+            ("arbitrage_settings.dust_sweep.dust_threshold", |c| c.arbitrage_settings.dust_sweep.dust_threshold = dec!(-0.1)),
+            ("arbitrage_settings.best_execution.usdt_fee_rate", |c| c.arbitrage_settings.best_execution.usdt_fee_rate = Decimal::ONE),
+            ("arbitrage_settings.best_execution.usdc_fee_rate", |c| c.arbitrage_settings.best_execution.usdc_fee_rate = dec!(-0.001)),
+            ("arbitrage_settings.best_execution.conversion_symbol", |c| {
+                c.arbitrage_settings.best_execution.enabled = true;
+                c.arbitrage_settings.best_execution.conversion_symbol = " ".to_string();
+            }),
+            ("arbitrage_settings.inventory_skew.target_usdt_ratio", |c| c.arbitrage_settings.inventory_skew.target_usdt_ratio = dec!(1.5)),
+            ("arbitrage_settings.inventory_skew.skew_factor", |c| c.arbitrage_settings.inventory_skew.skew_factor = dec!(-1)),
+            ("arbitrage_settings.inventory_skew.max_multiplier", |c| c.arbitrage_settings.inventory_skew.max_multiplier = dec!(0.5)),
+            ("arbitrage_settings.profit_distribution.threshold_usdt", |c| c.arbitrage_settings.profit_distribution.threshold_usdt = Decimal::ZERO),
+            ("arbitrage_settings.profit_distribution.portion", |c| c.arbitrage_settings.profit_distribution.portion = Decimal::ZERO),
+            ("arbitrage_settings.profit_distribution.asset", |c| {
+                c.arbitrage_settings.profit_distribution.enabled = true;
+                c.arbitrage_settings.profit_distribution.transfer_enabled = true;
+                c.arbitrage_settings.profit_distribution.to_email = "savings@example.com".to_string();
+                c.arbitrage_settings.profit_distribution.asset = String::new();
+            }),
+            ("arbitrage_settings.profit_distribution.to_email", |c| {
+                c.arbitrage_settings.profit_distribution.enabled = true;
+                c.arbitrage_settings.profit_distribution.transfer_enabled = true;
+            }),
+            ("strategy_settings.twap.slices", |c| c.strategy_settings.twap.slices = 0),
+            ("strategy_settings.order_book_depth.depth_levels", |c| c.strategy_settings.order_book_depth.depth_levels = 0),
+            ("strategy_settings.order_book_depth.min_liquidity", |c| c.strategy_settings.order_book_depth.min_liquidity = dec!(-1)),
+            ("strategy_settings.order_book_depth.min_book_levels", |c| c.strategy_settings.order_book_depth.min_book_levels = 21),
+            ("strategy_settings.order_book_depth.min_impact_pct", |c| c.strategy_settings.order_book_depth.min_impact_pct = dec!(-0.1)),
+            ("strategy_settings.slippage_control.max_slippage_pct", |c| c.strategy_settings.slippage_control.max_slippage_pct = dec!(-0.5)),
+            ("strategy_settings.slippage_control.volatility_window_size", |c| c.strategy_settings.slippage_control.volatility_window_size = 0),
+            ("strategy_settings.trend_following.short_window", |c| c.strategy_settings.trend_following.short_window = 0),
+            ("strategy_settings.trend_following.long_window", |c| c.strategy_settings.trend_following.short_window = 30),
+            ("strategy_settings.trend_following.trend_threshold", |c| c.strategy_settings.trend_following.trend_threshold = dec!(-0.5)),
+            ("strategy_settings.trend_following.ema_smoothing", |c| c.strategy_settings.trend_following.ema_smoothing = Decimal::ZERO),
+            ("strategy_settings.composite.min_agreement", |c| c.strategy_settings.composite.min_agreement = 1.1),
+            ("strategy_settings.composite.members[1]", |c| c.strategy_settings.composite.members[1].1 = -1.0),
+            ("strategy_settings.composite.members[3]", |c| c.strategy_settings.composite.members.push((StrategyType::Composite, 1.0))),
+            ("strategy_settings.liquidity_guard.levels", |c| c.strategy_settings.liquidity_guard.levels = 0),
+            ("risk_settings.daily_loss_limit.max_daily_loss", |c| c.risk_settings.daily_loss_limit.max_daily_loss = Decimal::ZERO),
+            ("risk_settings.abnormal_price.window_size", |c| c.risk_settings.abnormal_price.window_size = 0),
+            ("risk_settings.abnormal_price.abnormal_threshold", |c| c.risk_settings.abnormal_price.abnormal_threshold = Decimal::ZERO),
+            ("risk_settings.abnormal_price.cooldown_period", |c| c.risk_settings.abnormal_price.cooldown_period = -1),
+            ("risk_settings.exposure.max_exposures[\"BTC\"]", |c| {
+                c.risk_settings.exposure.max_exposures.insert("BTC".to_string(), Decimal::ZERO);
+            }),
+            ("risk_settings.exposure.max_exposures[\"\"]", |c| {
+                c.risk_settings.exposure.max_exposures.insert(String::new(), Decimal::ONE);
+            }),
+            ("risk_settings.exposure.update_interval_seconds", |c| {
+                c.risk_settings.exposure.update_mode = ExposureUpdateMode::Interval;
+                c.risk_settings.exposure.update_interval_seconds = 0;
+            }),
+            ("risk_settings.trading_time_window.start_hour", |c| c.risk_settings.trading_time_window.start_hour = 24),
+            ("risk_settings.trading_time_window.end_hour", |c| c.risk_settings.trading_time_window.end_hour = 25),
+            ("risk_settings.trading_time_window.start_minute", |c| c.risk_settings.trading_time_window.start_minute = 60),
+            ("risk_settings.trading_time_window.end_minute", |c| c.risk_settings.trading_time_window.end_minute = 60),
+            ("risk_settings.trading_time_window", |c| {
+                c.risk_settings.trading_time_window.start_hour = 23;
+                c.risk_settings.trading_time_window.start_minute = 59;
+            }),
+            ("risk_settings.trading_frequency.timeframe_seconds", |c| c.risk_settings.trading_frequency.timeframe_seconds = 0),
+            ("risk_settings.trading_frequency.min_interval_seconds", |c| c.risk_settings.trading_frequency.min_interval_seconds = -1),
+            ("risk_settings.trading_frequency.max_trades_per_timeframe", |c| c.risk_settings.trading_frequency.max_trades_per_timeframe = 0),
+            ("risk_settings.trading_frequency.min_spread_percentage", |c| c.risk_settings.trading_frequency.min_spread_percentage = dec!(-0.1)),
+            ("risk_settings.pair_blacklist.blacklisted_pairs[1]", |c| {
+                c.risk_settings.pair_blacklist.blacklisted_pairs = vec!["ETHUSDC".to_string(), "btc-usdt".to_string(), "SOL".to_string(), "*USDT".to_string()];
+            }),
+            ("risk_settings.open_positions.max_open_positions", |c| c.risk_settings.open_positions.max_open_positions = 0),
+            ("api_latency.window_size", |c| {
+                c.api_latency.enabled = true;
+                c.api_latency.window_size = 0;
+            }),
+            ("report_delivery", |c| c.report_delivery.enabled = true),
+            ("fees.taker_rate", |c| c.fees.taker_rate = dec!(0.02)),
+            ("fees.fee_asset_discount", |c| c.fees.fee_asset_discount = Decimal::ONE),
+            ("fees.fixed_cost_per_trade", |c| c.fees.fixed_cost_per_trade = dec!(-1)),
+            ("fees.min_notional_multiple", |c| c.fees.min_notional_multiple = dec!(0.5)),
+            ("execution.max_fill_deviation_pct", |c| c.execution.max_fill_deviation_pct = dec!(-1)),
+            ("api_secret_file", |c| c.secret.secret_source = SecretSource::File),
+            ("api_secret_encrypted", |c| c.secret.secret_source = SecretSource::Encrypted),
+            ("fallback_base_urls[1]", |c| {
+                c.fallback_base_urls = vec!["https://api1.binance.com".to_string(), "api2.binance.com".to_string()];
+            }),
+        ];
+        
+        for (field, apply) in rules {
+            let mut config = test_config();
+            apply(&mut config);
+            assert_eq!(invalid_fields(&config), vec![field.to_string()], "规则 {}", field);
+        }
+    }
+    
+    #[test]
+    fn test_validate_reports_all_errors() {
+        let mut config = test_config();
+        config.arbitrage_settings.min_profit_percentage = dec!(-1);
+        config.arbitrage_settings.usdt_symbol = String::new();
+        config.strategy_settings.twap.slices = 0;
+        config.strategy_settings.trend_following.short_window = 50;
+        
+        let errors = config.validate().unwrap_err();
+        let fields: Vec<&str> = errors.iter().map(|e| e.field.as_str()).collect();
+        assert_eq!(fields, vec![
+            "arbitrage_settings.min_profit_percentage",
+            "arbitrage_settings.usdt_symbol",
+            "strategy_settings.twap.slices",
+            "strategy_settings.trend_following.long_window",
+        ]);
+        assert_eq!(errors[2].to_string(), "strategy_settings.twap.slices: TWAP分割的订单数量必须大于0");
+        
+        let combined = ConfigError::combine(errors).to_string();
+        assert!(combined.starts_with("4 项配置错误"));
+        assert!(combined.contains("arbitrage_settings.usdt_symbol"));
+    }
+    
+    #[test]
+    fn test_decimal_fields_accept_numbers_and_strings() {
+        let mut value = serde_json::to_value(test_config()).unwrap();
+        value["arbitrage_settings"]["min_profit_percentage"] = serde_json::json!(0.1);
+        value["arbitrage_settings"]["max_trade_amount_usdt"] = serde_json::json!("0.2");
+        value["risk_settings"]["exposure"]["max_exposures"] = serde_json::json!([["BTC", 0.1], ["ETH", "2.5"]]);
+        let parsed: Config = serde_json::from_str(&value.to_string()).unwrap();
+        
+        // 0.1 和 0.2 在 f64 中不能精确表示，两者之和不等于 0.3
+        assert_ne!(0.1_f64 + 0.2_f64, 0.3_f64);
+        let arbitrage = &parsed.arbitrage_settings;
+        assert_eq!(arbitrage.min_profit_percentage, dec!(0.1));
+        assert_eq!(arbitrage.min_profit_percentage + arbitrage.max_trade_amount_usdt, dec!(0.3));
+        assert_eq!(
+            parsed.risk_settings.exposure.max_exposures,
+            HashMap::from([("BTC".to_string(), dec!(0.1)), ("ETH".to_string(), dec!(2.5))])
+        );
+        
+        // 仍然序列化为数字
+        let serialized = serde_json::to_value(&parsed).unwrap();
+        assert_eq!(serialized["arbitrage_settings"]["max_trade_amount_usdt"], serde_json::json!(0.2));
+        assert_eq!(serialized["risk_settings"]["exposure"]["max_exposures"]["ETH"], serde_json::json!(2.5));
+        
+        value["arbitrage_settings"]["min_profit_percentage"] = serde_json::json!("abc");
+        assert!(serde_json::from_str::<Config>(&value.to_string()).is_err());
+    }
+    
+    #[test]
+    fn test_exposure_map_rejects_duplicates() {
+        let mut value = serde_json::to_value(test_config()).unwrap();
+        value["risk_settings"]["exposure"]["max_exposures"] = serde_json::json!({"BTC": 1, "ETH": "20"});
+        let parsed: Config = serde_json::from_str(&value.to_string()).unwrap();
+        assert_eq!(parsed.risk_settings.exposure.max_exposures["ETH"], dec!(20));
+        
+        // 对象和旧版列表中重复的币种都会报错
+        let json = value.to_string().replace(r#""BTC":1"#, r#""BTC":1,"BTC":2"#);
+        let error = serde_json::from_str::<Config>(&json).unwrap_err();
+        assert!(error.to_string().contains("重复的名称: BTC"), "{}", error);
+        value["risk_settings"]["exposure"]["max_exposures"] = serde_json::json!([["BTC", 1], ["BTC", 2]]);
+        assert!(serde_json::from_str::<Config>(&value.to_string()).is_err());
+    }
+    
+    #[test]
+    fn test_diff_lists_changed_fields() {
+        let old = test_config();
+        assert!(old.diff(&old.clone()).is_empty());
+        
+        let mut new = old.clone();
+        new.api_secret = "rotated".to_string();
+        new.arbitrage_settings.max_trade_amount_usdt = dec!(50);
+        new.risk_settings.enabled_controllers.push(RiskControllerType::PairBlacklist);
+        
+        let changes = old.diff(&new);
+        assert_eq!(changes.len(), 3);
+        assert!(changes.contains(&"api_secret: 已修改".to_string()));
+        assert!(changes.contains(&"arbitrage_settings.max_trade_amount_usdt: 100.0 -> 50.0".to_string()));
+        assert!(changes.iter().any(|c| c.starts_with("risk_settings.enabled_controllers: ") && c.contains("PairBlacklist")));
+    }
+    
+    #[test]
+    fn test_generated_template_round_trips() {
+        let template = Config::template();
+        assert!(template.validate().is_ok());
+        
+        let parsed = Config::from_json(&template.to_json().unwrap()).unwrap();
+        assert_eq!(serde_json::to_value(&parsed).unwrap(), serde_json::to_value(&template).unwrap());
+        assert!(template.diff(&parsed).is_empty());
+        assert_eq!(parsed.api_key, "YOUR_BINANCE_API_KEY");
+    }
+    
+    #[test]
+    fn test_partial_config_files_use_defaults() {
+        let defaults = serde_json::to_value(Config::default()).unwrap();
+        
+        let minimal = Config::from_json("{}").unwrap();
+        assert_eq!(serde_json::to_value(&minimal).unwrap(), defaults);
+        
+        let partial = Config::from_json(
+            r#"{
+                "api_key": "key",
+                "api_secret": "secret",
+                "arbitrage_settings": {"min_profit_percentage": 0.2},
+                "risk_settings": {"daily_loss_limit": {"max_daily_loss": 20}, "trading_frequency": {"min_interval_seconds": 5}}
+            }"#,
+        ).unwrap();
+        assert_eq!(partial.api_key, "key");
+        assert_eq!(partial.base_url, "https://api.binance.com");
+        assert_eq!(partial.arbitrage_settings.min_profit_percentage, dec!(0.2));
+        assert_eq!(partial.arbitrage_settings.max_trade_amount_usdt, dec!(100));
+        assert_eq!(partial.arbitrage_settings.price_cache.capacity_per_symbol, 200);
+        assert_eq!(partial.risk_settings.daily_loss_limit.max_daily_loss, dec!(20));
+        assert_eq!(partial.risk_settings.trading_frequency.min_interval_seconds, 5);
+        assert_eq!(partial.risk_settings.trading_frequency.max_trades_per_timeframe, 10);
+        assert_eq!(partial.risk_settings.enabled_controllers, RiskSettings::default().enabled_controllers);
+        assert_eq!(serde_json::to_value(&partial.strategy_settings).unwrap(), defaults["strategy_settings"]);
+        assert!(partial.validate().is_ok());
+        
+        // 完整的配置文件原样读取
+        let mut full = Config::template();
+        full.arbitrage_settings.check_interval_ms = 250;
+        full.strategy_settings.trend_following.short_window = 5;
+        let parsed = Config::from_json(&full.to_json().unwrap()).unwrap();
+        assert_eq!(serde_json::to_value(&parsed).unwrap(), serde_json::to_value(&full).unwrap());
+    }
+    
+    #[test]
+    fn test_profile_overrides_nested_settings() {
+        let contents = r#"{
+            "arbitrage_settings": {"min_profit_percentage": 0.2, "max_trade_amount_usdt": 50},
+            "risk_settings": {
+                "enabled_controllers": ["DailyLossLimit", "AbnormalPrice"],
+                "daily_loss_limit": {"max_daily_loss": 20},
+                "trading_frequency": {"min_interval_seconds": 5, "max_trades_per_timeframe": 3}
+            },
+            "profiles": {
+                "aggressive": {
+                    "arbitrage_settings": {"min_profit_percentage": 0.05},
+                    "risk_settings": {
+                        "enabled_controllers": ["DailyLossLimit"],
+                        "trading_frequency": {"min_interval_seconds": 1}
+                    }
+                },
+                "broken": {"arbitrage_settings": {"max_trade_amount_usdt": 0}}
+            }
+        }"#;
+        
+        let base = Config::from_json_with_profile(contents, None).unwrap();
+        assert_eq!(base.arbitrage_settings.min_profit_percentage, dec!(0.2));
+        assert_eq!(base.active_profile, None);
+        
+        let merged = Config::from_json_with_profile(contents, Some("aggressive")).unwrap();
+        assert_eq!(merged.active_profile.as_deref(), Some("aggressive"));
+        // 配置档中的字段覆盖基础设置，同一对象中未覆盖的字段保持基础设置的值
+        assert_eq!(merged.arbitrage_settings.min_profit_percentage, dec!(0.05));
+        assert_eq!(merged.arbitrage_settings.max_trade_amount_usdt, dec!(50));
+        assert_eq!(merged.risk_settings.trading_frequency.min_interval_seconds, 1);
+        assert_eq!(merged.risk_settings.trading_frequency.max_trades_per_timeframe, 3);
+        assert_eq!(merged.risk_settings.daily_loss_limit.max_daily_loss, dec!(20));
+        // 数组整体替换
+        assert_eq!(merged.risk_settings.enabled_controllers, vec![RiskControllerType::DailyLossLimit]);
+        assert!(merged.validate().is_ok());
+        
+        // 校验合并后的配置
+        let broken = Config::from_json_with_profile(contents, Some("broken")).unwrap();
+        let errors = broken.validate().unwrap_err();
+        assert!(errors.iter().any(|e| e.to_string().contains("arbitrage_settings.max_trade_amount_usdt")));
+        
+        let error = Config::from_json_with_profile(contents, Some("missing")).unwrap_err().to_string();
+        assert!(error.contains("missing") && error.contains("aggressive, broken"), "{}", error);
+    }
+    
+    #[test]
+    fn test_missing_credentials_fall_back_to_env() {
+        env::set_var("BINANCE_API_SECRET", "env-secret");
+        let mut config = Config::from_json(r#"{"api_key": "file-key"}"#).unwrap();
+        config.fill_credentials_from_env();
+        assert_eq!(config.api_key, "file-key");
+        assert_eq!(config.api_secret, "env-secret");
+    }
+    
+    #[test]
+    fn test_redacted_json_masks_secrets() {
+        let mut config = test_config();
+        config.api_key = "live-key-123".to_string();
+        config.api_secret = "live-secret-456".to_string();
+        config.admin_api.bearer_token = "admin-token-789".to_string();
+        config.report_delivery.channel = ReportChannel::Webhook { url: "https://example.com/hook/abc".to_string() };
+        config.profiles.insert("testnet".to_string(), serde_json::json!({"api_key": "testnet-key-000", "base_url": "https://testnet.binance.vision"}));
+        
+        let shown = config.to_redacted_json().unwrap();
+        for secret in ["live-key-123", "live-secret-456", "admin-token-789", "https://example.com/hook/abc", "testnet-key-000"] {
+            assert!(!shown.contains(secret), "{} 未隐藏", secret);
+        }
+        let value: serde_json::Value = serde_json::from_str(&shown).unwrap();
+        assert_eq!(value["api_key"], "******");
+        assert_eq!(value["admin_api"]["bearer_token"], "******");
+        assert_eq!(value["report_delivery"]["channel"]["url"], "******");
+        assert_eq!(value["profiles"]["testnet"]["base_url"], "https://testnet.binance.vision");
+        // 非敏感字段和空的密钥原样输出
+        assert_eq!(value["arbitrage_settings"], serde_json::to_value(&config.arbitrage_settings).unwrap());
+        assert!(test_config().to_redacted_json().unwrap().contains(r#""bearer_token": """#));
+    }
+
+    #[test]
+    fn test_resolved_secret_is_never_serialized() {
+        let path = env::temp_dir().join(format!("api-secret-{}", uuid::Uuid::new_v4()));
+        std::fs::write(&path, "file-secret\n").unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600)).unwrap();
+        }
+
+        let mut config = Config::from_json(&format!(
+            r#"{{"api_key": "key", "secret_source": "File", "api_secret_file": {:?}}}"#,
+            path.to_string_lossy()
+        )).unwrap();
+        assert!(config.validate().is_ok());
+        config.resolve_secret().unwrap();
+        assert_eq!(config.api_secret, "file-secret");
+
+        for output in [config.to_json().unwrap(), config.to_redacted_json().unwrap()] {
+            assert!(!output.contains("file-secret"));
+            let value: serde_json::Value = serde_json::from_str(&output).unwrap();
+            assert_eq!(value["secret_source"], "File");
+            assert_eq!(value["api_secret"], "");
+        }
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_report_schedule_next_after() {
+        let offset = FixedOffset::east_opt(8 * 3600).unwrap();
+        // 东八区 2024-03-01 07:30
+        let now = Utc.with_ymd_and_hms(2024, 2, 29, 23, 30, 0).unwrap();
+        
+        let daily = ReportSchedule::Daily { time: "08:00".to_string() };
+        assert_eq!(daily.next_after(now, offset).unwrap(), Utc.with_ymd_and_hms(2024, 3, 1, 0, 0, 0).unwrap());
+        // 正好在推送时间时，下一次为次日
+        let at_run = Utc.with_ymd_and_hms(2024, 3, 1, 0, 0, 0).unwrap();
+        assert_eq!(daily.next_after(at_run, offset).unwrap(), Utc.with_ymd_and_hms(2024, 3, 2, 0, 0, 0).unwrap());
+        
+        // 2024-03-01 为周五，工作日 08:30 推送，下一次为周一
+        let cron = ReportSchedule::Cron { expression: "0 30 8 * * Mon-Fri".to_string() };
+        assert_eq!(cron.next_after(now, offset).unwrap(), Utc.with_ymd_and_hms(2024, 3, 1, 0, 30, 0).unwrap());
+        let after_friday = Utc.with_ymd_and_hms(2024, 3, 1, 1, 0, 0).unwrap();
+        assert_eq!(cron.next_after(after_friday, offset).unwrap(), Utc.with_ymd_and_hms(2024, 3, 4, 0, 30, 0).unwrap());
+        
+        assert!(ReportSchedule::Daily { time: "25:00".to_string() }.validate().is_err());
+        assert!(ReportSchedule::Cron { expression: "every morning".to_string() }.validate().is_err());
+    }
+    
+    #[test]
+    fn test_report_delivery_validation() {
+        let mut config = test_config();
+        // 未启用时不检查推送设置
+        assert!(config.validate().is_ok());
+        
+        config.report_delivery.enabled = true;
+        assert!(config.validate().is_err());
+        config.report_delivery.channel = ReportChannel::Webhook { url: "https://example.com/hook".to_string() };
+        assert!(config.validate().is_ok());
+        
+        config.report_delivery.channel = ReportChannel::Email(SmtpSettings {
+            host: "smtp.example.com".to_string(),
+            port: 465,
+            username: String::new(),
+            password: String::new(),
+            from: "bot@example.com".to_string(),
+            to: Vec::new(),
+        });
+        assert!(config.validate().is_err());
+        
+        // SMTP端口可以省略，渠道按 type 字段区分
+        let parsed: ReportDeliverySettings = serde_json::from_str(
+            r#"{"enabled": true, "schedule": {"type": "cron", "expression": "0 0 9 * * *"}, "channel": {"type": "email", "host": "smtp.example.com", "from": "bot@example.com", "to": ["me@example.com"]}, "format": "json", "max_retries": 2, "retry_backoff_ms": 1000}"#,
+        ).unwrap();
+        assert!(parsed.validate().is_ok());
+        assert!(matches!(parsed.channel, ReportChannel::Email(SmtpSettings { port: 465, .. })));
+        assert_eq!(parsed.format, ReportDeliveryFormat::Json);
+    }
+    
+    #[test]
+    fn test_json_schema() {
+        let schema = Config::json_schema();
+        let definitions = &schema["definitions"];
+        
+        let enabled_strategies = &definitions["StrategySettings"]["properties"]["enabled_strategies"];
+        assert_eq!(enabled_strategies["type"], "array");
+        assert_eq!(enabled_strategies["items"]["$ref"], "#/definitions/StrategyType");
+        
+        // 可选的策略和风控组件都列在对应的枚举定义中
+        let strategy_options = definitions["StrategyType"].to_string();
+        for option in ["Simple", "TimeWeighted", "OrderBookDepth", "SlippageControl", "TrendFollowing", "Composite"] {
+            assert!(strategy_options.contains(&format!("\"{}\"", option)), "缺少策略 {}", option);
+        }
+        assert!(definitions["RiskControllerType"].to_string().contains("\"OpenPositions\""));
+        
+        // 字段说明来自文档注释；省略的字段使用默认值，套利参数中没有必填项，默认值列在字段定义中
+        let arbitrage = &definitions["ArbitrageSettings"];
+        assert!(arbitrage["properties"]["min_profit_percentage"]["description"].as_str().unwrap().contains("最小利润率"));
+        assert!(arbitrage.get("required").is_none());
+        assert_eq!(arbitrage["properties"]["check_interval_ms"]["default"], ArbitrageSettings::default().check_interval_ms);
+        assert_eq!(arbitrage["properties"]["dry_run"]["default"], false);
+    }
+    
+    #[test]
+    fn test_fingerprint() {
+        let config = test_config();
+        let hash = config.fingerprint();
+        assert_eq!(hash.len(), 16);
+        assert_eq!(hash, config.clone().fingerprint());
+        
+        // 密钥不影响指纹，交易参数会改变指纹
+        let mut rotated = config.clone();
+        rotated.api_secret = "rotated".to_string();
+        assert_eq!(rotated.fingerprint(), hash);
+        
+        let mut changed = config.clone();
+        changed.arbitrage_settings.min_profit_percentage += dec!(0.1);
+        assert_ne!(changed.fingerprint(), hash);
     }
 }
@@ -0,0 +1,251 @@
+use aes_gcm::aead::{Aead, KeyInit};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use anyhow::{anyhow, Context, Result};
+use argon2::Argon2;
+use rand::RngCore;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+/// 加密私钥的格式版本前缀
+const ENCRYPTED_PREFIX: &str = "v1";
+/// argon2 盐的字节数
+const SALT_LEN: usize = 16;
+/// AES-GCM 随机数的字节数
+const NONCE_LEN: usize = 12;
+/// 系统钥匙串中的服务名称，账户名为API密钥
+pub const KEYRING_SERVICE: &str = "binance-arbitrage";
+/// 解密口令的环境变量，未设置时在终端提示输入
+pub const PASSPHRASE_ENV: &str = "BINANCE_SECRET_PASSPHRASE";
+
+/// API私钥的来源
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum SecretSource {
+    /// 配置文件的 `api_secret` 或环境变量 BINANCE_API_SECRET
+    #[default]
+    Inline,
+    /// `api_secret_file` 指定的文件，只允许所有者读写
+    File,
+    /// `api_secret_encrypted` 中加密的私钥，启动时输入口令解密
+    Encrypted,
+    /// 系统钥匙串，需要启用 `keyring` 特性
+    Keyring,
+}
+
+/// API私钥的存储方式，字段直接写在配置文件顶层
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct SecretSettings {
+    /// 私钥来源，默认 `Inline`
+    pub secret_source: SecretSource,
+    /// `File` 来源的私钥文件路径，文件权限应为600
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub api_secret_file: Option<String>,
+    /// `Encrypted` 来源的加密私钥，由 `config encrypt-secret` 生成
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub api_secret_encrypted: Option<String>,
+}
+
+impl FromStr for SecretSource {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "inline" => Ok(SecretSource::Inline),
+            "file" => Ok(SecretSource::File),
+            "encrypted" => Ok(SecretSource::Encrypted),
+            "keyring" => Ok(SecretSource::Keyring),
+            _ => Err(anyhow!("无效的私钥来源: {}，可选 Inline, File, Encrypted, Keyring", s)),
+        }
+    }
+}
+
+impl SecretSettings {
+    /// 从环境变量 BINANCE_SECRET_SOURCE、BINANCE_API_SECRET_FILE 和 BINANCE_API_SECRET_ENCRYPTED 读取，用于 `Config::new`
+    pub fn from_env() -> Result<Self> {
+        let secret_source = match env::var("BINANCE_SECRET_SOURCE") {
+            Ok(source) if !source.is_empty() => source.parse()?,
+            _ => SecretSource::Inline,
+        };
+        Ok(Self {
+            secret_source,
+            api_secret_file: env::var("BINANCE_API_SECRET_FILE").ok(),
+            api_secret_encrypted: env::var("BINANCE_API_SECRET_ENCRYPTED").ok(),
+        })
+    }
+
+    /// 按来源读取API私钥，`Inline` 来源返回 None
+    ///
+    /// `Encrypted` 来源的口令从环境变量 BINANCE_SECRET_PASSPHRASE 读取，未设置时在终端提示输入；
+    /// `Keyring` 来源以 `api_key` 为账户名查找。
+    pub fn resolve(&self, api_key: &str) -> Result<Option<String>> {
+        match self.secret_source {
+            SecretSource::Inline => Ok(None),
+            SecretSource::File => {
+                let path = self.api_secret_file.as_deref().filter(|path| !path.is_empty())
+                    .context("secret_source 为 File 时需要设置 api_secret_file")?;
+                read_secret_file(Path::new(path)).map(Some)
+            }
+            SecretSource::Encrypted => {
+                let encrypted = self.api_secret_encrypted.as_deref().filter(|value| !value.is_empty())
+                    .context("secret_source 为 Encrypted 时需要设置 api_secret_encrypted")?;
+                let passphrase = read_passphrase("请输入API私钥的解密口令: ")?;
+                decrypt_secret(encrypted, &passphrase).map(Some)
+            }
+            SecretSource::Keyring => read_keyring(api_key).map(Some),
+        }
+    }
+}
+
+/// 读取私钥文件，去掉末尾的换行
+///
+/// Unix系统上文件的组或其他用户有任何权限时拒绝读取，避免私钥被同一台服务器上的其他用户读到。
+pub fn read_secret_file(path: &Path) -> Result<String> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = fs::metadata(path)
+            .with_context(|| format!("无法读取API私钥文件: {}", path.display()))?
+            .permissions()
+            .mode();
+        if mode & 0o077 != 0 {
+            return Err(anyhow!(
+                "API私钥文件 {} 的权限为 {:o}，其他用户可以访问。请执行 `chmod 600 {}` 只允许所有者读写后重试",
+                path.display(),
+                mode & 0o777,
+                path.display()
+            ));
+        }
+    }
+
+    let contents = fs::read_to_string(path).with_context(|| format!("无法读取API私钥文件: {}", path.display()))?;
+    let secret = contents.trim_end_matches(['\r', '\n']).to_string();
+    if secret.is_empty() {
+        return Err(anyhow!("API私钥文件为空: {}", path.display()));
+    }
+    Ok(secret)
+}
+
+/// 读取解密口令：优先使用环境变量 BINANCE_SECRET_PASSPHRASE，否则在终端提示输入（不回显）
+pub fn read_passphrase(prompt: &str) -> Result<String> {
+    match env::var(PASSPHRASE_ENV) {
+        Ok(passphrase) if !passphrase.is_empty() => Ok(passphrase),
+        _ => rpassword::prompt_password(prompt).context("读取口令失败"),
+    }
+}
+
+/// 由口令和盐派生256位密钥（argon2id，默认参数）
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<[u8; 32]> {
+    let mut key = [0u8; 32];
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| anyhow!("派生密钥失败: {}", e))?;
+    Ok(key)
+}
+
+/// 用口令加密API私钥，返回 `v1$盐$随机数$密文`（十六进制）格式的字符串，写入 `api_secret_encrypted`
+///
+/// 密钥由argon2id从口令派生，私钥用AES-256-GCM加密，每次加密使用新的随机盐和随机数。
+pub fn encrypt_secret(secret: &str, passphrase: &str) -> Result<String> {
+    if passphrase.is_empty() {
+        return Err(anyhow!("口令不能为空"));
+    }
+    let mut salt = [0u8; SALT_LEN];
+    let mut nonce = [0u8; NONCE_LEN];
+    rand::rngs::OsRng.fill_bytes(&mut salt);
+    rand::rngs::OsRng.fill_bytes(&mut nonce);
+
+    let key = derive_key(passphrase, &salt)?;
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key));
+    let ciphertext = cipher
+        .encrypt(Nonce::from_slice(&nonce), secret.as_bytes())
+        .map_err(|_| anyhow!("加密API私钥失败"))?;
+    Ok(format!("{}${}${}${}", ENCRYPTED_PREFIX, hex::encode(salt), hex::encode(nonce), hex::encode(ciphertext)))
+}
+
+/// 解密 `encrypt_secret` 生成的字符串，口令错误或内容被修改时返回错误
+pub fn decrypt_secret(encrypted: &str, passphrase: &str) -> Result<String> {
+    let parts: Vec<&str> = encrypted.trim().split('$').collect();
+    let [version, salt, nonce, ciphertext] = parts[..] else {
+        return Err(anyhow!("加密私钥格式无效，应为 config encrypt-secret 的输出"));
+    };
+    if version != ENCRYPTED_PREFIX {
+        return Err(anyhow!("不支持的加密私钥版本: {}", version));
+    }
+    let salt = hex::decode(salt).context("加密私钥的盐格式无效")?;
+    let nonce = hex::decode(nonce).context("加密私钥的随机数格式无效")?;
+    let ciphertext = hex::decode(ciphertext).context("加密私钥的密文格式无效")?;
+    if nonce.len() != NONCE_LEN {
+        return Err(anyhow!("加密私钥的随机数长度无效: {}", nonce.len()));
+    }
+
+    let key = derive_key(passphrase, &salt)?;
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key));
+    let plaintext = cipher
+        .decrypt(Nonce::from_slice(&nonce), ciphertext.as_ref())
+        .map_err(|_| anyhow!("解密API私钥失败：口令错误或加密内容已损坏"))?;
+    String::from_utf8(plaintext).context("解密后的API私钥不是有效的UTF-8")
+}
+
+#[cfg(feature = "keyring")]
+fn read_keyring(api_key: &str) -> Result<String> {
+    if api_key.is_empty() {
+        return Err(anyhow!("从系统钥匙串读取私钥需要设置 api_key（作为账户名）"));
+    }
+    keyring::Entry::new(KEYRING_SERVICE, api_key)
+        .and_then(|entry| entry.get_password())
+        .with_context(|| format!("无法从系统钥匙串读取私钥 (服务: {}, 账户: {})", KEYRING_SERVICE, api_key))
+}
+
+#[cfg(not(feature = "keyring"))]
+fn read_keyring(_api_key: &str) -> Result<String> {
+    Err(anyhow!("系统钥匙串支持未启用，请使用 keyring 特性重新编译"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_file(name: &str, contents: &str) -> std::path::PathBuf {
+        let path = env::temp_dir().join(format!("{}-{}", name, uuid::Uuid::new_v4()));
+        fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_secret_file_requires_owner_only_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = temp_file("api-secret", "file-secret\n");
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+        let error = read_secret_file(&path).unwrap_err().to_string();
+        assert!(error.contains("644"), "{}", error);
+        assert!(error.contains("chmod 600"), "{}", error);
+
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o600)).unwrap();
+        let settings = SecretSettings {
+            secret_source: SecretSource::File,
+            api_secret_file: Some(path.to_string_lossy().into_owned()),
+            ..SecretSettings::default()
+        };
+        assert_eq!(settings.resolve("key").unwrap().as_deref(), Some("file-secret"));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_encrypted_secret_round_trips() {
+        let encrypted = encrypt_secret("live-secret", "correct horse").unwrap();
+        assert!(!encrypted.contains("live-secret"));
+        assert_eq!(decrypt_secret(&encrypted, "correct horse").unwrap(), "live-secret");
+        // 每次加密使用新的盐和随机数
+        assert_ne!(encrypt_secret("live-secret", "correct horse").unwrap(), encrypted);
+
+        let error = decrypt_secret(&encrypted, "wrong").unwrap_err().to_string();
+        assert!(error.contains("口令错误"), "{}", error);
+        assert!(decrypt_secret("not-encrypted", "correct horse").is_err());
+    }
+}
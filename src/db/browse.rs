@@ -0,0 +1,365 @@
+//! 在命令行中浏览交易历史
+//!
+//! 按筛选条件查询 `arbitrage_history`，按时间顺序输出为对齐的表格、JSON Lines或CSV。
+//! 持续跟踪模式下定期查询最新的记录，只输出之前没有输出过的记录，用于在实盘运行期间查看新成交的套利。
+
+use super::{DatabaseManager, TradeHistoryFilter, TradeRecord};
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveTime, TimeZone, Utc};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use std::fmt;
+use std::io::Write;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// 持续跟踪时查询新记录的间隔
+pub const FOLLOW_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 持续跟踪时每次查询的最大记录数，两次查询之间新增的记录超过该数量时只输出最新的部分
+const FOLLOW_BATCH_SIZE: i64 = 500;
+
+/// 表格各列的最小宽度，持续跟踪时后续的行沿用第一次输出时的宽度
+const MIN_COLUMN_WIDTHS: [usize; COLUMNS] = [19, 5, 10, 12, 12, 10, 12, 9, 8];
+
+const COLUMNS: usize = 9;
+
+/// 表头，与CSV的列名相同
+const HEADERS: [&str; COLUMNS] = ["time", "asset", "direction", "buy_price", "sell_price", "amount", "profit", "status", "duration_ms"];
+
+/// 右对齐的数值列
+const NUMERIC_COLUMNS: [bool; COLUMNS] = [false, false, false, true, true, true, true, false, true];
+
+/// 交易历史的输出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HistoryViewFormat {
+    /// 对齐的表格，第一行为表头
+    Table,
+    /// JSON Lines，每行一条 `TradeRecord`
+    Json,
+    /// CSV，第一行为表头，列与表格相同
+    Csv,
+}
+
+impl FromStr for HistoryViewFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "table" => Ok(HistoryViewFormat::Table),
+            "json" | "jsonl" => Ok(HistoryViewFormat::Json),
+            "csv" => Ok(HistoryViewFormat::Csv),
+            _ => Err(anyhow!("不支持的输出格式: {}，应为 table、json 或 csv", s)),
+        }
+    }
+}
+
+impl fmt::Display for HistoryViewFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HistoryViewFormat::Table => write!(f, "table"),
+            HistoryViewFormat::Json => write!(f, "json"),
+            HistoryViewFormat::Csv => write!(f, "csv"),
+        }
+    }
+}
+
+/// 解析 `--since` / `--until` 的时间
+///
+/// `YYYY-MM-DD` 按 `offset` 时区取当天的开始，`end_of_day` 为true时取当天的最后一秒；也可以是RFC 3339格式的时间。
+pub fn parse_history_time(s: &str, offset: FixedOffset, end_of_day: bool) -> Result<DateTime<Utc>> {
+    let s = s.trim();
+    if let Ok(time) = DateTime::parse_from_rfc3339(s) {
+        return Ok(time.with_timezone(&Utc));
+    }
+    let date = NaiveDate::from_str(s)
+        .map_err(|_| anyhow!("无效的时间: {}，应为 YYYY-MM-DD 或 RFC 3339 格式", s))?;
+    let time = if end_of_day { NaiveTime::from_hms_opt(23, 59, 59).unwrap() } else { NaiveTime::MIN };
+    offset
+        .from_local_datetime(&date.and_time(time))
+        .single()
+        .map(|time| time.with_timezone(&Utc))
+        .ok_or_else(|| anyhow!("无效的时间: {}", s))
+}
+
+/// 表格和CSV中的一行
+#[derive(Debug, Serialize)]
+struct HistoryRow {
+    time: String,
+    asset: String,
+    direction: String,
+    buy_price: Decimal,
+    sell_price: Decimal,
+    amount: Decimal,
+    profit: Decimal,
+    status: String,
+    duration_ms: i64,
+}
+
+impl HistoryRow {
+    fn from_record(record: &TradeRecord, offset: FixedOffset) -> Self {
+        let result = &record.result;
+        Self {
+            time: result.start_time.with_timezone(&offset).format("%Y-%m-%d %H:%M:%S").to_string(),
+            asset: result.base_asset.clone(),
+            direction: format!("{}->{}", result.buy_quote, result.sell_quote),
+            buy_price: result.buy_price.normalize(),
+            sell_price: result.sell_price.normalize(),
+            amount: result.trade_amount.normalize(),
+            profit: result.profit.normalize(),
+            status: format!("{:?}", result.status),
+            duration_ms: record.duration_ms,
+        }
+    }
+
+    fn cells(&self) -> [String; COLUMNS] {
+        [
+            self.time.clone(),
+            self.asset.clone(),
+            self.direction.clone(),
+            self.buy_price.to_string(),
+            self.sell_price.to_string(),
+            self.amount.to_string(),
+            self.profit.to_string(),
+            self.status.clone(),
+            self.duration_ms.to_string(),
+        ]
+    }
+}
+
+/// 按格式逐批输出交易记录，表格和CSV的表头只输出一次
+pub struct HistoryPrinter<W: Write> {
+    sink: PrinterSink<W>,
+    /// 显示时间使用的时区
+    offset: FixedOffset,
+}
+
+enum PrinterSink<W: Write> {
+    /// 第一次输出后确定列宽
+    Table { writer: W, widths: Option<[usize; COLUMNS]> },
+    Json(W),
+    Csv(Box<csv::Writer<W>>),
+}
+
+impl<W: Write> HistoryPrinter<W> {
+    pub fn new(writer: W, format: HistoryViewFormat, offset: FixedOffset) -> Self {
+        let sink = match format {
+            HistoryViewFormat::Table => PrinterSink::Table { writer, widths: None },
+            HistoryViewFormat::Json => PrinterSink::Json(writer),
+            HistoryViewFormat::Csv => PrinterSink::Csv(Box::new(csv::Writer::from_writer(writer))),
+        };
+        Self { sink, offset }
+    }
+
+    /// 按给定顺序输出一批记录并刷新输出
+    ///
+    /// 表格在第一次调用时输出表头，即使没有记录；之后的行使用第一次确定的列宽，更长的值不截断。
+    pub fn print(&mut self, records: &[TradeRecord]) -> Result<()> {
+        let offset = self.offset;
+        match &mut self.sink {
+            PrinterSink::Table { writer, widths } => {
+                let rows: Vec<[String; COLUMNS]> = records
+                    .iter()
+                    .map(|record| HistoryRow::from_record(record, offset).cells())
+                    .collect();
+                if widths.is_none() {
+                    let mut computed = MIN_COLUMN_WIDTHS;
+                    for (index, width) in computed.iter_mut().enumerate() {
+                        *width = rows
+                            .iter()
+                            .map(|row| row[index].len())
+                            .chain([HEADERS[index].len(), *width])
+                            .max()
+                            .unwrap_or_default();
+                    }
+                    write_table_row(writer, &HEADERS.map(String::from), &computed)?;
+                    writeln!(writer, "{}", computed.map(|width| "-".repeat(width)).join("  "))?;
+                    *widths = Some(computed);
+                }
+                let widths = widths.unwrap_or(MIN_COLUMN_WIDTHS);
+                for row in &rows {
+                    write_table_row(writer, row, &widths)?;
+                }
+                writer.flush()?;
+            },
+            PrinterSink::Json(writer) => {
+                for record in records {
+                    serde_json::to_writer(&mut *writer, record)?;
+                    writer.write_all(b"\n")?;
+                }
+                writer.flush()?;
+            },
+            PrinterSink::Csv(writer) => {
+                for record in records {
+                    writer.serialize(HistoryRow::from_record(record, offset))?;
+                }
+                writer.flush()?;
+            },
+        }
+        Ok(())
+    }
+}
+
+fn write_table_row<W: Write>(writer: &mut W, cells: &[String; COLUMNS], widths: &[usize; COLUMNS]) -> Result<()> {
+    let line = cells
+        .iter()
+        .enumerate()
+        .map(|(index, cell)| {
+            if NUMERIC_COLUMNS[index] {
+                format!("{:>width$}", cell, width = widths[index])
+            } else {
+                format!("{:<width$}", cell, width = widths[index])
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("  ");
+    writeln!(writer, "{}", line.trim_end())?;
+    Ok(())
+}
+
+/// 查询交易历史并记住已输出的记录，持续跟踪时只返回新写入的记录
+pub struct HistoryBrowser {
+    filter: TradeHistoryFilter,
+    /// 已输出记录的最大ID
+    last_id: i64,
+}
+
+impl HistoryBrowser {
+    pub fn new(filter: TradeHistoryFilter) -> Self {
+        Self { filter, last_id: 0 }
+    }
+
+    /// 符合条件的最近 `limit` 条记录，按开始时间从早到晚排列
+    pub async fn latest(&mut self, db: &DatabaseManager, limit: i64) -> Result<Vec<TradeRecord>> {
+        let mut records = db.get_trade_history(&self.filter, limit, 0).await?;
+        records.reverse();
+        self.remember(&records);
+        Ok(records)
+    }
+
+    /// 上次查询之后新写入的记录，按开始时间从早到晚排列
+    pub async fn poll(&mut self, db: &DatabaseManager) -> Result<Vec<TradeRecord>> {
+        let mut records = db.get_trade_history(&self.filter, FOLLOW_BATCH_SIZE, 0).await?;
+        records.retain(|record| record.id > self.last_id);
+        records.reverse();
+        self.remember(&records);
+        Ok(records)
+    }
+
+    fn remember(&mut self, records: &[TradeRecord]) {
+        if let Some(max_id) = records.iter().map(|record| record.id).max() {
+            self.last_id = self.last_id.max(max_id);
+        }
+    }
+}
+
+#[cfg(all(test, feature = "db-sqlite"))]
+mod tests {
+    use super::*;
+    use crate::models::testing::arbitrage_result_at;
+    use crate::models::{ArbitrageResult, ArbitrageStatus};
+    use chrono::Duration as ChronoDuration;
+    use rust_decimal_macros::dec;
+
+    fn make_result(asset: &str, buy_quote: &str, sell_quote: &str, profit: Decimal, status: ArbitrageStatus, minute: i64) -> ArbitrageResult {
+        let start_time = Utc.with_ymd_and_hms(2024, 3, 1, 8, 0, 0).unwrap() + ChronoDuration::minutes(minute);
+        ArbitrageResult {
+            base_asset: asset.to_string(),
+            buy_quote: buy_quote.to_string(),
+            sell_quote: sell_quote.to_string(),
+            buy_price: dec!(50000.5),
+            trade_amount: dec!(0.002),
+            profit,
+            buy_order_id: None,
+            sell_order_id: None,
+            ..arbitrage_result_at(status, start_time, ChronoDuration::milliseconds(250 + minute))
+        }
+    }
+
+    async fn seeded_db() -> DatabaseManager {
+        let db = DatabaseManager::in_memory().await.unwrap();
+        for result in [
+            make_result("BTC", "USDT", "USDC", dec!(0.19), ArbitrageStatus::Completed, 0),
+            make_result("ETH", "USDC", "USDT", dec!(-0.05), ArbitrageStatus::Failed, 1),
+            make_result("BTC", "USDC", "USDT", dec!(0.215), ArbitrageStatus::Completed, 2),
+        ] {
+            db.record_arbitrage_result(&result).await.unwrap();
+        }
+        db
+    }
+
+    fn render(records: &[TradeRecord], format: HistoryViewFormat) -> String {
+        let mut output = Vec::new();
+        let offset = FixedOffset::east_opt(8 * 3600).unwrap();
+        HistoryPrinter::new(&mut output, format, offset).print(records).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_table_output() {
+        let db = seeded_db().await;
+        let records = HistoryBrowser::new(TradeHistoryFilter::default()).latest(&db, 10).await.unwrap();
+
+        let expected = "\
+time                 asset  direction      buy_price    sell_price      amount        profit  status     duration_ms
+-------------------  -----  ----------  ------------  ------------  ----------  ------------  ---------  -----------
+2024-03-01 16:00:00  BTC    USDT->USDC       50000.5         50100       0.002          0.19  Completed          250
+2024-03-01 16:01:00  ETH    USDC->USDT       50000.5         50100       0.002         -0.05  Failed             251
+2024-03-01 16:02:00  BTC    USDC->USDT       50000.5         50100       0.002         0.215  Completed          252
+";
+        assert_eq!(render(&records, HistoryViewFormat::Table), expected);
+    }
+
+    #[tokio::test]
+    async fn test_filtered_csv_output() {
+        let db = seeded_db().await;
+        let filter = TradeHistoryFilter {
+            asset: Some("BTC".to_string()),
+            status: Some(ArbitrageStatus::Completed),
+            ..TradeHistoryFilter::default()
+        };
+        let records = HistoryBrowser::new(filter).latest(&db, 1).await.unwrap();
+
+        let expected = "\
+time,asset,direction,buy_price,sell_price,amount,profit,status,duration_ms
+2024-03-01 16:02:00,BTC,USDC->USDT,50000.5,50100,0.002,0.215,Completed,252
+";
+        assert_eq!(render(&records, HistoryViewFormat::Csv), expected);
+
+        let json = render(&records, HistoryViewFormat::Json);
+        let record: TradeRecord = serde_json::from_str(json.trim_end()).unwrap();
+        assert_eq!(record.result.profit, dec!(0.215));
+    }
+
+    #[tokio::test]
+    async fn test_poll_returns_only_new_records() {
+        let db = seeded_db().await;
+        let mut browser = HistoryBrowser::new(TradeHistoryFilter::default());
+        assert_eq!(browser.latest(&db, 2).await.unwrap().len(), 2);
+        assert!(browser.poll(&db).await.unwrap().is_empty());
+
+        db.record_arbitrage_result(&make_result("BNB", "USDT", "USDC", dec!(0.3), ArbitrageStatus::Completed, 3)).await.unwrap();
+        db.record_arbitrage_result(&make_result("SOL", "USDT", "USDC", dec!(0.4), ArbitrageStatus::Completed, 4)).await.unwrap();
+        let assets: Vec<String> = browser.poll(&db).await.unwrap().into_iter().map(|r| r.result.base_asset).collect();
+        assert_eq!(assets, vec!["BNB", "SOL"]);
+        assert!(browser.poll(&db).await.unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_parse_history_time() {
+        let offset = FixedOffset::east_opt(8 * 3600).unwrap();
+        assert_eq!(
+            parse_history_time("2024-03-01", offset, false).unwrap(),
+            Utc.with_ymd_and_hms(2024, 2, 29, 16, 0, 0).unwrap()
+        );
+        assert_eq!(
+            parse_history_time("2024-03-01", offset, true).unwrap(),
+            Utc.with_ymd_and_hms(2024, 3, 1, 15, 59, 59).unwrap()
+        );
+        assert_eq!(
+            parse_history_time("2024-03-01T12:00:00Z", offset, false).unwrap(),
+            Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap()
+        );
+        assert!(parse_history_time("03/01/2024", offset, false).is_err());
+    }
+}
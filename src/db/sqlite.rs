@@ -0,0 +1,1753 @@
+//! SQLite存储后端，适用于不需要单独数据库服务的单机部署
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePool, SqlitePoolOptions};
+use sqlx::{QueryBuilder, Row, Sqlite, Transaction};
+use std::collections::{BTreeMap, HashMap};
+use std::str::FromStr;
+use super::{DatabaseOptions, TradeStore, TradeRecord, OrderRecord, TradeHistoryFilter, HistoryCursor, HistoryPage, TradeStats, DailyStats, HourlyStats, AssetStats, AssetStatsQuery, AssetDailyStats, AssetDailyTotals, StrategyStats, StrategyTotals, FeeSlippageStats, FeeSlippageTotals, RejectedOpportunity, StatsDelta, StatsDeltas, attach_orders, decimal_column, fill_columns, hourly_buckets, idempotency_key, opportunity_id_column, stats_bucket, stats_cutoff_date};
+use crate::models::{realized_slippage_percentage, ArbitrageResult, ArbitrageStatus, OrderInfo, OrderStatus, Side, TradingMode};
+use chrono::{DateTime, FixedOffset, Utc};
+use log::{info, debug};
+use rust_decimal::Decimal;
+use rust_decimal::prelude::ToPrimitive;
+
+/// SQLite交易存储
+pub struct SqliteTradeStore {
+    pool: SqlitePool,
+    stats_offset: FixedOffset,
+}
+
+impl SqliteTradeStore {
+    /// 打开（必要时创建）SQLite数据库并执行迁移，使用默认的连接选项
+    pub async fn new(database_url: &str) -> Result<Self> {
+        Self::connect(database_url, &DatabaseOptions::default()).await
+    }
+
+    /// 按指定的连接选项打开（必要时创建）SQLite数据库并执行迁移
+    pub async fn connect(database_url: &str, options: &DatabaseOptions) -> Result<Self> {
+        let connect_options = SqliteConnectOptions::from_str(database_url)
+            .context(format!("无效的SQLite连接字符串: {}", database_url))?
+            .create_if_missing(true);
+
+        // 内存数据库的每个连接都是独立的数据库，只能使用单个常驻连接
+        let in_memory = database_url.contains(":memory:");
+        let mut pool_options = SqlitePoolOptions::new()
+            .max_connections(if in_memory { 1 } else { options.max_connections.max(1) })
+            .acquire_timeout(options.acquire_timeout())
+            .test_before_acquire(options.test_before_acquire);
+        if in_memory {
+            pool_options = pool_options.idle_timeout(None).max_lifetime(None);
+        }
+
+        let pool = pool_options
+            .connect_with(connect_options)
+            .await
+            .context("无法打开SQLite数据库")?;
+
+        sqlx::migrate!("./migrations/sqlite")
+            .run(&pool)
+            .await
+            .context("SQLite数据库迁移失败")?;
+
+        info!("SQLite数据库初始化完成: {}", database_url);
+
+        Ok(Self { pool, stats_offset: options.stats_offset })
+    }
+
+    /// 设置每日和按小时统计分桶使用的时区
+    pub fn with_stats_offset(mut self, offset: FixedOffset) -> Self {
+        self.stats_offset = offset;
+        self
+    }
+
+    /// 追加按开始时间筛选的 `[start, end)` 条件
+    fn push_time_range(
+        query: &mut QueryBuilder<'_, Sqlite>,
+        start: Option<DateTime<Utc>>,
+        end: Option<DateTime<Utc>>,
+    ) {
+        if let Some(start) = start {
+            query.push(" AND start_time >= ").push_bind(start);
+        }
+        if let Some(end) = end {
+            query.push(" AND start_time < ").push_bind(end);
+        }
+    }
+
+    /// 更新汇总统计表（daily_stats/asset_stats）中的一行
+    /// 金额在程序中以Decimal累加后写回，避免SQLite对TEXT做浮点运算
+    async fn upsert_stats(
+        tx: &mut Transaction<'_, Sqlite>,
+        table: &str,
+        key_column: &str,
+        key: &str,
+        delta: &StatsDelta,
+    ) -> Result<()> {
+        let existing = sqlx::query(&format!(
+            "SELECT total_profit, total_volume FROM {} WHERE {} = ?",
+            table, key_column
+        ))
+        .bind(key)
+        .fetch_optional(&mut **tx)
+        .await?;
+
+        let (total_profit, total_volume) = match existing {
+            Some(row) => (
+                decimal_column(&row, "total_profit")? + delta.profit,
+                decimal_column(&row, "total_volume")? + delta.volume,
+            ),
+            None => (delta.profit, delta.volume),
+        };
+
+        sqlx::query(&format!(
+            r#"
+            INSERT INTO {table} ({key_column}, trades, successful_trades, failed_trades, total_profit, total_volume)
+            VALUES (?, ?, ?, ?, ?, ?)
+            ON CONFLICT({key_column}) DO UPDATE SET
+                trades = trades + excluded.trades,
+                successful_trades = successful_trades + excluded.successful_trades,
+                failed_trades = failed_trades + excluded.failed_trades,
+                total_profit = excluded.total_profit,
+                total_volume = excluded.total_volume
+            "#,
+            table = table,
+            key_column = key_column
+        ))
+        .bind(key)
+        .bind(delta.trades)
+        .bind(delta.successful_trades)
+        .bind(delta.failed_trades)
+        .bind(total_profit.to_string())
+        .bind(total_volume.to_string())
+        .execute(&mut **tx)
+        .await?;
+
+        Ok(())
+    }
+
+    /// 累加按小时统计中的一行
+    async fn upsert_hourly_stats(
+        tx: &mut Transaction<'_, Sqlite>,
+        date: &str,
+        hour: u32,
+        delta: &StatsDelta,
+    ) -> Result<()> {
+        let existing = sqlx::query("SELECT total_profit, total_volume FROM hourly_stats WHERE date = ? AND hour = ?")
+            .bind(date)
+            .bind(hour as i64)
+            .fetch_optional(&mut **tx)
+            .await?;
+
+        let (total_profit, total_volume) = match existing {
+            Some(row) => (
+                decimal_column(&row, "total_profit")? + delta.profit,
+                decimal_column(&row, "total_volume")? + delta.volume,
+            ),
+            None => (delta.profit, delta.volume),
+        };
+
+        sqlx::query(
+            r#"
+            INSERT INTO hourly_stats (date, hour, trades, successful_trades, failed_trades, total_profit, total_volume)
+            VALUES (?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(date, hour) DO UPDATE SET
+                trades = trades + excluded.trades,
+                successful_trades = successful_trades + excluded.successful_trades,
+                failed_trades = failed_trades + excluded.failed_trades,
+                total_profit = excluded.total_profit,
+                total_volume = excluded.total_volume
+            "#,
+        )
+        .bind(date)
+        .bind(hour as i64)
+        .bind(delta.trades)
+        .bind(delta.successful_trades)
+        .bind(delta.failed_trades)
+        .bind(total_profit.to_string())
+        .bind(total_volume.to_string())
+        .execute(&mut **tx)
+        .await?;
+
+        Ok(())
+    }
+
+    /// 在事务中将增量写入每日统计、按小时统计和币种统计
+    async fn upsert_deltas(tx: &mut Transaction<'_, Sqlite>, deltas: &StatsDeltas) -> Result<()> {
+        for (date, delta) in &deltas.daily {
+            Self::upsert_stats(tx, "daily_stats", "date", date, delta).await?;
+        }
+        for ((date, hour), delta) in &deltas.hourly {
+            Self::upsert_hourly_stats(tx, date, *hour, delta).await?;
+        }
+        for (asset, delta) in &deltas.assets {
+            Self::upsert_stats(tx, "asset_stats", "asset", asset, delta).await?;
+        }
+
+        Ok(())
+    }
+
+    /// 在事务中插入交易历史，返回记录ID和是否为新写入的记录
+    async fn insert_history(tx: &mut Transaction<'_, Sqlite>, result: &ArbitrageResult) -> Result<(i64, bool)> {
+        let duration_ms = (result.end_time - result.start_time).num_milliseconds();
+        let key = idempotency_key(result);
+
+        // 插入交易历史，幂等键已存在时不做任何修改
+        let inserted = sqlx::query(
+            r#"
+            INSERT INTO arbitrage_history
+            (base_asset, buy_quote, sell_quote, buy_price, sell_price,
+             trade_amount, profit, profit_percentage, buy_order_id, sell_order_id,
+             status, start_time, end_time, duration_ms, idempotency_key,
+             buy_fill_qty, buy_fill_price, buy_commission, buy_commission_asset,
+             sell_fill_qty, sell_fill_price, sell_commission, sell_commission_asset,
+             mode, config_hash, strategy, opportunity_id)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(idempotency_key) DO NOTHING
+            "#,
+        )
+        .bind(&result.base_asset)
+        .bind(&result.buy_quote)
+        .bind(&result.sell_quote)
+        .bind(result.buy_price.to_string())
+        .bind(result.sell_price.to_string())
+        .bind(result.trade_amount.to_string())
+        .bind(result.profit.to_string())
+        .bind(result.profit_percentage.to_string())
+        .bind(result.buy_order_id.map(|id| id as i64))
+        .bind(result.sell_order_id.map(|id| id as i64))
+        .bind(format!("{:?}", result.status))
+        .bind(result.start_time)
+        .bind(result.end_time)
+        .bind(duration_ms)
+        .bind(&key)
+        .bind(result.buy_fill.qty.to_string())
+        .bind(result.buy_fill.avg_price.to_string())
+        .bind(result.buy_fill.commission.to_string())
+        .bind(&result.buy_fill.commission_asset)
+        .bind(result.sell_fill.qty.to_string())
+        .bind(result.sell_fill.avg_price.to_string())
+        .bind(result.sell_fill.commission.to_string())
+        .bind(&result.sell_fill.commission_asset)
+        .bind(result.mode.to_string())
+        .bind(&result.config_hash)
+        .bind(&result.strategy)
+        .bind(result.opportunity_id.map(|id| id.to_string()))
+        .execute(&mut **tx)
+        .await?;
+
+        if inserted.rows_affected() == 0 {
+            let id: i64 = sqlx::query_scalar("SELECT id FROM arbitrage_history WHERE idempotency_key = ?")
+                .bind(&key)
+                .fetch_one(&mut **tx)
+                .await?;
+
+            debug!("套利结果已存在，跳过重复写入: ID={}, 幂等键={}", id, key);
+            return Ok((id, false));
+        }
+
+        Ok((inserted.last_insert_rowid(), true))
+    }
+
+    /// 查询历史交易记录，按 `(start_time, id)` 倒序，可以从游标之后开始并跳过 `offset` 条
+    async fn query_history(
+        &self,
+        filter: &TradeHistoryFilter,
+        limit: i64,
+        cursor: Option<&HistoryCursor>,
+        offset: i64,
+    ) -> Result<Vec<TradeRecord>> {
+        let mut query = QueryBuilder::<Sqlite>::new(
+            r#"
+            SELECT
+                id, base_asset, buy_quote, sell_quote,
+                buy_price, sell_price, trade_amount, profit,
+                profit_percentage, buy_order_id, sell_order_id,
+                status, start_time, end_time, duration_ms,
+                buy_fill_qty, buy_fill_price, buy_commission, buy_commission_asset,
+                sell_fill_qty, sell_fill_price, sell_commission, sell_commission_asset,
+                mode, config_hash, strategy, opportunity_id
+            FROM arbitrage_history
+            WHERE 1=1
+            "#,
+        );
+
+        if let Some(asset) = &filter.asset {
+            query.push(" AND base_asset = ").push_bind(asset.clone());
+        }
+
+        if let Some(status) = filter.status {
+            query.push(" AND status = ").push_bind(format!("{:?}", status));
+        }
+
+        if let Some(start) = filter.start_date {
+            query.push(" AND start_time >= ").push_bind(start);
+        }
+
+        if let Some(end) = filter.end_date {
+            query.push(" AND start_time <= ").push_bind(end);
+        }
+
+        // 金额以TEXT存储，比较时转换为数值
+        if let Some(min_profit) = filter.min_profit {
+            query.push(" AND CAST(profit AS REAL) >= ").push_bind(min_profit.to_f64().unwrap_or(f64::MIN));
+        }
+
+        if let Some(max_profit) = filter.max_profit {
+            query.push(" AND CAST(profit AS REAL) <= ").push_bind(max_profit.to_f64().unwrap_or(f64::MAX));
+        }
+
+        if let Some(buy_quote) = &filter.buy_quote {
+            query.push(" AND buy_quote = ").push_bind(buy_quote.clone());
+        }
+
+        if let Some(sell_quote) = &filter.sell_quote {
+            query.push(" AND sell_quote = ").push_bind(sell_quote.clone());
+        }
+
+        if let Some(mode) = filter.mode {
+            query.push(" AND mode = ").push_bind(mode.to_string());
+        }
+
+        // 游标之后的记录：开始时间更早，或开始时间相同但ID更小
+        if let Some(cursor) = cursor {
+            query.push(" AND (start_time < ").push_bind(cursor.start_time);
+            query.push(" OR (start_time = ").push_bind(cursor.start_time);
+            query.push(" AND id < ").push_bind(cursor.id).push("))");
+        }
+
+        query.push(" ORDER BY start_time DESC, id DESC LIMIT ").push_bind(limit);
+        query.push(" OFFSET ").push_bind(offset);
+
+        let rows = query.build().fetch_all(&self.pool).await?;
+
+        let mut results = Vec::new();
+
+        for row in rows {
+            let status: String = row.try_get("status")?;
+            let mode: String = row.try_get("mode")?;
+            let start_time: DateTime<Utc> = row.try_get("start_time")?;
+            let end_time: Option<DateTime<Utc>> = row.try_get("end_time")?;
+            let duration_ms: Option<i64> = row.try_get("duration_ms")?;
+            let buy_order_id: Option<i64> = row.try_get("buy_order_id")?;
+            let sell_order_id: Option<i64> = row.try_get("sell_order_id")?;
+
+            let result = ArbitrageResult {
+                base_asset: row.try_get("base_asset")?,
+                buy_quote: row.try_get("buy_quote")?,
+                sell_quote: row.try_get("sell_quote")?,
+                buy_price: decimal_column(&row, "buy_price")?,
+                sell_price: decimal_column(&row, "sell_price")?,
+                trade_amount: decimal_column(&row, "trade_amount")?,
+                profit: decimal_column(&row, "profit")?,
+                profit_percentage: decimal_column(&row, "profit_percentage")?,
+                buy_order_id: buy_order_id.map(|id| id as u64),
+                sell_order_id: sell_order_id.map(|id| id as u64),
+                status: status.parse::<ArbitrageStatus>()?,
+                timestamp: start_time,
+                start_time,
+                end_time: start_time,
+                buy_fill: fill_columns(&row, "buy")?,
+                sell_fill: fill_columns(&row, "sell")?,
+                mode: mode.parse::<TradingMode>()?,
+                config_hash: row.try_get("config_hash")?,
+                strategy: row.try_get("strategy")?,
+                opportunity_id: opportunity_id_column(&row)?,
+            };
+
+            results.push(TradeRecord::from_parts(row.try_get("id")?, result, end_time, duration_ms));
+        }
+
+        if filter.include_orders {
+            let ids: Vec<i64> = results.iter().map(|record| record.id).collect();
+            let orders = self.query_orders(&ids).await?;
+            attach_orders(&mut results, orders);
+        }
+
+        Ok(results)
+    }
+
+    /// 查询指定套利记录的订单记录，按写入顺序排列
+    async fn query_orders(&self, arbitrage_ids: &[i64]) -> Result<Vec<OrderRecord>> {
+        if arbitrage_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut query = QueryBuilder::<Sqlite>::new(
+            r#"
+            SELECT
+                id, arbitrage_id, order_id, symbol, side, price,
+                requested_qty, executed_qty, avg_price, status,
+                commission, commission_asset, created_at, filled_at
+            FROM orders
+            WHERE arbitrage_id IN (
+            "#,
+        );
+        let mut ids = query.separated(", ");
+        for id in arbitrage_ids {
+            ids.push_bind(*id);
+        }
+        query.push(") ORDER BY id");
+
+        let rows = query.build().fetch_all(&self.pool).await?;
+
+        let mut orders = Vec::new();
+
+        for row in rows {
+            let order_id: i64 = row.try_get("order_id")?;
+            let side: String = row.try_get("side")?;
+            let status: String = row.try_get("status")?;
+
+            orders.push(OrderRecord {
+                id: row.try_get("id")?,
+                arbitrage_id: row.try_get("arbitrage_id")?,
+                order: OrderInfo {
+                    order_id: order_id as u64,
+                    symbol: row.try_get("symbol")?,
+                    price: decimal_column(&row, "price")?,
+                    qty: decimal_column(&row, "requested_qty")?,
+                    executed_qty: decimal_column(&row, "executed_qty")?,
+                    avg_price: decimal_column(&row, "avg_price")?,
+                    commission: decimal_column(&row, "commission")?,
+                    commission_asset: row.try_get("commission_asset")?,
+                    side: side.parse::<Side>()?,
+                    status: status.parse::<OrderStatus>()?,
+                    timestamp: row.try_get("created_at")?,
+                },
+                filled_at: row.try_get("filled_at")?,
+            });
+        }
+
+        Ok(orders)
+    }
+}
+
+#[async_trait]
+impl TradeStore for SqliteTradeStore {
+    fn backend_name(&self) -> &str {
+        "sqlite"
+    }
+
+    fn stats_offset(&self) -> FixedOffset {
+        self.stats_offset
+    }
+
+    async fn ping(&self) -> Result<()> {
+        sqlx::query("SELECT 1").execute(&self.pool).await?;
+        Ok(())
+    }
+
+    async fn record_arbitrage_result(&self, result: &ArbitrageResult) -> Result<i64> {
+        let mut tx = self.pool.begin().await?;
+
+        let (id, inserted) = Self::insert_history(&mut tx, result).await?;
+        if !inserted {
+            tx.rollback().await?;
+            return Ok(id);
+        }
+
+        // 更新每日统计、按小时统计和币种统计
+        Self::upsert_deltas(&mut tx, &StatsDeltas::from_result(result, self.stats_offset)).await?;
+
+        tx.commit().await?;
+
+        debug!("记录套利结果: ID={}, 资产={}, 利润={}", id, result.base_asset, result.profit);
+
+        Ok(id)
+    }
+
+    async fn record_arbitrage_history(&self, result: &ArbitrageResult) -> Result<(i64, bool)> {
+        let mut tx = self.pool.begin().await?;
+        let (id, inserted) = Self::insert_history(&mut tx, result).await?;
+        tx.commit().await?;
+
+        if inserted {
+            debug!("记录套利历史: ID={}, 资产={}, 利润={}", id, result.base_asset, result.profit);
+        }
+
+        Ok((id, inserted))
+    }
+
+    async fn apply_stats_deltas(&self, deltas: &StatsDeltas) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        Self::upsert_deltas(&mut tx, deltas).await?;
+        tx.commit().await?;
+
+        debug!("写入汇总统计增量: {} 笔交易", deltas.trades());
+
+        Ok(())
+    }
+
+    async fn record_order(&self, arbitrage_id: i64, record: &OrderRecord) -> Result<i64> {
+        let order = &record.order;
+
+        let inserted = sqlx::query(
+            r#"
+            INSERT INTO orders
+            (arbitrage_id, order_id, symbol, side, price,
+             requested_qty, executed_qty, avg_price, status,
+             commission, commission_asset, created_at, filled_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(arbitrage_id, order_id) DO NOTHING
+            "#,
+        )
+        .bind(arbitrage_id)
+        .bind(order.order_id as i64)
+        .bind(&order.symbol)
+        .bind(order.side.to_string())
+        .bind(order.price.to_string())
+        .bind(order.qty.to_string())
+        .bind(order.executed_qty.to_string())
+        .bind(order.avg_price.to_string())
+        .bind(format!("{:?}", order.status))
+        .bind(order.commission.to_string())
+        .bind(&order.commission_asset)
+        .bind(order.timestamp)
+        .bind(record.filled_at)
+        .execute(&self.pool)
+        .await?;
+
+        if inserted.rows_affected() == 0 {
+            let id: i64 = sqlx::query_scalar("SELECT id FROM orders WHERE arbitrage_id = ? AND order_id = ?")
+                .bind(arbitrage_id)
+                .bind(order.order_id as i64)
+                .fetch_one(&self.pool)
+                .await?;
+
+            debug!("订单记录已存在，跳过重复写入: ID={}, 订单ID={}", id, order.order_id);
+            return Ok(id);
+        }
+
+        debug!("记录订单: 套利ID={}, 订单ID={}, 状态={:?}", arbitrage_id, order.order_id, order.status);
+
+        Ok(inserted.last_insert_rowid())
+    }
+
+    async fn get_overall_stats(
+        &self,
+        start: Option<DateTime<Utc>>,
+        end: Option<DateTime<Utc>>,
+    ) -> Result<TradeStats> {
+        let mut query = QueryBuilder::<Sqlite>::new(
+            "SELECT status, profit, trade_amount, duration_ms FROM arbitrage_history WHERE 1=1",
+        );
+        Self::push_time_range(&mut query, start, end);
+
+        let rows = query.build().fetch_all(&self.pool).await?;
+
+        let mut stats = TradeStats {
+            total_trades: 0,
+            successful_trades: 0,
+            failed_trades: 0,
+            total_profit: Decimal::ZERO,
+            total_volume: Decimal::ZERO,
+            avg_profit_per_trade: Decimal::ZERO,
+            max_profit: Decimal::ZERO,
+            max_loss: Decimal::ZERO,
+            avg_trade_duration_ms: 0,
+            winning_trades: 0,
+            losing_trades: 0,
+            avg_win: Decimal::ZERO,
+            avg_loss: Decimal::ZERO,
+        };
+
+        if rows.is_empty() {
+            return Ok(stats);
+        }
+
+        let mut max_profit = Decimal::MIN;
+        let mut min_profit = Decimal::MAX;
+        let mut total_duration_ms: i64 = 0;
+        let mut total_win = Decimal::ZERO;
+        let mut total_loss = Decimal::ZERO;
+
+        for row in &rows {
+            let status: String = row.try_get("status")?;
+            let profit = decimal_column(row, "profit")?;
+            let volume = decimal_column(row, "trade_amount")?;
+            let duration_ms: i64 = row.try_get("duration_ms")?;
+
+            stats.total_trades += 1;
+            if status == "Completed" {
+                stats.successful_trades += 1;
+            } else {
+                stats.failed_trades += 1;
+            }
+
+            stats.total_profit += profit;
+            stats.total_volume += volume;
+            max_profit = max_profit.max(profit);
+            min_profit = min_profit.min(profit);
+            total_duration_ms += duration_ms;
+
+            if profit > Decimal::ZERO {
+                stats.winning_trades += 1;
+                total_win += profit;
+            } else if profit < Decimal::ZERO {
+                stats.losing_trades += 1;
+                total_loss += profit;
+            }
+        }
+
+        stats.avg_profit_per_trade = stats.total_profit / Decimal::from(stats.total_trades);
+        stats.max_profit = max_profit;
+        stats.max_loss = min_profit;
+        stats.avg_trade_duration_ms = total_duration_ms / stats.total_trades;
+        if stats.winning_trades > 0 {
+            stats.avg_win = total_win / Decimal::from(stats.winning_trades);
+        }
+        if stats.losing_trades > 0 {
+            stats.avg_loss = total_loss / Decimal::from(stats.losing_trades);
+        }
+
+        Ok(stats)
+    }
+
+    async fn get_daily_stats(&self, days: i32) -> Result<Vec<DailyStats>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT date, trades, successful_trades, total_profit, total_volume
+            FROM daily_stats
+            WHERE date >= date('now', ?)
+            ORDER BY date
+            "#,
+        )
+        .bind(format!("-{} days", days))
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut stats = Vec::new();
+
+        for row in rows {
+            let trades: i64 = row.try_get("trades")?;
+            let successful_trades: i64 = row.try_get("successful_trades")?;
+            let successful_rate = if trades > 0 {
+                successful_trades as f64 / trades as f64 * 100.0
+            } else {
+                0.0
+            };
+
+            stats.push(DailyStats {
+                date: row.try_get("date")?,
+                trades,
+                profit: decimal_column(&row, "total_profit")?,
+                volume: decimal_column(&row, "total_volume")?,
+                successful_rate,
+            });
+        }
+
+        Ok(stats)
+    }
+
+    async fn get_daily_stats_between(
+        &self,
+        start: Option<DateTime<Utc>>,
+        end: Option<DateTime<Utc>>,
+    ) -> Result<Vec<DailyStats>> {
+        let mut query = QueryBuilder::<Sqlite>::new(
+            "SELECT start_time, status, profit, trade_amount FROM arbitrage_history WHERE 1=1",
+        );
+        Self::push_time_range(&mut query, start, end);
+
+        let rows = query.build().fetch_all(&self.pool).await?;
+
+        // 日期 -> (交易数, 成功数, 利润, 交易量)，按日期升序
+        let mut days: BTreeMap<String, (i64, i64, Decimal, Decimal)> = BTreeMap::new();
+
+        for row in &rows {
+            let start_time: DateTime<Utc> = row.try_get("start_time")?;
+            let status: String = row.try_get("status")?;
+            let day = days
+                .entry(stats_bucket(start_time, self.stats_offset).0)
+                .or_insert((0, 0, Decimal::ZERO, Decimal::ZERO));
+
+            day.0 += 1;
+            if status == "Completed" {
+                day.1 += 1;
+            }
+            day.2 += decimal_column(row, "profit")?;
+            day.3 += decimal_column(row, "trade_amount")?;
+        }
+
+        Ok(days
+            .into_iter()
+            .map(|(date, (trades, successful_trades, profit, volume))| DailyStats {
+                date,
+                trades,
+                profit,
+                volume,
+                successful_rate: successful_trades as f64 / trades as f64 * 100.0,
+            })
+            .collect())
+    }
+
+    async fn get_hourly_stats(&self, days: i32) -> Result<Vec<HourlyStats>> {
+        let rows = sqlx::query(
+            "SELECT hour, trades, successful_trades, total_profit, total_volume FROM hourly_stats WHERE date >= ?",
+        )
+        .bind(stats_cutoff_date(days, self.stats_offset).format("%Y-%m-%d").to_string())
+        .fetch_all(&self.pool)
+        .await?;
+
+        // 小时 -> (交易数, 成功数, 利润, 交易量)
+        let mut hours: BTreeMap<u32, (i64, i64, Decimal, Decimal)> = BTreeMap::new();
+
+        for row in &rows {
+            let hour: i64 = row.try_get("hour")?;
+            let bucket = hours.entry(hour as u32).or_insert((0, 0, Decimal::ZERO, Decimal::ZERO));
+            bucket.0 += row.try_get::<i64, _>("trades")?;
+            bucket.1 += row.try_get::<i64, _>("successful_trades")?;
+            bucket.2 += decimal_column(row, "total_profit")?;
+            bucket.3 += decimal_column(row, "total_volume")?;
+        }
+
+        Ok(hourly_buckets(&hours))
+    }
+
+    async fn get_hourly_stats_between(
+        &self,
+        start: Option<DateTime<Utc>>,
+        end: Option<DateTime<Utc>>,
+    ) -> Result<Vec<HourlyStats>> {
+        let mut query = QueryBuilder::<Sqlite>::new(
+            "SELECT start_time, status, profit, trade_amount FROM arbitrage_history WHERE 1=1",
+        );
+        Self::push_time_range(&mut query, start, end);
+
+        let rows = query.build().fetch_all(&self.pool).await?;
+
+        let mut hours: BTreeMap<u32, (i64, i64, Decimal, Decimal)> = BTreeMap::new();
+
+        for row in &rows {
+            let start_time: DateTime<Utc> = row.try_get("start_time")?;
+            let status: String = row.try_get("status")?;
+            let bucket = hours
+                .entry(stats_bucket(start_time, self.stats_offset).1)
+                .or_insert((0, 0, Decimal::ZERO, Decimal::ZERO));
+
+            bucket.0 += 1;
+            if status == "Completed" {
+                bucket.1 += 1;
+            }
+            bucket.2 += decimal_column(row, "profit")?;
+            bucket.3 += decimal_column(row, "trade_amount")?;
+        }
+
+        Ok(hourly_buckets(&hours))
+    }
+
+    async fn get_asset_stats(&self, filter: &AssetStatsQuery) -> Result<Vec<AssetStats>> {
+        let mut query = QueryBuilder::<Sqlite>::new(
+            "SELECT base_asset, status, profit, trade_amount FROM arbitrage_history WHERE 1=1",
+        );
+        Self::push_time_range(&mut query, filter.start, filter.end);
+        if let Some(asset) = &filter.asset {
+            query.push(" AND base_asset = ").push_bind(asset.clone());
+        }
+
+        let rows = query.build().fetch_all(&self.pool).await?;
+
+        // 币种 -> (交易数, 成功数, 利润, 交易量)
+        let mut assets: HashMap<String, (i64, i64, Decimal, Decimal)> = HashMap::new();
+
+        for row in &rows {
+            let asset: String = row.try_get("base_asset")?;
+            let status: String = row.try_get("status")?;
+            let entry = assets.entry(asset).or_insert((0, 0, Decimal::ZERO, Decimal::ZERO));
+            entry.0 += 1;
+            if status == "Completed" {
+                entry.1 += 1;
+            }
+            entry.2 += decimal_column(row, "profit")?;
+            entry.3 += decimal_column(row, "trade_amount")?;
+        }
+
+        let mut stats: Vec<AssetStats> = assets
+            .into_iter()
+            .map(|(asset, (trades, successful, profit, volume))| {
+                AssetStats::from_totals(asset, trades, successful, profit, volume)
+            })
+            .collect();
+
+        filter.sort_by.sort(&mut stats);
+        stats.truncate(filter.limit.max(0) as usize);
+
+        Ok(stats)
+    }
+
+    async fn get_asset_daily_stats(
+        &self,
+        start: Option<DateTime<Utc>>,
+        end: Option<DateTime<Utc>>,
+    ) -> Result<Vec<AssetDailyStats>> {
+        let mut query = QueryBuilder::<Sqlite>::new(
+            "SELECT start_time, base_asset, status, profit, trade_amount FROM arbitrage_history WHERE 1=1",
+        );
+        Self::push_time_range(&mut query, start, end);
+
+        let rows = query.build().fetch_all(&self.pool).await?;
+
+        let mut totals = AssetDailyTotals::default();
+        for row in &rows {
+            let start_time: DateTime<Utc> = row.try_get("start_time")?;
+            let status: String = row.try_get("status")?;
+            totals.add(
+                stats_bucket(start_time, self.stats_offset).0,
+                row.try_get("base_asset")?,
+                status == "Completed",
+                decimal_column(row, "profit")?,
+                decimal_column(row, "trade_amount")?,
+            );
+        }
+
+        Ok(totals.into_stats())
+    }
+
+    async fn get_strategy_stats(
+        &self,
+        start: Option<DateTime<Utc>>,
+        end: Option<DateTime<Utc>>,
+    ) -> Result<Vec<StrategyStats>> {
+        let mut query = QueryBuilder::<Sqlite>::new(
+            r#"
+            SELECT strategy, status, profit, buy_price, sell_price,
+                buy_fill_qty, buy_fill_price, buy_commission, buy_commission_asset,
+                sell_fill_qty, sell_fill_price, sell_commission, sell_commission_asset
+            FROM arbitrage_history
+            WHERE 1=1
+            "#,
+        );
+        Self::push_time_range(&mut query, start, end);
+
+        let rows = query.build().fetch_all(&self.pool).await?;
+
+        let mut totals = StrategyTotals::default();
+        for row in &rows {
+            let status: String = row.try_get("status")?;
+            let slippage = realized_slippage_percentage(
+                decimal_column(row, "buy_price")?,
+                decimal_column(row, "sell_price")?,
+                &fill_columns(row, "buy")?,
+                &fill_columns(row, "sell")?,
+            );
+            totals.add(row.try_get("strategy")?, status == "Completed", decimal_column(row, "profit")?, slippage);
+        }
+
+        Ok(totals.into_stats())
+    }
+
+    async fn get_fee_slippage_stats(
+        &self,
+        start: Option<DateTime<Utc>>,
+        end: Option<DateTime<Utc>>,
+    ) -> Result<Option<FeeSlippageStats>> {
+        let mut query = QueryBuilder::<Sqlite>::new(
+            r#"
+            SELECT base_asset, profit, buy_price, sell_price,
+                buy_fill_qty, buy_fill_price, buy_commission, buy_commission_asset,
+                sell_fill_qty, sell_fill_price, sell_commission, sell_commission_asset
+            FROM arbitrage_history
+            WHERE 1=1
+            "#,
+        );
+        Self::push_time_range(&mut query, start, end);
+
+        let rows = query.build().fetch_all(&self.pool).await?;
+
+        let mut totals = FeeSlippageTotals::default();
+        for row in &rows {
+            let base_asset: String = row.try_get("base_asset")?;
+            totals.add(
+                &base_asset,
+                decimal_column(row, "buy_price")?,
+                decimal_column(row, "sell_price")?,
+                &fill_columns(row, "buy")?,
+                &fill_columns(row, "sell")?,
+                decimal_column(row, "profit")?,
+            );
+        }
+
+        Ok(totals.into_stats())
+    }
+
+    async fn record_rejection(&self, rejection: &RejectedOpportunity) -> Result<i64> {
+        let inserted = sqlx::query(
+            r#"
+            INSERT INTO rejected_opportunities
+            (base_asset, buy_quote, sell_quote, buy_price, sell_price, trade_amount,
+             profit_percentage, controller, reason, strategy, mode, rejected_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&rejection.base_asset)
+        .bind(&rejection.buy_quote)
+        .bind(&rejection.sell_quote)
+        .bind(rejection.buy_price.to_string())
+        .bind(rejection.sell_price.to_string())
+        .bind(rejection.trade_amount.to_string())
+        .bind(rejection.profit_percentage.to_string())
+        .bind(&rejection.controller)
+        .bind(&rejection.reason)
+        .bind(&rejection.strategy)
+        .bind(rejection.mode.to_string())
+        .bind(rejection.rejected_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(inserted.last_insert_rowid())
+    }
+
+    async fn get_rejections(
+        &self,
+        start: Option<DateTime<Utc>>,
+        end: Option<DateTime<Utc>>,
+    ) -> Result<Vec<RejectedOpportunity>> {
+        let mut query = QueryBuilder::<Sqlite>::new("SELECT * FROM rejected_opportunities WHERE 1=1");
+        if let Some(start) = start {
+            query.push(" AND rejected_at >= ").push_bind(start);
+        }
+        if let Some(end) = end {
+            query.push(" AND rejected_at < ").push_bind(end);
+        }
+        query.push(" ORDER BY rejected_at, id");
+
+        let rows = query.build().fetch_all(&self.pool).await?;
+
+        let mut rejections = Vec::new();
+        for row in &rows {
+            let mode: String = row.try_get("mode")?;
+            rejections.push(RejectedOpportunity {
+                base_asset: row.try_get("base_asset")?,
+                buy_quote: row.try_get("buy_quote")?,
+                sell_quote: row.try_get("sell_quote")?,
+                buy_price: decimal_column(row, "buy_price")?,
+                sell_price: decimal_column(row, "sell_price")?,
+                trade_amount: decimal_column(row, "trade_amount")?,
+                profit_percentage: decimal_column(row, "profit_percentage")?,
+                controller: row.try_get("controller")?,
+                reason: row.try_get("reason")?,
+                strategy: row.try_get("strategy")?,
+                mode: mode.parse::<TradingMode>()?,
+                rejected_at: row.try_get("rejected_at")?,
+            });
+        }
+
+        Ok(rejections)
+    }
+
+    async fn get_trade_history(
+        &self,
+        filter: &TradeHistoryFilter,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<TradeRecord>> {
+        self.query_history(filter, limit, None, offset).await
+    }
+
+    async fn get_trade_history_page(
+        &self,
+        filter: &TradeHistoryFilter,
+        limit: i64,
+        cursor: Option<&HistoryCursor>,
+    ) -> Result<HistoryPage> {
+        // 多查询一行判断是否还有下一页
+        let rows = self.query_history(filter, limit + 1, cursor, 0).await?;
+        Ok(HistoryPage::from_rows(rows, limit))
+    }
+}
+
+// 完整的存储测试，使用内存SQLite数据库，无需外部数据库服务
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{BufferedWriter, WriteBufferConfig};
+    use crate::models::testing::arbitrage_result_at;
+    use crate::models::FillInfo;
+    use chrono::Duration;
+    use rust_decimal_macros::dec;
+
+    async fn get_test_store() -> SqliteTradeStore {
+        SqliteTradeStore::new("sqlite::memory:").await.expect("创建内存SQLite数据库失败")
+    }
+
+    fn make_result(asset: &str, profit: Decimal, status: ArbitrageStatus) -> ArbitrageResult {
+        ArbitrageResult {
+            base_asset: asset.to_string(),
+            profit,
+            ..arbitrage_result_at(status, Utc::now(), Duration::milliseconds(1500))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_record_arbitrage_result() {
+        let store = get_test_store().await;
+
+        let id = store.record_arbitrage_result(&make_result("BTC", dec!(10), ArbitrageStatus::Completed))
+            .await
+            .expect("记录套利结果失败");
+        assert!(id > 0);
+
+        let id2 = store.record_arbitrage_result(&make_result("BTC", dec!(5), ArbitrageStatus::Completed))
+            .await
+            .unwrap();
+        assert!(id2 > id);
+    }
+
+    #[tokio::test]
+    async fn test_record_is_idempotent() {
+        let store = get_test_store().await;
+        let result = make_result("BTC", dec!(10), ArbitrageStatus::Completed);
+
+        let id = store.record_arbitrage_result(&result).await.unwrap();
+        let retried_id = store.record_arbitrage_result(&result).await.unwrap();
+        assert_eq!(id, retried_id);
+
+        let stats = store.get_overall_stats(None, None).await.unwrap();
+        assert_eq!(stats.total_trades, 1);
+        assert_eq!(stats.total_profit, dec!(10));
+
+        let daily = store.get_daily_stats(1).await.unwrap();
+        assert_eq!(daily[0].trades, 1);
+        let assets = store.get_asset_stats(&AssetStatsQuery::between(10, None, None)).await.unwrap();
+        assert_eq!(assets[0].trades, 1);
+        assert_eq!(assets[0].profit, dec!(10));
+    }
+
+    #[tokio::test]
+    async fn test_record_is_atomic() {
+        let store = get_test_store().await;
+
+        // 让币种统计的写入失败，模拟第一条语句之后的错误
+        sqlx::query(
+            "CREATE TRIGGER fail_asset_stats BEFORE INSERT ON asset_stats BEGIN SELECT RAISE(ABORT, 'forced failure'); END",
+        )
+        .execute(&store.pool)
+        .await
+        .unwrap();
+
+        let result = make_result("BTC", dec!(10), ArbitrageStatus::Completed);
+        assert!(store.record_arbitrage_result(&result).await.is_err());
+
+        // 交易历史和每日统计均未写入
+        assert_eq!(store.get_overall_stats(None, None).await.unwrap().total_trades, 0);
+        assert!(store.get_daily_stats(1).await.unwrap().is_empty());
+
+        // 故障排除后可以重新写入同一结果
+        sqlx::query("DROP TRIGGER fail_asset_stats").execute(&store.pool).await.unwrap();
+        store.record_arbitrage_result(&result).await.unwrap();
+        assert_eq!(store.get_overall_stats(None, None).await.unwrap().total_trades, 1);
+        assert_eq!(store.get_asset_stats(&AssetStatsQuery::between(10, None, None)).await.unwrap()[0].trades, 1);
+    }
+
+    #[tokio::test]
+    async fn test_overall_stats() {
+        let store = get_test_store().await;
+
+        store.record_arbitrage_result(&make_result("BTC", dec!(10.5), ArbitrageStatus::Completed)).await.unwrap();
+        store.record_arbitrage_result(&make_result("ETH", dec!(-2.25), ArbitrageStatus::Completed)).await.unwrap();
+        store.record_arbitrage_result(&make_result("BTC", dec!(0), ArbitrageStatus::Failed)).await.unwrap();
+
+        let stats = store.get_overall_stats(None, None).await.unwrap();
+        assert_eq!(stats.total_trades, 3);
+        assert_eq!(stats.successful_trades, 2);
+        assert_eq!(stats.failed_trades, 1);
+        assert_eq!(stats.total_profit, dec!(8.25));
+        assert_eq!(stats.total_volume, dec!(0.3));
+        assert_eq!(stats.avg_profit_per_trade, dec!(2.75));
+        assert_eq!(stats.max_profit, dec!(10.5));
+        assert_eq!(stats.max_loss, dec!(-2.25));
+        assert_eq!(stats.avg_trade_duration_ms, 1500);
+        assert_eq!(stats.winning_trades, 1);
+        assert_eq!(stats.losing_trades, 1);
+        assert_eq!(stats.avg_win, dec!(10.5));
+        assert_eq!(stats.avg_loss, dec!(-2.25));
+    }
+
+    #[tokio::test]
+    async fn test_overall_stats_empty() {
+        let store = get_test_store().await;
+
+        let stats = store.get_overall_stats(None, None).await.unwrap();
+        assert_eq!(stats.total_trades, 0);
+        assert_eq!(stats.total_profit, Decimal::ZERO);
+    }
+
+    #[tokio::test]
+    async fn test_daily_stats() {
+        let store = get_test_store().await;
+
+        store.record_arbitrage_result(&make_result("BTC", dec!(10), ArbitrageStatus::Completed)).await.unwrap();
+        store.record_arbitrage_result(&make_result("BTC", dec!(0), ArbitrageStatus::Failed)).await.unwrap();
+
+        let daily = store.get_daily_stats(7).await.unwrap();
+        assert_eq!(daily.len(), 1);
+        assert_eq!(daily[0].date, Utc::now().format("%Y-%m-%d").to_string());
+        assert_eq!(daily[0].trades, 2);
+        assert_eq!(daily[0].profit, dec!(10));
+        assert_eq!(daily[0].volume, dec!(0.2));
+        assert!((daily[0].successful_rate - 50.0).abs() < f64::EPSILON);
+    }
+
+    #[tokio::test]
+    async fn test_asset_stats() {
+        let store = get_test_store().await;
+
+        store.record_arbitrage_result(&make_result("ETH", dec!(3), ArbitrageStatus::Completed)).await.unwrap();
+        store.record_arbitrage_result(&make_result("BTC", dec!(10), ArbitrageStatus::Completed)).await.unwrap();
+        store.record_arbitrage_result(&make_result("BTC", dec!(20), ArbitrageStatus::Completed)).await.unwrap();
+
+        let assets = store.get_asset_stats(&AssetStatsQuery::between(10, None, None)).await.unwrap();
+        assert_eq!(assets.len(), 2);
+        assert_eq!(assets[0].asset, "BTC");
+        assert_eq!(assets[0].trades, 2);
+        assert_eq!(assets[0].profit, dec!(30));
+        assert_eq!(assets[0].avg_profit, dec!(15));
+        assert_eq!(assets[1].asset, "ETH");
+
+        let top = store.get_asset_stats(&AssetStatsQuery::between(1, None, None)).await.unwrap();
+        assert_eq!(top.len(), 1);
+        assert_eq!(top[0].asset, "BTC");
+    }
+
+    #[tokio::test]
+    async fn test_asset_stats_filter_and_sort() {
+        use chrono::TimeZone;
+        use crate::db::AssetSortBy;
+
+        let store = get_test_store().await;
+        let day1 = Utc.with_ymd_and_hms(2024, 3, 8, 12, 0, 0).unwrap();
+        let day2 = day1 + Duration::days(1);
+
+        // BTC：2笔、成功1笔、利润7、交易量0.2；ETH：4笔、成功1笔、利润-1、交易量7
+        let seeds = [
+            (day1, "BTC", dec!(10), dec!(0.1), ArbitrageStatus::Completed),
+            (day1, "ETH", dec!(1), dec!(2), ArbitrageStatus::Completed),
+            (day1 + Duration::hours(1), "ETH", dec!(-1), dec!(2), ArbitrageStatus::Failed),
+            (day2, "BTC", dec!(-3), dec!(0.1), ArbitrageStatus::Failed),
+            (day2, "ETH", dec!(-1), dec!(2), ArbitrageStatus::Failed),
+            (day2 + Duration::hours(1), "ETH", dec!(0), dec!(1), ArbitrageStatus::Failed),
+        ];
+        for (start_time, asset, profit, amount, status) in seeds {
+            let mut result = make_result(asset, profit, status);
+            result.trade_amount = amount;
+            result.timestamp = start_time;
+            result.start_time = start_time;
+            result.end_time = start_time + Duration::milliseconds(1500);
+            store.record_arbitrage_result(&result).await.unwrap();
+        }
+
+        let order = |stats: Vec<AssetStats>| stats.into_iter().map(|a| a.asset).collect::<Vec<_>>();
+        let sorted = |sort_by| AssetStatsQuery { sort_by, ..AssetStatsQuery::default() };
+
+        assert_eq!(order(store.get_asset_stats(&sorted(AssetSortBy::Profit)).await.unwrap()), vec!["BTC", "ETH"]);
+        assert_eq!(order(store.get_asset_stats(&sorted(AssetSortBy::Volume)).await.unwrap()), vec!["ETH", "BTC"]);
+        assert_eq!(order(store.get_asset_stats(&sorted(AssetSortBy::Trades)).await.unwrap()), vec!["ETH", "BTC"]);
+        assert_eq!(order(store.get_asset_stats(&sorted(AssetSortBy::SuccessRate)).await.unwrap()), vec!["BTC", "ETH"]);
+
+        let all = store.get_asset_stats(&sorted(AssetSortBy::Trades)).await.unwrap();
+        assert_eq!(all[0].trades, 4);
+        assert_eq!(all[0].successful_trades, 1);
+        assert!((all[0].successful_rate - 25.0).abs() < f64::EPSILON);
+        assert_eq!(all[0].volume, dec!(7));
+        assert!((all[1].successful_rate - 50.0).abs() < f64::EPSILON);
+
+        // 仅第二天：两个币种都没有成功交易，ETH 亏损更少
+        let day2_start = Utc.with_ymd_and_hms(2024, 3, 9, 0, 0, 0).unwrap();
+        let day2_only = store.get_asset_stats(&AssetStatsQuery::between(10, Some(day2_start), None)).await.unwrap();
+        assert_eq!(order(day2_only.clone()), vec!["ETH", "BTC"]);
+        assert_eq!(day2_only[0].profit, dec!(-1));
+        assert_eq!(day2_only[0].successful_rate, 0.0);
+
+        // 指定币种与时间范围
+        let eth_day1 = store
+            .get_asset_stats(&AssetStatsQuery {
+                end: Some(day2_start),
+                asset: Some("ETH".to_string()),
+                ..AssetStatsQuery::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(eth_day1.len(), 1);
+        assert_eq!(eth_day1[0].trades, 2);
+        assert_eq!(eth_day1[0].profit, dec!(0));
+        assert!((eth_day1[0].successful_rate - 50.0).abs() < f64::EPSILON);
+
+        let unknown = AssetStatsQuery { asset: Some("SOL".to_string()), ..AssetStatsQuery::default() };
+        assert!(store.get_asset_stats(&unknown).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_stats_by_time_range() {
+        use chrono::TimeZone;
+
+        let store = get_test_store().await;
+        let day1 = Utc.with_ymd_and_hms(2024, 3, 8, 12, 0, 0).unwrap();
+
+        // 三天的交易：第一天 BTC 10，第二天 BTC 5 + ETH -2（失败），第三天 ETH 8
+        let seeds = [
+            (day1, "BTC", dec!(10), ArbitrageStatus::Completed),
+            (day1 + Duration::days(1), "BTC", dec!(5), ArbitrageStatus::Completed),
+            (day1 + Duration::days(1) + Duration::hours(1), "ETH", dec!(-2), ArbitrageStatus::Failed),
+            (day1 + Duration::days(2), "ETH", dec!(8), ArbitrageStatus::Completed),
+        ];
+        for (start_time, asset, profit, status) in seeds {
+            let mut result = make_result(asset, profit, status);
+            result.timestamp = start_time;
+            result.start_time = start_time;
+            result.end_time = start_time + Duration::milliseconds(1500);
+            store.record_arbitrage_result(&result).await.unwrap();
+        }
+
+        let day2_start = Utc.with_ymd_and_hms(2024, 3, 9, 0, 0, 0).unwrap();
+        let day3_start = Utc.with_ymd_and_hms(2024, 3, 10, 0, 0, 0).unwrap();
+
+        // 全部时间
+        let all = store.get_overall_stats(None, None).await.unwrap();
+        assert_eq!(all.total_trades, 4);
+        assert_eq!(all.total_profit, dec!(21));
+
+        // 仅第二天，结束时间不包含在范围内
+        let day2 = store.get_overall_stats(Some(day2_start), Some(day3_start)).await.unwrap();
+        assert_eq!(day2.total_trades, 2);
+        assert_eq!(day2.successful_trades, 1);
+        assert_eq!(day2.failed_trades, 1);
+        assert_eq!(day2.total_profit, dec!(3));
+        assert_eq!(day2.max_loss, dec!(-2));
+
+        // 第二天起
+        let since_day2 = store.get_overall_stats(Some(day2_start), None).await.unwrap();
+        assert_eq!(since_day2.total_trades, 3);
+        assert_eq!(since_day2.total_profit, dec!(11));
+
+        // 第三天之前
+        let until_day3 = store.get_overall_stats(None, Some(day3_start)).await.unwrap();
+        assert_eq!(until_day3.total_trades, 3);
+        assert_eq!(until_day3.total_profit, dec!(13));
+
+        let daily = store.get_daily_stats_between(None, None).await.unwrap();
+        let dates: Vec<&str> = daily.iter().map(|d| d.date.as_str()).collect();
+        assert_eq!(dates, vec!["2024-03-08", "2024-03-09", "2024-03-10"]);
+        assert_eq!(daily[1].trades, 2);
+        assert_eq!(daily[1].profit, dec!(3));
+        assert!((daily[1].successful_rate - 50.0).abs() < f64::EPSILON);
+
+        let daily = store.get_daily_stats_between(Some(day2_start), Some(day3_start)).await.unwrap();
+        assert_eq!(daily.len(), 1);
+        assert_eq!(daily[0].date, "2024-03-09");
+
+        let assets = store.get_asset_stats(&AssetStatsQuery::between(10, None, None)).await.unwrap();
+        assert_eq!(assets[0].asset, "BTC");
+        assert_eq!(assets[0].profit, dec!(15));
+        assert_eq!(assets[1].profit, dec!(6));
+
+        // 第二天起 ETH 利润更高
+        let assets = store.get_asset_stats(&AssetStatsQuery::between(10, Some(day2_start), None)).await.unwrap();
+        assert_eq!(assets[0].asset, "ETH");
+        assert_eq!(assets[0].trades, 2);
+        assert_eq!(assets[0].profit, dec!(6));
+        assert_eq!(assets[1].asset, "BTC");
+        assert_eq!(assets[1].trades, 1);
+    }
+
+    #[tokio::test]
+    async fn test_hourly_stats_buckets() {
+        use chrono::TimeZone;
+
+        let offset = FixedOffset::east_opt(8 * 3600).unwrap();
+        let store = get_test_store().await.with_stats_offset(offset);
+
+        // 以昨天UTC零点为基准：01:30、01:45 对应东八区 09 点，17:10 对应东八区次日 01 点
+        let base = Utc.from_utc_datetime(&(Utc::now().date_naive() - Duration::days(1)).and_hms_opt(0, 0, 0).unwrap());
+        let seeds = [
+            (base + Duration::minutes(90), dec!(4), ArbitrageStatus::Completed),
+            (base + Duration::minutes(105), dec!(-1), ArbitrageStatus::Failed),
+            (base + Duration::minutes(17 * 60 + 10), dec!(2.5), ArbitrageStatus::Completed),
+        ];
+        for (start_time, profit, status) in seeds {
+            let mut result = make_result("BTC", profit, status);
+            result.timestamp = start_time;
+            result.start_time = start_time;
+            result.end_time = start_time + Duration::milliseconds(1500);
+            store.record_arbitrage_result(&result).await.unwrap();
+        }
+
+        let stored = store.get_hourly_stats(7).await.unwrap();
+        let computed = store.get_hourly_stats_between(None, None).await.unwrap();
+
+        for hourly in [&stored, &computed] {
+            assert_eq!(hourly.len(), 24);
+            assert!(hourly.iter().enumerate().all(|(i, h)| h.hour == i as u32));
+
+            assert_eq!(hourly[9].trades, 2);
+            assert_eq!(hourly[9].profit, dec!(3));
+            assert_eq!(hourly[9].volume, dec!(0.2));
+            assert!((hourly[9].successful_rate - 50.0).abs() < f64::EPSILON);
+
+            assert_eq!(hourly[1].trades, 1);
+            assert_eq!(hourly[1].profit, dec!(2.5));
+
+            assert_eq!(hourly.iter().map(|h| h.trades).sum::<i64>(), 3);
+            assert_eq!(hourly[17].trades, 0);
+            assert_eq!(hourly[17].successful_rate, 0.0);
+        }
+
+        // 每日统计使用同一时区分桶，17:10 的交易计入东八区的次日
+        let local_day = (base + Duration::hours(8)).format("%Y-%m-%d").to_string();
+        let next_day = (base + Duration::hours(32)).format("%Y-%m-%d").to_string();
+        let daily = store.get_daily_stats_between(None, None).await.unwrap();
+        let dates: Vec<(&str, i64)> = daily.iter().map(|d| (d.date.as_str(), d.trades)).collect();
+        assert_eq!(dates, vec![(local_day.as_str(), 2), (next_day.as_str(), 1)]);
+        assert_eq!(store.get_daily_stats(7).await.unwrap().len(), 2);
+
+        // 按时间范围筛选只统计范围内的交易
+        let hourly = store.get_hourly_stats_between(Some(base + Duration::hours(2)), None).await.unwrap();
+        assert_eq!(hourly[9].trades, 0);
+        assert_eq!(hourly[1].trades, 1);
+    }
+
+    #[tokio::test]
+    async fn test_hourly_stats_follow_aggregated_deltas() {
+        let offset = FixedOffset::west_opt(5 * 3600).unwrap();
+        let per_trade = get_test_store().await.with_stats_offset(offset);
+        let aggregated = get_test_store().await.with_stats_offset(offset);
+
+        let mut deltas = StatsDeltas::default();
+        for (i, profit) in [dec!(1), dec!(2), dec!(-0.5)].into_iter().enumerate() {
+            let mut result = make_result("ETH", profit, ArbitrageStatus::Completed);
+            result.start_time -= Duration::hours(i as i64);
+            result.timestamp = result.start_time;
+
+            per_trade.record_arbitrage_result(&result).await.unwrap();
+            aggregated.record_arbitrage_history(&result).await.unwrap();
+            deltas.add_result(&result, aggregated.stats_offset());
+        }
+        aggregated.apply_stats_deltas(&deltas).await.unwrap();
+
+        let expected = per_trade.get_hourly_stats(7).await.unwrap();
+        let actual = aggregated.get_hourly_stats(7).await.unwrap();
+        for (e, a) in expected.iter().zip(actual.iter()) {
+            assert_eq!((e.hour, e.trades, e.profit), (a.hour, a.trades, a.profit));
+        }
+        assert_eq!(actual.iter().map(|h| h.trades).sum::<i64>(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_trade_history_filters() {
+        let store = get_test_store().await;
+
+        let usdc_to_usdt = |mut result: ArbitrageResult| {
+            result.buy_quote = "USDC".to_string();
+            result.sell_quote = "USDT".to_string();
+            result
+        };
+
+        store.record_arbitrage_result(&make_result("BTC", dec!(10), ArbitrageStatus::Completed)).await.unwrap();
+        store.record_arbitrage_result(&usdc_to_usdt(make_result("ETH", dec!(3), ArbitrageStatus::Completed))).await.unwrap();
+        store.record_arbitrage_result(&make_result("BTC", dec!(0), ArbitrageStatus::Failed)).await.unwrap();
+        store.record_arbitrage_result(&usdc_to_usdt(make_result("BTC", dec!(-2.5), ArbitrageStatus::Completed))).await.unwrap();
+
+        let query = |filter: TradeHistoryFilter| {
+            let store = &store;
+            async move { store.get_trade_history(&filter, 10, 0).await.unwrap() }
+        };
+
+        assert_eq!(query(TradeHistoryFilter::default()).await.len(), 4);
+
+        let btc = query(TradeHistoryFilter { asset: Some("BTC".to_string()), ..Default::default() }).await;
+        assert_eq!(btc.len(), 3);
+        assert!(btc.iter().all(|r| r.result.base_asset == "BTC"));
+
+        let failed = query(TradeHistoryFilter { status: Some(ArbitrageStatus::Failed), ..Default::default() }).await;
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].result.status, ArbitrageStatus::Failed);
+
+        let future = query(TradeHistoryFilter::between(Some(Utc::now() + Duration::days(1)), None)).await;
+        assert!(future.is_empty());
+        let past = query(TradeHistoryFilter::between(None, Some(Utc::now() - Duration::days(1)))).await;
+        assert!(past.is_empty());
+
+        // 利润范围
+        let profitable = query(TradeHistoryFilter { min_profit: Some(dec!(0.01)), ..Default::default() }).await;
+        assert_eq!(profitable.len(), 2);
+        let losses = query(TradeHistoryFilter { max_profit: Some(dec!(-0.01)), ..Default::default() }).await;
+        assert_eq!(losses.len(), 1);
+        assert_eq!(losses[0].result.profit, dec!(-2.5));
+        let small = query(TradeHistoryFilter { min_profit: Some(dec!(0)), max_profit: Some(dec!(3)), ..Default::default() }).await;
+        assert_eq!(small.len(), 2);
+
+        // 报价货币方向
+        let buy_usdc = query(TradeHistoryFilter { buy_quote: Some("USDC".to_string()), ..Default::default() }).await;
+        assert_eq!(buy_usdc.len(), 2);
+        assert!(buy_usdc.iter().all(|r| r.result.buy_quote == "USDC"));
+        let sell_usdc = query(TradeHistoryFilter { sell_quote: Some("USDC".to_string()), ..Default::default() }).await;
+        assert_eq!(sell_usdc.len(), 2);
+        let none = query(TradeHistoryFilter {
+            buy_quote: Some("USDC".to_string()),
+            sell_quote: Some("USDC".to_string()),
+            ..Default::default()
+        })
+        .await;
+        assert!(none.is_empty());
+
+        // 组合条件
+        let combined = query(TradeHistoryFilter {
+            asset: Some("BTC".to_string()),
+            status: Some(ArbitrageStatus::Completed),
+            buy_quote: Some("USDT".to_string()),
+            min_profit: Some(dec!(5)),
+            ..Default::default()
+        })
+        .await;
+        assert_eq!(combined.len(), 1);
+        assert_eq!(combined[0].result.profit, dec!(10));
+
+        let combined = query(TradeHistoryFilter {
+            asset: Some("BTC".to_string()),
+            sell_quote: Some("USDT".to_string()),
+            max_profit: Some(dec!(0)),
+            start_date: Some(Utc::now() - Duration::days(1)),
+            end_date: Some(Utc::now() + Duration::days(1)),
+            ..Default::default()
+        })
+        .await;
+        assert_eq!(combined.len(), 1);
+        assert_eq!(combined[0].result.profit, dec!(-2.5));
+
+        // 分页，按开始时间倒序
+        let page = store.get_trade_history(&TradeHistoryFilter::default(), 2, 2).await.unwrap();
+        assert_eq!(page.len(), 2);
+        assert_eq!(page[1].result.profit, dec!(10));
+        let page = store.get_trade_history(&TradeHistoryFilter::default(), 2, 4).await.unwrap();
+        assert!(page.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_trade_mode_and_config_hash() {
+        let store = get_test_store().await;
+
+        let live = ArbitrageResult { config_hash: "0123456789abcdef".to_string(), ..make_result("BTC", dec!(10), ArbitrageStatus::Completed) };
+        let simulated = ArbitrageResult { mode: TradingMode::Simulate, ..make_result("BTC", dec!(3), ArbitrageStatus::Completed) };
+        let backtest = ArbitrageResult { mode: TradingMode::Backtest, ..make_result("ETH", dec!(-1), ArbitrageStatus::Failed) };
+        for result in [&live, &simulated, &backtest] {
+            store.record_arbitrage_result(result).await.unwrap();
+        }
+
+        let all = store.get_trade_history(&TradeHistoryFilter::default(), 10, 0).await.unwrap();
+        assert_eq!(all.len(), 3);
+
+        let filter = TradeHistoryFilter { mode: Some(TradingMode::Live), ..Default::default() };
+        let records = store.get_trade_history(&filter, 10, 0).await.unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].result.mode, TradingMode::Live);
+        assert_eq!(records[0].result.config_hash, "0123456789abcdef");
+
+        let filter = TradeHistoryFilter { mode: Some(TradingMode::Simulate), ..Default::default() };
+        let records = store.get_trade_history(&filter, 10, 0).await.unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].result.profit, dec!(3));
+        assert!(records[0].result.config_hash.is_empty());
+
+        let filter = TradeHistoryFilter { mode: Some(TradingMode::DryRun), ..Default::default() };
+        assert!(store.get_trade_history(&filter, 10, 0).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_trade_history_keyset_pagination() {
+        use std::collections::HashSet;
+
+        let store = get_test_store().await;
+        let base = Utc::now() - Duration::hours(1);
+        let result_at = |start_time: DateTime<Utc>, order_id: u64| ArbitrageResult {
+            timestamp: start_time,
+            start_time,
+            end_time: start_time,
+            buy_order_id: Some(order_id),
+            ..make_result("BTC", dec!(1), ArbitrageStatus::Completed)
+        };
+
+        // 每3条记录共用一个开始时间，分页边界会落在开始时间相同的记录之间
+        let mut seeded = HashSet::new();
+        for i in 0..25 {
+            let result = result_at(base + Duration::seconds(i / 3), i as u64);
+            seeded.insert(store.record_arbitrage_result(&result).await.unwrap());
+        }
+
+        let filter = TradeHistoryFilter::default();
+        let mut seen = Vec::new();
+        let mut cursor = None;
+        let mut pages = 0;
+        loop {
+            let page = store.get_trade_history_page(&filter, 10, cursor.as_ref()).await.unwrap();
+            pages += 1;
+            seen.extend(page.items.iter().map(|record| (record.result.start_time, record.id)));
+
+            // 翻页期间写入新的交易
+            store.record_arbitrage_result(&result_at(Utc::now(), 1000 + pages)).await.unwrap();
+
+            match page.next_cursor {
+                Some(next) => cursor = Some(next),
+                None => break,
+            }
+        }
+
+        // 每条种子记录恰好返回一次，且按 (start_time, id) 倒序
+        assert_eq!(pages, 3);
+        assert_eq!(seen.len(), 25);
+        assert_eq!(seen.iter().map(|(_, id)| *id).collect::<HashSet<_>>(), seeded);
+        assert!(seen.windows(2).all(|pair| pair[0] > pair[1]));
+
+        // 记录数恰好是页大小的整数倍时，最后一页没有下一页游标
+        let filter = TradeHistoryFilter::between(None, Some(base + Duration::minutes(1)));
+        let mut cursor = None;
+        let mut pages = 0;
+        loop {
+            let page = store.get_trade_history_page(&filter, 5, cursor.as_ref()).await.unwrap();
+            assert_eq!(page.items.len(), 5);
+            pages += 1;
+            match page.next_cursor {
+                Some(next) => cursor = Some(next),
+                None => break,
+            }
+        }
+        assert_eq!(pages, 5);
+
+        // OFFSET分页在翻页期间写入新交易时会重复返回记录
+        let first = store.get_trade_history(&TradeHistoryFilter::default(), 10, 0).await.unwrap();
+        store.record_arbitrage_result(&result_at(Utc::now(), 2000)).await.unwrap();
+        let second = store.get_trade_history(&TradeHistoryFilter::default(), 10, 10).await.unwrap();
+        let first_ids: HashSet<_> = first.iter().map(|record| record.id).collect();
+        assert!(second.iter().any(|record| first_ids.contains(&record.id)));
+    }
+
+    #[tokio::test]
+    async fn test_trade_history_round_trip() {
+        let store = get_test_store().await;
+
+        let mut result = make_result("ETH", dec!(1.23456789), ArbitrageStatus::SellOrderFilled);
+        result.buy_price = dec!(3000.12);
+        result.sell_price = dec!(3003.5);
+        result.trade_amount = dec!(0.0333);
+        result.profit_percentage = dec!(0.1126);
+        result.buy_order_id = Some(42);
+        result.sell_order_id = None;
+        result.buy_fill = FillInfo {
+            qty: dec!(0.0333),
+            avg_price: dec!(3000.11),
+            commission: dec!(0.0000333),
+            commission_asset: "ETH".to_string(),
+        };
+        result.sell_fill = FillInfo {
+            qty: dec!(0.0332667),
+            avg_price: dec!(3003.52),
+            commission: dec!(0.09991764),
+            commission_asset: "USDC".to_string(),
+        };
+
+        let id = store.record_arbitrage_result(&result).await.unwrap();
+
+        let history = store.get_trade_history(&TradeHistoryFilter::default(), 10, 0).await.unwrap();
+        assert_eq!(history.len(), 1);
+
+        let record = &history[0];
+        assert_eq!(record.id, id);
+        assert_eq!(record.duration_ms, 1500);
+
+        let loaded = &record.result;
+        assert_eq!(loaded.base_asset, result.base_asset);
+        assert_eq!(loaded.buy_quote, result.buy_quote);
+        assert_eq!(loaded.sell_quote, result.sell_quote);
+        assert_eq!(loaded.buy_price, result.buy_price);
+        assert_eq!(loaded.sell_price, result.sell_price);
+        assert_eq!(loaded.trade_amount, result.trade_amount);
+        assert_eq!(loaded.profit, result.profit);
+        assert_eq!(loaded.profit_percentage, result.profit_percentage);
+        assert_eq!(loaded.buy_order_id, result.buy_order_id);
+        assert_eq!(loaded.sell_order_id, result.sell_order_id);
+        assert_eq!(loaded.status, result.status);
+        assert_eq!(loaded.start_time, result.start_time);
+        assert_eq!(loaded.end_time, result.end_time);
+        assert_eq!(loaded.timestamp, result.start_time);
+        assert_eq!(loaded.buy_fill, result.buy_fill);
+        assert_eq!(loaded.sell_fill, result.sell_fill);
+    }
+
+    #[tokio::test]
+    async fn test_trade_history_unknown_status() {
+        let store = get_test_store().await;
+        store.record_arbitrage_result(&make_result("BTC", dec!(1), ArbitrageStatus::Completed)).await.unwrap();
+
+        sqlx::query("UPDATE arbitrage_history SET status = 'Pending'")
+            .execute(&store.pool)
+            .await
+            .unwrap();
+
+        // 未知状态返回错误，而不是被当作失败记录
+        assert!(store.get_trade_history(&TradeHistoryFilter::default(), 10, 0).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_stats_sums_are_exact() {
+        let store = get_test_store().await;
+        for profit in [dec!(0.1), dec!(0.2), dec!(0.0000000001)] {
+            store.record_arbitrage_result(&make_result("BTC", profit, ArbitrageStatus::Completed)).await.unwrap();
+        }
+
+        // 浮点数汇总会得到 0.30000000000000004 之类的结果
+        let expected = dec!(0.3000000001);
+        assert_eq!(store.get_overall_stats(None, None).await.unwrap().total_profit, expected);
+        assert_eq!(store.get_daily_stats_between(None, None).await.unwrap()[0].profit, expected);
+        assert_eq!(store.get_asset_stats(&AssetStatsQuery::between(10, None, None)).await.unwrap()[0].profit, expected);
+        assert_eq!(store.get_daily_stats(1).await.unwrap()[0].profit, expected);
+    }
+
+    #[tokio::test]
+    async fn test_corrupt_amount_is_error() {
+        let store = get_test_store().await;
+        store.record_arbitrage_result(&make_result("BTC", dec!(1), ArbitrageStatus::Completed)).await.unwrap();
+
+        sqlx::query("UPDATE arbitrage_history SET profit = 'not-a-number'")
+            .execute(&store.pool)
+            .await
+            .unwrap();
+
+        // 损坏的金额返回错误，而不是被当作0参与统计
+        assert!(store.get_overall_stats(None, None).await.is_err());
+        assert!(store.get_asset_stats(&AssetStatsQuery::between(10, None, None)).await.is_err());
+        assert!(store.get_trade_history(&TradeHistoryFilter::default(), 10, 0).await.is_err());
+    }
+
+    /// 读取汇总统计表的全部行
+    async fn summary_rows(store: &SqliteTradeStore, table: &str, key_column: &str) -> Vec<(String, i64, i64, i64, Decimal, Decimal)> {
+        let rows = sqlx::query(&format!(
+            "SELECT {key} AS key, trades, successful_trades, failed_trades, total_profit, total_volume FROM {table} ORDER BY {key}",
+            key = key_column,
+            table = table
+        ))
+        .fetch_all(&store.pool)
+        .await
+        .unwrap();
+
+        rows.iter()
+            .map(|row| (
+                row.get("key"),
+                row.get("trades"),
+                row.get("successful_trades"),
+                row.get("failed_trades"),
+                decimal_column(row, "total_profit").unwrap(),
+                decimal_column(row, "total_volume").unwrap(),
+            ))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_aggregated_stats_match_per_trade_updates() {
+        let results = vec![
+            make_result("BTC", dec!(10), ArbitrageStatus::Completed),
+            make_result("ETH", dec!(-2.5), ArbitrageStatus::Failed),
+            make_result("BTC", dec!(0.0000000001), ArbitrageStatus::Completed),
+            make_result("ETH", dec!(3), ArbitrageStatus::Completed),
+        ];
+
+        let per_trade = get_test_store().await;
+        for result in &results {
+            per_trade.record_arbitrage_result(result).await.unwrap();
+        }
+
+        let aggregated = std::sync::Arc::new(get_test_store().await);
+        let config = WriteBufferConfig { stats_flush_interval_ms: 60_000, ..WriteBufferConfig::default() };
+        let writer = BufferedWriter::spawn(aggregated.clone(), config);
+        for result in &results {
+            writer.enqueue(result).unwrap();
+        }
+        // 重复的结果按幂等键跳过，不重复累计统计
+        writer.enqueue(&results[0]).unwrap();
+        writer.flush().await.unwrap();
+
+        assert_eq!(aggregated.get_overall_stats(None, None).await.unwrap().total_trades, 4);
+        for (table, key_column) in [("daily_stats", "date"), ("asset_stats", "asset")] {
+            assert_eq!(
+                summary_rows(&aggregated, table, key_column).await,
+                summary_rows(&per_trade, table, key_column).await,
+                "{} 不一致",
+                table
+            );
+        }
+    }
+
+    fn make_order(order_id: u64, side: Side, executed_qty: Decimal, status: OrderStatus) -> OrderRecord {
+        OrderRecord::resolved(OrderInfo {
+            order_id,
+            symbol: "BTCUSDT".to_string(),
+            price: dec!(50000.12),
+            qty: dec!(0.1),
+            executed_qty,
+            avg_price: if executed_qty.is_zero() { Decimal::ZERO } else { dec!(50000.0712345678) },
+            commission: dec!(0.0000001),
+            commission_asset: "BTC".to_string(),
+            side,
+            status,
+            timestamp: Utc::now(),
+        })
+    }
+
+    #[tokio::test]
+    async fn test_orders_round_trip() {
+        let store = get_test_store().await;
+        let id = store.record_arbitrage_result(&make_result("BTC", dec!(10), ArbitrageStatus::Completed)).await.unwrap();
+        let other = store.record_arbitrage_result(&make_result("ETH", dec!(1), ArbitrageStatus::Failed)).await.unwrap();
+
+        let buy = make_order(11, Side::Buy, dec!(0.1), OrderStatus::Filled);
+        let sell = make_order(12, Side::Sell, dec!(0.04), OrderStatus::Expired);
+        let buy_id = store.record_order(id, &buy).await.unwrap();
+        store.record_order(id, &sell).await.unwrap();
+        store.record_order(other, &make_order(21, Side::Buy, Decimal::ZERO, OrderStatus::Cancelled)).await.unwrap();
+
+        // 重复写入同一订单返回已有记录ID
+        assert_eq!(store.record_order(id, &buy).await.unwrap(), buy_id);
+
+        // 默认不返回订单记录
+        let records = store.get_trade_history(&TradeHistoryFilter::default(), 10, 0).await.unwrap();
+        assert!(records.iter().all(|record| record.orders.is_empty()));
+
+        let filter = TradeHistoryFilter { include_orders: true, ..Default::default() };
+        let records = store.get_trade_history(&filter, 10, 0).await.unwrap();
+        let record = records.iter().find(|record| record.id == id).unwrap();
+        assert_eq!(record.orders.len(), 2);
+
+        let stored = &record.orders[0];
+        assert_eq!(stored.id, buy_id);
+        assert_eq!(stored.arbitrage_id, id);
+        assert_eq!(stored.order.order_id, 11);
+        assert_eq!(stored.order.side, Side::Buy);
+        assert_eq!(stored.order.status, OrderStatus::Filled);
+        assert_eq!(stored.order.price, dec!(50000.12));
+        assert_eq!(stored.order.qty, dec!(0.1));
+        assert_eq!(stored.order.executed_qty, dec!(0.1));
+        assert_eq!(stored.order.avg_price, dec!(50000.0712345678));
+        assert_eq!(stored.order.commission, dec!(0.0000001));
+        assert_eq!(stored.order.timestamp, buy.order.timestamp);
+        assert_eq!(stored.filled_at, buy.filled_at);
+
+        let stored = &record.orders[1];
+        assert_eq!(stored.order.side, Side::Sell);
+        assert_eq!(stored.order.status, OrderStatus::Expired);
+        assert_eq!(stored.order.executed_qty, dec!(0.04));
+
+        // 没有成交的订单没有成交时间
+        let record = records.iter().find(|record| record.id == other).unwrap();
+        assert_eq!(record.orders.len(), 1);
+        assert!(record.orders[0].filled_at.is_none());
+
+        // 游标分页同样返回订单记录
+        let page = store.get_trade_history_page(&filter, 10, None).await.unwrap();
+        assert_eq!(page.items.iter().map(|record| record.orders.len()).sum::<usize>(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_order_requires_existing_arbitrage() {
+        let store = get_test_store().await;
+        let order = make_order(11, Side::Buy, dec!(0.1), OrderStatus::Filled);
+        assert!(store.record_order(999, &order).await.is_err());
+    }
+}
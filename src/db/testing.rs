@@ -0,0 +1,196 @@
+//! 测试用的内存交易存储，供写入队列和引擎的测试共用
+
+use super::{
+    AssetDailyStats, AssetStats, AssetStatsQuery, DailyStats, FeeSlippageStats, HistoryCursor, HistoryPage, HourlyStats,
+    OrderRecord, RejectedOpportunity, StatsDeltas, StrategyStats, TradeHistoryFilter, TradeRecord, TradeStats, TradeStore,
+};
+use crate::models::ArbitrageResult;
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// 内存存储，接下来 `failures` 次写入返回错误，`down` 为true时模拟数据库不可用
+///
+/// 只保存写入的记录，查询统计和交易历史的方法返回错误。
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+    failures: Mutex<u32>,
+    down: AtomicBool,
+    records: Mutex<Vec<ArbitrageResult>>,
+    /// 已写入汇总表的统计
+    stats: Mutex<StatsDeltas>,
+    /// 汇总统计的写入次数
+    stats_writes: Mutex<u32>,
+    /// 已写入的订单记录 (套利记录ID, 订单ID)
+    orders: Mutex<Vec<(i64, u64)>>,
+    rejections: Mutex<Vec<RejectedOpportunity>>,
+}
+
+impl MemoryStore {
+    /// 写入总是成功的存储
+    pub fn new() -> Arc<Self> {
+        Self::with_failures(0)
+    }
+
+    /// 前 `failures` 次写入返回错误的存储
+    pub fn with_failures(failures: u32) -> Arc<Self> {
+        Arc::new(Self { failures: Mutex::new(failures), ..Self::default() })
+    }
+
+    /// 已写入的套利结果
+    pub fn records(&self) -> Vec<ArbitrageResult> {
+        self.records.lock().unwrap().clone()
+    }
+
+    /// 已写入的套利结果数量
+    pub fn count(&self) -> usize {
+        self.records.lock().unwrap().len()
+    }
+
+    pub fn stats(&self) -> StatsDeltas {
+        self.stats.lock().unwrap().clone()
+    }
+
+    pub fn stats_writes(&self) -> u32 {
+        *self.stats_writes.lock().unwrap()
+    }
+
+    pub fn orders(&self) -> Vec<(i64, u64)> {
+        self.orders.lock().unwrap().clone()
+    }
+
+    /// 模拟数据库不可用或恢复，不可用时连接检查和所有写入都失败
+    pub fn set_down(&self, down: bool) {
+        self.down.store(down, Ordering::SeqCst);
+    }
+
+    fn check_available(&self) -> Result<()> {
+        if self.down.load(Ordering::SeqCst) {
+            return Err(anyhow!("模拟数据库连接失败"));
+        }
+        Ok(())
+    }
+
+    fn insert(&self, result: &ArbitrageResult) -> Result<i64> {
+        self.check_available()?;
+        let mut failures = self.failures.lock().unwrap();
+        if *failures > 0 {
+            *failures -= 1;
+            return Err(anyhow!("模拟写入失败"));
+        }
+        let mut records = self.records.lock().unwrap();
+        records.push(result.clone());
+        Ok(records.len() as i64)
+    }
+}
+
+fn unsupported<T>(method: &str) -> Result<T> {
+    Err(anyhow!("内存测试存储不支持 {}", method))
+}
+
+#[async_trait]
+impl TradeStore for MemoryStore {
+    fn backend_name(&self) -> &str {
+        "memory"
+    }
+
+    async fn ping(&self) -> Result<()> {
+        self.check_available()
+    }
+
+    async fn record_arbitrage_result(&self, result: &ArbitrageResult) -> Result<i64> {
+        let id = self.insert(result)?;
+        self.stats.lock().unwrap().add_result(result, self.stats_offset());
+        *self.stats_writes.lock().unwrap() += 1;
+        Ok(id)
+    }
+
+    async fn record_arbitrage_history(&self, result: &ArbitrageResult) -> Result<(i64, bool)> {
+        Ok((self.insert(result)?, true))
+    }
+
+    async fn apply_stats_deltas(&self, deltas: &StatsDeltas) -> Result<()> {
+        self.check_available()?;
+        let mut stats = self.stats.lock().unwrap();
+        for (date, delta) in &deltas.daily {
+            stats.daily.entry(date.clone()).or_default().add(delta);
+        }
+        for (key, delta) in &deltas.hourly {
+            stats.hourly.entry(key.clone()).or_default().add(delta);
+        }
+        for (asset, delta) in &deltas.assets {
+            stats.assets.entry(asset.clone()).or_default().add(delta);
+        }
+        *self.stats_writes.lock().unwrap() += 1;
+        Ok(())
+    }
+
+    async fn record_order(&self, arbitrage_id: i64, order: &OrderRecord) -> Result<i64> {
+        self.check_available()?;
+        let mut orders = self.orders.lock().unwrap();
+        orders.push((arbitrage_id, order.order.order_id));
+        Ok(orders.len() as i64)
+    }
+
+    async fn get_overall_stats(&self, _start: Option<DateTime<Utc>>, _end: Option<DateTime<Utc>>) -> Result<TradeStats> {
+        unsupported("get_overall_stats")
+    }
+
+    async fn get_daily_stats(&self, _days: i32) -> Result<Vec<DailyStats>> {
+        unsupported("get_daily_stats")
+    }
+
+    async fn get_daily_stats_between(&self, _start: Option<DateTime<Utc>>, _end: Option<DateTime<Utc>>) -> Result<Vec<DailyStats>> {
+        unsupported("get_daily_stats_between")
+    }
+
+    async fn get_hourly_stats(&self, _days: i32) -> Result<Vec<HourlyStats>> {
+        unsupported("get_hourly_stats")
+    }
+
+    async fn get_hourly_stats_between(&self, _start: Option<DateTime<Utc>>, _end: Option<DateTime<Utc>>) -> Result<Vec<HourlyStats>> {
+        unsupported("get_hourly_stats_between")
+    }
+
+    async fn get_asset_stats(&self, _query: &AssetStatsQuery) -> Result<Vec<AssetStats>> {
+        unsupported("get_asset_stats")
+    }
+
+    async fn get_asset_daily_stats(&self, _start: Option<DateTime<Utc>>, _end: Option<DateTime<Utc>>) -> Result<Vec<AssetDailyStats>> {
+        unsupported("get_asset_daily_stats")
+    }
+
+    async fn get_strategy_stats(&self, _start: Option<DateTime<Utc>>, _end: Option<DateTime<Utc>>) -> Result<Vec<StrategyStats>> {
+        unsupported("get_strategy_stats")
+    }
+
+    async fn get_fee_slippage_stats(&self, _start: Option<DateTime<Utc>>, _end: Option<DateTime<Utc>>) -> Result<Option<FeeSlippageStats>> {
+        unsupported("get_fee_slippage_stats")
+    }
+
+    async fn record_rejection(&self, rejection: &RejectedOpportunity) -> Result<i64> {
+        self.check_available()?;
+        let mut rejections = self.rejections.lock().unwrap();
+        rejections.push(rejection.clone());
+        Ok(rejections.len() as i64)
+    }
+
+    async fn get_rejections(&self, _start: Option<DateTime<Utc>>, _end: Option<DateTime<Utc>>) -> Result<Vec<RejectedOpportunity>> {
+        Ok(self.rejections.lock().unwrap().clone())
+    }
+
+    async fn get_trade_history(&self, _filter: &TradeHistoryFilter, _limit: i64, _offset: i64) -> Result<Vec<TradeRecord>> {
+        unsupported("get_trade_history")
+    }
+
+    async fn get_trade_history_page(
+        &self,
+        _filter: &TradeHistoryFilter,
+        _limit: i64,
+        _cursor: Option<&HistoryCursor>,
+    ) -> Result<HistoryPage> {
+        unsupported("get_trade_history_page")
+    }
+}
@@ -0,0 +1,558 @@
+//! 交易历史的批量导出和导入
+//!
+//! 导出按游标分页读取 `arbitrage_history`，每页写出后即释放，不会把全部记录加载到内存；
+//! 导入逐行校验后写入，按幂等键去重，重复导入同一文件不会重复记录或重复累计统计。
+//! 支持CSV和JSON Lines两种格式，两者都包含每笔套利的订单记录（CSV中以JSON数组存放在 `orders` 列）。
+
+use super::{HistoryCursor, OrderRecord, StatsDeltas, TradeHistoryFilter, TradeRecord, TradeStore};
+use crate::models::{ArbitrageResult, ArbitrageStatus, FillInfo, TradingMode};
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Utc};
+use log::{debug, info};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io::{BufRead, Write};
+use std::path::Path;
+use std::str::FromStr;
+use uuid::Uuid;
+
+/// 导出时每次查询的记录数
+const EXPORT_PAGE_SIZE: i64 = 500;
+
+/// 导入时累计多少条新记录后写入一次汇总统计
+const IMPORT_STATS_BATCH: usize = 500;
+
+/// 交易历史的导出/导入格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HistoryFormat {
+    /// CSV，第一行为表头
+    Csv,
+    /// JSON Lines，每行一条 `TradeRecord`
+    JsonLines,
+}
+
+impl FromStr for HistoryFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "csv" => Ok(HistoryFormat::Csv),
+            "jsonl" | "ndjson" | "json-lines" => Ok(HistoryFormat::JsonLines),
+            _ => Err(anyhow!("不支持的历史记录格式: {}，应为 csv 或 jsonl", s)),
+        }
+    }
+}
+
+impl HistoryFormat {
+    /// 按文件扩展名判断格式，`.csv` 为CSV，其余按JSON Lines处理
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some(extension) if extension.eq_ignore_ascii_case("csv") => HistoryFormat::Csv,
+            _ => HistoryFormat::JsonLines,
+        }
+    }
+}
+
+impl fmt::Display for HistoryFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HistoryFormat::Csv => write!(f, "csv"),
+            HistoryFormat::JsonLines => write!(f, "jsonl"),
+        }
+    }
+}
+
+/// 导入结果统计
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ImportSummary {
+    /// 新写入的套利记录数
+    pub imported: u64,
+    /// 幂等键已存在而跳过的记录数
+    pub duplicates: u64,
+    /// 写入（或已存在）的订单记录数
+    pub orders: u64,
+}
+
+/// CSV中的一行，成交信息展开为独立的列
+#[derive(Debug, Serialize, Deserialize)]
+struct CsvHistoryRow {
+    id: i64,
+    base_asset: String,
+    buy_quote: String,
+    sell_quote: String,
+    buy_price: Decimal,
+    sell_price: Decimal,
+    trade_amount: Decimal,
+    profit: Decimal,
+    profit_percentage: Decimal,
+    buy_order_id: Option<u64>,
+    sell_order_id: Option<u64>,
+    status: ArbitrageStatus,
+    start_time: DateTime<Utc>,
+    end_time: DateTime<Utc>,
+    duration_ms: i64,
+    buy_fill_qty: Decimal,
+    buy_fill_price: Decimal,
+    buy_commission: Decimal,
+    buy_commission_asset: String,
+    sell_fill_qty: Decimal,
+    sell_fill_price: Decimal,
+    sell_commission: Decimal,
+    sell_commission_asset: String,
+    /// 旧版本导出的文件没有这两列，按实盘交易、未知配置导入
+    #[serde(default)]
+    mode: TradingMode,
+    #[serde(default)]
+    config_hash: String,
+    /// 发现该机会的策略，为空表示未知
+    #[serde(default)]
+    strategy: Option<String>,
+    /// 套利机会ID，为空表示未知
+    #[serde(default)]
+    opportunity_id: Option<Uuid>,
+    /// 订单记录，JSON数组
+    orders: String,
+}
+
+impl CsvHistoryRow {
+    fn from_record(record: &TradeRecord) -> Result<Self> {
+        let result = &record.result;
+        Ok(Self {
+            id: record.id,
+            base_asset: result.base_asset.clone(),
+            buy_quote: result.buy_quote.clone(),
+            sell_quote: result.sell_quote.clone(),
+            buy_price: result.buy_price,
+            sell_price: result.sell_price,
+            trade_amount: result.trade_amount,
+            profit: result.profit,
+            profit_percentage: result.profit_percentage,
+            buy_order_id: result.buy_order_id,
+            sell_order_id: result.sell_order_id,
+            status: result.status,
+            start_time: result.start_time,
+            end_time: result.end_time,
+            duration_ms: record.duration_ms,
+            buy_fill_qty: result.buy_fill.qty,
+            buy_fill_price: result.buy_fill.avg_price,
+            buy_commission: result.buy_fill.commission,
+            buy_commission_asset: result.buy_fill.commission_asset.clone(),
+            sell_fill_qty: result.sell_fill.qty,
+            sell_fill_price: result.sell_fill.avg_price,
+            sell_commission: result.sell_fill.commission,
+            sell_commission_asset: result.sell_fill.commission_asset.clone(),
+            mode: result.mode,
+            config_hash: result.config_hash.clone(),
+            strategy: result.strategy.clone(),
+            opportunity_id: result.opportunity_id,
+            orders: serde_json::to_string(&record.orders)?,
+        })
+    }
+
+    fn into_record(self) -> Result<TradeRecord> {
+        let orders: Vec<OrderRecord> = if self.orders.trim().is_empty() {
+            Vec::new()
+        } else {
+            serde_json::from_str(&self.orders).context("orders 列不是有效的订单记录JSON数组")?
+        };
+
+        Ok(TradeRecord {
+            id: self.id,
+            result: ArbitrageResult {
+                base_asset: self.base_asset,
+                buy_quote: self.buy_quote,
+                sell_quote: self.sell_quote,
+                buy_price: self.buy_price,
+                sell_price: self.sell_price,
+                trade_amount: self.trade_amount,
+                profit: self.profit,
+                profit_percentage: self.profit_percentage,
+                buy_order_id: self.buy_order_id,
+                sell_order_id: self.sell_order_id,
+                status: self.status,
+                timestamp: self.start_time,
+                start_time: self.start_time,
+                end_time: self.end_time,
+                buy_fill: FillInfo {
+                    qty: self.buy_fill_qty,
+                    avg_price: self.buy_fill_price,
+                    commission: self.buy_commission,
+                    commission_asset: self.buy_commission_asset,
+                },
+                sell_fill: FillInfo {
+                    qty: self.sell_fill_qty,
+                    avg_price: self.sell_fill_price,
+                    commission: self.sell_commission,
+                    commission_asset: self.sell_commission_asset,
+                },
+                mode: self.mode,
+                config_hash: self.config_hash,
+                strategy: self.strategy,
+                opportunity_id: self.opportunity_id,
+            },
+            duration_ms: self.duration_ms,
+            orders,
+        })
+    }
+}
+
+/// 按筛选条件导出交易历史（含订单记录），返回导出的记录数
+///
+/// 记录按开始时间倒序逐页写出。
+pub async fn export_history<W: Write>(
+    store: &dyn TradeStore,
+    writer: W,
+    filter: &TradeHistoryFilter,
+    format: HistoryFormat,
+) -> Result<u64> {
+    let filter = TradeHistoryFilter { include_orders: true, ..filter.clone() };
+    let mut sink = HistorySink::new(writer, format);
+    let mut cursor: Option<HistoryCursor> = None;
+    let mut exported = 0u64;
+
+    loop {
+        let page = store.get_trade_history_page(&filter, EXPORT_PAGE_SIZE, cursor.as_ref()).await?;
+        for record in &page.items {
+            sink.write(record)?;
+        }
+        exported += page.items.len() as u64;
+        debug!("已导出 {} 条交易历史", exported);
+
+        match page.next_cursor {
+            Some(next) => cursor = Some(next),
+            None => break,
+        }
+    }
+
+    sink.finish()?;
+    info!("交易历史导出完成: {} 条 ({})", exported, format);
+    Ok(exported)
+}
+
+/// 导入交易历史，逐行校验后写入，按幂等键跳过已存在的记录
+///
+/// 遇到无法解析或校验失败的行时返回带行号的错误，之前的行已经写入；
+/// 由于导入是幂等的，修正文件后可以重新导入整个文件。
+pub async fn import_history<R: BufRead>(
+    store: &dyn TradeStore,
+    reader: R,
+    format: HistoryFormat,
+) -> Result<ImportSummary> {
+    let mut summary = ImportSummary::default();
+    let mut pending = StatsDeltas::default();
+
+    let outcome = match format {
+        HistoryFormat::Csv => {
+            let mut csv_reader = csv::Reader::from_reader(reader);
+            let mut outcome = Ok(());
+            for (index, row) in csv_reader.deserialize::<CsvHistoryRow>().enumerate() {
+                // 第1行为表头
+                let line = index + 2;
+                let record = row
+                    .map_err(anyhow::Error::from)
+                    .and_then(CsvHistoryRow::into_record)
+                    .with_context(|| format!("第{}行无法解析", line));
+                let step = match record {
+                    Ok(record) => import_record(store, &record, &mut summary, &mut pending).await,
+                    Err(e) => Err(e),
+                };
+                if let Err(e) = step {
+                    outcome = Err(e.context(format!("导入第{}行失败", line)));
+                    break;
+                }
+            }
+            outcome
+        },
+        HistoryFormat::JsonLines => {
+            let mut outcome = Ok(());
+            for (index, line) in reader.lines().enumerate() {
+                let line_no = index + 1;
+                let step = match line {
+                    Ok(line) if line.trim().is_empty() => continue,
+                    Ok(line) => match serde_json::from_str::<TradeRecord>(&line) {
+                        Ok(record) => import_record(store, &record, &mut summary, &mut pending).await,
+                        Err(e) => Err(anyhow::Error::from(e).context("无法解析为交易记录")),
+                    },
+                    Err(e) => Err(e.into()),
+                };
+                if let Err(e) = step {
+                    outcome = Err(e.context(format!("导入第{}行失败", line_no)));
+                    break;
+                }
+            }
+            outcome
+        },
+    };
+
+    // 出错时也写入已导入记录的统计，保证统计与已写入的历史一致
+    if !pending.is_empty() {
+        store.apply_stats_deltas(&pending).await?;
+    }
+    outcome?;
+
+    info!(
+        "交易历史导入完成: 新增 {} 条, 已存在 {} 条, 订单 {} 条",
+        summary.imported, summary.duplicates, summary.orders
+    );
+    Ok(summary)
+}
+
+/// 校验并写入一条记录，新写入记录的统计累加到 `pending`，达到批量大小时写入
+async fn import_record(
+    store: &dyn TradeStore,
+    record: &TradeRecord,
+    summary: &mut ImportSummary,
+    pending: &mut StatsDeltas,
+) -> Result<()> {
+    validate_result(&record.result)?;
+
+    let (id, inserted) = store.record_arbitrage_history(&record.result).await?;
+    if inserted {
+        summary.imported += 1;
+        pending.add_result(&record.result, store.stats_offset());
+        if pending.trades() as usize >= IMPORT_STATS_BATCH {
+            store.apply_stats_deltas(pending).await?;
+            *pending = StatsDeltas::default();
+        }
+    } else {
+        summary.duplicates += 1;
+    }
+
+    for order in &record.orders {
+        store.record_order(id, order).await?;
+        summary.orders += 1;
+    }
+
+    Ok(())
+}
+
+/// 校验导入的套利结果
+fn validate_result(result: &ArbitrageResult) -> Result<()> {
+    if result.base_asset.trim().is_empty() {
+        return Err(anyhow!("基础资产不能为空"));
+    }
+    if result.buy_quote.trim().is_empty() || result.sell_quote.trim().is_empty() {
+        return Err(anyhow!("买入和卖出的报价货币不能为空"));
+    }
+    for (name, value) in [
+        ("buy_price", result.buy_price),
+        ("sell_price", result.sell_price),
+        ("trade_amount", result.trade_amount),
+    ] {
+        if value.is_sign_negative() {
+            return Err(anyhow!("{} 不能为负数: {}", name, value));
+        }
+    }
+    if result.end_time < result.start_time {
+        return Err(anyhow!("结束时间 {} 早于开始时间 {}", result.end_time, result.start_time));
+    }
+
+    Ok(())
+}
+
+/// 按格式逐条写出记录
+enum HistorySink<W: Write> {
+    Csv(Box<csv::Writer<W>>),
+    JsonLines(W),
+}
+
+impl<W: Write> HistorySink<W> {
+    fn new(writer: W, format: HistoryFormat) -> Self {
+        match format {
+            HistoryFormat::Csv => HistorySink::Csv(Box::new(csv::Writer::from_writer(writer))),
+            HistoryFormat::JsonLines => HistorySink::JsonLines(writer),
+        }
+    }
+
+    fn write(&mut self, record: &TradeRecord) -> Result<()> {
+        match self {
+            HistorySink::Csv(writer) => writer.serialize(CsvHistoryRow::from_record(record)?)?,
+            HistorySink::JsonLines(writer) => {
+                serde_json::to_writer(&mut *writer, record)?;
+                writer.write_all(b"\n")?;
+            },
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> Result<()> {
+        match self {
+            HistorySink::Csv(mut writer) => writer.flush()?,
+            HistorySink::JsonLines(mut writer) => writer.flush()?,
+        }
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "db-sqlite"))]
+mod tests {
+    use super::*;
+    use crate::db::DatabaseManager;
+    use crate::models::testing::{arbitrage_result_at, fill};
+    use crate::models::{OrderInfo, OrderStatus, Side};
+    use chrono::Duration;
+    use rust_decimal_macros::dec;
+
+    fn make_result(asset: &str, profit: Decimal, status: ArbitrageStatus, minutes_ago: i64) -> ArbitrageResult {
+        let start_time = Utc::now() - Duration::minutes(minutes_ago);
+        ArbitrageResult {
+            base_asset: asset.to_string(),
+            profit,
+            buy_order_id: Some(minutes_ago as u64 * 2 + 1),
+            sell_order_id: Some(minutes_ago as u64 * 2 + 2),
+            buy_fill: fill(dec!(0.1), dec!(50000), dec!(0.0001), asset),
+            ..arbitrage_result_at(status, start_time, Duration::milliseconds(250))
+        }
+    }
+
+    fn make_order(order_id: u64, side: Side) -> OrderInfo {
+        OrderInfo {
+            order_id,
+            symbol: "BTCUSDT".to_string(),
+            price: dec!(50000),
+            qty: dec!(0.1),
+            executed_qty: dec!(0.1),
+            avg_price: dec!(50000),
+            commission: Decimal::ZERO,
+            commission_asset: "BTC".to_string(),
+            side,
+            status: OrderStatus::Filled,
+            timestamp: Utc::now(),
+        }
+    }
+
+    /// 写入若干套利记录，第一条带有买卖两个订单
+    async fn seeded_db() -> DatabaseManager {
+        let db = DatabaseManager::in_memory().await.unwrap();
+        let results = vec![
+            ArbitrageResult {
+                mode: TradingMode::Simulate,
+                config_hash: "0123456789abcdef".to_string(),
+                strategy: Some("简单价格差异套利".to_string()),
+                opportunity_id: Some(Uuid::from_u128(0x0123_4567_89ab_cdef_0123_4567_89ab_cdef)),
+                ..make_result("BTC", dec!(10.5), ArbitrageStatus::Completed, 1)
+            },
+            make_result("ETH", dec!(-2.25), ArbitrageStatus::Failed, 2),
+            make_result("BTC", dec!(0.0000000001), ArbitrageStatus::Completed, 3),
+            make_result("BNB", dec!(3), ArbitrageStatus::Completed, 4),
+        ];
+        for (index, result) in results.iter().enumerate() {
+            let id = db.record_arbitrage_result(result).await.unwrap();
+            if index == 0 {
+                db.record_order(id, &make_order(11, Side::Buy)).await.unwrap();
+                db.record_order(id, &make_order(12, Side::Sell)).await.unwrap();
+            }
+        }
+        db
+    }
+
+    async fn assert_same_stats(source: &DatabaseManager, target: &DatabaseManager) {
+        let (a, b) = (source.get_overall_stats().await.unwrap(), target.get_overall_stats().await.unwrap());
+        assert_eq!(a.total_trades, b.total_trades);
+        assert_eq!(a.successful_trades, b.successful_trades);
+        assert_eq!(a.total_profit, b.total_profit);
+        assert_eq!(a.total_volume, b.total_volume);
+
+        let assets = |stats: Vec<crate::db::AssetStats>| {
+            stats.into_iter().map(|s| (s.asset, s.trades, s.profit)).collect::<Vec<_>>()
+        };
+        assert_eq!(
+            assets(source.get_asset_stats(10).await.unwrap()),
+            assets(target.get_asset_stats(10).await.unwrap())
+        );
+
+        let daily = |stats: Vec<crate::db::DailyStats>| {
+            stats.into_iter().map(|s| (s.date, s.trades, s.profit)).collect::<Vec<_>>()
+        };
+        assert_eq!(
+            daily(source.get_daily_stats(7).await.unwrap()),
+            daily(target.get_daily_stats(7).await.unwrap())
+        );
+    }
+
+    async fn round_trip(format: HistoryFormat) {
+        let source = seeded_db().await;
+        let mut buffer = Vec::new();
+        let exported = source
+            .export_history(&mut buffer, &TradeHistoryFilter::default(), format)
+            .await
+            .unwrap();
+        assert_eq!(exported, 4);
+
+        let target = DatabaseManager::in_memory().await.unwrap();
+        let summary = target.import_history(buffer.as_slice(), format).await.unwrap();
+        assert_eq!(summary, ImportSummary { imported: 4, duplicates: 0, orders: 2 });
+        assert_same_stats(&source, &target).await;
+
+        let filter = TradeHistoryFilter { include_orders: true, ..TradeHistoryFilter::default() };
+        let records = target.get_trade_history(&filter, 10, 0).await.unwrap();
+        assert_eq!(records[0].result.profit, dec!(10.5));
+        assert_eq!(records[0].result.buy_fill.commission, dec!(0.0001));
+        assert_eq!(records[0].result.mode, TradingMode::Simulate);
+        assert_eq!(records[0].result.config_hash, "0123456789abcdef");
+        assert_eq!(records[0].result.strategy.as_deref(), Some("简单价格差异套利"));
+        assert_eq!(records[1].result.strategy, None);
+        assert_eq!(records[0].result.opportunity_id, Some(Uuid::from_u128(0x0123_4567_89ab_cdef_0123_4567_89ab_cdef)));
+        assert_eq!(records[1].result.opportunity_id, None);
+        assert_eq!(records[1].result.mode, TradingMode::Live);
+        let order_ids: Vec<u64> = records[0].orders.iter().map(|o| o.order.order_id).collect();
+        assert_eq!(order_ids, vec![11, 12]);
+
+        // 重复导入不会重复记录或重复累计统计
+        let summary = target.import_history(buffer.as_slice(), format).await.unwrap();
+        assert_eq!(summary, ImportSummary { imported: 0, duplicates: 4, orders: 2 });
+        assert_same_stats(&source, &target).await;
+        assert_eq!(target.get_trade_history(&filter, 10, 0).await.unwrap()[0].orders.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_csv_round_trip() {
+        round_trip(HistoryFormat::Csv).await;
+    }
+
+    #[tokio::test]
+    async fn test_jsonl_round_trip() {
+        round_trip(HistoryFormat::JsonLines).await;
+    }
+
+    #[tokio::test]
+    async fn test_export_respects_range() {
+        let source = seeded_db().await;
+        let mut buffer = Vec::new();
+        let filter = TradeHistoryFilter::between(Some(Utc::now() - Duration::seconds(150)), None);
+        let exported = source.export_history(&mut buffer, &filter, HistoryFormat::JsonLines).await.unwrap();
+        assert_eq!(exported, 2);
+        assert_eq!(String::from_utf8(buffer).unwrap().lines().count(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_row_reports_line_and_keeps_stats_consistent() {
+        let source = seeded_db().await;
+        let mut buffer = Vec::new();
+        source
+            .export_history(&mut buffer, &TradeHistoryFilter::default(), HistoryFormat::JsonLines)
+            .await
+            .unwrap();
+
+        // 第2行的交易量改为负数
+        let mut lines: Vec<String> = String::from_utf8(buffer).unwrap().lines().map(str::to_string).collect();
+        let mut record: TradeRecord = serde_json::from_str(&lines[1]).unwrap();
+        record.result.trade_amount = dec!(-1);
+        lines[1] = serde_json::to_string(&record).unwrap();
+        let input = lines.join("\n");
+
+        let target = DatabaseManager::in_memory().await.unwrap();
+        let err = target.import_history(input.as_bytes(), HistoryFormat::JsonLines).await.unwrap_err();
+        assert!(format!("{:#}", err).contains("第2行"));
+
+        // 第1行已经写入，统计与历史一致
+        let stats = target.get_overall_stats().await.unwrap();
+        assert_eq!(stats.total_trades, 1);
+        assert_eq!(target.get_asset_stats(10).await.unwrap()[0].trades, 1);
+
+        assert!(target.import_history("not json".as_bytes(), HistoryFormat::JsonLines).await.is_err());
+        assert!("xml".parse::<HistoryFormat>().is_err());
+        assert_eq!("JSONL".parse::<HistoryFormat>().unwrap(), HistoryFormat::JsonLines);
+    }
+}
@@ -0,0 +1,792 @@
+//! 异步批量写入队列
+//!
+//! 交易主循环只把结果放入有界队列，由后台任务按批量大小或时间间隔写入存储，
+//! 写入失败时按指数退避重试，仍然失败的记录放入有界的重试缓冲，在配置的时长内随后续写入继续重试，
+//! 超时或缓冲已满时丢弃并计入指标，不会阻塞交易。
+//! 可选地在内存中累计汇总统计的增量并定期写入，避免高频交易时逐笔更新汇总表。
+
+use anyhow::{anyhow, Result};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, oneshot};
+use tokio::time::sleep;
+use log::{debug, info, warn, error};
+use serde::{Serialize, Deserialize};
+use super::{OrderRecord, StatsDeltas, TradeStore};
+use crate::models::ArbitrageResult;
+
+/// 写入队列配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WriteBufferConfig {
+    /// 队列容量，队列满时新记录被丢弃并计数
+    pub capacity: usize,
+    /// 达到该数量时立即批量写入
+    pub batch_size: usize,
+    /// 距上次写入超过该时间（毫秒）时写入缓冲中的记录
+    pub flush_interval_ms: u64,
+    /// 单条记录写入失败后的最大重试次数
+    pub max_retries: u32,
+    /// 首次重试前的等待时间（毫秒），之后每次翻倍
+    pub retry_backoff_ms: u64,
+    /// 汇总统计表两次写入之间的最短间隔（毫秒）
+    ///
+    /// 为0时每笔交易都在同一事务中更新汇总表；大于0时交易历史仍立即写入，
+    /// 汇总统计的增量在内存中累计，超过该间隔、显式flush或写入器退出时合并写入。
+    #[serde(default)]
+    pub stats_flush_interval_ms: u64,
+    /// 重试耗尽后仍写入失败的记录在内存中保留的最长时间（毫秒）
+    ///
+    /// 保留期间每次写入前先按顺序重试这些记录，数据库恢复后自动补写；超过该时间后丢弃。
+    /// 为0时不保留，重试耗尽后立即丢弃。
+    #[serde(default = "default_retry_period_ms")]
+    pub retry_period_ms: u64,
+    /// 等待重试的记录上限，超过时丢弃最早的记录
+    #[serde(default = "default_retry_buffer_capacity")]
+    pub retry_buffer_capacity: usize,
+}
+
+fn default_retry_period_ms() -> u64 {
+    300_000
+}
+
+fn default_retry_buffer_capacity() -> usize {
+    1000
+}
+
+impl Default for WriteBufferConfig {
+    fn default() -> Self {
+        Self {
+            capacity: 1000,
+            batch_size: 20,
+            flush_interval_ms: 1000,
+            max_retries: 3,
+            retry_backoff_ms: 100,
+            stats_flush_interval_ms: 0,
+            retry_period_ms: default_retry_period_ms(),
+            retry_buffer_capacity: default_retry_buffer_capacity(),
+        }
+    }
+}
+
+/// 写入队列指标
+#[derive(Debug, Default)]
+pub struct WriteMetrics {
+    queued: AtomicU64,
+    written: AtomicU64,
+    retries: AtomicU64,
+    failed: AtomicU64,
+    dropped: AtomicU64,
+    pending_retry: AtomicU64,
+}
+
+/// 写入队列指标快照
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WriteMetricsSnapshot {
+    /// 成功放入队列的记录数
+    pub queued: u64,
+    /// 成功写入存储的记录数
+    pub written: u64,
+    /// 重试次数
+    pub retries: u64,
+    /// 最终未能写入而放弃的记录数（重试耗尽且未保留、超过重试时长或重试缓冲已满）
+    pub failed: u64,
+    /// 因队列已满或已关闭而丢弃的记录数
+    pub dropped: u64,
+    /// 当前在重试缓冲中等待重试的记录数
+    #[serde(default)]
+    pub pending_retry: u64,
+}
+
+impl WriteMetrics {
+    /// 获取当前指标快照
+    pub fn snapshot(&self) -> WriteMetricsSnapshot {
+        WriteMetricsSnapshot {
+            queued: self.queued.load(Ordering::Relaxed),
+            written: self.written.load(Ordering::Relaxed),
+            retries: self.retries.load(Ordering::Relaxed),
+            failed: self.failed.load(Ordering::Relaxed),
+            dropped: self.dropped.load(Ordering::Relaxed),
+            pending_retry: self.pending_retry.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// 队列中待写入的套利结果及其订单记录
+struct QueuedResult {
+    result: ArbitrageResult,
+    orders: Vec<OrderRecord>,
+}
+
+/// 写入失败、等待重试的记录
+struct RetryEntry {
+    queued: QueuedResult,
+    /// 首次写入失败的时间
+    failed_at: Instant,
+}
+
+/// 写入失败的记录的有界重试缓冲，按失败顺序重试
+struct RetryBuffer {
+    entries: VecDeque<RetryEntry>,
+    capacity: usize,
+    period: Duration,
+}
+
+impl RetryBuffer {
+    fn new(config: &WriteBufferConfig) -> Self {
+        Self {
+            entries: VecDeque::new(),
+            capacity: config.retry_buffer_capacity,
+            period: Duration::from_millis(config.retry_period_ms),
+        }
+    }
+
+    /// 是否保留写入失败的记录
+    fn enabled(&self) -> bool {
+        !self.period.is_zero() && self.capacity > 0
+    }
+
+    fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// 放入一条写入失败的记录，缓冲已满时丢弃最早的记录
+    fn push(&mut self, queued: QueuedResult, failed_at: Instant, metrics: &WriteMetrics) {
+        if self.entries.len() >= self.capacity {
+            if let Some(oldest) = self.entries.pop_front() {
+                metrics.failed.fetch_add(1, Ordering::Relaxed);
+                error!(
+                    "重试缓冲已满 ({} 条)，丢弃最早未写入的套利结果: {} 开始时间 {} 利润 {}",
+                    self.capacity, oldest.queued.result.base_asset, oldest.queued.result.start_time, oldest.queued.result.profit
+                );
+            }
+        }
+        self.entries.push_back(RetryEntry { queued, failed_at });
+        metrics.pending_retry.store(self.entries.len() as u64, Ordering::Relaxed);
+    }
+
+    /// 丢弃超过重试时长的记录
+    fn expire(&mut self, metrics: &WriteMetrics) {
+        while let Some(entry) = self.entries.front() {
+            if entry.failed_at.elapsed() < self.period {
+                break;
+            }
+            let entry = self.entries.pop_front().unwrap();
+            metrics.failed.fetch_add(1, Ordering::Relaxed);
+            error!(
+                "套利结果在 {:?} 内仍未能写入数据库，已丢弃: {} 开始时间 {} 利润 {}",
+                self.period, entry.queued.result.base_asset, entry.queued.result.start_time, entry.queued.result.profit
+            );
+        }
+        metrics.pending_retry.store(self.entries.len() as u64, Ordering::Relaxed);
+    }
+
+    /// 清空缓冲，返回丢弃的记录数
+    fn discard_all(&mut self, metrics: &WriteMetrics) -> usize {
+        let count = self.entries.len();
+        self.entries.clear();
+        metrics.failed.fetch_add(count as u64, Ordering::Relaxed);
+        metrics.pending_retry.store(0, Ordering::Relaxed);
+        count
+    }
+}
+
+/// 发送给后台写入任务的命令
+enum WriteCommand {
+    Record(Box<QueuedResult>),
+    Flush(oneshot::Sender<()>),
+}
+
+/// 异步批量写入器，克隆后共享同一个后台任务
+#[derive(Clone)]
+pub struct BufferedWriter {
+    sender: mpsc::Sender<WriteCommand>,
+    metrics: Arc<WriteMetrics>,
+}
+
+impl BufferedWriter {
+    /// 创建写入器并启动后台写入任务，需要在tokio运行时中调用
+    pub fn spawn(store: Arc<dyn TradeStore>, config: WriteBufferConfig) -> Self {
+        let (sender, receiver) = mpsc::channel(config.capacity.max(1));
+        let metrics = Arc::new(WriteMetrics::default());
+
+        tokio::spawn(run_writer(store, config, receiver, metrics.clone()));
+
+        Self { sender, metrics }
+    }
+
+    /// 将套利结果放入队列，不等待写入完成；队列已满时丢弃并返回错误
+    pub fn enqueue(&self, result: &ArbitrageResult) -> Result<()> {
+        self.enqueue_with_orders(result, Vec::new())
+    }
+
+    /// 将套利结果及其订单记录放入队列，订单在套利结果写入后按其记录ID写入
+    pub fn enqueue_with_orders(&self, result: &ArbitrageResult, orders: Vec<OrderRecord>) -> Result<()> {
+        let queued = QueuedResult { result: result.clone(), orders };
+        match self.sender.try_send(WriteCommand::Record(Box::new(queued))) {
+            Ok(()) => {
+                self.metrics.queued.fetch_add(1, Ordering::Relaxed);
+                Ok(())
+            },
+            Err(mpsc::error::TrySendError::Full(_)) => {
+                self.metrics.dropped.fetch_add(1, Ordering::Relaxed);
+                Err(anyhow!("写入队列已满，丢弃套利结果: {}", result.base_asset))
+            },
+            Err(mpsc::error::TrySendError::Closed(_)) => {
+                self.metrics.dropped.fetch_add(1, Ordering::Relaxed);
+                Err(anyhow!("写入队列已关闭，丢弃套利结果: {}", result.base_asset))
+            },
+        }
+    }
+
+    /// 等待队列中已有的记录全部处理完毕（写入成功或重试耗尽）
+    pub async fn flush(&self) -> Result<()> {
+        let (tx, rx) = oneshot::channel();
+        self.sender
+            .send(WriteCommand::Flush(tx))
+            .await
+            .map_err(|_| anyhow!("写入队列已关闭"))?;
+        rx.await.map_err(|_| anyhow!("写入任务已退出"))?;
+        Ok(())
+    }
+
+    /// 写入队列指标
+    pub fn metrics(&self) -> WriteMetricsSnapshot {
+        self.metrics.snapshot()
+    }
+}
+
+/// 后台写入任务，按批量大小或时间间隔写入，所有发送端关闭后写入剩余记录并退出
+async fn run_writer(
+    store: Arc<dyn TradeStore>,
+    config: WriteBufferConfig,
+    mut receiver: mpsc::Receiver<WriteCommand>,
+    metrics: Arc<WriteMetrics>,
+) {
+    let flush_interval = Duration::from_millis(config.flush_interval_ms.max(1));
+    let batch_size = config.batch_size.max(1);
+    let mut buffer: Vec<QueuedResult> = Vec::with_capacity(batch_size);
+    let mut retry = RetryBuffer::new(&config);
+    let mut last_flush = Instant::now();
+
+    // 汇总统计的增量，仅在启用统计合并写入时使用
+    let stats_interval = (config.stats_flush_interval_ms > 0)
+        .then(|| Duration::from_millis(config.stats_flush_interval_ms));
+    let mut pending_stats = stats_interval.map(|_| StatsDeltas::default());
+    let mut last_stats_flush = Instant::now();
+
+    loop {
+        let mut wait = flush_interval.saturating_sub(last_flush.elapsed());
+        if let (Some(interval), Some(pending)) = (stats_interval, &pending_stats) {
+            if !pending.is_empty() {
+                wait = wait.min(interval.saturating_sub(last_stats_flush.elapsed()));
+            }
+        }
+
+        match tokio::time::timeout(wait, receiver.recv()).await {
+            Ok(Some(WriteCommand::Record(queued))) => {
+                buffer.push(*queued);
+                if buffer.len() >= batch_size {
+                    write_batch(store.as_ref(), &config, &mut buffer, pending_stats.as_mut(), &mut retry, &metrics).await;
+                    last_flush = Instant::now();
+                }
+            },
+            Ok(Some(WriteCommand::Flush(done))) => {
+                write_batch(store.as_ref(), &config, &mut buffer, pending_stats.as_mut(), &mut retry, &metrics).await;
+                last_flush = Instant::now();
+                if let Some(pending) = pending_stats.as_mut() {
+                    flush_stats(store.as_ref(), pending).await;
+                    last_stats_flush = Instant::now();
+                }
+                let _ = done.send(());
+            },
+            Ok(None) => {
+                write_batch(store.as_ref(), &config, &mut buffer, pending_stats.as_mut(), &mut retry, &metrics).await;
+                if let Some(pending) = pending_stats.as_mut() {
+                    flush_stats(store.as_ref(), pending).await;
+                }
+                let discarded = retry.discard_all(&metrics);
+                if discarded > 0 {
+                    error!("写入任务退出时仍有 {} 条套利结果未能写入数据库，已丢弃", discarded);
+                }
+                debug!("写入队列已关闭，后台写入任务退出");
+                return;
+            },
+            Err(_) => {
+                // 超过写入间隔
+                if last_flush.elapsed() >= flush_interval {
+                    write_batch(store.as_ref(), &config, &mut buffer, pending_stats.as_mut(), &mut retry, &metrics).await;
+                    last_flush = Instant::now();
+                }
+            },
+        }
+
+        if let (Some(interval), Some(pending)) = (stats_interval, pending_stats.as_mut()) {
+            if last_stats_flush.elapsed() >= interval {
+                flush_stats(store.as_ref(), pending).await;
+                last_stats_flush = Instant::now();
+            }
+        }
+    }
+}
+
+/// 将累计的汇总统计增量写入存储，失败时保留增量等待下次写入
+async fn flush_stats(store: &dyn TradeStore, pending: &mut StatsDeltas) {
+    if pending.is_empty() {
+        return;
+    }
+
+    match store.apply_stats_deltas(pending).await {
+        Ok(()) => {
+            debug!("已合并写入 {} 笔交易的汇总统计", pending.trades());
+            *pending = StatsDeltas::default();
+        },
+        Err(e) => {
+            warn!("写入汇总统计失败，{} 笔交易的增量保留到下次写入: {}", pending.trades(), e);
+        },
+    }
+}
+
+/// 写入一条套利结果及其订单记录，返回套利记录ID
+///
+/// 套利结果和订单记录的写入都是幂等的，订单写入失败后整体重试不会重复记录或重复累计统计。
+async fn write_one(
+    store: &dyn TradeStore,
+    queued: &QueuedResult,
+    pending_stats: Option<&mut StatsDeltas>,
+) -> Result<i64> {
+    let id = match pending_stats {
+        Some(pending) => {
+            let (id, inserted) = store.record_arbitrage_history(&queued.result).await?;
+            if inserted {
+                pending.add_result(&queued.result, store.stats_offset());
+            }
+            id
+        },
+        None => store.record_arbitrage_result(&queued.result).await?,
+    };
+
+    for order in &queued.orders {
+        store.record_order(id, order).await?;
+    }
+
+    Ok(id)
+}
+
+/// 按失败顺序重试缓冲中的记录，遇到仍然失败的记录时停止，保留剩余记录等待下次重试
+async fn retry_pending(
+    store: &dyn TradeStore,
+    retry: &mut RetryBuffer,
+    mut pending_stats: Option<&mut StatsDeltas>,
+    metrics: &WriteMetrics,
+) {
+    retry.expire(metrics);
+    if retry.is_empty() {
+        return;
+    }
+
+    while let Some(entry) = retry.entries.front() {
+        match write_one(store, &entry.queued, pending_stats.as_deref_mut()).await {
+            Ok(id) => {
+                retry.entries.pop_front();
+                metrics.written.fetch_add(1, Ordering::Relaxed);
+                debug!("重试写入套利结果成功: ID={}", id);
+            },
+            Err(e) => {
+                metrics.retries.fetch_add(1, Ordering::Relaxed);
+                warn!("数据库仍不可用，{} 条套利结果等待重试: {}", retry.entries.len(), e);
+                break;
+            },
+        }
+    }
+
+    metrics.pending_retry.store(retry.entries.len() as u64, Ordering::Relaxed);
+    if retry.is_empty() {
+        info!("数据库写入已恢复，重试缓冲中的套利结果已全部写入");
+    }
+}
+
+/// 逐条写入缓冲中的记录，失败时按指数退避重试
+///
+/// 先重试之前写入失败的记录；重试缓冲不为空说明存储尚未恢复，新记录直接放入重试缓冲以保持写入顺序。
+/// `pending_stats` 不为None时只写入交易历史，新写入记录的统计增量累加到其中。
+async fn write_batch(
+    store: &dyn TradeStore,
+    config: &WriteBufferConfig,
+    buffer: &mut Vec<QueuedResult>,
+    mut pending_stats: Option<&mut StatsDeltas>,
+    retry: &mut RetryBuffer,
+    metrics: &WriteMetrics,
+) {
+    if buffer.is_empty() && retry.is_empty() {
+        return;
+    }
+
+    retry_pending(store, retry, pending_stats.as_deref_mut(), metrics).await;
+
+    if buffer.is_empty() {
+        return;
+    }
+
+    debug!("批量写入 {} 条套利结果", buffer.len());
+
+    for queued in buffer.drain(..) {
+        if !retry.is_empty() {
+            retry.push(queued, Instant::now(), metrics);
+            continue;
+        }
+
+        let mut attempt = 0;
+        let mut backoff = Duration::from_millis(config.retry_backoff_ms);
+
+        loop {
+            match write_one(store, &queued, pending_stats.as_deref_mut()).await {
+                Ok(id) => {
+                    metrics.written.fetch_add(1, Ordering::Relaxed);
+                    debug!("已写入套利结果: ID={}", id);
+                    break;
+                },
+                Err(e) if attempt < config.max_retries => {
+                    attempt += 1;
+                    metrics.retries.fetch_add(1, Ordering::Relaxed);
+                    warn!("写入套利结果失败，{:?}后第{}次重试: {}", backoff, attempt, e);
+                    sleep(backoff).await;
+                    backoff *= 2;
+                },
+                Err(e) if retry.enabled() => {
+                    warn!("写入套利结果失败，已重试{}次，放入重试缓冲: {}", attempt, e);
+                    retry.push(queued, Instant::now(), metrics);
+                    break;
+                },
+                Err(e) => {
+                    metrics.failed.fetch_add(1, Ordering::Relaxed);
+                    error!("写入套利结果失败，已重试{}次，放弃该记录: {}", attempt, e);
+                    break;
+                },
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::DatabaseManager;
+    use crate::db::testing::MemoryStore;
+    use crate::models::testing::arbitrage_result;
+    use crate::models::{ArbitrageStatus, OrderInfo, OrderStatus, Side};
+    use chrono::Utc;
+    use rust_decimal::Decimal;
+    use rust_decimal_macros::dec;
+
+    fn make_result() -> ArbitrageResult {
+        make_asset_result("BTC", dec!(10), ArbitrageStatus::Completed)
+    }
+
+    fn make_asset_result(asset: &str, profit: Decimal, status: ArbitrageStatus) -> ArbitrageResult {
+        ArbitrageResult { base_asset: asset.to_string(), profit, ..arbitrage_result(status) }
+    }
+
+    fn make_order(order_id: u64, side: Side) -> OrderRecord {
+        OrderRecord::resolved(OrderInfo {
+            order_id,
+            symbol: "BTCUSDT".to_string(),
+            price: dec!(50000),
+            qty: dec!(0.1),
+            executed_qty: dec!(0.1),
+            avg_price: dec!(50000),
+            commission: Decimal::ZERO,
+            commission_asset: "BTC".to_string(),
+            side,
+            status: OrderStatus::Filled,
+            timestamp: Utc::now(),
+        })
+    }
+
+    fn test_config() -> WriteBufferConfig {
+        WriteBufferConfig {
+            capacity: 100,
+            batch_size: 10,
+            flush_interval_ms: 60_000,
+            max_retries: 2,
+            retry_backoff_ms: 1,
+            stats_flush_interval_ms: 0,
+            retry_period_ms: 0,
+            retry_buffer_capacity: 100,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_flush_writes_buffered_results() {
+        let store = MemoryStore::new();
+        let writer = BufferedWriter::spawn(store.clone(), test_config());
+
+        for _ in 0..3 {
+            writer.enqueue(&make_result()).unwrap();
+        }
+        // 未达到批量大小且未到写入间隔，记录仍在缓冲中
+        sleep(Duration::from_millis(20)).await;
+        assert_eq!(store.count(), 0);
+
+        writer.flush().await.unwrap();
+        assert_eq!(store.count(), 3);
+
+        let metrics = writer.metrics();
+        assert_eq!(metrics.queued, 3);
+        assert_eq!(metrics.written, 3);
+        assert_eq!(metrics.failed, 0);
+    }
+
+    #[tokio::test]
+    async fn test_flush_on_batch_size_and_interval() {
+        let store = MemoryStore::new();
+        let config = WriteBufferConfig { batch_size: 2, flush_interval_ms: 50, ..test_config() };
+        let writer = BufferedWriter::spawn(store.clone(), config);
+
+        writer.enqueue(&make_result()).unwrap();
+        writer.enqueue(&make_result()).unwrap();
+        writer.enqueue(&make_result()).unwrap();
+        sleep(Duration::from_millis(10)).await;
+        assert_eq!(store.count(), 2);
+
+        sleep(Duration::from_millis(100)).await;
+        assert_eq!(store.count(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_retry_and_failure_metrics() {
+        // 第一条记录失败两次后成功
+        let store = MemoryStore::with_failures(2);
+        let writer = BufferedWriter::spawn(store.clone(), test_config());
+        writer.enqueue(&make_result()).unwrap();
+        writer.flush().await.unwrap();
+        assert_eq!(store.count(), 1);
+        assert_eq!(writer.metrics().retries, 2);
+        assert_eq!(writer.metrics().failed, 0);
+
+        // 重试耗尽后计入失败，不影响后续记录
+        let store = MemoryStore::with_failures(3);
+        let writer = BufferedWriter::spawn(store.clone(), test_config());
+        writer.enqueue(&make_result()).unwrap();
+        writer.enqueue(&make_result()).unwrap();
+        writer.flush().await.unwrap();
+        assert_eq!(store.count(), 1);
+        let metrics = writer.metrics();
+        assert_eq!(metrics.failed, 1);
+        assert_eq!(metrics.written, 1);
+    }
+
+    #[tokio::test]
+    async fn test_full_queue_drops_without_blocking() {
+        let store = MemoryStore::new();
+        let config = WriteBufferConfig { capacity: 1, ..test_config() };
+        let writer = BufferedWriter::spawn(store.clone(), config);
+
+        let mut dropped = 0;
+        for _ in 0..10 {
+            if writer.enqueue(&make_result()).is_err() {
+                dropped += 1;
+            }
+        }
+        assert!(dropped > 0);
+
+        writer.flush().await.unwrap();
+        let metrics = writer.metrics();
+        assert_eq!(metrics.dropped, dropped);
+        assert_eq!(metrics.queued + metrics.dropped, 10);
+        assert_eq!(store.count() as u64, metrics.written);
+    }
+
+    fn mixed_results() -> Vec<ArbitrageResult> {
+        vec![
+            make_asset_result("BTC", dec!(10), ArbitrageStatus::Completed),
+            make_asset_result("ETH", dec!(-2.5), ArbitrageStatus::Failed),
+            make_asset_result("BTC", dec!(0.0000000001), ArbitrageStatus::Completed),
+            make_asset_result("ETH", dec!(3), ArbitrageStatus::Completed),
+        ]
+    }
+
+    #[tokio::test]
+    async fn test_aggregated_stats_match_per_trade_updates() {
+        // 逐笔更新汇总表
+        let per_trade = MemoryStore::new();
+        let writer = BufferedWriter::spawn(per_trade.clone(), test_config());
+        for result in mixed_results() {
+            writer.enqueue(&result).unwrap();
+        }
+        writer.flush().await.unwrap();
+        assert_eq!(per_trade.stats_writes(), 4);
+
+        // 合并写入汇总表
+        let aggregated = MemoryStore::new();
+        let config = WriteBufferConfig { batch_size: 1, stats_flush_interval_ms: 60_000, ..test_config() };
+        let writer = BufferedWriter::spawn(aggregated.clone(), config);
+        for result in mixed_results() {
+            writer.enqueue(&result).unwrap();
+        }
+        // 交易历史立即写入，汇总统计仍在内存中
+        sleep(Duration::from_millis(20)).await;
+        assert_eq!(aggregated.count(), 4);
+        assert!(aggregated.stats().is_empty());
+
+        writer.flush().await.unwrap();
+        assert_eq!(aggregated.stats_writes(), 1);
+        assert_eq!(aggregated.stats(), per_trade.stats());
+
+        let btc = &aggregated.stats().assets["BTC"];
+        assert_eq!(btc.trades, 2);
+        assert_eq!(btc.profit, dec!(10.0000000001));
+        let eth = &aggregated.stats().assets["ETH"];
+        assert_eq!((eth.successful_trades, eth.failed_trades), (1, 1));
+    }
+
+    #[tokio::test]
+    async fn test_aggregated_stats_flush_on_interval() {
+        let store = MemoryStore::new();
+        let config = WriteBufferConfig { batch_size: 1, stats_flush_interval_ms: 50, ..test_config() };
+        let writer = BufferedWriter::spawn(store.clone(), config);
+
+        writer.enqueue(&make_result()).unwrap();
+        writer.enqueue(&make_result()).unwrap();
+        sleep(Duration::from_millis(150)).await;
+
+        // 两笔交易的统计合并为一次写入
+        assert_eq!(store.count(), 2);
+        assert_eq!(store.stats_writes(), 1);
+        assert_eq!(store.stats().assets["BTC"].trades, 2);
+    }
+
+    #[tokio::test]
+    async fn test_orders_linked_to_written_result() {
+        // 第一次写入失败，重试后订单仍关联到最终的记录ID
+        let store = MemoryStore::with_failures(1);
+        let writer = BufferedWriter::spawn(store.clone(), test_config());
+
+        writer.enqueue(&make_result()).unwrap();
+        writer
+            .enqueue_with_orders(&make_result(), vec![make_order(11, Side::Buy), make_order(12, Side::Sell)])
+            .unwrap();
+        writer.flush().await.unwrap();
+
+        assert_eq!(store.count(), 2);
+        assert_eq!(store.orders(), vec![(2, 11), (2, 12)]);
+    }
+
+    #[tokio::test]
+    async fn test_failed_results_buffered_until_store_recovers() {
+        let store = MemoryStore::new();
+        let config = WriteBufferConfig { retry_period_ms: 60_000, ..test_config() };
+        let writer = BufferedWriter::spawn(store.clone(), config);
+
+        // 数据库不可用时记录进入重试缓冲，不计入失败
+        store.set_down(true);
+        for _ in 0..3 {
+            writer.enqueue(&make_result()).unwrap();
+        }
+        writer.flush().await.unwrap();
+        assert_eq!(store.count(), 0);
+        let metrics = writer.metrics();
+        assert_eq!(metrics.pending_retry, 3);
+        assert_eq!(metrics.failed, 0);
+
+        // 仍不可用时再次写入，新记录排在缓冲之后
+        writer.enqueue(&make_asset_result("ETH", dec!(1), ArbitrageStatus::Completed)).unwrap();
+        writer.flush().await.unwrap();
+        assert_eq!(writer.metrics().pending_retry, 4);
+
+        // 恢复后按原顺序补写
+        store.set_down(false);
+        writer.flush().await.unwrap();
+        assert_eq!(store.count(), 4);
+        assert_eq!(store.records()[3].base_asset, "ETH");
+        let metrics = writer.metrics();
+        assert_eq!(metrics.pending_retry, 0);
+        assert_eq!(metrics.written, 4);
+        assert_eq!(metrics.failed, 0);
+    }
+
+    #[tokio::test]
+    async fn test_buffered_results_retried_on_interval() {
+        let store = MemoryStore::new();
+        let config = WriteBufferConfig { flush_interval_ms: 20, retry_period_ms: 60_000, ..test_config() };
+        let writer = BufferedWriter::spawn(store.clone(), config);
+
+        store.set_down(true);
+        writer.enqueue(&make_result()).unwrap();
+        writer.flush().await.unwrap();
+        assert_eq!(writer.metrics().pending_retry, 1);
+
+        // 无新记录时也按写入间隔重试
+        store.set_down(false);
+        sleep(Duration::from_millis(100)).await;
+        assert_eq!(store.count(), 1);
+        assert_eq!(writer.metrics().pending_retry, 0);
+    }
+
+    #[tokio::test]
+    async fn test_buffered_results_dropped_after_retry_period() {
+        let store = MemoryStore::new();
+        let config = WriteBufferConfig { retry_period_ms: 30, ..test_config() };
+        let writer = BufferedWriter::spawn(store.clone(), config);
+
+        store.set_down(true);
+        writer.enqueue(&make_result()).unwrap();
+        writer.enqueue(&make_result()).unwrap();
+        writer.flush().await.unwrap();
+        assert_eq!(writer.metrics().pending_retry, 2);
+
+        sleep(Duration::from_millis(50)).await;
+        store.set_down(false);
+        writer.flush().await.unwrap();
+
+        assert_eq!(store.count(), 0);
+        let metrics = writer.metrics();
+        assert_eq!(metrics.pending_retry, 0);
+        assert_eq!(metrics.failed, 2);
+    }
+
+    #[tokio::test]
+    async fn test_retry_buffer_drops_oldest_when_full() {
+        let store = MemoryStore::new();
+        let config = WriteBufferConfig { retry_period_ms: 60_000, retry_buffer_capacity: 2, ..test_config() };
+        let writer = BufferedWriter::spawn(store.clone(), config);
+
+        store.set_down(true);
+        writer.enqueue(&make_asset_result("BTC", dec!(1), ArbitrageStatus::Completed)).unwrap();
+        writer.enqueue(&make_asset_result("ETH", dec!(1), ArbitrageStatus::Completed)).unwrap();
+        writer.enqueue(&make_asset_result("BNB", dec!(1), ArbitrageStatus::Completed)).unwrap();
+        writer.flush().await.unwrap();
+        let metrics = writer.metrics();
+        assert_eq!(metrics.pending_retry, 2);
+        assert_eq!(metrics.failed, 1);
+
+        store.set_down(false);
+        writer.flush().await.unwrap();
+        let assets: Vec<String> = store.records().iter().map(|r| r.base_asset.clone()).collect();
+        assert_eq!(assets, vec!["ETH", "BNB"]);
+    }
+
+    #[tokio::test]
+    async fn test_health_check_tracks_failures_and_recovery() {
+        let store = MemoryStore::new();
+        let db = DatabaseManager::from_store(store.clone());
+        assert!(db.health().healthy);
+        assert!(db.health().checked_at.is_none());
+
+        store.set_down(true);
+        db.health_check().await;
+        let health = db.health_check().await;
+        assert!(!health.healthy);
+        assert_eq!(health.consecutive_failures, 2);
+        assert!(health.error.is_some());
+
+        store.set_down(false);
+        let health = db.health_check().await;
+        assert!(health.healthy);
+        assert_eq!(health.consecutive_failures, 0);
+        assert!(health.error.is_none());
+        assert!(health.latency_ms.is_some());
+        assert_eq!(db.health().checked_at, health.checked_at);
+    }
+}
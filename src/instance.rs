@@ -0,0 +1,210 @@
+//! 单实例运行：PID文件和排他锁
+//!
+//! 两个实例使用同一个账户交易会使风险敞口翻倍，也会绕过交易频率限制。启动时在PID文件上加排他的建议锁（flock），
+//! 锁随进程存在，进程退出（包括崩溃）时由系统释放；另一个实例持有锁时拒绝启动。正常退出时删除PID文件。
+
+use anyhow::{anyhow, Context, Result};
+use fs2::FileExt;
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Seek, Write};
+use std::path::{Path, PathBuf};
+
+/// 持有PID文件排他锁的守卫，丢弃时删除PID文件并释放锁
+#[derive(Debug)]
+pub struct PidFile {
+    path: PathBuf,
+    /// 锁跟随打开的文件，关闭文件即释放
+    file: File,
+}
+
+impl PidFile {
+    /// 锁定PID文件并写入当前进程的PID
+    ///
+    /// 另一个实例持有锁时返回错误，错误信息中包含该实例的PID。
+    pub fn acquire(path: &Path) -> Result<Self> {
+        // 不截断：加锁失败时不能清掉正在运行的实例写入的PID
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)
+            .with_context(|| format!("无法打开PID文件: {:?}", path))?;
+
+        if let Err(e) = FileExt::try_lock_exclusive(&file) {
+            if e.kind() != fs2::lock_contended_error().kind() {
+                return Err(anyhow::Error::new(e).context(format!("无法锁定PID文件: {:?}", path)));
+            }
+            let running = match read_pid(&mut file) {
+                Some(pid) => format!("PID {}", pid),
+                None => "PID 未知".to_string(),
+            };
+            return Err(anyhow!(
+                "另一个实例正在运行 ({})，PID文件: {:?}；同一账户只能运行一个实例",
+                running,
+                path
+            ));
+        }
+
+        file.set_len(0)?;
+        file.rewind()?;
+        writeln!(file, "{}", std::process::id())?;
+        file.sync_all()?;
+        Ok(Self { path: path.to_path_buf(), file })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for PidFile {
+    fn drop(&mut self) {
+        // 在持有锁时删除，且只删除仍然写着本进程PID的文件：路径已被其他实例的PID文件替换时保留，
+        // 否则会删掉正在运行的实例的PID文件
+        let ours = fs::read_to_string(&self.path)
+            .ok()
+            .and_then(|contents| contents.trim().parse::<u32>().ok())
+            == Some(std::process::id());
+        if ours {
+            if let Err(e) = fs::remove_file(&self.path) {
+                log::warn!("删除PID文件失败 {:?}: {}", self.path, e);
+            }
+        } else {
+            log::warn!("PID文件 {:?} 已不属于本进程，不删除", self.path);
+        }
+        let _ = FileExt::unlock(&self.file);
+    }
+}
+
+/// 读取文件中的PID，内容不是PID时返回None
+fn read_pid(file: &mut File) -> Option<u32> {
+    let mut contents = String::new();
+    file.rewind().ok()?;
+    file.read_to_string(&mut contents).ok()?;
+    contents.trim().parse().ok()
+}
+
+/// 按PID文件判断的实例运行状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InstanceStatus {
+    /// 有实例持有PID文件的锁
+    Running { pid: Option<u32> },
+    /// PID文件不存在
+    NotRunning,
+    /// PID文件存在但没有实例持有锁，通常是上次没有正常退出
+    Stale { pid: Option<u32> },
+}
+
+impl InstanceStatus {
+    /// 检查PID文件上是否有实例持有锁
+    ///
+    /// 检查时短暂加共享锁，恰好在这一刻启动的实例会加锁失败，重新启动即可。
+    pub fn check(path: &Path) -> Result<Self> {
+        let mut file = match File::open(path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(InstanceStatus::NotRunning),
+            Err(e) => return Err(anyhow::Error::new(e).context(format!("无法打开PID文件: {:?}", path))),
+        };
+        let pid = read_pid(&mut file);
+        match FileExt::try_lock_shared(&file) {
+            Ok(()) => {
+                let _ = FileExt::unlock(&file);
+                Ok(InstanceStatus::Stale { pid })
+            },
+            Err(e) if e.kind() == fs2::lock_contended_error().kind() => Ok(InstanceStatus::Running { pid }),
+            Err(e) => Err(anyhow::Error::new(e).context(format!("无法检查PID文件的锁: {:?}", path))),
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        matches!(self, InstanceStatus::Running { .. })
+    }
+}
+
+impl fmt::Display for InstanceStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let pid = |pid: &Option<u32>| pid.map_or_else(|| "未知".to_string(), |pid| pid.to_string());
+        match self {
+            InstanceStatus::Running { pid: running } => write!(f, "运行中 (PID {})", pid(running)),
+            InstanceStatus::NotRunning => write!(f, "未运行"),
+            InstanceStatus::Stale { pid: stale } => {
+                write!(f, "未运行 (PID文件残留，记录的PID {}，上次可能没有正常退出)", pid(stale))
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_pid_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("arbitrage_{}_{}.pid", name, std::process::id()));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn test_second_instance_refused_while_lock_held() {
+        let path = temp_pid_path("lock");
+        assert_eq!(InstanceStatus::check(&path).unwrap(), InstanceStatus::NotRunning);
+
+        let first = PidFile::acquire(&path).unwrap();
+        let pid = std::process::id();
+        assert_eq!(fs::read_to_string(&path).unwrap(), format!("{}\n", pid));
+
+        // 第二个实例在另一个线程中启动，独立打开的文件与第一个实例的锁冲突
+        let second = {
+            let path = path.clone();
+            std::thread::spawn(move || PidFile::acquire(&path)).join().unwrap()
+        };
+        let message = second.unwrap_err().to_string();
+        assert!(message.contains("另一个实例正在运行"), "{}", message);
+        assert!(message.contains(&format!("PID {}", pid)));
+        // 加锁失败没有改写第一个实例的PID
+        assert_eq!(fs::read_to_string(&path).unwrap(), format!("{}\n", pid));
+
+        let status = InstanceStatus::check(&path).unwrap();
+        assert_eq!(status, InstanceStatus::Running { pid: Some(pid) });
+        assert_eq!(status.to_string(), format!("运行中 (PID {})", pid));
+
+        // 正常退出后删除PID文件，可以再次启动
+        drop(first);
+        assert!(!path.exists());
+        assert!(!InstanceStatus::check(&path).unwrap().is_running());
+        let again = PidFile::acquire(&path).unwrap();
+        assert_eq!(again.path(), path.as_path());
+    }
+
+    #[test]
+    fn test_stale_pid_file_does_not_block_start() {
+        let path = temp_pid_path("stale");
+        fs::write(&path, "999999\n").unwrap();
+
+        let status = InstanceStatus::check(&path).unwrap();
+        assert_eq!(status, InstanceStatus::Stale { pid: Some(999999) });
+        assert!(!status.is_running());
+        assert!(status.to_string().contains("PID文件残留"));
+
+        // 没有实例持有锁，覆盖残留的PID
+        let pid_file = PidFile::acquire(&path).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), format!("{}\n", std::process::id()));
+        drop(pid_file);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_replaced_pid_file_kept_on_drop() {
+        let path = temp_pid_path("replaced");
+        let pid_file = PidFile::acquire(&path).unwrap();
+
+        // PID文件被删除后另一个实例创建了新的PID文件
+        fs::remove_file(&path).unwrap();
+        fs::write(&path, "999999\n").unwrap();
+        drop(pid_file);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "999999\n");
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -9,8 +9,15 @@
 //! - `ArbitrageEngine`: 套利引擎，实现套利逻辑
 //! - `BinanceApi`: 币安API客户端，用于与币安交易所通信
 //! - `MockBinanceApi`: 模拟API客户端，用于测试和开发
-//! - `DatabaseManager`: 数据库管理器，用于存储和检索套利历史记录
+//! - `DatabaseManager`: 数据库管理器，用于存储和检索套利历史记录（支持MySQL、SQLite和PostgreSQL）
 //! - `AnalyticsManager`: 分析管理器，用于生成套利绩效报告和统计数据
+//! - `run_simulation`: 使用模拟数据运行套利引擎并生成本次模拟的绩效报告
+//! - `PriceCache`: 引擎每轮获取的行情缓存，与风控组件共享
+//! - `admin`: 运行中引擎的REST管理接口（`admin-api` 特性）
+//! - `Metrics`: 引擎更新的运行指标，`metrics` 特性提供Prometheus导出接口
+//! - `logging`: 文本或JSON格式的日志输出，可以写入按天滚动的文件
+//! - `instance`: PID文件和排他锁，保证同一账户只运行一个实例
+//! - `tui`: 实盘和模拟模式的终端仪表盘（`tui` 特性）
 
 pub mod arbitrage;
 pub mod binance;
@@ -20,14 +27,32 @@ pub mod strategies;
 pub mod risk;
 pub mod db;
 pub mod analytics;
+pub mod simulation;
+pub mod market_data;
+pub mod metrics;
+pub mod logging;
+pub mod instance;
+#[cfg(feature = "admin-api")]
+pub mod admin;
+#[cfg(feature = "tui")]
+pub mod tui;
 
 // 重导出主要类型
-pub use arbitrage::ArbitrageEngine;
-pub use binance::{BinanceApi, ExchangeApi, MockBinanceApi};
-pub use config::Config;
+pub use arbitrage::{watch_config_file, ArbitrageEngine, BalanceLedger, DustOutcome, DustSweep, EngineStatus, LiquidationReport, ProfitDistributionState, RuntimeSettings, SessionLimits, SessionStats, SessionSummary, SettingsUpdate, ShutdownSignal};
+pub use binance::{ApiLatencyStats, BinanceApi, ExchangeApi, LatencyTrackedApi, LatencyTracker, MockBinanceApi, UserDataStream};
+pub use config::{AdminApiSettings, ApiLatencySettings, Config, ConfigError};
 pub use models::{
-    ArbitrageOpportunity, ArbitrageResult, ArbitrageStatus, 
-    OrderBook, OrderInfo, OrderStatus, Price, QuoteCurrency, Side, Symbol,
+    ArbitrageOpportunity, ArbitrageResult, ArbitrageStatus, DepthFill, DustConversion, FillInfo,
+    OrderBook, OrderInfo, OrderStatus, Price, PriceSource, QuoteCurrency, Side, SubAccountTransfer, Symbol, SystemStatus, TimeInForce, TradingMode, UserDataEvent,
 };
-pub use db::{DatabaseManager, TradeStats, DailyStats, AssetStats};
-pub use analytics::{AnalyticsManager, PerformanceReport, TimeRange};
+pub use db::{DatabaseManager, DatabaseOptions, DatabaseHealth, TradeStore, TradeRecord, OrderRecord, TradeHistoryFilter, HistoryCursor, HistoryPage, TradeStats, DailyStats, HourlyStats, AssetStats, AssetStatsQuery, AssetSortBy, AssetDailyStats, StrategyStats, FeeSlippageStats, RejectedOpportunity, RejectionStats, RejectionReasonStats, RejectionHourStats, StatsDelta, StatsDeltas, WriteBufferConfig, WriteMetricsSnapshot, HistoryFormat, ImportSummary};
+#[cfg(feature = "db-mysql")]
+pub use db::MySqlTradeStore;
+#[cfg(feature = "db-sqlite")]
+pub use db::SqliteTradeStore;
+#[cfg(feature = "db-postgres")]
+pub use db::PostgresTradeStore;
+pub use analytics::{AnalyticsManager, DurationStats, ExportFormat, FeeScenario, Locale, PerformanceReport, RiskMetrics, TimeRange};
+pub use simulation::{run_simulation, SimulationSettings};
+pub use market_data::{CachedPrice, PriceCache};
+pub use metrics::Metrics;
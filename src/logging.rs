@@ -0,0 +1,248 @@
+//! 日志输出设置
+//!
+//! 文本格式适合在终端查看；JSON格式每行一个事件，事件的字段（`asset`、`profit`、`strategy`、`reason` 等）
+//! 作为顶层键输出，可以在Loki等日志系统中直接查询。指定日志文件时按天滚动，不输出到终端。
+//! 终端仪表盘占用整个终端，运行期间日志只保留在内存中的 [`LogTail`]，由仪表盘显示最近的几行。
+
+use anyhow::{anyhow, Context, Result};
+use std::collections::VecDeque;
+use std::fmt;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use tracing::{Level, Subscriber};
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::util::SubscriberInitExt;
+
+/// 日志输出格式
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LogFormat {
+    /// 便于阅读的单行文本，事件字段以 `key=value` 附在消息后
+    #[default]
+    Text,
+    /// JSON Lines，每行一个事件
+    Json,
+}
+
+impl FromStr for LogFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "text" => Ok(LogFormat::Text),
+            "json" => Ok(LogFormat::Json),
+            _ => Err(anyhow!("不支持的日志格式: {}，应为 text 或 json", s)),
+        }
+    }
+}
+
+impl fmt::Display for LogFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LogFormat::Text => write!(f, "text"),
+            LogFormat::Json => write!(f, "json"),
+        }
+    }
+}
+
+/// 创建按指定格式写入 `writer` 的日志订阅者
+///
+/// `ansi` 为false时文本格式不输出颜色控制字符，写入文件时使用。
+pub fn subscriber<W>(level: Level, format: LogFormat, writer: W, ansi: bool) -> Box<dyn Subscriber + Send + Sync>
+where
+    W: for<'a> MakeWriter<'a> + Send + Sync + 'static,
+{
+    let builder = tracing_subscriber::fmt().with_max_level(level).with_writer(writer);
+    match format {
+        LogFormat::Text => Box::new(builder.with_ansi(ansi).finish()),
+        LogFormat::Json => Box::new(builder.json().flatten_event(true).with_current_span(false).finish()),
+    }
+}
+
+/// 设置全局日志订阅者，同时接收 `log` 宏输出的日志
+///
+/// 指定 `file` 时写入按天滚动的文件（`<文件名>.YYYY-MM-DD`），返回的guard需要保持到程序退出，
+/// 丢弃时写完缓冲中的日志；否则输出到标准输出。
+pub fn init(level: Level, format: LogFormat, file: Option<&Path>) -> Result<Option<WorkerGuard>> {
+    let Some(path) = file else {
+        subscriber(level, format, std::io::stdout, true).try_init()?;
+        return Ok(None);
+    };
+
+    let file_name = path
+        .file_name()
+        .with_context(|| format!("日志文件路径无效: {}", path.display()))?;
+    let directory = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    std::fs::create_dir_all(directory).with_context(|| format!("无法创建日志目录: {}", directory.display()))?;
+
+    let (writer, guard) = tracing_appender::non_blocking(tracing_appender::rolling::daily(directory, file_name));
+    subscriber(level, format, writer, false).try_init()?;
+    Ok(Some(guard))
+}
+
+/// 设置全局日志订阅者，日志只写入内存中的 `tail`，不输出到终端
+pub fn init_tail(level: Level, format: LogFormat, tail: &LogTail) -> Result<()> {
+    let tail = tail.clone();
+    subscriber(level, format, move || tail.clone(), false).try_init()?;
+    Ok(())
+}
+
+/// 内存中最近的日志行，超过容量时丢弃最早的行
+#[derive(Debug, Clone)]
+pub struct LogTail {
+    inner: Arc<Mutex<LogTailBuffer>>,
+}
+
+#[derive(Debug)]
+struct LogTailBuffer {
+    lines: VecDeque<String>,
+    /// 还没有写完的一行
+    partial: String,
+    capacity: usize,
+}
+
+impl LogTail {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(LogTailBuffer {
+                lines: VecDeque::with_capacity(capacity),
+                partial: String::new(),
+                capacity: capacity.max(1),
+            })),
+        }
+    }
+
+    /// 保留的日志行，从早到晚排列
+    pub fn lines(&self) -> Vec<String> {
+        self.inner.lock().unwrap().lines.iter().cloned().collect()
+    }
+}
+
+impl io::Write for LogTail {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut inner = self.inner.lock().unwrap();
+        inner.partial.push_str(&String::from_utf8_lossy(buf));
+        while let Some(end) = inner.partial.find('\n') {
+            let line: String = inner.partial.drain(..=end).collect();
+            if inner.lines.len() == inner.capacity {
+                inner.lines.pop_front();
+            }
+            inner.lines.push_back(line.trim_end().to_string());
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// 写入内存的日志，测试中用来检查输出的事件和字段
+#[cfg(test)]
+#[derive(Clone, Default)]
+pub(crate) struct CapturedLogs(std::sync::Arc<std::sync::Mutex<Vec<u8>>>);
+
+#[cfg(test)]
+impl std::io::Write for CapturedLogs {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+impl CapturedLogs {
+    /// 写入这里的INFO及以上级别的订阅者
+    pub(crate) fn subscriber(&self, format: LogFormat) -> Box<dyn Subscriber + Send + Sync> {
+        let logs = self.clone();
+        subscriber(Level::INFO, format, move || logs.clone(), false)
+    }
+
+    pub(crate) fn output(&self) -> String {
+        String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+    }
+
+    /// JSON格式输出的全部事件
+    pub(crate) fn json_events(&self) -> Vec<serde_json::Value> {
+        self.output().lines().map(|line| serde_json::from_str(line).unwrap()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn capture(format: LogFormat, emit: impl FnOnce()) -> CapturedLogs {
+        let logs = CapturedLogs::default();
+        tracing::subscriber::with_default(logs.subscriber(format), emit);
+        logs
+    }
+
+    #[test]
+    fn test_parse_log_format() {
+        assert_eq!(LogFormat::from_str(" JSON ").unwrap(), LogFormat::Json);
+        assert_eq!(LogFormat::from_str("text").unwrap(), LogFormat::Text);
+        assert!(LogFormat::from_str("xml").is_err());
+        assert_eq!(LogFormat::Json.to_string(), "json");
+    }
+
+    #[test]
+    fn test_json_events_have_top_level_fields() {
+        let events = capture(LogFormat::Json, || {
+            tracing::info!(asset = %"BTC", profit = %"1.25", strategy = %"简单价格差异套利", "套利结束");
+            tracing::debug!(asset = %"ETH", "低于日志级别");
+        })
+        .json_events();
+
+        assert_eq!(events.len(), 1);
+        let event = &events[0];
+        assert_eq!(event["level"], "INFO");
+        assert_eq!(event["message"], "套利结束");
+        assert_eq!(event["asset"], "BTC");
+        assert_eq!(event["profit"], "1.25");
+        assert_eq!(event["strategy"], "简单价格差异套利");
+    }
+
+    #[test]
+    fn test_text_events_append_fields() {
+        let output = capture(LogFormat::Text, || {
+            tracing::warn!(asset = %"BTC", reason = %"blacklisted", "风控拒绝");
+        })
+        .output();
+
+        assert!(output.contains("WARN"));
+        assert!(output.contains("风控拒绝 asset=BTC reason=blacklisted"));
+        assert!(!output.contains('\u{1b}'));
+    }
+
+    #[test]
+    fn test_log_tail_keeps_latest_lines() {
+        use std::io::Write;
+
+        let tail = LogTail::new(2);
+        let mut writer = tail.clone();
+        writer.write_all(b"first\nsecond\nthi").unwrap();
+        // 没有写完的行不显示
+        assert_eq!(tail.lines(), vec!["first", "second"]);
+        writer.write_all(b"rd\n").unwrap();
+        assert_eq!(tail.lines(), vec!["second", "third"]);
+
+        let subscriber = {
+            let tail = tail.clone();
+            subscriber(Level::INFO, LogFormat::Text, move || tail.clone(), false)
+        };
+        tracing::subscriber::with_default(subscriber, || tracing::info!(asset = %"BTC", "套利结束"));
+        let lines = tail.lines();
+        assert_eq!(lines[0], "third");
+        assert!(lines[1].ends_with("套利结束 asset=BTC"), "{}", lines[1]);
+    }
+}
@@ -0,0 +1,298 @@
+//! 行情缓存，保存引擎每轮获取的最新价格，供风控组件等共享使用
+//!
+//! 引擎每次检查套利机会时都会写入行情，不论该轮是否发现机会、机会是否通过策略验证，
+//! 因此依赖价格历史的组件可以看到全部行情，而不只是到达它们的套利机会。
+
+use crate::config::PriceCacheSettings;
+use crate::models::Price;
+use chrono::{DateTime, Duration, Utc};
+use log::debug;
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::RwLock;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// 缓存中的一条行情
+#[derive(Debug, Clone, PartialEq)]
+pub struct CachedPrice {
+    /// 行情
+    pub price: Price,
+    /// 写入缓存的本地时间，过期判断使用该时间而不是交易所时间戳
+    pub received_at: DateTime<Utc>,
+}
+
+/// 行情缓存的内存占用
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct PriceCacheUsage {
+    /// 缓存的交易对数量
+    pub symbols: usize,
+    /// 所有交易对的行情总条数
+    pub records: usize,
+    /// 行情总条数上限，为None时不限制
+    pub max_total_records: Option<usize>,
+    /// 估算的内存占用（字节），包括每条行情的交易对名称
+    pub estimated_bytes: usize,
+}
+
+/// 一个交易对的行情及其最近一次读写的顺序号
+#[derive(Debug, Default)]
+struct SymbolTicks {
+    ticks: VecDeque<CachedPrice>,
+    last_used: AtomicU64,
+}
+
+/// 按交易对保存最近行情的缓存，可在多个任务间共享（`Arc<PriceCache>`）
+#[derive(Debug)]
+pub struct PriceCache {
+    /// 每个交易对保留的行情数量
+    capacity: usize,
+    /// 行情的最长保留时间，为None时只按数量淘汰
+    max_age: Option<Duration>,
+    /// 所有交易对合计的行情条数上限，超出时淘汰最久未读写的交易对，为None时不限制
+    max_total_records: Option<usize>,
+    ticks: RwLock<HashMap<String, SymbolTicks>>,
+    /// 读写交易对时递增的顺序号，用于找出最久未使用的交易对
+    clock: AtomicU64,
+}
+
+impl PriceCache {
+    /// 创建缓存，每个交易对最多保留 `capacity` 条行情（至少为1）
+    pub fn new(capacity: usize, max_age: Option<Duration>) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            max_age,
+            max_total_records: None,
+            ticks: RwLock::new(HashMap::new()),
+            clock: AtomicU64::new(0),
+        }
+    }
+
+    /// 限制所有交易对合计的行情条数（至少为1）
+    ///
+    /// 超出时按交易对淘汰最久未读写的交易对的全部行情；只剩正在写入的交易对时淘汰它最早的行情。
+    pub fn with_max_total_records(mut self, max_total_records: usize) -> Self {
+        self.max_total_records = Some(max_total_records.max(1));
+        self
+    }
+
+    /// 按配置创建缓存，`max_age_seconds` 为0时不按时间淘汰，`max_total_records` 为0时不限制总条数
+    pub fn from_settings(settings: &PriceCacheSettings) -> Self {
+        let max_age = (settings.max_age_seconds > 0).then(|| Duration::seconds(settings.max_age_seconds as i64));
+        let cache = Self::new(settings.capacity_per_symbol, max_age);
+        if settings.max_total_records > 0 {
+            cache.with_max_total_records(settings.max_total_records)
+        } else {
+            cache
+        }
+    }
+
+    /// 每个交易对保留的行情数量
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// 写入一条行情，超出容量时淘汰该交易对最早的行情
+    pub fn record(&self, price: &Price) {
+        self.record_at(price, Utc::now());
+    }
+
+    pub(crate) fn record_at(&self, price: &Price, received_at: DateTime<Utc>) {
+        let mut ticks = self.ticks.write().unwrap();
+        let entry = ticks.entry(price.symbol.clone()).or_default();
+        entry.ticks.push_back(CachedPrice { price: price.clone(), received_at });
+        while entry.ticks.len() > self.capacity {
+            entry.ticks.pop_front();
+        }
+        self.touch(entry);
+        self.enforce_total_limit(&mut ticks, &price.symbol);
+    }
+
+    /// 行情总条数超过上限时淘汰最久未使用的其他交易对，只剩 `current` 时淘汰它最早的行情
+    fn enforce_total_limit(&self, ticks: &mut HashMap<String, SymbolTicks>, current: &str) {
+        let Some(max_total_records) = self.max_total_records else {
+            return;
+        };
+        let mut total: usize = ticks.values().map(|entry| entry.ticks.len()).sum();
+        while total > max_total_records {
+            let least_recent = ticks
+                .iter()
+                .filter(|(symbol, _)| symbol.as_str() != current)
+                .min_by_key(|(_, entry)| entry.last_used.load(Ordering::Relaxed))
+                .map(|(symbol, _)| symbol.clone());
+            match least_recent {
+                Some(symbol) => {
+                    if let Some(evicted) = ticks.remove(&symbol) {
+                        debug!("行情缓存超过 {} 条，淘汰最久未使用的 {} 的 {} 条行情", max_total_records, symbol, evicted.ticks.len());
+                        total -= evicted.ticks.len();
+                    }
+                }
+                None => {
+                    if let Some(entry) = ticks.get_mut(current) {
+                        entry.ticks.drain(..total - max_total_records);
+                    }
+                    total = max_total_records;
+                }
+            }
+        }
+    }
+
+    /// 记录交易对被读写
+    fn touch(&self, entry: &SymbolTicks) {
+        entry.last_used.store(self.clock.fetch_add(1, Ordering::Relaxed) + 1, Ordering::Relaxed);
+    }
+
+    /// 交易对的最新行情，已过期时返回None
+    pub fn latest(&self, symbol: &str) -> Option<CachedPrice> {
+        let ticks = self.ticks.read().unwrap();
+        let entry = ticks.get(symbol)?;
+        self.touch(entry);
+        entry.ticks.back().filter(|tick| !self.is_expired(tick, Utc::now())).cloned()
+    }
+
+    /// 交易对最近的行情，按写入时间升序，不包含过期和早于 `since` 的行情
+    pub fn history(&self, symbol: &str, since: Option<DateTime<Utc>>) -> Vec<CachedPrice> {
+        let now = Utc::now();
+        let ticks = self.ticks.read().unwrap();
+        let Some(entry) = ticks.get(symbol) else {
+            return Vec::new();
+        };
+        self.touch(entry);
+        entry
+            .ticks
+            .iter()
+            .filter(|tick| !self.is_expired(tick, now))
+            .filter(|tick| since.is_none_or(|since| tick.received_at >= since))
+            .cloned()
+            .collect()
+    }
+
+    /// 当前的行情条数和估算的内存占用
+    pub fn usage(&self) -> PriceCacheUsage {
+        let ticks = self.ticks.read().unwrap();
+        let records = ticks.values().map(|entry| entry.ticks.len()).sum();
+        let estimated_bytes = ticks
+            .iter()
+            .map(|(symbol, entry)| {
+                let per_record = std::mem::size_of::<CachedPrice>() + symbol.len();
+                symbol.len() + std::mem::size_of::<SymbolTicks>() + entry.ticks.len() * per_record
+            })
+            .sum();
+        PriceCacheUsage {
+            symbols: ticks.len(),
+            records,
+            max_total_records: self.max_total_records,
+            estimated_bytes,
+        }
+    }
+
+    /// 清空全部行情
+    pub fn clear(&self) {
+        self.ticks.write().unwrap().clear();
+    }
+
+    fn is_expired(&self, tick: &CachedPrice, now: DateTime<Utc>) -> bool {
+        self.max_age.is_some_and(|max_age| now - tick.received_at > max_age)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal::Decimal;
+    use rust_decimal_macros::dec;
+    use std::sync::Arc;
+
+    fn price(symbol: &str, value: Decimal) -> Price {
+        Price { symbol: symbol.to_string(), price: value, timestamp: Utc::now() }
+    }
+
+    #[test]
+    fn test_capacity_per_symbol() {
+        let cache = PriceCache::new(3, None);
+        for value in [dec!(1), dec!(2), dec!(3), dec!(4)] {
+            cache.record(&price("BTCUSDT", value));
+        }
+        cache.record(&price("BTCUSDC", dec!(10)));
+
+        let values: Vec<Decimal> = cache.history("BTCUSDT", None).iter().map(|tick| tick.price.price).collect();
+        assert_eq!(values, vec![dec!(2), dec!(3), dec!(4)]);
+        assert_eq!(cache.latest("BTCUSDC").unwrap().price.price, dec!(10));
+        assert!(cache.latest("ETHUSDT").is_none());
+
+        cache.clear();
+        assert!(cache.history("BTCUSDT", None).is_empty());
+    }
+
+    #[test]
+    fn test_expired_and_since_filters() {
+        let cache = PriceCache::new(10, Some(Duration::seconds(60)));
+        let now = Utc::now();
+        cache.record_at(&price("BTCUSDT", dec!(1)), now - Duration::seconds(120));
+        cache.record_at(&price("BTCUSDT", dec!(2)), now - Duration::seconds(30));
+        cache.record_at(&price("BTCUSDT", dec!(3)), now);
+
+        let values: Vec<Decimal> = cache.history("BTCUSDT", None).iter().map(|tick| tick.price.price).collect();
+        assert_eq!(values, vec![dec!(2), dec!(3)]);
+
+        let recent = cache.history("BTCUSDT", Some(now - Duration::seconds(10)));
+        assert_eq!(recent.len(), 1);
+        assert_eq!(recent[0].price.price, dec!(3));
+
+        let stale = PriceCache::new(10, Some(Duration::seconds(60)));
+        stale.record_at(&price("BTCUSDT", dec!(1)), now - Duration::seconds(120));
+        assert!(stale.latest("BTCUSDT").is_none());
+    }
+
+    #[test]
+    fn test_total_limit_evicts_least_recently_used_symbol() {
+        let cache = PriceCache::new(3, None).with_max_total_records(5);
+        cache.record(&price("BTCUSDT", dec!(1)));
+        cache.record(&price("BTCUSDT", dec!(2)));
+        cache.record(&price("ETHUSDT", dec!(3)));
+        cache.record(&price("ETHUSDT", dec!(4)));
+        // 读取BTCUSDT后，ETHUSDT成为最久未使用的交易对
+        assert!(cache.latest("BTCUSDT").is_some());
+        cache.record(&price("BNBUSDT", dec!(5)));
+        assert_eq!(cache.usage().records, 5);
+
+        cache.record(&price("BNBUSDT", dec!(6)));
+        assert!(cache.history("ETHUSDT", None).is_empty());
+        assert_eq!(cache.history("BTCUSDT", None).len(), 2);
+        assert_eq!(cache.history("BNBUSDT", None).len(), 2);
+
+        let usage = cache.usage();
+        assert_eq!((usage.symbols, usage.records, usage.max_total_records), (2, 4, Some(5)));
+        assert!(usage.estimated_bytes >= 4 * std::mem::size_of::<CachedPrice>());
+    }
+
+    #[test]
+    fn test_total_limit_trims_only_symbol() {
+        let cache = PriceCache::new(10, None).with_max_total_records(3);
+        for value in [dec!(1), dec!(2), dec!(3), dec!(4), dec!(5)] {
+            cache.record(&price("BTCUSDT", value));
+        }
+
+        let values: Vec<Decimal> = cache.history("BTCUSDT", None).iter().map(|tick| tick.price.price).collect();
+        assert_eq!(values, vec![dec!(3), dec!(4), dec!(5)]);
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_writers() {
+        let cache = Arc::new(PriceCache::new(1000, None));
+        let tasks: Vec<_> = (0..4)
+            .map(|task| {
+                let cache = cache.clone();
+                tokio::spawn(async move {
+                    for i in 0..100 {
+                        cache.record(&price("BTCUSDT", Decimal::from(task * 100 + i)));
+                    }
+                })
+            })
+            .collect();
+        for task in tasks {
+            task.await.unwrap();
+        }
+
+        assert_eq!(cache.history("BTCUSDT", None).len(), 400);
+    }
+}
@@ -0,0 +1,368 @@
+//! 运行指标，以Prometheus文本格式导出
+//!
+//! 引擎运行时更新共享的 `Metrics`（多个引擎可以共享同一个 `Arc<Metrics>`），
+//! 启用 `metrics` 特性后可以通过 `serve` 在HTTP `/metrics` 接口上导出。
+//!
+//! | 指标 | 类型 | 说明 |
+//! |------|------|------|
+//! | `arbitrage_opportunities_found_total` | counter | 提交风控检查的套利机会数 |
+//! | `arbitrage_opportunities_rejected_total{controller,reason}` | counter | 被风控组件拒绝的次数，`reason` 为拒绝原因分类（如 `frequency_limited`） |
+//! | `arbitrage_trades_completed_total` | counter | 完成的套利次数 |
+//! | `arbitrage_trades_failed_total` | counter | 失败的套利次数 |
+//! | `arbitrage_profit_total` | gauge | 累计利润（报价货币，可能为负） |
+//! | `arbitrage_spread_percentage{asset}` | gauge | 最近一轮USDT与USDC交易对的价差百分比 |
+//! | `arbitrage_api_errors_total{operation}` | counter | 交易所API调用失败次数 |
+//! | `arbitrage_loop_latency_seconds` | histogram | 每轮套利检查的耗时（不含等待间隔） |
+//! | `arbitrage_db_write_failures_total` | counter | 套利结果写入数据库失败的次数 |
+
+use crate::models::RejectionReason;
+use rust_decimal::Decimal;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// 每轮耗时直方图的桶上限（秒）
+const LATENCY_BUCKETS: [f64; 11] = [0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0];
+
+/// 耗时直方图，按Prometheus约定导出累积计数
+#[derive(Debug, Default)]
+struct Histogram {
+    /// 各桶（不累积）的计数，最后一个为超过所有上限的计数
+    buckets: [AtomicU64; LATENCY_BUCKETS.len() + 1],
+    /// 耗时总和（微秒）
+    sum_micros: AtomicU64,
+    count: AtomicU64,
+}
+
+impl Histogram {
+    fn observe(&self, elapsed: Duration) {
+        let seconds = elapsed.as_secs_f64();
+        let index = LATENCY_BUCKETS
+            .iter()
+            .position(|bound| seconds <= *bound)
+            .unwrap_or(LATENCY_BUCKETS.len());
+        self.buckets[index].fetch_add(1, Ordering::Relaxed);
+        self.sum_micros.fetch_add(elapsed.as_micros() as u64, Ordering::Relaxed);
+        self.count.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// 套利运行指标
+#[derive(Debug, Default)]
+pub struct Metrics {
+    opportunities_found: AtomicU64,
+    /// 按风控组件名称和拒绝原因统计的拒绝次数
+    opportunities_rejected: Mutex<BTreeMap<(String, RejectionReason), u64>>,
+    trades_completed: AtomicU64,
+    trades_failed: AtomicU64,
+    profit: Mutex<Decimal>,
+    /// 按基础资产记录的最新价差百分比
+    spreads: Mutex<BTreeMap<String, Decimal>>,
+    /// 按操作统计的API错误次数
+    api_errors: Mutex<BTreeMap<String, u64>>,
+    loop_latency: Histogram,
+    /// 引擎直接观察到的写入失败（例如写入队列已关闭）
+    db_write_failures: AtomicU64,
+    /// 写入队列报告的失败和丢弃记录数，由引擎定期同步
+    db_writer_failures: AtomicU64,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 记录一个提交风控检查的套利机会
+    pub fn record_opportunity(&self) {
+        self.opportunities_found.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// 记录风控组件以 `reason` 拒绝了一个套利机会
+    pub fn record_rejection(&self, controller: &str, reason: RejectionReason) {
+        *self.opportunities_rejected.lock().unwrap().entry((controller.to_string(), reason)).or_default() += 1;
+    }
+
+    /// 记录一次完成的套利及其利润
+    pub fn record_trade_completed(&self, profit: Decimal) {
+        self.trades_completed.fetch_add(1, Ordering::Relaxed);
+        *self.profit.lock().unwrap() += profit;
+    }
+
+    /// 记录一次失败的套利
+    pub fn record_trade_failed(&self) {
+        self.trades_failed.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// 更新基础资产当前的价差百分比
+    pub fn set_spread(&self, asset: &str, spread_percentage: Decimal) {
+        self.spreads.lock().unwrap().insert(asset.to_string(), spread_percentage);
+    }
+
+    /// 记录一次失败的API调用，`operation` 为调用的操作，例如 `get_price`
+    pub fn record_api_error(&self, operation: &str) {
+        *self.api_errors.lock().unwrap().entry(operation.to_string()).or_default() += 1;
+    }
+
+    /// 记录一轮套利检查的耗时
+    pub fn observe_loop_latency(&self, elapsed: Duration) {
+        self.loop_latency.observe(elapsed);
+    }
+
+    /// 记录一次套利结果写入失败
+    pub fn record_db_write_failure(&self) {
+        self.db_write_failures.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// 同步写入队列累计的失败和丢弃记录数
+    pub fn set_db_writer_failures(&self, failures: u64) {
+        self.db_writer_failures.store(failures, Ordering::Relaxed);
+    }
+
+    /// 各风控组件按拒绝原因的拒绝次数，按组件名称和原因排序
+    pub fn rejection_counts(&self) -> Vec<(String, RejectionReason, u64)> {
+        self.opportunities_rejected
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|((controller, reason), count)| (controller.clone(), *reason, *count))
+            .collect()
+    }
+
+    /// 完成的套利次数
+    pub fn trades_completed(&self) -> u64 {
+        self.trades_completed.load(Ordering::Relaxed)
+    }
+
+    /// 以Prometheus文本格式（0.0.4）输出全部指标
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+
+        write_header(&mut out, "arbitrage_opportunities_found_total", "counter", "提交风控检查的套利机会数");
+        let _ = writeln!(out, "arbitrage_opportunities_found_total {}", self.opportunities_found.load(Ordering::Relaxed));
+
+        write_header(&mut out, "arbitrage_opportunities_rejected_total", "counter", "被风控组件拒绝的套利机会数");
+        for ((controller, reason), count) in self.opportunities_rejected.lock().unwrap().iter() {
+            let _ = writeln!(
+                out,
+                "arbitrage_opportunities_rejected_total{{controller=\"{}\",reason=\"{}\"}} {}",
+                escape_label(controller),
+                reason,
+                count
+            );
+        }
+
+        write_header(&mut out, "arbitrage_trades_completed_total", "counter", "完成的套利次数");
+        let _ = writeln!(out, "arbitrage_trades_completed_total {}", self.trades_completed());
+
+        write_header(&mut out, "arbitrage_trades_failed_total", "counter", "失败的套利次数");
+        let _ = writeln!(out, "arbitrage_trades_failed_total {}", self.trades_failed.load(Ordering::Relaxed));
+
+        write_header(&mut out, "arbitrage_profit_total", "gauge", "累计利润（报价货币）");
+        let _ = writeln!(out, "arbitrage_profit_total {}", self.profit.lock().unwrap());
+
+        write_header(&mut out, "arbitrage_spread_percentage", "gauge", "最近一轮USDT与USDC交易对的价差百分比");
+        for (asset, spread) in self.spreads.lock().unwrap().iter() {
+            let _ = writeln!(out, "arbitrage_spread_percentage{{asset=\"{}\"}} {}", escape_label(asset), spread);
+        }
+
+        write_header(&mut out, "arbitrage_api_errors_total", "counter", "交易所API调用失败次数");
+        for (operation, count) in self.api_errors.lock().unwrap().iter() {
+            let _ = writeln!(out, "arbitrage_api_errors_total{{operation=\"{}\"}} {}", escape_label(operation), count);
+        }
+
+        write_header(&mut out, "arbitrage_loop_latency_seconds", "histogram", "每轮套利检查的耗时（不含等待间隔）");
+        let mut cumulative = 0;
+        for (bound, bucket) in LATENCY_BUCKETS.iter().zip(&self.loop_latency.buckets) {
+            cumulative += bucket.load(Ordering::Relaxed);
+            let _ = writeln!(out, "arbitrage_loop_latency_seconds_bucket{{le=\"{}\"}} {}", bound, cumulative);
+        }
+        let count = self.loop_latency.count.load(Ordering::Relaxed);
+        let _ = writeln!(out, "arbitrage_loop_latency_seconds_bucket{{le=\"+Inf\"}} {}", count);
+        let sum = self.loop_latency.sum_micros.load(Ordering::Relaxed) as f64 / 1_000_000.0;
+        let _ = writeln!(out, "arbitrage_loop_latency_seconds_sum {}", sum);
+        let _ = writeln!(out, "arbitrage_loop_latency_seconds_count {}", count);
+
+        write_header(&mut out, "arbitrage_db_write_failures_total", "counter", "套利结果写入数据库失败的次数");
+        let failures = self.db_write_failures.load(Ordering::Relaxed) + self.db_writer_failures.load(Ordering::Relaxed);
+        let _ = writeln!(out, "arbitrage_db_write_failures_total {}", failures);
+
+        out
+    }
+}
+
+fn write_header(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+}
+
+/// 转义标签值中的反斜杠、双引号和换行
+fn escape_label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+#[cfg(feature = "metrics")]
+mod exporter {
+    use super::Metrics;
+    use anyhow::{Context, Result};
+    use axum::extract::State;
+    use axum::http::header;
+    use axum::response::IntoResponse;
+    use axum::routing::get;
+    use axum::Router;
+    use log::info;
+    use std::sync::Arc;
+    use tokio::net::TcpListener;
+
+    /// 创建只包含 `GET /metrics` 的路由
+    pub fn router(metrics: Arc<Metrics>) -> Router {
+        Router::new().route("/metrics", get(get_metrics)).with_state(metrics)
+    }
+
+    /// 在指定地址上导出指标，直到出错才返回
+    pub async fn serve(metrics: Arc<Metrics>, bind_address: &str) -> Result<()> {
+        let listener = TcpListener::bind(bind_address)
+            .await
+            .with_context(|| format!("指标接口无法监听 {}", bind_address))?;
+        serve_listener(metrics, listener).await
+    }
+
+    /// 在已绑定的监听器上导出指标
+    pub async fn serve_listener(metrics: Arc<Metrics>, listener: TcpListener) -> Result<()> {
+        if let Ok(address) = listener.local_addr() {
+            info!("指标接口已启动: http://{}/metrics", address);
+        }
+        axum::serve(listener, router(metrics)).await.context("指标接口运行失败")
+    }
+
+    async fn get_metrics(State(metrics): State<Arc<Metrics>>) -> impl IntoResponse {
+        ([(header::CONTENT_TYPE, "text/plain; version=0.0.4; charset=utf-8")], metrics.render())
+    }
+}
+
+#[cfg(feature = "metrics")]
+pub use exporter::{router, serve, serve_listener};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_render_prometheus_text() {
+        let metrics = Metrics::new();
+        metrics.record_opportunity();
+        metrics.record_opportunity();
+        metrics.record_rejection("每日亏损限制", RejectionReason::DailyLossLimit);
+        metrics.record_trade_completed(dec!(1.5));
+        metrics.record_trade_completed(dec!(-0.5));
+        metrics.record_trade_failed();
+        metrics.set_spread("BTC", dec!(0.05));
+        metrics.record_api_error("get_price");
+        metrics.observe_loop_latency(Duration::from_millis(3));
+        metrics.observe_loop_latency(Duration::from_secs(10));
+        metrics.record_db_write_failure();
+        metrics.set_db_writer_failures(2);
+
+        let text = metrics.render();
+        assert!(text.contains("# TYPE arbitrage_opportunities_found_total counter\narbitrage_opportunities_found_total 2\n"));
+        assert!(text.contains("arbitrage_opportunities_rejected_total{controller=\"每日亏损限制\",reason=\"daily_loss_limit\"} 1\n"));
+        assert!(text.contains("arbitrage_trades_completed_total 2\n"));
+        assert!(text.contains("arbitrage_trades_failed_total 1\n"));
+        assert!(text.contains("arbitrage_profit_total 1.0\n"));
+        assert!(text.contains("arbitrage_spread_percentage{asset=\"BTC\"} 0.05\n"));
+        assert!(text.contains("arbitrage_api_errors_total{operation=\"get_price\"} 1\n"));
+        // 直方图的桶计数是累积的
+        assert!(text.contains("arbitrage_loop_latency_seconds_bucket{le=\"0.001\"} 0\n"));
+        assert!(text.contains("arbitrage_loop_latency_seconds_bucket{le=\"0.005\"} 1\n"));
+        assert!(text.contains("arbitrage_loop_latency_seconds_bucket{le=\"5\"} 1\n"));
+        assert!(text.contains("arbitrage_loop_latency_seconds_bucket{le=\"+Inf\"} 2\n"));
+        assert!(text.contains("arbitrage_loop_latency_seconds_sum 10.003\n"));
+        assert!(text.contains("arbitrage_loop_latency_seconds_count 2\n"));
+        assert!(text.contains("arbitrage_db_write_failures_total 3\n"));
+    }
+
+    #[test]
+    fn test_escape_label() {
+        assert_eq!(escape_label("a\"b\\c\nd"), "a\\\"b\\\\c\\nd");
+    }
+}
+
+#[cfg(all(test, feature = "metrics", feature = "db-sqlite"))]
+mod exporter_tests {
+    use super::*;
+    use crate::config::{ArbitrageSettings, Config, FeeSettings, RiskSettings};
+    use crate::db::DatabaseManager;
+    use crate::simulation::{run_simulation, SimulationSettings};
+    use std::sync::Arc;
+    use tokio::net::TcpListener;
+    use tokio::time::sleep;
+
+    fn test_config() -> Config {
+        Config {
+            arbitrage_settings: ArbitrageSettings {
+                min_profit_percentage: Decimal::ZERO,
+                check_interval_ms: 20,
+                ..ArbitrageSettings::default()
+            },
+            risk_settings: RiskSettings {
+                enabled_controllers: Vec::new(),
+                ..RiskSettings::default()
+            },
+            // 测试行情的价差小于默认手续费，手续费设为0
+            fees: FeeSettings { maker_rate: Decimal::ZERO, taker_rate: Decimal::ZERO, ..FeeSettings::default() },
+            ..Config::default()
+        }
+    }
+
+    async fn scrape(url: &str) -> (String, String) {
+        let response = reqwest::get(url).await.unwrap();
+        assert!(response.status().is_success());
+        let content_type = response.headers()[reqwest::header::CONTENT_TYPE].to_str().unwrap().to_string();
+        (content_type, response.text().await.unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_scrape_metrics_during_simulation() {
+        let metrics = Arc::new(Metrics::new());
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/metrics", listener.local_addr().unwrap());
+        let exporter = tokio::spawn(serve_listener(metrics.clone(), listener));
+
+        let settings = SimulationSettings {
+            runtime: Duration::from_millis(800),
+            volatility: 0.0,
+            opportunity_probability: 100,
+            price_update_interval: Duration::from_millis(20),
+            metrics: Some(metrics.clone()),
+            ..SimulationSettings::default()
+        };
+        let db = DatabaseManager::in_memory().await.unwrap();
+        let simulation = tokio::spawn(async move { run_simulation(test_config(), "BTC", &settings, db).await });
+
+        // 模拟运行期间抓取，直到出现完成的套利
+        let mut body = String::new();
+        for _ in 0..70 {
+            let (content_type, text) = scrape(&url).await;
+            assert!(content_type.starts_with("text/plain; version=0.0.4"));
+            body = text;
+            if metrics.trades_completed() > 0 && body.contains("arbitrage_spread_percentage{asset=\"BTC\"}") {
+                break;
+            }
+            sleep(Duration::from_millis(10)).await;
+        }
+        assert!(!simulation.is_finished(), "应在模拟运行期间抓取到指标");
+        assert!(body.contains("arbitrage_spread_percentage{asset=\"BTC\"}"));
+        assert!(!body.contains("arbitrage_trades_completed_total 0\n"));
+        assert!(!body.contains("arbitrage_loop_latency_seconds_count 0\n"));
+
+        // 模拟结束后仍可抓取最终的指标
+        let report = simulation.await.unwrap().unwrap();
+        assert!(report.overview.total_trades > 0);
+        let (_, body) = scrape(&url).await;
+        assert!(body.contains(&format!("arbitrage_trades_completed_total {}\n", metrics.trades_completed())));
+
+        exporter.abort();
+    }
+}
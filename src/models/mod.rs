@@ -1,10 +1,18 @@
 use rust_decimal::Decimal;
-use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use serde::{Deserialize, Deserializer, Serialize};
 use std::fmt;
+use std::str::FromStr;
 use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+#[cfg(test)]
+pub mod testing;
 
 /// 交易对类型
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+///
+/// 序列化为 `"USDT"`/`"USDC"`，反序列化时不区分大小写。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
 pub enum QuoteCurrency {
     USDT,
     USDC,
@@ -19,6 +27,26 @@ impl fmt::Display for QuoteCurrency {
     }
 }
 
+impl FromStr for QuoteCurrency {
+    type Err = anyhow::Error;
+
+    /// 解析报价货币名称，不区分大小写
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s.trim().to_uppercase().as_str() {
+            "USDT" => Ok(QuoteCurrency::USDT),
+            "USDC" => Ok(QuoteCurrency::USDC),
+            _ => Err(anyhow::anyhow!("未知的报价货币: {}，应为 USDT 或 USDC", s)),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for QuoteCurrency {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
 /// 交易对信息
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Symbol {
@@ -31,7 +59,7 @@ pub struct Symbol {
 }
 
 /// 市场价格
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Price {
     pub symbol: String,
     pub price: Decimal,
@@ -47,6 +75,108 @@ pub struct OrderBook {
     pub timestamp: DateTime<Utc>,
 }
 
+/// 沿订单簿逐档成交的结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DepthFill {
+    /// 成交的基础货币数量
+    pub qty: Decimal,
+    /// 成交金额（报价货币）
+    pub notional: Decimal,
+    /// 成交均价
+    pub avg_price: Decimal,
+}
+
+impl OrderBook {
+    /// 最优买价和最优卖价的中间价，任意一侧为空时返回 None
+    pub fn mid_price(&self) -> Option<Decimal> {
+        let (best_bid, _) = self.bids.first()?;
+        let (best_ask, _) = self.asks.first()?;
+        Some((*best_bid + *best_ask) / Decimal::TWO)
+    }
+
+    /// 用 `notional` 报价货币从卖盘逐档买入，卖盘深度不足以花完全部金额时返回 None
+    pub fn buy_with_notional(&self, notional: Decimal) -> Option<DepthFill> {
+        if notional <= Decimal::ZERO {
+            return None;
+        }
+
+        let mut remaining = notional;
+        let mut qty = Decimal::ZERO;
+
+        for (price, level_qty) in &self.asks {
+            if remaining.is_zero() {
+                break;
+            }
+            if price.is_zero() {
+                continue;
+            }
+
+            let level_notional = *price * *level_qty;
+            if level_notional >= remaining {
+                qty += remaining / *price;
+                remaining = Decimal::ZERO;
+            } else {
+                qty += *level_qty;
+                remaining -= level_notional;
+            }
+        }
+
+        if !remaining.is_zero() {
+            return None;
+        }
+
+        Some(DepthFill { qty, notional, avg_price: notional / qty })
+    }
+
+    /// 按数量向买盘逐档卖出 `qty` 基础货币，买盘深度不足时返回 None
+    pub fn sell_qty(&self, qty: Decimal) -> Option<DepthFill> {
+        if qty <= Decimal::ZERO {
+            return None;
+        }
+
+        let mut remaining = qty;
+        let mut notional = Decimal::ZERO;
+
+        for (price, level_qty) in &self.bids {
+            if remaining.is_zero() {
+                break;
+            }
+
+            let fill = remaining.min(*level_qty);
+            notional += fill * *price;
+            remaining -= fill;
+        }
+
+        if !remaining.is_zero() {
+            return None;
+        }
+
+        Some(DepthFill { qty, notional, avg_price: notional / qty })
+    }
+}
+
+/// 参考价格来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum PriceSource {
+    /// 行情接口的最新价格
+    #[default]
+    Ticker,
+    /// 订单簿最优买卖价的中间价
+    BookMid,
+    /// 最近一笔成交的价格
+    LastTrade,
+}
+
+impl fmt::Display for PriceSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PriceSource::Ticker => write!(f, "Ticker"),
+            PriceSource::BookMid => write!(f, "BookMid"),
+            PriceSource::LastTrade => write!(f, "LastTrade"),
+        }
+    }
+}
+
 /// 订单方向
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Side {
@@ -63,8 +193,79 @@ impl fmt::Display for Side {
     }
 }
 
+impl FromStr for Side {
+    type Err = anyhow::Error;
+
+    /// 解析订单方向（与 `Display` 输出一致）
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "BUY" => Ok(Side::Buy),
+            "SELL" => Ok(Side::Sell),
+            _ => Err(anyhow::anyhow!("未知的订单方向: {}", s)),
+        }
+    }
+}
+
+/// 限价单有效方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum TimeInForce {
+    /// 成交为止 (Good Till Cancel)
+    #[default]
+    GTC,
+    /// 立即成交，未成交部分取消 (Immediate Or Cancel)
+    IOC,
+    /// 全部成交或全部取消 (Fill Or Kill)
+    FOK,
+}
+
+impl fmt::Display for TimeInForce {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TimeInForce::GTC => write!(f, "GTC"),
+            TimeInForce::IOC => write!(f, "IOC"),
+            TimeInForce::FOK => write!(f, "FOK"),
+        }
+    }
+}
+
+/// 套利单边订单的类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum OrderType {
+    /// 市价单，保证成交但可能有滑点
+    Market,
+    /// 限价单，按机会价格下单，可能需要等待成交
+    Limit,
+}
+
+/// 交易所系统状态
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SystemStatus {
+    /// 是否处于系统维护中，维护期间所有下单接口不可用
+    pub maintenance: bool,
+    /// 交易所返回的状态说明
+    pub message: String,
+}
+
+impl SystemStatus {
+    /// 正常状态
+    pub fn normal() -> Self {
+        Self {
+            maintenance: false,
+            message: "normal".to_string(),
+        }
+    }
+
+    /// 维护状态
+    pub fn maintenance(message: &str) -> Self {
+        Self {
+            maintenance: true,
+            message: message.to_string(),
+        }
+    }
+}
+
 /// 套利机会
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ArbitrageOpportunity {
     pub base_asset: String,                 // 基础资产 如 BTC
     pub buy_quote: QuoteCurrency,           // 买入的报价货币 (USDT/USDC)
@@ -75,6 +276,8 @@ pub struct ArbitrageOpportunity {
     pub profit_percentage: Decimal,         // 利润百分比
     pub max_trade_amount: Decimal,          // 最大交易量
     pub timestamp: DateTime<Utc>,           // 时间戳
+    pub strategy: Option<String>,           // 发现该机会的策略名称
+    pub opportunity_id: Uuid,               // 发现机会时生成，关联订单、数据库记录和日志
 }
 
 impl ArbitrageOpportunity {
@@ -86,39 +289,178 @@ impl ArbitrageOpportunity {
         sell_price: Decimal,
         max_trade_amount: Decimal,
     ) -> Self {
-        let price_diff = sell_price - buy_price;
-        let profit_percentage = if buy_price.is_zero() {
-            Decimal::ZERO
-        } else {
-            (price_diff / buy_price) * Decimal::from(100)
-        };
-
-        Self {
+        let mut opportunity = Self {
             base_asset: base_asset.to_string(),
             buy_quote,
             sell_quote,
             buy_price,
             sell_price,
-            price_diff,
-            profit_percentage,
+            price_diff: Decimal::ZERO,
+            profit_percentage: Decimal::ZERO,
             max_trade_amount,
             timestamp: Utc::now(),
-        }
+            strategy: None,
+            opportunity_id: Uuid::new_v4(),
+        };
+        opportunity.set_prices(buy_price, sell_price);
+        opportunity
+    }
+
+    /// 更新买卖价格，并按新价格重新计算价差和未扣除手续费的利润率
+    ///
+    /// 扣除手续费的净利润由 `arbitrage::profit::ProfitCalculator` 计算。
+    pub fn set_prices(&mut self, buy_price: Decimal, sell_price: Decimal) {
+        self.buy_price = buy_price;
+        self.sell_price = sell_price;
+        self.price_diff = sell_price - buy_price;
+        self.profit_percentage = if buy_price.is_zero() {
+            Decimal::ZERO
+        } else {
+            (self.price_diff / buy_price) * Decimal::from(100)
+        };
+    }
+
+    /// 该机会某一侧订单的 `newClientOrderId`，以机会ID为前缀
+    ///
+    /// 币安要求不超过36个字符，因此机会ID使用不带连字符的32位格式。
+    pub fn client_order_id(&self, side: Side) -> String {
+        let suffix = match side {
+            Side::Buy => "b",
+            Side::Sell => "s",
+        };
+        format!("{}-{}", self.opportunity_id.simple(), suffix)
+    }
+
+    /// 该机会回补订单的 `newClientOrderId`，与两侧订单使用相同的机会ID前缀
+    pub fn unwind_client_order_id(&self) -> String {
+        format!("{}-u", self.opportunity_id.simple())
     }
 }
 
 /// 订单信息
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct OrderInfo {
     pub order_id: u64,
     pub symbol: String,
     pub price: Decimal,
     pub qty: Decimal,
+    pub executed_qty: Decimal,          // 已成交数量
+    pub avg_price: Decimal,             // 成交均价，未成交时为0
+    pub commission: Decimal,            // 手续费
+    pub commission_asset: String,       // 手续费币种
     pub side: Side,
     pub status: OrderStatus,
     pub timestamp: DateTime<Utc>,
 }
 
+/// 账户中一个币种的余额
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AssetBalance {
+    /// 币种
+    pub asset: String,
+    /// 可用余额
+    pub free: Decimal,
+    /// 挂单等冻结的余额
+    pub locked: Decimal,
+}
+
+impl AssetBalance {
+    /// 可用和冻结余额之和
+    pub fn total(&self) -> Decimal {
+        self.free + self.locked
+    }
+}
+
+/// 小额资产兑换为BNB的结果
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DustConversion {
+    /// 兑换的币种
+    pub asset: String,
+    /// 兑换的数量
+    pub amount: Decimal,
+    /// 获得的BNB数量（已扣除手续费）
+    pub transferred: Decimal,
+    /// 兑换手续费（BNB）
+    pub service_charge: Decimal,
+}
+
+/// 向子账户划转资产的结果
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SubAccountTransfer {
+    /// 交易所返回的划转ID
+    pub txn_id: String,
+    /// 接收划转的子账户邮箱
+    pub to_email: String,
+    /// 划转的币种
+    pub asset: String,
+    /// 划转的数量
+    pub amount: Decimal,
+}
+
+/// 用户数据流推送的事件
+#[derive(Debug, Clone)]
+pub enum UserDataEvent {
+    /// 订单状态更新（executionReport），成交数量和成交均价为累计值，手续费只包含最近一笔成交
+    ExecutionReport(OrderInfo),
+    /// 其他事件（如余额更新），保存事件类型
+    Other(String),
+}
+
+/// 单边订单的实际成交信息
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FillInfo {
+    /// 实际成交数量
+    pub qty: Decimal,
+    /// 成交均价
+    pub avg_price: Decimal,
+    /// 手续费
+    pub commission: Decimal,
+    /// 手续费币种
+    pub commission_asset: String,
+}
+
+impl FillInfo {
+    /// 从订单信息中提取成交信息
+    pub fn from_order(order: &OrderInfo) -> Self {
+        Self {
+            qty: order.executed_qty,
+            avg_price: order.avg_price,
+            commission: order.commission,
+            commission_asset: order.commission_asset.clone(),
+        }
+    }
+
+    /// 买入成交后实际到账的基础资产数量，扣除以基础资产收取的手续费
+    pub fn received_base_qty(&self, base_asset: &str) -> Decimal {
+        if self.commission_asset == base_asset {
+            self.qty - self.commission
+        } else {
+            self.qty
+        }
+    }
+
+    /// 成交金额（报价货币）
+    pub fn notional(&self) -> Decimal {
+        self.qty * self.avg_price
+    }
+
+    /// 按报价货币计价的手续费
+    ///
+    /// 以基础资产收取的手续费按成交均价折算，以USDT/USDC收取的视为等值报价货币；
+    /// 其他币种（如BNB）无法折算，返回 None。
+    pub fn commission_in_quote(&self, base_asset: &str) -> Option<Decimal> {
+        if self.commission.is_zero() {
+            return Some(Decimal::ZERO);
+        }
+
+        match self.commission_asset.as_str() {
+            asset if asset == base_asset => Some(self.commission * self.avg_price),
+            "USDT" | "USDC" => Some(self.commission),
+            _ => None,
+        }
+    }
+}
+
 /// 订单状态
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum OrderStatus {
@@ -130,6 +472,23 @@ pub enum OrderStatus {
     Expired,
 }
 
+impl FromStr for OrderStatus {
+    type Err = anyhow::Error;
+
+    /// 解析订单状态名称（与 `Debug` 输出一致，数据库中按此格式保存）
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "New" => Ok(OrderStatus::New),
+            "PartiallyFilled" => Ok(OrderStatus::PartiallyFilled),
+            "Filled" => Ok(OrderStatus::Filled),
+            "Cancelled" => Ok(OrderStatus::Cancelled),
+            "Rejected" => Ok(OrderStatus::Rejected),
+            "Expired" => Ok(OrderStatus::Expired),
+            _ => Err(anyhow::anyhow!("未知的订单状态: {}", s)),
+        }
+    }
+}
+
 /// 套利结果
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ArbitrageResult {
@@ -145,6 +504,84 @@ pub struct ArbitrageResult {
     pub sell_order_id: Option<u64>,
     pub status: ArbitrageStatus,
     pub timestamp: DateTime<Utc>,
+    pub start_time: DateTime<Utc>,      // 开始执行时间
+    pub end_time: DateTime<Utc>,        // 执行结束时间
+    pub buy_fill: FillInfo,             // 买入订单实际成交
+    pub sell_fill: FillInfo,            // 卖出订单实际成交
+    #[serde(default)]
+    pub mode: TradingMode,              // 产生该结果的运行模式
+    #[serde(default)]
+    pub config_hash: String,            // 生效配置的指纹，未知时为空
+    #[serde(default)]
+    pub strategy: Option<String>,       // 发现该机会的策略名称，旧记录为None
+    #[serde(default)]
+    pub opportunity_id: Option<Uuid>,   // 对应套利机会的ID，旧记录为None
+}
+
+impl ArbitrageResult {
+    /// 实际成交均价相对机会价格的滑点（百分比），见 `realized_slippage_percentage`
+    pub fn realized_slippage_percentage(&self) -> Option<Decimal> {
+        realized_slippage_percentage(self.buy_price, self.sell_price, &self.buy_fill, &self.sell_fill)
+    }
+}
+
+/// 实际成交均价相对机会价格的滑点（百分比），买入价格更高、卖出价格更低为正
+///
+/// 两边都有成交时才能计算，否则返回None。
+pub fn realized_slippage_percentage(
+    buy_price: Decimal,
+    sell_price: Decimal,
+    buy_fill: &FillInfo,
+    sell_fill: &FillInfo,
+) -> Option<Decimal> {
+    if buy_fill.qty.is_zero() || sell_fill.qty.is_zero() || buy_price.is_zero() || sell_price.is_zero() {
+        return None;
+    }
+
+    let buy_slippage = (buy_fill.avg_price - buy_price) / buy_price;
+    let sell_slippage = (sell_price - sell_fill.avg_price) / sell_price;
+    Some((buy_slippage + sell_slippage) * Decimal::from(100))
+}
+
+/// 产生交易记录的运行模式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TradingMode {
+    /// 实盘交易
+    #[default]
+    Live,
+    /// 模拟模式，使用模拟行情和模拟账户
+    Simulate,
+    /// 只记录套利机会、不实际下单
+    DryRun,
+    /// 历史数据回测
+    Backtest,
+}
+
+impl fmt::Display for TradingMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TradingMode::Live => write!(f, "live"),
+            TradingMode::Simulate => write!(f, "simulate"),
+            TradingMode::DryRun => write!(f, "dry_run"),
+            TradingMode::Backtest => write!(f, "backtest"),
+        }
+    }
+}
+
+impl FromStr for TradingMode {
+    type Err = anyhow::Error;
+
+    /// 解析运行模式名称（与 `Display` 输出一致，数据库中按此格式保存）
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "live" => Ok(TradingMode::Live),
+            "simulate" => Ok(TradingMode::Simulate),
+            "dry_run" | "dry-run" => Ok(TradingMode::DryRun),
+            "backtest" => Ok(TradingMode::Backtest),
+            _ => Err(anyhow::anyhow!("未知的运行模式: {}，应为 live、simulate、dry_run 或 backtest", s)),
+        }
+    }
 }
 
 /// 套利状态
@@ -157,4 +594,165 @@ pub enum ArbitrageStatus {
     SellOrderFilled,
     Completed,
     Failed,
+    /// 卖出腿失败后已按市价卖回买入的基础资产，利润为回补后的实际盈亏
+    PartiallyUnwound,
+}
+
+impl ArbitrageStatus {
+    /// 是否产生了已实现的盈亏，需要计入每日亏损等统计
+    pub fn has_realized_pnl(self) -> bool {
+        matches!(self, ArbitrageStatus::Completed | ArbitrageStatus::PartiallyUnwound)
+    }
+}
+
+impl FromStr for ArbitrageStatus {
+    type Err = anyhow::Error;
+
+    /// 解析状态名称（与 `Debug` 输出一致，数据库中按此格式保存）
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "Identified" => Ok(ArbitrageStatus::Identified),
+            "BuyOrderPlaced" => Ok(ArbitrageStatus::BuyOrderPlaced),
+            "BuyOrderFilled" => Ok(ArbitrageStatus::BuyOrderFilled),
+            "SellOrderPlaced" => Ok(ArbitrageStatus::SellOrderPlaced),
+            "SellOrderFilled" => Ok(ArbitrageStatus::SellOrderFilled),
+            "Completed" => Ok(ArbitrageStatus::Completed),
+            "Failed" => Ok(ArbitrageStatus::Failed),
+            "PartiallyUnwound" => Ok(ArbitrageStatus::PartiallyUnwound),
+            _ => Err(anyhow::anyhow!("未知的套利状态: {}", s)),
+        }
+    }
+}
+
+/// 套利机会被策略或风控组件拒绝的原因分类，用于按原因统计被跳过的机会
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RejectionReason {
+    /// 利润率低于最小利润率
+    BelowMinProfit,
+    /// 价差不足以覆盖买卖两边的手续费
+    BelowTradingFees,
+    /// 价格波动较大，利润率低于按波动率提高的要求
+    HighVolatility,
+    /// 盘口挂单量不足以覆盖计划交易量
+    InsufficientLiquidity,
+    /// 价格趋势强烈，利润率低于按趋势强度提高的要求
+    AdverseTrend,
+    /// 价格异常波动或处于异常后的冷却期
+    AbnormalPrice,
+    /// 交易对或资产在黑名单中
+    Blacklisted,
+    /// 不在允许交易的时间窗口内
+    OutsideTimeWindow,
+    /// 交易间隔过短或单位时间内交易次数过多
+    FrequencyLimited,
+    /// 价差未持续达到要求的时长
+    SpreadNotPersistent,
+    /// 超过资产的最大风险敞口
+    ExposureExceeded,
+    /// 已达到每日最大亏损
+    DailyLossLimit,
+    /// 同时持仓的套利数已达上限
+    MaxOpenPositions,
+    /// 买入所用的计价币余额不足
+    InsufficientBalance,
+    /// 风控检查出错
+    CheckFailed,
+}
+
+impl RejectionReason {
+    /// 原因的标识，用于指标标签和日志
+    pub fn as_str(self) -> &'static str {
+        match self {
+            RejectionReason::BelowMinProfit => "below_min_profit",
+            RejectionReason::BelowTradingFees => "below_trading_fees",
+            RejectionReason::HighVolatility => "high_volatility",
+            RejectionReason::InsufficientLiquidity => "insufficient_liquidity",
+            RejectionReason::AdverseTrend => "adverse_trend",
+            RejectionReason::AbnormalPrice => "abnormal_price",
+            RejectionReason::Blacklisted => "blacklisted",
+            RejectionReason::OutsideTimeWindow => "outside_time_window",
+            RejectionReason::FrequencyLimited => "frequency_limited",
+            RejectionReason::SpreadNotPersistent => "spread_not_persistent",
+            RejectionReason::ExposureExceeded => "exposure_exceeded",
+            RejectionReason::DailyLossLimit => "daily_loss_limit",
+            RejectionReason::MaxOpenPositions => "max_open_positions",
+            RejectionReason::InsufficientBalance => "insufficient_balance",
+            RejectionReason::CheckFailed => "check_failed",
+        }
+    }
+}
+
+impl fmt::Display for RejectionReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// 策略或风控组件拒绝套利机会的原因分类和具体说明
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rejection {
+    pub reason: RejectionReason,
+    /// 具体说明，例如当前值和限制值
+    pub message: String,
+}
+
+impl Rejection {
+    pub fn new(reason: RejectionReason, message: impl Into<String>) -> Self {
+        Self { reason, message: message.into() }
+    }
+}
+
+impl fmt::Display for Rejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_quote_currency_serde_round_trip() {
+        for quote in [QuoteCurrency::USDT, QuoteCurrency::USDC] {
+            let json = serde_json::to_string(&quote).unwrap();
+            assert_eq!(json, format!("\"{}\"", quote));
+            assert_eq!(serde_json::from_str::<QuoteCurrency>(&json).unwrap(), quote);
+        }
+        assert_eq!(serde_json::from_str::<QuoteCurrency>("\"usdc\"").unwrap(), QuoteCurrency::USDC);
+        assert_eq!(" Usdt ".parse::<QuoteCurrency>().unwrap(), QuoteCurrency::USDT);
+
+        let err = serde_json::from_str::<QuoteCurrency>("\"BUSD\"").unwrap_err();
+        assert!(err.to_string().contains("未知的报价货币: BUSD"));
+        assert!("".parse::<QuoteCurrency>().is_err());
+    }
+
+    #[test]
+    fn test_opportunity_serde_round_trip() {
+        let mut opportunity = ArbitrageOpportunity::new("BTC", QuoteCurrency::USDT, QuoteCurrency::USDC, dec!(50000), dec!(50025), dec!(100));
+        opportunity.strategy = Some("简单套利策略".to_string());
+
+        let json = serde_json::to_value(&opportunity).unwrap();
+        assert_eq!(json["buy_quote"], "USDT");
+        assert_eq!(json["sell_quote"], "USDC");
+        assert_eq!(json["opportunity_id"], opportunity.opportunity_id.to_string());
+
+        let parsed: ArbitrageOpportunity = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed, opportunity);
+    }
+
+    #[test]
+    fn test_opportunity_equality() {
+        let opportunity = ArbitrageOpportunity::new("BTC", QuoteCurrency::USDT, QuoteCurrency::USDC, dec!(50000), dec!(50025), dec!(100));
+        let mut repriced = opportunity.clone();
+        assert_eq!(repriced, opportunity);
+
+        repriced.set_prices(dec!(50000), dec!(50030));
+        assert_ne!(repriced, opportunity);
+        // 每次发现机会都生成新的ID，相同价格的两个机会也不相等
+        let same_prices = ArbitrageOpportunity { opportunity_id: Uuid::new_v4(), ..opportunity.clone() };
+        assert_ne!(same_prices, opportunity);
+    }
 }
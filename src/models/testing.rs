@@ -0,0 +1,56 @@
+//! 测试用的成交、套利结果和交易记录构造函数，供各模块的测试共用
+
+use super::{ArbitrageResult, ArbitrageStatus, FillInfo, TradingMode};
+use crate::db::TradeRecord;
+use chrono::{DateTime, Duration, Utc};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+/// 一笔成交
+pub fn fill(qty: Decimal, avg_price: Decimal, commission: Decimal, commission_asset: &str) -> FillInfo {
+    FillInfo { qty, avg_price, commission, commission_asset: commission_asset.to_string() }
+}
+
+/// 以当前时间开始和结束的套利结果，其他字段同 `arbitrage_result_at`
+pub fn arbitrage_result(status: ArbitrageStatus) -> ArbitrageResult {
+    arbitrage_result_at(status, Utc::now(), Duration::zero())
+}
+
+/// 从 `start_time` 开始、执行 `duration` 的实盘套利结果
+///
+/// 以 50000 USDT 买入、50100 USDC 卖出 0.1 BTC，利润10，买卖订单ID为1和2，没有成交明细；
+/// 测试按需用结构体更新语法覆盖其他字段。
+pub fn arbitrage_result_at(status: ArbitrageStatus, start_time: DateTime<Utc>, duration: Duration) -> ArbitrageResult {
+    ArbitrageResult {
+        base_asset: "BTC".to_string(),
+        buy_quote: "USDT".to_string(),
+        sell_quote: "USDC".to_string(),
+        buy_price: dec!(50000),
+        sell_price: dec!(50100),
+        trade_amount: dec!(0.1),
+        profit: dec!(10),
+        profit_percentage: dec!(0.2),
+        buy_order_id: Some(1),
+        sell_order_id: Some(2),
+        status,
+        timestamp: start_time,
+        start_time,
+        end_time: start_time + duration,
+        buy_fill: FillInfo::default(),
+        sell_fill: FillInfo::default(),
+        mode: TradingMode::Live,
+        config_hash: String::new(),
+        strategy: None,
+        opportunity_id: None,
+    }
+}
+
+/// 数据库中的交易记录，执行耗时按开始和结束时间计算，不含订单
+pub fn trade_record(id: i64, result: ArbitrageResult) -> TradeRecord {
+    TradeRecord {
+        id,
+        duration_ms: (result.end_time - result.start_time).num_milliseconds(),
+        result,
+        orders: Vec::new(),
+    }
+}
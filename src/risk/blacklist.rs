@@ -1,16 +1,99 @@
 use super::RiskController;
-use crate::models::{ArbitrageOpportunity, ArbitrageResult};
+use crate::config::RiskSettings;
+use crate::models::{ArbitrageOpportunity, ArbitrageResult, QuoteCurrency, Rejection, RejectionReason};
 use anyhow::Result;
 use async_trait::async_trait;
 use log::{debug, info, warn};
+use std::fmt;
+use std::str::FromStr;
 use std::sync::{Arc, Mutex};
 use std::collections::HashSet;
 
+/// 黑名单中的一项
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum BlacklistEntry {
+    /// 单个交易对，例如 BTCUSDT
+    Pair { base: String, quote: QuoteCurrency },
+    /// 基础资产的USDT和USDC交易对，例如 BTC
+    BaseAsset(String),
+    /// 报价货币的所有交易对，例如 *USDC
+    Quote(QuoteCurrency),
+}
+
+impl BlacklistEntry {
+    /// `base` 和 `quote` 组成的交易对是否命中该项
+    pub fn matches(&self, base: &str, quote: QuoteCurrency) -> bool {
+        match self {
+            BlacklistEntry::Pair { base: b, quote: q } => b == base && *q == quote,
+            BlacklistEntry::BaseAsset(b) => b == base,
+            BlacklistEntry::Quote(q) => *q == quote,
+        }
+    }
+}
+
+/// 大写字母和数字组成的非空名称
+fn is_asset(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit())
+}
+
+impl FromStr for BlacklistEntry {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let quote = |name: &str| match name {
+            "USDT" => Some(QuoteCurrency::USDT),
+            "USDC" => Some(QuoteCurrency::USDC),
+            _ => None,
+        };
+        let invalid = || format!(
+            "黑名单项 {:?} 格式无效，应为大写的交易对（例如 BTCUSDT）、基础资产（例如 BTC）或通配符（*USDT 或 *USDC）",
+            s
+        );
+
+        if let Some(name) = s.strip_prefix('*') {
+            return quote(name).map(BlacklistEntry::Quote).ok_or_else(invalid);
+        }
+        if !is_asset(s) || quote(s).is_some() {
+            return Err(invalid());
+        }
+        for name in ["USDT", "USDC"] {
+            if let Some(base) = s.strip_suffix(name).filter(|base| !base.is_empty()) {
+                return Ok(BlacklistEntry::Pair { base: base.to_string(), quote: quote(name).unwrap() });
+            }
+        }
+        Ok(BlacklistEntry::BaseAsset(s.to_string()))
+    }
+}
+
+impl fmt::Display for BlacklistEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BlacklistEntry::Pair { base, quote } => write!(f, "{}{}", base, quote),
+            BlacklistEntry::BaseAsset(base) => write!(f, "{}", base),
+            BlacklistEntry::Quote(quote) => write!(f, "*{}", quote),
+        }
+    }
+}
+
+/// 解析配置中的黑名单列表，格式无效的项记录警告后跳过
+fn parse_entries(entries: &[String]) -> HashSet<BlacklistEntry> {
+    entries
+        .iter()
+        .filter_map(|entry| match entry.parse() {
+            Ok(entry) => Some(entry),
+            Err(e) => {
+                warn!("{}", e);
+                None
+            }
+        })
+        .collect()
+}
+
 /// 交易对黑名单控制器
 /// 将特定交易对排除在套利操作之外，可用于避免问题币种或特定市场情况
 pub struct PairBlacklistController {
-    /// 黑名单交易对集合
-    blacklist: Arc<Mutex<HashSet<String>>>,
+    /// 黑名单
+    blacklist: Arc<Mutex<HashSet<BlacklistEntry>>>,
 }
 
 impl PairBlacklistController {
@@ -20,60 +103,67 @@ impl PairBlacklistController {
         }
     }
     
+    /// 按配置中的黑名单列表创建，每项可以是交易对、基础资产或 `*USDC` 形式的通配符
+    pub fn from_entries(entries: &[String]) -> Self {
+        let blacklist = parse_entries(entries);
+        info!("交易对黑名单: {:?}", entries);
+        Self {
+            blacklist: Arc::new(Mutex::new(blacklist)),
+        }
+    }
+    
     /// 添加交易对到黑名单
     pub fn add_to_blacklist(&self, asset: &str, quote: &str) {
         let pair = format!("{}{}", asset, quote);
-        let mut blacklist = self.blacklist.lock().unwrap();
-        blacklist.insert(pair.clone());
-        info!("添加交易对到黑名单: {}", pair);
+        match pair.parse::<BlacklistEntry>() {
+            Ok(entry) => {
+                self.blacklist.lock().unwrap().insert(entry);
+                info!("添加交易对到黑名单: {}", pair);
+            }
+            Err(e) => warn!("{}", e),
+        }
     }
     
     /// 添加一组基础资产到黑名单
     pub fn add_base_asset_to_blacklist(&self, base_asset: &str) {
-        let usdt_pair = format!("{}USDT", base_asset);
-        let usdc_pair = format!("{}USDC", base_asset);
-        
-        let mut blacklist = self.blacklist.lock().unwrap();
-        blacklist.insert(usdt_pair.clone());
-        blacklist.insert(usdc_pair.clone());
-        
-        info!("添加基础资产到黑名单: {} (添加 {} 和 {})", base_asset, usdt_pair, usdc_pair);
+        self.blacklist.lock().unwrap().insert(BlacklistEntry::BaseAsset(base_asset.to_string()));
+        info!("添加基础资产到黑名单: {} (USDT 和 USDC 交易对)", base_asset);
     }
     
     /// 从黑名单中移除交易对
     pub fn remove_from_blacklist(&self, asset: &str, quote: &str) {
         let pair = format!("{}{}", asset, quote);
-        let mut blacklist = self.blacklist.lock().unwrap();
-        if blacklist.remove(&pair) {
+        let Ok(entry) = pair.parse::<BlacklistEntry>() else {
+            return;
+        };
+        if self.blacklist.lock().unwrap().remove(&entry) {
             info!("从黑名单移除交易对: {}", pair);
         }
     }
     
-    /// 从黑名单中移除一组基础资产
+    /// 从黑名单中移除一组基础资产，包括该资产单独列出的交易对
     pub fn remove_base_asset_from_blacklist(&self, base_asset: &str) {
-        let usdt_pair = format!("{}USDT", base_asset);
-        let usdc_pair = format!("{}USDC", base_asset);
-        
         let mut blacklist = self.blacklist.lock().unwrap();
-        let removed_usdt = blacklist.remove(&usdt_pair);
-        let removed_usdc = blacklist.remove(&usdc_pair);
+        let before = blacklist.len();
+        blacklist.retain(|entry| match entry {
+            BlacklistEntry::Pair { base, .. } | BlacklistEntry::BaseAsset(base) => base != base_asset,
+            BlacklistEntry::Quote(_) => true,
+        });
         
-        if removed_usdt || removed_usdc {
+        if blacklist.len() < before {
             info!("从黑名单移除基础资产: {}", base_asset);
         }
     }
     
     /// 检查交易对是否在黑名单中
-    fn is_blacklisted(&self, asset: &str, quote: &str) -> bool {
-        let pair = format!("{}{}", asset, quote);
-        let blacklist = self.blacklist.lock().unwrap();
-        blacklist.contains(&pair)
+    fn is_blacklisted(&self, asset: &str, quote: QuoteCurrency) -> bool {
+        self.blacklist.lock().unwrap().iter().any(|entry| entry.matches(asset, quote))
     }
     
-    /// 获取所有黑名单交易对
+    /// 获取所有黑名单项
     pub fn get_blacklist(&self) -> Vec<String> {
         let blacklist = self.blacklist.lock().unwrap();
-        blacklist.iter().cloned().collect()
+        blacklist.iter().map(|entry| entry.to_string()).collect()
     }
 }
 
@@ -87,20 +177,17 @@ impl RiskController for PairBlacklistController {
         "将特定交易对排除在套利操作之外，可用于避免问题币种或特定市场情况"
     }
     
-    async fn check_opportunity(&self, opportunity: &ArbitrageOpportunity) -> Result<(bool, Option<String>)> {
-        let usdt_pair = format!("{}USDT", opportunity.base_asset);
-        let usdc_pair = format!("{}USDC", opportunity.base_asset);
-        
-        let blacklist = self.blacklist.lock().unwrap();
-        
+    async fn check_opportunity(&self, opportunity: &ArbitrageOpportunity) -> Result<(bool, Option<Rejection>)> {
         // 检查两个交易对是否有任何一个在黑名单中
-        if blacklist.contains(&usdt_pair) || blacklist.contains(&usdc_pair) {
+        if self.is_blacklisted(&opportunity.base_asset, opportunity.buy_quote)
+            || self.is_blacklisted(&opportunity.base_asset, opportunity.sell_quote)
+        {
             let reason = format!(
                 "{} 在黑名单中，不执行套利",
                 opportunity.base_asset
             );
             debug!("{}", reason);
-            return Ok((false, Some(reason)));
+            return Ok((false, Some(Rejection::new(RejectionReason::Blacklisted, reason))));
         }
         
         Ok((true, None))
@@ -119,6 +206,19 @@ impl RiskController for PairBlacklistController {
         
         Ok(())
     }
+    
+    fn update_settings(&self, settings: &RiskSettings) -> bool {
+        let entries = &settings.pair_blacklist.blacklisted_pairs;
+        info!("更新交易对黑名单: {:?}", entries);
+        *self.blacklist.lock().unwrap() = parse_entries(entries);
+        true
+    }
+    
+    fn state(&self) -> serde_json::Value {
+        let mut blacklist = self.get_blacklist();
+        blacklist.sort();
+        serde_json::json!({ "blacklist": blacklist })
+    }
 }
 
 #[cfg(test)]
@@ -147,7 +247,9 @@ mod tests {
         // 应该被拒绝
         let (valid, reason) = controller.check_opportunity(&btc_opportunity).await.unwrap();
         assert!(!valid);
-        assert!(reason.unwrap().contains("BTC 在黑名单中"));
+        let rejection = reason.unwrap();
+        assert_eq!(rejection.reason, RejectionReason::Blacklisted);
+        assert!(rejection.message.contains("BTC 在黑名单中"));
         
         // 创建一个ETH的套利机会
         let eth_opportunity = ArbitrageOpportunity::new(
@@ -169,5 +271,45 @@ mod tests {
         // 现在BTC应该能通过
         let (valid, _) = controller.check_opportunity(&btc_opportunity).await.unwrap();
         assert!(valid);
+        
+        // 热加载后黑名单替换为配置中的交易对
+        let mut settings = RiskSettings::default();
+        settings.pair_blacklist.blacklisted_pairs = vec!["ETHUSDC".to_string()];
+        assert!(controller.update_settings(&settings));
+        let (valid, _) = controller.check_opportunity(&eth_opportunity).await.unwrap();
+        assert!(!valid);
+        assert_eq!(controller.get_blacklist(), vec!["ETHUSDC".to_string()]);
+        assert_eq!(controller.state(), serde_json::json!({ "blacklist": ["ETHUSDC"] }));
+    }
+    
+    #[test]
+    fn test_parse_blacklist_entries() {
+        assert_eq!("BTCUSDT".parse::<BlacklistEntry>(), Ok(BlacklistEntry::Pair { base: "BTC".to_string(), quote: QuoteCurrency::USDT }));
+        assert_eq!("ETH".parse::<BlacklistEntry>(), Ok(BlacklistEntry::BaseAsset("ETH".to_string())));
+        assert_eq!("*USDC".parse::<BlacklistEntry>(), Ok(BlacklistEntry::Quote(QuoteCurrency::USDC)));
+        for invalid in ["", "btcusdt", "BTC-USDT", "USDC", "*BTC", "*"] {
+            assert!(invalid.parse::<BlacklistEntry>().is_err(), "{:?}", invalid);
+        }
+        
+        // 格式无效的项被跳过
+        let controller = PairBlacklistController::from_entries(&["SOL".to_string(), "btc-usdt".to_string()]);
+        assert_eq!(controller.get_blacklist(), vec!["SOL".to_string()]);
+    }
+    
+    #[tokio::test]
+    async fn test_base_asset_and_wildcard_entries() {
+        let opportunity = |base: &str| {
+            ArbitrageOpportunity::new(base, QuoteCurrency::USDC, QuoteCurrency::USDT, dec!(100), dec!(101), dec!(1000))
+        };
+        
+        let controller = PairBlacklistController::from_entries(&["DOGE".to_string()]);
+        assert!(!controller.check_opportunity(&opportunity("DOGE")).await.unwrap().0);
+        assert!(controller.check_opportunity(&opportunity("ETH")).await.unwrap().0);
+        
+        // 通配符命中该报价货币的所有交易对
+        let controller = PairBlacklistController::from_entries(&["*USDC".to_string()]);
+        assert!(controller.is_blacklisted("ETH", QuoteCurrency::USDC));
+        assert!(!controller.is_blacklisted("ETH", QuoteCurrency::USDT));
+        assert!(!controller.check_opportunity(&opportunity("ETH")).await.unwrap().0);
     }
 }
@@ -1,5 +1,6 @@
 use super::RiskController;
-use crate::models::{ArbitrageOpportunity, ArbitrageResult, ArbitrageStatus, QuoteCurrency};
+use crate::config::{ExposureUpdateMode, RiskSettings};
+use crate::models::{ArbitrageOpportunity, ArbitrageResult, ArbitrageStatus, QuoteCurrency, Rejection, RejectionReason};
 use crate::binance::ExchangeApi;
 use anyhow::Result;
 use async_trait::async_trait;
@@ -7,49 +8,76 @@ use log::{debug, info, warn};
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
 use std::sync::{Arc, Mutex};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::time::{Duration, Instant};
 
 /// 风险敞口控制器
 /// 控制单一币种的风险敞口，避免在特定币种上持有过多资产
 pub struct ExposureController<T: ExchangeApi + Send + Sync> {
     api: Arc<T>,
-    /// 币种最大风险敞口（以USDT计）
-    max_exposures: HashMap<String, Decimal>,
+    /// 币种最大风险敞口（以USDT计），热加载配置时整体替换
+    max_exposures: Mutex<HashMap<String, Decimal>>,
     /// 每种币的当前头寸
     current_positions: Arc<Mutex<HashMap<String, Decimal>>>,
+    /// 刷新持仓的时机
+    update_mode: Mutex<ExposureUpdateMode>,
+    /// `Interval` 模式下两次查询余额的最短间隔
+    update_interval: Mutex<Duration>,
+    /// 上次成功查询余额的时间，为None时下次检查前必须查询
+    last_update: Mutex<Option<Instant>>,
 }
 
 impl<T: ExchangeApi + Send + Sync + 'static> ExposureController<T> {
     pub fn new(api: T) -> Self {
         Self {
             api: Arc::new(api),
-            max_exposures: HashMap::new(),
+            max_exposures: Mutex::new(HashMap::new()),
             current_positions: Arc::new(Mutex::new(HashMap::new())),
+            update_mode: Mutex::new(ExposureUpdateMode::default()),
+            update_interval: Mutex::new(Duration::ZERO),
+            last_update: Mutex::new(None),
         }
     }
     
+    /// 设置刷新持仓的时机，默认每次检查前查询余额
+    pub fn with_update_mode(self, mode: ExposureUpdateMode, interval: Duration) -> Self {
+        *self.update_mode.lock().unwrap() = mode;
+        *self.update_interval.lock().unwrap() = interval;
+        self
+    }
+    
     /// 设置币种最大风险敞口
     pub fn set_max_exposure(&mut self, asset: &str, max_exposure: Decimal) {
-        self.max_exposures.insert(asset.to_string(), max_exposure);
+        self.max_exposures.get_mut().unwrap().insert(asset.to_string(), max_exposure);
         info!("设置 {} 最大风险敞口: {}", asset, max_exposure);
     }
     
     /// 更新当前持仓
     pub async fn update_positions(&self) -> Result<()> {
-        let mut positions = self.current_positions.lock().unwrap();
+        let assets: Vec<String> = self.max_exposures.lock().unwrap().keys().cloned().collect();
         
-        for (asset, _) in &self.max_exposures {
-            let balance = self.api.get_account_balance(asset).await?;
-            positions.insert(asset.clone(), balance);
-            
+        for asset in assets {
+            let balance = self.api.get_account_balance(&asset).await?;
             debug!("更新持仓: {} = {}", asset, balance);
+            self.current_positions.lock().unwrap().insert(asset, balance);
         }
+        *self.last_update.lock().unwrap() = Some(Instant::now());
         
         Ok(())
     }
     
+    /// 按刷新时机判断检查前是否需要重新查询余额
+    fn positions_stale(&self) -> bool {
+        let last_update = *self.last_update.lock().unwrap();
+        match (*self.update_mode.lock().unwrap(), last_update) {
+            (ExposureUpdateMode::EveryCheck, _) | (_, None) => true,
+            (ExposureUpdateMode::Interval, Some(updated_at)) => updated_at.elapsed() >= *self.update_interval.lock().unwrap(),
+            (ExposureUpdateMode::OnTrade, Some(_)) => false,
+        }
+    }
+    
     /// 检查交易后的风险敞口是否超过限制
-    fn check_exposure_after_trade(&self, asset: &str, change: Decimal) -> Result<(bool, Option<String>)> {
+    fn check_exposure_after_trade(&self, asset: &str, change: Decimal) -> Result<(bool, Option<Rejection>)> {
         let positions = self.current_positions.lock().unwrap();
         
         // 获取当前头寸
@@ -59,14 +87,14 @@ impl<T: ExchangeApi + Send + Sync + 'static> ExposureController<T> {
         let new_position = current_position + change;
         
         // 检查是否有风险限制
-        if let Some(max_exposure) = self.max_exposures.get(asset) {
+        if let Some(max_exposure) = self.max_exposures.lock().unwrap().get(asset) {
             if new_position.abs() > *max_exposure {
                 let reason = format!(
                     "{} 风险敞口将超过限制: {} + {} = {} > {}",
                     asset, current_position, change, new_position, max_exposure
                 );
                 warn!("{}", reason);
-                return Ok((false, Some(reason)));
+                return Ok((false, Some(Rejection::new(RejectionReason::ExposureExceeded, reason))));
             }
         }
         
@@ -84,9 +112,11 @@ impl<T: ExchangeApi + Send + Sync + 'static> RiskController for ExposureControll
         "控制单一币种的风险敞口，避免在特定币种上持有过多资产"
     }
     
-    async fn check_opportunity(&self, opportunity: &ArbitrageOpportunity) -> Result<(bool, Option<String>)> {
-        // 更新当前持仓
-        self.update_positions().await?;
+    async fn check_opportunity(&self, opportunity: &ArbitrageOpportunity) -> Result<(bool, Option<Rejection>)> {
+        // 按刷新时机更新当前持仓
+        if self.positions_stale() {
+            self.update_positions().await?;
+        }
         
         // 计算交易对基础资产的变化
         // 套利交易通常是先买入后卖出，基础资产的净变化应该是很小的
@@ -100,14 +130,11 @@ impl<T: ExchangeApi + Send + Sync + 'static> RiskController for ExposureControll
     }
     
     async fn record_result(&self, result: &ArbitrageResult) -> Result<()> {
+        if result.status.has_realized_pnl() || result.status == ArbitrageStatus::Failed {
+            // 已结束的交易改变了账户余额，下次检查前重新查询持仓
+            *self.last_update.lock().unwrap() = None;
+        }
         if result.status == ArbitrageStatus::Completed {
-            let mut positions = self.current_positions.lock().unwrap();
-            
-            // 更新基础资产头寸（买入后卖出，净变化应该很小，但仍然要记录）
-            let current = positions.entry(result.base_asset.clone()).or_insert(Decimal::ZERO);
-            
-            // 这里假设交易已经完成，资产头寸已经反映在账户余额中
-            // 实际上应该再次调用API获取最新头寸，但这里为了简化，我们只是记录交易
             info!(
                 "套利交易完成: {} - 利润: {}",
                 result.base_asset, result.profit
@@ -120,22 +147,47 @@ impl<T: ExchangeApi + Send + Sync + 'static> RiskController for ExposureControll
     async fn reset(&self) -> Result<()> {
         let mut positions = self.current_positions.lock().unwrap();
         positions.clear();
+        *self.last_update.lock().unwrap() = None;
         
         info!("重置风险敞口控制器");
         
         Ok(())
     }
+    
+    fn update_settings(&self, settings: &RiskSettings) -> bool {
+        let settings = &settings.exposure;
+        *self.max_exposures.lock().unwrap() = settings.max_exposures.clone();
+        *self.update_mode.lock().unwrap() = settings.update_mode;
+        *self.update_interval.lock().unwrap() = Duration::from_secs(settings.update_interval_seconds);
+        true
+    }
+    
+    fn state(&self) -> serde_json::Value {
+        let positions: BTreeMap<String, Decimal> = self.current_positions.lock().unwrap().clone().into_iter().collect();
+        let max_exposures: BTreeMap<String, Decimal> = self.max_exposures.lock().unwrap().clone().into_iter().collect();
+        // 距离上次查询余额的秒数，还没有查询过或需要重新查询时为None
+        let positions_age_seconds = self.last_update.lock().unwrap().map(|updated_at| updated_at.elapsed().as_secs());
+        serde_json::json!({
+            "positions": positions,
+            "max_exposures": max_exposures,
+            "update_mode": *self.update_mode.lock().unwrap(),
+            "positions_age_seconds": positions_age_seconds,
+        })
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::binance::MockBinanceApi;
+    use crate::binance::{LatencyTrackedApi, MockBinanceApi};
+    use crate::config::ApiLatencySettings;
+    use crate::models::testing::arbitrage_result;
     
     #[tokio::test]
     async fn test_exposure_control() {
         let api = MockBinanceApi::new();
-        let mut controller = ExposureController::new(api);
+        // 只在交易结束后刷新持仓，检查时不会用查询到的余额覆盖模拟的持仓
+        let mut controller = ExposureController::new(api).with_update_mode(ExposureUpdateMode::OnTrade, Duration::ZERO);
         
         // 设置BTC的最大风险敞口为2个BTC
         controller.set_max_exposure("BTC", dec!(2));
@@ -145,6 +197,7 @@ mod tests {
             let mut positions = controller.current_positions.lock().unwrap();
             positions.insert("BTC".to_string(), dec!(1.5));
         }
+        *controller.last_update.lock().unwrap() = Some(Instant::now());
         
         // 创建一个会超过风险敞口的套利机会
         let opportunity = ArbitrageOpportunity::new(
@@ -159,7 +212,9 @@ mod tests {
         // 应该被拒绝
         let (valid, reason) = controller.check_opportunity(&opportunity).await.unwrap();
         assert!(!valid);
-        assert!(reason.unwrap().contains("风险敞口将超过限制"));
+        let rejection = reason.unwrap();
+        assert_eq!(rejection.reason, RejectionReason::ExposureExceeded);
+        assert!(rejection.message.contains("风险敞口将超过限制"));
         
         // 创建一个不会超过风险敞口的套利机会
         let opportunity = ArbitrageOpportunity::new(
@@ -174,5 +229,107 @@ mod tests {
         // 应该通过
         let (valid, _) = controller.check_opportunity(&opportunity).await.unwrap();
         assert!(valid);
+        
+        // 热加载后使用新的限额
+        let mut settings = RiskSettings::default();
+        settings.exposure.max_exposures = HashMap::from([("BTC".to_string(), dec!(0.1))]);
+        assert!(controller.update_settings(&settings));
+        let (valid, _) = controller.check_opportunity(&opportunity).await.unwrap();
+        assert!(!valid);
+    }
+    
+    /// 记录API调用次数的模拟API上的风险敞口控制器，只限制BTC一个币种
+    fn counted_controller(mode: ExposureUpdateMode) -> ExposureController<LatencyTrackedApi<MockBinanceApi>> {
+        let settings = ApiLatencySettings { enabled: true, window_size: 10, warn_threshold_ms: 0 };
+        let api = LatencyTrackedApi::new(MockBinanceApi::new(), &settings);
+        let mut controller = ExposureController::new(api).with_update_mode(mode, Duration::from_secs(60));
+        controller.set_max_exposure("BTC", dec!(5));
+        controller
+    }
+    
+    fn balance_calls(controller: &ExposureController<LatencyTrackedApi<MockBinanceApi>>) -> u64 {
+        controller
+            .api
+            .latency_stats()
+            .into_iter()
+            .find(|stats| stats.method == "get_account_balance")
+            .map_or(0, |stats| stats.calls)
+    }
+    
+    #[tokio::test]
+    async fn test_interval_mode_queries_balance_once_per_interval() {
+        let controller = counted_controller(ExposureUpdateMode::Interval);
+        let opportunity = ArbitrageOpportunity::new("BTC", QuoteCurrency::USDT, QuoteCurrency::USDC, dec!(50000), dec!(50100), dec!(10000));
+        
+        for _ in 0..10 {
+            assert!(controller.check_opportunity(&opportunity).await.unwrap().0);
+        }
+        assert_eq!(balance_calls(&controller), 1);
+        
+        // 超过间隔后重新查询一次
+        *controller.last_update.lock().unwrap() = Some(Instant::now() - Duration::from_secs(61));
+        for _ in 0..10 {
+            controller.check_opportunity(&opportunity).await.unwrap();
+        }
+        assert_eq!(balance_calls(&controller), 2);
+        
+        // 套利结束后不等间隔到期就重新查询
+        controller.record_result(&arbitrage_result(ArbitrageStatus::Completed)).await.unwrap();
+        controller.check_opportunity(&opportunity).await.unwrap();
+        controller.check_opportunity(&opportunity).await.unwrap();
+        assert_eq!(balance_calls(&controller), 3);
+    }
+    
+    #[tokio::test]
+    async fn test_on_trade_mode_queries_balance_after_finished_trades() {
+        let controller = counted_controller(ExposureUpdateMode::OnTrade);
+        let opportunity = ArbitrageOpportunity::new("BTC", QuoteCurrency::USDT, QuoteCurrency::USDC, dec!(50000), dec!(50100), dec!(10000));
+        
+        for _ in 0..10 {
+            controller.check_opportunity(&opportunity).await.unwrap();
+        }
+        assert_eq!(balance_calls(&controller), 1);
+        
+        // 超过间隔也不查询，只有结束的套利（包括失败）才触发
+        *controller.last_update.lock().unwrap() = Some(Instant::now() - Duration::from_secs(61));
+        controller.check_opportunity(&opportunity).await.unwrap();
+        assert_eq!(balance_calls(&controller), 1);
+        
+        controller.record_result(&arbitrage_result(ArbitrageStatus::Failed)).await.unwrap();
+        for _ in 0..5 {
+            controller.check_opportunity(&opportunity).await.unwrap();
+        }
+        assert_eq!(balance_calls(&controller), 2);
+        
+        // 默认每次检查都查询
+        let mut settings = RiskSettings::default();
+        settings.exposure.update_mode = ExposureUpdateMode::EveryCheck;
+        assert!(controller.update_settings(&settings));
+        for _ in 0..3 {
+            controller.check_opportunity(&opportunity).await.unwrap();
+        }
+        // 默认配置限制BTC和ETH两个币种，每次检查查询两次
+        assert_eq!(balance_calls(&controller), 8);
+    }
+    
+    #[tokio::test]
+    async fn test_state_reports_positions_and_limits() {
+        let controller = counted_controller(ExposureUpdateMode::OnTrade);
+        let state = controller.state();
+        assert_eq!(state["max_exposures"]["BTC"], "5");
+        assert_eq!(state["positions"], serde_json::json!({}));
+        assert_eq!(state["update_mode"], "OnTrade");
+        assert!(state["positions_age_seconds"].is_null());
+        
+        let opportunity = ArbitrageOpportunity::new("BTC", QuoteCurrency::USDT, QuoteCurrency::USDC, dec!(50000), dec!(50100), dec!(10000));
+        controller.check_opportunity(&opportunity).await.unwrap();
+        let balance = controller.api.get_account_balance("BTC").await.unwrap();
+        let state = controller.state();
+        assert_eq!(state["positions"]["BTC"], balance.to_string());
+        assert_eq!(state["positions_age_seconds"], 0);
+        
+        // 套利结束后需要重新查询
+        controller.record_result(&arbitrage_result(ArbitrageStatus::Completed)).await.unwrap();
+        assert!(controller.state()["positions_age_seconds"].is_null());
     }
 }
@@ -1,53 +1,140 @@
 use super::RiskController;
-use crate::models::{ArbitrageOpportunity, ArbitrageResult, ArbitrageStatus};
+use crate::config::RiskSettings;
+use crate::market_data::PriceCache;
+use crate::models::{ArbitrageOpportunity, ArbitrageResult, ArbitrageStatus, Rejection, RejectionReason};
 use anyhow::Result;
 use async_trait::async_trait;
 use chrono::{DateTime, Utc, Duration};
 use log::{debug, info, warn};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
 use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicI64, AtomicUsize, Ordering};
 use std::collections::VecDeque;
 
 /// 交易频率控制器
 /// 控制套利交易的频率，避免API限制，同时防止在短时间内执行过多交易
 pub struct TradingFrequencyController {
     /// 最小交易间隔（秒）
-    min_interval_seconds: i64,
+    min_interval_seconds: AtomicI64,
     /// 单位时间最大交易次数
-    max_trades_per_timeframe: usize,
+    max_trades_per_timeframe: AtomicUsize,
     /// 时间窗口长度（秒）
-    timeframe_seconds: i64,
+    timeframe_seconds: AtomicI64,
     /// 上次交易时间
     last_trade_time: Arc<Mutex<Option<DateTime<Utc>>>>,
     /// 最近交易历史
     recent_trades: Arc<Mutex<VecDeque<DateTime<Utc>>>>,
+    /// 价差持续要求，为None时不检查
+    spread_persistence: Mutex<Option<SpreadPersistence>>,
+}
+
+/// 价差需要连续保持在阈值以上一段时间才视为可交易，过滤短暂闪现的价差
+struct SpreadPersistence {
+    /// 引擎共享的行情缓存
+    price_cache: Arc<PriceCache>,
+    /// 价差阈值（百分比）
+    min_spread_percentage: Decimal,
+    /// 最短持续时间
+    min_duration: Duration,
+}
+
+impl SpreadPersistence {
+    /// 价差满足持续要求时返回None，否则返回拒绝原因
+    ///
+    /// 按写入时间合并买入和卖出交易对的行情，每条行情到达后用两边的最新价格计算机会方向上的价差，
+    /// 价差最近一次连续保持在阈值以上的起点距今不少于最短持续时间时满足要求。
+    /// 起点之前的行情已被缓存淘汰时从最早的行情起算。
+    fn check(&self, opportunity: &ArbitrageOpportunity, now: DateTime<Utc>) -> Option<String> {
+        let buy_symbol = format!("{}{}", opportunity.base_asset, opportunity.buy_quote);
+        let sell_symbol = format!("{}{}", opportunity.base_asset, opportunity.sell_quote);
+        
+        let mut ticks: Vec<(DateTime<Utc>, bool, Decimal)> = self.price_cache
+            .history(&buy_symbol, None)
+            .into_iter()
+            .map(|tick| (tick.received_at, true, tick.price.price))
+            .chain(
+                self.price_cache
+                    .history(&sell_symbol, None)
+                    .into_iter()
+                    .map(|tick| (tick.received_at, false, tick.price.price)),
+            )
+            .collect();
+        ticks.sort_by_key(|(received_at, _, _)| *received_at);
+        
+        let (mut buy_price, mut sell_price) = (None, None);
+        let mut streak_start = None;
+        for (received_at, is_buy, price) in ticks {
+            if is_buy {
+                buy_price = Some(price);
+            } else {
+                sell_price = Some(price);
+            }
+            if let (Some(buy), Some(sell)) = (buy_price, sell_price) {
+                if buy > Decimal::ZERO && (sell - buy) / buy * dec!(100) >= self.min_spread_percentage {
+                    streak_start.get_or_insert(received_at);
+                } else {
+                    streak_start = None;
+                }
+            }
+        }
+        
+        match streak_start {
+            Some(start) if now - start >= self.min_duration => None,
+            Some(start) => Some(format!(
+                "价差仅持续 {} 毫秒，需要至少 {} 毫秒",
+                (now - start).num_milliseconds(),
+                self.min_duration.num_milliseconds()
+            )),
+            None => Some(format!(
+                "{} 当前价差未达到 {}%",
+                opportunity.base_asset, self.min_spread_percentage
+            )),
+        }
+    }
 }
 
 impl TradingFrequencyController {
     pub fn new(min_interval_seconds: i64, max_trades_per_timeframe: usize, timeframe_seconds: i64) -> Self {
         Self {
-            min_interval_seconds,
-            max_trades_per_timeframe,
-            timeframe_seconds,
+            min_interval_seconds: AtomicI64::new(min_interval_seconds),
+            max_trades_per_timeframe: AtomicUsize::new(max_trades_per_timeframe),
+            timeframe_seconds: AtomicI64::new(timeframe_seconds),
             last_trade_time: Arc::new(Mutex::new(None)),
             recent_trades: Arc::new(Mutex::new(VecDeque::new())),
+            spread_persistence: Mutex::new(None),
         }
     }
     
+    /// 要求机会方向上的价差连续保持在 `min_spread_percentage` 以上至少 `min_duration` 才允许交易，
+    /// 价差历史来自引擎共享的行情缓存，缓存容量应覆盖该时长内的行情
+    pub fn with_spread_persistence(mut self, price_cache: Arc<PriceCache>, min_spread_percentage: Decimal, min_duration: Duration) -> Self {
+        *self.spread_persistence.get_mut().unwrap() = Some(SpreadPersistence {
+            price_cache,
+            min_spread_percentage,
+            min_duration,
+        });
+        self
+    }
+    
     /// 检查交易频率是否超过限制
-    fn check_frequency(&self) -> Result<(bool, Option<String>)> {
+    fn check_frequency(&self) -> Result<(bool, Option<Rejection>)> {
         let now = Utc::now();
+        let min_interval_seconds = self.min_interval_seconds.load(Ordering::Relaxed);
+        let max_trades_per_timeframe = self.max_trades_per_timeframe.load(Ordering::Relaxed);
+        let timeframe_seconds = self.timeframe_seconds.load(Ordering::Relaxed);
         
         // 1. 检查最小交易间隔
         if let Some(last_time) = *self.last_trade_time.lock().unwrap() {
             let elapsed = now - last_time;
-            if elapsed < Duration::seconds(self.min_interval_seconds) {
-                let remaining = self.min_interval_seconds - elapsed.num_seconds();
+            if elapsed < Duration::seconds(min_interval_seconds) {
+                let remaining = min_interval_seconds - elapsed.num_seconds();
                 let reason = format!(
                     "交易频率过高，需等待 {} 秒",
                     remaining
                 );
                 debug!("{}", reason);
-                return Ok((false, Some(reason)));
+                return Ok((false, Some(Rejection::new(RejectionReason::FrequencyLimited, reason))));
             }
         }
         
@@ -55,7 +142,7 @@ impl TradingFrequencyController {
         let mut recent_trades = self.recent_trades.lock().unwrap();
         
         // 清除时间窗口外的交易记录
-        let cutoff_time = now - Duration::seconds(self.timeframe_seconds);
+        let cutoff_time = now - Duration::seconds(timeframe_seconds);
         while let Some(trade_time) = recent_trades.front() {
             if *trade_time < cutoff_time {
                 recent_trades.pop_front();
@@ -65,33 +152,35 @@ impl TradingFrequencyController {
         }
         
         // 检查是否达到最大交易次数
-        if recent_trades.len() >= self.max_trades_per_timeframe {
+        if recent_trades.len() >= max_trades_per_timeframe {
             let reason = format!(
                 "达到时间窗口内({} 秒)最大交易次数: {}",
-                self.timeframe_seconds, self.max_trades_per_timeframe
+                timeframe_seconds, max_trades_per_timeframe
             );
             debug!("{}", reason);
-            return Ok((false, Some(reason)));
+            return Ok((false, Some(Rejection::new(RejectionReason::FrequencyLimited, reason))));
         }
         
         Ok((true, None))
     }
     
-    /// 记录新交易
-    fn record_trade(&self) {
-        let now = Utc::now();
+    /// 记录在 `time` 结束的交易，不晚于当前时间
+    ///
+    /// 按交易的结束时间记录，从数据库回放历史交易时间隔和时间窗口与实际交易一致。
+    fn record_trade(&self, time: DateTime<Utc>) {
+        let time = time.min(Utc::now());
         
         // 更新上次交易时间
         let mut last_trade_time = self.last_trade_time.lock().unwrap();
-        *last_trade_time = Some(now);
+        *last_trade_time = Some(last_trade_time.map_or(time, |last| last.max(time)));
         
         // 添加到最近交易记录
         let mut recent_trades = self.recent_trades.lock().unwrap();
-        recent_trades.push_back(now);
+        recent_trades.push_back(time);
         
         debug!(
             "记录交易: {}, 窗口内交易计数: {}/{}",
-            now, recent_trades.len(), self.max_trades_per_timeframe
+            time, recent_trades.len(), self.max_trades_per_timeframe.load(Ordering::Relaxed)
         );
     }
 }
@@ -106,14 +195,22 @@ impl RiskController for TradingFrequencyController {
         "控制套利交易的频率，避免API限制，同时防止在短时间内执行过多交易"
     }
     
-    async fn check_opportunity(&self, _opportunity: &ArbitrageOpportunity) -> Result<(bool, Option<String>)> {
+    async fn check_opportunity(&self, opportunity: &ArbitrageOpportunity) -> Result<(bool, Option<Rejection>)> {
+        let persistence_rejection = self.spread_persistence.lock().unwrap()
+            .as_ref()
+            .and_then(|persistence| persistence.check(opportunity, Utc::now()));
+        if let Some(reason) = persistence_rejection {
+            debug!("{}", reason);
+            return Ok((false, Some(Rejection::new(RejectionReason::SpreadNotPersistent, reason))));
+        }
+        
         self.check_frequency()
     }
     
     async fn record_result(&self, result: &ArbitrageResult) -> Result<()> {
-        if result.status == ArbitrageStatus::Completed || result.status == ArbitrageStatus::Failed {
-            // 只记录已完成或失败的交易
-            self.record_trade();
+        if result.status.has_realized_pnl() || result.status == ArbitrageStatus::Failed {
+            // 只记录已结束（完成、回补或失败）的交易
+            self.record_trade(result.end_time);
             
             info!(
                 "记录交易结果: {} - 状态: {:?}, 时间: {}",
@@ -135,12 +232,50 @@ impl RiskController for TradingFrequencyController {
         
         Ok(())
     }
+    
+    fn update_settings(&self, settings: &RiskSettings) -> bool {
+        let settings = &settings.trading_frequency;
+        let mut spread_persistence = self.spread_persistence.lock().unwrap();
+        if settings.min_spread_persistence_ms > 0 {
+            // 行情缓存只在创建时传入，新启用价差持续要求时需要重新创建
+            let Some(persistence) = spread_persistence.as_mut() else {
+                return false;
+            };
+            persistence.min_spread_percentage = settings.min_spread_percentage;
+            persistence.min_duration = Duration::milliseconds(settings.min_spread_persistence_ms as i64);
+        } else {
+            *spread_persistence = None;
+        }
+        
+        self.min_interval_seconds.store(settings.min_interval_seconds, Ordering::Relaxed);
+        self.max_trades_per_timeframe.store(settings.max_trades_per_timeframe, Ordering::Relaxed);
+        self.timeframe_seconds.store(settings.timeframe_seconds, Ordering::Relaxed);
+        true
+    }
+    
+    fn state(&self) -> serde_json::Value {
+        let timeframe = Duration::seconds(self.timeframe_seconds.load(Ordering::Relaxed));
+        let since = Utc::now() - timeframe;
+        let recent_trades = self.recent_trades.lock().unwrap().iter().filter(|time| **time >= since).count();
+        let last_trade_time = *self.last_trade_time.lock().unwrap();
+        // 距离满足最小交易间隔还需等待的秒数
+        let min_interval = Duration::seconds(self.min_interval_seconds.load(Ordering::Relaxed));
+        let next_trade_in = last_trade_time
+            .map(|last| (last + min_interval - Utc::now()).num_seconds().max(0))
+            .unwrap_or(0);
+        serde_json::json!({
+            "last_trade_time": last_trade_time,
+            "next_trade_in_seconds": next_trade_in,
+            "trades_in_timeframe": recent_trades,
+            "max_trades_per_timeframe": self.max_trades_per_timeframe.load(Ordering::Relaxed),
+        })
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::models::{QuoteCurrency};
+    use crate::models::{QuoteCurrency, FillInfo, Price, TradingMode};
     use rust_decimal_macros::dec;
     
     #[tokio::test]
@@ -176,6 +311,14 @@ mod tests {
             sell_order_id: Some(2),
             status: ArbitrageStatus::Completed,
             timestamp: Utc::now(),
+            start_time: Utc::now(),
+            end_time: Utc::now(),
+            buy_fill: FillInfo::default(),
+            sell_fill: FillInfo::default(),
+            mode: TradingMode::Live,
+            config_hash: String::new(),
+            strategy: None,
+            opportunity_id: None,
         };
         
         controller.record_result(&result).await.unwrap();
@@ -183,7 +326,38 @@ mod tests {
         // 第二次检查应该失败，因为最小间隔是30秒
         let (valid, reason) = controller.check_opportunity(&opportunity).await.unwrap();
         assert!(!valid);
-        assert!(reason.unwrap().contains("交易频率过高"));
+        let rejection = reason.unwrap();
+        assert_eq!(rejection.reason, RejectionReason::FrequencyLimited);
+        assert!(rejection.message.contains("交易频率过高"));
+        let state = controller.state();
+        assert_eq!(state["trades_in_timeframe"], 1);
+        assert_eq!(state["max_trades_per_timeframe"], 5);
+        let next_trade_in = state["next_trade_in_seconds"].as_i64().unwrap();
+        assert!((29..=30).contains(&next_trade_in), "还需等待 {} 秒", next_trade_in);
+        
+        // 回放的历史交易按结束时间计入，超出时间窗口的不计数
+        let mut old_result = result.clone();
+        old_result.end_time = Utc::now() - Duration::seconds(700);
+        let replayed = TradingFrequencyController::new(30, 5, 600);
+        replayed.record_result(&old_result).await.unwrap();
+        assert_eq!(replayed.state()["trades_in_timeframe"], 0);
+        assert_eq!(replayed.state()["next_trade_in_seconds"], 0);
+        assert!(replayed.check_opportunity(&opportunity).await.unwrap().0);
+        
+        // 缩短最小间隔后保留交易记录，按新的时间窗口限制次数
+        let mut settings = RiskSettings::default();
+        settings.trading_frequency.min_interval_seconds = 0;
+        settings.trading_frequency.max_trades_per_timeframe = 1;
+        settings.trading_frequency.timeframe_seconds = 600;
+        settings.trading_frequency.min_spread_persistence_ms = 0;
+        assert!(controller.update_settings(&settings));
+        let (valid, reason) = controller.check_opportunity(&opportunity).await.unwrap();
+        assert!(!valid);
+        assert!(reason.unwrap().message.contains("最大交易次数: 1"));
+        
+        // 没有行情缓存时无法原地启用价差持续要求
+        settings.trading_frequency.min_spread_persistence_ms = 1000;
+        assert!(!controller.update_settings(&settings));
         
         // 重置控制器
         controller.reset().await.unwrap();
@@ -192,4 +366,67 @@ mod tests {
         let (valid, _) = controller.check_opportunity(&opportunity).await.unwrap();
         assert!(valid);
     }
+    
+    fn record_spread(cache: &PriceCache, received_at: DateTime<Utc>, usdt: Decimal, usdc: Decimal) {
+        for (symbol, price) in [("BTCUSDT", usdt), ("BTCUSDC", usdc)] {
+            let price = Price { symbol: symbol.to_string(), price, timestamp: received_at };
+            cache.record_at(&price, received_at);
+        }
+    }
+    
+    #[tokio::test]
+    async fn test_spread_persistence() {
+        let opportunity = ArbitrageOpportunity::new(
+            "BTC",
+            QuoteCurrency::USDT,
+            QuoteCurrency::USDC,
+            dec!(50000),
+            dec!(50100),
+            dec!(1000),
+        );
+        let now = Utc::now();
+        
+        // 价差在0.2%和0之间闪烁，每次持续不到1秒
+        let flickering = Arc::new(PriceCache::new(100, None));
+        for i in 0..10 {
+            let usdc = if i % 2 == 0 { dec!(50100) } else { dec!(50000) };
+            record_spread(&flickering, now - Duration::milliseconds(5000 - i * 500), dec!(50000), usdc);
+        }
+        let controller = TradingFrequencyController::new(0, 100, 600)
+            .with_spread_persistence(flickering.clone(), dec!(0.1), Duration::seconds(2));
+        let (valid, reason) = controller.check_opportunity(&opportunity).await.unwrap();
+        assert!(!valid);
+        assert!(reason.unwrap().message.contains("价差未达到"));
+        
+        // 价差刚回到阈值以上，持续时间不足
+        record_spread(&flickering, now - Duration::milliseconds(200), dec!(50000), dec!(50100));
+        let (valid, reason) = controller.check_opportunity(&opportunity).await.unwrap();
+        assert!(!valid);
+        let rejection = reason.unwrap();
+        assert_eq!(rejection.reason, RejectionReason::SpreadNotPersistent);
+        assert!(rejection.message.contains("价差仅持续"));
+        
+        // 价差持续3秒保持在阈值以上
+        let sustained = Arc::new(PriceCache::new(100, None));
+        record_spread(&sustained, now - Duration::seconds(5), dec!(50000), dec!(50000));
+        for i in 0..7 {
+            record_spread(&sustained, now - Duration::milliseconds(3000 - i * 500), dec!(50000), dec!(50080));
+        }
+        let controller = TradingFrequencyController::new(0, 100, 600)
+            .with_spread_persistence(sustained.clone(), dec!(0.1), Duration::seconds(2));
+        let (valid, reason) = controller.check_opportunity(&opportunity).await.unwrap();
+        assert!(valid, "{:?}", reason);
+        
+        // 反方向的价差不满足要求
+        let reversed = ArbitrageOpportunity::new(
+            "BTC",
+            QuoteCurrency::USDC,
+            QuoteCurrency::USDT,
+            dec!(50080),
+            dec!(50000),
+            dec!(1000),
+        );
+        let (valid, _) = controller.check_opportunity(&reversed).await.unwrap();
+        assert!(!valid);
+    }
 }
@@ -1,5 +1,6 @@
 use super::RiskController;
-use crate::models::{ArbitrageOpportunity, ArbitrageResult, ArbitrageStatus};
+use crate::config::RiskSettings;
+use crate::models::{ArbitrageOpportunity, ArbitrageResult, Rejection, RejectionReason};
 use anyhow::Result;
 use async_trait::async_trait;
 use chrono::{DateTime, Utc, Local, Datelike};
@@ -11,8 +12,8 @@ use std::sync::{Arc, Mutex};
 /// 每日亏损限制控制器
 /// 限制每日最大亏损金额，超过限制后停止交易
 pub struct DailyLossLimitController {
-    /// 每日最大亏损金额
-    max_daily_loss: Decimal,
+    /// 每日最大亏损金额，热加载配置时更新
+    max_daily_loss: Mutex<Decimal>,
     /// 当前日期
     current_date: Arc<Mutex<(i32, u32, u32)>>, // (year, month, day)
     /// 当日累计盈亏
@@ -25,7 +26,7 @@ impl DailyLossLimitController {
         let current_date = (now.year(), now.month(), now.day());
         
         Self {
-            max_daily_loss,
+            max_daily_loss: Mutex::new(max_daily_loss),
             current_date: Arc::new(Mutex::new(current_date)),
             daily_pnl: Arc::new(Mutex::new(dec!(0))),
         }
@@ -59,20 +60,21 @@ impl RiskController for DailyLossLimitController {
         "限制每日最大亏损金额，超过限制后停止交易"
     }
     
-    async fn check_opportunity(&self, _opportunity: &ArbitrageOpportunity) -> Result<(bool, Option<String>)> {
+    async fn check_opportunity(&self, _opportunity: &ArbitrageOpportunity) -> Result<(bool, Option<Rejection>)> {
         // 检查是否为新的一天
         self.check_new_day();
         
-        // 检查当前亏损是否超过限制
+        // 检查当前亏损是否超过限制，刚好等于限额时仍允许交易
         let daily_pnl = *self.daily_pnl.lock().unwrap();
+        let max_daily_loss = *self.max_daily_loss.lock().unwrap();
         
-        if daily_pnl <= -self.max_daily_loss {
+        if daily_pnl < -max_daily_loss {
             let reason = format!(
-                "已达到每日最大亏损限额: {:.2}，今日累计: {:.2}",
-                self.max_daily_loss, daily_pnl
+                "已超过每日最大亏损限额: {:.2}，今日累计: {:.2}",
+                max_daily_loss, daily_pnl
             );
             warn!("{}", reason);
-            return Ok((false, Some(reason)));
+            return Ok((false, Some(Rejection::new(RejectionReason::DailyLossLimit, reason))));
         }
         
         Ok((true, None))
@@ -82,8 +84,8 @@ impl RiskController for DailyLossLimitController {
         // 检查是否为新的一天
         self.check_new_day();
         
-        // 只记录已完成的交易
-        if result.status == ArbitrageStatus::Completed {
+        // 只记录产生已实现盈亏的交易（已完成或卖出失败后回补）
+        if result.status.has_realized_pnl() {
             let mut daily_pnl = self.daily_pnl.lock().unwrap();
             *daily_pnl += result.profit;
             
@@ -104,12 +106,29 @@ impl RiskController for DailyLossLimitController {
         
         Ok(())
     }
+    
+    fn update_settings(&self, settings: &RiskSettings) -> bool {
+        *self.max_daily_loss.lock().unwrap() = settings.daily_loss_limit.max_daily_loss;
+        true
+    }
+    
+    fn state(&self) -> serde_json::Value {
+        self.check_new_day();
+        let max_daily_loss = *self.max_daily_loss.lock().unwrap();
+        let daily_pnl = *self.daily_pnl.lock().unwrap();
+        serde_json::json!({
+            "daily_pnl": daily_pnl,
+            "max_daily_loss": max_daily_loss,
+            "remaining_loss": (max_daily_loss + daily_pnl).max(Decimal::ZERO),
+            "limit_reached": daily_pnl < -max_daily_loss,
+        })
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::models::{QuoteCurrency, ArbitrageStatus};
+    use crate::models::{QuoteCurrency, ArbitrageStatus, FillInfo, TradingMode};
     use chrono::Utc;
     
     #[tokio::test]
@@ -144,6 +163,14 @@ mod tests {
             sell_order_id: Some(2),
             status: ArbitrageStatus::Completed,
             timestamp: Utc::now(),
+            start_time: Utc::now(),
+            end_time: Utc::now(),
+            buy_fill: FillInfo::default(),
+            sell_fill: FillInfo::default(),
+            mode: TradingMode::Live,
+            config_hash: String::new(),
+            strategy: None,
+            opportunity_id: None,
         };
         
         // 记录亏损
@@ -152,6 +179,10 @@ mod tests {
         // 应该还能通过检查
         let (valid, _) = controller.check_opportunity(&opportunity).await.unwrap();
         assert!(valid);
+        let state = controller.state();
+        assert_eq!(state["daily_pnl"], "-50");
+        assert_eq!(state["remaining_loss"], "50");
+        assert_eq!(state["limit_reached"], false);
         
         // 再记录一次相同的亏损（总亏损100）
         controller.record_result(&loss_result).await.unwrap();
@@ -159,6 +190,9 @@ mod tests {
         // 应该刚好达到限额，但还能通过
         let (valid, _) = controller.check_opportunity(&opportunity).await.unwrap();
         assert!(valid);
+        let state = controller.state();
+        assert_eq!(state["remaining_loss"], "0");
+        assert_eq!(state["limit_reached"], false);
         
         // 再记录一次亏损（总亏损150）
         controller.record_result(&loss_result).await.unwrap();
@@ -166,7 +200,21 @@ mod tests {
         // 现在应该被拒绝
         let (valid, reason) = controller.check_opportunity(&opportunity).await.unwrap();
         assert!(!valid);
-        assert!(reason.unwrap().contains("已达到每日最大亏损限额"));
+        let rejection = reason.unwrap();
+        assert_eq!(rejection.reason, RejectionReason::DailyLossLimit);
+        assert!(rejection.message.contains("已超过每日最大亏损限额"));
+        let state = controller.state();
+        assert_eq!(state["daily_pnl"], "-150");
+        assert_eq!(state["remaining_loss"], "0");
+        assert_eq!(state["limit_reached"], true);
+        
+        // 提高限额后保留当日亏损，重新通过
+        let mut settings = RiskSettings::default();
+        settings.daily_loss_limit.max_daily_loss = dec!(200);
+        assert!(controller.update_settings(&settings));
+        let (valid, _) = controller.check_opportunity(&opportunity).await.unwrap();
+        assert!(valid);
+        assert_eq!(*controller.daily_pnl.lock().unwrap(), dec!(-150));
         
         // 重置后应该又能通过
         controller.reset().await.unwrap();
@@ -1,10 +1,13 @@
-use crate::models::{ArbitrageOpportunity, ArbitrageResult, QuoteCurrency};
-use crate::config::Config;
+use crate::models::{ArbitrageOpportunity, ArbitrageResult, QuoteCurrency, Rejection, RejectionReason};
+use crate::config::{Config, RiskSettings};
 use anyhow::Result;
 use async_trait::async_trait;
 use chrono::{DateTime, Utc, NaiveTime};
+use log::debug;
 use rust_decimal::Decimal;
-use std::sync::Arc;
+use serde::Serialize;
+use std::fmt;
+use std::sync::{Arc, Mutex};
 use std::collections::HashMap;
 
 /// 风险控制组件接口
@@ -18,19 +21,98 @@ pub trait RiskController: Send + Sync {
     
     /// 检查套利机会是否可以执行
     /// 返回: 是否可以执行, 拒绝原因(如果不可执行)
-    async fn check_opportunity(&self, opportunity: &ArbitrageOpportunity) -> Result<(bool, Option<String>)>;
+    async fn check_opportunity(&self, opportunity: &ArbitrageOpportunity) -> Result<(bool, Option<Rejection>)>;
     
     /// 记录套利结果
     async fn record_result(&self, result: &ArbitrageResult) -> Result<()>;
     
     /// 重置风险控制器状态
     async fn reset(&self) -> Result<()>;
+    
+    /// 热加载配置时原地更新风控参数，保留已累积的状态（如当日盈亏、冷却期）
+    ///
+    /// 返回false表示不支持原地更新，由调用方重新创建风控组件。
+    fn update_settings(&self, _settings: &RiskSettings) -> bool {
+        false
+    }
+    
+    /// 当前状态（如当日盈亏、冷却期），供管理接口和 `risk-status` 命令查看；没有需要展示的状态时为 `null`
+    fn state(&self) -> serde_json::Value {
+        serde_json::Value::Null
+    }
+}
+
+/// 风控组件的名称、描述和当前状态
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RiskControllerState {
+    pub name: String,
+    pub description: String,
+    pub state: serde_json::Value,
+    /// 最近一次拒绝套利机会的时间和原因，没有拒绝过时为None
+    pub last_rejection: Option<LastRejection>,
+}
+
+/// 每个状态字段一行，格式为 `key: value`
+impl fmt::Display for RiskControllerState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} - {}", self.name, self.description)?;
+        if let serde_json::Value::Object(fields) = &self.state {
+            for (key, value) in fields {
+                match value {
+                    serde_json::Value::String(text) => write!(f, "\n  {}: {}", key, text)?,
+                    serde_json::Value::Null => write!(f, "\n  {}: -", key)?,
+                    other => write!(f, "\n  {}: {}", key, other)?,
+                }
+            }
+        }
+        match &self.last_rejection {
+            Some(rejection) => write!(
+                f,
+                "\n  最近拒绝: {} ({}) {}",
+                rejection.time.format("%Y-%m-%d %H:%M:%S"),
+                rejection.reason,
+                rejection.message
+            ),
+            None => write!(f, "\n  最近拒绝: -"),
+        }
+    }
+}
+
+/// 风控组件最近一次拒绝套利机会的记录
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LastRejection {
+    pub time: DateTime<Utc>,
+    pub reason: RejectionReason,
+    pub message: String,
+}
+
+/// 风控组件拒绝套利机会的记录
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ControllerRejection {
+    /// 拒绝的组件名称
+    pub controller: String,
+    pub rejection: Rejection,
+}
+
+impl ControllerRejection {
+    pub fn reason(&self) -> RejectionReason {
+        self.rejection.reason
+    }
+}
+
+/// 格式为 `组件名称: 原因`，与数据库中记录的拒绝原因相同
+impl std::fmt::Display for ControllerRejection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.controller, self.rejection)
+    }
 }
 
 /// 风控管理器，集成多个风险控制组件
 pub struct RiskManager {
     config: Arc<Config>,
     controllers: Vec<Box<dyn RiskController>>,
+    /// 各组件最近一次拒绝套利机会的记录，按组件名称索引
+    last_rejections: Mutex<HashMap<String, LastRejection>>,
 }
 
 impl RiskManager {
@@ -38,6 +120,7 @@ impl RiskManager {
         Self {
             config: Arc::new(config),
             controllers: Vec::new(),
+            last_rejections: Mutex::new(HashMap::new()),
         }
     }
     
@@ -47,24 +130,25 @@ impl RiskManager {
     }
     
     /// 检查套利机会是否通过所有风控规则
-    pub async fn validate_opportunity(&self, opportunity: &ArbitrageOpportunity) -> Result<(bool, Vec<String>)> {
+    ///
+    /// 返回是否全部通过，以及各个拒绝组件的名称和原因。
+    pub async fn validate_opportunity(&self, opportunity: &ArbitrageOpportunity) -> Result<(bool, Vec<ControllerRejection>)> {
         let mut is_valid = true;
         let mut rejection_reasons = Vec::new();
         
         for controller in &self.controllers {
-            match controller.check_opportunity(opportunity).await {
-                Ok((valid, reason)) => {
-                    if !valid {
-                        is_valid = false;
-                        if let Some(reason_str) = reason {
-                            rejection_reasons.push(format!("{}: {}", controller.name(), reason_str));
-                        }
-                    }
-                },
-                Err(e) => {
-                    rejection_reasons.push(format!("{}: 风控检查错误 - {}", controller.name(), e));
-                    is_valid = false;
-                }
+            let rejection = match controller.check_opportunity(opportunity).await {
+                Ok((true, _)) => continue,
+                Ok((false, rejection)) => rejection,
+                Err(e) => Some(Rejection::new(RejectionReason::CheckFailed, format!("风控检查错误 - {}", e))),
+            };
+            is_valid = false;
+            if let Some(rejection) = rejection {
+                self.last_rejections.lock().unwrap().insert(
+                    controller.name().to_string(),
+                    LastRejection { time: Utc::now(), reason: rejection.reason, message: rejection.message.clone() },
+                );
+                rejection_reasons.push(ControllerRejection { controller: controller.name().to_string(), rejection });
             }
         }
         
@@ -80,11 +164,57 @@ impl RiskManager {
         Ok(())
     }
     
+    /// 热加载配置时更新所有风控组件的参数，保留各组件的状态
+    ///
+    /// 有组件不支持原地更新时返回false，调用方应重新创建风控管理器。
+    pub fn update_settings(&self, settings: &RiskSettings) -> bool {
+        self.controllers.iter().all(|controller| {
+            let updated = controller.update_settings(settings);
+            if !updated {
+                debug!("风控组件 {} 不支持原地更新参数", controller.name());
+            }
+            updated
+        })
+    }
+    
+    /// 已启用的风控组件名称
+    pub fn controller_names(&self) -> Vec<String> {
+        self.controllers.iter().map(|c| c.name().to_string()).collect()
+    }
+    
+    /// 各风控组件的当前状态和最近一次拒绝，按启用顺序排列
+    pub fn controller_states(&self) -> Vec<RiskControllerState> {
+        let last_rejections = self.last_rejections.lock().unwrap();
+        self.controllers
+            .iter()
+            .map(|c| RiskControllerState {
+                name: c.name().to_string(),
+                description: c.description().to_string(),
+                state: c.state(),
+                last_rejection: last_rejections.get(c.name()).cloned(),
+            })
+            .collect()
+    }
+    
+    /// 按名称重置风控组件，返回是否找到该组件
+    pub async fn reset_controller(&self, name: &str) -> Result<bool> {
+        for controller in &self.controllers {
+            if controller.name() == name {
+                controller.reset().await?;
+                self.last_rejections.lock().unwrap().remove(name);
+                return Ok(true);
+            }
+        }
+        
+        Ok(false)
+    }
+    
     /// 重置所有风控组件
     pub async fn reset_all(&self) -> Result<()> {
         for controller in &self.controllers {
             controller.reset().await?;
         }
+        self.last_rejections.lock().unwrap().clear();
         
         Ok(())
     }
@@ -97,6 +227,7 @@ pub mod exposure;
 pub mod time_window;
 pub mod frequency;
 pub mod blacklist;
+pub mod open_positions;
 
 // 重导出风控组件
 pub use loss_limit::DailyLossLimitController;
@@ -104,4 +235,36 @@ pub use price_protection::AbnormalPriceController;
 pub use exposure::ExposureController;
 pub use time_window::TradingTimeWindowController;
 pub use frequency::TradingFrequencyController;
-pub use blacklist::PairBlacklistController;
+pub use blacklist::{BlacklistEntry, PairBlacklistController};
+pub use open_positions::{OpenPositionGuard, OpenPositionTracker, OpenPositionsController};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+    
+    #[tokio::test]
+    async fn test_controller_states_report_last_rejection() {
+        let mut manager = RiskManager::new(Config::default());
+        manager.add_controller(PairBlacklistController::from_entries(&["BTC".to_string()]));
+        manager.add_controller(DailyLossLimitController::new(dec!(100)));
+        let opportunity = ArbitrageOpportunity::new("BTC", QuoteCurrency::USDT, QuoteCurrency::USDC, dec!(50000), dec!(50100), dec!(1000));
+        
+        assert!(!manager.validate_opportunity(&opportunity).await.unwrap().0);
+        let states = manager.controller_states();
+        let rejection = states[0].last_rejection.as_ref().unwrap();
+        assert_eq!(rejection.reason, RejectionReason::Blacklisted);
+        assert!(rejection.message.contains("BTC 在黑名单中"));
+        assert!(states[1].last_rejection.is_none());
+        
+        let text = states[1].to_string();
+        assert!(text.starts_with("每日亏损限制 - "));
+        assert!(text.contains("\n  daily_pnl: 0\n"));
+        assert!(text.contains("\n  limit_reached: false\n"));
+        assert!(text.ends_with("\n  最近拒绝: -"));
+        assert!(states[0].to_string().contains("\n  blacklist: [\"BTC\"]\n  最近拒绝: "));
+        
+        manager.reset_all().await.unwrap();
+        assert!(manager.controller_states()[0].last_rejection.is_none());
+    }
+}
@@ -0,0 +1,155 @@
+use super::RiskController;
+use crate::config::RiskSettings;
+use crate::models::{ArbitrageOpportunity, ArbitrageResult, Rejection, RejectionReason};
+use anyhow::Result;
+use async_trait::async_trait;
+use log::{debug, info};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// 正在执行的套利计数，可在多个引擎间共享（`Arc<OpenPositionTracker>`）
+///
+/// 引擎开始执行套利时调用 `open` 计数加一，返回的守卫在套利完成、失败或任务被取消时释放并减一。
+#[derive(Debug, Default)]
+pub struct OpenPositionTracker {
+    open: AtomicUsize,
+}
+
+impl OpenPositionTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 当前正在执行的套利数量
+    pub fn count(&self) -> usize {
+        self.open.load(Ordering::SeqCst)
+    }
+
+    /// 记录一笔开始执行的套利，守卫被释放时计数减一
+    pub fn open(self: &Arc<Self>) -> OpenPositionGuard {
+        let count = self.open.fetch_add(1, Ordering::SeqCst) + 1;
+        debug!("开始执行套利，当前持仓数: {}", count);
+        OpenPositionGuard { tracker: self.clone() }
+    }
+}
+
+/// 一笔正在执行的套利，释放时从计数中移除
+#[derive(Debug)]
+pub struct OpenPositionGuard {
+    tracker: Arc<OpenPositionTracker>,
+}
+
+impl Drop for OpenPositionGuard {
+    fn drop(&mut self) {
+        let count = self.tracker.open.fetch_sub(1, Ordering::SeqCst) - 1;
+        debug!("套利执行结束，当前持仓数: {}", count);
+    }
+}
+
+/// 最大同时持仓控制器
+/// 限制同时执行中的套利数量，多币种运行时多个引擎共享同一个计数
+pub struct OpenPositionsController {
+    /// 正在执行的套利计数
+    tracker: Arc<OpenPositionTracker>,
+    /// 最大同时持仓数
+    max_open_positions: AtomicUsize,
+}
+
+impl OpenPositionsController {
+    pub fn new(tracker: Arc<OpenPositionTracker>, max_open_positions: usize) -> Self {
+        Self {
+            tracker,
+            max_open_positions: AtomicUsize::new(max_open_positions),
+        }
+    }
+}
+
+#[async_trait]
+impl RiskController for OpenPositionsController {
+    fn name(&self) -> &str {
+        "最大持仓数限制"
+    }
+
+    fn description(&self) -> &str {
+        "限制同时执行中的套利数量，达到上限时拒绝新的套利机会"
+    }
+
+    async fn check_opportunity(&self, _opportunity: &ArbitrageOpportunity) -> Result<(bool, Option<Rejection>)> {
+        let open_positions = self.tracker.count();
+        let max_open_positions = self.max_open_positions.load(Ordering::Relaxed);
+        if open_positions >= max_open_positions {
+            let reason = format!(
+                "正在执行的套利数 {} 已达到上限 {}",
+                open_positions, max_open_positions
+            );
+            debug!("{}", reason);
+            return Ok((false, Some(Rejection::new(RejectionReason::MaxOpenPositions, reason))));
+        }
+
+        Ok((true, None))
+    }
+
+    async fn record_result(&self, _result: &ArbitrageResult) -> Result<()> {
+        // 持仓数由引擎在执行套利时维护
+        Ok(())
+    }
+
+    async fn reset(&self) -> Result<()> {
+        // 计数反映实际执行中的套利，重置不清空计数
+        info!("重置最大持仓数限制控制器，当前持仓数: {}", self.tracker.count());
+        Ok(())
+    }
+
+    fn update_settings(&self, settings: &RiskSettings) -> bool {
+        self.max_open_positions.store(settings.open_positions.max_open_positions, Ordering::Relaxed);
+        true
+    }
+
+    fn state(&self) -> serde_json::Value {
+        serde_json::json!({
+            "open_positions": self.tracker.count(),
+            "max_open_positions": self.max_open_positions.load(Ordering::Relaxed),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::QuoteCurrency;
+    use rust_decimal_macros::dec;
+
+    #[tokio::test]
+    async fn test_open_positions_limit() {
+        let tracker = Arc::new(OpenPositionTracker::new());
+        let controller = OpenPositionsController::new(tracker.clone(), 2);
+        let opportunity = ArbitrageOpportunity::new(
+            "BTC",
+            QuoteCurrency::USDT,
+            QuoteCurrency::USDC,
+            dec!(50000),
+            dec!(50100),
+            dec!(1000),
+        );
+
+        let first = tracker.open();
+        let (valid, _) = controller.check_opportunity(&opportunity).await.unwrap();
+        assert!(valid);
+
+        let second = tracker.open();
+        assert_eq!(tracker.count(), 2);
+        let (valid, reason) = controller.check_opportunity(&opportunity).await.unwrap();
+        assert!(!valid);
+        let rejection = reason.unwrap();
+        assert_eq!(rejection.reason, RejectionReason::MaxOpenPositions);
+        assert!(rejection.message.contains("上限 2"));
+        assert_eq!(controller.state(), serde_json::json!({ "open_positions": 2, "max_open_positions": 2 }));
+
+        // 套利结束后释放名额
+        drop(first);
+        let (valid, _) = controller.check_opportunity(&opportunity).await.unwrap();
+        assert!(valid);
+        drop(second);
+        assert_eq!(tracker.count(), 0);
+    }
+}
@@ -1,5 +1,7 @@
 use super::RiskController;
-use crate::models::{ArbitrageOpportunity, ArbitrageResult};
+use crate::config::RiskSettings;
+use crate::market_data::PriceCache;
+use crate::models::{ArbitrageOpportunity, ArbitrageResult, Price, Rejection, RejectionReason};
 use anyhow::Result;
 use async_trait::async_trait;
 use chrono::{DateTime, Utc, Duration};
@@ -7,80 +9,81 @@ use log::{debug, info, warn, error};
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
 use std::sync::{Arc, Mutex};
-use std::collections::VecDeque;
-
-/// 价格记录
-#[derive(Debug, Clone)]
-struct PriceRecord {
-    timestamp: DateTime<Utc>,
-    symbol: String,
-    price: Decimal,
-}
+use std::sync::atomic::{AtomicI64, AtomicUsize, Ordering};
 
 /// 异常价格保护控制器
 /// 检测极端价格波动，暂停交易以防止在异常市场条件下交易
 pub struct AbnormalPriceController {
     /// 价格历史记录
-    price_history: Arc<Mutex<VecDeque<PriceRecord>>>,
-    /// 窗口大小（保留的价格记录数量）
-    window_size: usize,
+    price_history: Arc<PriceCache>,
+    /// 是否使用引擎共享的行情缓存；共享时行情由引擎写入，检查套利机会时不再重复记录
+    shared_cache: bool,
+    /// 重置时间，只使用重置之后的行情
+    window_start: Mutex<Option<DateTime<Utc>>>,
+    /// 窗口大小（每个交易对参与检测的价格记录数量）
+    window_size: AtomicUsize,
     /// 异常价格变化阈值（百分比）
-    abnormal_threshold: Decimal,
+    abnormal_threshold: Mutex<Decimal>,
     /// 冷却期（秒），在检测到异常后暂停交易的时间
-    cooldown_period: i64,
+    cooldown_period: AtomicI64,
     /// 最后一次异常检测时间
     last_abnormal_time: Arc<Mutex<Option<DateTime<Utc>>>>,
 }
 
 impl AbnormalPriceController {
+    /// 创建使用独立价格历史的控制器，价格只在检查套利机会时记录
     pub fn new(window_size: usize, abnormal_threshold: Decimal, cooldown_period: i64) -> Self {
+        Self::build(Arc::new(PriceCache::new(window_size, None)), false, window_size, abnormal_threshold, cooldown_period)
+    }
+    
+    /// 创建使用共享行情缓存的控制器，缓存中的每条行情都参与异常检测，包括未形成套利机会的行情
+    pub fn with_price_cache(cache: Arc<PriceCache>, window_size: usize, abnormal_threshold: Decimal, cooldown_period: i64) -> Self {
+        if cache.capacity() < window_size {
+            warn!("行情缓存容量 {} 小于异常价格保护窗口 {}，检测只使用缓存中的行情", cache.capacity(), window_size);
+        }
+        Self::build(cache, true, window_size, abnormal_threshold, cooldown_period)
+    }
+    
+    fn build(cache: Arc<PriceCache>, shared_cache: bool, window_size: usize, abnormal_threshold: Decimal, cooldown_period: i64) -> Self {
         Self {
-            price_history: Arc::new(Mutex::new(VecDeque::with_capacity(window_size * 2))),
-            window_size,
-            abnormal_threshold,
-            cooldown_period,
+            price_history: cache,
+            shared_cache,
+            window_start: Mutex::new(None),
+            window_size: AtomicUsize::new(window_size),
+            abnormal_threshold: Mutex::new(abnormal_threshold),
+            cooldown_period: AtomicI64::new(cooldown_period),
             last_abnormal_time: Arc::new(Mutex::new(None)),
         }
     }
     
     /// 添加价格记录
     pub fn add_price(&self, symbol: &str, price: Decimal) {
-        let record = PriceRecord {
-            timestamp: Utc::now(),
+        self.price_history.record(&Price {
             symbol: symbol.to_string(),
             price,
-        };
-        
-        let mut history = self.price_history.lock().unwrap();
-        
-        // 添加新记录
-        history.push_back(record);
-        
-        // 保持窗口大小
-        if history.len() > self.window_size * 2 {  // 为每个交易对保留window_size个记录
-            history.pop_front();
-        }
+            timestamp: Utc::now(),
+        });
     }
     
     /// 检测异常价格变化
     fn detect_abnormal_price(&self, symbol: &str) -> Option<Decimal> {
-        let history = self.price_history.lock().unwrap();
+        let since = *self.window_start.lock().unwrap();
+        let history = self.price_history.history(symbol, since);
         
-        // 获取指定交易对的价格记录
-        let symbol_records: Vec<_> = history.iter()
-            .filter(|record| record.symbol == symbol)
-            .collect();
+        // 获取指定交易对最近的价格记录
+        let window_size = self.window_size.load(Ordering::Relaxed);
+        let symbol_records = &history[history.len().saturating_sub(window_size)..];
             
         if symbol_records.len() < 2 {
             return None;  // 没有足够的数据进行分析
         }
         
         // 计算最新价格相对于过去价格的变化
-        let latest = symbol_records.last().unwrap();
+        let latest = &symbol_records.last().unwrap().price;
         let previous_records = &symbol_records[..symbol_records.len() - 1];
         
         // 计算过去价格的平均值
-        let sum: Decimal = previous_records.iter().map(|r| r.price).sum();
+        let sum: Decimal = previous_records.iter().map(|r| r.price.price).sum();
         let avg_price = sum / Decimal::from(previous_records.len());
         
         if avg_price.is_zero() {
@@ -90,27 +93,30 @@ impl AbnormalPriceController {
         // 计算价格变化百分比
         let change_pct = ((latest.price - avg_price) / avg_price).abs() * dec!(100);
         
-        if change_pct > self.abnormal_threshold {
+        if change_pct > *self.abnormal_threshold.lock().unwrap() {
             Some(change_pct)
         } else {
             None
         }
     }
     
+    /// 冷却期剩余的秒数，不在冷却期内时为None
+    fn cooldown_remaining(&self) -> Option<i64> {
+        let last_time = (*self.last_abnormal_time.lock().unwrap())?;
+        let elapsed = Utc::now() - last_time;
+        let cooldown_period = self.cooldown_period.load(Ordering::Relaxed);
+        (elapsed < Duration::seconds(cooldown_period)).then(|| cooldown_period - elapsed.num_seconds())
+    }
+    
     /// 检查是否在冷却期内
     fn is_in_cooldown(&self) -> bool {
-        if let Some(last_time) = *self.last_abnormal_time.lock().unwrap() {
-            let elapsed = Utc::now() - last_time;
-            
-            // 如果冷却期尚未结束
-            if elapsed < Duration::seconds(self.cooldown_period) {
-                let remaining = self.cooldown_period - elapsed.num_seconds();
+        match self.cooldown_remaining() {
+            Some(remaining) => {
                 debug!("仍在冷却期内，剩余 {} 秒", remaining);
-                return true;
-            }
+                true
+            },
+            None => false,
         }
-        
-        false
     }
 }
 
@@ -124,54 +130,56 @@ impl RiskController for AbnormalPriceController {
         "检测极端价格波动，暂停交易以防止在异常市场条件下交易"
     }
     
-    async fn check_opportunity(&self, opportunity: &ArbitrageOpportunity) -> Result<(bool, Option<String>)> {
+    async fn check_opportunity(&self, opportunity: &ArbitrageOpportunity) -> Result<(bool, Option<Rejection>)> {
         // 构造交易对名称
         let usdt_symbol = format!("{}{}", opportunity.base_asset, "USDT");
         let usdc_symbol = format!("{}{}", opportunity.base_asset, "USDC");
         
-        // 添加价格记录
-        match opportunity.buy_quote {
-            crate::models::QuoteCurrency::USDT => {
-                self.add_price(&usdt_symbol, opportunity.buy_price);
-                self.add_price(&usdc_symbol, opportunity.sell_price);
-            },
-            crate::models::QuoteCurrency::USDC => {
-                self.add_price(&usdc_symbol, opportunity.buy_price);
-                self.add_price(&usdt_symbol, opportunity.sell_price);
-            },
+        // 添加价格记录，使用共享缓存时行情已由引擎写入
+        if !self.shared_cache {
+            match opportunity.buy_quote {
+                crate::models::QuoteCurrency::USDT => {
+                    self.add_price(&usdt_symbol, opportunity.buy_price);
+                    self.add_price(&usdc_symbol, opportunity.sell_price);
+                },
+                crate::models::QuoteCurrency::USDC => {
+                    self.add_price(&usdc_symbol, opportunity.buy_price);
+                    self.add_price(&usdt_symbol, opportunity.sell_price);
+                },
+            }
         }
         
         // 检查是否在冷却期内
         if self.is_in_cooldown() {
             let reason = "仍在异常价格冷却期内，暂停交易".to_string();
-            return Ok((false, Some(reason)));
+            return Ok((false, Some(Rejection::new(RejectionReason::AbnormalPrice, reason))));
         }
         
         // 检测异常价格
         if let Some(change_pct) = self.detect_abnormal_price(&usdt_symbol) {
             let reason = format!(
                 "检测到 {} 异常价格变化: {:.2}% > 阈值 {:.2}%",
-                usdt_symbol, change_pct, self.abnormal_threshold
+                usdt_symbol, change_pct, *self.abnormal_threshold.lock().unwrap()
             );
             warn!("{}", reason);
             
             // 设置冷却期
             *self.last_abnormal_time.lock().unwrap() = Some(Utc::now());
             
-            return Ok((false, Some(reason)));
+            return Ok((false, Some(Rejection::new(RejectionReason::AbnormalPrice, reason))));
         }
         
         if let Some(change_pct) = self.detect_abnormal_price(&usdc_symbol) {
             let reason = format!(
                 "检测到 {} 异常价格变化: {:.2}% > 阈值 {:.2}%",
-                usdc_symbol, change_pct, self.abnormal_threshold
+                usdc_symbol, change_pct, *self.abnormal_threshold.lock().unwrap()
             );
             warn!("{}", reason);
             
             // 设置冷却期
             *self.last_abnormal_time.lock().unwrap() = Some(Utc::now());
             
-            return Ok((false, Some(reason)));
+            return Ok((false, Some(Rejection::new(RejectionReason::AbnormalPrice, reason))));
         }
         
         Ok((true, None))
@@ -183,8 +191,12 @@ impl RiskController for AbnormalPriceController {
     }
     
     async fn reset(&self) -> Result<()> {
-        let mut history = self.price_history.lock().unwrap();
-        history.clear();
+        // 共享缓存还被其他组件使用，不清空，只忽略重置之前的行情
+        if self.shared_cache {
+            *self.window_start.lock().unwrap() = Some(Utc::now());
+        } else {
+            self.price_history.clear();
+        }
         
         let mut last_time = self.last_abnormal_time.lock().unwrap();
         *last_time = None;
@@ -193,6 +205,31 @@ impl RiskController for AbnormalPriceController {
         
         Ok(())
     }
+    
+    fn update_settings(&self, settings: &RiskSettings) -> bool {
+        let settings = &settings.abnormal_price;
+        // 独立的价格历史容量按创建时的窗口分配，无法扩大
+        if settings.window_size > self.price_history.capacity() {
+            if !self.shared_cache {
+                return false;
+            }
+            warn!("行情缓存容量 {} 小于异常价格保护窗口 {}，检测只使用缓存中的行情", self.price_history.capacity(), settings.window_size);
+        }
+        
+        self.window_size.store(settings.window_size, Ordering::Relaxed);
+        *self.abnormal_threshold.lock().unwrap() = settings.abnormal_threshold;
+        self.cooldown_period.store(settings.cooldown_period, Ordering::Relaxed);
+        true
+    }
+    
+    fn state(&self) -> serde_json::Value {
+        let cooldown_remaining = self.cooldown_remaining();
+        serde_json::json!({
+            "in_cooldown": cooldown_remaining.is_some(),
+            "cooldown_remaining_seconds": cooldown_remaining.unwrap_or(0),
+            "last_abnormal_time": *self.last_abnormal_time.lock().unwrap(),
+        })
+    }
 }
 
 #[cfg(test)]
@@ -239,6 +276,102 @@ mod tests {
         // 应该被拒绝
         let (valid, reason) = controller.check_opportunity(&opportunity).await.unwrap();
         assert!(!valid);
-        assert!(reason.unwrap().contains("检测到 BTCUSDT 异常价格变化"));
+        let rejection = reason.unwrap();
+        assert_eq!(rejection.reason, RejectionReason::AbnormalPrice);
+        assert!(rejection.message.contains("检测到 BTCUSDT 异常价格变化"));
+    }
+    
+    fn tick(symbol: &str, price: Decimal) -> Price {
+        Price { symbol: symbol.to_string(), price, timestamp: Utc::now() }
+    }
+    
+    #[tokio::test]
+    async fn test_shared_cache_ticks_populate_window() {
+        let cache = Arc::new(PriceCache::new(100, None));
+        let controller = AbnormalPriceController::with_price_cache(cache.clone(), 5, dec!(10), 60);
+        
+        // 行情写入缓存但没有形成套利机会，控制器从未检查过
+        for price in [dec!(50000), dec!(50100), dec!(50050), dec!(60000)] {
+            cache.record(&tick("BTCUSDT", price));
+        }
+        
+        // 机会本身的价格正常，但窗口内最新行情相对均值波动约20%
+        let opportunity = ArbitrageOpportunity::new(
+            "BTC",
+            QuoteCurrency::USDT,
+            QuoteCurrency::USDC,
+            dec!(50000),
+            dec!(50100),
+            dec!(1000),
+        );
+        let (valid, reason) = controller.check_opportunity(&opportunity).await.unwrap();
+        assert!(!valid);
+        assert!(reason.unwrap().message.contains("BTCUSDT"));
+        
+        // 共享缓存由引擎写入，检查机会时不重复记录价格
+        assert_eq!(cache.history("BTCUSDT", None).len(), 4);
+        assert!(cache.history("BTCUSDC", None).is_empty());
+        
+        // 重置后不清空共享缓存，只忽略重置之前的行情
+        controller.reset().await.unwrap();
+        assert_eq!(cache.history("BTCUSDT", None).len(), 4);
+        let (valid, _) = controller.check_opportunity(&opportunity).await.unwrap();
+        assert!(valid);
+    }
+    
+    #[tokio::test]
+    async fn test_update_settings_keeps_cooldown() {
+        let controller = AbnormalPriceController::new(5, dec!(10), 60);
+        controller.add_price("BTCUSDT", dec!(50000));
+        controller.add_price("BTCUSDT", dec!(50100));
+        // 约20%的波动
+        let opportunity = ArbitrageOpportunity::new("BTC", QuoteCurrency::USDT, QuoteCurrency::USDC, dec!(60000), dec!(60100), dec!(1000));
+        let (valid, _) = controller.check_opportunity(&opportunity).await.unwrap();
+        assert!(!valid);
+        
+        // 更新阈值后仍处于之前触发的冷却期
+        let mut settings = RiskSettings::default();
+        settings.abnormal_price.window_size = 5;
+        settings.abnormal_price.abnormal_threshold = dec!(50);
+        settings.abnormal_price.cooldown_period = 60;
+        assert!(controller.update_settings(&settings));
+        let (valid, reason) = controller.check_opportunity(&opportunity).await.unwrap();
+        assert!(!valid);
+        let rejection = reason.unwrap();
+        assert_eq!(rejection.reason, RejectionReason::AbnormalPrice);
+        assert!(rejection.message.contains("冷却期"));
+        
+        // 缩短冷却期后按新阈值检测，20%的波动不再视为异常
+        settings.abnormal_price.cooldown_period = 0;
+        assert!(controller.update_settings(&settings));
+        let (valid, _) = controller.check_opportunity(&opportunity).await.unwrap();
+        assert!(valid);
+        
+        // 独立的价格历史无法扩大窗口
+        settings.abnormal_price.window_size = 500;
+        assert!(!controller.update_settings(&settings));
+    }
+    
+    #[tokio::test]
+    async fn test_state_reports_cooldown() {
+        let controller = AbnormalPriceController::new(5, dec!(10), 60);
+        assert_eq!(controller.state()["in_cooldown"], false);
+        assert_eq!(controller.state()["cooldown_remaining_seconds"], 0);
+        
+        // 检查机会时记录买入价，相对之前的 50000 上涨12%，超过10%的阈值
+        controller.add_price("BTCUSDT", dec!(50000));
+        let opportunity = ArbitrageOpportunity::new("BTC", QuoteCurrency::USDT, QuoteCurrency::USDC, dec!(56000), dec!(56100), dec!(1000));
+        let (valid, rejection) = controller.check_opportunity(&opportunity).await.unwrap();
+        assert!(!valid);
+        assert_eq!(rejection.unwrap().reason, RejectionReason::AbnormalPrice);
+        
+        let state = controller.state();
+        assert_eq!(state["in_cooldown"], true);
+        let remaining = state["cooldown_remaining_seconds"].as_i64().unwrap();
+        assert!((59..=60).contains(&remaining), "剩余 {} 秒", remaining);
+        assert!(!state["last_abnormal_time"].is_null());
+        
+        controller.reset().await.unwrap();
+        assert!(controller.state()["last_abnormal_time"].is_null());
     }
 }
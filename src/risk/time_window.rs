@@ -1,14 +1,22 @@
 use super::RiskController;
-use crate::models::{ArbitrageOpportunity, ArbitrageResult};
+use crate::config::RiskSettings;
+use crate::models::{ArbitrageOpportunity, ArbitrageResult, Rejection, RejectionReason};
 use anyhow::Result;
 use async_trait::async_trait;
 use chrono::{DateTime, Utc, Local, NaiveTime, Timelike};
 use log::{debug, info, warn};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 /// 交易时间窗口控制器
 /// 限制只在特定时间段内进行交易，可用于避免低流动性时段或配合交易策略
 pub struct TradingTimeWindowController {
+    /// 允许交易的时段，热加载配置时整体替换
+    hours: Mutex<TradingHours>,
+}
+
+/// 允许交易的时段
+#[derive(Clone, Copy)]
+struct TradingHours {
     /// 允许交易的开始时间 (24小时制，如9:30)
     start_time: NaiveTime,
     /// 允许交易的结束时间 (24小时制，如16:00)
@@ -17,8 +25,8 @@ pub struct TradingTimeWindowController {
     trade_on_weekends: bool,
 }
 
-impl TradingTimeWindowController {
-    pub fn new(start_hour: u32, start_min: u32, end_hour: u32, end_min: u32, trade_on_weekends: bool) -> Result<Self> {
+impl TradingHours {
+    fn new(start_hour: u32, start_min: u32, end_hour: u32, end_min: u32, trade_on_weekends: bool) -> Result<Self> {
         let start_time = NaiveTime::from_hms_opt(start_hour, start_min, 0)
             .ok_or_else(|| anyhow::anyhow!("无效的开始时间: {}:{}", start_hour, start_min))?;
             
@@ -31,16 +39,24 @@ impl TradingTimeWindowController {
             trade_on_weekends,
         })
     }
+}
+
+impl TradingTimeWindowController {
+    pub fn new(start_hour: u32, start_min: u32, end_hour: u32, end_min: u32, trade_on_weekends: bool) -> Result<Self> {
+        let hours = TradingHours::new(start_hour, start_min, end_hour, end_min, trade_on_weekends)?;
+        Ok(Self { hours: Mutex::new(hours) })
+    }
     
     /// 检查当前时间是否在允许交易的时间窗口内
     fn is_within_trading_hours(&self) -> (bool, String) {
+        let hours = *self.hours.lock().unwrap();
         let now = Local::now();
         let current_time = now.time();
         let weekday = now.weekday().number_from_monday(); // 1 = 周一, 7 = 周日
         
         // 检查是否是周末
         let is_weekend = weekday >= 6; // 6 = 周六, 7 = 周日
-        if is_weekend && !self.trade_on_weekends {
+        if is_weekend && !hours.trade_on_weekends {
             return (
                 false, 
                 format!("当前是周末 ({}), 不在交易时段", 
@@ -50,12 +66,12 @@ impl TradingTimeWindowController {
         }
         
         // 检查是否在交易时间内
-        let is_trading_time = if self.start_time <= self.end_time {
+        let is_trading_time = if hours.start_time <= hours.end_time {
             // 简单情况：开始时间早于结束时间
-            current_time >= self.start_time && current_time <= self.end_time
+            current_time >= hours.start_time && current_time <= hours.end_time
         } else {
             // 复杂情况：开始时间晚于结束时间（跨午夜）
-            current_time >= self.start_time || current_time <= self.end_time
+            current_time >= hours.start_time || current_time <= hours.end_time
         };
         
         if is_trading_time {
@@ -66,8 +82,8 @@ impl TradingTimeWindowController {
                 format!(
                     "当前时间 {} 不在交易时段 {} - {} 内",
                     current_time.format("%H:%M"),
-                    self.start_time.format("%H:%M"),
-                    self.end_time.format("%H:%M")
+                    hours.start_time.format("%H:%M"),
+                    hours.end_time.format("%H:%M")
                 )
             )
         }
@@ -84,12 +100,12 @@ impl RiskController for TradingTimeWindowController {
         "限制只在特定时间段内进行交易，可用于避免低流动性时段或配合交易策略"
     }
     
-    async fn check_opportunity(&self, _opportunity: &ArbitrageOpportunity) -> Result<(bool, Option<String>)> {
+    async fn check_opportunity(&self, _opportunity: &ArbitrageOpportunity) -> Result<(bool, Option<Rejection>)> {
         let (is_valid, reason) = self.is_within_trading_hours();
         
         if !is_valid {
             debug!("交易时间检查: {}", reason);
-            return Ok((false, Some(reason)));
+            return Ok((false, Some(Rejection::new(RejectionReason::OutsideTimeWindow, reason))));
         }
         
         Ok((true, None))
@@ -104,6 +120,32 @@ impl RiskController for TradingTimeWindowController {
         // 这个控制器没有状态需要重置
         Ok(())
     }
+    
+    fn update_settings(&self, settings: &RiskSettings) -> bool {
+        let settings = &settings.trading_time_window;
+        match TradingHours::new(settings.start_hour, settings.start_minute, settings.end_hour, settings.end_minute, settings.trade_on_weekends) {
+            Ok(hours) => {
+                *self.hours.lock().unwrap() = hours;
+                true
+            },
+            Err(e) => {
+                warn!("交易时间窗口设置无效，保持原有时段: {}", e);
+                false
+            },
+        }
+    }
+    
+    fn state(&self) -> serde_json::Value {
+        let hours = *self.hours.lock().unwrap();
+        let (in_window, reason) = self.is_within_trading_hours();
+        serde_json::json!({
+            "in_window": in_window,
+            "reason": (!in_window).then_some(reason),
+            "start_time": hours.start_time.format("%H:%M").to_string(),
+            "end_time": hours.end_time.format("%H:%M").to_string(),
+            "trade_on_weekends": hours.trade_on_weekends,
+        })
+    }
 }
 
 #[cfg(test)]
@@ -135,4 +177,22 @@ mod tests {
         // 由于我们无法确定测试运行时的时间，所以这里不做具体断言
         println!("交易时间窗口测试结果: {}, 原因: {:?}", valid, reason);
     }
+    
+    #[tokio::test]
+    async fn test_outside_window_reports_reason() {
+        // 交易时段为当前时间2小时后的1小时，当前时间一定不在时段内
+        let hour = Local::now().hour();
+        let controller = TradingTimeWindowController::new((hour + 2) % 24, 0, (hour + 3) % 24, 0, true).unwrap();
+        let opportunity = ArbitrageOpportunity::new("BTC", QuoteCurrency::USDT, QuoteCurrency::USDC, dec!(50000), dec!(50100), dec!(1000));
+        
+        let (valid, reason) = controller.check_opportunity(&opportunity).await.unwrap();
+        assert!(!valid);
+        assert_eq!(reason.unwrap().reason, RejectionReason::OutsideTimeWindow);
+        
+        let state = controller.state();
+        assert_eq!(state["in_window"], false);
+        assert!(state["reason"].as_str().unwrap().contains("不在交易时段"));
+        assert_eq!(state["start_time"], format!("{:02}:00", (hour + 2) % 24));
+        assert_eq!(state["trade_on_weekends"], true);
+    }
 }
@@ -0,0 +1,476 @@
+//! 模拟交易模块，使用模拟API和随机价格驱动套利引擎，结束后生成本次模拟的绩效报告
+
+use crate::analytics::{AnalyticsManager, Label, PerformanceReport, TimeRange};
+use crate::arbitrage::{ArbitrageEngine, SessionLimits, SessionSummary, ShutdownSignal};
+use crate::binance::MockBinanceApi;
+use crate::config::{AdminApiSettings, Config};
+use crate::db::{DatabaseManager, HistoryFormat, TradeHistoryFilter};
+use crate::logging::LogTail;
+use crate::metrics::Metrics;
+use crate::models::TradingMode;
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Utc};
+use log::{debug, error, info};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rust_decimal::prelude::FromPrimitive;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::sleep;
+
+/// 模拟参数
+#[derive(Debug, Clone)]
+pub struct SimulationSettings {
+    /// 模拟运行时间
+    pub runtime: Duration,
+    /// 价格波动率 (百分比)
+    pub volatility: f64,
+    /// 每次价格更新时创造套利机会的概率 (0-100)
+    pub opportunity_probability: u32,
+    /// 价格更新间隔
+    pub price_update_interval: Duration,
+    /// 会话交易限制，达到限制时提前结束模拟
+    pub session_limits: SessionLimits,
+    /// 模拟引擎更新的运行指标，为None时不对外导出
+    pub metrics: Option<Arc<Metrics>>,
+    /// 模拟期间启动的管理接口（需要 `admin-api` 特性），为None时不启动
+    pub admin_api: Option<AdminApiSettings>,
+    /// 提前结束模拟的停止标志，例如由Ctrl-C信号处理任务触发
+    pub shutdown: Option<ShutdownSignal>,
+    /// 价格模拟的随机数种子，指定时每次模拟的价格序列相同
+    pub seed: Option<u64>,
+    /// 模拟期间显示终端仪表盘（需要 `tui` 特性），日志面板显示该缓冲中的日志；为None时不显示
+    pub tui: Option<LogTail>,
+}
+
+impl Default for SimulationSettings {
+    fn default() -> Self {
+        Self {
+            runtime: Duration::from_secs(60),
+            volatility: 1.0,
+            opportunity_probability: 30,
+            price_update_interval: Duration::from_millis(1000),
+            session_limits: SessionLimits::default(),
+            metrics: None,
+            admin_api: None,
+            shutdown: None,
+            seed: None,
+            tui: None,
+        }
+    }
+}
+
+/// 没有数据库连接时保存模拟结果的文件，按扩展名选择格式
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SimulationOutput {
+    /// JSON Lines（`.jsonl`），与 `export` 命令的格式相同，模拟期间记录在内存中，结束后写出
+    JsonLines(PathBuf),
+    /// SQLite数据库文件（`.db`、`.sqlite`），模拟期间直接写入
+    Sqlite(PathBuf),
+}
+
+impl SimulationOutput {
+    pub fn from_path(path: &Path) -> Result<Self> {
+        let extension = path.extension().and_then(|extension| extension.to_str()).map(str::to_lowercase);
+        match extension.as_deref() {
+            Some("jsonl" | "ndjson") => Ok(SimulationOutput::JsonLines(path.to_path_buf())),
+            Some("db" | "sqlite" | "sqlite3") => Ok(SimulationOutput::Sqlite(path.to_path_buf())),
+            _ => Err(anyhow!("不支持的模拟结果文件: {:?}，扩展名应为 .jsonl、.db 或 .sqlite", path)),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        match self {
+            SimulationOutput::JsonLines(path) | SimulationOutput::Sqlite(path) => path,
+        }
+    }
+
+    /// 打开记录模拟结果的数据库，SQLite文件不存在时创建
+    pub async fn open_database(&self) -> Result<DatabaseManager> {
+        match self {
+            SimulationOutput::JsonLines(_) => DatabaseManager::in_memory().await,
+            SimulationOutput::Sqlite(path) => DatabaseManager::new(&format!("sqlite://{}", path.display())).await,
+        }
+    }
+
+    /// 模拟结束后写出 `started_at` 之后的模拟结果；SQLite文件在模拟期间已写入，只等待写入完成
+    pub async fn save(&self, db: &DatabaseManager, started_at: DateTime<Utc>) -> Result<()> {
+        db.flush().await?;
+        let SimulationOutput::JsonLines(path) = self else {
+            return Ok(());
+        };
+
+        let filter = TradeHistoryFilter {
+            mode: Some(TradingMode::Simulate),
+            ..TradeHistoryFilter::between(Some(started_at), None)
+        };
+        let file = std::fs::File::create(path).with_context(|| format!("无法创建模拟结果文件: {:?}", path))?;
+        let exported = db
+            .export_history(std::io::BufWriter::new(file), &filter, HistoryFormat::JsonLines)
+            .await?;
+        info!("已写出 {} 条模拟结果到 {:?}", exported, path);
+        Ok(())
+    }
+}
+
+/// 运行模拟交易，运行时间结束后返回本次模拟的绩效报告
+///
+/// 套利结果写入 `db`，报告只统计本次模拟期间的交易，因此也可以传入已有历史数据的数据库；
+/// 没有配置数据库时可以使用 `DatabaseManager::in_memory`。
+pub async fn run_simulation(
+    config: Config,
+    base_asset: &str,
+    settings: &SimulationSettings,
+    db: DatabaseManager,
+) -> Result<PerformanceReport> {
+    run_simulation_session(config, base_asset, settings, db).await.map(|(report, _)| report)
+}
+
+/// 与 `run_simulation` 相同，同时返回引擎的会话汇总
+///
+/// 运行时间结束、达到会话限制或请求停止后，取消剩余的模拟挂单并写入全部结果，再生成报告。
+pub async fn run_simulation_session(
+    config: Config,
+    base_asset: &str,
+    settings: &SimulationSettings,
+    db: DatabaseManager,
+) -> Result<(PerformanceReport, SessionSummary)> {
+    let api = MockBinanceApi::new();
+    let locale = config.report_language;
+    let mut engine = ArbitrageEngine::new(api.clone(), config, base_asset)?;
+    engine.set_db_manager(db.clone());
+    engine.set_session_limits(settings.session_limits.clone());
+    engine.set_trading_mode(TradingMode::Simulate);
+    if let Some(metrics) = &settings.metrics {
+        engine.set_metrics(metrics.clone());
+    }
+    if let Some(shutdown) = &settings.shutdown {
+        engine.set_shutdown_signal(shutdown.clone());
+    }
+    let engine = Arc::new(engine);
+    let admin_task = settings.admin_api.as_ref().and_then(|admin_settings| start_admin_api(&engine, admin_settings));
+    let tui_stop = ShutdownSignal::new();
+    let tui_task = settings.tui.as_ref().and_then(|logs| start_tui(&engine, logs, &tui_stop));
+
+    // 启动价格模拟任务
+    let price_task = {
+        let api = api.clone();
+        let base_asset = base_asset.to_string();
+        let settings = settings.clone();
+        tokio::spawn(async move {
+            simulate_price_movements(
+                &api,
+                &base_asset,
+                settings.volatility,
+                settings.opportunity_probability,
+                settings.price_update_interval,
+                settings.seed,
+            )
+            .await;
+        })
+    };
+
+    let started_at = Utc::now();
+    info!("开始模拟监控套利机会...");
+    tokio::select! {
+        result = engine.monitor_opportunities() => {
+            match result {
+                Ok(()) if settings.shutdown.as_ref().is_some_and(ShutdownSignal::is_requested) => info!("收到停止请求，提前结束模拟"),
+                Ok(()) => info!("已达到会话限制，提前结束模拟"),
+                Err(e) => error!("模拟监控异常退出: {}", e),
+            }
+        },
+        _ = sleep(settings.runtime) => {
+            info!("模拟时间结束");
+        }
+    }
+    price_task.abort();
+    if let Some(admin_task) = admin_task {
+        admin_task.abort();
+    }
+    // 等待仪表盘恢复终端后再输出汇总
+    tui_stop.request();
+    if let Some(tui_task) = tui_task {
+        let _ = tui_task.await;
+    }
+    let summary = engine.finish_session().await;
+
+    // 等待写入队列中的结果全部写入后再统计
+    db.flush().await?;
+
+    let range = TimeRange::Custom(started_at, Utc::now());
+    let mut report = AnalyticsManager::new(db)
+        .with_mode(Some(TradingMode::Simulate))
+        .with_locale(locale)
+        .generate_report(range)
+        .await?;
+    report.title = format!("{} - {}", Label::SimulationReportTitle.text(locale), base_asset);
+
+    Ok((report, summary))
+}
+
+/// 在后台启动管理接口，返回接口任务，模拟结束时停止
+#[cfg(feature = "admin-api")]
+fn start_admin_api(engine: &Arc<ArbitrageEngine<MockBinanceApi>>, settings: &AdminApiSettings) -> Option<tokio::task::JoinHandle<()>> {
+    let engine: Arc<dyn crate::admin::EngineControl> = engine.clone();
+    let settings = settings.clone();
+    Some(tokio::spawn(async move {
+        if let Err(e) = crate::admin::serve(engine, &settings).await {
+            error!("管理接口启动失败: {:#}", e);
+        }
+    }))
+}
+
+#[cfg(not(feature = "admin-api"))]
+fn start_admin_api(_engine: &Arc<ArbitrageEngine<MockBinanceApi>>, _settings: &AdminApiSettings) -> Option<tokio::task::JoinHandle<()>> {
+    log::warn!("已配置管理接口，但编译时未启用 admin-api 特性，管理接口不会启动");
+    None
+}
+
+/// 在阻塞线程中显示终端仪表盘，`stop` 被请求时退出
+#[cfg(feature = "tui")]
+fn start_tui(engine: &Arc<ArbitrageEngine<MockBinanceApi>>, logs: &LogTail, stop: &ShutdownSignal) -> Option<tokio::task::JoinHandle<()>> {
+    Some(crate::tui::spawn(engine, logs.clone(), stop.clone()))
+}
+
+#[cfg(not(feature = "tui"))]
+fn start_tui(_engine: &Arc<ArbitrageEngine<MockBinanceApi>>, _logs: &LogTail, _stop: &ShutdownSignal) -> Option<tokio::task::JoinHandle<()>> {
+    log::warn!("编译时未启用 tui 特性，不显示终端仪表盘");
+    None
+}
+
+/// 模拟价格变动，按指定概率在USDT和USDC交易对之间制造价差
+pub async fn simulate_price_movements(
+    api: &MockBinanceApi,
+    base_asset: &str,
+    volatility: f64,
+    opportunity_probability: u32,
+    interval: Duration,
+    seed: Option<u64>,
+) {
+    // 构造交易对名称
+    let usdt_symbol = format!("{}{}", base_asset, "USDT");
+    let usdc_symbol = format!("{}{}", base_asset, "USDC");
+
+    let mut usdt_price = 50000.0;
+    let mut usdc_price = 50025.0;
+    let mut rng = seed.map_or_else(StdRng::from_entropy, StdRng::seed_from_u64);
+
+    loop {
+        // 模拟价格波动，根据设定的波动率
+        let volatility_factor = volatility / 100.0;
+        let usdt_change = (rng.gen::<f64>() - 0.5) * usdt_price * volatility_factor;
+        let usdc_change = (rng.gen::<f64>() - 0.5) * usdc_price * volatility_factor;
+
+        usdt_price += usdt_change;
+        usdc_price += usdc_change;
+
+        // 有指定概率会创造套利机会
+        if rng.gen_range(0..100) < opportunity_probability {
+            // 随机创造USDT价格低于或高于USDC的情况
+            if rng.gen_bool(0.5) {
+                usdt_price = usdc_price - rng.gen::<f64>() * 50.0;
+            } else {
+                usdt_price = usdc_price + rng.gen::<f64>() * 50.0;
+            }
+        }
+
+        // 确保价格不会变为负数
+        usdt_price = usdt_price.max(1.0);
+        usdc_price = usdc_price.max(1.0);
+
+        // 更新API中的价格
+        api.update_price(&usdt_symbol, Decimal::from_f64(usdt_price).unwrap_or(dec!(50000)));
+        api.update_price(&usdc_symbol, Decimal::from_f64(usdc_price).unwrap_or(dec!(50025)));
+
+        debug!("更新模拟价格 - {}: {:.2}, {}: {:.2}",
+            usdt_symbol, usdt_price,
+            usdc_symbol, usdc_price
+        );
+
+        sleep(interval).await;
+    }
+}
+
+#[cfg(all(test, feature = "db-sqlite"))]
+mod tests {
+    use super::*;
+    use crate::config::{ArbitrageSettings, FeeSettings, RiskSettings};
+
+    fn test_config() -> Config {
+        Config {
+            arbitrage_settings: ArbitrageSettings {
+                min_profit_percentage: Decimal::ZERO,
+                check_interval_ms: 20,
+                ..ArbitrageSettings::default()
+            },
+            risk_settings: RiskSettings {
+                enabled_controllers: Vec::new(),
+                ..RiskSettings::default()
+            },
+            // 测试行情的价差小于默认手续费，手续费设为0
+            fees: FeeSettings { maker_rate: Decimal::ZERO, taker_rate: Decimal::ZERO, ..FeeSettings::default() },
+            ..Config::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_short_simulation_produces_report() {
+        let settings = SimulationSettings {
+            runtime: Duration::from_millis(500),
+            volatility: 0.0,
+            opportunity_probability: 100,
+            price_update_interval: Duration::from_millis(20),
+            ..SimulationSettings::default()
+        };
+        let db = DatabaseManager::in_memory().await.unwrap();
+
+        let report = run_simulation(test_config(), "BTC", &settings, db).await.unwrap();
+
+        assert!(report.title.contains("模拟"));
+        assert!(report.overview.total_trades > 0, "模拟期间应记录交易");
+        assert_eq!(
+            report.overview.total_trades,
+            report.overview.successful_trades + report.overview.failed_trades
+        );
+        assert!(!report.daily_stats.is_empty());
+        assert_eq!(report.asset_stats[0].asset, "BTC");
+        assert_eq!(report.mode, Some(TradingMode::Simulate));
+    }
+
+    #[tokio::test]
+    async fn test_report_excludes_earlier_trades() {
+        let db = DatabaseManager::in_memory().await.unwrap();
+        let settings = SimulationSettings {
+            runtime: Duration::from_millis(300),
+            volatility: 0.0,
+            opportunity_probability: 100,
+            price_update_interval: Duration::from_millis(20),
+            ..SimulationSettings::default()
+        };
+
+        let first = run_simulation(test_config(), "BTC", &settings, db.clone()).await.unwrap();
+        let second = run_simulation(test_config(), "BTC", &settings, db.clone()).await.unwrap();
+
+        // 第二次模拟的报告不包含第一次模拟写入的交易
+        let total = db.get_overall_stats().await.unwrap().total_trades;
+        assert_eq!(total, first.overview.total_trades + second.overview.total_trades);
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_request_ends_simulation_with_summary() {
+        let shutdown = ShutdownSignal::new();
+        let settings = SimulationSettings {
+            runtime: Duration::from_secs(30),
+            volatility: 0.0,
+            opportunity_probability: 100,
+            price_update_interval: Duration::from_millis(20),
+            shutdown: Some(shutdown.clone()),
+            ..SimulationSettings::default()
+        };
+        let db = DatabaseManager::in_memory().await.unwrap();
+
+        // 与Ctrl-C信号处理任务一样在运行中请求停止
+        let trigger = tokio::spawn(async move {
+            sleep(Duration::from_millis(300)).await;
+            shutdown.request();
+        });
+        let (report, summary) = tokio::time::timeout(
+            Duration::from_secs(5),
+            run_simulation_session(test_config(), "BTC", &settings, db.clone()),
+        )
+        .await
+        .expect("请求停止后模拟应尽快结束")
+        .unwrap();
+        trigger.await.unwrap();
+
+        assert!(summary.stats.trades > 0);
+        assert_eq!(summary.stats.trades, report.overview.total_trades as u64);
+        assert!(summary.cancelled_orders.is_empty());
+        let text = summary.to_string();
+        assert!(text.contains(&format!("交易次数: {}", summary.stats.trades)));
+        assert!(text.contains("净利润"));
+    }
+
+    #[tokio::test]
+    async fn test_simulation_stops_at_session_limit() {
+        let settings = SimulationSettings {
+            runtime: Duration::from_secs(30),
+            volatility: 0.0,
+            opportunity_probability: 100,
+            price_update_interval: Duration::from_millis(20),
+            session_limits: SessionLimits { max_trades: Some(2), ..SessionLimits::default() },
+            ..SimulationSettings::default()
+        };
+        let db = DatabaseManager::in_memory().await.unwrap();
+
+        let started = std::time::Instant::now();
+        let report = run_simulation(test_config(), "BTC", &settings, db).await.unwrap();
+
+        assert!(started.elapsed() < settings.runtime, "达到交易次数限制后应提前结束");
+        assert_eq!(report.overview.total_trades, 2);
+    }
+
+    fn seeded_settings() -> SimulationSettings {
+        SimulationSettings {
+            runtime: Duration::from_millis(300),
+            volatility: 0.5,
+            opportunity_probability: 100,
+            price_update_interval: Duration::from_millis(20),
+            seed: Some(42),
+            ..SimulationSettings::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_seeded_simulation_writes_results_file() {
+        use crate::db::TradeRecord;
+
+        let path = std::env::temp_dir().join(format!("arbitrage_simulation_{}.jsonl", std::process::id()));
+        let output = SimulationOutput::from_path(&path).unwrap();
+        let db = output.open_database().await.unwrap();
+
+        let started_at = Utc::now();
+        let (report, summary) = run_simulation_session(test_config(), "BTC", &seeded_settings(), db.clone()).await.unwrap();
+        output.save(&db, started_at).await.unwrap();
+
+        // 每行一条与数据库中相同序列化的交易记录
+        let content = std::fs::read_to_string(&path).unwrap();
+        let records: Vec<TradeRecord> = content.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        assert!(!records.is_empty());
+        assert_eq!(records.len() as u64, summary.stats.trades);
+        assert!(records.iter().all(|record| record.result.mode == TradingMode::Simulate && record.result.base_asset == "BTC"));
+        assert_eq!(records.iter().map(|record| record.result.profit).sum::<Decimal>(), summary.stats.profit);
+        let best = records.iter().map(|record| record.result.profit).max();
+        assert_eq!(best, summary.stats.best_profit);
+
+        // 不连接数据库，直接分析结果文件
+        let loaded = DatabaseManager::from_history_file(&path).await.unwrap();
+        let overview = AnalyticsManager::new(loaded).generate_report(TimeRange::AllTime).await.unwrap().overview;
+        assert_eq!(overview.total_trades, report.overview.total_trades);
+        assert_eq!(overview.successful_trades, report.overview.successful_trades);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_simulation_writes_sqlite_file() {
+        let path = std::env::temp_dir().join(format!("arbitrage_simulation_{}.db", std::process::id()));
+        let output = SimulationOutput::from_path(&path).unwrap();
+        assert_eq!(output, SimulationOutput::Sqlite(path.clone()));
+        let db = output.open_database().await.unwrap();
+
+        let (_, summary) = run_simulation_session(test_config(), "BTC", &seeded_settings(), db.clone()).await.unwrap();
+        output.save(&db, Utc::now()).await.unwrap();
+        drop(db);
+
+        let reopened = DatabaseManager::new(&format!("sqlite://{}", path.display())).await.unwrap();
+        assert_eq!(reopened.get_overall_stats().await.unwrap().total_trades as u64, summary.stats.trades);
+
+        std::fs::remove_file(&path).unwrap();
+        assert!(SimulationOutput::from_path(Path::new("results.txt")).is_err());
+    }
+}
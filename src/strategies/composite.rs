@@ -0,0 +1,345 @@
+use super::{check_profit, StrategySignal, TradingStrategy};
+use crate::models::{ArbitrageOpportunity, Price, QuoteCurrency, Rejection, RejectionReason};
+use crate::config::Config;
+use anyhow::Result;
+use async_trait::async_trait;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::sync::{Mutex, RwLock};
+use log::{debug, info, warn};
+
+/// 多策略加权组合
+/// 运行多个子策略并按权重投票，只有加权多数认可同一套利方向、
+/// 且该方向的加权置信度达到阈值时才产生交易机会
+pub struct CompositeStrategy {
+    config: RwLock<Config>,
+    /// 子策略及其权重
+    members: Vec<(Box<dyn TradingStrategy>, f64)>,
+    /// 最低加权一致度 (0-1)
+    min_agreement: Mutex<f64>,
+}
+
+impl CompositeStrategy {
+    pub fn new(config: Config, members: Vec<(Box<dyn TradingStrategy>, f64)>, min_agreement: f64) -> Self {
+        Self {
+            config: RwLock::new(config),
+            members,
+            min_agreement: Mutex::new(min_agreement),
+        }
+    }
+
+    /// 汇总各子策略的信号（权重, 信号），返回加权决策
+    ///
+    /// 每个方向的支持度为该方向信号的 权重×置信度 之和，除以全部子策略的总权重得到一致度。
+    /// 支持度最高的方向必须严格高于相反方向，且一致度不低于 `min_agreement`。
+    /// 未产生信号的子策略视为弃权，其权重仍计入总权重。
+    fn combine(signals: Vec<(f64, Option<StrategySignal>)>, min_agreement: f64) -> Option<StrategySignal> {
+        let total_weight: f64 = signals.iter().map(|(weight, _)| weight.max(0.0)).sum();
+        if total_weight <= 0.0 {
+            return None;
+        }
+
+        // (买入报价货币, 卖出报价货币) -> (支持度, 该方向的信号及其得分)
+        let mut directions: HashMap<(QuoteCurrency, QuoteCurrency), (f64, Vec<(f64, StrategySignal)>)> = HashMap::new();
+        for (weight, signal) in signals {
+            if let Some(signal) = signal {
+                let score = weight.max(0.0) * signal.confidence.clamp(0.0, 1.0);
+                let entry = directions
+                    .entry((signal.opportunity.buy_quote, signal.opportunity.sell_quote))
+                    .or_insert((0.0, Vec::new()));
+                entry.0 += score;
+                entry.1.push((score, signal));
+            }
+        }
+
+        let mut ranked: Vec<_> = directions.into_values().collect();
+        ranked.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+
+        let mut ranked = ranked.into_iter();
+        let (support, mut agreeing) = ranked.next()?;
+        let opposing = ranked.next().map(|(support, _)| support).unwrap_or(0.0);
+
+        if support <= opposing {
+            debug!("子策略方向冲突: 支持 {:.2} / 反对 {:.2}，不交易", support, opposing);
+            return None;
+        }
+
+        let agreement = support / total_weight;
+        if agreement < min_agreement {
+            debug!("加权一致度 {:.2} 低于要求 {:.2}，不交易", agreement, min_agreement);
+            return None;
+        }
+
+        // 采用得分最高的信号，交易量取各认可信号中的最小值
+        agreeing.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+        let min_amount = agreeing
+            .iter()
+            .map(|(_, signal)| signal.opportunity.max_trade_amount)
+            .min()?;
+        let (_, best) = agreeing.into_iter().next()?;
+        let mut opportunity = best.opportunity;
+        opportunity.max_trade_amount = min_amount;
+
+        Some(StrategySignal {
+            opportunity,
+            confidence: agreement,
+        })
+    }
+
+    /// 收集所有子策略的信号，出错的子策略视为弃权
+    async fn collect_signals(&self, base_asset: &str, usdt_price: &Price, usdc_price: &Price) -> Vec<(f64, Option<StrategySignal>)> {
+        let mut signals = Vec::with_capacity(self.members.len());
+
+        for (strategy, weight) in &self.members {
+            let signal = match strategy.evaluate(base_asset, usdt_price, usdc_price).await {
+                Ok(signal) => signal,
+                Err(e) => {
+                    warn!("组合策略的子策略 {} 出错，视为弃权: {}", strategy.name(), e);
+                    None
+                }
+            };
+
+            debug!(
+                "子策略 {} (权重 {}): {}",
+                strategy.name(),
+                weight,
+                match &signal {
+                    Some(s) => format!("{} -> {}, 置信度 {:.2}", s.opportunity.buy_quote, s.opportunity.sell_quote, s.confidence),
+                    None => "无信号".to_string(),
+                }
+            );
+
+            signals.push((*weight, signal));
+        }
+
+        signals
+    }
+}
+
+#[async_trait]
+impl TradingStrategy for CompositeStrategy {
+    fn name(&self) -> &str {
+        "多策略加权组合"
+    }
+
+    fn description(&self) -> &str {
+        "运行多个子策略并按权重投票，仅在加权多数认可同一方向且一致度达到阈值时套利"
+    }
+
+    /// 子策略或权重变化时需要重新创建
+    fn update_params(&self, config: &Config) -> bool {
+        let settings = &config.strategy_settings.composite;
+        if settings.members != self.config.read().unwrap().strategy_settings.composite.members {
+            return false;
+        }
+        if !self.members.iter().all(|(strategy, _)| strategy.update_params(config)) {
+            return false;
+        }
+
+        *self.min_agreement.lock().unwrap() = settings.min_agreement;
+        *self.config.write().unwrap() = config.clone();
+        true
+    }
+
+    async fn find_opportunity(&self, base_asset: &str, usdt_price: &Price, usdc_price: &Price) -> Result<Option<ArbitrageOpportunity>> {
+        Ok(self
+            .evaluate(base_asset, usdt_price, usdc_price)
+            .await?
+            .map(|signal| signal.opportunity))
+    }
+
+    async fn validate_opportunity(&self, opportunity: &ArbitrageOpportunity) -> Result<(bool, Option<Rejection>)> {
+        let min_profit = self.config.read().unwrap().arbitrage_settings.min_profit_percentage;
+        Ok(check_profit(opportunity, min_profit, RejectionReason::BelowMinProfit))
+    }
+
+    async fn evaluate(&self, base_asset: &str, usdt_price: &Price, usdc_price: &Price) -> Result<Option<StrategySignal>> {
+        let signals = self.collect_signals(base_asset, usdt_price, usdc_price).await;
+        let decision = Self::combine(signals, *self.min_agreement.lock().unwrap());
+
+        if let Some(signal) = &decision {
+            info!(
+                "组合策略决策: {} 买入 {}, 卖出 {}, 一致度 {:.2}",
+                signal.opportunity.base_asset,
+                signal.opportunity.buy_quote,
+                signal.opportunity.sell_quote,
+                signal.confidence
+            );
+        }
+
+        Ok(decision)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use rust_decimal_macros::dec;
+    use QuoteCurrency::{USDC, USDT};
+
+    /// 固定给出某个方向信号的测试策略
+    struct FixedStrategy {
+        buy_quote: QuoteCurrency,
+        sell_quote: QuoteCurrency,
+        confidence: f64,
+        amount: Decimal,
+    }
+
+    impl FixedStrategy {
+        fn boxed(buy_quote: QuoteCurrency, sell_quote: QuoteCurrency, confidence: f64) -> Box<dyn TradingStrategy> {
+            Box::new(Self { buy_quote, sell_quote, confidence, amount: dec!(100) })
+        }
+    }
+
+    #[async_trait]
+    impl TradingStrategy for FixedStrategy {
+        fn name(&self) -> &str {
+            "fixed"
+        }
+
+        fn description(&self) -> &str {
+            "fixed"
+        }
+
+        async fn find_opportunity(&self, base_asset: &str, _usdt_price: &Price, _usdc_price: &Price) -> Result<Option<ArbitrageOpportunity>> {
+            Ok(Some(ArbitrageOpportunity::new(
+                base_asset,
+                self.buy_quote,
+                self.sell_quote,
+                dec!(50000),
+                dec!(50100),
+                self.amount,
+            )))
+        }
+
+        async fn validate_opportunity(&self, _opportunity: &ArbitrageOpportunity) -> Result<(bool, Option<Rejection>)> {
+            Ok((true, None))
+        }
+
+        async fn evaluate(&self, base_asset: &str, usdt_price: &Price, usdc_price: &Price) -> Result<Option<StrategySignal>> {
+            let opportunity = self.find_opportunity(base_asset, usdt_price, usdc_price).await?.unwrap();
+            Ok(Some(StrategySignal { opportunity, confidence: self.confidence }))
+        }
+    }
+
+    /// 从不给出信号的测试策略
+    struct SilentStrategy;
+
+    #[async_trait]
+    impl TradingStrategy for SilentStrategy {
+        fn name(&self) -> &str {
+            "silent"
+        }
+
+        fn description(&self) -> &str {
+            "silent"
+        }
+
+        async fn find_opportunity(&self, _base_asset: &str, _usdt_price: &Price, _usdc_price: &Price) -> Result<Option<ArbitrageOpportunity>> {
+            Ok(None)
+        }
+
+        async fn validate_opportunity(&self, _opportunity: &ArbitrageOpportunity) -> Result<(bool, Option<Rejection>)> {
+            Ok((false, None))
+        }
+    }
+
+    fn prices() -> (Price, Price) {
+        let now = Utc::now();
+        (
+            Price { symbol: "BTCUSDT".to_string(), price: dec!(50000), timestamp: now },
+            Price { symbol: "BTCUSDC".to_string(), price: dec!(50100), timestamp: now },
+        )
+    }
+
+    fn test_config() -> Config {
+        Config::default()
+    }
+
+    async fn run(members: Vec<(Box<dyn TradingStrategy>, f64)>, min_agreement: f64) -> Option<StrategySignal> {
+        let strategy = CompositeStrategy::new(test_config(), members, min_agreement);
+        let (usdt, usdc) = prices();
+        strategy.evaluate("BTC", &usdt, &usdc).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_agreeing_signals_approve_trade() {
+        let decision = run(
+            vec![
+                (FixedStrategy::boxed(USDT, USDC, 1.0), 1.0),
+                (FixedStrategy::boxed(USDT, USDC, 0.8), 2.0),
+            ],
+            0.6,
+        )
+        .await
+        .expect("方向一致时应产生交易");
+
+        assert_eq!(decision.opportunity.buy_quote, USDT);
+        assert_eq!(decision.opportunity.sell_quote, USDC);
+        // (1.0 * 1.0 + 2.0 * 0.8) / 3.0
+        assert!((decision.confidence - 2.6 / 3.0).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn test_conflicting_signals_suppress_trade() {
+        // 权重相同、方向相反
+        let decision = run(
+            vec![
+                (FixedStrategy::boxed(USDT, USDC, 1.0), 1.0),
+                (FixedStrategy::boxed(USDC, USDT, 1.0), 1.0),
+            ],
+            0.1,
+        )
+        .await;
+        assert!(decision.is_none());
+
+        // 多数方向胜出但一致度不足
+        let decision = run(
+            vec![
+                (FixedStrategy::boxed(USDT, USDC, 1.0), 2.0),
+                (FixedStrategy::boxed(USDC, USDT, 1.0), 1.0),
+            ],
+            0.7,
+        )
+        .await;
+        assert!(decision.is_none());
+
+        // 降低阈值后加权多数方向被采纳
+        let decision = run(
+            vec![
+                (FixedStrategy::boxed(USDT, USDC, 1.0), 2.0),
+                (FixedStrategy::boxed(USDC, USDT, 1.0), 1.0),
+            ],
+            0.6,
+        )
+        .await
+        .unwrap();
+        assert_eq!(decision.opportunity.buy_quote, USDT);
+    }
+
+    #[tokio::test]
+    async fn test_abstaining_members_count_against_agreement() {
+        let members = || vec![
+            (FixedStrategy::boxed(USDT, USDC, 1.0), 1.0),
+            (Box::new(SilentStrategy) as Box<dyn TradingStrategy>, 1.0),
+        ];
+
+        assert!(run(members(), 0.6).await.is_none());
+        assert!(run(members(), 0.5).await.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_uses_smallest_trade_amount() {
+        let small: Box<dyn TradingStrategy> = Box::new(FixedStrategy {
+            buy_quote: USDT,
+            sell_quote: USDC,
+            confidence: 0.5,
+            amount: dec!(20),
+        });
+        let decision = run(vec![(FixedStrategy::boxed(USDT, USDC, 1.0), 1.0), (small, 1.0)], 0.5)
+            .await
+            .unwrap();
+        assert_eq!(decision.opportunity.max_trade_amount, dec!(20));
+    }
+}
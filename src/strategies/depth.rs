@@ -1,102 +1,144 @@
-use super::TradingStrategy;
+use super::{check_profit, TradingStrategy};
 use crate::binance::ExchangeApi;
-use crate::models::{ArbitrageOpportunity, Price, QuoteCurrency, OrderBook};
-use crate::config::Config;
-use anyhow::{Result, anyhow};
+use crate::models::{ArbitrageOpportunity, DepthFill, Price, QuoteCurrency, OrderBook, PriceSource, Rejection, RejectionReason};
+use crate::config::{Config, SlippageModel};
+use anyhow::Result;
 use async_trait::async_trait;
-use rust_decimal::Decimal;
-use std::sync::Arc;
-use log::{debug, info, warn};
+use rust_decimal::{Decimal, MathematicalOps};
 use rust_decimal_macros::dec;
+use std::sync::{Arc, Mutex, RwLock};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use log::{debug, info};
 
 /// 订单簿深度分析策略
-/// 通过分析订单簿深度来判断市场流动性和潜在的滑点，避免在流动性不足的市场中进行套利
+/// 按计划交易金额沿订单簿逐档计算实际成交均价，以此判断套利空间，避免在流动性不足的市场中进行套利
 pub struct OrderBookDepthStrategy<T: ExchangeApi + Send + Sync> {
-    config: Arc<Config>,
+    config: RwLock<Config>,
     api: Arc<T>,
     /// 要分析的订单簿深度（价格档位数量）
-    depth_levels: usize,
+    depth_levels: AtomicUsize,
     /// 最小流动性要求（以基础货币计）
-    min_liquidity: Decimal,
+    min_liquidity: Mutex<Decimal>,
+}
+
+/// 按订单簿深度计算套利两边的成交价格
+///
+/// 用 `notional` 报价货币沿 `buy_book` 的卖盘逐档买入，再把买到的数量沿 `sell_book` 的买盘逐档卖出，
+/// 返回 (买入成交, 卖出成交)。任意一边深度不足时返回 None。
+pub fn depth_weighted_fills(buy_book: &OrderBook, sell_book: &OrderBook, notional: Decimal) -> Option<(DepthFill, DepthFill)> {
+    let buy = buy_book.buy_with_notional(notional)?;
+    let sell = sell_book.sell_qty(buy.qty)?;
+    Some((buy, sell))
+}
+
+/// 交易量为可见深度 `ratio` 倍时，滑点相对于可见深度处滑点的倍数，模型为 Disabled 时返回 None
+fn impact_multiplier(model: SlippageModel, ratio: Decimal) -> Option<Decimal> {
+    match model {
+        SlippageModel::Disabled => None,
+        SlippageModel::Linear => Some(ratio),
+        SlippageModel::SquareRoot => ratio.sqrt(),
+    }
+}
+
+/// 可见深度处的参考滑点（比例）：最差档位相对最优档位的偏离，不低于 `min_impact_pct`
+fn reference_impact(best: Decimal, worst: Decimal, min_impact_pct: Decimal) -> Decimal {
+    let observed = if best.is_zero() { Decimal::ZERO } else { (worst - best).abs() / best };
+    observed.max(min_impact_pct / dec!(100))
+}
+
+/// 用 `notional` 报价货币沿卖盘买入，深度不足时按滑点模型估算超出部分的成交
+///
+/// 可见档位逐档成交；超出部分按外推价格成交，外推价格的滑点为参考滑点乘以模型倍数（交易金额与可见金额之比）。
+/// 超出部分全部按外推价格计算，估算偏保守。
+pub fn estimate_buy_fill(book: &OrderBook, notional: Decimal, model: SlippageModel, min_impact_pct: Decimal) -> Option<DepthFill> {
+    if let Some(fill) = book.buy_with_notional(notional) {
+        return Some(fill);
+    }
+    let levels: Vec<&(Decimal, Decimal)> = book.asks.iter().filter(|(price, _)| !price.is_zero()).collect();
+    let (best, _) = levels.first()?;
+    let (worst, _) = levels.last()?;
+    let visible_notional: Decimal = levels.iter().map(|(price, qty)| *price * *qty).sum();
+    let visible_qty: Decimal = levels.iter().map(|(_, qty)| *qty).sum();
+    if notional <= Decimal::ZERO || visible_notional.is_zero() {
+        return None;
+    }
+
+    let multiplier = impact_multiplier(model, notional / visible_notional)?;
+    let price = *best * (Decimal::ONE + reference_impact(*best, *worst, min_impact_pct) * multiplier);
+    let qty = visible_qty + (notional - visible_notional) / price;
+    Some(DepthFill { qty, notional, avg_price: notional / qty })
+}
+
+/// 沿买盘卖出 `qty` 基础货币，深度不足时按滑点模型估算超出部分的成交，外推价格不低于0
+pub fn estimate_sell_fill(book: &OrderBook, qty: Decimal, model: SlippageModel, min_impact_pct: Decimal) -> Option<DepthFill> {
+    if let Some(fill) = book.sell_qty(qty) {
+        return Some(fill);
+    }
+    let (best, _) = book.bids.first()?;
+    let (worst, _) = book.bids.last()?;
+    let visible_qty: Decimal = book.bids.iter().map(|(_, qty)| *qty).sum();
+    let visible_notional: Decimal = book.bids.iter().map(|(price, qty)| *price * *qty).sum();
+    if qty <= Decimal::ZERO || visible_qty.is_zero() {
+        return None;
+    }
+
+    let multiplier = impact_multiplier(model, qty / visible_qty)?;
+    let price = (*best * (Decimal::ONE - reference_impact(*best, *worst, min_impact_pct) * multiplier)).max(Decimal::ZERO);
+    let notional = visible_notional + (qty - visible_qty) * price;
+    Some(DepthFill { qty, notional, avg_price: notional / qty })
+}
+
+/// 与 [`depth_weighted_fills`] 相同，但任意一边深度不足时按滑点模型估算超出部分的成交
+pub fn estimated_fills(
+    buy_book: &OrderBook,
+    sell_book: &OrderBook,
+    notional: Decimal,
+    model: SlippageModel,
+    min_impact_pct: Decimal,
+) -> Option<(DepthFill, DepthFill)> {
+    let buy = estimate_buy_fill(buy_book, notional, model, min_impact_pct)?;
+    let sell = estimate_sell_fill(sell_book, buy.qty, model, min_impact_pct)?;
+    Some((buy, sell))
 }
 
 impl<T: ExchangeApi + Send + Sync + 'static> OrderBookDepthStrategy<T> {
     pub fn new(config: Config, api: T, depth_levels: usize, min_liquidity: Decimal) -> Self {
         Self {
-            config: Arc::new(config),
+            config: RwLock::new(config),
             api: Arc::new(api),
-            depth_levels,
-            min_liquidity,
+            depth_levels: AtomicUsize::new(depth_levels),
+            min_liquidity: Mutex::new(min_liquidity),
         }
     }
     
-    /// 分析订单簿深度，计算可用流动性和预期滑点
-    async fn analyze_order_book_depth(&self, symbol: &str, side: &str, amount: Decimal) -> Result<(Decimal, Decimal)> {
-        // 获取订单簿数据
-        let order_book = self.api.get_order_book(symbol, Some(self.depth_levels as u32)).await?;
-        
-        // 根据交易方向选择买单或卖单
-        let orders = match side {
-            "buy" => &order_book.asks,  // 买入需要看卖单
-            "sell" => &order_book.bids, // 卖出需要看买单
-            _ => return Err(anyhow!("无效的交易方向: {}", side)),
-        };
+    /// 检查订单簿两侧的挂单总量是否满足最小流动性要求
+    fn has_liquidity(&self, book: &OrderBook) -> bool {
+        let bid_qty: Decimal = book.bids.iter().map(|(_, qty)| *qty).sum();
+        let ask_qty: Decimal = book.asks.iter().map(|(_, qty)| *qty).sum();
+        let min_liquidity = *self.min_liquidity.lock().unwrap();
         
-        if orders.is_empty() {
-            return Err(anyhow!("订单簿为空"));
-        }
-        
-        // 计算可用流动性和加权平均价格
-        let mut remaining_amount = amount;
-        let mut total_cost = Decimal::ZERO;
-        let mut total_executed = Decimal::ZERO;
-        let best_price = orders[0].0;
-        
-        for (price, qty) in orders {
-            if remaining_amount <= Decimal::ZERO {
-                break;
-            }
-            
-            let execute_qty = if remaining_amount > *qty {
-                *qty
-            } else {
-                remaining_amount
-            };
-            
-            total_cost += execute_qty * (*price);
-            total_executed += execute_qty;
-            remaining_amount -= execute_qty;
+        if bid_qty < min_liquidity || ask_qty < min_liquidity {
+            info!(
+                "{} 流动性不足 - 买盘:{}, 卖盘:{}, 最小要求:{}",
+                book.symbol, bid_qty, ask_qty, min_liquidity
+            );
+            return false;
         }
         
-        // 如果无法完全执行订单，返回错误
-        if remaining_amount > Decimal::ZERO {
-            warn!(
-                "订单簿深度不足: {} - 需要: {}, 可用: {}, 缺口: {}",
-                symbol, amount, total_executed, remaining_amount
+        true
+    }
+    
+    /// 检查订单簿两侧返回的价格档位数量是否达到 `min_levels`
+    fn has_enough_levels(book: &OrderBook, min_levels: usize) -> bool {
+        if book.bids.len() < min_levels || book.asks.len() < min_levels {
+            info!(
+                "{} 订单簿档位不足 - 买盘:{}档, 卖盘:{}档, 最少要求:{}档",
+                book.symbol, book.bids.len(), book.asks.len(), min_levels
             );
-            return Ok((total_executed, Decimal::ZERO));
+            return false;
         }
         
-        // 计算加权平均价格
-        let avg_price = if total_executed > Decimal::ZERO {
-            total_cost / total_executed
-        } else {
-            best_price
-        };
-        
-        // 计算滑点（相对于最佳价格的百分比）
-        let slippage = match side {
-            "buy" => (avg_price - best_price) / best_price * dec!(100),
-            "sell" => (best_price - avg_price) / best_price * dec!(100),
-            _ => Decimal::ZERO,
-        };
-        
-        debug!(
-            "{} 订单簿分析 - 方向: {}, 数量: {}, 加权均价: {}, 滑点: {}%",
-            symbol, side, amount, avg_price, slippage
-        );
-        
-        Ok((total_executed, slippage))
+        true
     }
 }
 
@@ -110,100 +152,297 @@ impl<T: ExchangeApi + Send + Sync + 'static> TradingStrategy for OrderBookDepthS
         "通过分析订单簿深度来判断市场流动性和潜在的滑点，避免在流动性不足的市场中进行套利"
     }
     
-    async fn find_opportunity(&self, base_asset: &str, usdt_price: &Price, usdc_price: &Price) -> Result<Option<ArbitrageOpportunity>> {
-        let max_trade_amount = Decimal::from(self.config.arbitrage_settings.max_trade_amount_usdt);
+    /// 订单簿分析关注盘口，使用买卖中间价作为参考价格
+    fn price_source(&self) -> PriceSource {
+        PriceSource::BookMid
+    }
+    
+    fn update_params(&self, config: &Config) -> bool {
+        let settings = &config.strategy_settings.order_book_depth;
+        self.depth_levels.store(settings.depth_levels, Ordering::Relaxed);
+        *self.min_liquidity.lock().unwrap() = settings.min_liquidity;
+        *self.config.write().unwrap() = config.clone();
+        true
+    }
+    
+    async fn find_opportunity(&self, base_asset: &str, _usdt_price: &Price, _usdc_price: &Price) -> Result<Option<ArbitrageOpportunity>> {
+        let (notional, min_book_levels, slippage_model, min_impact_pct) = {
+            let config = self.config.read().unwrap();
+            let depth = &config.strategy_settings.order_book_depth;
+            (config.arbitrage_settings.max_trade_amount_usdt, depth.min_book_levels, depth.slippage_model, depth.min_impact_pct)
+        };
         
         // 构造交易对名称
         let usdt_symbol = format!("{}{}", base_asset, "USDT");
         let usdc_symbol = format!("{}{}", base_asset, "USDC");
         
-        // 估算交易量（以基础货币计）
-        let approx_base_amount = max_trade_amount / usdt_price.price;
-        
-        // 分析USDT和USDC市场的订单簿深度
-        let (usdt_buy_liquidity, usdt_buy_slippage) = self.analyze_order_book_depth(&usdt_symbol, "buy", approx_base_amount).await?;
-        let (usdt_sell_liquidity, usdt_sell_slippage) = self.analyze_order_book_depth(&usdt_symbol, "sell", approx_base_amount).await?;
-        
-        let (usdc_buy_liquidity, usdc_buy_slippage) = self.analyze_order_book_depth(&usdc_symbol, "buy", approx_base_amount).await?;
-        let (usdc_sell_liquidity, usdc_sell_slippage) = self.analyze_order_book_depth(&usdc_symbol, "sell", approx_base_amount).await?;
+        // 获取USDT和USDC市场的订单簿
+        let limit = Some(self.depth_levels.load(Ordering::Relaxed) as u32);
+        let (usdt_book, usdc_book) = tokio::try_join!(
+            self.api.get_order_book(&usdt_symbol, limit),
+            self.api.get_order_book(&usdc_symbol, limit),
+        )?;
         
-        // 检查流动性是否满足要求
-        if usdt_buy_liquidity < self.min_liquidity || usdt_sell_liquidity < self.min_liquidity ||
-           usdc_buy_liquidity < self.min_liquidity || usdc_sell_liquidity < self.min_liquidity {
-            info!(
-                "流动性不足，放弃套利机会 - USDT买:{}/卖:{}, USDC买:{}/卖:{}, 最小要求:{}",
-                usdt_buy_liquidity, usdt_sell_liquidity, 
-                usdc_buy_liquidity, usdc_sell_liquidity,
-                self.min_liquidity
-            );
+        if !Self::has_enough_levels(&usdt_book, min_book_levels) || !Self::has_enough_levels(&usdc_book, min_book_levels) {
+            return Ok(None);
+        }
+        if !self.has_liquidity(&usdt_book) || !self.has_liquidity(&usdc_book) {
             return Ok(None);
         }
         
-        // 考虑滑点后的实际价格
-        let usdt_effective_buy_price = usdt_price.price * (Decimal::ONE + usdt_buy_slippage / dec!(100));
-        let usdt_effective_sell_price = usdt_price.price * (Decimal::ONE - usdt_sell_slippage / dec!(100));
+        // 按交易金额逐档计算两个方向的实际成交均价，取利润率更高的方向
+        let directions = [
+            (QuoteCurrency::USDT, QuoteCurrency::USDC, &usdt_book, &usdc_book),
+            (QuoteCurrency::USDC, QuoteCurrency::USDT, &usdc_book, &usdt_book),
+        ];
         
-        let usdc_effective_buy_price = usdc_price.price * (Decimal::ONE + usdc_buy_slippage / dec!(100));
-        let usdc_effective_sell_price = usdc_price.price * (Decimal::ONE - usdc_sell_slippage / dec!(100));
+        let mut best: Option<ArbitrageOpportunity> = None;
         
-        // 考虑滑点后的套利方向
-        let opportunity = if usdt_effective_buy_price < usdc_effective_sell_price {
-            // USDT买入，USDC卖出
-            let effective_profit = (usdc_effective_sell_price - usdt_effective_buy_price) / usdt_effective_buy_price * dec!(100);
+        for (buy_quote, sell_quote, buy_book, sell_book) in directions {
+            let (buy, sell) = match depth_weighted_fills(buy_book, sell_book, notional) {
+                Some(fills) => fills,
+                None => match estimated_fills(buy_book, sell_book, notional, slippage_model, min_impact_pct) {
+                    Some(fills) => {
+                        debug!(
+                            "订单簿深度不足，按 {:?} 模型估算 {}买入/{}卖出 的成交均价: {} / {}",
+                            slippage_model, buy_quote, sell_quote, fills.0.avg_price, fills.1.avg_price
+                        );
+                        fills
+                    }
+                    None => {
+                        debug!("订单簿深度不足，无法以 {} 的金额完成 {}买入/{}卖出", notional, buy_quote, sell_quote);
+                        continue;
+                    }
+                },
+            };
             
-            info!(
-                "考虑滑点后 - USDT买入({})，USDC卖出({}), 有效利润率: {}%",
-                usdt_effective_buy_price, usdc_effective_sell_price, effective_profit
-            );
+            if sell.avg_price <= buy.avg_price {
+                continue;
+            }
             
-            ArbitrageOpportunity::new(
+            let opportunity = ArbitrageOpportunity::new(
                 base_asset,
-                QuoteCurrency::USDT,
-                QuoteCurrency::USDC,
-                usdt_effective_buy_price,
-                usdc_effective_sell_price,
-                max_trade_amount,
-            )
-        } else if usdc_effective_buy_price < usdt_effective_sell_price {
-            // USDC买入，USDT卖出
-            let effective_profit = (usdt_effective_sell_price - usdc_effective_buy_price) / usdc_effective_buy_price * dec!(100);
-            
-            info!(
-                "考虑滑点后 - USDC买入({})，USDT卖出({}), 有效利润率: {}%",
-                usdc_effective_buy_price, usdt_effective_sell_price, effective_profit
+                buy_quote,
+                sell_quote,
+                buy.avg_price,
+                sell.avg_price,
+                notional,
             );
             
-            ArbitrageOpportunity::new(
-                base_asset,
-                QuoteCurrency::USDC,
-                QuoteCurrency::USDT,
-                usdc_effective_buy_price,
-                usdt_effective_sell_price,
-                max_trade_amount,
-            )
-        } else {
-            // 考虑滑点后没有套利空间
-            debug!("考虑滑点后没有套利空间");
-            return Ok(None);
-        };
+            if best.as_ref().is_none_or(|b| opportunity.profit_percentage > b.profit_percentage) {
+                best = Some(opportunity);
+            }
+        }
+        
+        match &best {
+            Some(opportunity) => info!(
+                "按订单簿深度 - {}买入({})，{}卖出({}), 有效利润率: {}%",
+                opportunity.buy_quote, opportunity.buy_price,
+                opportunity.sell_quote, opportunity.sell_price,
+                opportunity.profit_percentage
+            ),
+            None => debug!("按订单簿深度计算后没有套利空间"),
+        }
         
-        Ok(Some(opportunity))
+        Ok(best)
     }
     
-    async fn validate_opportunity(&self, opportunity: &ArbitrageOpportunity) -> Result<bool> {
-        // 验证利润是否超过最小阈值（考虑滑点影响，这里使用更高的阈值）
-        let min_profit = Decimal::from(self.config.arbitrage_settings.min_profit_percentage);
-        let adjusted_min_profit = min_profit * Decimal::from(1.5); // 使用150%的阈值，因为订单簿分析已经考虑了滑点
+    async fn validate_opportunity(&self, opportunity: &ArbitrageOpportunity) -> Result<(bool, Option<Rejection>)> {
+        // 利润率已按逐档成交均价计算，直接与最小利润率比较
+        let min_profit = self.config.read().unwrap().arbitrage_settings.min_profit_percentage;
         
-        let is_valid = opportunity.profit_percentage >= adjusted_min_profit;
+        let (is_valid, rejection) = check_profit(opportunity, min_profit, RejectionReason::BelowMinProfit);
         
         debug!(
-            "订单簿深度策略验证: 利润率 {}% {} 调整后的最小要求 {}%",
+            "订单簿深度策略验证: 利润率 {}% {} 最小要求 {}%",
             opportunity.profit_percentage,
             if is_valid { "满足" } else { "不满足" },
-            adjusted_min_profit
+            min_profit
         );
         
-        Ok(is_valid)
+        Ok((is_valid, rejection))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::binance::MockBinanceApi;
+    use crate::config::ArbitrageSettings;
+    use chrono::Utc;
+
+    fn book(symbol: &str, bids: Vec<(Decimal, Decimal)>, asks: Vec<(Decimal, Decimal)>) -> OrderBook {
+        OrderBook { symbol: symbol.to_string(), bids, asks, timestamp: Utc::now() }
+    }
+
+    fn test_config(max_trade_amount: Decimal, min_profit: Decimal) -> Config {
+        Config {
+            arbitrage_settings: ArbitrageSettings {
+                max_trade_amount_usdt: max_trade_amount,
+                min_profit_percentage: min_profit,
+                ..ArbitrageSettings::default()
+            },
+            ..Config::default()
+        }
+    }
+
+    fn prices() -> (Price, Price) {
+        let now = Utc::now();
+        (
+            Price { symbol: "BTCUSDT".to_string(), price: dec!(100), timestamp: now },
+            Price { symbol: "BTCUSDC".to_string(), price: dec!(104), timestamp: now },
+        )
+    }
+
+    #[test]
+    fn test_depth_weighted_fills_match_manual_walk() {
+        let buy_book = book("BTCUSDT", vec![(dec!(99), dec!(10))], vec![(dec!(100), dec!(1)), (dec!(101), dec!(2)), (dec!(103), dec!(5))]);
+        let sell_book = book("BTCUSDC", vec![(dec!(104), dec!(0.5)), (dec!(103), dec!(1)), (dec!(99), dec!(10))], vec![(dec!(105), dec!(10))]);
+
+        let (buy, sell) = depth_weighted_fills(&buy_book, &sell_book, dec!(250)).unwrap();
+
+        // 买入：第一档花费 100 买到 1，剩余 150 在第二档以 101 成交
+        let qty = dec!(1) + dec!(150) / dec!(101);
+        assert_eq!(buy.qty, qty);
+        assert_eq!(buy.notional, dec!(250));
+        assert_eq!(buy.avg_price, dec!(250) / qty);
+        assert!(buy.avg_price > dec!(100) && buy.avg_price < dec!(101));
+
+        // 卖出同样数量：0.5 @ 104，1 @ 103，其余 @ 99
+        let rest = qty - dec!(0.5) - dec!(1);
+        let proceeds = dec!(52) + dec!(103) + rest * dec!(99);
+        assert_eq!(sell.qty, qty);
+        assert_eq!(sell.notional, proceeds);
+        assert_eq!(sell.avg_price, proceeds / qty);
+
+        // 金额恰好在档位内成交时均价等于该档价格
+        let (buy, sell) = depth_weighted_fills(&buy_book, &sell_book, dec!(50)).unwrap();
+        assert_eq!(buy.avg_price, dec!(100));
+        assert_eq!(sell.avg_price, dec!(104));
+
+        // 深度不足
+        assert!(depth_weighted_fills(&buy_book, &sell_book, dec!(10000)).is_none());
+        assert!(depth_weighted_fills(&buy_book, &book("BTCUSDC", vec![(dec!(104), dec!(0.1))], Vec::new()), dec!(50)).is_none());
+        assert!(depth_weighted_fills(&buy_book, &sell_book, Decimal::ZERO).is_none());
+    }
+
+    #[test]
+    fn test_slippage_models_extrapolate_beyond_visible_depth() {
+        // 可见卖盘金额 201，最差档位偏离最优档位 1%；买入 804 为可见金额的 4 倍
+        let asks = book("BTCUSDT", Vec::new(), vec![(dec!(100), dec!(1)), (dec!(101), dec!(1))]);
+        assert!(estimate_buy_fill(&asks, dec!(804), SlippageModel::Disabled, dec!(0.1)).is_none());
+
+        // 线性模型外推滑点 4%，超出部分 603 按 104 成交
+        let linear = estimate_buy_fill(&asks, dec!(804), SlippageModel::Linear, dec!(0.1)).unwrap();
+        assert_eq!(linear.qty, dec!(2) + dec!(603) / dec!(104));
+        // 平方根模型外推滑点 1% × √4 = 2%，超出部分按 102 成交
+        let sqrt = estimate_buy_fill(&asks, dec!(804), SlippageModel::SquareRoot, dec!(0.1)).unwrap();
+        assert!((sqrt.qty - (dec!(2) + dec!(603) / dec!(102))).abs() < dec!(0.000001));
+        assert!(sqrt.avg_price < linear.avg_price);
+        assert!(linear.avg_price > dec!(100.5));
+
+        // 卖出可见数量 2 的 4 倍，线性模型外推价格 96，平方根模型 98
+        let bids = book("BTCUSDC", vec![(dec!(100), dec!(1)), (dec!(99), dec!(1))], Vec::new());
+        let linear = estimate_sell_fill(&bids, dec!(8), SlippageModel::Linear, dec!(0.1)).unwrap();
+        assert_eq!(linear.notional, dec!(199) + dec!(6) * dec!(96));
+        let sqrt = estimate_sell_fill(&bids, dec!(8), SlippageModel::SquareRoot, dec!(0.1)).unwrap();
+        assert!((sqrt.notional - (dec!(199) + dec!(6) * dec!(98))).abs() < dec!(0.000001));
+        assert!(sqrt.avg_price > linear.avg_price);
+
+        // 只有一档时按最小冲击滑点外推，深度足够时与逐档成交相同
+        let single = book("BTCUSDT", Vec::new(), vec![(dec!(100), dec!(1))]);
+        let fill = estimate_buy_fill(&single, dec!(200), SlippageModel::Linear, dec!(0.5)).unwrap();
+        assert_eq!(fill.qty, dec!(1) + dec!(100) / dec!(101));
+        assert_eq!(estimate_buy_fill(&asks, dec!(50), SlippageModel::Linear, dec!(0.1)), asks.buy_with_notional(dec!(50)));
+    }
+
+    #[tokio::test]
+    async fn test_slippage_model_keeps_opportunity_beyond_visible_depth() {
+        let api = MockBinanceApi::new();
+        api.set_order_book("BTCUSDT", vec![(dec!(99), dec!(10))], vec![(dec!(100), dec!(1))]);
+        api.set_order_book("BTCUSDC", vec![(dec!(110), dec!(10))], vec![(dec!(111), dec!(10))]);
+        let (usdt, usdc) = prices();
+
+        // 买入 200 超出卖盘可见金额 100，未配置模型时放弃
+        let strategy = OrderBookDepthStrategy::new(test_config(dec!(200), dec!(0.1)), api.clone(), 20, dec!(1));
+        assert!(strategy.find_opportunity("BTC", &usdt, &usdc).await.unwrap().is_none());
+
+        let mut config = test_config(dec!(200), dec!(0.1));
+        config.strategy_settings.order_book_depth.slippage_model = SlippageModel::SquareRoot;
+        let strategy = OrderBookDepthStrategy::new(config, api, 20, dec!(1));
+        let opportunity = strategy.find_opportunity("BTC", &usdt, &usdc).await.unwrap().expect("应按估算滑点发现套利机会");
+        assert_eq!(opportunity.buy_quote, QuoteCurrency::USDT);
+        assert!(opportunity.buy_price > dec!(100) && opportunity.buy_price < dec!(100.1));
+        assert_eq!(opportunity.sell_price, dec!(110));
+    }
+
+    #[tokio::test]
+    async fn test_opportunity_uses_depth_weighted_prices() {
+        let api = MockBinanceApi::new();
+        api.set_order_book("BTCUSDT", vec![(dec!(99), dec!(10))], vec![(dec!(100), dec!(1)), (dec!(101), dec!(2)), (dec!(103), dec!(5))]);
+        api.set_order_book("BTCUSDC", vec![(dec!(104), dec!(0.5)), (dec!(103), dec!(1)), (dec!(99), dec!(10))], vec![(dec!(105), dec!(10))]);
+        let strategy = OrderBookDepthStrategy::new(test_config(dec!(250), dec!(0.1)), api, 20, dec!(1));
+
+        let (usdt, usdc) = prices();
+        let opportunity = strategy.find_opportunity("BTC", &usdt, &usdc).await.unwrap().expect("应发现套利机会");
+
+        let (buy_book, sell_book) = (
+            book("BTCUSDT", vec![(dec!(99), dec!(10))], vec![(dec!(100), dec!(1)), (dec!(101), dec!(2)), (dec!(103), dec!(5))]),
+            book("BTCUSDC", vec![(dec!(104), dec!(0.5)), (dec!(103), dec!(1)), (dec!(99), dec!(10))], vec![(dec!(105), dec!(10))]),
+        );
+        let (buy, sell) = depth_weighted_fills(&buy_book, &sell_book, dec!(250)).unwrap();
+
+        assert_eq!(opportunity.buy_quote, QuoteCurrency::USDT);
+        assert_eq!(opportunity.sell_quote, QuoteCurrency::USDC);
+        assert_eq!(opportunity.buy_price, buy.avg_price);
+        assert_eq!(opportunity.sell_price, sell.avg_price);
+        assert_eq!(opportunity.max_trade_amount, dec!(250));
+        assert_eq!(opportunity.profit_percentage, (sell.avg_price - buy.avg_price) / buy.avg_price * dec!(100));
+
+        // 逐档成交的利润率低于最优档位之间的价差 (104 - 100) / 100
+        assert!(opportunity.profit_percentage < dec!(4));
+        assert_eq!(strategy.validate_opportunity(&opportunity).await.unwrap(), (true, None));
+    }
+
+    #[tokio::test]
+    async fn test_no_opportunity_when_depth_erases_spread() {
+        // 最优档位有 4% 价差，但交易金额需要吃到更差的档位，逐档成交后亏损
+        let api = MockBinanceApi::new();
+        api.set_order_book("BTCUSDT", vec![(dec!(99), dec!(10))], vec![(dec!(100), dec!(0.1)), (dec!(110), dec!(10))]);
+        api.set_order_book("BTCUSDC", vec![(dec!(104), dec!(0.1)), (dec!(95), dec!(10))], vec![(dec!(105), dec!(10))]);
+        let strategy = OrderBookDepthStrategy::new(test_config(dec!(500), dec!(0.1)), api, 20, dec!(1));
+
+        let (usdt, usdc) = prices();
+        assert!(strategy.find_opportunity("BTC", &usdt, &usdc).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_no_opportunity_when_book_has_too_few_levels() {
+        // 两侧都只有2档，深度足够覆盖交易金额
+        let api = MockBinanceApi::new();
+        api.set_order_book("BTCUSDT", vec![(dec!(99), dec!(10)), (dec!(98), dec!(10))], vec![(dec!(100), dec!(10)), (dec!(101), dec!(10))]);
+        api.set_order_book("BTCUSDC", vec![(dec!(104), dec!(10)), (dec!(103), dec!(10))], vec![(dec!(105), dec!(10)), (dec!(106), dec!(10))]);
+        let (usdt, usdc) = prices();
+
+        let mut config = test_config(dec!(250), dec!(0.1));
+        config.strategy_settings.order_book_depth.min_book_levels = 10;
+        let strategy = OrderBookDepthStrategy::new(config, api.clone(), 20, dec!(1));
+        assert!(strategy.find_opportunity("BTC", &usdt, &usdc).await.unwrap().is_none());
+
+        let mut config = test_config(dec!(250), dec!(0.1));
+        config.strategy_settings.order_book_depth.min_book_levels = 2;
+        let strategy = OrderBookDepthStrategy::new(config, api, 20, dec!(1));
+        assert!(strategy.find_opportunity("BTC", &usdt, &usdc).await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_no_opportunity_below_min_liquidity() {
+        let api = MockBinanceApi::new();
+        api.set_order_book("BTCUSDT", vec![(dec!(99), dec!(10))], vec![(dec!(100), dec!(0.5))]);
+        api.set_order_book("BTCUSDC", vec![(dec!(104), dec!(10))], vec![(dec!(105), dec!(10))]);
+        let strategy = OrderBookDepthStrategy::new(test_config(dec!(10), dec!(0.1)), api, 20, dec!(1));
+
+        let (usdt, usdc) = prices();
+        assert!(strategy.find_opportunity("BTC", &usdt, &usdc).await.unwrap().is_none());
     }
 }
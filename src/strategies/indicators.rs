@@ -0,0 +1,78 @@
+//! 策略共用的技术指标
+
+use rust_decimal::Decimal;
+
+/// 最近 `window` 个数据点的简单移动平均，数据不足时返回 None
+pub fn sma(values: &[Decimal], window: usize) -> Option<Decimal> {
+    if window == 0 || values.len() < window {
+        return None;
+    }
+
+    let recent = &values[values.len() - window..];
+    Some(recent.iter().sum::<Decimal>() / Decimal::from(window))
+}
+
+/// 指数移动平均，按数据点逐个更新
+///
+/// 平滑系数为 `smoothing / (window + 1)`（不超过1），`smoothing` 通常取2；
+/// 第一个数据点作为初始值。
+#[derive(Debug, Clone)]
+pub struct Ema {
+    alpha: Decimal,
+    value: Option<Decimal>,
+}
+
+impl Ema {
+    pub fn new(window: usize, smoothing: Decimal) -> Self {
+        let alpha = smoothing / Decimal::from(window + 1);
+        Self {
+            alpha: alpha.min(Decimal::ONE),
+            value: None,
+        }
+    }
+
+    /// 加入一个数据点，返回更新后的均值
+    pub fn update(&mut self, value: Decimal) -> Decimal {
+        let updated = match self.value {
+            Some(previous) => self.alpha * value + (Decimal::ONE - self.alpha) * previous,
+            None => value,
+        };
+        self.value = Some(updated);
+        updated
+    }
+
+    /// 当前均值，尚无数据时返回 None
+    pub fn value(&self) -> Option<Decimal> {
+        self.value
+    }
+
+    /// 清空数据
+    pub fn reset(&mut self) {
+        self.value = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_sma_and_ema() {
+        let values = [dec!(1), dec!(2), dec!(3), dec!(4)];
+        assert_eq!(sma(&values, 2), Some(dec!(3.5)));
+        assert_eq!(sma(&values, 4), Some(dec!(2.5)));
+        assert_eq!(sma(&values, 5), None);
+
+        // 窗口3、平滑系数2时 alpha = 0.5
+        let mut ema = Ema::new(3, dec!(2));
+        assert_eq!(ema.value(), None);
+        for value in values {
+            ema.update(value);
+        }
+        assert_eq!(ema.value(), Some(dec!(3.125)));
+
+        ema.reset();
+        assert_eq!(ema.update(dec!(10)), dec!(10));
+    }
+}
@@ -0,0 +1,115 @@
+use crate::binance::ExchangeApi;
+use crate::models::{ArbitrageOpportunity, OrderBook, Rejection, RejectionReason};
+use anyhow::Result;
+use async_trait::async_trait;
+use rust_decimal::Decimal;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use log::info;
+
+/// 订单簿来源，流动性检查只需要获取订单簿，任何交易所接口都可以作为来源
+#[async_trait]
+pub trait OrderBookSource: Send + Sync {
+    async fn order_book(&self, symbol: &str, limit: Option<u32>) -> Result<OrderBook>;
+}
+
+#[async_trait]
+impl<T: ExchangeApi + Send + Sync> OrderBookSource for T {
+    async fn order_book(&self, symbol: &str, limit: Option<u32>) -> Result<OrderBook> {
+        self.get_order_book(symbol, limit).await
+    }
+}
+
+/// 盘口流动性检查
+/// 策略验证套利机会时调用，买入交易对卖盘和卖出交易对买盘前几档的挂单量不足以覆盖计划交易量时拒绝该机会
+pub struct LiquidityGuard {
+    books: Arc<dyn OrderBookSource>,
+    /// 参与计算的价格档位数量，热加载配置时更新
+    levels: AtomicUsize,
+}
+
+/// 检查盘口挂单量能否覆盖套利机会的计划交易量
+///
+/// 计划交易量按 `max_trade_amount / buy_price` 换算为基础货币数量，与 `buy_book` 卖盘和 `sell_book` 买盘前 `levels` 档的挂单量比较。
+/// 能覆盖时返回 None，否则返回不足的原因。
+pub fn check_book_coverage(
+    buy_book: &OrderBook,
+    sell_book: &OrderBook,
+    opportunity: &ArbitrageOpportunity,
+    levels: usize,
+) -> Option<String> {
+    if opportunity.buy_price.is_zero() {
+        return Some("买入价格为0".to_string());
+    }
+    let qty = opportunity.max_trade_amount / opportunity.buy_price;
+    let ask_qty: Decimal = buy_book.asks.iter().take(levels).map(|(_, qty)| *qty).sum();
+    let bid_qty: Decimal = sell_book.bids.iter().take(levels).map(|(_, qty)| *qty).sum();
+
+    if ask_qty < qty {
+        return Some(format!("{} 卖盘前{}档挂单量 {} 不足计划买入数量 {}", buy_book.symbol, levels, ask_qty, qty));
+    }
+    if bid_qty < qty {
+        return Some(format!("{} 买盘前{}档挂单量 {} 不足计划卖出数量 {}", sell_book.symbol, levels, bid_qty, qty));
+    }
+    None
+}
+
+impl LiquidityGuard {
+    pub fn new(books: Arc<dyn OrderBookSource>, levels: usize) -> Self {
+        Self {
+            books,
+            levels: AtomicUsize::new(levels),
+        }
+    }
+
+    /// 更新参与计算的价格档位数量
+    pub fn set_levels(&self, levels: usize) {
+        self.levels.store(levels, Ordering::Relaxed);
+    }
+
+    /// 获取套利两边的订单簿并检查挂单量，能覆盖计划交易量时返回None，否则返回 `InsufficientLiquidity` 拒绝原因
+    pub async fn check(&self, opportunity: &ArbitrageOpportunity) -> Result<Option<Rejection>> {
+        let levels = self.levels.load(Ordering::Relaxed);
+        let buy_symbol = format!("{}{}", opportunity.base_asset, opportunity.buy_quote);
+        let sell_symbol = format!("{}{}", opportunity.base_asset, opportunity.sell_quote);
+        let limit = Some(levels as u32);
+        let (buy_book, sell_book) = tokio::try_join!(
+            self.books.order_book(&buy_symbol, limit),
+            self.books.order_book(&sell_symbol, limit),
+        )?;
+
+        let rejection = check_book_coverage(&buy_book, &sell_book, opportunity, levels).map(|reason| {
+            info!("盘口流动性不足，放弃套利机会: {}", reason);
+            Rejection::new(RejectionReason::InsufficientLiquidity, reason)
+        });
+        Ok(rejection)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::QuoteCurrency;
+    use chrono::Utc;
+    use rust_decimal_macros::dec;
+
+    fn book(symbol: &str, bids: Vec<(Decimal, Decimal)>, asks: Vec<(Decimal, Decimal)>) -> OrderBook {
+        OrderBook { symbol: symbol.to_string(), bids, asks, timestamp: Utc::now() }
+    }
+
+    #[test]
+    fn test_book_coverage_counts_configured_levels() {
+        // 计划买入 1000 / 50000 = 0.02 BTC
+        let opportunity = ArbitrageOpportunity::new("BTC", QuoteCurrency::USDT, QuoteCurrency::USDC, dec!(50000), dec!(50100), dec!(1000));
+        let buy_book = book("BTCUSDT", vec![], vec![(dec!(50000), dec!(0.01)), (dec!(50010), dec!(0.05))]);
+        let sell_book = book("BTCUSDC", vec![(dec!(50100), dec!(0.5))], vec![]);
+
+        let reason = check_book_coverage(&buy_book, &sell_book, &opportunity, 1).unwrap();
+        assert!(reason.contains("BTCUSDT"));
+        assert_eq!(check_book_coverage(&buy_book, &sell_book, &opportunity, 2), None);
+
+        let thin_sell_book = book("BTCUSDC", vec![(dec!(50100), dec!(0.001))], vec![]);
+        let reason = check_book_coverage(&buy_book, &thin_sell_book, &opportunity, 2).unwrap();
+        assert!(reason.contains("BTCUSDC"));
+    }
+}
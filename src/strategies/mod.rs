@@ -1,10 +1,17 @@
-use crate::models::{Price, OrderBook, ArbitrageOpportunity, QuoteCurrency};
+use crate::models::{Price, PriceSource, OrderBook, ArbitrageOpportunity, QuoteCurrency, Rejection, RejectionReason};
 use crate::config::Config;
 use async_trait::async_trait;
 use anyhow::Result;
 use rust_decimal::Decimal;
 use std::sync::Arc;
 
+/// 策略信号：发现的套利机会及策略对其的置信度 (0-1)
+#[derive(Debug, Clone)]
+pub struct StrategySignal {
+    pub opportunity: ArbitrageOpportunity,
+    pub confidence: f64,
+}
+
 /// 交易策略接口
 #[async_trait]
 pub trait TradingStrategy: Send + Sync {
@@ -14,11 +21,45 @@ pub trait TradingStrategy: Send + Sync {
     /// 策略描述
     fn description(&self) -> &str;
     
+    /// 策略使用的参考价格来源，引擎按此获取传入 `find_opportunity` 的价格
+    fn price_source(&self) -> PriceSource {
+        PriceSource::Ticker
+    }
+    
     /// 分析市场数据，寻找套利机会
     async fn find_opportunity(&self, base_asset: &str, usdt_price: &Price, usdc_price: &Price) -> Result<Option<ArbitrageOpportunity>>;
     
     /// 验证套利机会是否符合策略要求
-    async fn validate_opportunity(&self, opportunity: &ArbitrageOpportunity) -> Result<bool>;
+    /// 返回: 是否符合要求, 拒绝原因(如果不符合)
+    async fn validate_opportunity(&self, opportunity: &ArbitrageOpportunity) -> Result<(bool, Option<Rejection>)>;
+    
+    /// 热加载配置时原地更新策略参数，保留价格历史等已累积的状态
+    ///
+    /// 返回false表示不支持原地更新，由调用方重新创建策略。
+    fn update_params(&self, _config: &Config) -> bool {
+        false
+    }
+    
+    /// 生成策略信号，供组合策略投票使用
+    ///
+    /// 默认实现：发现机会且通过验证时置信度为1，否则不产生信号。
+    async fn evaluate(&self, base_asset: &str, usdt_price: &Price, usdc_price: &Price) -> Result<Option<StrategySignal>> {
+        match self.find_opportunity(base_asset, usdt_price, usdc_price).await? {
+            Some(opportunity) if self.validate_opportunity(&opportunity).await?.0 => {
+                Ok(Some(StrategySignal { opportunity, confidence: 1.0 }))
+            }
+            _ => Ok(None),
+        }
+    }
+}
+
+/// 利润率达到 `required` 时通过，否则按 `reason` 拒绝，用于各策略的 `validate_opportunity`
+pub(crate) fn check_profit(opportunity: &ArbitrageOpportunity, required: Decimal, reason: RejectionReason) -> (bool, Option<Rejection>) {
+    if opportunity.profit_percentage >= required {
+        return (true, None);
+    }
+    let message = format!("利润率 {}% 低于要求的 {}%", opportunity.profit_percentage, required);
+    (false, Some(Rejection::new(reason, message)))
 }
 
 pub mod simple;
@@ -26,6 +67,9 @@ pub mod twap;
 pub mod depth;
 pub mod slippage;
 pub mod trend;
+pub mod composite;
+pub mod indicators;
+pub mod liquidity;
 
 // 重导出所有策略
 pub use simple::SimpleArbitrageStrategy;
@@ -33,3 +77,5 @@ pub use twap::TimeWeightedAverageStrategy;
 pub use depth::OrderBookDepthStrategy;
 pub use slippage::SlippageControlStrategy;
 pub use trend::TrendFollowingStrategy;
+pub use composite::CompositeStrategy;
+pub use liquidity::{LiquidityGuard, OrderBookSource};
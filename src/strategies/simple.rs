@@ -1,25 +1,35 @@
-use super::TradingStrategy;
-use crate::models::{ArbitrageOpportunity, Price, QuoteCurrency};
+use super::{check_profit, TradingStrategy};
+use super::liquidity::LiquidityGuard;
+use crate::models::{ArbitrageOpportunity, Price, QuoteCurrency, Rejection, RejectionReason};
 use crate::config::Config;
 use anyhow::Result;
 use async_trait::async_trait;
 use rust_decimal::Decimal;
-use std::sync::Arc;
+use std::sync::RwLock;
 use log::debug;
 
 /// 创建简单套利策略实现，这是目前系统中使用的基本策略
 /// 简单的价格差异套利策略
 /// 当USDT和USDC之间的价格差异超过设定阈值时，执行套利操作
 pub struct SimpleArbitrageStrategy {
-    config: Arc<Config>,
+    config: RwLock<Config>,
+    /// 盘口流动性检查，未设置时不检查
+    liquidity_guard: Option<LiquidityGuard>,
 }
 
 impl SimpleArbitrageStrategy {
     pub fn new(config: Config) -> Self {
         Self {
-            config: Arc::new(config),
+            config: RwLock::new(config),
+            liquidity_guard: None,
         }
     }
+    
+    /// 验证套利机会时检查盘口挂单量能否覆盖计划交易量
+    pub fn with_liquidity_guard(mut self, guard: LiquidityGuard) -> Self {
+        self.liquidity_guard = Some(guard);
+        self
+    }
 }
 
 #[async_trait]
@@ -32,8 +42,27 @@ impl TradingStrategy for SimpleArbitrageStrategy {
         "当USDT和USDC交易对之间的价格差异超过设定阈值时，买入价格较低的一方，卖出价格较高的一方"
     }
     
+    /// 开关流动性检查需要重新创建策略，档位数量可以原地更新
+    fn update_params(&self, config: &Config) -> bool {
+        let guard_settings = &config.strategy_settings.liquidity_guard;
+        if guard_settings.enabled != self.liquidity_guard.is_some() {
+            return false;
+        }
+        if let Some(guard) = &self.liquidity_guard {
+            guard.set_levels(guard_settings.levels);
+        }
+        *self.config.write().unwrap() = config.clone();
+        true
+    }
+    
     async fn find_opportunity(&self, base_asset: &str, usdt_price: &Price, usdc_price: &Price) -> Result<Option<ArbitrageOpportunity>> {
-        let max_trade_amount = Decimal::from(self.config.arbitrage_settings.max_trade_amount_usdt);
+        // 价格相同时没有价差，不构造零利润的机会
+        if usdt_price.price == usdc_price.price {
+            debug!("{} 两个交易对价格相同 ({})，没有套利机会", base_asset, usdt_price.price);
+            return Ok(None);
+        }
+        
+        let max_trade_amount = self.config.read().unwrap().arbitrage_settings.max_trade_amount_usdt;
         
         // 比较价格，确定买入和卖出方向
         let opportunity = if usdt_price.price < usdc_price.price {
@@ -71,10 +100,10 @@ impl TradingStrategy for SimpleArbitrageStrategy {
         Ok(Some(opportunity))
     }
     
-    async fn validate_opportunity(&self, opportunity: &ArbitrageOpportunity) -> Result<bool> {
+    async fn validate_opportunity(&self, opportunity: &ArbitrageOpportunity) -> Result<(bool, Option<Rejection>)> {
         // 验证利润是否超过最小阈值
-        let min_profit = Decimal::from(self.config.arbitrage_settings.min_profit_percentage);
-        let is_valid = opportunity.profit_percentage >= min_profit;
+        let min_profit = self.config.read().unwrap().arbitrage_settings.min_profit_percentage;
+        let (is_valid, rejection) = check_profit(opportunity, min_profit, RejectionReason::BelowMinProfit);
         
         debug!(
             "套利机会验证: 利润率 {}% {} 最小要求 {}%",
@@ -82,7 +111,81 @@ impl TradingStrategy for SimpleArbitrageStrategy {
             if is_valid { "满足" } else { "不满足" },
             min_profit
         );
+        if !is_valid {
+            return Ok((false, rejection));
+        }
+        
+        // 利润率满足要求后再获取订单簿检查盘口流动性
+        if let Some(guard) = &self.liquidity_guard {
+            if let Some(rejection) = guard.check(opportunity).await? {
+                return Ok((false, Some(rejection)));
+            }
+        }
         
-        Ok(is_valid)
+        Ok((true, None))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::binance::MockBinanceApi;
+    use crate::config::ArbitrageSettings;
+    use chrono::Utc;
+    use rust_decimal_macros::dec;
+    use std::sync::Arc;
+
+    fn test_config() -> Config {
+        Config {
+            arbitrage_settings: ArbitrageSettings {
+                max_trade_amount_usdt: dec!(1000),
+                ..ArbitrageSettings::default()
+            },
+            ..Config::default()
+        }
+    }
+
+    fn prices() -> (Price, Price) {
+        let now = Utc::now();
+        (
+            Price { symbol: "BTCUSDT".to_string(), price: dec!(50000), timestamp: now },
+            Price { symbol: "BTCUSDC".to_string(), price: dec!(50100), timestamp: now },
+        )
+    }
+
+    #[tokio::test]
+    async fn test_thin_book_rejects_opportunity() {
+        let api = Arc::new(MockBinanceApi::new());
+        // 计划买入 1000 / 50000 = 0.02 BTC，USDT卖盘最优档只有0.001 BTC
+        api.set_order_book("BTCUSDT", vec![(dec!(49990), dec!(5))], vec![(dec!(50000), dec!(0.001))]);
+        api.set_order_book("BTCUSDC", vec![(dec!(50100), dec!(5))], vec![(dec!(50110), dec!(5))]);
+        let strategy = SimpleArbitrageStrategy::new(test_config())
+            .with_liquidity_guard(LiquidityGuard::new(api.clone(), 1));
+        let (usdt, usdc) = prices();
+
+        let opportunity = strategy.find_opportunity("BTC", &usdt, &usdc).await.unwrap().unwrap();
+        assert_eq!(opportunity.buy_quote, QuoteCurrency::USDT);
+        let (valid, rejection) = strategy.validate_opportunity(&opportunity).await.unwrap();
+        assert!(!valid);
+        assert_eq!(rejection.unwrap().reason, RejectionReason::InsufficientLiquidity);
+        assert!(strategy.evaluate("BTC", &usdt, &usdc).await.unwrap().is_none());
+
+        // 盘口补足后通过验证
+        api.set_order_book("BTCUSDT", vec![(dec!(49990), dec!(5))], vec![(dec!(50000), dec!(1))]);
+        assert_eq!(strategy.validate_opportunity(&opportunity).await.unwrap(), (true, None));
+    }
+
+    #[tokio::test]
+    async fn test_low_profit_rejected_as_below_min_profit() {
+        let mut config = test_config();
+        config.arbitrage_settings.min_profit_percentage = dec!(1);
+        let strategy = SimpleArbitrageStrategy::new(config);
+        let (usdt, usdc) = prices();
+
+        // 价差0.2%低于最小利润率1%
+        let opportunity = strategy.find_opportunity("BTC", &usdt, &usdc).await.unwrap().unwrap();
+        let (valid, rejection) = strategy.validate_opportunity(&opportunity).await.unwrap();
+        assert!(!valid);
+        assert_eq!(rejection.unwrap().reason, RejectionReason::BelowMinProfit);
     }
 }
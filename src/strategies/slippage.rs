@@ -1,5 +1,5 @@
-use super::TradingStrategy;
-use crate::models::{ArbitrageOpportunity, Price, QuoteCurrency};
+use super::{check_profit, TradingStrategy};
+use crate::models::{ArbitrageOpportunity, Price, QuoteCurrency, PriceSource, Rejection, RejectionReason};
 use crate::config::Config;
 use anyhow::Result;
 use async_trait::async_trait;
@@ -7,18 +7,19 @@ use rust_decimal::Decimal;
 use std::sync::Arc;
 use log::{debug, info, warn};
 use rust_decimal_macros::dec;
-use std::sync::Mutex;
+use std::sync::{Mutex, RwLock};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::collections::VecDeque;
 use chrono::{DateTime, Utc};
 
 /// 滑点控制策略
 /// 通过控制下单时的价格滑点，避免在价格波动较大的市场中产生亏损
 pub struct SlippageControlStrategy {
-    config: Arc<Config>,
+    config: RwLock<Config>,
     /// 最大允许的滑点百分比
-    max_slippage_pct: Decimal,
+    max_slippage_pct: Mutex<Decimal>,
     /// 历史价格波动率窗口大小
-    volatility_window_size: usize,
+    volatility_window_size: AtomicUsize,
     /// 历史价格数据
     price_history: Arc<Mutex<VecDeque<(DateTime<Utc>, Decimal, Decimal)>>>,
 }
@@ -26,9 +27,9 @@ pub struct SlippageControlStrategy {
 impl SlippageControlStrategy {
     pub fn new(config: Config, max_slippage_pct: Decimal, volatility_window_size: usize) -> Self {
         Self {
-            config: Arc::new(config),
-            max_slippage_pct,
-            volatility_window_size,
+            config: RwLock::new(config),
+            max_slippage_pct: Mutex::new(max_slippage_pct),
+            volatility_window_size: AtomicUsize::new(volatility_window_size),
             price_history: Arc::new(Mutex::new(VecDeque::with_capacity(volatility_window_size + 1))),
         }
     }
@@ -41,8 +42,8 @@ impl SlippageControlStrategy {
         // 添加新价格
         history.push_back((now, usdt_price, usdc_price));
         
-        // 保持窗口大小
-        if history.len() > self.volatility_window_size {
+        // 保持窗口大小，窗口缩小后一次丢弃多余的记录
+        while history.len() > self.volatility_window_size.load(Ordering::Relaxed) {
             history.pop_front();
         }
     }
@@ -104,32 +105,25 @@ impl SlippageControlStrategy {
         // 基于波动率调整价格
         // 如果波动率高，我们需要设置更严格的价格限制，避免成交价格大幅偏离预期
         let volatility_factor = Decimal::ONE + (max_vol / dec!(100));
+        let max_slippage_pct = *self.max_slippage_pct.lock().unwrap();
         
         // 根据交易方向调整价格
         match (opportunity.buy_quote, opportunity.sell_quote) {
             (QuoteCurrency::USDT, QuoteCurrency::USDC) => {
                 // 买入价格略低，卖出价格略高
-                opportunity.buy_price = opportunity.buy_price * (Decimal::ONE - self.max_slippage_pct / dec!(100) / volatility_factor);
-                opportunity.sell_price = opportunity.sell_price * (Decimal::ONE + self.max_slippage_pct / dec!(100) / volatility_factor);
+                opportunity.buy_price = opportunity.buy_price * (Decimal::ONE - max_slippage_pct / dec!(100) / volatility_factor);
+                opportunity.sell_price = opportunity.sell_price * (Decimal::ONE + max_slippage_pct / dec!(100) / volatility_factor);
             },
             (QuoteCurrency::USDC, QuoteCurrency::USDT) => {
                 // 买入价格略低，卖出价格略高
-                opportunity.buy_price = opportunity.buy_price * (Decimal::ONE - self.max_slippage_pct / dec!(100) / volatility_factor);
-                opportunity.sell_price = opportunity.sell_price * (Decimal::ONE + self.max_slippage_pct / dec!(100) / volatility_factor);
+                opportunity.buy_price = opportunity.buy_price * (Decimal::ONE - max_slippage_pct / dec!(100) / volatility_factor);
+                opportunity.sell_price = opportunity.sell_price * (Decimal::ONE + max_slippage_pct / dec!(100) / volatility_factor);
             },
             _ => {}
         }
         
         // 重新计算利润率
-        let price_diff = opportunity.sell_price - opportunity.buy_price;
-        let profit_percentage = if opportunity.buy_price.is_zero() {
-            Decimal::ZERO
-        } else {
-            (price_diff / opportunity.buy_price) * Decimal::from(100)
-        };
-        
-        opportunity.price_diff = price_diff;
-        opportunity.profit_percentage = profit_percentage;
+        opportunity.set_prices(opportunity.buy_price, opportunity.sell_price);
         
         max_vol
     }
@@ -145,11 +139,30 @@ impl TradingStrategy for SlippageControlStrategy {
         "通过控制下单时的价格滑点，在波动较大的市场中保护套利交易"
     }
     
+    /// 滑点以盘口为基准衡量，使用买卖中间价作为参考价格
+    fn price_source(&self) -> PriceSource {
+        PriceSource::BookMid
+    }
+    
+    fn update_params(&self, config: &Config) -> bool {
+        let settings = &config.strategy_settings.slippage_control;
+        *self.max_slippage_pct.lock().unwrap() = settings.max_slippage_pct;
+        self.volatility_window_size.store(settings.volatility_window_size, Ordering::Relaxed);
+        *self.config.write().unwrap() = config.clone();
+        true
+    }
+    
     async fn find_opportunity(&self, base_asset: &str, usdt_price: &Price, usdc_price: &Price) -> Result<Option<ArbitrageOpportunity>> {
         // 记录价格历史
         self.record_price(usdt_price.price, usdc_price.price);
         
-        let max_trade_amount = Decimal::from(self.config.arbitrage_settings.max_trade_amount_usdt);
+        // 价格相同时没有价差，价格仍计入波动率历史
+        if usdt_price.price == usdc_price.price {
+            debug!("{} 两个交易对价格相同 ({})，没有套利机会", base_asset, usdt_price.price);
+            return Ok(None);
+        }
+        
+        let max_trade_amount = self.config.read().unwrap().arbitrage_settings.max_trade_amount_usdt;
         
         // 基于当前价格创建潜在的套利机会
         let mut opportunity = if usdt_price.price < usdc_price.price {
@@ -191,8 +204,8 @@ impl TradingStrategy for SlippageControlStrategy {
         Ok(Some(opportunity))
     }
     
-    async fn validate_opportunity(&self, opportunity: &ArbitrageOpportunity) -> Result<bool> {
-        let min_profit = Decimal::from(self.config.arbitrage_settings.min_profit_percentage);
+    async fn validate_opportunity(&self, opportunity: &ArbitrageOpportunity) -> Result<(bool, Option<Rejection>)> {
+        let min_profit = self.config.read().unwrap().arbitrage_settings.min_profit_percentage;
         
         // 根据波动率调整最小利润要求
         let (usdt_vol, usdc_vol) = self.calculate_volatility();
@@ -202,7 +215,12 @@ impl TradingStrategy for SlippageControlStrategy {
         let volatility_factor = Decimal::ONE + (max_vol / dec!(20)); // 每5%的波动率增加20%的利润要求
         let adjusted_min_profit = min_profit * volatility_factor;
         
-        let is_valid = opportunity.profit_percentage >= adjusted_min_profit;
+        // 未达到最小利润率时按利润不足拒绝，只是未达到按波动率提高的要求时按波动过大拒绝
+        let (is_valid, rejection) = if opportunity.profit_percentage < min_profit {
+            check_profit(opportunity, min_profit, RejectionReason::BelowMinProfit)
+        } else {
+            check_profit(opportunity, adjusted_min_profit, RejectionReason::HighVolatility)
+        };
         
         debug!(
             "滑点策略验证: 利润率 {}%, 波动率 {}%, {} 调整后的最小要求 {}%",
@@ -212,6 +230,26 @@ impl TradingStrategy for SlippageControlStrategy {
             adjusted_min_profit
         );
         
-        Ok(is_valid)
+        Ok((is_valid, rejection))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_high_volatility_raises_required_profit() {
+        let strategy = SlippageControlStrategy::new(Config::default(), dec!(0.1), 10);
+        let opportunity = ArbitrageOpportunity::new("BTC", QuoteCurrency::USDT, QuoteCurrency::USDC, dec!(100), dec!(100.1), dec!(1000));
+        assert_eq!(strategy.validate_opportunity(&opportunity).await.unwrap(), (true, None));
+
+        // 波动率约10%，最小利润率提高到约0.15%
+        for price in [dec!(100), dec!(110), dec!(90)] {
+            strategy.record_price(price, price);
+        }
+        let (valid, rejection) = strategy.validate_opportunity(&opportunity).await.unwrap();
+        assert!(!valid);
+        assert_eq!(rejection.unwrap().reason, RejectionReason::HighVolatility);
     }
 }
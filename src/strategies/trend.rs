@@ -1,13 +1,15 @@
-use super::TradingStrategy;
-use crate::models::{ArbitrageOpportunity, Price, QuoteCurrency};
-use crate::config::Config;
+use super::{check_profit, TradingStrategy};
+use super::indicators::{sma, Ema};
+use super::liquidity::LiquidityGuard;
+use crate::models::{ArbitrageOpportunity, Price, QuoteCurrency, PriceSource, Rejection, RejectionReason};
+use crate::config::{Config, TrendMethod};
 use anyhow::Result;
 use async_trait::async_trait;
 use rust_decimal::Decimal;
 use std::sync::Arc;
 use log::{debug, info, warn};
 use rust_decimal_macros::dec;
-use std::sync::Mutex;
+use std::sync::{Mutex, RwLock};
 use std::collections::VecDeque;
 use chrono::{DateTime, Utc, Duration};
 
@@ -32,15 +34,23 @@ impl std::fmt::Display for TrendDirection {
 /// 趋势跟踪策略
 /// 分析短期价格趋势，避免在价格波动的不利方向进行套利
 pub struct TrendFollowingStrategy {
-    config: Arc<Config>,
+    config: RwLock<Config>,
     /// 价格历史
     price_history: Arc<Mutex<VecDeque<(DateTime<Utc>, Decimal, Decimal)>>>,
     /// 短期趋势窗口（数据点数量）
     short_window: usize,
     /// 长期趋势窗口（数据点数量）
     long_window: usize,
-    /// 趋势判断阈值（百分比）
-    trend_threshold: Decimal,
+    /// 趋势判断阈值（百分比），热加载配置时更新
+    trend_threshold: Mutex<Decimal>,
+    /// 短期和长期均线的计算方式
+    trend_method: TrendMethod,
+    /// EMA平滑因子
+    ema_smoothing: Decimal,
+    /// USDT和USDC价格的短期、长期指数移动平均，只在EMA模式下使用
+    emas: Mutex<[(Ema, Ema); 2]>,
+    /// 盘口流动性检查，未设置时不检查
+    liquidity_guard: Option<LiquidityGuard>,
 }
 
 impl TrendFollowingStrategy {
@@ -51,14 +61,37 @@ impl TrendFollowingStrategy {
         trend_threshold: Decimal
     ) -> Self {
         Self {
-            config: Arc::new(config),
+            config: RwLock::new(config),
             price_history: Arc::new(Mutex::new(VecDeque::with_capacity(long_window + 1))),
             short_window,
             long_window,
-            trend_threshold,
+            trend_threshold: Mutex::new(trend_threshold),
+            trend_method: TrendMethod::Sma,
+            ema_smoothing: dec!(2),
+            emas: Mutex::new(Self::new_emas(short_window, long_window, dec!(2))),
+            liquidity_guard: None,
         }
     }
     
+    /// 验证套利机会时检查盘口挂单量能否覆盖计划交易量
+    pub fn with_liquidity_guard(mut self, guard: LiquidityGuard) -> Self {
+        self.liquidity_guard = Some(guard);
+        self
+    }
+    
+    /// 设置均线计算方式，`smoothing` 为EMA的平滑因子（平滑系数为 smoothing / (窗口 + 1)）
+    pub fn with_trend_method(mut self, trend_method: TrendMethod, smoothing: Decimal) -> Self {
+        self.trend_method = trend_method;
+        self.ema_smoothing = smoothing;
+        self.emas = Mutex::new(Self::new_emas(self.short_window, self.long_window, smoothing));
+        self
+    }
+    
+    fn new_emas(short_window: usize, long_window: usize, smoothing: Decimal) -> [(Ema, Ema); 2] {
+        let pair = (Ema::new(short_window, smoothing), Ema::new(long_window, smoothing));
+        [pair.clone(), pair]
+    }
+    
     /// 记录价格历史
     fn record_price(&self, usdt_price: Decimal, usdc_price: Decimal) {
         let now = Utc::now();
@@ -67,6 +100,15 @@ impl TrendFollowingStrategy {
         // 添加新价格
         history.push_back((now, usdt_price, usdc_price));
         
+        // EMA按每个数据点递推，不受窗口截断影响
+        if self.trend_method == TrendMethod::Ema {
+            let mut emas = self.emas.lock().unwrap();
+            for ((short, long), price) in emas.iter_mut().zip([usdt_price, usdc_price]) {
+                short.update(price);
+                long.update(price);
+            }
+        }
+        
         // 保持窗口大小
         if history.len() > self.long_window {
             history.pop_front();
@@ -88,22 +130,29 @@ impl TrendFollowingStrategy {
             history.iter().map(|(_, _, usdc)| *usdc).collect()
         };
         
-        // 计算短期均价（最近N个数据点）
-        let short_window_prices = prices.iter().rev().take(self.short_window);
-        let short_mean: Decimal = short_window_prices.clone().sum::<Decimal>() / Decimal::from(self.short_window);
+        // 数据点足够时计算短期和长期均价，EMA同样等到长期窗口填满后才判断趋势
+        let means = if history.len() < self.long_window {
+            None
+        } else {
+            match self.trend_method {
+                TrendMethod::Sma => sma(&prices, self.short_window).zip(sma(&prices, self.long_window)),
+                TrendMethod::Ema => {
+                    let emas = self.emas.lock().unwrap();
+                    let (short, long) = &emas[if is_usdt { 0 } else { 1 }];
+                    short.value().zip(long.value())
+                },
+            }
+        };
         
-        // 如果数据点足够，计算长期均价
-        if history.len() >= self.long_window {
-            let long_window_prices = prices.iter().rev().take(self.long_window);
-            let long_mean: Decimal = long_window_prices.sum::<Decimal>() / Decimal::from(self.long_window);
-            
+        if let Some((short_mean, long_mean)) = means.filter(|(_, long_mean)| !long_mean.is_zero()) {
             // 计算趋势变化百分比
             let trend_change = ((short_mean - long_mean) / long_mean) * dec!(100);
             
             // 根据阈值判断趋势方向
-            let direction = if trend_change > self.trend_threshold {
+            let trend_threshold = *self.trend_threshold.lock().unwrap();
+            let direction = if trend_change > trend_threshold {
                 TrendDirection::Up
-            } else if trend_change < -self.trend_threshold {
+            } else if trend_change < -trend_threshold {
                 TrendDirection::Down
             } else {
                 TrendDirection::Sideways
@@ -172,10 +221,41 @@ impl TradingStrategy for TrendFollowingStrategy {
         "分析短期价格趋势，避免在价格波动的不利方向进行套利"
     }
     
+    /// 趋势基于实际成交价格判断
+    fn price_source(&self) -> PriceSource {
+        PriceSource::LastTrade
+    }
+    
+    /// 窗口、均线方式或平滑因子变化后已累积的均线不再适用，开关流动性检查也需要重新创建
+    fn update_params(&self, config: &Config) -> bool {
+        let settings = &config.strategy_settings.trend_following;
+        if settings.short_window != self.short_window
+            || settings.long_window != self.long_window
+            || settings.trend_method != self.trend_method
+            || settings.ema_smoothing != self.ema_smoothing
+            || config.strategy_settings.liquidity_guard.enabled != self.liquidity_guard.is_some()
+        {
+            return false;
+        }
+        
+        if let Some(guard) = &self.liquidity_guard {
+            guard.set_levels(config.strategy_settings.liquidity_guard.levels);
+        }
+        *self.trend_threshold.lock().unwrap() = settings.trend_threshold;
+        *self.config.write().unwrap() = config.clone();
+        true
+    }
+    
     async fn find_opportunity(&self, base_asset: &str, usdt_price: &Price, usdc_price: &Price) -> Result<Option<ArbitrageOpportunity>> {
         // 记录价格历史
         self.record_price(usdt_price.price, usdc_price.price);
         
+        // 价格相同时没有价差，价格仍计入趋势历史
+        if usdt_price.price == usdc_price.price {
+            debug!("{} 两个交易对价格相同 ({})，没有套利机会", base_asset, usdt_price.price);
+            return Ok(None);
+        }
+        
         // 检查是否有最近的异常波动，如果有则避免交易
         if self.has_recent_volatility_spike(5) {  // 检查过去5分钟
             warn!("检测到最近的异常价格波动，暂停套利操作");
@@ -192,7 +272,7 @@ impl TradingStrategy for TrendFollowingStrategy {
         );
         
         // 基于趋势做出决策
-        let max_trade_amount = Decimal::from(self.config.arbitrage_settings.max_trade_amount_usdt);
+        let max_trade_amount = self.config.read().unwrap().arbitrage_settings.max_trade_amount_usdt;
         
         // 套利方向决策
         let mut opportunity = if usdt_price.price < usdc_price.price {
@@ -260,9 +340,9 @@ impl TradingStrategy for TrendFollowingStrategy {
         Ok(Some(opportunity))
     }
     
-    async fn validate_opportunity(&self, opportunity: &ArbitrageOpportunity) -> Result<bool> {
+    async fn validate_opportunity(&self, opportunity: &ArbitrageOpportunity) -> Result<(bool, Option<Rejection>)> {
         // 获取最小利润阈值
-        let min_profit = Decimal::from(self.config.arbitrage_settings.min_profit_percentage);
+        let min_profit = self.config.read().unwrap().arbitrage_settings.min_profit_percentage;
         
         // 在趋势强烈的情况下，增加最小利润要求
         let (usdt_trend, usdt_strength) = self.calculate_trend(true);
@@ -274,7 +354,12 @@ impl TradingStrategy for TrendFollowingStrategy {
         let profit_multiplier = Decimal::ONE + (trend_strength / dec!(10.0));
         let adjusted_min_profit = min_profit * profit_multiplier;
         
-        let is_valid = opportunity.profit_percentage >= adjusted_min_profit;
+        // 未达到最小利润率时按利润不足拒绝，只是未达到按趋势提高的要求时按不利趋势拒绝
+        let (is_valid, rejection) = if opportunity.profit_percentage < min_profit {
+            check_profit(opportunity, min_profit, RejectionReason::BelowMinProfit)
+        } else {
+            check_profit(opportunity, adjusted_min_profit, RejectionReason::AdverseTrend)
+        };
         
         debug!(
             "趋势策略验证: 利润率 {:.2}%, 趋势强度 {:.2}%, {} 调整后的最小要求 {:.2}%",
@@ -283,7 +368,99 @@ impl TradingStrategy for TrendFollowingStrategy {
             if is_valid { "满足" } else { "不满足" },
             adjusted_min_profit
         );
+        if !is_valid {
+            return Ok((false, rejection));
+        }
+        
+        // 按调整后的交易金额检查盘口
+        if let Some(guard) = &self.liquidity_guard {
+            if let Some(rejection) = guard.check(opportunity).await? {
+                return Ok((false, Some(rejection)));
+            }
+        }
+        
+        Ok((true, None))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_strategy(trend_method: TrendMethod) -> TrendFollowingStrategy {
+        TrendFollowingStrategy::new(Config::default(), 5, 20, dec!(0.8)).with_trend_method(trend_method, dec!(2))
+    }
+
+    /// 返回第一次判断为上涨趋势时的数据点序号
+    fn first_uptrend(strategy: &TrendFollowingStrategy, prices: &[Decimal]) -> Option<usize> {
+        prices.iter().enumerate().find_map(|(i, price)| {
+            strategy.record_price(*price, *price);
+            (strategy.calculate_trend(true).0 == TrendDirection::Up).then_some(i + 1)
+        })
+    }
+
+    #[test]
+    fn test_ema_detects_reversal_earlier_than_sma() {
+        // 缓慢下跌25个点后快速上涨
+        let mut prices: Vec<Decimal> = (0..25).map(|i| dec!(100) - dec!(0.2) * Decimal::from(i)).collect();
+        prices.extend((1..15).map(|i| dec!(95.2) + dec!(2) * Decimal::from(i)));
+
+        let sma_flip = first_uptrend(&test_strategy(TrendMethod::Sma), &prices).unwrap();
+        let ema_flip = first_uptrend(&test_strategy(TrendMethod::Ema), &prices).unwrap();
+        assert_eq!(sma_flip, 29);
+        assert_eq!(ema_flip, 28);
+        assert!(ema_flip < sma_flip);
+    }
+
+    #[test]
+    fn test_update_params_keeps_history() {
+        let strategy = test_strategy(TrendMethod::Sma);
+        for i in 0..20 {
+            strategy.record_price(dec!(100) + Decimal::from(i), dec!(100));
+        }
+        assert_eq!(strategy.calculate_trend(true).0, TrendDirection::Up);
+
+        // 提高阈值后按已有的价格历史重新判断
+        let mut config = strategy.config.read().unwrap().clone();
+        let settings = &mut config.strategy_settings.trend_following;
+        settings.short_window = 5;
+        settings.long_window = 20;
+        settings.trend_method = TrendMethod::Sma;
+        settings.ema_smoothing = dec!(2);
+        settings.trend_threshold = dec!(50);
+        assert!(strategy.update_params(&config));
+        assert_eq!(strategy.calculate_trend(true).0, TrendDirection::Sideways);
+
+        // 窗口变化需要重新创建
+        config.strategy_settings.trend_following.long_window = 30;
+        assert!(!strategy.update_params(&config));
+    }
+
+    #[tokio::test]
+    async fn test_strong_trend_rejected_as_adverse_trend() {
+        let strategy = test_strategy(TrendMethod::Sma);
+        for i in 0..20 {
+            strategy.record_price(dec!(100) + Decimal::from(i), dec!(100));
+        }
+        
+        // 利润率0.1%满足最小利润率，但低于按趋势强度提高后的要求
+        let opportunity = ArbitrageOpportunity::new("BTC", QuoteCurrency::USDT, QuoteCurrency::USDC, dec!(100), dec!(100.1), dec!(1000));
+        let (valid, rejection) = strategy.validate_opportunity(&opportunity).await.unwrap();
+        assert!(!valid);
+        assert_eq!(rejection.unwrap().reason, RejectionReason::AdverseTrend);
         
-        Ok(is_valid)
+        let opportunity = ArbitrageOpportunity::new("BTC", QuoteCurrency::USDT, QuoteCurrency::USDC, dec!(100), dec!(100.05), dec!(1000));
+        let (valid, rejection) = strategy.validate_opportunity(&opportunity).await.unwrap();
+        assert!(!valid);
+        assert_eq!(rejection.unwrap().reason, RejectionReason::BelowMinProfit);
+    }
+
+    #[test]
+    fn test_ema_waits_for_long_window() {
+        let strategy = test_strategy(TrendMethod::Ema);
+        for i in 0..19 {
+            strategy.record_price(dec!(100) + Decimal::from(i * 5), dec!(100));
+        }
+        assert_eq!(strategy.calculate_trend(true), (TrendDirection::Sideways, Decimal::ZERO));
     }
 }
@@ -1,23 +1,25 @@
-use super::TradingStrategy;
-use crate::models::{ArbitrageOpportunity, Price, QuoteCurrency};
+use super::{check_profit, TradingStrategy};
+use crate::models::{ArbitrageOpportunity, Price, QuoteCurrency, Rejection, RejectionReason};
 use crate::config::Config;
 use anyhow::Result;
 use async_trait::async_trait;
 use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
 use std::sync::Arc;
 use log::{debug, info};
-use std::sync::Mutex;
+use std::sync::{Mutex, RwLock};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
 use chrono::{DateTime, Duration, Utc};
 
 /// 时间加权平均价格（TWAP）策略
 /// 将一个大的套利订单分解成多个小订单，在特定时间段内均匀执行
 /// 这可以减少市场冲击，并降低在波动市场中的风险
 pub struct TimeWeightedAverageStrategy {
-    config: Arc<Config>,
+    config: RwLock<Config>,
     /// 分割的订单数量
-    slices: usize,
+    slices: AtomicUsize,
     /// 每个分割订单之间的间隔（秒）
-    interval_seconds: u64,
+    interval_seconds: AtomicU64,
     /// 价格历史记录
     price_history: Arc<Mutex<Vec<(DateTime<Utc>, Decimal, Decimal)>>>,
 }
@@ -25,9 +27,9 @@ pub struct TimeWeightedAverageStrategy {
 impl TimeWeightedAverageStrategy {
     pub fn new(config: Config, slices: usize, interval_seconds: u64) -> Self {
         Self {
-            config: Arc::new(config),
-            slices,
-            interval_seconds,
+            config: RwLock::new(config),
+            slices: AtomicUsize::new(slices),
+            interval_seconds: AtomicU64::new(interval_seconds),
             price_history: Arc::new(Mutex::new(Vec::new())),
         }
     }
@@ -87,6 +89,14 @@ impl TradingStrategy for TimeWeightedAverageStrategy {
         "将套利订单分割成多个小订单在一段时间内执行，减少市场冲击并降低风险"
     }
     
+    fn update_params(&self, config: &Config) -> bool {
+        let settings = &config.strategy_settings.twap;
+        self.slices.store(settings.slices, Ordering::Relaxed);
+        self.interval_seconds.store(settings.interval_seconds, Ordering::Relaxed);
+        *self.config.write().unwrap() = config.clone();
+        true
+    }
+    
     async fn find_opportunity(&self, base_asset: &str, usdt_price: &Price, usdc_price: &Price) -> Result<Option<ArbitrageOpportunity>> {
         // 记录最新价格
         self.record_price(usdt_price.price, usdc_price.price);
@@ -105,9 +115,16 @@ impl TradingStrategy for TimeWeightedAverageStrategy {
             usdt_price.price, usdc_price.price, twap_usdt, twap_usdc
         );
         
+        // 平均价格相同时没有价差
+        if twap_usdt == twap_usdc {
+            debug!("{} 两个交易对的TWAP相同 ({})，没有套利机会", base_asset, twap_usdt);
+            return Ok(None);
+        }
+        
         // 计算每个分片的交易金额
-        let total_amount = Decimal::from(self.config.arbitrage_settings.max_trade_amount_usdt);
-        let slice_amount = total_amount / Decimal::from(self.slices);
+        let total_amount = self.config.read().unwrap().arbitrage_settings.max_trade_amount_usdt;
+        let slices = self.slices.load(Ordering::Relaxed);
+        let slice_amount = total_amount / Decimal::from(slices);
         
         // 比较TWAP价格，确定买入和卖出方向
         let opportunity = if twap_usdt < twap_usdc {
@@ -139,7 +156,7 @@ impl TradingStrategy for TimeWeightedAverageStrategy {
             opportunity.buy_price,
             opportunity.sell_quote,
             opportunity.sell_price,
-            self.slices,
+            slices,
             slice_amount,
             total_amount
         );
@@ -147,14 +164,14 @@ impl TradingStrategy for TimeWeightedAverageStrategy {
         Ok(Some(opportunity))
     }
     
-    async fn validate_opportunity(&self, opportunity: &ArbitrageOpportunity) -> Result<bool> {
+    async fn validate_opportunity(&self, opportunity: &ArbitrageOpportunity) -> Result<(bool, Option<Rejection>)> {
         // 验证利润是否超过最小阈值
-        let min_profit = Decimal::from(self.config.arbitrage_settings.min_profit_percentage);
+        let min_profit = self.config.read().unwrap().arbitrage_settings.min_profit_percentage;
         
         // TWAP策略可能需要较低的利润阈值，因为它降低了风险
-        let adjusted_min_profit = min_profit * Decimal::from(0.8); // 使用80%的阈值
+        let adjusted_min_profit = min_profit * dec!(0.8); // 使用80%的阈值
         
-        let is_valid = opportunity.profit_percentage >= adjusted_min_profit;
+        let (is_valid, rejection) = check_profit(opportunity, adjusted_min_profit, RejectionReason::BelowMinProfit);
         
         debug!(
             "TWAP套利机会验证: 利润率 {}% {} 调整后的最小要求 {}%",
@@ -163,6 +180,6 @@ impl TradingStrategy for TimeWeightedAverageStrategy {
             adjusted_min_profit
         );
         
-        Ok(is_valid)
+        Ok((is_valid, rejection))
     }
 }
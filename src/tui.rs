@@ -0,0 +1,518 @@
+//! 终端仪表盘（需要启用 `tui` 特性）
+//!
+//! 实盘和模拟模式加 `--tui` 时占用整个终端，显示行情和价差、最近的套利、风控组件状态、会话盈亏曲线和最近的日志，
+//! 每隔 [`REFRESH_INTERVAL`] 读取一次引擎的状态快照刷新。按 `p` 暂停交易、`r` 恢复交易，`q`、`Esc` 或 `Ctrl-C`
+//! 请求优雅停止。终端小于 [`MIN_WIDTH`]x[`MIN_HEIGHT`] 时只显示一行状态摘要。
+
+use crate::arbitrage::{spread_percentage, ArbitrageEngine, EngineStatus, ShutdownSignal};
+use crate::binance::ExchangeApi;
+use crate::logging::LogTail;
+use crate::models::{ArbitrageResult, QuoteCurrency};
+use anyhow::Result;
+use chrono::Local;
+use log::error;
+use ratatui::backend::CrosstermBackend;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::crossterm::execute;
+use ratatui::crossterm::terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span, Text};
+use ratatui::widgets::{Block, Borders, Cell, List, ListItem, Paragraph, Row, Sparkline, Table, Wrap};
+use ratatui::{Frame, Terminal};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use std::io::{self, Stdout};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+/// 刷新间隔，也是等待按键的最长时间
+pub const REFRESH_INTERVAL: Duration = Duration::from_millis(250);
+/// 显示完整布局需要的最小终端宽度
+pub const MIN_WIDTH: u16 = 80;
+/// 显示完整布局需要的最小终端高度
+pub const MIN_HEIGHT: u16 = 24;
+/// 盈亏曲线保留的点数
+const MAX_PROFIT_POINTS: usize = 200;
+/// 日志面板的高度（包括边框）
+const LOG_PANEL_HEIGHT: u16 = 8;
+
+/// 仪表盘一次刷新显示的数据
+#[derive(Debug, Clone)]
+pub struct DashboardState {
+    pub base_asset: String,
+    /// USDT交易对的最新价格，行情缓存中没有时为None
+    pub usdt_price: Option<Decimal>,
+    /// USDC交易对的最新价格
+    pub usdc_price: Option<Decimal>,
+    pub status: EngineStatus,
+    /// 最近的套利结果，从晚到早
+    pub trades: Vec<ArbitrageResult>,
+    /// 每笔套利后的累计利润，从早到晚
+    pub profit_history: Vec<Decimal>,
+    /// 最近的日志行，从早到晚
+    pub logs: Vec<String>,
+}
+
+/// 按键对应的操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyAction {
+    Pause,
+    Resume,
+    Quit,
+}
+
+impl KeyAction {
+    pub fn from_key(key: &KeyEvent) -> Option<Self> {
+        if key.kind != KeyEventKind::Press {
+            return None;
+        }
+        match key.code {
+            KeyCode::Char('p') => Some(KeyAction::Pause),
+            KeyCode::Char('r') => Some(KeyAction::Resume),
+            KeyCode::Char('q') | KeyCode::Esc => Some(KeyAction::Quit),
+            // 原始模式下Ctrl-C不会产生信号
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => Some(KeyAction::Quit),
+            _ => None,
+        }
+    }
+}
+
+/// 从引擎收集仪表盘数据，累计会话的盈亏曲线
+struct Dashboard<T: ExchangeApi + Send + Sync + 'static> {
+    engine: Arc<ArbitrageEngine<T>>,
+    logs: LogTail,
+    usdt_symbol: String,
+    usdc_symbol: String,
+    profit_history: Vec<Decimal>,
+    /// 已记入盈亏曲线的套利次数
+    recorded_trades: u64,
+}
+
+impl<T: ExchangeApi + Send + Sync + 'static> Dashboard<T> {
+    fn new(engine: Arc<ArbitrageEngine<T>>, logs: LogTail) -> Self {
+        let base_asset = engine.base_asset().to_string();
+        Self {
+            engine,
+            logs,
+            usdt_symbol: format!("{}{}", base_asset, QuoteCurrency::USDT),
+            usdc_symbol: format!("{}{}", base_asset, QuoteCurrency::USDC),
+            profit_history: vec![Decimal::ZERO],
+            recorded_trades: 0,
+        }
+    }
+
+    fn snapshot(&mut self) -> DashboardState {
+        let status = self.engine.status();
+        if status.session.trades != self.recorded_trades {
+            self.recorded_trades = status.session.trades;
+            self.profit_history.push(status.session.profit);
+            if self.profit_history.len() > MAX_PROFIT_POINTS {
+                self.profit_history.remove(0);
+            }
+        }
+        let cache = self.engine.price_cache();
+        DashboardState {
+            base_asset: self.engine.base_asset().to_string(),
+            usdt_price: cache.latest(&self.usdt_symbol).map(|cached| cached.price.price),
+            usdc_price: cache.latest(&self.usdc_symbol).map(|cached| cached.price.price),
+            status,
+            trades: self.engine.recent_results(),
+            profit_history: self.profit_history.clone(),
+            logs: self.logs.lines(),
+        }
+    }
+}
+
+/// 在阻塞线程中运行仪表盘，直到用户退出或 `stop` 被请求
+///
+/// 用户退出时请求引擎优雅停止；返回的任务结束时终端已恢复，之后可以正常输出。
+pub fn spawn<T: ExchangeApi + Send + Sync + 'static>(
+    engine: &Arc<ArbitrageEngine<T>>,
+    logs: LogTail,
+    stop: ShutdownSignal,
+) -> JoinHandle<()> {
+    let dashboard = Dashboard::new(engine.clone(), logs);
+    tokio::task::spawn_blocking(move || {
+        if let Err(e) = run(dashboard, &stop) {
+            error!("终端仪表盘异常退出: {:#}", e);
+        }
+    })
+}
+
+fn run<T: ExchangeApi + Send + Sync + 'static>(mut dashboard: Dashboard<T>, stop: &ShutdownSignal) -> Result<()> {
+    let mut terminal = TerminalGuard::enter()?;
+    while !stop.is_requested() {
+        let state = dashboard.snapshot();
+        terminal.0.draw(|frame| render(frame, &state))?;
+        if !event::poll(REFRESH_INTERVAL)? {
+            continue;
+        }
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        match KeyAction::from_key(&key) {
+            Some(KeyAction::Pause) => dashboard.engine.pause(),
+            Some(KeyAction::Resume) => dashboard.engine.resume(),
+            Some(KeyAction::Quit) => {
+                dashboard.engine.request_shutdown();
+                break;
+            },
+            None => {},
+        }
+    }
+    Ok(())
+}
+
+/// 进入原始模式和备用屏幕，丢弃时恢复终端
+struct TerminalGuard(Terminal<CrosstermBackend<Stdout>>);
+
+impl TerminalGuard {
+    fn enter() -> Result<Self> {
+        enable_raw_mode()?;
+        let mut stdout = io::stdout();
+        if let Err(e) = execute!(stdout, EnterAlternateScreen) {
+            let _ = disable_raw_mode();
+            return Err(e.into());
+        }
+        // 先创建守卫，之后出错时同样恢复终端
+        let mut guard = Self(Terminal::new(CrosstermBackend::new(stdout))?);
+        guard.0.clear()?;
+        Ok(guard)
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = disable_raw_mode();
+        let _ = execute!(self.0.backend_mut(), LeaveAlternateScreen);
+        let _ = self.0.show_cursor();
+    }
+}
+
+/// 绘制仪表盘，终端太小时只显示状态摘要
+pub fn render(frame: &mut Frame, state: &DashboardState) {
+    let area = frame.area();
+    if area.width < MIN_WIDTH || area.height < MIN_HEIGHT {
+        render_compact(frame, area, state);
+        return;
+    }
+
+    let [header, middle, logs, help] = Layout::vertical([
+        Constraint::Length(7),
+        Constraint::Min(8),
+        Constraint::Length(LOG_PANEL_HEIGHT),
+        Constraint::Length(1),
+    ])
+    .areas(area);
+    let [market, session] = Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)]).areas(header);
+    let [trades, risk] = Layout::horizontal([Constraint::Percentage(60), Constraint::Percentage(40)]).areas(middle);
+
+    render_market(frame, market, state);
+    render_session(frame, session, state);
+    render_trades(frame, trades, state);
+    render_risk(frame, risk, state);
+    render_logs(frame, logs, state);
+    frame.render_widget(
+        Paragraph::new(" p 暂停交易  r 恢复交易  q 退出").style(Style::default().fg(Color::DarkGray)),
+        help,
+    );
+}
+
+fn render_compact(frame: &mut Frame, area: Rect, state: &DashboardState) {
+    let (label, color) = trading_state(&state.status);
+    let lines = vec![
+        Line::from(format!("终端窗口太小 ({}x{})，至少需要 {}x{}", area.width, area.height, MIN_WIDTH, MIN_HEIGHT)),
+        Line::from(vec![
+            Span::raw(format!("{} ", state.base_asset)),
+            Span::styled(label, Style::default().fg(color)),
+            Span::raw(format!(" | 交易 {} 次 | 净利润 ", state.status.session.trades)),
+            Span::styled(format!("{:.4}", state.status.session.profit), profit_style(state.status.session.profit)),
+        ]),
+        Line::from("q 退出"),
+    ];
+    frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: true }), area);
+}
+
+fn render_market(frame: &mut Frame, area: Rect, state: &DashboardState) {
+    let price = |price: Option<Decimal>| price.map_or_else(|| "-".to_string(), |price| format!("{:.2}", price));
+    let spread = match (state.usdt_price, state.usdc_price) {
+        (Some(usdt), Some(usdc)) => spread_percentage(usdt, usdc).map_or_else(|| "-".to_string(), |spread| format!("{:.4}%", spread)),
+        _ => "-".to_string(),
+    };
+    let (label, color) = trading_state(&state.status);
+    let settings = &state.status.runtime_settings;
+    let lines = vec![
+        Line::from(format!("USDT: {}   USDC: {}", price(state.usdt_price), price(state.usdc_price))),
+        Line::from(format!("价差 (USDC-USDT): {}", spread)),
+        Line::from(vec![
+            Span::raw("状态: "),
+            Span::styled(label, Style::default().fg(color).add_modifier(Modifier::BOLD)),
+            Span::raw(format!("  模式: {}{}", state.status.mode, if settings.dry_run { " (只记录)" } else { "" })),
+        ]),
+        Line::from(format!("执行中: {}  检查间隔: {} ms", state.status.open_positions, settings.check_interval_ms)),
+    ];
+    let block = Block::default().borders(Borders::ALL).title(format!(" {} 行情 ", state.base_asset));
+    frame.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+fn render_session(frame: &mut Frame, area: Rect, state: &DashboardState) {
+    let block = Block::default().borders(Borders::ALL).title(" 会话盈亏 ");
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let [summary, chart] = Layout::vertical([Constraint::Length(2), Constraint::Min(1)]).areas(inner);
+    let session = &state.status.session;
+    let lines = vec![
+        Line::from(format!(
+            "交易 {} 次 (成功 {}, 失败 {})",
+            session.trades, session.successful_trades, session.failed_trades
+        )),
+        Line::from(vec![
+            Span::raw("净利润 "),
+            Span::styled(format!("{:.4}", session.profit), profit_style(session.profit)),
+            Span::raw(format!("  手续费 {:.4}", session.fees)),
+        ]),
+    ];
+    frame.render_widget(Paragraph::new(lines), summary);
+
+    let points = sparkline_points(&state.profit_history);
+    let color = state.profit_history.last().map_or(Color::Reset, |profit| profit_color(*profit));
+    frame.render_widget(Sparkline::default().data(&points).style(Style::default().fg(color)), chart);
+}
+
+fn render_trades(frame: &mut Frame, area: Rect, state: &DashboardState) {
+    let header = Row::new(["时间", "方向", "数量", "利润率%", "利润", "状态"]).style(Style::default().add_modifier(Modifier::BOLD));
+    let rows = state.trades.iter().map(|trade| {
+        Row::new(vec![
+            Cell::from(trade.end_time.with_timezone(&Local).format("%H:%M:%S").to_string()),
+            Cell::from(format!("{}→{}", trade.buy_quote, trade.sell_quote)),
+            Cell::from(trade.trade_amount.normalize().to_string()),
+            Cell::from(format!("{:.4}", trade.profit_percentage)),
+            Cell::from(format!("{:.4}", trade.profit)).style(profit_style(trade.profit)),
+            Cell::from(format!("{:?}", trade.status)),
+        ])
+    });
+    let widths = [
+        Constraint::Length(8),
+        Constraint::Length(9),
+        Constraint::Length(8),
+        Constraint::Length(8),
+        Constraint::Length(9),
+        Constraint::Min(9),
+    ];
+    let block = Block::default().borders(Borders::ALL).title(" 最近套利 ");
+    frame.render_widget(Table::new(rows, widths).header(header).block(block), area);
+}
+
+fn render_risk(frame: &mut Frame, area: Rect, state: &DashboardState) {
+    let block = Block::default().borders(Borders::ALL).title(" 风控状态 ");
+    if state.status.risk.is_empty() {
+        frame.render_widget(Paragraph::new("未启用风控组件").block(block), area);
+        return;
+    }
+    let items: Vec<ListItem> = state
+        .status
+        .risk
+        .iter()
+        .map(|controller| {
+            let style = if controller.last_rejection.is_some() { Style::default().fg(Color::Yellow) } else { Style::default() };
+            ListItem::new(Text::from(controller.to_string())).style(style)
+        })
+        .collect();
+    frame.render_widget(List::new(items).block(block), area);
+}
+
+fn render_logs(frame: &mut Frame, area: Rect, state: &DashboardState) {
+    let visible = area.height.saturating_sub(2) as usize;
+    let skip = state.logs.len().saturating_sub(visible);
+    let lines: Vec<Line> = state.logs[skip..].iter().map(|line| Line::from(line.as_str())).collect();
+    let block = Block::default().borders(Borders::ALL).title(" 日志 ");
+    frame.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+/// 交易状态的说明和颜色，多种暂停同时存在时显示最先需要处理的一种
+fn trading_state(status: &EngineStatus) -> (&'static str, Color) {
+    if status.manually_paused {
+        ("手动暂停", Color::Yellow)
+    } else if status.db_write_paused {
+        ("数据库写入失败暂停", Color::Red)
+    } else if status.trading_paused {
+        ("系统维护暂停", Color::Yellow)
+    } else if status.warming_up {
+        ("预热中", Color::Cyan)
+    } else {
+        ("运行中", Color::Green)
+    }
+}
+
+fn profit_color(profit: Decimal) -> Color {
+    if profit > Decimal::ZERO {
+        Color::Green
+    } else if profit < Decimal::ZERO {
+        Color::Red
+    } else {
+        Color::Reset
+    }
+}
+
+fn profit_style(profit: Decimal) -> Style {
+    Style::default().fg(profit_color(profit))
+}
+
+/// 将累计利润缩放到1到100，亏损时同样可以画出曲线
+fn sparkline_points(history: &[Decimal]) -> Vec<u64> {
+    let (Some(min), Some(max)) = (history.iter().min(), history.iter().max()) else {
+        return Vec::new();
+    };
+    let range = *max - *min;
+    history
+        .iter()
+        .map(|profit| {
+            if range.is_zero() {
+                1
+            } else {
+                (Decimal::ONE + (*profit - *min) / range * Decimal::from(99)).round().to_u64().unwrap_or(1)
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::binance::MockBinanceApi;
+    use crate::config::{Config, RiskControllerType, RiskSettings};
+    use crate::models::testing::arbitrage_result_at;
+    use crate::models::{ArbitrageStatus, TradingMode};
+    use chrono::{TimeZone, Utc};
+    use ratatui::backend::TestBackend;
+    use ratatui::buffer::Buffer;
+    use rust_decimal_macros::dec;
+
+    fn trade(profit: Decimal, status: ArbitrageStatus) -> ArbitrageResult {
+        let start_time = Utc.with_ymd_and_hms(2024, 5, 1, 8, 0, 0).unwrap();
+        ArbitrageResult {
+            trade_amount: dec!(0.002),
+            profit,
+            mode: TradingMode::Simulate,
+            ..arbitrage_result_at(status, start_time, chrono::Duration::seconds(1))
+        }
+    }
+
+    fn fixture() -> DashboardState {
+        let config = Config {
+            risk_settings: RiskSettings {
+                enabled_controllers: vec![RiskControllerType::DailyLossLimit],
+                ..RiskSettings::default()
+            },
+            ..Config::default()
+        };
+        let engine = ArbitrageEngine::new(MockBinanceApi::new(), config, "BTC").unwrap();
+        let mut status = engine.status();
+        status.session.trades = 2;
+        status.session.successful_trades = 1;
+        status.session.failed_trades = 1;
+        status.session.profit = dec!(0.7345);
+        DashboardState {
+            base_asset: "BTC".to_string(),
+            usdt_price: Some(dec!(50000)),
+            usdc_price: Some(dec!(50100)),
+            status,
+            trades: vec![trade(dec!(-0.5), ArbitrageStatus::Failed), trade(dec!(1.2345), ArbitrageStatus::Completed)],
+            profit_history: vec![Decimal::ZERO, dec!(1.2345), dec!(0.7345)],
+            logs: (1..=10).map(|i| format!("fixture log {}", i)).collect(),
+        }
+    }
+
+    fn draw(width: u16, height: u16, state: &DashboardState) -> Buffer {
+        let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+        terminal.draw(|frame| render(frame, state)).unwrap();
+        terminal.backend().buffer().clone()
+    }
+
+    /// 去掉空格后的全部文本，宽字符后面的占位单元格也是空格
+    fn text(buffer: &Buffer) -> String {
+        buffer.content.iter().map(|cell| cell.symbol()).collect::<String>().replace(' ', "")
+    }
+
+    fn text_of(width: u16, height: u16, state: &DashboardState) -> String {
+        text(&draw(width, height, state))
+    }
+
+    /// ASCII文本第一次出现的位置
+    fn find(buffer: &Buffer, needle: &str) -> Option<(u16, u16)> {
+        let area = buffer.area;
+        for y in 0..area.height {
+            for x in 0..area.width {
+                let matches = needle.chars().enumerate().all(|(i, c)| {
+                    let x = x + i as u16;
+                    x < area.width && buffer.content[(y * area.width + x) as usize].symbol() == c.to_string()
+                });
+                if matches {
+                    return Some((x, y));
+                }
+            }
+        }
+        None
+    }
+
+    #[test]
+    fn test_render_dashboard_layout() {
+        let state = fixture();
+        let buffer = draw(100, 30, &state);
+        let text = text(&buffer);
+
+        for expected in ["BTC行情", "USDT:50000.00", "USDC:50100.00", "0.2000%", "运行中", "会话盈亏", "交易2次(成功1,失败1)", "最近套利", "USDT→USDC", "Completed", "风控状态", "每日亏损限制", "日志", "q退出"] {
+            assert!(text.contains(expected), "缺少 {}: {}", expected, text);
+        }
+        // 日志面板只显示最后几行
+        assert!(text.contains("fixturelog5"));
+        assert!(text.contains("fixturelog10"));
+        assert!(!text.contains("fixturelog4"));
+
+        // 盈利为绿色，亏损为红色
+        let (x, y) = find(&buffer, "1.2345").unwrap();
+        assert_eq!(buffer.content[(y * 100 + x) as usize].fg, Color::Green);
+        let (x, y) = find(&buffer, "-0.5000").unwrap();
+        assert_eq!(buffer.content[(y * 100 + x) as usize].fg, Color::Red);
+
+        assert_eq!(sparkline_points(&state.profit_history), vec![1, 100, 60]);
+        assert_eq!(sparkline_points(&[dec!(-2), dec!(-2)]), vec![1, 1]);
+        assert!(sparkline_points(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_render_compact_when_terminal_too_small() {
+        let mut state = fixture();
+        state.status.manually_paused = true;
+        let buffer = draw(40, 10, &state);
+        let text = text(&buffer);
+
+        assert!(text.contains("终端窗口太小(40x10)"), "{}", text);
+        assert!(text.contains("BTC手动暂停"));
+        assert!(text.contains("0.7345"));
+        assert!(!text.contains("最近套利"));
+
+        // 高度足够但宽度不够同样只显示摘要
+        assert!(text_of(79, 40, &state).contains("终端窗口太小(79x40)"));
+        assert!(!text_of(MIN_WIDTH, MIN_HEIGHT, &state).contains("终端窗口太小"));
+    }
+
+    #[test]
+    fn test_key_actions() {
+        let key = |code: KeyCode, modifiers: KeyModifiers| KeyEvent::new(code, modifiers);
+        assert_eq!(KeyAction::from_key(&key(KeyCode::Char('p'), KeyModifiers::NONE)), Some(KeyAction::Pause));
+        assert_eq!(KeyAction::from_key(&key(KeyCode::Char('r'), KeyModifiers::NONE)), Some(KeyAction::Resume));
+        assert_eq!(KeyAction::from_key(&key(KeyCode::Char('q'), KeyModifiers::NONE)), Some(KeyAction::Quit));
+        assert_eq!(KeyAction::from_key(&key(KeyCode::Esc, KeyModifiers::NONE)), Some(KeyAction::Quit));
+        assert_eq!(KeyAction::from_key(&key(KeyCode::Char('c'), KeyModifiers::CONTROL)), Some(KeyAction::Quit));
+        assert_eq!(KeyAction::from_key(&key(KeyCode::Char('c'), KeyModifiers::NONE)), None);
+
+        // 只响应按下，忽略松开
+        let mut release = key(KeyCode::Char('p'), KeyModifiers::NONE);
+        release.kind = KeyEventKind::Release;
+        assert_eq!(KeyAction::from_key(&release), None);
+    }
+}